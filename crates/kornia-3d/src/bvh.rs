@@ -0,0 +1,323 @@
+/// Maximum number of triangles stored in a leaf of the hierarchy.
+const LEAF_SIZE: usize = 4;
+
+/// A node of a [`Bvh`], with the bounding box of its triangles.
+#[derive(Debug, Clone)]
+struct BvhNode {
+    min: [f64; 3],
+    max: [f64; 3],
+    /// For a leaf, the first triangle in the permutation. For an inner node, the index of
+    /// the first child, the second one being right after it.
+    start: usize,
+    /// The number of triangles of a leaf, zero for an inner node.
+    count: usize,
+}
+
+/// A bounding volume hierarchy (BVH) over the triangles of a mesh.
+///
+/// Each node holds the axis-aligned bounding box of its triangles. The triangles of a node
+/// are split at the median of their centroids along the axis of largest spread.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::bvh::Bvh;
+///
+/// let vertices = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+/// let bvh = Bvh::new(&vertices, &[[0, 1, 2]]);
+/// assert_eq!(bvh.bounds(), ([0.0, 0.0, 0.0], [1.0, 1.0, 0.0]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    triangles: Vec<usize>,
+}
+
+impl Bvh {
+    /// Build a new BVH over the faces of a mesh.
+    ///
+    /// # Arguments
+    ///
+    /// * `vertices` - The vertices of the mesh.
+    /// * `faces` - The triangles of the mesh as indices into the vertices. Query results
+    ///   refer to positions in this slice.
+    pub fn new(vertices: &[[f64; 3]], faces: &[[usize; 3]]) -> Self {
+        let boxes = faces
+            .iter()
+            .map(|face| {
+                let mut min = [f64::INFINITY; 3];
+                let mut max = [f64::NEG_INFINITY; 3];
+                for &v in face {
+                    for k in 0..3 {
+                        min[k] = min[k].min(vertices[v][k]);
+                        max[k] = max[k].max(vertices[v][k]);
+                    }
+                }
+                (min, max)
+            })
+            .collect::<Vec<_>>();
+
+        let mut bvh = Self {
+            nodes: Vec::new(),
+            triangles: (0..faces.len()).collect(),
+        };
+        if !faces.is_empty() {
+            bvh.nodes.push(BvhNode {
+                min: [0.0; 3],
+                max: [0.0; 3],
+                start: 0,
+                count: 0,
+            });
+            bvh.build(0, 0, faces.len(), &boxes);
+        }
+        bvh
+    }
+
+    fn build(&mut self, node: usize, lo: usize, hi: usize, boxes: &[([f64; 3], [f64; 3])]) {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        let mut centroid_min = [f64::INFINITY; 3];
+        let mut centroid_max = [f64::NEG_INFINITY; 3];
+        for &t in &self.triangles[lo..hi] {
+            let (bmin, bmax) = &boxes[t];
+            for k in 0..3 {
+                min[k] = min[k].min(bmin[k]);
+                max[k] = max[k].max(bmax[k]);
+                let c = 0.5 * (bmin[k] + bmax[k]);
+                centroid_min[k] = centroid_min[k].min(c);
+                centroid_max[k] = centroid_max[k].max(c);
+            }
+        }
+        self.nodes[node].min = min;
+        self.nodes[node].max = max;
+
+        if hi - lo <= LEAF_SIZE {
+            self.nodes[node].start = lo;
+            self.nodes[node].count = hi - lo;
+            return;
+        }
+
+        let axis = (0..3)
+            .max_by(|&a, &b| {
+                (centroid_max[a] - centroid_min[a]).total_cmp(&(centroid_max[b] - centroid_min[b]))
+            })
+            .unwrap_or(0);
+        let mid = lo + (hi - lo) / 2;
+        let centroid = |t: &usize| boxes[*t].0[axis] + boxes[*t].1[axis];
+        self.triangles[lo..hi]
+            .select_nth_unstable_by(mid - lo, |a, b| centroid(a).total_cmp(&centroid(b)));
+
+        let left = self.nodes.len();
+        for _ in 0..2 {
+            self.nodes.push(BvhNode {
+                min: [0.0; 3],
+                max: [0.0; 3],
+                start: 0,
+                count: 0,
+            });
+        }
+        self.nodes[node].start = left;
+        self.build(left, lo, mid, boxes);
+        self.build(left + 1, mid, hi, boxes);
+    }
+
+    /// Get the number of triangles in the hierarchy.
+    pub fn len(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Check if the hierarchy is empty.
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    /// Get the bounding box of the mesh as its minimum and maximum corners.
+    ///
+    /// The box of an empty hierarchy is the origin.
+    pub fn bounds(&self) -> ([f64; 3], [f64; 3]) {
+        self.nodes
+            .first()
+            .map_or(([0.0; 3], [0.0; 3]), |root| (root.min, root.max))
+    }
+
+    /// Find the triangle minimizing a squared distance to a query point.
+    ///
+    /// The nodes are visited nearest first and pruned when their bounding box is farther
+    /// than the best triangle found so far, so `sq_distance` must not be smaller than the
+    /// squared Euclidean distance from the query to the triangle.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query point.
+    /// * `sq_distance` - The squared distance from the query to a triangle given its index.
+    ///
+    /// # Returns
+    ///
+    /// The index of the nearest triangle and its squared distance, or `None` if the
+    /// hierarchy is empty.
+    pub(crate) fn nearest_triangle(
+        &self,
+        query: &[f64; 3],
+        mut sq_distance: impl FnMut(usize) -> f64,
+    ) -> Option<(usize, f64)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut best: Option<(usize, f64)> = None;
+        let mut stack = vec![(0, self.box_sq_distance(0, query))];
+        while let Some((node, node_sq_distance)) = stack.pop() {
+            if best.is_some_and(|(_, d)| node_sq_distance >= d) {
+                continue;
+            }
+            let BvhNode { start, count, .. } = self.nodes[node];
+            if count > 0 {
+                for &t in &self.triangles[start..start + count] {
+                    let d = sq_distance(t);
+                    if best.map_or(true, |(bt, bd)| d < bd || (d == bd && t < bt)) {
+                        best = Some((t, d));
+                    }
+                }
+                continue;
+            }
+
+            // push the farthest child first to visit the nearest one first
+            let (d_left, d_right) = (
+                self.box_sq_distance(start, query),
+                self.box_sq_distance(start + 1, query),
+            );
+            if d_left <= d_right {
+                stack.push((start + 1, d_right));
+                stack.push((start, d_left));
+            } else {
+                stack.push((start, d_left));
+                stack.push((start + 1, d_right));
+            }
+        }
+        best
+    }
+
+    /// Visit the triangles whose bounding boxes are hit by a ray.
+    ///
+    /// The triangles are candidates: the caller intersects the ray with each of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The origin of the ray.
+    /// * `direction` - The direction of the ray, not necessarily unit.
+    /// * `visit` - Called with the index of each candidate triangle.
+    pub(crate) fn ray_triangles(
+        &self,
+        origin: &[f64; 3],
+        direction: &[f64; 3],
+        mut visit: impl FnMut(usize),
+    ) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            if !self.ray_hits_box(node, origin, direction) {
+                continue;
+            }
+            let BvhNode { start, count, .. } = self.nodes[node];
+            if count > 0 {
+                self.triangles[start..start + count]
+                    .iter()
+                    .for_each(|&t| visit(t));
+            } else {
+                stack.push(start + 1);
+                stack.push(start);
+            }
+        }
+    }
+
+    /// Check if a ray hits the bounding box of a node with the slab method.
+    fn ray_hits_box(&self, node: usize, origin: &[f64; 3], direction: &[f64; 3]) -> bool {
+        let BvhNode { min, max, .. } = &self.nodes[node];
+        let (mut t_enter, mut t_exit) = (0.0f64, f64::INFINITY);
+        for k in 0..3 {
+            if direction[k] == 0.0 {
+                if origin[k] < min[k] || origin[k] > max[k] {
+                    return false;
+                }
+                continue;
+            }
+            let t0 = (min[k] - origin[k]) / direction[k];
+            let t1 = (max[k] - origin[k]) / direction[k];
+            t_enter = t_enter.max(t0.min(t1));
+            t_exit = t_exit.min(t0.max(t1));
+        }
+        t_enter <= t_exit
+    }
+
+    /// Squared distance from a point to the bounding box of a node, zero inside the box.
+    fn box_sq_distance(&self, node: usize, query: &[f64; 3]) -> f64 {
+        let BvhNode { min, max, .. } = &self.nodes[node];
+        (0..3)
+            .map(|k| {
+                let d = (min[k] - query[k]).max(query[k] - max[k]).max(0.0);
+                d * d
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bvh_nearest_triangle() {
+        // a strip of unit squares along the x axis
+        let vertices = (0..=20)
+            .flat_map(|i| [[i as f64, 0.0, 0.0], [i as f64, 1.0, 0.0]])
+            .collect::<Vec<_>>();
+        let faces = (0..20)
+            .flat_map(|i| {
+                [
+                    [2 * i, 2 * i + 2, 2 * i + 1],
+                    [2 * i + 1, 2 * i + 2, 2 * i + 3],
+                ]
+            })
+            .collect::<Vec<_>>();
+        let bvh = Bvh::new(&vertices, &faces);
+        assert_eq!(bvh.len(), 40);
+        assert_eq!(bvh.bounds(), ([0.0; 3], [20.0, 1.0, 0.0]));
+
+        // the distance to the centroid of each triangle
+        let centroid_sq_distance = |query: &[f64; 3], t: usize| {
+            (0..3)
+                .map(|k| {
+                    let c = faces[t].iter().map(|&v| vertices[v][k]).sum::<f64>() / 3.0;
+                    (c - query[k]) * (c - query[k])
+                })
+                .sum::<f64>()
+        };
+        for query in [[0.2, 0.3, 1.0], [12.7, 0.9, -0.5], [30.0, 0.0, 0.0]] {
+            let brute_force = (0..faces.len())
+                .min_by(|&a, &b| {
+                    centroid_sq_distance(&query, a).total_cmp(&centroid_sq_distance(&query, b))
+                })
+                .unwrap();
+            let (t, d) = bvh
+                .nearest_triangle(&query, |t| centroid_sq_distance(&query, t))
+                .unwrap();
+            assert_eq!(t, brute_force);
+            assert_eq!(d, centroid_sq_distance(&query, brute_force));
+        }
+
+        assert!(Bvh::new(&vertices, &[])
+            .nearest_triangle(&[0.0; 3], |_| 0.0)
+            .is_none());
+
+        // a ray across the strip hits the boxes of the triangles of a square
+        let mut candidates = Vec::new();
+        bvh.ray_triangles(&[12.5, 0.5, 1.0], &[0.0, 0.0, -1.0], |t| candidates.push(t));
+        assert!(candidates.contains(&24) && candidates.contains(&25));
+        assert!(candidates.len() <= 2 * LEAF_SIZE);
+        // a ray pointing away from the strip hits nothing
+        bvh.ray_triangles(&[12.5, 0.5, 1.0], &[0.0, 0.0, 1.0], |_| panic!());
+    }
+}
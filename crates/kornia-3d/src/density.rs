@@ -0,0 +1,148 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{kdtree::KdTree, pointcloud::PointCloud};
+
+/// Error types for the density estimation.
+#[derive(Debug, thiserror::Error)]
+pub enum DensityError {
+    /// The point cloud has too few points to compute nearest neighbor distances.
+    #[error("Not enough points to estimate the density: {0}")]
+    NotEnoughPoints(usize),
+}
+
+/// Statistics of the nearest neighbor distances and of the extents of a point cloud.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DensityStats {
+    /// Mean distance to the nearest neighbor.
+    pub mean_nn_dist: f64,
+    /// Median distance to the nearest neighbor.
+    pub median_nn_dist: f64,
+    /// 95th percentile of the distance to the nearest neighbor.
+    pub p95_nn_dist: f64,
+    /// Number of points of the cloud.
+    pub num_points: usize,
+    /// Size of the axis-aligned bounding box of the cloud along the x, y and z axes.
+    pub extent: [f64; 3],
+}
+
+/// Estimate the point spacing of a point cloud from sampled nearest neighbor distances.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `sample_size` - The number of points to sample. If larger than the cloud, all points are used.
+/// * `seed` - The seed of the random generator used to draw the samples.
+///
+/// # Returns
+///
+/// The statistics of the distance from each sampled point to its nearest neighbor, with the
+/// number of points and the extents of the whole cloud.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::density::estimate_density;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..10).map(|i| [i as f64 * 0.5, 0.0, 0.0]).collect();
+/// let cloud = PointCloud::new(points, None, None);
+/// let stats = estimate_density(&cloud, 100, 0).unwrap();
+/// assert_eq!(stats.median_nn_dist, 0.5);
+/// ```
+pub fn estimate_density(
+    cloud: &PointCloud,
+    sample_size: usize,
+    seed: u64,
+) -> Result<DensityStats, DensityError> {
+    if cloud.len() < 2 || sample_size == 0 {
+        return Err(DensityError::NotEnoughPoints(cloud.len()));
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let num_samples = sample_size.min(cloud.len());
+    let samples = rand::seq::index::sample(&mut rng, cloud.len(), num_samples);
+
+    let kdtree = KdTree::new(cloud.points());
+
+    // the first neighbor is the query point itself
+    let mut distances = samples
+        .iter()
+        .filter_map(|i| {
+            kdtree
+                .nearest_n(&cloud.points()[i], 2)
+                .get(1)
+                .map(|n| n.distance)
+        })
+        .collect::<Vec<_>>();
+    distances.sort_by(|a, b| a.total_cmp(b));
+
+    let n = distances.len();
+    let mean_nn_dist = distances.iter().sum::<f64>() / n as f64;
+    let median_nn_dist = if n % 2 == 0 {
+        0.5 * (distances[n / 2 - 1] + distances[n / 2])
+    } else {
+        distances[n / 2]
+    };
+    // nearest-rank percentile
+    let p95_rank = ((0.95 * n as f64).ceil() as usize).clamp(1, n);
+    let p95_nn_dist = distances[p95_rank - 1];
+
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for p in cloud.points() {
+        for k in 0..3 {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+
+    Ok(DensityStats {
+        mean_nn_dist,
+        median_nn_dist,
+        p95_nn_dist,
+        num_points: cloud.len(),
+        extent: [0, 1, 2].map(|k| max[k] - min[k]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn grid(n: usize, spacing: f64) -> PointCloud {
+        let mut points = Vec::with_capacity(n * n * n);
+        for i in 0..n {
+            for j in 0..n {
+                for k in 0..n {
+                    points.push([i as f64 * spacing, j as f64 * spacing, k as f64 * spacing]);
+                }
+            }
+        }
+        PointCloud::new(points, None, None)
+    }
+
+    #[test]
+    fn test_estimate_density_uniform_grid() -> Result<(), Box<dyn std::error::Error>> {
+        for spacing in [0.05, 1.0, 3.0] {
+            let stats = estimate_density(&grid(8, spacing), 200, 42)?;
+            assert_relative_eq!(stats.mean_nn_dist, spacing, epsilon = 1e-9);
+            assert_relative_eq!(stats.median_nn_dist, spacing, epsilon = 1e-9);
+            assert_relative_eq!(stats.p95_nn_dist, spacing, epsilon = 1e-9);
+            assert_eq!(stats.num_points, 512);
+            for extent in stats.extent {
+                assert_relative_eq!(extent, 7.0 * spacing, epsilon = 1e-9);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate_density_not_enough_points() {
+        let cloud = PointCloud::new(vec![[0.0, 0.0, 0.0]], None, None);
+        assert!(matches!(
+            estimate_density(&cloud, 10, 0),
+            Err(DensityError::NotEnoughPoints(1))
+        ));
+    }
+}
@@ -0,0 +1,611 @@
+use crate::pointcloud::OrganizedPointCloud;
+
+/// Error types for the depth map processing.
+#[derive(Debug, thiserror::Error)]
+pub enum DepthError {
+    /// The length of an image buffer does not match its dimensions.
+    #[error("Size mismatch: expected {expected} pixels but the buffer has {actual}")]
+    SizeMismatch {
+        /// The number of pixels given by the dimensions, i.e. `width * height`.
+        expected: usize,
+        /// The length of the buffer.
+        actual: usize,
+    },
+}
+
+/// The intrinsic parameters of a pinhole camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraIntrinsics {
+    /// The focal length in the x direction in pixels.
+    pub fx: f64,
+    /// The focal length in the y direction in pixels.
+    pub fy: f64,
+    /// The x coordinate of the principal point in pixels.
+    pub cx: f64,
+    /// The y coordinate of the principal point in pixels.
+    pub cy: f64,
+}
+
+/// Check if a depth value is a valid measurement.
+#[inline]
+fn is_valid(depth: f32) -> bool {
+    depth.is_finite() && depth > 0.0
+}
+
+/// Check that the length of an image buffer is `width * height`.
+fn check_size<T>(buffer: &[T], width: usize, height: usize) -> Result<(), DepthError> {
+    if buffer.len() != width * height {
+        return Err(DepthError::SizeMismatch {
+            expected: width * height,
+            actual: buffer.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Fill the small holes of a depth map from their valid neighbors.
+///
+/// The holes are the 4-connected components of invalid pixels, i.e. zero, negative or not
+/// finite depths. The holes with at most `max_hole_size` pixels are filled from their
+/// border inwards: at each pass, the hole pixels with valid 8-neighbors take the mean of
+/// those neighbors. Larger holes, such as the sky or out of range areas, are left invalid.
+///
+/// # Arguments
+///
+/// * `depth` - The depth map in row-major order.
+/// * `width` - The width of the depth map.
+/// * `height` - The height of the depth map.
+/// * `max_hole_size` - The maximum number of pixels of a hole to fill.
+///
+/// # Returns
+///
+/// The depth map with the small holes filled.
+///
+/// # Errors
+///
+/// Returns [`DepthError::SizeMismatch`] if the length of `depth` is not `width * height`.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::depth::fill_depth_holes;
+///
+/// let depth = vec![1.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0];
+/// assert_eq!(fill_depth_holes(&depth, 3, 3, 1).unwrap(), vec![1.0; 9]);
+/// ```
+pub fn fill_depth_holes(
+    depth: &[f32],
+    width: usize,
+    height: usize,
+    max_hole_size: usize,
+) -> Result<Vec<f32>, DepthError> {
+    check_size(depth, width, height)?;
+
+    let mut filled = depth.to_vec();
+    let mut visited = vec![false; depth.len()];
+    let mut stack = Vec::new();
+
+    for start in 0..depth.len() {
+        if visited[start] || is_valid(depth[start]) {
+            continue;
+        }
+
+        // collect the 4-connected hole
+        let mut hole = Vec::new();
+        visited[start] = true;
+        stack.push(start);
+        while let Some(idx) = stack.pop() {
+            hole.push(idx);
+            let (x, y) = (idx % width, idx / width);
+            let neighbors = [
+                (x > 0).then(|| idx - 1),
+                (x + 1 < width).then(|| idx + 1),
+                (y > 0).then(|| idx - width),
+                (y + 1 < height).then(|| idx + width),
+            ];
+            for n in neighbors.into_iter().flatten() {
+                if !visited[n] && !is_valid(depth[n]) {
+                    visited[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+
+        if hole.len() <= max_hole_size {
+            fill_hole(&mut filled, width, height, hole);
+        }
+    }
+
+    Ok(filled)
+}
+
+/// Fill a hole from its border inwards with the mean of the valid 8-neighbors.
+fn fill_hole(depth: &mut [f32], width: usize, height: usize, mut hole: Vec<usize>) {
+    while !hole.is_empty() {
+        let updates = hole
+            .iter()
+            .filter_map(|&idx| {
+                let (x, y) = ((idx % width) as isize, (idx / width) as isize);
+                let (mut sum, mut count) = (0.0f64, 0usize);
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        let (nx, ny) = (x + dx, y + dy);
+                        if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                            continue;
+                        }
+                        let value = depth[ny as usize * width + nx as usize];
+                        if is_valid(value) {
+                            sum += value as f64;
+                            count += 1;
+                        }
+                    }
+                }
+                (count > 0).then(|| (idx, (sum / count as f64) as f32))
+            })
+            .collect::<Vec<_>>();
+
+        // a hole without any valid neighbor cannot be filled
+        if updates.is_empty() {
+            break;
+        }
+        for (idx, value) in updates.iter() {
+            depth[*idx] = *value;
+        }
+        hole.retain(|idx| !is_valid(depth[*idx]));
+    }
+}
+
+/// Smooth a depth map with a bilateral filter that preserves the depth discontinuities.
+///
+/// Each valid pixel is replaced by the weighted mean of the valid pixels in a window of
+/// radius `ceil(2 * sigma_space)`, with the weight
+/// `exp(-d² / (2 sigma_space²)) * exp(-Δz² / (2 sigma_depth²))`, where `d` is the distance
+/// in pixels and `Δz` the depth difference. Pixels across a depth discontinuity much larger
+/// than `sigma_depth` do not contribute, so the edges are not blurred. Invalid pixels are
+/// left unchanged.
+///
+/// # Arguments
+///
+/// * `depth` - The depth map in row-major order.
+/// * `width` - The width of the depth map.
+/// * `height` - The height of the depth map.
+/// * `sigma_space` - The standard deviation of the spatial kernel in pixels.
+/// * `sigma_depth` - The standard deviation of the range kernel in depth units.
+///
+/// # Returns
+///
+/// The smoothed depth map.
+///
+/// # Errors
+///
+/// Returns [`DepthError::SizeMismatch`] if the length of `depth` is not `width * height`.
+pub fn bilateral_filter_depth(
+    depth: &[f32],
+    width: usize,
+    height: usize,
+    sigma_space: f64,
+    sigma_depth: f64,
+) -> Result<Vec<f32>, DepthError> {
+    check_size(depth, width, height)?;
+
+    let range_norm = 1.0 / (2.0 * sigma_depth * sigma_depth);
+    let filtered = bilateral_filter(depth, width, height, sigma_space, |center, neighbor| {
+        let dz = (depth[neighbor] - depth[center]) as f64;
+        (-dz * dz * range_norm).exp()
+    });
+    Ok(filtered)
+}
+
+/// Smooth a depth map with a cross-bilateral filter guided by a color image.
+///
+/// The filter is the same as [`bilateral_filter_depth`], except that the range kernel
+/// `exp(-Δc² / (2 sigma_color²))` is computed from the Euclidean distance `Δc` between the
+/// RGB colors of the guidance image. The color edges of a registered RGB-D frame are
+/// usually sharper and less noisy than its depth edges, so the depth is not blurred across
+/// the boundaries of the objects even where their depths are close. Invalid pixels are left
+/// unchanged.
+///
+/// # Arguments
+///
+/// * `depth` - The depth map in row-major order.
+/// * `guide` - The RGB guidance image registered to the depth map, in row-major order.
+/// * `width` - The width of the depth map.
+/// * `height` - The height of the depth map.
+/// * `sigma_space` - The standard deviation of the spatial kernel in pixels.
+/// * `sigma_color` - The standard deviation of the range kernel in color levels.
+///
+/// # Returns
+///
+/// The smoothed depth map.
+///
+/// # Errors
+///
+/// Returns [`DepthError::SizeMismatch`] if the length of `depth` or of `guide` is not
+/// `width * height`.
+pub fn guided_bilateral_filter_depth(
+    depth: &[f32],
+    guide: &[[u8; 3]],
+    width: usize,
+    height: usize,
+    sigma_space: f64,
+    sigma_color: f64,
+) -> Result<Vec<f32>, DepthError> {
+    check_size(depth, width, height)?;
+    check_size(guide, width, height)?;
+
+    let range_norm = 1.0 / (2.0 * sigma_color * sigma_color);
+    let filtered = bilateral_filter(depth, width, height, sigma_space, |center, neighbor| {
+        let dc2 = (0..3)
+            .map(|k| (guide[neighbor][k] as f64 - guide[center][k] as f64).powi(2))
+            .sum::<f64>();
+        (-dc2 * range_norm).exp()
+    });
+    Ok(filtered)
+}
+
+/// Replace each valid pixel by the mean of the valid pixels of its window, weighted by a
+/// Gaussian spatial kernel and a range kernel given the indices of the center and the
+/// neighbor.
+fn bilateral_filter(
+    depth: &[f32],
+    width: usize,
+    height: usize,
+    sigma_space: f64,
+    range_weight: impl Fn(usize, usize) -> f64,
+) -> Vec<f32> {
+    let radius = (2.0 * sigma_space).ceil().max(0.0) as isize;
+    let space_kernel = (-radius..=radius)
+        .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+        .map(|(dx, dy)| {
+            let d2 = (dx * dx + dy * dy) as f64;
+            (dx, dy, (-d2 / (2.0 * sigma_space * sigma_space)).exp())
+        })
+        .collect::<Vec<_>>();
+
+    let mut filtered = depth.to_vec();
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let center = y as usize * width + x as usize;
+            if !is_valid(depth[center]) {
+                continue;
+            }
+
+            let (mut sum, mut weight_sum) = (0.0f64, 0.0f64);
+            for &(dx, dy, space_weight) in space_kernel.iter() {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    continue;
+                }
+                let neighbor = ny as usize * width + nx as usize;
+                let value = depth[neighbor];
+                if !is_valid(value) {
+                    continue;
+                }
+                let weight = space_weight * range_weight(center, neighbor);
+                sum += weight * value as f64;
+                weight_sum += weight;
+            }
+
+            filtered[center] = (sum / weight_sum) as f32;
+        }
+    }
+
+    filtered
+}
+
+/// Convert a stereo disparity map to a depth map.
+///
+/// The depth of a pixel is `fx * baseline / disparity` for a rectified stereo pair.
+///
+/// # Arguments
+///
+/// * `disparity` - The disparity map in pixels.
+/// * `fx` - The focal length of the rectified cameras in pixels.
+/// * `baseline` - The distance between the two cameras.
+/// * `min_disparity` - The smallest valid disparity, which bounds the largest depth.
+///
+/// # Returns
+///
+/// The depth map in the units of the baseline. The pixels with a disparity below
+/// `min_disparity`, not positive or not finite are invalid and set to zero.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::depth::disparity_to_depth;
+///
+/// let depth = disparity_to_depth(&[10.0, 0.0, 0.5], 500.0, 0.1, 1.0);
+/// assert_eq!(depth, vec![5.0, 0.0, 0.0]);
+/// ```
+pub fn disparity_to_depth(
+    disparity: &[f32],
+    fx: f64,
+    baseline: f64,
+    min_disparity: f32,
+) -> Vec<f32> {
+    disparity
+        .iter()
+        .map(|&d| {
+            if is_valid(d) && d >= min_disparity {
+                (fx * baseline / d as f64) as f32
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Convert a stereo disparity map to an organized point cloud in the left camera frame.
+///
+/// The pixel `(u, v)` with disparity `d` is unprojected to
+/// `z = fx * baseline / d`, `x = (u - cx) * z / fx` and `y = (v - cy) * z / fy`.
+///
+/// # Arguments
+///
+/// * `disparity` - The disparity map in pixels, in row-major order.
+/// * `width` - The width of the disparity map.
+/// * `height` - The height of the disparity map.
+/// * `intrinsics` - The intrinsics of the rectified left camera.
+/// * `baseline` - The distance between the two cameras.
+///
+/// # Returns
+///
+/// The organized point cloud, where the pixels with a non-positive or not finite disparity
+/// are invalid.
+///
+/// # Errors
+///
+/// Returns [`DepthError::SizeMismatch`] if the length of `disparity` is not `width * height`.
+pub fn disparity_to_pointcloud(
+    disparity: &[f32],
+    width: usize,
+    height: usize,
+    intrinsics: &CameraIntrinsics,
+    baseline: f64,
+) -> Result<OrganizedPointCloud, DepthError> {
+    check_size(disparity, width, height)?;
+
+    let mut points = vec![[0.0; 3]; disparity.len()];
+    let mut valid = vec![false; disparity.len()];
+    for (i, &d) in disparity.iter().enumerate() {
+        if !is_valid(d) {
+            continue;
+        }
+        let (u, v) = ((i % width) as f64, (i / width) as f64);
+        let z = intrinsics.fx * baseline / d as f64;
+        points[i] = [
+            (u - intrinsics.cx) * z / intrinsics.fx,
+            (v - intrinsics.cy) * z / intrinsics.fy,
+            z,
+        ];
+        valid[i] = true;
+    }
+
+    Ok(OrganizedPointCloud {
+        width,
+        height,
+        points,
+        valid,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_fill_depth_holes_size_limit() {
+        let (width, height) = (20, 20);
+        // a slanted plane
+        let plane = |x: usize, y: usize| 1.0 + 0.01 * x as f32 + 0.02 * y as f32;
+        let mut depth = (0..width * height)
+            .map(|i| plane(i % width, i / width))
+            .collect::<Vec<_>>();
+
+        // a 2x2 hole, a 3x3 hole and a 5x5 hole
+        let holes = [(2, 2, 2), (10, 3, 3), (5, 12, 5)];
+        for &(x0, y0, size) in holes.iter() {
+            for y in y0..y0 + size {
+                for x in x0..x0 + size {
+                    depth[y * width + x] = 0.0;
+                }
+            }
+        }
+
+        let filled = fill_depth_holes(&depth, width, height, 9).unwrap();
+        for &(x0, y0, size) in holes.iter() {
+            for y in y0..y0 + size {
+                for x in x0..x0 + size {
+                    let value = filled[y * width + x];
+                    if size * size <= 9 {
+                        assert_relative_eq!(value, plane(x, y), epsilon = 0.05);
+                    } else {
+                        assert_eq!(value, 0.0);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill_depth_holes_keeps_valid_pixels() {
+        let depth = vec![1.0, f32::NAN, 3.0, 4.0];
+        let filled = fill_depth_holes(&depth, 2, 2, 1).unwrap();
+        assert_eq!(filled[0], 1.0);
+        assert_eq!(filled[2], 3.0);
+        assert_eq!(filled[3], 4.0);
+        assert_relative_eq!(filled[1], 8.0f32 / 3.0);
+    }
+
+    #[test]
+    fn test_bilateral_filter_depth_preserves_step() {
+        let (width, height) = (40, 30);
+        let mut rng = StdRng::seed_from_u64(5);
+        let step = |x: usize| if x < 20 { 1.0 } else { 2.0 };
+        let mut depth = (0..width * height)
+            .map(|i| {
+                let noise: f32 = rng.random_range(-0.01..0.01);
+                step(i % width) + noise
+            })
+            .collect::<Vec<_>>();
+        // an invalid pixel stays invalid
+        depth[5 * width + 5] = 0.0;
+
+        let filtered = bilateral_filter_depth(&depth, width, height, 2.0, 0.05).unwrap();
+        assert_eq!(filtered[5 * width + 5], 0.0);
+
+        for y in 0..height {
+            let row = &filtered[y * width..(y + 1) * width];
+            // the largest jump stays between the columns 19 and 20 within one pixel
+            let edge = (0..width - 1)
+                .max_by(|&a, &b| {
+                    let ja = (row[a + 1] - row[a]).abs();
+                    let jb = (row[b + 1] - row[b]).abs();
+                    ja.total_cmp(&jb)
+                })
+                .unwrap();
+            assert!((18..=20).contains(&edge));
+
+            for (x, value) in row.iter().enumerate() {
+                if *value != 0.0 {
+                    assert_relative_eq!(*value, step(x), epsilon = 0.01);
+                }
+            }
+        }
+
+        // the noise is reduced
+        let residual = |d: &[f32]| {
+            d.iter()
+                .enumerate()
+                .filter(|(_, v)| **v != 0.0)
+                .map(|(i, v)| (v - step(i % width)).abs())
+                .sum::<f32>()
+        };
+        assert!(residual(&filtered) < 0.5 * residual(&depth));
+    }
+
+    #[test]
+    fn test_guided_bilateral_filter_depth_color_edge() {
+        // two objects at close depths, of different colors
+        let (width, height) = (40, 30);
+        let mut rng = StdRng::seed_from_u64(6);
+        let step = |x: usize| if x < 20 { 1.0 } else { 1.02 };
+        let depth = (0..width * height)
+            .map(|i| {
+                let noise: f32 = rng.random_range(-0.005..0.005);
+                step(i % width) + noise
+            })
+            .collect::<Vec<_>>();
+        let guide = (0..width * height)
+            .map(|i| {
+                if i % width < 20 {
+                    [200, 40, 40]
+                } else {
+                    [40, 40, 200]
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // the depth kernel does not separate the objects, the color kernel does
+        let filtered = bilateral_filter_depth(&depth, width, height, 2.0, 0.05).unwrap();
+        let guided =
+            guided_bilateral_filter_depth(&depth, &guide, width, height, 2.0, 20.0).unwrap();
+        let edge_error = |d: &[f32]| {
+            (0..height)
+                .flat_map(|y| [19, 20].map(|x| (d[y * width + x] - step(x)).abs()))
+                .sum::<f32>()
+                / (2 * height) as f32
+        };
+        assert!(edge_error(&filtered) > 0.004);
+        assert!(edge_error(&guided) < 0.002);
+
+        // the noise is reduced
+        let residual = |d: &[f32]| {
+            d.iter()
+                .enumerate()
+                .map(|(i, v)| (v - step(i % width)).abs())
+                .sum::<f32>()
+        };
+        assert!(residual(&guided) < 0.5 * residual(&depth));
+    }
+
+    #[test]
+    fn test_depth_size_mismatch() {
+        let depth = vec![1.0; 5];
+        let guide = vec![[0, 0, 0]; 6];
+        assert!(matches!(
+            fill_depth_holes(&depth, 3, 2, 1),
+            Err(DepthError::SizeMismatch {
+                expected: 6,
+                actual: 5
+            })
+        ));
+        assert!(bilateral_filter_depth(&depth, 3, 2, 1.0, 0.1).is_err());
+        assert!(guided_bilateral_filter_depth(&depth, &guide, 3, 2, 1.0, 10.0).is_err());
+        assert!(guided_bilateral_filter_depth(&[1.0; 6], &guide[..5], 3, 2, 1.0, 10.0).is_err());
+    }
+
+    #[test]
+    fn test_disparity_to_depth_plane() {
+        let (fx, baseline) = (525.0, 0.12);
+        // a fronto-parallel plane at 2.5 has a constant disparity
+        let plane_disparity = (fx * baseline / 2.5) as f32;
+        let mut disparity = vec![plane_disparity; 12];
+        disparity[3] = 0.0;
+        disparity[4] = -1.0;
+        disparity[5] = f32::NAN;
+        disparity[6] = 0.1;
+
+        let depth = disparity_to_depth(&disparity, fx, baseline, 1.0);
+        for (i, d) in depth.iter().enumerate() {
+            if (3..=6).contains(&i) {
+                assert_eq!(*d, 0.0);
+            } else {
+                assert_relative_eq!(*d, 2.5, epsilon = 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_disparity_to_pointcloud_plane() {
+        let (width, height) = (8, 6);
+        let intrinsics = CameraIntrinsics {
+            fx: 500.0,
+            fy: 490.0,
+            cx: 3.5,
+            cy: 2.5,
+        };
+        let baseline = 0.1;
+        let mut disparity = vec![(500.0 * baseline / 4.0) as f32; width * height];
+        disparity[10] = 0.0;
+
+        let cloud =
+            disparity_to_pointcloud(&disparity, width, height, &intrinsics, baseline).unwrap();
+        assert_eq!(cloud.points.len(), width * height);
+        assert_eq!(cloud.valid.iter().filter(|v| !**v).count(), 1);
+        assert!(cloud.get(10 % width, 10 / width).is_none());
+        assert_eq!(cloud.to_pointcloud().len(), width * height - 1);
+
+        for y in 0..height {
+            for x in 0..width {
+                let Some(p) = cloud.get(x, y) else {
+                    continue;
+                };
+                assert_relative_eq!(p[2], 4.0, epsilon = 1e-5);
+                assert_relative_eq!(p[0], (x as f64 - 3.5) * 4.0 / 500.0, epsilon = 1e-6);
+                assert_relative_eq!(p[1], (y as f64 - 2.5) * 4.0 / 490.0, epsilon = 1e-6);
+            }
+        }
+
+        // a disparity map of the wrong size is rejected
+        assert!(matches!(
+            disparity_to_pointcloud(&disparity[1..], width, height, &intrinsics, baseline),
+            Err(DepthError::SizeMismatch {
+                expected: 48,
+                actual: 47
+            })
+        ));
+    }
+}
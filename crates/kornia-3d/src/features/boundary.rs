@@ -0,0 +1,151 @@
+use super::curvature::tangent_frame;
+use crate::{kdtree::KdTree, linalg::dot_product3, pointcloud::PointCloud};
+
+/// Detect the points on the boundary of the surface sampled by a point cloud.
+///
+/// The neighbors of each point within `radius` are projected onto the tangent plane of the
+/// point and sorted by their angle around it. A point is on the boundary if the largest
+/// angular gap between two consecutive neighbors exceeds `angle_threshold`: interior points
+/// are surrounded by neighbors in all directions, while boundary points have an empty
+/// half-plane on one side.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `normals` - The unit normal of each point.
+/// * `radius` - The radius of the neighborhood of each point.
+/// * `angle_threshold` - The largest angular gap of an interior point in radians, typically `PI / 2`.
+///
+/// # Returns
+///
+/// Whether each point is on the boundary. Points with less than two neighbors are on the
+/// boundary.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::detect_boundary_points;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..25).map(|i| [(i % 5) as f64, (i / 5) as f64, 0.0]).collect();
+/// let normals = vec![[0.0, 0.0, 1.0]; 25];
+/// let cloud = PointCloud::new(points, None, None);
+/// let boundary = detect_boundary_points(&cloud, &normals, 1.5, std::f64::consts::FRAC_PI_2);
+/// assert!(boundary[0] && !boundary[12]);
+/// ```
+pub fn detect_boundary_points(
+    cloud: &PointCloud,
+    normals: &[[f64; 3]],
+    radius: f64,
+    angle_threshold: f64,
+) -> Vec<bool> {
+    let kdtree = KdTree::new(cloud.points());
+
+    cloud
+        .points()
+        .iter()
+        .zip(normals.iter())
+        .enumerate()
+        .map(|(i, (point, normal))| {
+            let Some((tu, tv, _)) = tangent_frame(normal) else {
+                return true;
+            };
+
+            let mut angles = kdtree
+                .within_radius(point, radius)
+                .iter()
+                .filter(|n| n.index != i)
+                .filter_map(|n| {
+                    let q = &cloud.points()[n.index];
+                    let d = [q[0] - point[0], q[1] - point[1], q[2] - point[2]];
+                    let (u, v) = (dot_product3(&d, &tu), dot_product3(&d, &tv));
+                    // skip the neighbors projected onto the point itself
+                    (u != 0.0 || v != 0.0).then(|| v.atan2(u))
+                })
+                .collect::<Vec<_>>();
+
+            max_angular_gap(&mut angles) > angle_threshold
+        })
+        .collect()
+}
+
+/// Compute the largest gap between consecutive angles around the circle.
+fn max_angular_gap(angles: &mut [f64]) -> f64 {
+    if angles.len() < 2 {
+        return std::f64::consts::TAU;
+    }
+    angles.sort_by(|a, b| a.total_cmp(b));
+
+    let wrap_gap = angles[0] + std::f64::consts::TAU - angles[angles.len() - 1];
+    angles
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .fold(wrap_gap, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+    #[test]
+    fn test_max_angular_gap() {
+        assert_relative_eq!(max_angular_gap(&mut [0.0, PI]), PI);
+        assert_relative_eq!(max_angular_gap(&mut [0.5, -0.5, 0.0]), TAU - 1.0);
+        assert_relative_eq!(max_angular_gap(&mut [1.0]), TAU);
+    }
+
+    #[test]
+    fn test_detect_boundary_points_disk() {
+        // concentric rings of a disk of radius 1 with a spacing of 0.05
+        let spacing = 0.05;
+        let num_rings = 20;
+        let mut points = vec![[0.0, 0.0, 0.0]];
+        let mut rings = vec![0];
+        for k in 1..=num_rings {
+            let r = k as f64 * spacing;
+            let n = (TAU * r / spacing).round() as usize;
+            for j in 0..n {
+                let theta = j as f64 * TAU / n as f64;
+                points.push([r * theta.cos(), r * theta.sin(), 0.0]);
+                rings.push(k);
+            }
+        }
+        // the orientation of the normals is irrelevant
+        let normals = (0..points.len())
+            .map(|i| {
+                if i % 2 == 0 {
+                    [0.0, 0.0, 1.0]
+                } else {
+                    [0.0, 0.0, -1.0]
+                }
+            })
+            .collect::<Vec<_>>();
+        let cloud = PointCloud::new(points, None, None);
+
+        let boundary = detect_boundary_points(&cloud, &normals, 2.5 * spacing, FRAC_PI_2);
+        for (is_boundary, ring) in boundary.iter().zip(rings.iter()) {
+            assert_eq!(*is_boundary, *ring == num_rings, "ring {ring}");
+        }
+    }
+
+    #[test]
+    fn test_detect_boundary_points_sphere() {
+        let num_points = 2000;
+        let golden = PI * (3.0 - 5.0f64.sqrt());
+        let normals = (0..num_points)
+            .map(|i| {
+                let z = 1.0 - 2.0 * (i as f64 + 0.5) / num_points as f64;
+                let r = (1.0 - z * z).sqrt();
+                let theta = golden * i as f64;
+                [r * theta.cos(), r * theta.sin(), z]
+            })
+            .collect::<Vec<_>>();
+        let cloud = PointCloud::new(normals.clone(), None, None);
+
+        let boundary = detect_boundary_points(&cloud, &normals, 0.2, FRAC_PI_2);
+        let num_boundary = boundary.iter().filter(|b| **b).count();
+        assert!(num_boundary <= num_points / 100);
+    }
+}
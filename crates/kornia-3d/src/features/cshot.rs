@@ -0,0 +1,226 @@
+use super::shot::{
+    interpolate, normalized, shot_support, spatial_bins, SHOT_COSINE_BINS, SHOT_VOLUMES,
+};
+use crate::{kdtree::KdTree, linalg::dot_product3, pointcloud::PointCloud};
+
+/// Number of bins of the colour histogram of each spatial division.
+const CSHOT_COLOUR_BINS: usize = 31;
+
+/// Compute the colour SHOT (CSHOT) descriptor of keypoints.
+///
+/// The descriptor concatenates the SHOT descriptor, see [`super::compute_shot`], with a
+/// texture component built the same way: each of the 32 volumes of the support holds a
+/// 31 bins histogram of the L1 distance in the CIELab space between the colours of its
+/// points and of the keypoint, as described in Tombari et al. 2011. The whole descriptor is
+/// normalized to unit length.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `normals` - The unit normal of each point.
+/// * `colours` - The RGB colour of each point with channels in `[0, 1]`.
+/// * `keypoint_indices` - The indices of the points to describe.
+/// * `radius` - The radius of the support, typically about ten times the point spacing.
+///
+/// # Returns
+///
+/// The 1344 bins descriptor of each keypoint: the 352 bins of the shape component and the
+/// 992 bins of the colour component. Keypoints with less than 5 neighbors or whose
+/// neighbors do not span a plane get a zero descriptor.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::compute_cshot;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..121)
+///     .map(|i| {
+///         let (u, v) = ((i % 11) as f64 * 0.1 - 0.5, (i / 11) as f64 * 0.1 - 0.5);
+///         [u, v, 0.3 * u * u + 0.1 * v * v]
+///     })
+///     .collect::<Vec<_>>();
+/// let normals = vec![[0.0, 0.0, 1.0]; 121];
+/// let colours = (0..121).map(|i| [(i % 11) as f32 / 10.0, 0.5, 0.2]).collect::<Vec<_>>();
+/// let cloud = PointCloud::new(points, None, None);
+/// let descriptors = compute_cshot(&cloud, &normals, &colours, &[60], 0.4);
+/// let norm = descriptors[0].iter().map(|x| x * x).sum::<f32>().sqrt();
+/// assert!((norm - 1.0).abs() < 1e-5);
+/// ```
+pub fn compute_cshot(
+    cloud: &PointCloud,
+    normals: &[[f64; 3]],
+    colours: &[[f32; 3]],
+    keypoint_indices: &[usize],
+    radius: f64,
+) -> Vec<[f32; 1344]> {
+    let points = cloud.points();
+    let kdtree = KdTree::new(points);
+    let lab = colours.iter().map(rgb_to_lab).collect::<Vec<_>>();
+    let colour_offset = SHOT_VOLUMES * SHOT_COSINE_BINS;
+
+    keypoint_indices
+        .iter()
+        .map(|&i| {
+            let Some((lrf, neighbors)) = shot_support(points, &kdtree, i, radius) else {
+                return [0.0; 1344];
+            };
+
+            let mut histogram = [0.0f64; 1344];
+            for n in neighbors.iter() {
+                let cosine = dot_product3(&lrf[2], &normals[n.index]).clamp(-1.0, 1.0);
+                let cosine_bins = interpolate((cosine + 1.0) / 2.0, SHOT_COSINE_BINS, false);
+                let colour_bins = interpolate(
+                    colour_distance(&lab[i], &lab[n.index]),
+                    CSHOT_COLOUR_BINS,
+                    false,
+                );
+                for (volume, wv) in spatial_bins(&lrf, &points[i], &points[n.index], radius) {
+                    for (c, wc) in cosine_bins {
+                        histogram[volume * SHOT_COSINE_BINS + c] += wv * wc;
+                    }
+                    for (c, wc) in colour_bins {
+                        histogram[colour_offset + volume * CSHOT_COLOUR_BINS + c] += wv * wc;
+                    }
+                }
+            }
+
+            normalized(&histogram)
+        })
+        .collect()
+}
+
+/// The L1 distance between two CIELab colours, normalized to `[0, 1]`.
+///
+/// The lightness spans 100 and the chromatic channels about 200 units.
+fn colour_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    let distance =
+        (a[0] - b[0]).abs() / 100.0 + (a[1] - b[1]).abs() / 200.0 + (a[2] - b[2]).abs() / 200.0;
+    (distance / 3.0).clamp(0.0, 1.0)
+}
+
+/// Convert a RGB colour with channels in `[0, 1]` to CIELab with the D65 white point.
+fn rgb_to_lab(rgb: &[f32; 3]) -> [f64; 3] {
+    // sRGB to linear RGB
+    let linear = rgb.map(|c| {
+        let c = (c as f64).clamp(0.0, 1.0);
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    });
+
+    // linear RGB to XYZ relative to the white point
+    let xyz = [
+        (0.4124564 * linear[0] + 0.3575761 * linear[1] + 0.1804375 * linear[2]) / 0.95047,
+        0.2126729 * linear[0] + 0.7151522 * linear[1] + 0.0721750 * linear[2],
+        (0.0193339 * linear[0] + 0.1191920 * linear[1] + 0.9503041 * linear[2]) / 1.08883,
+    ];
+
+    let f = xyz.map(|t| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    });
+    [
+        116.0 * f[1] - 16.0,
+        500.0 * (f[0] - f[1]),
+        200.0 * (f[1] - f[2]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::compute_shot;
+
+    /// A bumpy surface without symmetries with a colour gradient.
+    fn coloured_surface() -> (Vec<[f64; 3]>, Vec<[f32; 3]>) {
+        let points = (0..900)
+            .map(|i| {
+                let (u, v) = ((i % 30) as f64 * 0.05, (i / 30) as f64 * 0.05);
+                [u, v, 0.3 * (2.0 * u).sin() * (3.0 * v).cos()]
+            })
+            .collect::<Vec<_>>();
+        let colours = points
+            .iter()
+            .map(|p| [p[0] as f32 / 1.5, 0.5, (1.0 - p[1] / 1.5) as f32])
+            .collect();
+        (points, colours)
+    }
+
+    #[test]
+    fn test_rgb_to_lab() {
+        let white = rgb_to_lab(&[1.0, 1.0, 1.0]);
+        assert!((white[0] - 100.0).abs() < 1e-2);
+        assert!(white[1].abs() < 1e-2 && white[2].abs() < 1e-2);
+        assert!(rgb_to_lab(&[0.0, 0.0, 0.0])[0].abs() < 1e-9);
+        // red has a positive a channel and blue a negative b channel
+        assert!(rgb_to_lab(&[1.0, 0.0, 0.0])[1] > 50.0);
+        assert!(rgb_to_lab(&[0.0, 0.0, 1.0])[2] < -50.0);
+        assert!(colour_distance(&white, &rgb_to_lab(&[0.0, 0.0, 0.0])) > 0.3);
+    }
+
+    #[test]
+    fn test_cshot_identical_clouds() {
+        let (points, colours) = coloured_surface();
+        let normals = vec![[0.0, 0.0, 1.0]; points.len()];
+        let keypoints = [310, 465, 560];
+
+        let a = compute_cshot(
+            &PointCloud::new(points.clone(), None, None),
+            &normals,
+            &colours,
+            &keypoints,
+            0.3,
+        );
+        let b = compute_cshot(
+            &PointCloud::new(points.clone(), None, None),
+            &normals,
+            &colours,
+            &keypoints,
+            0.3,
+        );
+        assert_eq!(a, b);
+
+        // the shape component is proportional to SHOT
+        let cloud = PointCloud::new(points, None, None);
+        let shot = compute_shot(&cloud, &normals, &keypoints, 0.3);
+        for (c, s) in a.iter().zip(shot.iter()) {
+            let norm = c[..352].iter().map(|x| x * x).sum::<f32>().sqrt();
+            for (x, y) in c[..352].iter().zip(s.iter()) {
+                assert!((x / norm - y).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cshot_colour_component() {
+        let (points, colours) = coloured_surface();
+        let normals = vec![[0.0, 0.0, 1.0]; points.len()];
+        let cloud = PointCloud::new(points, None, None);
+
+        // a uniform colour puts all the colour weight in the first bin of each volume
+        let uniform = vec![[0.3, 0.6, 0.1]; colours.len()];
+        let descriptor = compute_cshot(&cloud, &normals, &uniform, &[465], 0.3)[0];
+        let colour = &descriptor[352..];
+        assert!(colour.iter().any(|x| *x > 0.0));
+        for (k, x) in colour.iter().enumerate() {
+            if k % CSHOT_COLOUR_BINS != 0 {
+                assert_eq!(*x, 0.0);
+            }
+        }
+
+        // the colours change the colour component only
+        let coloured = compute_cshot(&cloud, &normals, &colours, &[465], 0.3)[0];
+        let difference = colour
+            .iter()
+            .zip(coloured[352..].iter())
+            .map(|(x, y)| (x - y).abs())
+            .sum::<f32>();
+        assert!(difference > 0.1);
+    }
+}
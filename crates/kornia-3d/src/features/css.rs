@@ -0,0 +1,233 @@
+use crate::linalg::{cross_vec3, dot_product3, eigen_symmetric33};
+
+/// Compute the Curvature Scale Space (CSS) representation of a 3D curve.
+///
+/// The curve is smoothed with Gaussian kernels of increasing standard deviation and the
+/// zero-crossings of its signed curvature are extracted at each scale. The curvature is
+/// signed by the normal of the best-fit plane of the curve, so the representation is meant
+/// for nearly planar curves such as the silhouettes extracted from depth images. The ends of
+/// the curve are extended by point reflection, which preserves their tangent and does not
+/// introduce spurious inflections.
+///
+/// # Arguments
+///
+/// * `curve_3d` - The ordered points of an open curve, roughly uniformly sampled.
+/// * `sigma_range` - The smallest and largest standard deviation of the Gaussian kernel, in samples.
+/// * `sigma_steps` - The number of scales, linearly spaced in `sigma_range`.
+///
+/// # Returns
+///
+/// The zero-crossings as `(position, sigma)` pairs ordered by scale and position, where
+/// `position` is the arc length from the start of the curve normalized to `[0, 1]`.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::compute_css;
+///
+/// let curve = (0..200)
+///     .map(|i| {
+///         let t = i as f64 * std::f64::consts::TAU / 199.0;
+///         [t, t.sin(), 0.0]
+///     })
+///     .collect::<Vec<_>>();
+/// let css = compute_css(&curve, (1.0, 4.0), 4);
+/// assert_eq!(css.len(), 4);
+/// ```
+pub fn compute_css(
+    curve_3d: &[[f64; 3]],
+    sigma_range: (f64, f64),
+    sigma_steps: usize,
+) -> Vec<(f64, f64)> {
+    let n = curve_3d.len();
+    if n < 3 || sigma_steps == 0 {
+        return Vec::new();
+    }
+
+    let normal = best_fit_normal(curve_3d);
+
+    // normalized arc length of the original samples
+    let mut arc_length = vec![0.0; n];
+    for i in 1..n {
+        let d = sub(&curve_3d[i], &curve_3d[i - 1]);
+        arc_length[i] = arc_length[i - 1] + dot_product3(&d, &d).sqrt();
+    }
+    let total_length = arc_length[n - 1];
+    if total_length <= 0.0 {
+        return Vec::new();
+    }
+    arc_length.iter_mut().for_each(|s| *s /= total_length);
+
+    let mut zero_crossings = Vec::new();
+    for step in 0..sigma_steps {
+        let sigma = if sigma_steps == 1 {
+            sigma_range.0
+        } else {
+            sigma_range.0 + (sigma_range.1 - sigma_range.0) * step as f64 / (sigma_steps - 1) as f64
+        };
+
+        let smoothed = gaussian_smooth(curve_3d, sigma);
+        let curvature = (1..n - 1)
+            .map(|i| signed_curvature(&smoothed[i - 1], &smoothed[i], &smoothed[i + 1], &normal))
+            .collect::<Vec<_>>();
+
+        for (k, pair) in curvature.windows(2).enumerate() {
+            if pair[0] * pair[1] < 0.0 {
+                // interpolate the crossing between the samples k + 1 and k + 2
+                let f = pair[0] / (pair[0] - pair[1]);
+                let position = arc_length[k + 1] + f * (arc_length[k + 2] - arc_length[k + 1]);
+                zero_crossings.push((position, sigma));
+            }
+        }
+    }
+
+    zero_crossings
+}
+
+/// Smooth a curve with a Gaussian kernel, extending its ends by point reflection.
+fn gaussian_smooth(curve: &[[f64; 3]], sigma: f64) -> Vec<[f64; 3]> {
+    if sigma <= 0.0 {
+        return curve.to_vec();
+    }
+
+    let n = curve.len() as isize;
+    let radius = (3.0 * sigma).ceil() as isize;
+    let kernel = (-radius..=radius)
+        .map(|k| (-((k * k) as f64) / (2.0 * sigma * sigma)).exp())
+        .collect::<Vec<_>>();
+    let kernel_sum = kernel.iter().sum::<f64>();
+
+    // the point of the curve extended by point reflection about its ends
+    let extended = |j: isize| -> [f64; 3] {
+        if j < 0 {
+            let p = &curve[(-j).min(n - 1) as usize];
+            let p0 = &curve[0];
+            [2.0 * p0[0] - p[0], 2.0 * p0[1] - p[1], 2.0 * p0[2] - p[2]]
+        } else if j >= n {
+            let p = &curve[(2 * (n - 1) - j).max(0) as usize];
+            let pn = &curve[(n - 1) as usize];
+            [2.0 * pn[0] - p[0], 2.0 * pn[1] - p[1], 2.0 * pn[2] - p[2]]
+        } else {
+            curve[j as usize]
+        }
+    };
+
+    (0..n)
+        .map(|i| {
+            let mut p = [0.0; 3];
+            for (k, w) in (-radius..=radius).zip(kernel.iter()) {
+                let q = extended(i + k);
+                for c in 0..3 {
+                    p[c] += w * q[c] / kernel_sum;
+                }
+            }
+            p
+        })
+        .collect()
+}
+
+/// Curvature at `p1` from central differences, signed by the orientation around `normal`.
+fn signed_curvature(p0: &[f64; 3], p1: &[f64; 3], p2: &[f64; 3], normal: &[f64; 3]) -> f64 {
+    let d1 = [
+        0.5 * (p2[0] - p0[0]),
+        0.5 * (p2[1] - p0[1]),
+        0.5 * (p2[2] - p0[2]),
+    ];
+    let d2 = [
+        p2[0] - 2.0 * p1[0] + p0[0],
+        p2[1] - 2.0 * p1[1] + p0[1],
+        p2[2] - 2.0 * p1[2] + p0[2],
+    ];
+    let speed = dot_product3(&d1, &d1).sqrt();
+    if speed < 1e-12 {
+        return 0.0;
+    }
+    let mut cross = [0.0; 3];
+    cross_vec3(&d1, &d2, &mut cross);
+    dot_product3(&cross, normal) / speed.powi(3)
+}
+
+/// Normal of the best-fit plane of a set of points.
+fn best_fit_normal(points: &[[f64; 3]]) -> [f64; 3] {
+    let n = points.len() as f64;
+    let mut mean = [0.0; 3];
+    for p in points {
+        for k in 0..3 {
+            mean[k] += p[k] / n;
+        }
+    }
+    let mut covariance = [[0.0; 3]; 3];
+    for p in points {
+        let d = sub(p, &mean);
+        for r in 0..3 {
+            for c in 0..3 {
+                covariance[r][c] += d[r] * d[c];
+            }
+        }
+    }
+    let (_, eigenvectors) = eigen_symmetric33(&covariance);
+    eigenvectors[0]
+}
+
+fn sub(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_css_sinusoid() {
+        // a sinusoid in a tilted plane with inflections at t = pi, 2 pi and 3 pi
+        let num_samples = 401;
+        let curve = (0..num_samples)
+            .map(|i| {
+                let t = i as f64 * 4.0 * PI / (num_samples - 1) as f64;
+                [t, t.sin(), 0.5 * t]
+            })
+            .collect::<Vec<_>>();
+
+        let sigma_steps = 4;
+        let css = compute_css(&curve, (1.0, 8.0), sigma_steps);
+        assert_eq!(css.len(), 3 * sigma_steps);
+
+        // by symmetry, the inflections are at a quarter of the arc length
+        for (i, (position, sigma)) in css.iter().enumerate() {
+            assert_relative_eq!(*position, ((i % 3) + 1) as f64 * 0.25, epsilon = 1e-3);
+            assert_relative_eq!(*sigma, 1.0 + (i / 3) as f64 * 7.0 / 3.0, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_css_arc_has_no_crossings() {
+        let curve = (0..100)
+            .map(|i| {
+                let t = i as f64 * 0.03;
+                [t.cos(), t.sin(), 0.2]
+            })
+            .collect::<Vec<_>>();
+        assert!(compute_css(&curve, (0.5, 5.0), 5).is_empty());
+    }
+
+    #[test]
+    fn test_css_scales_remove_small_wiggles() {
+        // a slow sinusoid with a fast low-amplitude ripple
+        let num_samples = 400;
+        let curve = (0..num_samples)
+            .map(|i| {
+                let t = i as f64 * 2.0 * PI / (num_samples - 1) as f64;
+                [t, t.sin() + 0.02 * (20.0 * t).sin(), 0.0]
+            })
+            .collect::<Vec<_>>();
+
+        let css = compute_css(&curve, (0.5, 20.0), 2);
+        let fine = css.iter().filter(|(_, s)| *s < 1.0).count();
+        let coarse = css.iter().filter(|(_, s)| *s > 1.0).collect::<Vec<_>>();
+        assert!(fine > 1);
+        assert_eq!(coarse.len(), 1);
+        assert_relative_eq!(coarse[0].0, 0.5, epsilon = 1e-2);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{
+    kdtree::KdTree,
+    linalg::{cross_vec3, dot_product3, eigen_symmetric22, solve_linear},
+    pointcloud::PointCloud,
+};
+
+/// Minimum ratio between the smallest and largest tangential spread of a neighborhood.
+const MIN_TANGENTIAL_SPREAD: f64 = 0.05;
+
+/// The principal curvatures and directions at a point of a surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrincipalCurvature {
+    /// The maximum principal curvature.
+    pub k1: f64,
+    /// The minimum principal curvature.
+    pub k2: f64,
+    /// The unit direction of maximum curvature.
+    pub dir1: [f64; 3],
+    /// The unit direction of minimum curvature.
+    pub dir2: [f64; 3],
+    /// Whether the neighborhood was spread enough in the tangent plane to fit the surface.
+    pub valid: bool,
+}
+
+impl PrincipalCurvature {
+    const INVALID: Self = Self {
+        k1: 0.0,
+        k2: 0.0,
+        dir1: [0.0; 3],
+        dir2: [0.0; 3],
+        valid: false,
+    };
+}
+
+/// Estimate the principal curvatures and directions of a point cloud surface.
+///
+/// For each point, the `k` nearest neighbors are expressed in the tangent frame of the
+/// point normal and fitted with the quadratic height field
+/// `h(u, v) = a u² + b uv + c v² + d u + e v + f`. The principal curvatures are the
+/// eigenvalues of the shape operator `-[[2a, b], [b, 2c]]`, so that the curvature is
+/// positive where the surface bends away from the normal, e.g. on a sphere with outward
+/// normals.
+///
+/// Points whose neighborhood has too little spread in one tangential direction, such as
+/// points on the border of a thin strip, are flagged as invalid.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `normals` - The unit normal of each point.
+/// * `k` - The number of neighbors used to fit the surface, including the point itself.
+///
+/// # Returns
+///
+/// The principal curvatures of each point.
+pub fn estimate_principal_curvatures(
+    cloud: &PointCloud,
+    normals: &[[f64; 3]],
+    k: usize,
+) -> Vec<PrincipalCurvature> {
+    let kdtree = KdTree::new(cloud.points());
+
+    cloud
+        .points()
+        .iter()
+        .zip(normals.iter())
+        .map(|(point, normal)| {
+            let neighbors = kdtree
+                .nearest_n(point, k)
+                .iter()
+                .map(|n| cloud.points()[n.index])
+                .collect::<Vec<_>>();
+            fit_principal_curvature(point, normal, &neighbors)
+                .unwrap_or(PrincipalCurvature::INVALID)
+        })
+        .collect()
+}
+
+fn fit_principal_curvature(
+    point: &[f64; 3],
+    normal: &[f64; 3],
+    neighbors: &[[f64; 3]],
+) -> Option<PrincipalCurvature> {
+    // at least as many neighbors as coefficients of the height field
+    if neighbors.len() < 6 {
+        return None;
+    }
+
+    let (tu, tv, n) = tangent_frame(normal)?;
+
+    // local coordinates in the tangent frame
+    let local = neighbors
+        .iter()
+        .map(|p| {
+            let d = [p[0] - point[0], p[1] - point[1], p[2] - point[2]];
+            [
+                dot_product3(&d, &tu),
+                dot_product3(&d, &tv),
+                dot_product3(&d, &n),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    // reject neighborhoods that are degenerate in one tangential direction
+    let mut spread = [[0.0; 2]; 2];
+    for [u, v, _] in local.iter() {
+        spread[0][0] += u * u;
+        spread[0][1] += u * v;
+        spread[1][1] += v * v;
+    }
+    let (spread_eigenvalues, _) = eigen_symmetric22(&spread);
+    if spread_eigenvalues[0] <= MIN_TANGENTIAL_SPREAD * spread_eigenvalues[1] {
+        return None;
+    }
+
+    // scale the coordinates to keep the normal equations well conditioned
+    let scale = (spread_eigenvalues[1] / local.len() as f64).sqrt();
+
+    let mut ata = [[0.0; 6]; 6];
+    let mut atb = [0.0; 6];
+    for [u, v, h] in local.iter() {
+        let (u, v, h) = (u / scale, v / scale, h / scale);
+        let row = [u * u, u * v, v * v, u, v, 1.0];
+        for i in 0..6 {
+            for j in 0..6 {
+                ata[i][j] += row[i] * row[j];
+            }
+            atb[i] += row[i] * h;
+        }
+    }
+    let [a, b, c, ..] = solve_linear(&ata, &atb)?;
+
+    // shape operator in the tangent frame, in the original units
+    let shape = [
+        [-2.0 * a / scale, -b / scale],
+        [-b / scale, -2.0 * c / scale],
+    ];
+    let (curvatures, directions) = eigen_symmetric22(&shape);
+
+    let to_3d = |d: [f64; 2]| {
+        [
+            d[0] * tu[0] + d[1] * tv[0],
+            d[0] * tu[1] + d[1] * tv[1],
+            d[0] * tu[2] + d[1] * tv[2],
+        ]
+    };
+
+    Some(PrincipalCurvature {
+        k1: curvatures[1],
+        k2: curvatures[0],
+        dir1: to_3d(directions[1]),
+        dir2: to_3d(directions[0]),
+        valid: true,
+    })
+}
+
+/// Build an orthonormal frame with the normal as third axis.
+pub(crate) fn tangent_frame(normal: &[f64; 3]) -> Option<([f64; 3], [f64; 3], [f64; 3])> {
+    let norm = dot_product3(normal, normal).sqrt();
+    if norm < 1e-12 || !norm.is_finite() {
+        return None;
+    }
+    let n = [normal[0] / norm, normal[1] / norm, normal[2] / norm];
+
+    // any axis not parallel to the normal
+    let helper = if n[0].abs() < 0.9 {
+        [1.0, 0.0, 0.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    let mut tu = [0.0; 3];
+    cross_vec3(&helper, &n, &mut tu);
+    let tu_norm = dot_product3(&tu, &tu).sqrt();
+    let tu = [tu[0] / tu_norm, tu[1] / tu_norm, tu[2] / tu_norm];
+    let mut tv = [0.0; 3];
+    cross_vec3(&n, &tu, &mut tv);
+
+    Some((tu, tv, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_principal_curvatures_cylinder() {
+        let radius = 2.0;
+        let mut points = Vec::new();
+        let mut normals = Vec::new();
+        for i in 0..120 {
+            let theta = i as f64 * std::f64::consts::TAU / 120.0;
+            for j in 0..40 {
+                let z = j as f64 * 0.1;
+                points.push([radius * theta.cos(), radius * theta.sin(), z]);
+                normals.push([theta.cos(), theta.sin(), 0.0]);
+            }
+        }
+        let cloud = PointCloud::new(points, None, None);
+
+        let curvatures = estimate_principal_curvatures(&cloud, &normals, 25);
+        // check points away from the top and bottom borders
+        for (i, c) in curvatures.iter().enumerate().filter(|(i, _)| i % 40 == 20) {
+            assert!(c.valid, "point {i} is invalid");
+            assert_relative_eq!(c.k1, 1.0 / radius, epsilon = 1e-2);
+            assert_relative_eq!(c.k2, 0.0, epsilon = 1e-2);
+            assert_relative_eq!(c.dir2[2].abs(), 1.0, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_principal_curvatures_sphere() {
+        let radius = 3.0;
+        let mut points = Vec::new();
+        let mut normals = Vec::new();
+        // fibonacci sphere
+        let num_points = 4000;
+        let golden = std::f64::consts::PI * (3.0 - 5.0f64.sqrt());
+        for i in 0..num_points {
+            let z = 1.0 - 2.0 * (i as f64 + 0.5) / num_points as f64;
+            let r = (1.0 - z * z).sqrt();
+            let theta = golden * i as f64;
+            let n = [r * theta.cos(), r * theta.sin(), z];
+            points.push([radius * n[0], radius * n[1], radius * n[2]]);
+            normals.push(n);
+        }
+        let cloud = PointCloud::new(points, None, None);
+
+        let curvatures = estimate_principal_curvatures(&cloud, &normals, 30);
+        for c in curvatures.iter().step_by(97) {
+            assert!(c.valid);
+            assert_relative_eq!(c.k1, 1.0 / radius, epsilon = 1e-2);
+            assert_relative_eq!(c.k2, 1.0 / radius, epsilon = 1e-2);
+        }
+    }
+
+    #[test]
+    fn test_principal_curvatures_invalid_line() {
+        let points = (0..20).map(|i| [i as f64 * 0.1, 0.0, 0.0]).collect();
+        let normals = vec![[0.0, 0.0, 1.0]; 20];
+        let cloud = PointCloud::new(points, None, None);
+
+        let curvatures = estimate_principal_curvatures(&cloud, &normals, 8);
+        assert!(curvatures.iter().all(|c| !c.valid));
+    }
+}
@@ -0,0 +1,272 @@
+use crate::{
+    kdtree::KdTree,
+    linalg::{cross_vec3, dot_product3},
+    pointcloud::PointCloud,
+};
+use std::f64::consts::PI;
+
+/// Number of bins of each of the three angular features.
+const FPFH_BINS: usize = 11;
+
+/// Compute the Fast Point Feature Histogram (FPFH) descriptor of each point of a cloud.
+///
+/// For each pair formed by a point and one of its neighbors within `radius`, the Darboux
+/// frame of the pair gives three angular features, each binned in 11 bins: this is the
+/// Simplified Point Feature Histogram (SPFH) of the point. The FPFH of a point is its SPFH
+/// plus the SPFH of its neighbors weighted by the inverse of their distance, as described in
+/// Rusu et al. 2009. Each of the three sub-histograms is normalized to sum to 100.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `normals` - The unit normal of each point.
+/// * `radius` - The radius of the neighborhood of each point, typically about five times
+///   the point spacing.
+///
+/// # Returns
+///
+/// The 33 bins descriptor of each point. Points without neighbors get a zero descriptor.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::compute_fpfh;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..25).map(|i| [(i % 5) as f64, (i / 5) as f64, 0.0]).collect();
+/// let normals = vec![[0.0, 0.0, 1.0]; 25];
+/// let cloud = PointCloud::new(points, None, None);
+/// let descriptors = compute_fpfh(&cloud, &normals, 1.5);
+/// assert_eq!(descriptors.len(), 25);
+/// assert!((descriptors[12].iter().sum::<f32>() - 300.0).abs() < 1e-3);
+/// ```
+pub fn compute_fpfh(cloud: &PointCloud, normals: &[[f64; 3]], radius: f64) -> Vec<[f32; 33]> {
+    let points = cloud.points();
+    let kdtree = KdTree::new(points);
+
+    let neighborhoods = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            kdtree
+                .within_radius(p, radius)
+                .into_iter()
+                .filter(|n| n.index != i)
+                .map(|n| (n.index, n.distance))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // simplified point feature histograms
+    let spfh = neighborhoods
+        .iter()
+        .enumerate()
+        .map(|(i, neighbors)| {
+            let mut histogram = [0.0f64; 3 * FPFH_BINS];
+            let mut num_pairs = 0;
+            for &(j, _) in neighbors {
+                let Some(features) =
+                    pair_features(&points[i], &normals[i], &points[j], &normals[j])
+                else {
+                    continue;
+                };
+                let [alpha, phi, theta] = features;
+                histogram[feature_bin(alpha, -1.0, 1.0)] += 1.0;
+                histogram[FPFH_BINS + feature_bin(phi, -1.0, 1.0)] += 1.0;
+                histogram[2 * FPFH_BINS + feature_bin(theta, -PI, PI)] += 1.0;
+                num_pairs += 1;
+            }
+            if num_pairs > 0 {
+                histogram
+                    .iter_mut()
+                    .for_each(|h| *h *= 100.0 / num_pairs as f64);
+            }
+            histogram
+        })
+        .collect::<Vec<_>>();
+
+    neighborhoods
+        .iter()
+        .enumerate()
+        .map(|(i, neighbors)| {
+            let mut histogram = spfh[i];
+            let mut weighted = [0.0f64; 3 * FPFH_BINS];
+            let mut weight_sum = 0.0;
+            for &(j, distance) in neighbors {
+                if distance <= 0.0 {
+                    continue;
+                }
+                let weight = 1.0 / distance;
+                for (w, s) in weighted.iter_mut().zip(spfh[j].iter()) {
+                    *w += weight * s;
+                }
+                weight_sum += weight;
+            }
+            if weight_sum > 0.0 {
+                for (h, w) in histogram.iter_mut().zip(weighted.iter()) {
+                    *h += w / weight_sum;
+                }
+            }
+
+            // normalize each sub-histogram to sum to 100
+            let mut descriptor = [0.0f32; 33];
+            for k in 0..3 {
+                let bins = k * FPFH_BINS..(k + 1) * FPFH_BINS;
+                let sum = histogram[bins.clone()].iter().sum::<f64>();
+                if sum > 0.0 {
+                    for b in bins {
+                        descriptor[b] = (100.0 * histogram[b] / sum) as f32;
+                    }
+                }
+            }
+            descriptor
+        })
+        .collect()
+}
+
+/// Compute the angular features `(alpha, phi, theta)` of a pair of oriented points.
+///
+/// The source of the Darboux frame is the point whose normal makes the smallest angle with
+/// the line joining the points, which makes the features symmetric in the pair.
+pub(crate) fn pair_features(
+    p1: &[f64; 3],
+    n1: &[f64; 3],
+    p2: &[f64; 3],
+    n2: &[f64; 3],
+) -> Option<[f64; 3]> {
+    let mut d = [p2[0] - p1[0], p2[1] - p1[1], p2[2] - p1[2]];
+    let distance = dot_product3(&d, &d).sqrt();
+    if distance <= 0.0 {
+        return None;
+    }
+
+    let angle1 = dot_product3(n1, &d) / distance;
+    let angle2 = dot_product3(n2, &d) / distance;
+    let (ns, nt, phi) = if angle1.abs().acos() > angle2.abs().acos() {
+        d = [-d[0], -d[1], -d[2]];
+        (n2, n1, -angle2)
+    } else {
+        (n1, n2, angle1)
+    };
+
+    let mut v = [0.0; 3];
+    cross_vec3(&d, ns, &mut v);
+    let v_norm = dot_product3(&v, &v).sqrt();
+    if v_norm <= 1e-12 * distance {
+        return None;
+    }
+    let v = [v[0] / v_norm, v[1] / v_norm, v[2] / v_norm];
+    let mut w = [0.0; 3];
+    cross_vec3(ns, &v, &mut w);
+
+    let alpha = dot_product3(&v, nt);
+    let theta = dot_product3(&w, nt).atan2(dot_product3(ns, nt));
+
+    Some([alpha, phi, theta])
+}
+
+/// Bin of a feature value in the range `[min, max]`.
+fn feature_bin(value: f64, min: f64, max: f64) -> usize {
+    let bin = ((value - min) / (max - min) * FPFH_BINS as f64).floor() as isize;
+    bin.clamp(0, FPFH_BINS as isize - 1) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        features::estimate_normals, linalg::transform_points3d_vec,
+        transforms::axis_angle_to_rotation_matrix,
+    };
+
+    /// A bumpy surface without symmetries.
+    fn bumpy_surface() -> Vec<[f64; 3]> {
+        (0..900)
+            .map(|i| {
+                let (u, v) = ((i % 30) as f64 * 0.05, (i / 30) as f64 * 0.05);
+                [u, v, 0.3 * (2.0 * u).sin() * (3.0 * v).cos() + 0.1 * u * v]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pair_features_symmetric() {
+        let (p1, n1) = ([0.0, 0.0, 0.0], [0.0, 0.0, 1.0]);
+        let (p2, n2) = ([1.0, 0.0, 0.2], [0.6, 0.0, 0.8]);
+        let f12 = pair_features(&p1, &n1, &p2, &n2).unwrap();
+        let f21 = pair_features(&p2, &n2, &p1, &n1).unwrap();
+        for k in 0..3 {
+            assert!((f12[k] - f21[k]).abs() < 1e-12);
+        }
+        assert!(pair_features(&p1, &n1, &p1, &n1).is_none());
+    }
+
+    #[test]
+    fn test_fpfh_rigid_invariance() -> Result<(), Box<dyn std::error::Error>> {
+        let rotation = axis_angle_to_rotation_matrix(&[0.3, -1.0, 0.5], 1.2)?;
+        let translation = [2.0, -1.0, 0.5];
+
+        let points = bumpy_surface();
+        let moved_points = transform_points3d_vec(&points, &rotation, &translation);
+        // orient the normals consistently in both frames
+        let viewpoint = [0.7, 0.7, 5.0];
+        let moved_viewpoint = transform_points3d_vec(&[viewpoint], &rotation, &translation)[0];
+
+        let cloud = PointCloud::new(points, None, None);
+        let moved = PointCloud::new(moved_points, None, None);
+        let a = compute_fpfh(&cloud, &estimate_normals(&cloud, 0.115, &viewpoint), 0.235);
+        let b = compute_fpfh(
+            &moved,
+            &estimate_normals(&moved, 0.115, &moved_viewpoint),
+            0.235,
+        );
+        for (da, db) in a.iter().zip(b.iter()) {
+            for k in 0..33 {
+                assert!((da[k] - db[k]).abs() < 0.5, "{da:?} != {db:?}");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fpfh_discriminates_plane_and_sphere() {
+        let plane = (0..400)
+            .map(|i| [(i % 20) as f64 * 0.05, (i / 20) as f64 * 0.05, 0.0])
+            .collect::<Vec<_>>();
+        let num_points = 1500;
+        let golden = PI * (3.0 - 5.0f64.sqrt());
+        let sphere = (0..num_points)
+            .map(|i| {
+                let z = 1.0 - 2.0 * (i as f64 + 0.5) / num_points as f64;
+                let r = (1.0 - z * z).sqrt();
+                let theta = golden * i as f64;
+                [0.5 * r * theta.cos(), 0.5 * r * theta.sin(), 0.5 * z]
+            })
+            .collect::<Vec<_>>();
+
+        let plane_cloud = PointCloud::new(plane, None, None);
+        let sphere_cloud = PointCloud::new(sphere, None, None);
+        let plane_fpfh = compute_fpfh(
+            &plane_cloud,
+            &estimate_normals(&plane_cloud, 0.12, &[0.0, 0.0, 1.0]),
+            0.2,
+        );
+        let sphere_fpfh = compute_fpfh(
+            &sphere_cloud,
+            &estimate_normals(&sphere_cloud, 0.12, &[0.0; 3]),
+            0.2,
+        );
+
+        let distance = |a: &[f32; 33], b: &[f32; 33]| {
+            a.iter()
+                .zip(b.iter())
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f32>()
+                .sqrt()
+        };
+        // two interior plane points are alike, a plane point and a sphere point are not
+        let (p0, p1) = (&plane_fpfh[8 * 20 + 8], &plane_fpfh[11 * 20 + 11]);
+        assert!(distance(p0, p1) < 1.0);
+        assert!(distance(p0, &sphere_fpfh[700]) > 10.0 * distance(p0, p1).max(1.0));
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+
+use crate::{linalg::dot_product3, pointcloud::PointCloud};
+
+/// Number of surface classes of a voxel.
+const NUM_CLASSES: usize = 5;
+
+/// Radius, relative to the voxel size, above which a surface is considered flat.
+const FLAT_RADIUS_RATIO: f64 = 5.0;
+
+/// Radius, relative to the voxel size, below which a curved surface is a sharp edge.
+const EDGE_RADIUS_RATIO: f64 = 1.0;
+
+/// Minimum number of points in a voxel to estimate its surface class.
+const MIN_VOXEL_POINTS: usize = 3;
+
+/// The surface class of a voxel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SurfaceClass {
+    Empty = 0,
+    Plane = 1,
+    Cylinder = 2,
+    Sphere = 3,
+    Edge = 4,
+}
+
+/// Compute the Global Radius-based Surface Descriptor (GRSD) of a point cloud.
+///
+/// The cloud is voxelized and the surface in each voxel is classified from its minimum
+/// and maximum radii (RSD), estimated from the angle between the normals of the point
+/// pairs in the voxel as a function of their distance:
+///
+/// * plane: the minimum radius is large,
+/// * cylinder: only the maximum radius is large,
+/// * edge: only the maximum radius is large and the minimum radius is below the voxel size,
+/// * sphere: both radii are small, which also includes corners.
+///
+/// Voxels with fewer than three points are considered empty. The descriptor is the
+/// normalized histogram of the class pairs of all the face-adjacent voxels with at least
+/// one occupied voxel, e.g. plane-plane, plane-edge or edge-edge. The bins are ordered by
+/// the class pairs `(i, j)` with `i <= j` in the order empty, plane, cylinder, sphere, edge.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `normals` - The unit normal of each point.
+/// * `voxel_size` - The edge length of the voxels.
+/// * `n_bins` - The number of distance bins used to estimate the radii in each voxel.
+///
+/// # Returns
+///
+/// The descriptor with 15 elements summing to one, or all zeros if no voxel is occupied.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::compute_grsd;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..400).map(|i| [(i % 20) as f64 * 0.05, (i / 20) as f64 * 0.05, 0.0]).collect();
+/// let normals = vec![[0.0, 0.0, 1.0]; 400];
+/// let descriptor = compute_grsd(&PointCloud::new(points, None, None), &normals, 0.25, 8);
+/// assert_eq!(descriptor.len(), 15);
+/// ```
+pub fn compute_grsd(
+    cloud: &PointCloud,
+    normals: &[[f64; 3]],
+    voxel_size: f64,
+    n_bins: usize,
+) -> Vec<f32> {
+    let num_pairs = NUM_CLASSES * (NUM_CLASSES + 1) / 2;
+    let mut descriptor = vec![0.0f32; num_pairs];
+
+    // group the points by voxel
+    let mut voxels: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+    for (i, p) in cloud.points().iter().enumerate().take(normals.len()) {
+        let key = [
+            (p[0] / voxel_size).floor() as i64,
+            (p[1] / voxel_size).floor() as i64,
+            (p[2] / voxel_size).floor() as i64,
+        ];
+        voxels.entry(key).or_default().push(i);
+    }
+
+    let classes = voxels
+        .iter()
+        .filter(|(_, indices)| indices.len() >= MIN_VOXEL_POINTS)
+        .map(|(key, indices)| {
+            let class = classify_voxel(cloud.points(), normals, indices, voxel_size, n_bins);
+            (*key, class)
+        })
+        .collect::<HashMap<_, _>>();
+
+    // count the class pairs of the face-adjacent voxels, each unordered pair once
+    let mut total = 0.0f32;
+    for (key, class) in classes.iter() {
+        for axis in 0..3 {
+            for step in [-1, 1] {
+                let mut neighbor = *key;
+                neighbor[axis] += step;
+                let neighbor_class = classes
+                    .get(&neighbor)
+                    .copied()
+                    .unwrap_or(SurfaceClass::Empty);
+                // occupied pairs are visited from both sides
+                if neighbor_class != SurfaceClass::Empty && neighbor < *key {
+                    continue;
+                }
+                descriptor[pair_bin(*class, neighbor_class)] += 1.0;
+                total += 1.0;
+            }
+        }
+    }
+
+    if total > 0.0 {
+        descriptor.iter_mut().for_each(|d| *d /= total);
+    }
+
+    descriptor
+}
+
+/// Classify the surface in a voxel from its minimum and maximum radii.
+fn classify_voxel(
+    points: &[[f64; 3]],
+    normals: &[[f64; 3]],
+    indices: &[usize],
+    voxel_size: f64,
+    n_bins: usize,
+) -> SurfaceClass {
+    let n_bins = n_bins.max(1);
+    let max_distance = voxel_size * 3.0f64.sqrt();
+
+    // minimum and maximum angle between the normals for each distance bin
+    let mut min_angle = vec![f64::INFINITY; n_bins];
+    let mut max_angle = vec![f64::NEG_INFINITY; n_bins];
+    for (a, &i) in indices.iter().enumerate() {
+        for &j in &indices[a + 1..] {
+            let d = [
+                points[i][0] - points[j][0],
+                points[i][1] - points[j][1],
+                points[i][2] - points[j][2],
+            ];
+            let distance = dot_product3(&d, &d).sqrt();
+            if distance < 1e-12 {
+                continue;
+            }
+            // normals may be oriented inconsistently
+            let angle = dot_product3(&normals[i], &normals[j])
+                .abs()
+                .clamp(0.0, 1.0)
+                .acos();
+            let bin = ((distance / max_distance * n_bins as f64) as usize).min(n_bins - 1);
+            min_angle[bin] = min_angle[bin].min(angle);
+            max_angle[bin] = max_angle[bin].max(angle);
+        }
+    }
+
+    // fit angle = distance / radius through the origin for both envelopes
+    let bin_center = |bin: usize| (bin as f64 + 0.5) * max_distance / n_bins as f64;
+    let fit_curvature = |angles: &[f64]| {
+        let (num, den) = angles
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| a.is_finite())
+            .fold((0.0, 0.0), |(num, den), (bin, a)| {
+                let d = bin_center(bin);
+                (num + d * a, den + d * d)
+            });
+        if den > 0.0 {
+            num / den
+        } else {
+            0.0
+        }
+    };
+    let r_min = 1.0 / fit_curvature(&max_angle);
+    let r_max = 1.0 / fit_curvature(&min_angle);
+
+    let flat_radius = FLAT_RADIUS_RATIO * voxel_size;
+    if r_min > flat_radius {
+        SurfaceClass::Plane
+    } else if r_max > flat_radius {
+        if r_min < EDGE_RADIUS_RATIO * voxel_size {
+            SurfaceClass::Edge
+        } else {
+            SurfaceClass::Cylinder
+        }
+    } else {
+        SurfaceClass::Sphere
+    }
+}
+
+/// Index of an unordered pair of classes in the upper triangle of the class matrix.
+fn pair_bin(a: SurfaceClass, b: SurfaceClass) -> usize {
+    let (i, j) = if (a as usize) <= (b as usize) {
+        (a as usize, b as usize)
+    } else {
+        (b as usize, a as usize)
+    };
+    i * NUM_CLASSES - i * (i + 1) / 2 + j
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn plane_cloud() -> (PointCloud, Vec<[f64; 3]>) {
+        let points = (0..1600)
+            .map(|i| [(i % 40) as f64 * 0.05, (i / 40) as f64 * 0.05, 0.1])
+            .collect::<Vec<_>>();
+        let normals = vec![[0.0, 0.0, 1.0]; points.len()];
+        (PointCloud::new(points, None, None), normals)
+    }
+
+    fn sphere_cloud(radius: f64) -> (PointCloud, Vec<[f64; 3]>) {
+        let num_points = 3000;
+        let golden = std::f64::consts::PI * (3.0 - 5.0f64.sqrt());
+        let normals = (0..num_points)
+            .map(|i| {
+                let z = 1.0 - 2.0 * (i as f64 + 0.5) / num_points as f64;
+                let r = (1.0 - z * z).sqrt();
+                let theta = golden * i as f64;
+                [r * theta.cos(), r * theta.sin(), z]
+            })
+            .collect::<Vec<_>>();
+        let points = normals
+            .iter()
+            .map(|n| [radius * n[0], radius * n[1], radius * n[2]])
+            .collect();
+        (PointCloud::new(points, None, None), normals)
+    }
+
+    #[test]
+    fn test_grsd_plane() {
+        let (cloud, normals) = plane_cloud();
+        let descriptor = compute_grsd(&cloud, &normals, 0.25, 8);
+
+        assert_eq!(descriptor.len(), 15);
+        assert_relative_eq!(descriptor.iter().sum::<f32>(), 1.0, epsilon = 1e-5);
+
+        // only plane-empty and plane-plane transitions
+        let plane_empty = pair_bin(SurfaceClass::Empty, SurfaceClass::Plane);
+        let plane_plane = pair_bin(SurfaceClass::Plane, SurfaceClass::Plane);
+        assert_relative_eq!(
+            descriptor[plane_empty] + descriptor[plane_plane],
+            1.0,
+            epsilon = 1e-5
+        );
+        assert!(descriptor[plane_plane] > 0.0);
+    }
+
+    #[test]
+    fn test_grsd_sphere_differs_from_plane() {
+        let (plane, plane_normals) = plane_cloud();
+        let (sphere, sphere_normals) = sphere_cloud(0.3);
+
+        let plane_descriptor = compute_grsd(&plane, &plane_normals, 0.1, 8);
+        let sphere_descriptor = compute_grsd(&sphere, &sphere_normals, 0.1, 8);
+
+        let sphere_bins = [
+            pair_bin(SurfaceClass::Empty, SurfaceClass::Sphere),
+            pair_bin(SurfaceClass::Sphere, SurfaceClass::Sphere),
+        ];
+        let sphere_mass = sphere_bins
+            .iter()
+            .map(|&b| sphere_descriptor[b])
+            .sum::<f32>();
+        assert!(sphere_mass > 0.5);
+        assert!(sphere_bins.iter().all(|&b| plane_descriptor[b] == 0.0));
+    }
+
+    #[test]
+    fn test_grsd_pair_bins() {
+        let mut bins = Vec::new();
+        for i in 0..NUM_CLASSES {
+            for j in i..NUM_CLASSES {
+                let class = |c: usize| match c {
+                    0 => SurfaceClass::Empty,
+                    1 => SurfaceClass::Plane,
+                    2 => SurfaceClass::Cylinder,
+                    3 => SurfaceClass::Sphere,
+                    _ => SurfaceClass::Edge,
+                };
+                assert_eq!(pair_bin(class(i), class(j)), pair_bin(class(j), class(i)));
+                bins.push(pair_bin(class(i), class(j)));
+            }
+        }
+        assert_eq!(bins, (0..15).collect::<Vec<_>>());
+    }
+}
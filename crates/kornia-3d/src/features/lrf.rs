@@ -0,0 +1,263 @@
+use crate::linalg::{cross_vec3, dot_product3, eigen_symmetric33};
+
+/// Compute a local reference frame (LRF) aligned with the gravity direction.
+///
+/// The frame follows the gravity-aligned SHOT convention:
+///
+/// * the z-axis is the flipped surface normal,
+/// * the x-axis is the projection of the gravity onto the tangent plane, normalized,
+/// * the y-axis is `z × x`.
+///
+/// Descriptors computed in this frame are invariant to rotations around the gravity
+/// direction (yaw) but not to tilts, which is desirable for outdoor LiDAR data.
+///
+/// If the gravity is parallel to the normal, the x-axis is chosen as an arbitrary
+/// direction in the tangent plane.
+///
+/// # Arguments
+///
+/// * `normal` - The surface normal at the point of the frame.
+/// * `gravity` - The gravity direction.
+///
+/// # Returns
+///
+/// The rotation matrix with the x, y and z axes of the frame as rows.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::compute_gravity_aligned_lrf;
+///
+/// let lrf = compute_gravity_aligned_lrf([1.0, 0.0, 0.0], [0.0, 0.0, -1.0]);
+/// assert_eq!(lrf[2], [-1.0, 0.0, 0.0]);
+/// assert_eq!(lrf[0], [0.0, 0.0, -1.0]);
+/// ```
+pub fn compute_gravity_aligned_lrf(normal: [f64; 3], gravity: [f64; 3]) -> [[f64; 3]; 3] {
+    let z_axis = normalize([-normal[0], -normal[1], -normal[2]]).unwrap_or([0.0, 0.0, 1.0]);
+
+    let project = |v: [f64; 3]| {
+        let d = dot_product3(&v, &z_axis);
+        normalize([
+            v[0] - d * z_axis[0],
+            v[1] - d * z_axis[1],
+            v[2] - d * z_axis[2],
+        ])
+    };
+
+    // fall back to the world axis least aligned with z when gravity is parallel to the normal
+    let x_axis = project(gravity).unwrap_or_else(|| {
+        let axis = (0..3)
+            .min_by(|&a, &b| z_axis[a].abs().total_cmp(&z_axis[b].abs()))
+            .unwrap_or(0);
+        let mut e = [0.0; 3];
+        e[axis] = 1.0;
+        project(e).unwrap_or([1.0, 0.0, 0.0])
+    });
+
+    let mut y_axis = [0.0; 3];
+    cross_vec3(&z_axis, &x_axis, &mut y_axis);
+
+    [x_axis, y_axis, z_axis]
+}
+
+/// Compute the local reference frame (LRF) of the SHOT descriptor.
+///
+/// The frame is computed as in Tombari et al. 2010 from the covariance of the neighbors
+/// around the point itself, each weighted by `radius - distance` so that the far points,
+/// more likely to be occluded, matter less:
+///
+/// * the x-axis is the eigenvector of the largest eigenvalue,
+/// * the z-axis is the eigenvector of the smallest eigenvalue,
+/// * the y-axis is `z × x`.
+///
+/// The sign of the x and z axes is disambiguated towards the majority of the neighbors, so
+/// that the frame is repeatable under rigid transforms of the cloud.
+///
+/// # Arguments
+///
+/// * `point` - The point at which the frame is computed.
+/// * `neighbors` - The neighbors of the point within `radius`.
+/// * `radius` - The radius of the neighborhood.
+///
+/// # Returns
+///
+/// The rotation matrix with the x, y and z axes of the frame as rows, or `None` if the
+/// neighbors do not span a plane.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::compute_shot_lrf;
+///
+/// let neighbors = [[1.0, 0.0, 0.0], [-0.8, 0.0, 0.0], [0.0, 0.5, 0.0], [0.0, -0.4, 0.0]];
+/// let lrf = compute_shot_lrf([0.0; 3], &neighbors, 2.0).unwrap();
+/// assert!((lrf[0][0] - 1.0).abs() < 1e-9);
+/// assert!((lrf[2][2].abs() - 1.0).abs() < 1e-9);
+/// ```
+pub fn compute_shot_lrf(
+    point: [f64; 3],
+    neighbors: &[[f64; 3]],
+    radius: f64,
+) -> Option<[[f64; 3]; 3]> {
+    let offsets = neighbors
+        .iter()
+        .map(|q| [q[0] - point[0], q[1] - point[1], q[2] - point[2]])
+        .collect::<Vec<_>>();
+
+    let mut covariance = [[0.0; 3]; 3];
+    let mut weight_sum = 0.0;
+    for d in offsets.iter() {
+        let weight = (radius - dot_product3(d, d).sqrt()).max(0.0);
+        for r in 0..3 {
+            for c in 0..3 {
+                covariance[r][c] += weight * d[r] * d[c];
+            }
+        }
+        weight_sum += weight;
+    }
+    if weight_sum <= 0.0 {
+        return None;
+    }
+
+    let (eigenvalues, eigenvectors) = eigen_symmetric33(&covariance);
+    if eigenvalues[1] <= 1e-12 * eigenvalues[2].max(f64::MIN_POSITIVE) {
+        return None;
+    }
+
+    // point the axis towards the side of the majority of the neighbors
+    let disambiguate = |axis: [f64; 3]| {
+        let (mut balance, mut sum) = (0i64, 0.0);
+        for d in offsets.iter() {
+            let projection = dot_product3(d, &axis);
+            balance += (projection > 0.0) as i64 - (projection < 0.0) as i64;
+            sum += projection;
+        }
+        if balance < 0 || (balance == 0 && sum < 0.0) {
+            [-axis[0], -axis[1], -axis[2]]
+        } else {
+            axis
+        }
+    };
+    let x_axis = disambiguate(eigenvectors[2]);
+    let z_axis = disambiguate(eigenvectors[0]);
+    let mut y_axis = [0.0; 3];
+    cross_vec3(&z_axis, &x_axis, &mut y_axis);
+
+    Some([x_axis, y_axis, z_axis])
+}
+
+fn normalize(v: [f64; 3]) -> Option<[f64; 3]> {
+    let norm = dot_product3(&v, &v).sqrt();
+    if norm < 1e-12 || !norm.is_finite() {
+        return None;
+    }
+    Some([v[0] / norm, v[1] / norm, v[2] / norm])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{linalg::mat33_mul_vec3, transforms::axis_angle_to_rotation_matrix};
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_gravity_aligned_lrf_opposes_normal() {
+        let normal = [0.3, -0.2, 0.9];
+        let norm = dot_product3(&normal, &normal).sqrt();
+        let lrf = compute_gravity_aligned_lrf(normal, [0.0, 0.0, -9.81]);
+
+        assert_relative_eq!(dot_product3(&lrf[2], &normal), -norm, epsilon = 1e-12);
+
+        // orthonormal and right-handed
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert_relative_eq!(dot_product3(&lrf[i], &lrf[j]), expected, epsilon = 1e-12);
+            }
+        }
+        let mut z = [0.0; 3];
+        cross_vec3(&lrf[0], &lrf[1], &mut z);
+        assert_relative_eq!(dot_product3(&z, &lrf[2]), 1.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_gravity_aligned_lrf_yaw_invariant() {
+        let gravity = [0.0, 0.0, -1.0];
+        let normal = [0.6, 0.0, 0.8];
+        let lrf = compute_gravity_aligned_lrf(normal, gravity);
+
+        // rotating the normal around gravity rotates the frame with it
+        let (s, c) = 0.7f64.sin_cos();
+        let rotate = |v: [f64; 3]| [c * v[0] - s * v[1], s * v[0] + c * v[1], v[2]];
+        let lrf_rot = compute_gravity_aligned_lrf(rotate(normal), gravity);
+        for (axis, axis_rot) in lrf.iter().zip(lrf_rot.iter()) {
+            let expected = rotate(*axis);
+            for k in 0..3 {
+                assert_relative_eq!(axis_rot[k], expected[k], epsilon = 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_gravity_aligned_lrf_degenerate() {
+        let lrf = compute_gravity_aligned_lrf([0.0, 0.0, 1.0], [0.0, 0.0, -1.0]);
+        assert_eq!(lrf[2], [0.0, 0.0, -1.0]);
+        assert_relative_eq!(dot_product3(&lrf[0], &lrf[2]), 0.0);
+        assert_relative_eq!(dot_product3(&lrf[0], &lrf[0]), 1.0);
+    }
+
+    #[test]
+    fn test_shot_lrf_rigid_consistency() -> Result<(), Box<dyn std::error::Error>> {
+        let rotation = axis_angle_to_rotation_matrix(&[0.2, 1.0, -0.4], 2.1)?;
+        let translation = [1.0, -3.0, 0.5];
+        let transform = |p: &[f64; 3]| {
+            let mut q = [0.0; 3];
+            mat33_mul_vec3(&rotation, p, &mut q);
+            [
+                q[0] + translation[0],
+                q[1] + translation[1],
+                q[2] + translation[2],
+            ]
+        };
+
+        // a patch of a bumpy surface around the origin
+        let neighbors = (0..121)
+            .map(|i| {
+                let (u, v) = ((i % 11) as f64 * 0.1 - 0.5, (i / 11) as f64 * 0.1 - 0.5);
+                [u, v, 0.3 * u * u - 0.1 * v * v + 0.2 * u * v + 0.1 * u]
+            })
+            .collect::<Vec<_>>();
+        let moved = neighbors.iter().map(transform).collect::<Vec<_>>();
+
+        let lrf = compute_shot_lrf([0.0; 3], &neighbors, 0.8).unwrap();
+        let moved_lrf = compute_shot_lrf(transform(&[0.0; 3]), &moved, 0.8).unwrap();
+
+        // orthonormal and right-handed
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert_relative_eq!(dot_product3(&lrf[i], &lrf[j]), expected, epsilon = 1e-12);
+            }
+        }
+        let mut z = [0.0; 3];
+        cross_vec3(&lrf[0], &lrf[1], &mut z);
+        assert_relative_eq!(dot_product3(&z, &lrf[2]), 1.0, epsilon = 1e-12);
+
+        // the frame rotates with the cloud
+        for (axis, moved_axis) in lrf.iter().zip(moved_lrf.iter()) {
+            let mut expected = [0.0; 3];
+            mat33_mul_vec3(&rotation, axis, &mut expected);
+            for k in 0..3 {
+                assert_relative_eq!(moved_axis[k], expected[k], epsilon = 1e-9);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_shot_lrf_degenerate() {
+        let line = [[1.0, 0.0, 0.0], [2.0, 0.0, 0.0], [-1.0, 0.0, 0.0]];
+        assert!(compute_shot_lrf([0.0; 3], &line, 3.0).is_none());
+        assert!(compute_shot_lrf([0.0; 3], &[], 3.0).is_none());
+    }
+}
@@ -0,0 +1,38 @@
+mod boundary;
+pub use boundary::*;
+
+mod cshot;
+pub use cshot::*;
+
+mod css;
+pub use css::*;
+
+mod curvature;
+pub use curvature::*;
+
+mod fpfh;
+pub use fpfh::*;
+
+mod grsd;
+pub use grsd::*;
+
+mod lrf;
+pub use lrf::*;
+
+mod normals;
+pub use normals::*;
+
+mod persistence;
+pub use persistence::*;
+
+mod saliency;
+pub use saliency::*;
+
+mod shot;
+pub use shot::*;
+
+mod surface_signature;
+pub use surface_signature::*;
+
+mod vfh;
+pub use vfh::*;
@@ -0,0 +1,116 @@
+use crate::{
+    kdtree::KdTree,
+    linalg::{dot_product3, eigen_symmetric33},
+    pointcloud::PointCloud,
+};
+
+/// Estimate the surface normals of a point cloud.
+///
+/// The normal of each point is the direction of least variance of its neighbors within
+/// `radius`, i.e. the eigenvector of the smallest eigenvalue of their covariance. The normals
+/// are flipped to point towards the `viewpoint`, typically the sensor center.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `radius` - The radius of the neighborhood of each point.
+/// * `viewpoint` - The point the normals are oriented towards.
+///
+/// # Returns
+///
+/// The unit normal of each point. Points with less than three neighbors, including the
+/// point itself, get a zero normal.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::estimate_normals;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..25).map(|i| [(i % 5) as f64, (i / 5) as f64, 0.0]).collect();
+/// let cloud = PointCloud::new(points, None, None);
+/// let normals = estimate_normals(&cloud, 1.5, &[0.0, 0.0, -1.0]);
+/// assert_eq!(normals[12], [0.0, 0.0, -1.0]);
+/// ```
+pub fn estimate_normals(cloud: &PointCloud, radius: f64, viewpoint: &[f64; 3]) -> Vec<[f64; 3]> {
+    let kdtree = KdTree::new(cloud.points());
+
+    cloud
+        .points()
+        .iter()
+        .map(|point| {
+            let neighbors = kdtree.within_radius(point, radius);
+            if neighbors.len() < 3 {
+                return [0.0; 3];
+            }
+
+            let n = neighbors.len() as f64;
+            let mut mean = [0.0; 3];
+            for neighbor in neighbors.iter() {
+                let q = &cloud.points()[neighbor.index];
+                for k in 0..3 {
+                    mean[k] += q[k] / n;
+                }
+            }
+            let mut covariance = [[0.0; 3]; 3];
+            for neighbor in neighbors.iter() {
+                let q = &cloud.points()[neighbor.index];
+                let d = [q[0] - mean[0], q[1] - mean[1], q[2] - mean[2]];
+                for r in 0..3 {
+                    for c in r..3 {
+                        covariance[r][c] += d[r] * d[c];
+                    }
+                }
+            }
+
+            let (_, eigenvectors) = eigen_symmetric33(&covariance);
+            let normal = eigenvectors[0];
+            let to_viewpoint = [
+                viewpoint[0] - point[0],
+                viewpoint[1] - point[1],
+                viewpoint[2] - point[2],
+            ];
+            if dot_product3(&normal, &to_viewpoint) < 0.0 {
+                [-normal[0], -normal[1], -normal[2]]
+            } else {
+                normal
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_estimate_normals_sphere() {
+        // a Fibonacci sphere seen from its center
+        let num_points = 2000;
+        let golden = std::f64::consts::PI * (3.0 - 5.0f64.sqrt());
+        let points = (0..num_points)
+            .map(|i| {
+                let z = 1.0 - 2.0 * (i as f64 + 0.5) / num_points as f64;
+                let r = (1.0 - z * z).sqrt();
+                let theta = golden * i as f64;
+                [2.0 + r * theta.cos(), r * theta.sin(), z]
+            })
+            .collect::<Vec<_>>();
+        let cloud = PointCloud::new(points.clone(), None, None);
+
+        let normals = estimate_normals(&cloud, 0.15, &[2.0, 0.0, 0.0]);
+        for (p, n) in points.iter().zip(normals.iter()) {
+            let inward = [2.0 - p[0], -p[1], -p[2]];
+            assert_relative_eq!(dot_product3(n, n), 1.0, epsilon = 1e-9);
+            assert!(dot_product3(n, &inward) > 0.99);
+        }
+    }
+
+    #[test]
+    fn test_estimate_normals_isolated_points() {
+        let cloud = PointCloud::new(vec![[0.0, 0.0, 0.0], [5.0, 0.0, 0.0]], None, None);
+        let normals = estimate_normals(&cloud, 1.0, &[0.0; 3]);
+        assert_eq!(normals, vec![[0.0; 3]; 2]);
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{kdtree::KdTree, pointcloud::PointCloud};
+
+/// Number of nearest neighbors of each point joined by an edge of the complex.
+const PERSISTENCE_NEIGHBORS: usize = 8;
+
+/// Compute the persistence diagram of a point cloud.
+///
+/// The Vietoris-Rips complex is restricted to the graph joining each point to its 8 nearest
+/// neighbors, and to the triangles of this graph. The filtration value of a point is
+/// `filtration(p)`, of an edge the largest of the values of its points and of its length,
+/// and of a triangle the largest value of its edges, so that a constant filtration gives the
+/// Vietoris-Rips filtration by distance and e.g. a height function sweeps the shape along an
+/// axis. The connected components, the homology of dimension 0, are tracked with a
+/// union-find in which the younger of two merged components dies, and the cycles, the
+/// homology of dimension 1, by the reduction of the boundary matrix of the triangles.
+///
+/// With a constant filtration, the diagram only depends on the distances between the points
+/// and is invariant to the isometries of the cloud.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `filtration` - The filtration value of a point.
+/// * `max_dimension` - The largest homology dimension to compute, at most 1.
+///
+/// # Returns
+///
+/// The `(dimension, birth, death)` of each feature with a positive persistence, sorted by
+/// dimension and birth. The features that never die, e.g. the component of a connected
+/// cloud, have an infinite death.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::compute_persistence_diagram;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// // two clusters of points 10 apart
+/// let points = (0..20)
+///     .map(|i| [(i % 10) as f64 * 0.1 + (i / 10) as f64 * 10.0, 0.0, 0.0])
+///     .collect();
+/// let cloud = PointCloud::new(points, None, None);
+/// let diagram = compute_persistence_diagram(&cloud, |_| 0.0, 0);
+/// let num_components = diagram.iter().filter(|(_, _, death)| death.is_infinite()).count();
+/// assert_eq!(num_components, 2);
+/// ```
+pub fn compute_persistence_diagram(
+    cloud: &PointCloud,
+    filtration: impl Fn([f64; 3]) -> f64,
+    max_dimension: usize,
+) -> Vec<(usize, f64, f64)> {
+    let points = cloud.points();
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let values = points.iter().map(|p| filtration(*p)).collect::<Vec<_>>();
+
+    // the edges of the k-NN graph in the order of the filtration
+    let kdtree = KdTree::new(points);
+    let mut edge_set = HashSet::new();
+    for (i, p) in points.iter().enumerate() {
+        for nn in kdtree.nearest_n(p, PERSISTENCE_NEIGHBORS + 1) {
+            if nn.index != i {
+                edge_set.insert((i.min(nn.index), i.max(nn.index)));
+            }
+        }
+    }
+    let mut edges = edge_set
+        .into_iter()
+        .map(|(i, j)| {
+            let length = (0..3)
+                .map(|k| (points[i][k] - points[j][k]).powi(2))
+                .sum::<f64>()
+                .sqrt();
+            ((i, j), values[i].max(values[j]).max(length))
+        })
+        .collect::<Vec<_>>();
+    edges.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+
+    // the components, the younger one dying when two of them merge, so that the root of a
+    // component is its oldest point
+    let mut diagram = Vec::new();
+    let mut parent = (0..points.len()).collect::<Vec<_>>();
+    let mut creates_cycle = vec![false; edges.len()];
+    for (e, ((i, j), value)) in edges.iter().enumerate() {
+        let (a, b) = (find(&mut parent, *i), find(&mut parent, *j));
+        if a == b {
+            creates_cycle[e] = true;
+            continue;
+        }
+        let (elder, younger) = if (values[a], a) < (values[b], b) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        if *value > values[younger] {
+            diagram.push((0, values[younger], *value));
+        }
+        parent[younger] = elder;
+    }
+    let roots = (0..points.len())
+        .filter(|&i| find(&mut parent, i) == i)
+        .collect::<Vec<_>>();
+    diagram.extend(roots.iter().map(|&root| (0, values[root], f64::INFINITY)));
+
+    if max_dimension >= 1 {
+        diagram.extend(cycle_pairs(&edges, &creates_cycle));
+    }
+
+    diagram.sort_by(|a, b| {
+        a.0.cmp(&b.0)
+            .then(a.1.total_cmp(&b.1))
+            .then(a.2.total_cmp(&b.2))
+    });
+    diagram
+}
+
+/// Find the root of the component of a point, compressing the path to it.
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    let mut i = i;
+    while parent[i] != root {
+        (parent[i], i) = (root, parent[i]);
+    }
+    root
+}
+
+/// Pair the cycles created by the edges with the triangles that fill them.
+///
+/// The boundary of each triangle, as the positions of its edges in the filtration, is
+/// reduced over Z/2 by the boundaries of the earlier triangles with the same last edge. A
+/// non-zero reduced boundary kills the cycle born with its last edge.
+fn cycle_pairs(edges: &[((usize, usize), f64)], creates_cycle: &[bool]) -> Vec<(usize, f64, f64)> {
+    let position = edges
+        .iter()
+        .enumerate()
+        .map(|(e, (ij, _))| (*ij, e))
+        .collect::<HashMap<_, _>>();
+    let mut neighbors: HashMap<usize, HashSet<usize>> = HashMap::new();
+    for ((i, j), _) in edges.iter() {
+        neighbors.entry(*i).or_default().insert(*j);
+        neighbors.entry(*j).or_default().insert(*i);
+    }
+
+    // the triangles of the graph in the order of the filtration
+    let mut triangles = Vec::new();
+    for ((i, j), _) in edges.iter() {
+        for k in neighbors[i].intersection(&neighbors[j]) {
+            if k > j {
+                let mut boundary = vec![
+                    position[&(*i, *j)],
+                    position[&(*i, *k)],
+                    position[&(*j, *k)],
+                ];
+                boundary.sort_unstable();
+                let value = boundary
+                    .iter()
+                    .map(|e| edges[*e].1)
+                    .fold(f64::MIN, f64::max);
+                triangles.push((boundary, value));
+            }
+        }
+    }
+    triangles.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+
+    let mut pairs = Vec::new();
+    let mut reduced: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut killed = vec![false; edges.len()];
+    for (mut boundary, value) in triangles {
+        while let Some(&last) = boundary.last() {
+            let Some(other) = reduced.get(&last) else {
+                break;
+            };
+            boundary = symmetric_difference(&boundary, other);
+        }
+        if let Some(&last) = boundary.last() {
+            killed[last] = true;
+            if value > edges[last].1 {
+                pairs.push((1, edges[last].1, value));
+            }
+            reduced.insert(last, boundary);
+        }
+    }
+
+    for (e, (_, value)) in edges.iter().enumerate() {
+        if creates_cycle[e] && !killed[e] {
+            pairs.push((1, *value, f64::INFINITY));
+        }
+    }
+    pairs
+}
+
+/// Compute the sum over Z/2 of two sorted sets of indices.
+fn symmetric_difference(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut sum = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => {
+                sum.push(a[i]);
+                i += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                sum.push(b[j]);
+                j += 1;
+            }
+            std::cmp::Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    sum.extend_from_slice(&a[i..]);
+    sum.extend_from_slice(&b[j..]);
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::{axis_angle_to_rotation_matrix, RigidTransform3};
+    use approx::assert_relative_eq;
+
+    fn circle(num_points: usize, radius: f64) -> Vec<[f64; 3]> {
+        (0..num_points)
+            .map(|i| {
+                let angle = std::f64::consts::TAU * i as f64 / num_points as f64;
+                [radius * angle.cos(), radius * angle.sin(), 0.0]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compute_persistence_diagram_circle() -> Result<(), Box<dyn std::error::Error>> {
+        let points = circle(64, 1.0);
+        let spacing = std::f64::consts::TAU / 64.0;
+        let cloud = PointCloud::new(points.clone(), None, None);
+        let diagram = compute_persistence_diagram(&cloud, |_| 0.0, 1);
+
+        // a single component, merged from the points at the spacing of the circle
+        let components = diagram.iter().filter(|f| f.0 == 0).collect::<Vec<_>>();
+        assert_eq!(components.len(), 64);
+        assert_eq!(components.iter().filter(|f| f.2.is_infinite()).count(), 1);
+        assert!(components
+            .iter()
+            .filter(|f| f.2.is_finite())
+            .all(|f| f.2 < 1.01 * spacing));
+
+        // a single long-lived cycle, the small ones being filled as soon as they appear
+        let cycles = diagram.iter().filter(|f| f.0 == 1).collect::<Vec<_>>();
+        let long_lived = cycles
+            .iter()
+            .filter(|f| f.2 - f.1 > 0.5)
+            .collect::<Vec<_>>();
+        assert_eq!(long_lived.len(), 1);
+        assert!(long_lived[0].1 < 1.01 * spacing);
+        assert!(cycles.iter().all(|f| f.2 - f.1 < 0.1 || f.2.is_infinite()));
+
+        // the dimension 0 only
+        let components_only = compute_persistence_diagram(&cloud, |_| 0.0, 0);
+        assert!(components_only.iter().all(|f| f.0 == 0));
+        assert_eq!(components_only.len(), components.len());
+
+        // invariant to the isometries of the cloud
+        let transform = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.3, -0.5, 1.0], 0.7)?,
+            [2.0, -1.0, 5.0],
+        );
+        let moved = PointCloud::new(
+            points.iter().map(|p| transform.apply(p)).collect(),
+            None,
+            None,
+        );
+        let moved_diagram = compute_persistence_diagram(&moved, |_| 0.0, 1);
+        let persistent = |diagram: &[(usize, f64, f64)]| {
+            diagram
+                .iter()
+                .filter(|f| f.2 - f.1 > 1e-6)
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        let (expected, actual) = (persistent(&diagram), persistent(&moved_diagram));
+        assert_eq!(expected.len(), actual.len());
+        for (a, b) in expected.iter().zip(actual.iter()) {
+            assert_eq!(a.0, b.0);
+            assert_relative_eq!(a.1, b.1, epsilon = 1e-9);
+            if a.2.is_finite() {
+                assert_relative_eq!(a.2, b.2, epsilon = 1e-9);
+            } else {
+                assert!(b.2.is_infinite());
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_compute_persistence_diagram_height() {
+        // two vertical segments joined at the top, swept upwards from their bottom
+        let mut points = (0..=20)
+            .flat_map(|i| [[0.0, 0.0, i as f64 * 0.1], [1.0, 0.0, i as f64 * 0.1]])
+            .collect::<Vec<_>>();
+        points.extend((1..10).map(|i| [i as f64 * 0.1, 0.0, 2.0]));
+        let cloud = PointCloud::new(points, None, None);
+        let diagram = compute_persistence_diagram(&cloud, |p| p[2], 0);
+
+        // the second segment is born at the bottom and merges at the top
+        assert_eq!(diagram.len(), 2);
+        assert_relative_eq!(diagram[0].1, 0.0);
+        assert!(diagram[0].2.is_finite());
+        assert!(diagram[0].2 >= 2.0);
+        assert_eq!(diagram[1], (0, 0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn test_compute_persistence_diagram_empty() {
+        let cloud = PointCloud::new(vec![], None, None);
+        assert!(compute_persistence_diagram(&cloud, |_| 0.0, 1).is_empty());
+    }
+}
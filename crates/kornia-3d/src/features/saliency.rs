@@ -0,0 +1,221 @@
+use crate::{
+    kdtree::KdTree,
+    linalg::{dot_product3, eigen_symmetric33},
+};
+
+/// Compute the saliency of each point of a point cloud for registration.
+///
+/// The saliency combines three cues computed on the `k` nearest neighbors of each point:
+///
+/// * the curvature `c = 3 λ0 / (λ0 + λ1 + λ2)`, the surface variation of the neighborhood
+///   covariance scaled to `[0, 1]`,
+/// * the depth variation `v = min(1, 2 σ / r)`, the standard deviation `σ` of the offsets of
+///   the neighbors along the normal relative to the neighborhood radius `r`, which responds
+///   to steps and creases,
+/// * the relative sparsity `ρ = r / median(r)`, a point of a sparse region standing for more
+///   surface than a point of a dense one.
+///
+/// The raw saliency `(c + v) / 2 * ρ` is normalized by its maximum over the cloud. Points of
+/// flat surfaces carry little information to constrain a registration and have a saliency
+/// close to zero, while corners, edges and curved regions are salient.
+///
+/// # Arguments
+///
+/// * `points` - The points of the cloud.
+/// * `normals` - The unit normal of each point.
+/// * `k` - The number of neighbors, including the point itself.
+///
+/// # Returns
+///
+/// The saliency of each point in `[0, 1]`. All the saliencies are zero for a cloud without
+/// any salient point, or with fewer than three neighbors per point.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::compute_point_saliency;
+///
+/// // a flat patch with a step
+/// let points = (0..400)
+///     .map(|i| {
+///         let (u, v) = ((i % 20) as f64 * 0.1, (i / 20) as f64 * 0.1);
+///         [u, v, if u < 1.0 { 0.0 } else { 0.05 }]
+///     })
+///     .collect::<Vec<_>>();
+/// let normals = vec![[0.0, 0.0, 1.0]; points.len()];
+/// let saliency = compute_point_saliency(&points, &normals, 9);
+/// // a point far from the step and a point on the step
+/// assert!(saliency[5 * 20 + 3] < 1e-9);
+/// assert!(saliency[5 * 20 + 10] > 0.5);
+/// ```
+pub fn compute_point_saliency(points: &[[f64; 3]], normals: &[[f64; 3]], k: usize) -> Vec<f64> {
+    if k < 3 || points.len() < 3 {
+        return vec![0.0; points.len()];
+    }
+    let kdtree = KdTree::new(points);
+
+    // the geometric cue and the neighborhood radius of each point
+    let (geometry, radii): (Vec<_>, Vec<_>) = points
+        .iter()
+        .zip(normals.iter())
+        .map(|(point, normal)| {
+            let neighbors = kdtree.nearest_n(point, k);
+            let radius = neighbors.last().map_or(0.0, |n| n.distance);
+            if neighbors.len() < 3 || radius <= 0.0 {
+                return (0.0, radius);
+            }
+
+            let num = neighbors.len() as f64;
+            let mut centroid = [0.0; 3];
+            for n in neighbors.iter() {
+                let p = &points[n.index];
+                for i in 0..3 {
+                    centroid[i] += p[i] / num;
+                }
+            }
+            let mut covariance = [[0.0; 3]; 3];
+            let (mut sum_offsets, mut sum_sq_offsets) = (0.0, 0.0);
+            for n in neighbors.iter() {
+                let p = &points[n.index];
+                let d = [p[0] - centroid[0], p[1] - centroid[1], p[2] - centroid[2]];
+                for i in 0..3 {
+                    for j in 0..3 {
+                        covariance[i][j] += d[i] * d[j] / num;
+                    }
+                }
+                let offset =
+                    dot_product3(&[p[0] - point[0], p[1] - point[1], p[2] - point[2]], normal);
+                sum_offsets += offset;
+                sum_sq_offsets += offset * offset;
+            }
+
+            let (eigenvalues, _) = eigen_symmetric33(&covariance);
+            let trace = eigenvalues.iter().sum::<f64>();
+            let curvature = if trace > 0.0 {
+                (3.0 * eigenvalues[0].max(0.0) / trace).min(1.0)
+            } else {
+                0.0
+            };
+            let mean_offset = sum_offsets / num;
+            let std_offset = (sum_sq_offsets / num - mean_offset * mean_offset)
+                .max(0.0)
+                .sqrt();
+            let depth_variation = (2.0 * std_offset / radius).min(1.0);
+
+            (0.5 * (curvature + depth_variation), radius)
+        })
+        .unzip();
+
+    let mut sorted_radii = radii.clone();
+    sorted_radii.sort_by(|a, b| a.total_cmp(b));
+    let median_radius = sorted_radii[sorted_radii.len() / 2];
+    if median_radius <= 0.0 {
+        return vec![0.0; points.len()];
+    }
+
+    let mut saliency = geometry
+        .iter()
+        .zip(radii.iter())
+        .map(|(g, r)| g * r / median_radius)
+        .collect::<Vec<_>>();
+    let max = saliency.iter().fold(0.0f64, |acc, s| acc.max(*s));
+    if max > 0.0 {
+        saliency.iter_mut().for_each(|s| *s /= max);
+    }
+    saliency
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A flat floor with a box, and the upward normals of the floor.
+    fn floor_with_box() -> (Vec<[f64; 3]>, Vec<[f64; 3]>) {
+        let mut points = Vec::new();
+        let mut normals = Vec::new();
+        for i in 0..40 {
+            for j in 0..40 {
+                let (x, y) = (i as f64 * 0.05, j as f64 * 0.05);
+                let inside = (0.8..1.2).contains(&x) && (0.8..1.2).contains(&y);
+                points.push([x, y, if inside { 0.3 } else { 0.0 }]);
+                normals.push([0.0, 0.0, 1.0]);
+            }
+        }
+        (points, normals)
+    }
+
+    #[test]
+    fn test_point_saliency_flat_and_box() {
+        let (points, normals) = floor_with_box();
+        let saliency = compute_point_saliency(&points, &normals, 10);
+        assert_eq!(saliency.len(), points.len());
+        assert!(saliency.iter().all(|s| (0.0..=1.0).contains(s)));
+        assert!(saliency.contains(&1.0));
+
+        // the flat points far from the box and the border of the floor are not salient
+        for (p, s) in points.iter().zip(saliency.iter()) {
+            let far = (p[0] - 1.0).abs().max((p[1] - 1.0).abs()) > 0.5;
+            let inner = p[0] > 0.2 && p[0] < 1.7 && p[1] > 0.2 && p[1] < 1.7;
+            if far && inner {
+                assert!(*s < 1e-6, "{p:?} {s}");
+            }
+        }
+
+        // the points around the edges of the box are salient
+        let salient = saliency.iter().filter(|s| **s > 0.2).count();
+        assert!(salient > 20);
+        let near_box = points
+            .iter()
+            .zip(saliency.iter())
+            .filter(|(p, s)| **s > 0.2 && (p[0] - 1.0).abs().max((p[1] - 1.0).abs()) < 0.35)
+            .count();
+        assert_eq!(near_box, salient);
+    }
+
+    #[test]
+    fn test_point_saliency_sparsity() {
+        // the same sphere sampled densely on one half and sparsely on the other
+        let mut points = Vec::new();
+        let golden = std::f64::consts::PI * (3.0 - 5.0f64.sqrt());
+        let n = 2000;
+        for i in 0..n {
+            let z = 1.0 - 2.0 * (i as f64 + 0.5) / n as f64;
+            if z < 0.0 && i % 4 != 0 {
+                continue;
+            }
+            let r = (1.0 - z * z).sqrt();
+            let theta = golden * i as f64;
+            points.push([r * theta.cos(), r * theta.sin(), z]);
+        }
+        let normals = points.clone();
+        let saliency = compute_point_saliency(&points, &normals, 10);
+
+        let mean = |upper: bool| {
+            let values = points
+                .iter()
+                .zip(saliency.iter())
+                .filter(|(p, _)| (p[2] > 0.2) == upper && p[2].abs() > 0.2)
+                .map(|(_, s)| *s)
+                .collect::<Vec<_>>();
+            values.iter().sum::<f64>() / values.len() as f64
+        };
+        assert!(mean(false) > 1.5 * mean(true));
+    }
+
+    #[test]
+    fn test_point_saliency_degenerate() {
+        assert!(compute_point_saliency(&[], &[], 10).is_empty());
+        let points = vec![[0.0; 3], [1.0, 0.0, 0.0]];
+        let normals = vec![[0.0, 0.0, 1.0]; 2];
+        assert_eq!(compute_point_saliency(&points, &normals, 10), vec![0.0; 2]);
+
+        // a plane has no salient point
+        let points = (0..100)
+            .map(|i| [(i % 10) as f64, (i / 10) as f64, 0.0])
+            .collect::<Vec<_>>();
+        let normals = vec![[0.0, 0.0, 1.0]; points.len()];
+        assert!(compute_point_saliency(&points, &normals, 8)
+            .iter()
+            .all(|s| *s == 0.0));
+    }
+}
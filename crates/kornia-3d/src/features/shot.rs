@@ -0,0 +1,281 @@
+use crate::{
+    features::compute_shot_lrf,
+    kdtree::{KdTree, Neighbor},
+    linalg::dot_product3,
+    pointcloud::PointCloud,
+};
+use std::f64::consts::PI;
+
+/// Number of azimuth divisions of the SHOT support.
+const SHOT_AZIMUTH_BINS: usize = 8;
+
+/// Number of elevation divisions of the SHOT support.
+const SHOT_ELEVATION_BINS: usize = 2;
+
+/// Number of radial divisions of the SHOT support.
+const SHOT_RADIAL_BINS: usize = 2;
+
+/// Number of volumes of the SHOT support.
+pub(super) const SHOT_VOLUMES: usize = SHOT_AZIMUTH_BINS * SHOT_ELEVATION_BINS * SHOT_RADIAL_BINS;
+
+/// Number of bins of the cosine histogram of each spatial division.
+pub(super) const SHOT_COSINE_BINS: usize = 11;
+
+/// Minimum number of neighbors, besides the keypoint, to compute a descriptor.
+const SHOT_MIN_NEIGHBORS: usize = 5;
+
+/// Compute the Signature of Histograms of OrienTations (SHOT) descriptor of keypoints.
+///
+/// The spherical support of radius `radius` around each keypoint is expressed in its SHOT
+/// local reference frame and split in 32 volumes: 8 azimuth, 2 elevation and 2 radial
+/// divisions. Each volume holds an 11 bins histogram of the cosine between the normals of
+/// its points and the z-axis of the frame, as described in Tombari et al. 2010. Each point
+/// is spread by quadrilinear interpolation over the neighboring cosine bins and volumes, to
+/// be robust to the boundary effects. The descriptor is normalized to unit length.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `normals` - The unit normal of each point.
+/// * `keypoint_indices` - The indices of the points to describe.
+/// * `radius` - The radius of the support, typically about ten times the point spacing.
+///
+/// # Returns
+///
+/// The 352 bins descriptor of each keypoint. Keypoints with less than 5 neighbors or
+/// whose neighbors do not span a plane get a zero descriptor.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::compute_shot;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..121)
+///     .map(|i| {
+///         let (u, v) = ((i % 11) as f64 * 0.1 - 0.5, (i / 11) as f64 * 0.1 - 0.5);
+///         [u, v, 0.3 * u * u + 0.1 * v * v]
+///     })
+///     .collect::<Vec<_>>();
+/// let normals = vec![[0.0, 0.0, 1.0]; 121];
+/// let cloud = PointCloud::new(points, None, None);
+/// let descriptors = compute_shot(&cloud, &normals, &[60], 0.4);
+/// let norm = descriptors[0].iter().map(|x| x * x).sum::<f32>().sqrt();
+/// assert!((norm - 1.0).abs() < 1e-5);
+/// ```
+pub fn compute_shot(
+    cloud: &PointCloud,
+    normals: &[[f64; 3]],
+    keypoint_indices: &[usize],
+    radius: f64,
+) -> Vec<[f32; 352]> {
+    let points = cloud.points();
+    let kdtree = KdTree::new(points);
+
+    keypoint_indices
+        .iter()
+        .map(|&i| {
+            let Some((lrf, neighbors)) = shot_support(points, &kdtree, i, radius) else {
+                return [0.0; 352];
+            };
+
+            let mut histogram = [0.0f64; 352];
+            for n in neighbors.iter() {
+                let cosine = dot_product3(&lrf[2], &normals[n.index]).clamp(-1.0, 1.0);
+                let cosine_bins = interpolate((cosine + 1.0) / 2.0, SHOT_COSINE_BINS, false);
+                for (volume, wv) in spatial_bins(&lrf, &points[i], &points[n.index], radius) {
+                    for (c, wc) in cosine_bins {
+                        histogram[volume * SHOT_COSINE_BINS + c] += wv * wc;
+                    }
+                }
+            }
+
+            normalized(&histogram)
+        })
+        .collect()
+}
+
+/// The neighbors of a keypoint within the support radius and its SHOT local reference
+/// frame, if the keypoint has enough neighbors spanning a plane.
+pub(super) fn shot_support(
+    points: &[[f64; 3]],
+    kdtree: &KdTree,
+    keypoint: usize,
+    radius: f64,
+) -> Option<([[f64; 3]; 3], Vec<Neighbor>)> {
+    let neighbors = kdtree
+        .within_radius(&points[keypoint], radius)
+        .into_iter()
+        .filter(|n| n.distance > 0.0)
+        .collect::<Vec<_>>();
+    if neighbors.len() < SHOT_MIN_NEIGHBORS {
+        return None;
+    }
+
+    let neighbor_points = neighbors
+        .iter()
+        .map(|n| points[n.index])
+        .collect::<Vec<_>>();
+    let lrf = compute_shot_lrf(points[keypoint], &neighbor_points, radius)?;
+    Some((lrf, neighbors))
+}
+
+/// The SHOT volumes of a neighbor of a keypoint with their quadrilinear weights.
+///
+/// The neighbor is spread over the two nearest azimuth, elevation and radial divisions,
+/// with the volumes indexed by radial, then elevation, then azimuth division.
+pub(super) fn spatial_bins(
+    lrf: &[[f64; 3]; 3],
+    keypoint: &[f64; 3],
+    neighbor: &[f64; 3],
+    radius: f64,
+) -> [(usize, f64); 8] {
+    let d = [
+        neighbor[0] - keypoint[0],
+        neighbor[1] - keypoint[1],
+        neighbor[2] - keypoint[2],
+    ];
+    let distance = dot_product3(&d, &d).sqrt();
+    let local = [
+        dot_product3(&lrf[0], &d),
+        dot_product3(&lrf[1], &d),
+        dot_product3(&lrf[2], &d),
+    ];
+    let azimuth = local[1].atan2(local[0]) + PI;
+    let elevation = (local[2] / distance).clamp(-1.0, 1.0).asin();
+
+    // the two nearest bins of each dimension with their linear weights
+    let azimuth_bins = interpolate(azimuth / (2.0 * PI), SHOT_AZIMUTH_BINS, true);
+    let elevation_bins = interpolate(elevation / PI + 0.5, SHOT_ELEVATION_BINS, false);
+    let radial_bins = interpolate(distance / radius, SHOT_RADIAL_BINS, false);
+
+    let mut bins = [(0, 0.0); 8];
+    for (r, (radial, wr)) in radial_bins.into_iter().enumerate() {
+        for (e, (elevation, we)) in elevation_bins.into_iter().enumerate() {
+            for (a, (azimuth, wa)) in azimuth_bins.into_iter().enumerate() {
+                let volume =
+                    (radial * SHOT_ELEVATION_BINS + elevation) * SHOT_AZIMUTH_BINS + azimuth;
+                bins[(r * 2 + e) * 2 + a] = (volume, wr * we * wa);
+            }
+        }
+    }
+    bins
+}
+
+/// Normalize a histogram to unit length, keeping an empty histogram zero.
+pub(super) fn normalized<const N: usize>(histogram: &[f64; N]) -> [f32; N] {
+    let norm = histogram.iter().map(|h| h * h).sum::<f64>().sqrt();
+    let mut descriptor = [0.0f32; N];
+    if norm > 0.0 {
+        for (x, h) in descriptor.iter_mut().zip(histogram.iter()) {
+            *x = (h / norm) as f32;
+        }
+    }
+    descriptor
+}
+
+/// Spread a value over the two nearest of `num_bins` bins with linear weights.
+///
+/// The `value` is in `[0, 1]` and the bins are centered at `(k + 0.5) / num_bins`. With
+/// `cyclic`, the first and last bins are neighbors, otherwise the values beyond the outer
+/// bin centers fall entirely in the outer bins.
+pub(super) fn interpolate(value: f64, num_bins: usize, cyclic: bool) -> [(usize, f64); 2] {
+    let position = value * num_bins as f64 - 0.5;
+    let lower = position.floor();
+    let fraction = position - lower;
+    let lower = lower as isize;
+    let bin = |k: isize| {
+        if cyclic {
+            k.rem_euclid(num_bins as isize) as usize
+        } else {
+            k.clamp(0, num_bins as isize - 1) as usize
+        }
+    };
+    [(bin(lower), 1.0 - fraction), (bin(lower + 1), fraction)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        features::estimate_normals, linalg::transform_points3d_vec,
+        transforms::axis_angle_to_rotation_matrix,
+    };
+
+    /// A bumpy surface without symmetries.
+    fn bumpy_surface() -> Vec<[f64; 3]> {
+        (0..1600)
+            .map(|i| {
+                let (u, v) = ((i % 40) as f64 * 0.05, (i / 40) as f64 * 0.05);
+                [u, v, 0.3 * (2.0 * u).sin() * (3.0 * v).cos() + 0.1 * u * v]
+            })
+            .collect()
+    }
+
+    fn cosine_similarity(a: &[f32; 352], b: &[f32; 352]) -> f32 {
+        let dot = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f32>();
+        let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+        dot / (norm_a * norm_b)
+    }
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(interpolate(0.5, 2, false), [(0, 0.5), (1, 0.5)]);
+        assert_eq!(interpolate(0.0, 4, false), [(0, 0.5), (0, 0.5)]);
+        assert_eq!(interpolate(0.0, 4, true), [(3, 0.5), (0, 0.5)]);
+        let [(a, wa), (b, wb)] = interpolate(0.3, 11, false);
+        assert_eq!((a, b), (2, 3));
+        assert!((wa + wb - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_shot_rigid_repeatability() -> Result<(), Box<dyn std::error::Error>> {
+        let rotation = axis_angle_to_rotation_matrix(&[0.3, -1.0, 0.5], 1.2)?;
+        let translation = [2.0, -1.0, 0.5];
+
+        let points = bumpy_surface();
+        let moved_points = transform_points3d_vec(&points, &rotation, &translation);
+        let viewpoint = [1.0, 1.0, 5.0];
+        let moved_viewpoint = transform_points3d_vec(&[viewpoint], &rotation, &translation)[0];
+
+        let cloud = PointCloud::new(points, None, None);
+        let moved = PointCloud::new(moved_points, None, None);
+        // interior keypoints whose support is complete
+        let keypoints = (0..1600)
+            .filter(|i| (10..30).contains(&(i % 40)) && (10..30).contains(&(i / 40)))
+            .step_by(7)
+            .collect::<Vec<_>>();
+
+        let a = compute_shot(
+            &cloud,
+            &estimate_normals(&cloud, 0.115, &viewpoint),
+            &keypoints,
+            0.44,
+        );
+        let b = compute_shot(
+            &moved,
+            &estimate_normals(&moved, 0.115, &moved_viewpoint),
+            &keypoints,
+            0.44,
+        );
+        for (da, db) in a.iter().zip(b.iter()) {
+            assert!(cosine_similarity(da, db) > 0.99);
+        }
+
+        // the descriptors of distinct keypoints differ
+        let num_distinct = (1..a.len())
+            .filter(|&k| cosine_similarity(&a[0], &a[k]) < 0.9)
+            .count();
+        assert!(num_distinct > a.len() / 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shot_isolated_keypoint() {
+        let cloud = PointCloud::new(vec![[0.0; 3], [5.0, 0.0, 0.0]], None, None);
+        let descriptors = compute_shot(&cloud, &[[0.0, 0.0, 1.0]; 2], &[0, 1], 1.0);
+        assert_eq!(descriptors.len(), 2);
+        assert!(descriptors.iter().all(|d| d.iter().all(|x| *x == 0.0)));
+    }
+}
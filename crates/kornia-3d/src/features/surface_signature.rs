@@ -0,0 +1,235 @@
+use crate::pointcloud::PointCloud;
+
+/// Error types for the surface signature.
+#[derive(Debug, thiserror::Error)]
+pub enum SurfaceSignatureError {
+    /// The histogram has no azimuth or no elevation bin.
+    #[error("The signature needs at least one bin per angle, got {bins_azimuth} azimuth and {bins_elevation} elevation bins")]
+    InvalidBins {
+        /// The number of azimuth bins.
+        bins_azimuth: usize,
+        /// The number of elevation bins.
+        bins_elevation: usize,
+    },
+}
+
+/// Compute the surface signature of a point cloud.
+///
+/// The signature is a 2D histogram of the surface normal directions on the Gauss sphere,
+/// binned by azimuth and elevation. The histogram is stored in row-major order with the
+/// elevation as rows and the azimuth as columns, and it is normalized to sum to one.
+///
+/// A rotation of the cloud around the z-axis produces a cyclic shift of the signature along
+/// the azimuth dimension.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud with normals.
+/// * `bins_azimuth` - The number of bins for the azimuth angle in [-pi, pi).
+/// * `bins_elevation` - The number of bins for the elevation angle in [-pi/2, pi/2].
+///
+/// # Returns
+///
+/// The signature with `bins_elevation * bins_azimuth` elements, or an error if there are
+/// no azimuth or no elevation bins. If the cloud has no normals the signature is all zeros.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::compute_surface_signature;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let cloud = PointCloud::new(
+///     vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]],
+///     None,
+///     Some(vec![[0.0, 0.0, 1.0], [1.0, 0.0, 0.0]]),
+/// );
+/// let signature = compute_surface_signature(&cloud, 8, 4)?;
+/// assert_eq!(signature.len(), 32);
+/// # Ok::<(), kornia_3d::features::SurfaceSignatureError>(())
+/// ```
+pub fn compute_surface_signature(
+    cloud: &PointCloud,
+    bins_azimuth: usize,
+    bins_elevation: usize,
+) -> Result<Vec<f32>, SurfaceSignatureError> {
+    if bins_azimuth == 0 || bins_elevation == 0 {
+        return Err(SurfaceSignatureError::InvalidBins {
+            bins_azimuth,
+            bins_elevation,
+        });
+    }
+
+    let mut signature = vec![0.0f32; bins_azimuth * bins_elevation];
+
+    let Some(normals) = cloud.normals() else {
+        return Ok(signature);
+    };
+
+    let mut num_valid = 0usize;
+    for n in normals.iter() {
+        let norm = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        if norm < 1e-12 || !norm.is_finite() {
+            continue;
+        }
+
+        let azimuth = n[1].atan2(n[0]);
+        let elevation = (n[2] / norm).clamp(-1.0, 1.0).asin();
+
+        let az_bin = ((azimuth + std::f64::consts::PI) / std::f64::consts::TAU
+            * bins_azimuth as f64)
+            .floor() as usize
+            % bins_azimuth;
+        let el_bin = (((elevation + std::f64::consts::FRAC_PI_2) / std::f64::consts::PI
+            * bins_elevation as f64)
+            .floor() as usize)
+            .min(bins_elevation - 1);
+
+        signature[el_bin * bins_azimuth + az_bin] += 1.0;
+        num_valid += 1;
+    }
+
+    if num_valid > 0 {
+        signature.iter_mut().for_each(|v| *v /= num_valid as f32);
+    }
+
+    Ok(signature)
+}
+
+/// Compute the normalized cross-correlation between two surface signatures.
+///
+/// # Arguments
+///
+/// * `a` - The first signature.
+/// * `b` - The second signature.
+///
+/// # Returns
+///
+/// The correlation in [-1, 1]. A value close to one indicates similar environments.
+/// Returns zero if the signatures have different lengths or zero variance.
+pub fn surface_signature_correlation(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let n = a.len() as f64;
+    let mean_a = a.iter().map(|&v| v as f64).sum::<f64>() / n;
+    let mean_b = b.iter().map(|&v| v as f64).sum::<f64>() / n;
+
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (&va, &vb) in a.iter().zip(b.iter()) {
+        let da = va as f64 - mean_a;
+        let db = vb as f64 - mean_b;
+        cov += da * db;
+        var_a += da * da;
+        var_b += db * db;
+    }
+
+    let denom = (var_a * var_b).sqrt();
+    if denom < f64::EPSILON {
+        return 0.0;
+    }
+
+    cov / denom
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{linalg::mat33_mul_vec3, transforms::axis_angle_to_rotation_matrix};
+    use approx::assert_relative_eq;
+
+    // create normals pointing to the center of a subset of the signature bins
+    fn create_normals(bins_azimuth: usize, bins_elevation: usize) -> Vec<[f64; 3]> {
+        let mut normals = Vec::new();
+        for j in 0..bins_elevation {
+            for i in 0..bins_azimuth {
+                // add a different number of normals per bin to get a non-uniform signature
+                for _ in 0..(1 + (i * i + j) % 5) {
+                    let az = -std::f64::consts::PI
+                        + (i as f64 + 0.5) * std::f64::consts::TAU / bins_azimuth as f64;
+                    let el = -std::f64::consts::FRAC_PI_2
+                        + (j as f64 + 0.5) * std::f64::consts::PI / bins_elevation as f64;
+                    normals.push([el.cos() * az.cos(), el.cos() * az.sin(), el.sin()]);
+                }
+            }
+        }
+        normals
+    }
+
+    #[test]
+    fn test_surface_signature_normalized() -> Result<(), Box<dyn std::error::Error>> {
+        let normals = create_normals(8, 4);
+        let cloud = PointCloud::new(vec![[0.0; 3]; normals.len()], None, Some(normals));
+        let signature = compute_surface_signature(&cloud, 8, 4)?;
+        assert_eq!(signature.len(), 32);
+        assert_relative_eq!(signature.iter().sum::<f32>(), 1.0, epsilon = 1e-6);
+        assert_relative_eq!(
+            surface_signature_correlation(&signature, &signature),
+            1.0,
+            epsilon = 1e-9
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_surface_signature_rotation_shift() -> Result<(), Box<dyn std::error::Error>> {
+        let (bins_azimuth, bins_elevation) = (12, 6);
+        let normals = create_normals(bins_azimuth, bins_elevation);
+        let cloud = PointCloud::new(vec![[0.0; 3]; normals.len()], None, Some(normals.clone()));
+
+        // rotate the cloud around the z-axis by a multiple of the azimuth bin size
+        let shift = 3;
+        let angle = shift as f64 * std::f64::consts::TAU / bins_azimuth as f64;
+        let rotation = axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], angle)?;
+        let rotated_normals = normals
+            .iter()
+            .map(|n| {
+                let mut n_rot = [0.0; 3];
+                mat33_mul_vec3(&rotation, n, &mut n_rot);
+                n_rot
+            })
+            .collect::<Vec<_>>();
+        let rotated = PointCloud::new(vec![[0.0; 3]; normals.len()], None, Some(rotated_normals));
+
+        let signature = compute_surface_signature(&cloud, bins_azimuth, bins_elevation)?;
+        let signature_rot = compute_surface_signature(&rotated, bins_azimuth, bins_elevation)?;
+
+        for j in 0..bins_elevation {
+            for i in 0..bins_azimuth {
+                let shifted = j * bins_azimuth + (i + shift) % bins_azimuth;
+                assert_relative_eq!(
+                    signature[j * bins_azimuth + i],
+                    signature_rot[shifted],
+                    epsilon = 1e-6
+                );
+            }
+        }
+
+        // the unshifted signatures are less correlated than the identical ones
+        assert!(surface_signature_correlation(&signature, &signature_rot) < 1.0 - 1e-6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_surface_signature_no_normals() -> Result<(), Box<dyn std::error::Error>> {
+        let cloud = PointCloud::new(vec![[0.0; 3]; 4], None, None);
+        let signature = compute_surface_signature(&cloud, 4, 2)?;
+        assert!(signature.iter().all(|&v| v == 0.0));
+        assert_eq!(surface_signature_correlation(&signature, &signature), 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_surface_signature_invalid_bins() {
+        let normals = create_normals(4, 2);
+        let cloud = PointCloud::new(vec![[0.0; 3]; normals.len()], None, Some(normals));
+        for (bins_azimuth, bins_elevation) in [(0, 2), (4, 0), (0, 0)] {
+            assert!(matches!(
+                compute_surface_signature(&cloud, bins_azimuth, bins_elevation),
+                Err(SurfaceSignatureError::InvalidBins { .. })
+            ));
+        }
+    }
+}
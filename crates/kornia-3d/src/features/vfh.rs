@@ -0,0 +1,276 @@
+use super::fpfh::pair_features;
+use crate::linalg::dot_product3;
+use std::f64::consts::PI;
+
+/// Number of bins of each of the three angular features and of the distance feature.
+const VFH_FEATURE_BINS: usize = 45;
+
+/// Number of bins of the viewpoint component.
+const VFH_VIEWPOINT_BINS: usize = 128;
+
+/// Compute the Viewpoint Feature Histogram (VFH) descriptor of an object.
+///
+/// VFH describes a whole object seen from a viewpoint, as described in Rusu et al. 2010.
+/// The extended FPFH component pairs the centroid of the object, with the mean of the
+/// normals, with each of its points: the three angular features of the Darboux frame of
+/// each pair and its distance, relative to the largest one, are binned in 45 bins each.
+/// The viewpoint component bins in 128 bins the cosine between the direction from the
+/// centroid to the viewpoint and each normal. Each histogram sums to 100.
+///
+/// # Arguments
+///
+/// * `points` - The points of the object.
+/// * `normals` - The unit normal of each point, oriented towards the viewpoint.
+/// * `viewpoint` - The position of the sensor.
+///
+/// # Returns
+///
+/// The 308 bins descriptor: the 45 bins of each angular feature, the 45 bins of the
+/// distance and the 128 bins of the viewpoint component. An object without points gets a
+/// zero descriptor.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::compute_vfh;
+///
+/// let points = (0..100)
+///     .map(|i| {
+///         let (u, v) = ((i % 10) as f64 * 0.1, (i / 10) as f64 * 0.1);
+///         [u, v, 0.2 * u * v]
+///     })
+///     .collect::<Vec<_>>();
+/// let normals = vec![[0.0, 0.0, 1.0]; 100];
+/// let vfh = compute_vfh(&points, &normals, [0.5, 0.5, 3.0]);
+/// assert!((vfh[180..].iter().sum::<f32>() - 100.0).abs() < 1e-3);
+/// ```
+pub fn compute_vfh(points: &[[f64; 3]], normals: &[[f64; 3]], viewpoint: [f64; 3]) -> [f32; 308] {
+    let mut descriptor = [0.0f32; 308];
+    if points.is_empty() {
+        return descriptor;
+    }
+
+    let num_points = points.len() as f64;
+    let mut centroid = [0.0; 3];
+    let mut centroid_normal = [0.0; 3];
+    for (p, n) in points.iter().zip(normals.iter()) {
+        for k in 0..3 {
+            centroid[k] += p[k] / num_points;
+            centroid_normal[k] += n[k];
+        }
+    }
+    let norm = dot_product3(&centroid_normal, &centroid_normal).sqrt();
+    if norm > 0.0 {
+        centroid_normal = centroid_normal.map(|x| x / norm);
+    }
+
+    let max_distance = points
+        .iter()
+        .map(|p| distance(p, &centroid))
+        .fold(0.0, f64::max);
+
+    // extended FPFH component
+    let increment = 100.0 / num_points;
+    let mut histogram = [0.0f64; 308];
+    for (p, n) in points.iter().zip(normals.iter()) {
+        let Some([alpha, phi, theta]) = pair_features(&centroid, &centroid_normal, p, n) else {
+            continue;
+        };
+        let d = if max_distance > 0.0 {
+            distance(p, &centroid) / max_distance
+        } else {
+            0.0
+        };
+        histogram[vfh_bin(alpha, -1.0, 1.0, VFH_FEATURE_BINS)] += increment;
+        histogram[VFH_FEATURE_BINS + vfh_bin(phi, -1.0, 1.0, VFH_FEATURE_BINS)] += increment;
+        histogram[2 * VFH_FEATURE_BINS + vfh_bin(theta, -PI, PI, VFH_FEATURE_BINS)] += increment;
+        histogram[3 * VFH_FEATURE_BINS + vfh_bin(d, 0.0, 1.0, VFH_FEATURE_BINS)] += increment;
+    }
+
+    // viewpoint component
+    let direction = [
+        viewpoint[0] - centroid[0],
+        viewpoint[1] - centroid[1],
+        viewpoint[2] - centroid[2],
+    ];
+    let direction_norm = dot_product3(&direction, &direction).sqrt();
+    if direction_norm > 0.0 {
+        let direction = direction.map(|x| x / direction_norm);
+        for n in normals.iter().take(points.len()) {
+            let cosine = dot_product3(&direction, n);
+            histogram[4 * VFH_FEATURE_BINS + vfh_bin(cosine, -1.0, 1.0, VFH_VIEWPOINT_BINS)] +=
+                increment;
+        }
+    }
+
+    for (x, h) in descriptor.iter_mut().zip(histogram.iter()) {
+        *x = *h as f32;
+    }
+    descriptor
+}
+
+/// Find the nearest VFH descriptors of a set of descriptors in another set.
+///
+/// # Arguments
+///
+/// * `src` - The query descriptors, e.g. of the objects of a scene.
+/// * `dst` - The descriptors to search, e.g. of the views of the known objects.
+/// * `k` - The number of nearest descriptors of each query.
+///
+/// # Returns
+///
+/// For each query, its index, the index of one of its `k` nearest descriptors and their
+/// Euclidean distance, sorted by query and then by increasing distance.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::features::match_vfh_descriptors;
+///
+/// let mut a = [0.0f32; 308];
+/// let mut b = [0.0f32; 308];
+/// a[0] = 100.0;
+/// b[1] = 100.0;
+/// let matches = match_vfh_descriptors(&[b], &[a, b], 1);
+/// assert_eq!(matches, vec![(0, 1, 0.0)]);
+/// ```
+pub fn match_vfh_descriptors(
+    src: &[[f32; 308]],
+    dst: &[[f32; 308]],
+    k: usize,
+) -> Vec<(usize, usize, f64)> {
+    src.iter()
+        .enumerate()
+        .flat_map(|(i, query)| {
+            let mut candidates = dst
+                .iter()
+                .enumerate()
+                .map(|(j, candidate)| {
+                    let sq_distance = query
+                        .iter()
+                        .zip(candidate.iter())
+                        .map(|(a, b)| ((a - b) as f64).powi(2))
+                        .sum::<f64>();
+                    (i, j, sq_distance.sqrt())
+                })
+                .collect::<Vec<_>>();
+            candidates.sort_by(|a, b| a.2.total_cmp(&b.2).then(a.1.cmp(&b.1)));
+            candidates.truncate(k);
+            candidates
+        })
+        .collect()
+}
+
+fn distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+/// Bin of a value in the range `[min, max]` split in `num_bins` bins.
+fn vfh_bin(value: f64, min: f64, max: f64, num_bins: usize) -> usize {
+    let bin = ((value - min) / (max - min) * num_bins as f64).floor() as isize;
+    bin.clamp(0, num_bins as isize - 1) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        features::estimate_normals, linalg::transform_points3d_vec, pointcloud::PointCloud,
+        transforms::axis_angle_to_rotation_matrix,
+    };
+
+    /// Sample the visible part of an ellipsoid with semi-axes `radii` seen from above.
+    fn ellipsoid(radii: [f64; 3]) -> Vec<[f64; 3]> {
+        let num_points = 2000;
+        let golden = PI * (3.0 - 5.0f64.sqrt());
+        (0..num_points)
+            .map(|i| {
+                let z = 1.0 - (i as f64 + 0.5) / num_points as f64;
+                let r = (1.0 - z * z).sqrt();
+                let theta = golden * i as f64;
+                [
+                    radii[0] * r * theta.cos(),
+                    radii[1] * r * theta.sin(),
+                    radii[2] * z,
+                ]
+            })
+            .collect()
+    }
+
+    fn vfh_of(points: &[[f64; 3]], viewpoint: [f64; 3]) -> [f32; 308] {
+        let cloud = PointCloud::new(points.to_vec(), None, None);
+        let normals = estimate_normals(&cloud, 0.15, &viewpoint);
+        compute_vfh(points, &normals, viewpoint)
+    }
+
+    #[test]
+    fn test_vfh_rigid_invariance() -> Result<(), Box<dyn std::error::Error>> {
+        let points = ellipsoid([1.0, 0.7, 0.5]);
+        let viewpoint = [0.2, 0.1, 4.0];
+        let a = vfh_of(&points, viewpoint);
+
+        let rotation = axis_angle_to_rotation_matrix(&[1.0, -0.5, 0.3], 0.8)?;
+        let translation = [3.0, -2.0, 1.0];
+        let moved = transform_points3d_vec(&points, &rotation, &translation);
+        let moved_viewpoint = transform_points3d_vec(&[viewpoint], &rotation, &translation)[0];
+        let b = vfh_of(&moved, moved_viewpoint);
+
+        for k in 0..4 {
+            let sum = a[k * 45..(k + 1) * 45].iter().sum::<f32>();
+            assert!((sum - 100.0).abs() < 1e-3);
+        }
+        let difference = a
+            .iter()
+            .zip(b.iter())
+            .map(|(x, y)| (x - y).abs())
+            .sum::<f32>();
+        assert!(difference < 1.0, "{difference}");
+
+        // the viewpoint component depends on the viewpoint
+        let c = vfh_of(&points, [4.0, 0.0, 0.5]);
+        let difference = a[180..]
+            .iter()
+            .zip(c[180..].iter())
+            .map(|(x, y)| (x - y).abs())
+            .sum::<f32>();
+        assert!(difference > 50.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_match_vfh_descriptors() -> Result<(), Box<dyn std::error::Error>> {
+        let shapes = [
+            [1.0, 0.7, 0.5],
+            [1.0, 1.0, 1.0],
+            [1.5, 0.5, 0.3],
+            [0.8, 0.8, 0.2],
+        ];
+        let viewpoint = [0.0, 0.0, 4.0];
+        let models = shapes
+            .iter()
+            .map(|radii| vfh_of(&ellipsoid(*radii), viewpoint))
+            .collect::<Vec<_>>();
+
+        // the same objects moved rigidly with the sensor
+        let rotation = axis_angle_to_rotation_matrix(&[0.0, 1.0, 1.0], 2.0)?;
+        let translation = [1.0, 2.0, 3.0];
+        let moved_viewpoint = transform_points3d_vec(&[viewpoint], &rotation, &translation)[0];
+        let queries = shapes
+            .iter()
+            .rev()
+            .map(|radii| {
+                let points = transform_points3d_vec(&ellipsoid(*radii), &rotation, &translation);
+                vfh_of(&points, moved_viewpoint)
+            })
+            .collect::<Vec<_>>();
+
+        let matches = match_vfh_descriptors(&queries, &models, 2);
+        assert_eq!(matches.len(), 8);
+        for (q, pair) in matches.chunks(2).enumerate() {
+            assert_eq!(pair[0].0, q);
+            assert_eq!(pair[0].1, shapes.len() - 1 - q);
+            assert!(pair[0].2 < pair[1].2);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use crate::pointcloud::PointCloud;
+
+/// Policy to select the attributes of the point kept for each group of duplicates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupPolicy {
+    /// Keep the point with the lowest index in the group, with its color and normal.
+    First,
+    /// Replace the group by its centroid, with the mean color and the renormalized mean normal.
+    Centroid,
+}
+
+/// Remove duplicated points from a point cloud.
+///
+/// The points are hashed into a grid of cell size `tolerance` and all the points falling
+/// in the same cell are collapsed into a single point according to the `policy`. A tolerance
+/// of zero only merges points with bitwise identical coordinates.
+///
+/// The output is independent of the order of the input points: the groups are emitted
+/// sorted by their grid cell.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `tolerance` - The grid cell size in the units of the points.
+/// * `policy` - How to build the point kept for each group of duplicates.
+///
+/// # Returns
+///
+/// The deduplicated point cloud and, for each input point, the index of the output point
+/// it was merged into.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::filters::{deduplicate, DedupPolicy};
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let cloud = PointCloud::new(
+///     vec![[1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [1.0, 0.0, 0.0]],
+///     None,
+///     None,
+/// );
+/// let (dedup, mapping) = deduplicate(&cloud, 0.0, DedupPolicy::First);
+/// assert_eq!(dedup.len(), 2);
+/// assert_eq!(mapping, vec![1, 0, 1]);
+/// ```
+pub fn deduplicate(
+    cloud: &PointCloud,
+    tolerance: f64,
+    policy: DedupPolicy,
+) -> (PointCloud, Vec<usize>) {
+    let cell_key = |p: &[f64; 3]| -> [i64; 3] {
+        if tolerance > 0.0 {
+            [
+                (p[0] / tolerance).floor() as i64,
+                (p[1] / tolerance).floor() as i64,
+                (p[2] / tolerance).floor() as i64,
+            ]
+        } else {
+            [
+                p[0].to_bits() as i64,
+                p[1].to_bits() as i64,
+                p[2].to_bits() as i64,
+            ]
+        }
+    };
+
+    let mut cells: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+    for (i, p) in cloud.points().iter().enumerate() {
+        cells.entry(cell_key(p)).or_default().push(i);
+    }
+
+    let mut groups = cells.into_iter().collect::<Vec<_>>();
+    groups.sort_unstable_by_key(|(key, _)| *key);
+
+    let mut points = Vec::with_capacity(groups.len());
+    let mut colors = cloud.colors().map(|_| Vec::with_capacity(groups.len()));
+    let mut normals = cloud.normals().map(|_| Vec::with_capacity(groups.len()));
+    let mut mapping = vec![0; cloud.len()];
+
+    for (out_idx, (_, indices)) in groups.iter().enumerate() {
+        indices.iter().for_each(|&i| mapping[i] = out_idx);
+
+        match policy {
+            DedupPolicy::First => {
+                let first = indices[0];
+                points.push(cloud.points()[first]);
+                if let (Some(out), Some(src)) = (colors.as_mut(), cloud.colors()) {
+                    out.push(src[first]);
+                }
+                if let (Some(out), Some(src)) = (normals.as_mut(), cloud.normals()) {
+                    out.push(src[first]);
+                }
+            }
+            DedupPolicy::Centroid => {
+                points.push(mean_of(indices, cloud.points()));
+                if let (Some(out), Some(src)) = (colors.as_mut(), cloud.colors()) {
+                    let mut sum = [0.0f64; 3];
+                    for &i in indices {
+                        for k in 0..3 {
+                            sum[k] += src[i][k] as f64;
+                        }
+                    }
+                    let n = indices.len() as f64;
+                    out.push([
+                        (sum[0] / n).round() as u8,
+                        (sum[1] / n).round() as u8,
+                        (sum[2] / n).round() as u8,
+                    ]);
+                }
+                if let (Some(out), Some(src)) = (normals.as_mut(), cloud.normals()) {
+                    let n = mean_of(indices, src);
+                    let norm = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+                    out.push(if norm > 1e-12 {
+                        [n[0] / norm, n[1] / norm, n[2] / norm]
+                    } else {
+                        n
+                    });
+                }
+            }
+        }
+    }
+
+    (PointCloud::new(points, colors, normals), mapping)
+}
+
+fn mean_of(indices: &[usize], values: &[[f64; 3]]) -> [f64; 3] {
+    let mut sum = [0.0; 3];
+    for &i in indices {
+        for k in 0..3 {
+            sum[k] += values[i][k];
+        }
+    }
+    let n = indices.len() as f64;
+    [sum[0] / n, sum[1] / n, sum[2] / n]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_deduplicate_exact() {
+        let points = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 2.0, 3.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 2.0, 3.0],
+            [1.0, 2.0, 3.0 + 1e-12],
+        ];
+        let cloud = PointCloud::new(points, None, None);
+        let (dedup, mapping) = deduplicate(&cloud, 0.0, DedupPolicy::First);
+
+        assert_eq!(dedup.len(), 3);
+        assert_eq!(mapping[0], mapping[2]);
+        assert_eq!(mapping[1], mapping[3]);
+        assert_ne!(mapping[3], mapping[4]);
+    }
+
+    #[test]
+    fn test_deduplicate_tolerance_jittered() {
+        let base = [[0.25, 0.25, 0.25], [5.25, 0.25, 0.25], [0.25, 5.25, 0.25]];
+        let jitter = [[0.0, 0.0, 0.0], [0.01, -0.02, 0.0], [-0.01, 0.0, 0.02]];
+
+        let mut points = Vec::new();
+        for b in base.iter() {
+            for j in jitter.iter() {
+                points.push([b[0] + j[0], b[1] + j[1], b[2] + j[2]]);
+            }
+        }
+        // the output does not depend on the input order
+        let mut reversed = points.clone();
+        reversed.reverse();
+
+        let (dedup, mapping) = deduplicate(
+            &PointCloud::new(points, None, None),
+            0.1,
+            DedupPolicy::Centroid,
+        );
+        let (dedup_rev, _) = deduplicate(
+            &PointCloud::new(reversed, None, None),
+            0.1,
+            DedupPolicy::Centroid,
+        );
+
+        assert_eq!(dedup.len(), 3);
+        assert_eq!(mapping, vec![0, 0, 0, 2, 2, 2, 1, 1, 1]);
+        for (a, b) in dedup.points().iter().zip(dedup_rev.points().iter()) {
+            for k in 0..3 {
+                assert_relative_eq!(a[k], b[k], epsilon = 1e-12);
+            }
+        }
+        assert_relative_eq!(dedup.points()[0][0], 0.25, epsilon = 1e-12);
+        assert_relative_eq!(dedup.points()[0][2], 0.25 + 0.02 / 3.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_deduplicate_attribute_policy() {
+        let cloud = PointCloud::new(
+            vec![[0.0, 0.0, 0.0], [0.0, 0.0, 0.0]],
+            Some(vec![[10, 20, 30], [20, 40, 61]]),
+            Some(vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]),
+        );
+
+        let (first, _) = deduplicate(&cloud, 0.0, DedupPolicy::First);
+        assert_eq!(first.colors().unwrap(), &vec![[10, 20, 30]]);
+        assert_eq!(first.normals().unwrap(), &vec![[1.0, 0.0, 0.0]]);
+
+        let (centroid, _) = deduplicate(&cloud, 0.0, DedupPolicy::Centroid);
+        assert_eq!(centroid.colors().unwrap(), &vec![[15, 30, 46]]);
+        let n = centroid.normals().unwrap()[0];
+        assert_relative_eq!(n[0], std::f64::consts::FRAC_1_SQRT_2, epsilon = 1e-12);
+        assert_relative_eq!(n[1], std::f64::consts::FRAC_1_SQRT_2, epsilon = 1e-12);
+    }
+}
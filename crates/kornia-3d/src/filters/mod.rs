@@ -0,0 +1,5 @@
+mod deduplicate;
+pub use deduplicate::*;
+
+mod sampling;
+pub use sampling::*;
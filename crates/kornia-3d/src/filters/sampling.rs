@@ -0,0 +1,125 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Seed of the choice of the first point of the farthest point sampling, fixed for
+/// reproducibility.
+const FPS_SEED: u64 = 0;
+
+/// Select evenly spread points by farthest point sampling.
+///
+/// Starting from a random point, the point farthest from the already selected points is
+/// selected greedily until `n` points are selected. The selected points cover the cloud much
+/// more uniformly than a random sample, whatever its density, at a cost of `O(n * N)` for
+/// `N` points.
+///
+/// # Arguments
+///
+/// * `points` - The points to sample.
+/// * `n` - The number of points to select.
+///
+/// # Returns
+///
+/// The indices of the selected points, in the order of selection. All the points are
+/// selected if there are at most `n` of them.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::filters::farthest_point_sample;
+///
+/// // a dense cluster around the origin and two isolated points
+/// let mut points = (0..100)
+///     .map(|i| [(i % 10) as f64 * 0.01, (i / 10) as f64 * 0.01, 0.0])
+///     .collect::<Vec<_>>();
+/// points.push([5.0, 0.0, 0.0]);
+/// points.push([0.0, 5.0, 0.0]);
+///
+/// let selected = farthest_point_sample(&points, 3);
+/// assert_eq!(selected.len(), 3);
+/// assert!(selected.contains(&100) && selected.contains(&101));
+/// ```
+pub fn farthest_point_sample(points: &[[f64; 3]], n: usize) -> Vec<usize> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+
+    let num_samples = n.min(points.len());
+    let mut selected = Vec::with_capacity(num_samples);
+    let mut sq_distances = vec![f64::INFINITY; points.len()];
+    let mut next = StdRng::seed_from_u64(FPS_SEED).random_range(0..points.len());
+    while selected.len() < num_samples {
+        selected.push(next);
+        let q = points[next];
+        for (d, p) in sq_distances.iter_mut().zip(points.iter()) {
+            *d = d.min((0..3).map(|a| (p[a] - q[a]).powi(2)).sum::<f64>());
+        }
+        next = sq_distances
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or(0, |(i, _)| i);
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn min_pairwise_distance(points: &[[f64; 3]], indices: &[usize]) -> f64 {
+        let mut min_distance = f64::INFINITY;
+        for (k, &i) in indices.iter().enumerate() {
+            for &j in indices[k + 1..].iter() {
+                let d = (0..3)
+                    .map(|a| (points[i][a] - points[j][a]).powi(2))
+                    .sum::<f64>()
+                    .sqrt();
+                min_distance = min_distance.min(d);
+            }
+        }
+        min_distance
+    }
+
+    #[test]
+    fn test_farthest_point_sample_coverage() {
+        // a cloud ten times denser on one half of the square
+        let mut rng = StdRng::seed_from_u64(1);
+        let points = (0..5500)
+            .map(|i| {
+                let x = if i < 5000 {
+                    rng.random_range(0.0..0.5)
+                } else {
+                    rng.random_range(0.5..1.0)
+                };
+                [x, rng.random_range(0.0..1.0), 0.0]
+            })
+            .collect::<Vec<_>>();
+
+        let selected = farthest_point_sample(&points, 100);
+        assert_eq!(selected.len(), 100);
+        let mut unique = selected.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), 100);
+
+        // the samples are spread over the square: 100 points on a regular grid are 0.1 apart
+        let fps_distance = min_pairwise_distance(&points, &selected);
+        assert!(fps_distance > 0.07);
+        for seed in 0..10 {
+            let random = rand::seq::index::sample(&mut StdRng::seed_from_u64(seed), 5500, 100);
+            assert!(min_pairwise_distance(&points, &random.into_vec()) < 0.5 * fps_distance);
+        }
+
+        // both halves are covered alike
+        let dense_half = selected.iter().filter(|&&i| points[i][0] < 0.5).count();
+        assert!((35..=65).contains(&dense_half));
+    }
+
+    #[test]
+    fn test_farthest_point_sample_all_points() {
+        let points = [[0.0; 3], [1.0, 0.0, 0.0], [0.0, 2.0, 0.0]];
+        let mut selected = farthest_point_sample(&points, 10);
+        selected.sort();
+        assert_eq!(selected, vec![0, 1, 2]);
+        assert!(farthest_point_sample(&[], 10).is_empty());
+    }
+}
@@ -0,0 +1,434 @@
+use super::FittingError;
+use crate::linalg::{det_mat33, eigen_symmetric, eigen_symmetric33};
+
+/// An ellipsoid defined by its center, semi-axes lengths and orientation.
+///
+/// The surface points are `center + rotation * (radii ⊙ u)` for the unit vectors `u`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ellipsoid {
+    /// The center of the ellipsoid.
+    pub center: [f64; 3],
+    /// The lengths of the semi-axes in descending order.
+    pub radii: [f64; 3],
+    /// The rotation matrix whose columns are the directions of the semi-axes.
+    pub rotation: [[f64; 3]; 3],
+}
+
+impl Ellipsoid {
+    /// Compute the closest point to a point on the surface of the ellipsoid.
+    ///
+    /// The point is expressed in the frame of the semi-axes, where the closest point is the
+    /// root of a monotonic function found by bisection as described by Eberly. The points on
+    /// a plane of symmetry are handled by reducing the problem to the ellipse in the plane.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to project, inside or outside of the ellipsoid.
+    ///
+    /// # Returns
+    ///
+    /// The closest point on the surface.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_3d::fitting::Ellipsoid;
+    ///
+    /// let ellipsoid = Ellipsoid {
+    ///     center: [1.0, 0.0, 0.0],
+    ///     radii: [3.0, 2.0, 1.0],
+    ///     rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    /// };
+    /// let closest = ellipsoid.closest_point(&[1.0, 0.0, 5.0]);
+    /// assert!((closest[2] - 1.0).abs() < 1e-12);
+    /// ```
+    pub fn closest_point(&self, point: &[f64; 3]) -> [f64; 3] {
+        // the point in the frame of the semi-axes, sorted by decreasing length
+        let d = [0, 1, 2].map(|k| point[k] - self.center[k]);
+        let local = [0, 1, 2].map(|c| (0..3).map(|r| self.rotation[r][c] * d[r]).sum::<f64>());
+        let mut order = [0, 1, 2];
+        order.sort_by(|&a, &b| self.radii[b].total_cmp(&self.radii[a]));
+        let radii = order.map(|k| self.radii[k]);
+        let y = order.map(|k| local[k].abs());
+
+        // by symmetry the closest point is in the same octant as the point
+        let mut x = [0.0; 3];
+        closest_point_axis_aligned(&radii, &y, &mut x);
+        let mut closest_local = [0.0; 3];
+        for (i, &k) in order.iter().enumerate() {
+            closest_local[k] = x[i].copysign(local[k]);
+        }
+
+        [0, 1, 2].map(|r| {
+            self.center[r]
+                + (0..3)
+                    .map(|c| self.rotation[r][c] * closest_local[c])
+                    .sum::<f64>()
+        })
+    }
+}
+
+/// Fit an ellipsoid to a set of points with algebraic least squares.
+///
+/// The points are fitted with the general quadric
+/// `a x² + b y² + c z² + d xy + e xz + f yz + g x + h y + i z + j = 0`,
+/// whose 10 coefficients are the eigenvector with the smallest eigenvalue of the normal
+/// equations of the design matrix. The center, semi-axes and orientation are then
+/// extracted from the quadric. The points are normalized beforehand to keep the normal
+/// equations well conditioned.
+///
+/// # Arguments
+///
+/// * `points` - The points sampled on the surface of the ellipsoid.
+///
+/// # Returns
+///
+/// The fitted ellipsoid, or [`FittingError::NotEllipsoid`] if the fitted quadric is
+/// another kind of surface, e.g. a hyperboloid.
+pub fn fit_ellipsoid(points: &[[f64; 3]]) -> Result<Ellipsoid, FittingError> {
+    if points.len() < 9 {
+        return Err(FittingError::NotEnoughPoints(points.len()));
+    }
+
+    // normalize the points to zero mean and unit rms distance
+    let n = points.len() as f64;
+    let mut mean = [0.0; 3];
+    for p in points {
+        for k in 0..3 {
+            mean[k] += p[k] / n;
+        }
+    }
+    let rms = (points
+        .iter()
+        .map(|p| (p[0] - mean[0]).powi(2) + (p[1] - mean[1]).powi(2) + (p[2] - mean[2]).powi(2))
+        .sum::<f64>()
+        / n)
+        .sqrt();
+    if rms < 1e-12 || !rms.is_finite() {
+        return Err(FittingError::Degenerate);
+    }
+
+    // accumulate the normal equations of the design matrix
+    let mut normal_eq = [[0.0; 10]; 10];
+    for p in points {
+        let (x, y, z) = (
+            (p[0] - mean[0]) / rms,
+            (p[1] - mean[1]) / rms,
+            (p[2] - mean[2]) / rms,
+        );
+        let row = [x * x, y * y, z * z, x * y, x * z, y * z, x, y, z, 1.0];
+        for (i, normal_row) in normal_eq.iter_mut().enumerate() {
+            for (j, value) in normal_row.iter_mut().enumerate().skip(i) {
+                *value += row[i] * row[j];
+            }
+        }
+    }
+
+    let (_, eigenvectors) = eigen_symmetric(&normal_eq);
+    let [a, b, c, d, e, f, g, h, i, j] = eigenvectors[0];
+
+    // quadric in matrix form: x^T A x + q^T x + j = 0
+    let quad = [
+        [a, d / 2.0, e / 2.0],
+        [d / 2.0, b, f / 2.0],
+        [e / 2.0, f / 2.0, c],
+    ];
+    let q = [g, h, i];
+
+    let det = det_mat33(&quad);
+    if det.abs() < 1e-12 {
+        return Err(FittingError::NotEllipsoid);
+    }
+
+    // the center solves 2 A c = -q
+    let inv = inverse33(&quad, det);
+    let center_n = [
+        -0.5 * (inv[0][0] * q[0] + inv[0][1] * q[1] + inv[0][2] * q[2]),
+        -0.5 * (inv[1][0] * q[0] + inv[1][1] * q[1] + inv[1][2] * q[2]),
+        -0.5 * (inv[2][0] * q[0] + inv[2][1] * q[1] + inv[2][2] * q[2]),
+    ];
+
+    // translated to the center the quadric is (x - c)^T A (x - c) = c^T A c - j
+    let mut k = -j;
+    for r in 0..3 {
+        for s in 0..3 {
+            k += center_n[r] * quad[r][s] * center_n[s];
+        }
+    }
+
+    let (eigenvalues, axes) = eigen_symmetric33(&quad);
+    let all_same_sign = eigenvalues.iter().all(|l| l * k > 0.0);
+    if !all_same_sign {
+        return Err(FittingError::NotEllipsoid);
+    }
+
+    // the smallest eigenvalue corresponds to the largest semi-axis
+    let radii = [
+        (k / eigenvalues[0]).sqrt() * rms,
+        (k / eigenvalues[1]).sqrt() * rms,
+        (k / eigenvalues[2]).sqrt() * rms,
+    ];
+
+    let mut rotation = [[0.0; 3]; 3];
+    for (col, axis) in axes.iter().enumerate() {
+        for row in 0..3 {
+            rotation[row][col] = axis[row];
+        }
+    }
+    if det_mat33(&rotation) < 0.0 {
+        for row in rotation.iter_mut() {
+            row[2] = -row[2];
+        }
+    }
+
+    Ok(Ellipsoid {
+        center: [
+            center_n[0] * rms + mean[0],
+            center_n[1] * rms + mean[1],
+            center_n[2] * rms + mean[2],
+        ],
+        radii,
+        rotation,
+    })
+}
+
+/// Closest point on the axis-aligned ellipsoid of any dimension with radii in decreasing
+/// order to a point with non-negative coordinates.
+fn closest_point_axis_aligned(radii: &[f64], y: &[f64], x: &mut [f64]) {
+    let last = radii.len() - 1;
+    let sq_radii = radii.iter().map(|a| a * a).collect::<Vec<_>>();
+
+    // a point numerically on the plane of symmetry is considered on it
+    if y[last] > 1e-10 * radii[last] {
+        // the root of F(t) = Σ (a_i y_i / (t + a_i²))² - 1, decreasing for t > -a_n²
+        let f = |t: f64| {
+            (0..radii.len())
+                .map(|i| (radii[i] * y[i] / (t + sq_radii[i])).powi(2))
+                .sum::<f64>()
+                - 1.0
+        };
+        let norm = (0..radii.len())
+            .map(|i| (radii[i] * y[i]).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        let (mut lo, mut hi) = (
+            -sq_radii[last] + radii[last] * y[last],
+            -sq_radii[last] + norm,
+        );
+        for _ in 0..200 {
+            let mid = 0.5 * (lo + hi);
+            if mid <= lo || mid >= hi {
+                break;
+            }
+            if f(mid) > 0.0 {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let t = 0.5 * (lo + hi);
+        for i in 0..radii.len() {
+            x[i] = sq_radii[i] * y[i] / (t + sq_radii[i]);
+        }
+        return;
+    }
+
+    if last == 0 {
+        x[0] = radii[0];
+        return;
+    }
+
+    // on the plane of symmetry the closest point is off the plane if the point is close
+    // enough to the center, otherwise it is on the ellipse in the plane
+    let mut inside = true;
+    let mut sum = 0.0;
+    for i in 0..last {
+        let denom = sq_radii[i] - sq_radii[last];
+        if denom <= 0.0 {
+            inside = false;
+            break;
+        }
+        x[i] = sq_radii[i] * y[i] / denom;
+        sum += (x[i] / radii[i]).powi(2);
+    }
+    if inside && sum < 1.0 {
+        x[last] = radii[last] * (1.0 - sum).sqrt();
+    } else {
+        x[last] = 0.0;
+        closest_point_axis_aligned(&radii[..last], &y[..last], &mut x[..last]);
+    }
+}
+
+/// Invert a 3x3 matrix given its determinant with the adjugate formula.
+fn inverse33(m: &[[f64; 3]; 3], det: f64) -> [[f64; 3]; 3] {
+    let mut inv = [[0.0; 3]; 3];
+    for (r, row) in inv.iter_mut().enumerate() {
+        for (c, value) in row.iter_mut().enumerate() {
+            let (r1, r2) = ((c + 1) % 3, (c + 2) % 3);
+            let (c1, c2) = ((r + 1) % 3, (r + 2) % 3);
+            *value = (m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1]) / det;
+        }
+    }
+    inv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::matmul33;
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn rotation_zx(yaw: f64, roll: f64) -> [[f64; 3]; 3] {
+        let (sz, cz) = yaw.sin_cos();
+        let (sx, cx) = roll.sin_cos();
+        let rz = [[cz, -sz, 0.0], [sz, cz, 0.0], [0.0, 0.0, 1.0]];
+        let rx = [[1.0, 0.0, 0.0], [0.0, cx, -sx], [0.0, sx, cx]];
+        let mut r = [[0.0; 3]; 3];
+        matmul33(&rz, &rx, &mut r);
+        r
+    }
+
+    fn random_unit(rng: &mut StdRng) -> [f64; 3] {
+        loop {
+            let v: [f64; 3] = [
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+            ];
+            let norm = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+            if norm > 1e-3 && norm <= 1.0 {
+                return [v[0] / norm, v[1] / norm, v[2] / norm];
+            }
+        }
+    }
+
+    #[test]
+    fn test_fit_ellipsoid() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(9);
+        let center = [1.0, -2.0, 0.5];
+        let radii = [3.0, 2.0, 1.0];
+        let rotation = rotation_zx(0.5, 0.3);
+
+        let points = (0..2000)
+            .map(|_| {
+                let u = random_unit(&mut rng);
+                let local = [radii[0] * u[0], radii[1] * u[1], radii[2] * u[2]];
+                let mut p = center;
+                for r in 0..3 {
+                    for c in 0..3 {
+                        p[r] += rotation[r][c] * local[c];
+                    }
+                    p[r] += rng.random_range(-0.005..0.005);
+                }
+                p
+            })
+            .collect::<Vec<_>>();
+
+        let ellipsoid = fit_ellipsoid(&points)?;
+        for k in 0..3 {
+            assert_relative_eq!(ellipsoid.center[k], center[k], epsilon = 1e-2);
+            assert_relative_eq!(ellipsoid.radii[k], radii[k], epsilon = 1e-2);
+            // the axes match up to their sign
+            let cos = (0..3)
+                .map(|r| ellipsoid.rotation[r][k] * rotation[r][k])
+                .sum::<f64>();
+            assert_relative_eq!(cos.abs(), 1.0, epsilon = 1e-3);
+        }
+        assert_relative_eq!(det_mat33(&ellipsoid.rotation), 1.0, epsilon = 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ellipsoid_closest_point() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let ellipsoid = Ellipsoid {
+            center: [1.0, -2.0, 0.5],
+            radii: [3.0, 2.0, 1.0],
+            rotation: rotation_zx(0.5, 0.3),
+        };
+        let to_local = |p: &[f64; 3]| {
+            let d = [0, 1, 2].map(|k| p[k] - ellipsoid.center[k]);
+            [0, 1, 2].map(|c| (0..3).map(|r| ellipsoid.rotation[r][c] * d[r]).sum::<f64>())
+        };
+
+        for _ in 0..200 {
+            let point = [0, 1, 2].map(|k| ellipsoid.center[k] + rng.random_range(-5.0..5.0));
+            let closest = ellipsoid.closest_point(&point);
+
+            // the closest point is on the surface
+            let x = to_local(&closest);
+            let level = (0..3)
+                .map(|k| (x[k] / ellipsoid.radii[k]).powi(2))
+                .sum::<f64>();
+            assert_relative_eq!(level, 1.0, epsilon = 1e-9);
+
+            // and the point is along the normal of the surface
+            let y = to_local(&point);
+            let gradient = [0, 1, 2].map(|k| x[k] / ellipsoid.radii[k].powi(2));
+            let offset = [0, 1, 2].map(|k| y[k] - x[k]);
+            let mut cross = [0.0; 3];
+            crate::linalg::cross_vec3(&gradient, &offset, &mut cross);
+            let norm = (0..3).map(|k| cross[k] * cross[k]).sum::<f64>().sqrt();
+            assert!(norm < 1e-8, "{norm}");
+
+            // no sampled surface point is closer
+            let distance = (0..3)
+                .map(|k| (point[k] - closest[k]).powi(2))
+                .sum::<f64>()
+                .sqrt();
+            for _ in 0..20 {
+                let u = random_unit(&mut rng);
+                let s = [0, 1, 2].map(|k| ellipsoid.radii[k] * u[k]);
+                let other = [0, 1, 2].map(|r| {
+                    ellipsoid.center[r]
+                        + (0..3).map(|c| ellipsoid.rotation[r][c] * s[c]).sum::<f64>()
+                });
+                let other_distance = (0..3)
+                    .map(|k| (point[k] - other[k]).powi(2))
+                    .sum::<f64>()
+                    .sqrt();
+                assert!(distance <= other_distance + 1e-9);
+            }
+        }
+
+        // the center is closest to the ends of the shortest axis
+        let closest = to_local(&ellipsoid.closest_point(&ellipsoid.center));
+        assert_relative_eq!(closest[0], 0.0, epsilon = 1e-12);
+        assert_relative_eq!(closest[1], 0.0, epsilon = 1e-12);
+        assert_relative_eq!(closest[2].abs(), 1.0, epsilon = 1e-12);
+
+        // the points on the longest axis project off the axis close to the center
+        let from_local = |x: [f64; 3]| {
+            [0, 1, 2].map(|r| {
+                ellipsoid.center[r] + (0..3).map(|c| ellipsoid.rotation[r][c] * x[c]).sum::<f64>()
+            })
+        };
+        let closest = to_local(&ellipsoid.closest_point(&from_local([2.0, 0.0, 0.0])));
+        assert_relative_eq!(closest[0], 2.25, epsilon = 1e-9);
+        assert_relative_eq!(closest[2].abs(), 0.4375f64.sqrt(), epsilon = 1e-9);
+        let closest = to_local(&ellipsoid.closest_point(&from_local([5.0, 0.0, 0.0])));
+        assert_relative_eq!(closest[0], 3.0, epsilon = 1e-9);
+        assert_relative_eq!(closest[1], 0.0, epsilon = 1e-9);
+        assert_relative_eq!(closest[2], 0.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_fit_ellipsoid_rejects_hyperboloid() {
+        // one-sheet hyperboloid x² + y² - z² = 1
+        let points = (0..400)
+            .map(|i| {
+                let t = i as f64 * 0.173;
+                let z = -1.0 + 2.0 * (i % 20) as f64 / 19.0;
+                let r = (1.0 + z * z).sqrt();
+                [r * t.cos(), r * t.sin(), z]
+            })
+            .collect::<Vec<_>>();
+
+        assert!(matches!(
+            fit_ellipsoid(&points),
+            Err(FittingError::NotEllipsoid)
+        ));
+    }
+}
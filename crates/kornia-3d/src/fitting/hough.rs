@@ -0,0 +1,399 @@
+use std::f64::consts::PI;
+
+use super::{orient, PlaneModel};
+use crate::linalg::{cross_vec3, dot_product3};
+
+/// The distance, in bins, under which a weaker peak is a duplicate of a stronger one.
+const SUPPRESSION_BINS: f64 = 1.5;
+
+/// Detect lines in a point cloud with the 3D Hough transform.
+///
+/// Each line is parameterized by its direction, binned in spherical coordinates, and by the
+/// position where it crosses the plane through the centroid orthogonal to the direction,
+/// binned on a square grid of the plane. Every point votes, for every direction, for the line
+/// of that direction passing through it. The directions cover each axis once, with an
+/// azimuth `theta` in `[0, pi)` and a polar angle `phi` in `[0, pi]`.
+///
+/// # Arguments
+///
+/// * `points` - The points of the cloud.
+/// * `rho_bins` - The number of bins of each of the two position coordinates, over the
+///   radius of the cloud around its centroid.
+/// * `theta_bins` - The number of bins of the azimuth of the direction.
+/// * `phi_bins` - The number of bins of the polar angle of the direction.
+/// * `threshold` - The minimum number of votes of a line.
+///
+/// # Returns
+///
+/// The origin and unit direction of the lines with at least `threshold` votes which are
+/// local maxima of the accumulator, sorted by decreasing number of votes, without the weaker
+/// of two lines closer than a bin and a half in direction and position. The origin is the
+/// center of the position bin, the point of the line closest to the centroid of the cloud.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::fitting::hough_3d_lines;
+///
+/// // points along the x axis
+/// let points = (0..20).map(|i| [i as f64 * 0.1, 0.0, 0.0]).collect::<Vec<_>>();
+/// let lines = hough_3d_lines(&points, 20, 16, 16, 10);
+/// let (_, direction) = lines[0];
+/// assert!(direction[0].abs() > 0.99);
+/// ```
+pub fn hough_3d_lines(
+    points: &[[f64; 3]],
+    rho_bins: usize,
+    theta_bins: usize,
+    phi_bins: usize,
+    threshold: usize,
+) -> Vec<([f64; 3], [f64; 3])> {
+    let Some((centroid, radius)) = bounding_sphere(points) else {
+        return Vec::new();
+    };
+    if rho_bins == 0 || theta_bins == 0 || phi_bins == 0 {
+        return Vec::new();
+    }
+
+    let directions = direction_bins(theta_bins, phi_bins);
+    let bases = directions.iter().map(orthonormal_basis).collect::<Vec<_>>();
+
+    let dims = [directions.len(), rho_bins, rho_bins];
+    let mut accumulator = vec![0usize; dims.iter().product()];
+    for p in points {
+        let d = sub(p, &centroid);
+        for (i, (u, v)) in bases.iter().enumerate() {
+            let x = rho_bin(dot_product3(&d, u), radius, rho_bins);
+            let y = rho_bin(dot_product3(&d, v), radius, rho_bins);
+            accumulator[(i * rho_bins + x) * rho_bins + y] += 1;
+        }
+    }
+
+    // the peaks across the boundary of the azimuth are found twice, with opposite directions
+    let angle_step = PI / theta_bins.min(phi_bins) as f64;
+    let position_step = 2.0 * radius / rho_bins as f64;
+    let mut lines: Vec<([f64; 3], [f64; 3])> = Vec::new();
+    for index in local_maxima(&accumulator, &dims, threshold) {
+        let (i, x, y) = (
+            index / (rho_bins * rho_bins),
+            (index / rho_bins) % rho_bins,
+            index % rho_bins,
+        );
+        let (u, v) = &bases[i];
+        let (x, y) = (
+            rho_center(x, radius, rho_bins),
+            rho_center(y, radius, rho_bins),
+        );
+        let origin = [0, 1, 2].map(|k| centroid[k] + x * u[k] + y * v[k]);
+        let direction = directions[i];
+
+        let duplicate = lines.iter().any(|(o, d)| {
+            let mut c = [0.0; 3];
+            cross_vec3(&sub(&origin, o), d, &mut c);
+            axis_angle(d, &direction) <= SUPPRESSION_BINS * angle_step
+                && dot_product3(&c, &c).sqrt() <= SUPPRESSION_BINS * position_step
+        });
+        if !duplicate {
+            lines.push((origin, direction));
+        }
+    }
+    lines
+}
+
+/// Detect planes in a point cloud with the 3D Hough transform.
+///
+/// Each plane is parameterized by its normal, binned in spherical coordinates as the
+/// directions of [`hough_3d_lines`], and by its signed distance `rho` to the centroid of the
+/// cloud. Every point votes, for every normal, for the plane of that normal passing through
+/// it.
+///
+/// # Arguments
+///
+/// * `points` - The points of the cloud.
+/// * `rho_bins` - The number of bins of the distance to the centroid, over the radius of the
+///   cloud around its centroid.
+/// * `theta_bins` - The number of bins of the azimuth of the normal.
+/// * `phi_bins` - The number of bins of the polar angle of the normal.
+/// * `threshold` - The minimum number of votes of a plane.
+///
+/// # Returns
+///
+/// The planes with at least `threshold` votes which are local maxima of the accumulator,
+/// sorted by decreasing number of votes, without the weaker of two planes closer than a bin
+/// and a half in normal and offset. The normal is oriented so that its largest
+/// component is positive.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::fitting::hough_3d_planes;
+///
+/// // a grid on the plane z = 1
+/// let points = (0..100)
+///     .map(|i| [(i % 10) as f64 * 0.1, (i / 10) as f64 * 0.1, 1.0])
+///     .collect::<Vec<_>>();
+/// let planes = hough_3d_planes(&points, 20, 16, 16, 50);
+/// assert!(planes[0].normal[2] > 0.99);
+/// ```
+pub fn hough_3d_planes(
+    points: &[[f64; 3]],
+    rho_bins: usize,
+    theta_bins: usize,
+    phi_bins: usize,
+    threshold: usize,
+) -> Vec<PlaneModel> {
+    let Some((centroid, radius)) = bounding_sphere(points) else {
+        return Vec::new();
+    };
+    if rho_bins == 0 || theta_bins == 0 || phi_bins == 0 {
+        return Vec::new();
+    }
+
+    let normals = direction_bins(theta_bins, phi_bins);
+    let dims = [normals.len(), rho_bins];
+    let mut accumulator = vec![0usize; dims.iter().product()];
+    for p in points {
+        let d = sub(p, &centroid);
+        for (i, n) in normals.iter().enumerate() {
+            accumulator[i * rho_bins + rho_bin(dot_product3(&d, n), radius, rho_bins)] += 1;
+        }
+    }
+
+    // the peaks across the boundary of the azimuth are found twice, with opposite normals
+    let angle_step = PI / theta_bins.min(phi_bins) as f64;
+    let position_step = 2.0 * radius / rho_bins as f64;
+    let mut planes: Vec<PlaneModel> = Vec::new();
+    for index in local_maxima(&accumulator, &dims, threshold) {
+        let n = normals[index / rho_bins];
+        let rho = rho_center(index % rho_bins, radius, rho_bins);
+        let d = -(dot_product3(&n, &centroid) + rho);
+        let normal = orient(n);
+        let plane = PlaneModel {
+            normal,
+            d: if normal == n { d } else { -d },
+        };
+
+        // the offsets are compared at the centroid, where the binning of rho is uniform
+        let duplicate = planes.iter().any(|other| {
+            let sign = dot_product3(&other.normal, &plane.normal).signum();
+            axis_angle(&other.normal, &plane.normal) <= SUPPRESSION_BINS * angle_step
+                && (other.signed_distance(&centroid) - sign * plane.signed_distance(&centroid))
+                    .abs()
+                    <= SUPPRESSION_BINS * position_step
+        });
+        if !duplicate {
+            planes.push(plane);
+        }
+    }
+    planes
+}
+
+/// The angle between the axes of two unit vectors, regardless of their signs.
+fn axis_angle(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    dot_product3(a, b).abs().min(1.0).acos()
+}
+
+/// Compute the centroid of the points and their largest distance to it.
+fn bounding_sphere(points: &[[f64; 3]]) -> Option<([f64; 3], f64)> {
+    if points.is_empty() {
+        return None;
+    }
+    let mut centroid = [0.0; 3];
+    for p in points {
+        for k in 0..3 {
+            centroid[k] += p[k] / points.len() as f64;
+        }
+    }
+    let radius = points
+        .iter()
+        .map(|p| {
+            let d = sub(p, &centroid);
+            dot_product3(&d, &d).sqrt()
+        })
+        .fold(0.0, f64::max);
+    // a cloud of coincident points still gets a valid range
+    Some((centroid, radius.max(f64::EPSILON)))
+}
+
+/// The unit directions at the centers of the spherical bins, the azimuth varying fastest.
+fn direction_bins(theta_bins: usize, phi_bins: usize) -> Vec<[f64; 3]> {
+    (0..phi_bins)
+        .flat_map(|j| {
+            let phi = (j as f64 + 0.5) * PI / phi_bins as f64;
+            (0..theta_bins).map(move |i| {
+                let theta = (i as f64 + 0.5) * PI / theta_bins as f64;
+                [phi.sin() * theta.cos(), phi.sin() * theta.sin(), phi.cos()]
+            })
+        })
+        .collect()
+}
+
+/// Two unit vectors completing the unit direction into an orthonormal basis.
+fn orthonormal_basis(direction: &[f64; 3]) -> ([f64; 3], [f64; 3]) {
+    let axis = if direction[2].abs() < 0.9 {
+        [0.0, 0.0, 1.0]
+    } else {
+        [1.0, 0.0, 0.0]
+    };
+    let mut u = [0.0; 3];
+    cross_vec3(direction, &axis, &mut u);
+    let norm = dot_product3(&u, &u).sqrt();
+    let u = u.map(|x| x / norm);
+    let mut v = [0.0; 3];
+    cross_vec3(direction, &u, &mut v);
+    (u, v)
+}
+
+/// The bin of a coordinate in `[-radius, radius]`.
+fn rho_bin(rho: f64, radius: f64, bins: usize) -> usize {
+    let bin = ((rho + radius) / (2.0 * radius) * bins as f64).floor();
+    (bin.max(0.0) as usize).min(bins - 1)
+}
+
+/// The center of a bin of [`rho_bin`].
+fn rho_center(bin: usize, radius: f64, bins: usize) -> f64 {
+    -radius + (bin as f64 + 0.5) * 2.0 * radius / bins as f64
+}
+
+/// The indices of the cells with at least `threshold` votes and no neighbor with more votes,
+/// sorted by decreasing votes.
+///
+/// The neighbors are the cells differing by at most one in each dimension, and of two equal
+/// neighboring cells only the first one is kept.
+fn local_maxima(accumulator: &[usize], dims: &[usize], threshold: usize) -> Vec<usize> {
+    let mut strides = vec![1; dims.len()];
+    for k in (0..dims.len().saturating_sub(1)).rev() {
+        strides[k] = strides[k + 1] * dims[k + 1];
+    }
+
+    let num_offsets = 3usize.pow(dims.len() as u32);
+    let mut maxima = accumulator
+        .iter()
+        .enumerate()
+        .filter(|(_, votes)| **votes >= threshold.max(1))
+        .filter(|(index, votes)| {
+            let coords = dims
+                .iter()
+                .zip(strides.iter())
+                .map(|(dim, stride)| (index / stride) % dim)
+                .collect::<Vec<_>>();
+            (0..num_offsets).all(|offset| {
+                let mut neighbor = 0;
+                let mut code = offset;
+                for ((coord, dim), stride) in coords.iter().zip(dims.iter()).zip(strides.iter()) {
+                    let c = (*coord + code % 3) as isize - 1;
+                    code /= 3;
+                    if c < 0 || c >= *dim as isize {
+                        return true;
+                    }
+                    neighbor += c as usize * stride;
+                }
+                let other = accumulator[neighbor];
+                other < **votes || (other == **votes && neighbor >= *index)
+            })
+        })
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    maxima.sort_by(|a, b| accumulator[*b].cmp(&accumulator[*a]).then(a.cmp(b)));
+    maxima
+}
+
+fn sub(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn normalize(v: [f64; 3]) -> [f64; 3] {
+        let norm = dot_product3(&v, &v).sqrt();
+        v.map(|x| x / norm)
+    }
+
+    fn distance_to_line(p: &[f64; 3], origin: &[f64; 3], direction: &[f64; 3]) -> f64 {
+        let mut c = [0.0; 3];
+        cross_vec3(&sub(p, origin), direction, &mut c);
+        dot_product3(&c, &c).sqrt()
+    }
+
+    #[test]
+    fn test_hough_3d_planes() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let truth = [
+            PlaneModel {
+                normal: normalize([0.2, 0.3, 1.0]),
+                d: -0.5,
+            },
+            PlaneModel {
+                normal: normalize([1.0, -0.4, 0.1]),
+                d: 0.2,
+            },
+        ];
+
+        // random points of a patch of each plane
+        let mut points = Vec::new();
+        for plane in truth.iter() {
+            let (u, v) = orthonormal_basis(&plane.normal);
+            for _ in 0..400 {
+                let (a, b): (f64, f64) = (rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0));
+                points.push([0, 1, 2].map(|k| -plane.d * plane.normal[k] + a * u[k] + b * v[k]));
+            }
+        }
+
+        let planes = hough_3d_planes(&points, 40, 30, 30, 150);
+        assert!(planes.len() >= 2);
+        for expected in truth.iter() {
+            let plane = planes[..2]
+                .iter()
+                .min_by(|a, b| {
+                    axis_angle(&a.normal, &expected.normal)
+                        .total_cmp(&axis_angle(&b.normal, &expected.normal))
+                })
+                .unwrap();
+            assert!(axis_angle(&plane.normal, &expected.normal) < 0.1);
+            let sign = dot_product3(&plane.normal, &expected.normal).signum();
+            assert!((sign * plane.d - expected.d).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_hough_3d_lines() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let truth = [
+            ([0.0, 0.0, 0.0], normalize([1.0, 0.2, 0.1])),
+            ([0.3, -0.2, 0.8], normalize([-0.3, 1.0, 0.4])),
+        ];
+
+        // random points along two skew lines
+        let mut points = Vec::new();
+        for (origin, direction) in truth.iter() {
+            for _ in 0..100 {
+                let s: f64 = rng.random_range(-1.0..1.0);
+                points.push([0, 1, 2].map(|k| origin[k] + s * direction[k]));
+            }
+        }
+
+        let lines = hough_3d_lines(&points, 30, 24, 24, 50);
+        assert!(lines.len() >= 2);
+        for (expected_origin, expected_direction) in truth.iter() {
+            let (origin, direction) = lines[..2]
+                .iter()
+                .min_by(|a, b| {
+                    axis_angle(&a.1, expected_direction)
+                        .total_cmp(&axis_angle(&b.1, expected_direction))
+                })
+                .unwrap();
+            assert!(axis_angle(direction, expected_direction) < 0.15);
+            assert!(distance_to_line(expected_origin, origin, direction) < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_hough_3d_empty() {
+        assert!(hough_3d_lines(&[], 10, 10, 10, 1).is_empty());
+        assert!(hough_3d_planes(&[], 10, 10, 10, 1).is_empty());
+        assert!(hough_3d_planes(&[[0.0; 3]; 5], 0, 10, 10, 1).is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+mod ellipsoid;
+pub use ellipsoid::*;
+
+mod hough;
+pub use hough::*;
+
+mod plane;
+pub use plane::*;
+
+/// Error types for the fitting module.
+#[derive(Debug, thiserror::Error)]
+pub enum FittingError {
+    /// Not enough points to fit the model
+    #[error("Not enough points to fit the model. Got:{0}")]
+    NotEnoughPoints(usize),
+
+    /// The weights do not match the points
+    #[error("Expected {expected} weights but got {actual}")]
+    WeightsLengthMismatch {
+        /// The number of points.
+        expected: usize,
+        /// The number of weights.
+        actual: usize,
+    },
+
+    /// The weights are negative, not finite, or sum to zero
+    #[error("Invalid weights")]
+    InvalidWeights,
+
+    /// The fitted quadric is not an ellipsoid
+    #[error("The fitted quadric is not an ellipsoid")]
+    NotEllipsoid,
+
+    /// The points do not constrain the model
+    #[error("Degenerate configuration")]
+    Degenerate,
+}
@@ -0,0 +1,388 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::FittingError;
+use crate::linalg::{cross_vec3, dot_product3, eigen_symmetric33};
+
+/// A plane defined by the equation `normal · x + d = 0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaneModel {
+    /// The unit normal of the plane.
+    pub normal: [f64; 3],
+    /// The offset of the plane along the normal.
+    pub d: f64,
+}
+
+impl PlaneModel {
+    /// Compute the signed distance from a point to the plane.
+    pub fn signed_distance(&self, point: &[f64; 3]) -> f64 {
+        dot_product3(&self.normal, point) + self.d
+    }
+}
+
+/// A constraint on the plane for [`fit_plane_constrained`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaneConstraint {
+    /// The plane has the given normal and only its offset is fitted.
+    FixedNormal([f64; 3]),
+    /// The plane passes through the given point and only its normal is fitted.
+    ThroughPoint([f64; 3]),
+}
+
+/// Fit a plane to a set of points with weighted least squares.
+///
+/// The normal is the eigenvector with the smallest eigenvalue of the weighted covariance
+/// of the points and the plane passes through their weighted centroid. Points with zero
+/// weight have no influence on the result. The normal is oriented so that its largest
+/// component is positive.
+///
+/// # Arguments
+///
+/// * `points` - The points to fit.
+/// * `weights` - Optional non-negative weight for each point. All points weigh one if `None`.
+///
+/// # Returns
+///
+/// The fitted plane, or an error if the points are fewer than three or collinear.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::fitting::fit_plane_lsq;
+///
+/// let points = vec![[0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0], [1.0, 1.0, 1.0]];
+/// let plane = fit_plane_lsq(&points, None).unwrap();
+/// assert!((plane.normal[2] - 1.0).abs() < 1e-12);
+/// assert!((plane.d + 1.0).abs() < 1e-12);
+/// ```
+pub fn fit_plane_lsq(
+    points: &[[f64; 3]],
+    weights: Option<&[f64]>,
+) -> Result<PlaneModel, FittingError> {
+    let num_active = check_weights(points, weights)?;
+    if num_active < 3 {
+        return Err(FittingError::NotEnoughPoints(num_active));
+    }
+
+    let centroid = weighted_centroid(points, weights);
+    let (eigenvalues, eigenvectors) = eigen_symmetric33(&scatter(points, weights, &centroid));
+    if eigenvalues[1] <= 1e-12 * eigenvalues[2] {
+        return Err(FittingError::Degenerate);
+    }
+
+    let normal = orient(eigenvectors[0]);
+    Ok(PlaneModel {
+        normal,
+        d: -dot_product3(&normal, &centroid),
+    })
+}
+
+/// Fit a plane to a set of points with weighted least squares under a constraint.
+///
+/// * With [`PlaneConstraint::FixedNormal`] the offset is the one that makes the plane pass
+///   through the weighted centroid of the points.
+/// * With [`PlaneConstraint::ThroughPoint`] the normal is the eigenvector with the smallest
+///   eigenvalue of the weighted scatter of the points around the anchor point.
+///
+/// # Arguments
+///
+/// * `points` - The points to fit.
+/// * `weights` - Optional non-negative weight for each point. All points weigh one if `None`.
+/// * `constraint` - The constraint the plane must satisfy.
+///
+/// # Returns
+///
+/// The fitted plane satisfying the constraint.
+pub fn fit_plane_constrained(
+    points: &[[f64; 3]],
+    weights: Option<&[f64]>,
+    constraint: PlaneConstraint,
+) -> Result<PlaneModel, FittingError> {
+    let num_active = check_weights(points, weights)?;
+
+    match constraint {
+        PlaneConstraint::FixedNormal(normal) => {
+            if num_active < 1 {
+                return Err(FittingError::NotEnoughPoints(num_active));
+            }
+            let norm = dot_product3(&normal, &normal).sqrt();
+            if norm < 1e-12 || !norm.is_finite() {
+                return Err(FittingError::Degenerate);
+            }
+            let normal = [normal[0] / norm, normal[1] / norm, normal[2] / norm];
+            let centroid = weighted_centroid(points, weights);
+            Ok(PlaneModel {
+                normal,
+                d: -dot_product3(&normal, &centroid),
+            })
+        }
+        PlaneConstraint::ThroughPoint(anchor) => {
+            if num_active < 2 {
+                return Err(FittingError::NotEnoughPoints(num_active));
+            }
+            let (eigenvalues, eigenvectors) = eigen_symmetric33(&scatter(points, weights, &anchor));
+            if eigenvalues[1] <= 1e-12 * eigenvalues[2] {
+                return Err(FittingError::Degenerate);
+            }
+            let normal = orient(eigenvectors[0]);
+            Ok(PlaneModel {
+                normal,
+                d: -dot_product3(&normal, &anchor),
+            })
+        }
+    }
+}
+
+/// Fit a plane robustly with RANSAC and refine it with least squares on the inliers.
+///
+/// # Arguments
+///
+/// * `points` - The points to fit.
+/// * `distance_threshold` - The maximum distance from a point to the plane to be an inlier.
+/// * `max_iterations` - The number of random minimal samples to evaluate.
+/// * `seed` - The seed of the random generator.
+///
+/// # Returns
+///
+/// The refined plane and the indices of its inliers.
+pub fn fit_plane_ransac(
+    points: &[[f64; 3]],
+    distance_threshold: f64,
+    max_iterations: usize,
+    seed: u64,
+) -> Result<(PlaneModel, Vec<usize>), FittingError> {
+    if points.len() < 3 {
+        return Err(FittingError::NotEnoughPoints(points.len()));
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut best_inliers = Vec::new();
+
+    for _ in 0..max_iterations {
+        let i0 = rng.random_range(0..points.len());
+        let i1 = rng.random_range(0..points.len());
+        let i2 = rng.random_range(0..points.len());
+        let Some(plane) = plane_from_three_points(&points[i0], &points[i1], &points[i2]) else {
+            continue;
+        };
+
+        let inliers = inliers_of(points, &plane, distance_threshold);
+        if inliers.len() > best_inliers.len() {
+            best_inliers = inliers;
+        }
+    }
+
+    if best_inliers.len() < 3 {
+        return Err(FittingError::Degenerate);
+    }
+
+    let inlier_points = best_inliers.iter().map(|&i| points[i]).collect::<Vec<_>>();
+    let plane = fit_plane_lsq(&inlier_points, None)?;
+    let inliers = inliers_of(points, &plane, distance_threshold);
+
+    Ok((plane, inliers))
+}
+
+fn inliers_of(points: &[[f64; 3]], plane: &PlaneModel, distance_threshold: f64) -> Vec<usize> {
+    points
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| plane.signed_distance(p).abs() <= distance_threshold)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn plane_from_three_points(a: &[f64; 3], b: &[f64; 3], c: &[f64; 3]) -> Option<PlaneModel> {
+    let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let ac = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let mut n = [0.0; 3];
+    cross_vec3(&ab, &ac, &mut n);
+    let norm = dot_product3(&n, &n).sqrt();
+    if norm < 1e-12 {
+        return None;
+    }
+    let normal = orient([n[0] / norm, n[1] / norm, n[2] / norm]);
+    Some(PlaneModel {
+        normal,
+        d: -dot_product3(&normal, a),
+    })
+}
+
+/// Validate the weights and return the number of points with positive weight.
+fn check_weights(points: &[[f64; 3]], weights: Option<&[f64]>) -> Result<usize, FittingError> {
+    let Some(weights) = weights else {
+        return Ok(points.len());
+    };
+    if weights.len() != points.len() {
+        return Err(FittingError::WeightsLengthMismatch {
+            expected: points.len(),
+            actual: weights.len(),
+        });
+    }
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+        return Err(FittingError::InvalidWeights);
+    }
+    Ok(weights.iter().filter(|w| **w > 0.0).count())
+}
+
+fn weight_at(weights: Option<&[f64]>, i: usize) -> f64 {
+    weights.map_or(1.0, |w| w[i])
+}
+
+fn weighted_centroid(points: &[[f64; 3]], weights: Option<&[f64]>) -> [f64; 3] {
+    let mut sum = [0.0; 3];
+    let mut sum_w = 0.0;
+    for (i, p) in points.iter().enumerate() {
+        let w = weight_at(weights, i);
+        sum_w += w;
+        for k in 0..3 {
+            sum[k] += w * p[k];
+        }
+    }
+    [sum[0] / sum_w, sum[1] / sum_w, sum[2] / sum_w]
+}
+
+/// Compute the weighted scatter matrix of the points around a center.
+fn scatter(points: &[[f64; 3]], weights: Option<&[f64]>, center: &[f64; 3]) -> [[f64; 3]; 3] {
+    let mut m = [[0.0; 3]; 3];
+    for (i, p) in points.iter().enumerate() {
+        let w = weight_at(weights, i);
+        let d = [p[0] - center[0], p[1] - center[1], p[2] - center[2]];
+        for (r, row) in m.iter_mut().enumerate() {
+            for (c, value) in row.iter_mut().enumerate() {
+                *value += w * d[r] * d[c];
+            }
+        }
+    }
+    m
+}
+
+/// Flip the vector so that its component of largest magnitude is positive.
+pub(super) fn orient(n: [f64; 3]) -> [f64; 3] {
+    let largest = (0..3)
+        .max_by(|&a, &b| n[a].abs().total_cmp(&n[b].abs()))
+        .unwrap_or(0);
+    if n[largest] < 0.0 {
+        [-n[0], -n[1], -n[2]]
+    } else {
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    /// Points on the plane z = 0.5 x - 0.25 y + 2 with a small deterministic noise.
+    fn plane_points(n: usize, noise: f64) -> Vec<[f64; 3]> {
+        (0..n)
+            .map(|i| {
+                let x = (i % 10) as f64 * 0.3;
+                let y = (i / 10) as f64 * 0.2;
+                let e = noise * ((i * 7919) % 13) as f64 / 13.0 - 0.5 * noise;
+                [x, y, 0.5 * x - 0.25 * y + 2.0 + e]
+            })
+            .collect()
+    }
+
+    fn expected_normal() -> [f64; 3] {
+        let n: [f64; 3] = [-0.5, 0.25, 1.0];
+        let norm = dot_product3(&n, &n).sqrt();
+        [n[0] / norm, n[1] / norm, n[2] / norm]
+    }
+
+    #[test]
+    fn test_fit_plane_lsq_zero_weight_outliers() -> Result<(), Box<dyn std::error::Error>> {
+        let inliers = plane_points(50, 0.0);
+        let mut points = inliers.clone();
+        points.extend_from_slice(&[[0.0, 0.0, 100.0], [5.0, -3.0, -40.0], [1.0, 1.0, 7.0]]);
+
+        let mut weights = vec![1.0; inliers.len()];
+        weights.extend_from_slice(&[0.0, 0.0, 0.0]);
+
+        let clean = fit_plane_lsq(&inliers, None)?;
+        let weighted = fit_plane_lsq(&points, Some(&weights))?;
+        for k in 0..3 {
+            assert_relative_eq!(weighted.normal[k], clean.normal[k], epsilon = 1e-12);
+            assert_relative_eq!(weighted.normal[k], expected_normal()[k], epsilon = 1e-9);
+        }
+        assert_relative_eq!(weighted.d, clean.d, epsilon = 1e-12);
+
+        // the outliers bias the unweighted fit
+        let unweighted = fit_plane_lsq(&points, None)?;
+        assert!((unweighted.d - clean.d).abs() > 1e-3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fit_plane_fixed_normal() -> Result<(), Box<dyn std::error::Error>> {
+        let points = plane_points(60, 0.01);
+        let normal = expected_normal();
+        let plane = fit_plane_constrained(&points, None, PlaneConstraint::FixedNormal(normal))?;
+
+        assert_eq!(plane.normal, normal);
+        // the plane contains (0, 0, 2)
+        assert_relative_eq!(plane.d, -2.0 * normal[2], epsilon = 1e-3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fit_plane_through_point() -> Result<(), Box<dyn std::error::Error>> {
+        let points = plane_points(60, 0.0);
+        let anchor = [1.0, 1.0, 2.25];
+        let plane = fit_plane_constrained(&points, None, PlaneConstraint::ThroughPoint(anchor))?;
+
+        assert_relative_eq!(plane.signed_distance(&anchor), 0.0, epsilon = 1e-12);
+        for k in 0..3 {
+            assert_relative_eq!(plane.normal[k], expected_normal()[k], epsilon = 1e-9);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fit_plane_ransac_matches_lsq() -> Result<(), Box<dyn std::error::Error>> {
+        let inliers = plane_points(80, 0.0);
+        let mut points = inliers.clone();
+        for i in 0..20 {
+            points.push([i as f64 * 0.1, 1.0, 10.0 + i as f64]);
+        }
+
+        let (plane, inlier_indices) = fit_plane_ransac(&points, 1e-6, 200, 5)?;
+        assert_eq!(inlier_indices, (0..80).collect::<Vec<_>>());
+
+        let lsq = fit_plane_lsq(&inliers, None)?;
+        for k in 0..3 {
+            assert_relative_eq!(plane.normal[k], lsq.normal[k], epsilon = 1e-12);
+        }
+        assert_relative_eq!(plane.d, lsq.d, epsilon = 1e-12);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fit_plane_errors() {
+        let points = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [2.0, 0.0, 0.0]];
+        assert!(matches!(
+            fit_plane_lsq(&points, None),
+            Err(FittingError::Degenerate)
+        ));
+        assert!(matches!(
+            fit_plane_lsq(&points, Some(&[1.0, 1.0])),
+            Err(FittingError::WeightsLengthMismatch {
+                expected: 3,
+                actual: 2
+            })
+        ));
+        assert!(matches!(
+            fit_plane_lsq(&points, Some(&[1.0, -1.0, 1.0])),
+            Err(FittingError::InvalidWeights)
+        ));
+        assert!(matches!(
+            fit_plane_lsq(&points[..2], None),
+            Err(FittingError::NotEnoughPoints(2))
+        ));
+    }
+}
@@ -6,3 +6,6 @@ pub mod pcd;
 
 /// PLY reader module.
 pub mod ply;
+
+/// Velodyne binary reader module.
+pub mod velodyne;
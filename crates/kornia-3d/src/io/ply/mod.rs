@@ -1,8 +1,10 @@
 mod parser;
 mod properties;
+mod writer;
 
 pub use parser::*;
 pub use properties::*;
+pub use writer::*;
 
 /// Error types for the PLY module.
 #[derive(Debug, thiserror::Error)]
@@ -18,4 +20,15 @@ pub enum PlyError {
     /// Unsupported PLY property
     #[error("Unsupported PLY property")]
     UnsupportedProperty,
+
+    /// An attribute of the point cloud does not have one value per point
+    #[error("The point cloud has {actual} {attribute} for {expected} points")]
+    AttributeLengthMismatch {
+        /// The name of the attribute.
+        attribute: &'static str,
+        /// The number of points.
+        expected: usize,
+        /// The number of values of the attribute.
+        actual: usize,
+    },
 }
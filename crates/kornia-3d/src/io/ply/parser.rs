@@ -4,34 +4,55 @@ use std::path::Path;
 use super::{properties::PlyType, PlyError, PlyPropertyTrait};
 use crate::pointcloud::PointCloud;
 
-/// Read a PLY file in binary format.
-///
-/// NOTE: This function only supports the OpenSplat and XYZRgbNormals PLY file format for now.
-/// REF: <https://github.com/pierotofy/OpenSplat>
-///
-/// Args:
-///     path: The path to the PLY file.
-///
-/// Returns:
-///     A `PointCloud` struct containing the points, colors, and normals.
-pub fn read_ply_binary(path: impl AsRef<Path>, property: PlyType) -> Result<PointCloud, PlyError> {
-    // open the file
-    let file = std::fs::File::open(path)?;
-    let mut reader = std::io::BufReader::new(file);
+/// Element counts parsed from the PLY header.
+struct PlyHeader {
+    // number of vertices declared in the header, if any
+    num_vertices: Option<usize>,
+    // number of edges declared in the header
+    num_edges: usize,
+}
+
+/// Read the header of a PLY file until the `end_header` line.
+fn read_header(reader: &mut impl BufRead) -> Result<PlyHeader, PlyError> {
+    let mut header = PlyHeader {
+        num_vertices: None,
+        num_edges: 0,
+    };
 
-    // read the header
-    // TODO: parse automatically the header
-    let mut header = String::new();
     loop {
         let mut line = String::new();
-        reader.read_line(&mut line)?;
+        if reader.read_line(&mut line)? == 0 {
+            return Err(PlyError::Io(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "missing end_header",
+            )));
+        }
         if line.starts_with("end_header") {
-            header.push_str(&line);
             break;
         }
-        header.push_str(&line);
+
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        if let ["element", name, count] = tokens.as_slice() {
+            let count = count
+                .parse::<usize>()
+                .map_err(|_| PlyError::UnsupportedProperty)?;
+            match *name {
+                "vertex" => header.num_vertices = Some(count),
+                "edge" => header.num_edges = count,
+                _ => {}
+            }
+        }
     }
 
+    Ok(header)
+}
+
+/// Read the vertices of a PLY file after the header.
+fn read_vertices(
+    reader: &mut impl Read,
+    property: &PlyType,
+    num_vertices: Option<usize>,
+) -> Result<PointCloud, PlyError> {
     // create a buffer for the points
     let mut buffer = vec![0u8; property.size_of()];
 
@@ -40,7 +61,7 @@ pub fn read_ply_binary(path: impl AsRef<Path>, property: PlyType) -> Result<Poin
     let mut colors = Vec::new();
     let mut normals = Vec::new();
 
-    while reader.read_exact(&mut buffer).is_ok() {
+    while num_vertices != Some(points.len()) && reader.read_exact(&mut buffer).is_ok() {
         let property_entry = property.deserialize(&buffer)?;
         points.push(property_entry.to_point());
         colors.push(property_entry.to_color());
@@ -49,3 +70,65 @@ pub fn read_ply_binary(path: impl AsRef<Path>, property: PlyType) -> Result<Poin
 
     Ok(PointCloud::new(points, Some(colors), Some(normals)))
 }
+
+/// Read a PLY file in binary format.
+///
+/// NOTE: This function only supports the OpenSplat and XYZRgbNormals PLY file format for now.
+/// REF: <https://github.com/pierotofy/OpenSplat>
+///
+/// Args:
+///     path: The path to the PLY file.
+///
+/// Returns:
+///     A `PointCloud` struct containing the points, colors, and normals.
+pub fn read_ply_binary(path: impl AsRef<Path>, property: PlyType) -> Result<PointCloud, PlyError> {
+    // open the file
+    let file = std::fs::File::open(path)?;
+    let mut reader = std::io::BufReader::new(file);
+
+    // read the header
+    let header = read_header(&mut reader)?;
+
+    read_vertices(&mut reader, &property, header.num_vertices)
+}
+
+/// Read a PLY line set in binary format.
+///
+/// The file must contain a vertex element followed by an edge element with two `int`
+/// properties (`vertex1` and `vertex2`), as written by [`super::write_ply_line_set_binary`].
+///
+/// Args:
+///     path: The path to the PLY file.
+///     property: The vertex property format.
+///
+/// Returns:
+///     A `PointCloud` with the vertices and the list of edges as pairs of vertex indices.
+pub fn read_ply_line_set_binary(
+    path: impl AsRef<Path>,
+    property: PlyType,
+) -> Result<(PointCloud, Vec<[u32; 2]>), PlyError> {
+    // open the file
+    let file = std::fs::File::open(path)?;
+    let mut reader = std::io::BufReader::new(file);
+
+    // read the header
+    let header = read_header(&mut reader)?;
+
+    // the number of vertices is required to know where the edges start
+    let Some(num_vertices) = header.num_vertices else {
+        return Err(PlyError::UnsupportedProperty);
+    };
+
+    let cloud = read_vertices(&mut reader, &property, Some(num_vertices))?;
+
+    let mut edges = Vec::with_capacity(header.num_edges);
+    let mut buffer = [0u8; 8];
+    for _ in 0..header.num_edges {
+        reader.read_exact(&mut buffer)?;
+        let v1 = i32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
+        let v2 = i32::from_le_bytes([buffer[4], buffer[5], buffer[6], buffer[7]]);
+        edges.push([v1 as u32, v2 as u32]);
+    }
+
+    Ok((cloud, edges))
+}
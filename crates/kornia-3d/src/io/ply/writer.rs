@@ -0,0 +1,200 @@
+use std::io::Write;
+use std::path::Path;
+
+use super::PlyError;
+use crate::pointcloud::PointCloud;
+
+/// Check that the colors and the normals of a point cloud have one value per point.
+fn check_attribute_lengths(cloud: &PointCloud) -> Result<(), PlyError> {
+    let lengths = [
+        ("colors", cloud.colors().map(|colors| colors.len())),
+        ("normals", cloud.normals().map(|normals| normals.len())),
+    ];
+    for (attribute, length) in lengths {
+        if let Some(actual) = length.filter(|&actual| actual != cloud.len()) {
+            return Err(PlyError::AttributeLengthMismatch {
+                attribute,
+                expected: cloud.len(),
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Write the header and the vertices of a point cloud in the XYZRgbNormals format.
+fn write_vertices(
+    writer: &mut impl Write,
+    cloud: &PointCloud,
+    num_edges: Option<usize>,
+) -> Result<(), PlyError> {
+    // write the header
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format binary_little_endian 1.0")?;
+    writeln!(writer, "element vertex {}", cloud.len())?;
+    writeln!(writer, "property float x")?;
+    writeln!(writer, "property float y")?;
+    writeln!(writer, "property float z")?;
+    writeln!(writer, "property uchar red")?;
+    writeln!(writer, "property uchar green")?;
+    writeln!(writer, "property uchar blue")?;
+    writeln!(writer, "property float nx")?;
+    writeln!(writer, "property float ny")?;
+    writeln!(writer, "property float nz")?;
+    if let Some(num_edges) = num_edges {
+        writeln!(writer, "element edge {}", num_edges)?;
+        writeln!(writer, "property int vertex1")?;
+        writeln!(writer, "property int vertex2")?;
+    }
+    writeln!(writer, "end_header")?;
+
+    // write the vertices
+    for (i, point) in cloud.points().iter().enumerate() {
+        let color = cloud.colors().map_or([0, 0, 0], |colors| colors[i]);
+        let normal = cloud.normals().map_or([0.0; 3], |normals| normals[i]);
+        for v in point.iter() {
+            writer.write_all(&(*v as f32).to_le_bytes())?;
+        }
+        writer.write_all(&color)?;
+        for v in normal.iter() {
+            writer.write_all(&(*v as f32).to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a point cloud to a PLY file in binary format.
+///
+/// The file is written in the XYZRgbNormals format and can be read back with
+/// [`super::read_ply_binary`] using [`super::PlyType::XYZRgbNormals`]. Missing colors and
+/// normals are written as zeros, and colors or normals with not exactly one value per point
+/// are rejected before the file is created.
+///
+/// Args:
+///     path: The path to the PLY file.
+///     cloud: The point cloud to write.
+pub fn write_ply_binary(path: impl AsRef<Path>, cloud: &PointCloud) -> Result<(), PlyError> {
+    check_attribute_lengths(cloud)?;
+
+    let file = std::fs::File::create(path)?;
+    let mut writer = std::io::BufWriter::new(file);
+
+    write_vertices(&mut writer, cloud, None)?;
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Write a line set to a PLY file in binary format.
+///
+/// The vertices are written in the XYZRgbNormals format followed by an edge element
+/// with the pairs of vertex indices. The file can be opened by tools like MeshLab or
+/// CloudCompare and read back with [`super::read_ply_line_set_binary`].
+///
+/// Args:
+///     path: The path to the PLY file.
+///     cloud: The vertices of the line set.
+///     edges: The edges as pairs of indices into the vertices.
+pub fn write_ply_line_set_binary(
+    path: impl AsRef<Path>,
+    cloud: &PointCloud,
+    edges: &[[u32; 2]],
+) -> Result<(), PlyError> {
+    check_attribute_lengths(cloud)?;
+
+    let file = std::fs::File::create(path)?;
+    let mut writer = std::io::BufWriter::new(file);
+
+    write_vertices(&mut writer, cloud, Some(edges.len()))?;
+
+    for edge in edges.iter() {
+        writer.write_all(&(edge[0] as i32).to_le_bytes())?;
+        writer.write_all(&(edge[1] as i32).to_le_bytes())?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::ply::{read_ply_binary, read_ply_line_set_binary, PlyType};
+
+    #[test]
+    fn test_write_read_ply_binary() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let file_path = tmp_dir.path().join("cloud.ply");
+
+        let cloud = PointCloud::new(
+            vec![[0.0, 1.0, 2.0], [3.0, 4.0, 5.0]],
+            Some(vec![[255, 0, 0], [0, 255, 0]]),
+            Some(vec![[0.0, 0.0, 1.0], [1.0, 0.0, 0.0]]),
+        );
+        write_ply_binary(&file_path, &cloud)?;
+
+        let cloud_back = read_ply_binary(&file_path, PlyType::XYZRgbNormals)?;
+        assert_eq!(cloud_back.points(), cloud.points());
+        assert_eq!(cloud_back.colors(), cloud.colors());
+        assert_eq!(cloud_back.normals(), cloud.normals());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_ply_binary_length_mismatch() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let file_path = tmp_dir.path().join("cloud.ply");
+
+        let points = vec![[0.0, 1.0, 2.0], [3.0, 4.0, 5.0]];
+        let few_colors = PointCloud::new(points.clone(), Some(vec![[255, 0, 0]]), None);
+        assert!(matches!(
+            write_ply_binary(&file_path, &few_colors),
+            Err(PlyError::AttributeLengthMismatch {
+                attribute: "colors",
+                expected: 2,
+                actual: 1,
+            })
+        ));
+        let many_normals = PointCloud::new(points, None, Some(vec![[0.0, 0.0, 1.0]; 3]));
+        assert!(matches!(
+            write_ply_line_set_binary(&file_path, &many_normals, &[[0, 1]]),
+            Err(PlyError::AttributeLengthMismatch {
+                attribute: "normals",
+                expected: 2,
+                actual: 3,
+            })
+        ));
+        assert!(!file_path.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_read_ply_line_set_binary() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let file_path = tmp_dir.path().join("lines.ply");
+
+        let cloud = PointCloud::new(
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            None,
+            None,
+        );
+        let edges = vec![[0, 1], [1, 2]];
+        write_ply_line_set_binary(&file_path, &cloud, &edges)?;
+
+        let (cloud_back, edges_back) =
+            read_ply_line_set_binary(&file_path, PlyType::XYZRgbNormals)?;
+        assert_eq!(cloud_back.points(), cloud.points());
+        assert_eq!(edges_back, edges);
+
+        // the vertex reader stops at the declared number of vertices
+        let cloud_back = read_ply_binary(&file_path, PlyType::XYZRgbNormals)?;
+        assert_eq!(cloud_back.len(), 3);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+/// Velodyne binary file parser
+mod parser;
+pub use parser::*;
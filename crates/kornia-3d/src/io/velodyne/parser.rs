@@ -0,0 +1,182 @@
+use std::io::Read;
+use std::path::Path;
+
+use crate::pointcloud::{PointCloud, TimestampedCloud};
+
+/// Size in bytes of a point in the Velodyne binary format.
+const POINT_SIZE: usize = 4 * std::mem::size_of::<f32>();
+
+/// Error types for the Velodyne module.
+#[derive(Debug, thiserror::Error)]
+pub enum VelodyneError {
+    /// Failed to read Velodyne file
+    #[error("Failed to read Velodyne file")]
+    Io(#[from] std::io::Error),
+
+    /// The file ends in the middle of a point
+    #[error("Truncated Velodyne file. Got {0} trailing bytes")]
+    TruncatedPoint(usize),
+
+    /// Invalid Velodyne file extension
+    #[error("Invalid Velodyne file extension. Got:{0}")]
+    InvalidFileExtension(String),
+}
+
+/// Read a Velodyne binary file as used in the KITTI raw dataset.
+///
+/// The file is a packed sequence of `[f32; 4]` points with the x, y, z coordinates and the
+/// reflectance, all stored as little-endian floats. The format carries no timestamp.
+///
+/// Args:
+///     path: The path to the `.bin` file.
+///
+/// Returns:
+///     A `TimestampedCloud` with the points and the reflectance as intensities.
+pub fn read_velodyne_bin(path: impl AsRef<Path>) -> Result<TimestampedCloud, VelodyneError> {
+    let mut points = Vec::new();
+    let mut intensities = Vec::new();
+
+    for point in read_velodyne_bin_iter(path)? {
+        let [x, y, z, reflectance] = point?;
+        points.push([x as f64, y as f64, z as f64]);
+        intensities.push(reflectance);
+    }
+
+    Ok(TimestampedCloud {
+        timestamp_ns: None,
+        cloud: PointCloud::new(points, None, None),
+        intensities: Some(intensities),
+    })
+}
+
+/// Stream the points of a Velodyne binary file as used in the KITTI raw dataset.
+///
+/// The points are read one at a time through a buffered reader, so the whole scan is
+/// never held in memory.
+///
+/// Args:
+///     path: The path to the `.bin` file.
+///
+/// Returns:
+///     An iterator over the `[x, y, z, reflectance]` points of the file.
+pub fn read_velodyne_bin_iter(
+    path: impl AsRef<Path>,
+) -> Result<impl Iterator<Item = Result<[f32; 4], VelodyneError>>, VelodyneError> {
+    let Some(file_ext) = path.as_ref().extension() else {
+        return Err(VelodyneError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "File extension is missing",
+        )));
+    };
+
+    if file_ext != "bin" {
+        return Err(VelodyneError::InvalidFileExtension(
+            file_ext.to_string_lossy().to_string(),
+        ));
+    }
+
+    let file = std::fs::File::open(path)?;
+    let mut reader = std::io::BufReader::new(file);
+    let mut done = false;
+
+    Ok(std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let point = read_point(&mut reader).transpose();
+        done = !matches!(point, Some(Ok(_)));
+        point
+    }))
+}
+
+/// Read the next point, returning `None` at the end of the file.
+fn read_point(reader: &mut impl Read) -> Result<Option<[f32; 4]>, VelodyneError> {
+    let mut buffer = [0u8; POINT_SIZE];
+    let mut num_read = 0;
+    while num_read < POINT_SIZE {
+        match reader.read(&mut buffer[num_read..]) {
+            Ok(0) => break,
+            Ok(n) => num_read += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    match num_read {
+        0 => Ok(None),
+        POINT_SIZE => {
+            let mut point = [0.0f32; 4];
+            for (value, bytes) in point.iter_mut().zip(buffer.chunks_exact(4)) {
+                *value = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            }
+            Ok(Some(point))
+        }
+        n => Err(VelodyneError::TruncatedPoint(n)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_points(path: &Path, points: &[[f32; 4]]) -> std::io::Result<()> {
+        let bytes = points
+            .iter()
+            .flat_map(|p| p.iter().flat_map(|v| v.to_le_bytes()))
+            .collect::<Vec<_>>();
+        std::fs::write(path, bytes)
+    }
+
+    #[test]
+    fn test_read_velodyne_bin() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let path = tmp_dir.path().join("0000000000.bin");
+
+        let points = [[1.5, -2.0, 0.25, 0.1], [10.0, 20.0, -1.0, 0.9]];
+        write_points(&path, &points)?;
+
+        let scan = read_velodyne_bin(&path)?;
+        assert_eq!(scan.timestamp_ns, None);
+        assert_eq!(scan.cloud.len(), 2);
+        assert_eq!(scan.cloud.points()[0], [1.5, -2.0, 0.25]);
+        assert_eq!(scan.cloud.points()[1], [10.0, 20.0, -1.0]);
+        assert_eq!(scan.intensities, Some(vec![0.1, 0.9]));
+
+        let streamed = read_velodyne_bin_iter(&path)?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(streamed, points);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_velodyne_bin_truncated() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let path = tmp_dir.path().join("truncated.bin");
+
+        let mut bytes = [1.0f32, 2.0, 3.0, 4.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        bytes.extend_from_slice(&[0u8; 6]);
+        std::fs::write(&path, bytes)?;
+
+        let mut iter = read_velodyne_bin_iter(&path)?;
+        assert_eq!(iter.next().transpose()?, Some([1.0, 2.0, 3.0, 4.0]));
+        assert!(matches!(
+            iter.next(),
+            Some(Err(VelodyneError::TruncatedPoint(6)))
+        ));
+        assert!(iter.next().is_none());
+
+        assert!(matches!(
+            read_velodyne_bin(&path),
+            Err(VelodyneError::TruncatedPoint(6))
+        ));
+        assert!(matches!(
+            read_velodyne_bin(tmp_dir.path().join("scan.pcd")),
+            Err(VelodyneError::InvalidFileExtension(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+use std::num::NonZeroUsize;
+
+use kiddo::{immutable::float::kdtree::ImmutableKdTree, NearestNeighbour, SquaredEuclidean};
+
+/// Maximum number of points stored in a leaf of the tree.
+const LEAF_SIZE: usize = 32;
+
+/// A neighbor returned by a query on a [`KdTree`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbor {
+    /// The index of the point in the slice used to build the tree.
+    pub index: usize,
+    /// The Euclidean distance from the query to the point.
+    pub distance: f64,
+}
+
+/// A KD-tree over a set of 3D points for nearest neighbor and radius queries.
+///
+/// The queries are answered by the immutable tree of `kiddo`, the one of the registration
+/// pipelines, while this type keeps the indexed points and their bounds and returns the
+/// neighbors with Euclidean distances, sorted by increasing distance and then by index so
+/// that the results do not depend on the layout of the tree.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::kdtree::KdTree;
+///
+/// let points = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 2.0, 0.0]];
+/// let tree = KdTree::new(&points);
+/// let nn = tree.nearest_one(&[0.9, 0.1, 0.0]).unwrap();
+/// assert_eq!(nn.index, 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct KdTree {
+    points: Vec<[f64; 3]>,
+    tree: ImmutableKdTree<f64, u64, 3, LEAF_SIZE>,
+    bounds: ([f64; 3], [f64; 3]),
+}
+
+impl KdTree {
+    /// Build a new KD-tree from a slice of points.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - The points to index. Query results refer to positions in this slice.
+    pub fn new(points: &[[f64; 3]]) -> Self {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for p in points {
+            for k in 0..3 {
+                min[k] = min[k].min(p[k]);
+                max[k] = max[k].max(p[k]);
+            }
+        }
+
+        Self {
+            points: points.to_vec(),
+            tree: ImmutableKdTree::new_from_slice(points),
+            bounds: (min, max),
+        }
+    }
+
+    /// Return the number of points in the tree.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Check if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Return the points indexed by the tree.
+    pub fn points(&self) -> &[[f64; 3]] {
+        &self.points
+    }
+
+    /// Return the minimum and maximum corners of the bounding box of the points.
+    ///
+    /// # Returns
+    ///
+    /// The corners of the axis-aligned bounding box, or `None` if the tree is empty.
+    pub fn bounds(&self) -> Option<([f64; 3], [f64; 3])> {
+        (!self.is_empty()).then_some(self.bounds)
+    }
+
+    /// Find the closest point to the query.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query point.
+    ///
+    /// # Returns
+    ///
+    /// The closest neighbor, or `None` if the tree is empty.
+    pub fn nearest_one(&self, query: &[f64; 3]) -> Option<Neighbor> {
+        self.nearest_n(query, 1).into_iter().next()
+    }
+
+    /// Find the `n` closest points to the query.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query point.
+    /// * `n` - The number of neighbors to return.
+    ///
+    /// # Returns
+    ///
+    /// Up to `n` neighbors sorted by increasing distance.
+    pub fn nearest_n(&self, query: &[f64; 3], n: usize) -> Vec<Neighbor> {
+        match NonZeroUsize::new(n.min(self.points.len())) {
+            Some(n) => sorted_neighbors(self.tree.nearest_n::<SquaredEuclidean>(query, n)),
+            None => Vec::new(),
+        }
+    }
+
+    /// Find all the points within a radius of the query.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query point.
+    /// * `radius` - The search radius (inclusive).
+    ///
+    /// # Returns
+    ///
+    /// The neighbors within the radius sorted by increasing distance.
+    pub fn within_radius(&self, query: &[f64; 3], radius: f64) -> Vec<Neighbor> {
+        if self.is_empty() || radius.is_nan() || radius < 0.0 {
+            return Vec::new();
+        }
+        sorted_neighbors(
+            self.tree
+                .within_unsorted::<SquaredEuclidean>(query, radius * radius),
+        )
+    }
+}
+
+/// Convert the neighbors of `kiddo` to Euclidean distances, sorted by distance and index.
+fn sorted_neighbors(neighbors: Vec<NearestNeighbour<f64, u64>>) -> Vec<Neighbor> {
+    let mut neighbors = neighbors
+        .into_iter()
+        .map(|nn| Neighbor {
+            index: nn.item as usize,
+            distance: nn.distance.sqrt(),
+        })
+        .collect::<Vec<_>>();
+    neighbors.sort_by(|a, b| {
+        a.distance
+            .total_cmp(&b.distance)
+            .then(a.index.cmp(&b.index))
+    });
+    neighbors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn squared_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+        (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+    }
+
+    fn brute_force(points: &[[f64; 3]], query: &[f64; 3]) -> Vec<Neighbor> {
+        let mut all = points
+            .iter()
+            .enumerate()
+            .map(|(index, p)| Neighbor {
+                index,
+                distance: squared_distance(query, p).sqrt(),
+            })
+            .collect::<Vec<_>>();
+        all.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        all
+    }
+
+    fn random_points(rng: &mut StdRng, n: usize) -> Vec<[f64; 3]> {
+        (0..n)
+            .map(|_| {
+                [
+                    rng.random_range(-5.0..5.0),
+                    rng.random_range(-5.0..5.0),
+                    rng.random_range(-1.0..1.0),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_kdtree_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let points = random_points(&mut rng, 500);
+        let tree = KdTree::new(&points);
+        assert_eq!(tree.len(), 500);
+
+        for query in random_points(&mut rng, 50) {
+            let expected = brute_force(&points, &query);
+
+            let nn = tree.nearest_one(&query).unwrap();
+            assert_relative_eq!(nn.distance, expected[0].distance);
+
+            let knn = tree.nearest_n(&query, 8);
+            assert_eq!(knn.len(), 8);
+            for (a, b) in knn.iter().zip(expected.iter()) {
+                assert_relative_eq!(a.distance, b.distance);
+            }
+
+            let radius = 1.0;
+            let within = tree.within_radius(&query, radius);
+            let num_expected = expected.iter().filter(|n| n.distance <= radius).count();
+            assert_eq!(within.len(), num_expected);
+        }
+    }
+
+    #[test]
+    fn test_kdtree_duplicates() {
+        // many copies of a few points
+        let points = (0..1000)
+            .map(|i| [(i % 3) as f64, 0.0, 0.0])
+            .collect::<Vec<_>>();
+        let tree = KdTree::new(&points);
+
+        let knn = tree.nearest_n(&[0.1, 0.0, 0.0], 4);
+        assert_eq!(knn.len(), 4);
+        assert!(knn.iter().all(|n| n.index % 3 == 0));
+        assert!(knn.windows(2).all(|w| w[0].index < w[1].index));
+        let within = tree.within_radius(&[0.0, 0.0, 0.0], 1.0);
+        assert_eq!(within.len(), 667);
+        assert!(within.windows(2).all(|w| w[0].distance < w[1].distance
+            || (w[0].distance == w[1].distance && w[0].index < w[1].index)));
+        assert!(tree.within_radius(&[0.0, 0.0, 0.0], -1.0).is_empty());
+    }
+
+    #[test]
+    fn test_kdtree_empty_and_small() {
+        let tree = KdTree::new(&[]);
+        assert!(tree.is_empty());
+        assert!(tree.nearest_one(&[0.0, 0.0, 0.0]).is_none());
+        assert!(tree.bounds().is_none());
+
+        let tree = KdTree::new(&[[1.0, 0.0, 0.0], [2.0, 0.0, 0.0]]);
+        let knn = tree.nearest_n(&[0.0, 0.0, 0.0], 5);
+        assert_eq!(knn.len(), 2);
+        assert_eq!(knn[0].index, 0);
+        assert_eq!(knn[1].index, 1);
+        assert_eq!(tree.bounds(), Some(([1.0, 0.0, 0.0], [2.0, 0.0, 0.0])));
+    }
+}
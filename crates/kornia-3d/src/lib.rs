@@ -1,23 +1,69 @@
 #![deny(missing_docs)]
 #![doc = env!("CARGO_PKG_DESCRIPTION")]
 
+/// Bounding volume hierarchy over triangle meshes.
+pub mod bvh;
+
+/// Point cloud density estimation.
+pub mod density;
+
+/// Depth map processing.
+pub mod depth;
+
+/// Feature descriptors for 3D data.
+pub mod features;
+
+/// Point cloud filtering operations.
+pub mod filters;
+
+/// Geometric primitive fitting.
+pub mod fitting;
+
 /// I/O utilities for reading and writing 3D data.
 pub mod io;
 
+/// KD-tree for nearest neighbor search.
+pub mod kdtree;
+
+/// LiDAR scan analysis.
+pub mod lidar;
+
 /// Linear algebra utilities.
 pub mod linalg;
 
+/// Occupancy and Gaussian voxel mapping from LiDAR scans.
+#[cfg(feature = "mapping")]
+pub mod mapping;
+
+/// Triangle meshes, their geometric properties and queries.
+pub mod mesh;
+
 /// Operations on 3D data processing.
 pub mod ops;
 
+/// Ray picking and spatial selection of points.
+pub mod picking;
+
 /// Point cloud traits.
 pub mod pointcloud;
 
 /// Pose estimation algorithms.
 pub mod pose;
 
+/// Curve skeleton extraction from point clouds.
+pub mod skeleton;
+
+/// Statistical utilities for 3D data.
+pub mod stats;
+
+/// Surface resampling and reconstruction.
+pub mod surface;
+
 /// 3D transforms algorithms.
 pub mod transforms;
 
 /// 3D vector traits.
 pub mod vector;
+
+/// Voxel grids and operations on them.
+pub mod voxel;
@@ -0,0 +1,178 @@
+use crate::pointcloud::PointCloud;
+
+/// Minimum ratio between the smallest ring gap and the largest in-ring difference.
+const MIN_GAP_RATIO: f64 = 5.0;
+
+/// Angular differences below this value (radians) are considered numerical noise.
+const ANGLE_EPS: f64 = 1e-9;
+
+/// Estimate the angular resolution of a LiDAR scan.
+///
+/// The points are expressed in spherical coordinates around the origin, which is assumed
+/// to be the sensor center. The elevations are grouped into rings (beams) by splitting the
+/// sorted elevations at the differences that stand out from the rest. The vertical
+/// resolution is the median difference between the mean elevations of consecutive rings
+/// and the horizontal resolution is the median azimuth step between consecutive points of
+/// the same ring.
+///
+/// # Arguments
+///
+/// * `cloud` - The LiDAR scan in the sensor frame.
+///
+/// # Returns
+///
+/// The horizontal and vertical angular resolutions in degrees. A resolution is zero if it
+/// cannot be estimated, e.g. the vertical resolution of a single-beam scan.
+pub fn estimate_angular_resolution(cloud: &PointCloud) -> (f64, f64) {
+    let mut angles = cloud
+        .points()
+        .iter()
+        .filter_map(|p| {
+            let horizontal_range = (p[0] * p[0] + p[1] * p[1]).sqrt();
+            if horizontal_range < 1e-9 && p[2].abs() < 1e-9 {
+                return None;
+            }
+            let azimuth = p[1].atan2(p[0]);
+            let elevation = p[2].atan2(horizontal_range);
+            Some((elevation, azimuth))
+        })
+        .collect::<Vec<_>>();
+
+    if angles.len() < 2 {
+        return (0.0, 0.0);
+    }
+
+    angles.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // split the sorted elevations into rings at the outstanding gaps
+    let diffs = angles
+        .windows(2)
+        .map(|w| w[1].0 - w[0].0)
+        .collect::<Vec<_>>();
+    let threshold = ring_gap_threshold(&diffs);
+
+    let mut ring_elevations = Vec::new();
+    let mut azimuth_steps = Vec::new();
+    let mut ring_start = 0;
+    for i in 0..=diffs.len() {
+        let is_ring_end = i == diffs.len() || threshold.is_some_and(|t| diffs[i] >= t);
+        if !is_ring_end {
+            continue;
+        }
+
+        let ring = &angles[ring_start..=i];
+        ring_elevations.push(ring.iter().map(|(e, _)| e).sum::<f64>() / ring.len() as f64);
+
+        let mut azimuths = ring.iter().map(|(_, azimuth)| *azimuth).collect::<Vec<_>>();
+        azimuths.sort_by(|a, b| a.total_cmp(b));
+        azimuth_steps.extend(
+            azimuths
+                .windows(2)
+                .map(|w| w[1] - w[0])
+                .filter(|step| *step > ANGLE_EPS),
+        );
+
+        ring_start = i + 1;
+    }
+
+    let mut ring_gaps = ring_elevations
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .collect::<Vec<_>>();
+
+    (
+        median(&mut azimuth_steps).to_degrees(),
+        median(&mut ring_gaps).to_degrees(),
+    )
+}
+
+/// Find the smallest elevation difference that separates two rings, if any.
+///
+/// The differences are sorted in decreasing order and split at the largest ratio between
+/// consecutive values, considering that there are fewer rings than half the points.
+fn ring_gap_threshold(diffs: &[f64]) -> Option<f64> {
+    let mut sorted = diffs.to_vec();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+
+    let max_rings = sorted.len().div_ceil(2);
+    let (split, ratio) = (1..=max_rings.min(sorted.len() - 1))
+        .map(|k| (k, sorted[k - 1] / sorted[k].max(ANGLE_EPS)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    (ratio >= MIN_GAP_RATIO).then_some(sorted[split - 1])
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let n = values.len();
+    if n % 2 == 0 {
+        0.5 * (values[n / 2 - 1] + values[n / 2])
+    } else {
+        values[n / 2]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn synthetic_scan(
+        num_rings: usize,
+        vertical_deg: f64,
+        horizontal_deg: f64,
+        dropout: f64,
+        seed: u64,
+    ) -> PointCloud {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let num_azimuths = (360.0 / horizontal_deg).round() as usize;
+        let mut points = Vec::new();
+        for ring in 0..num_rings {
+            let elevation = (-15.0 + ring as f64 * vertical_deg).to_radians();
+            for j in 0..num_azimuths {
+                if rng.random::<f64>() < dropout {
+                    continue;
+                }
+                let azimuth = (-180.0 + j as f64 * horizontal_deg).to_radians();
+                // small calibration noise on the beam elevation
+                let noise: f64 = rng.random_range(-1e-5..1e-5);
+                let elevation = elevation + noise;
+                let range: f64 = rng.random_range(5.0..40.0);
+                points.push([
+                    range * elevation.cos() * azimuth.cos(),
+                    range * elevation.cos() * azimuth.sin(),
+                    range * elevation.sin(),
+                ]);
+            }
+        }
+        PointCloud::new(points, None, None)
+    }
+
+    #[test]
+    fn test_estimate_angular_resolution() {
+        let cloud = synthetic_scan(16, 2.0, 0.2, 0.0, 1);
+        let (horizontal, vertical) = estimate_angular_resolution(&cloud);
+        assert_relative_eq!(horizontal, 0.2, epsilon = 1e-6);
+        assert_relative_eq!(vertical, 2.0, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_estimate_angular_resolution_dropout() {
+        let cloud = synthetic_scan(32, 1.33, 0.4, 0.2, 2);
+        let (horizontal, vertical) = estimate_angular_resolution(&cloud);
+        assert_relative_eq!(horizontal, 0.4, epsilon = 1e-6);
+        assert_relative_eq!(vertical, 1.33, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_estimate_angular_resolution_single_ring() {
+        let cloud = synthetic_scan(1, 1.0, 1.0, 0.0, 3);
+        let (horizontal, vertical) = estimate_angular_resolution(&cloud);
+        assert_relative_eq!(horizontal, 1.0, epsilon = 1e-6);
+        assert_eq!(vertical, 0.0);
+    }
+}
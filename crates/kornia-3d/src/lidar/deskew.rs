@@ -0,0 +1,173 @@
+use crate::{pointcloud::PointCloud, transforms::RigidTransform3};
+
+/// Compensate the motion of a spinning LiDAR during a sweep.
+///
+/// The points of a sweep are captured over its duration, typically 100 ms, while the sensor
+/// moves, so that a raw frame is distorted. The pose of the sensor when each point was
+/// captured is interpolated between the poses at the beginning and at the end of the sweep
+/// with [`RigidTransform3::interpolate`], i.e. assuming constant velocities, and each point
+/// is transformed into the frame of the sensor at the end of the sweep.
+///
+/// # Arguments
+///
+/// * `cloud` - The sweep in the sensor frame. The colors and normals are kept, the normals
+///   being rotated as the points.
+/// * `timestamps` - The time of each point as a fraction of the sweep, from 0 at its
+///   beginning to 1 at its end.
+/// * `pose_begin` - The pose of the sensor in the world frame at the beginning of the sweep.
+/// * `pose_end` - The pose of the sensor in the world frame at the end of the sweep.
+///
+/// # Returns
+///
+/// The motion compensated sweep in the frame of the sensor at the end of the sweep.
+///
+/// PRECONDITION: there is one timestamp per point.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::lidar::deskew_cloud;
+/// use kornia_3d::pointcloud::PointCloud;
+/// use kornia_3d::transforms::RigidTransform3;
+///
+/// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// // the sensor moves 1 m forward during the sweep
+/// let pose_end = RigidTransform3::new(identity, [1.0, 0.0, 0.0]);
+/// // a static point at 10 m seen at the beginning and at the end of the sweep
+/// let cloud = PointCloud::new(vec![[10.0, 0.0, 0.0], [9.0, 0.0, 0.0]], None, None);
+/// let deskewed = deskew_cloud(&cloud, &[0.0, 1.0], &RigidTransform3::identity(), &pose_end);
+/// assert_eq!(deskewed.points(), &vec![[9.0, 0.0, 0.0], [9.0, 0.0, 0.0]]);
+/// ```
+pub fn deskew_cloud(
+    cloud: &PointCloud,
+    timestamps: &[f64],
+    pose_begin: &RigidTransform3,
+    pose_end: &RigidTransform3,
+) -> PointCloud {
+    let end_from_world = pose_end.inverse();
+
+    // the transformation from the sensor frame at the time of each point to the end frame
+    let transforms = timestamps
+        .iter()
+        .map(|t| end_from_world.compose(&pose_begin.interpolate(pose_end, *t)))
+        .collect::<Vec<_>>();
+
+    let points = cloud
+        .points()
+        .iter()
+        .zip(transforms.iter())
+        .map(|(p, transform)| transform.apply(p))
+        .collect();
+    let normals = cloud.normals().map(|normals| {
+        normals
+            .iter()
+            .zip(transforms.iter())
+            .map(|(n, transform)| RigidTransform3::new(transform.rotation, [0.0; 3]).apply(n))
+            .collect()
+    });
+
+    PointCloud::new(points, cloud.colors().cloned(), normals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{linalg::eigen_symmetric33, transforms::axis_angle_to_rotation_matrix};
+    use approx::assert_relative_eq;
+
+    /// The RMS distance of the points to their least squares plane.
+    fn plane_residual(points: &[[f64; 3]]) -> f64 {
+        let num = points.len() as f64;
+        let mut centroid = [0.0; 3];
+        for p in points {
+            for k in 0..3 {
+                centroid[k] += p[k] / num;
+            }
+        }
+        let mut covariance = [[0.0; 3]; 3];
+        for p in points {
+            let d = [0, 1, 2].map(|k| p[k] - centroid[k]);
+            for i in 0..3 {
+                for j in 0..3 {
+                    covariance[i][j] += d[i] * d[j] / num;
+                }
+            }
+        }
+        let (eigenvalues, _) = eigen_symmetric33(&covariance);
+        eigenvalues[0].max(0.0).sqrt()
+    }
+
+    #[test]
+    fn test_deskew_cloud_wall() -> Result<(), Box<dyn std::error::Error>> {
+        // the sensor turns and moves towards the wall x = 5 during the sweep
+        let pose_begin = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], -0.15)?,
+            [0.0, 0.0, 0.0],
+        );
+        let pose_end = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.15)?,
+            [0.8, 0.3, 0.0],
+        );
+
+        // the columns of the sweep facing the wall, with their capture times
+        let mut points = Vec::new();
+        let mut timestamps = Vec::new();
+        let num_columns = 200;
+        for col in 0..num_columns {
+            let t = col as f64 / (num_columns - 1) as f64;
+            let azimuth = (-40.0 + 80.0 * t).to_radians();
+            let pose = pose_begin.interpolate(&pose_end, t);
+            for beam in 0..8 {
+                let elevation = (-7.0 + 2.0 * beam as f64).to_radians();
+                let direction = [
+                    elevation.cos() * azimuth.cos(),
+                    elevation.cos() * azimuth.sin(),
+                    elevation.sin(),
+                ];
+                // cast the ray in the world frame and express the hit in the sensor frame
+                let world_direction =
+                    RigidTransform3::new(pose.rotation, [0.0; 3]).apply(&direction);
+                let range = (5.0 - pose.translation[0]) / world_direction[0];
+                points.push(direction.map(|d| range * d));
+                timestamps.push(t);
+            }
+        }
+        let cloud = PointCloud::new(points, None, None);
+
+        let raw_residual = plane_residual(cloud.points());
+        let deskewed = deskew_cloud(&cloud, &timestamps, &pose_begin, &pose_end);
+        let deskewed_residual = plane_residual(deskewed.points());
+        assert!(raw_residual > 0.05);
+        assert!(deskewed_residual < 0.1 * raw_residual);
+
+        // the wall is at x = 5 in the world frame
+        for p in deskewed.points() {
+            assert_relative_eq!(pose_end.apply(p)[0], 5.0, epsilon = 1e-9);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_deskew_cloud_normals() -> Result<(), Box<dyn std::error::Error>> {
+        let pose_end = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], std::f64::consts::FRAC_PI_2)?,
+            [0.0; 3],
+        );
+        let cloud = PointCloud::new(
+            vec![[1.0, 0.0, 0.0]; 2],
+            Some(vec![[1, 2, 3], [4, 5, 6]]),
+            Some(vec![[1.0, 0.0, 0.0]; 2]),
+        );
+        let deskewed = deskew_cloud(&cloud, &[0.0, 1.0], &RigidTransform3::identity(), &pose_end);
+        assert_eq!(deskewed.colors(), cloud.colors());
+
+        // the point seen at the beginning is rotated back by the turn of the sensor
+        let normals = deskewed.normals().ok_or("no normals")?;
+        for (p, n) in deskewed.points().iter().zip(normals.iter()) {
+            assert_eq!(p, n);
+        }
+        assert_relative_eq!(deskewed.points()[0][1], -1.0, epsilon = 1e-12);
+        assert_relative_eq!(deskewed.points()[1][0], 1.0, epsilon = 1e-12);
+        Ok(())
+    }
+}
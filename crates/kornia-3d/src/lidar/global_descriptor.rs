@@ -0,0 +1,393 @@
+use crate::pointcloud::PointCloud;
+
+/// Configuration of the global descriptor of a LiDAR scan.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlobalDescriptorConfig {
+    /// The number of rings, i.e. radial bins.
+    pub num_rings: usize,
+    /// The number of sectors, i.e. azimuth bins over a full revolution.
+    pub num_sectors: usize,
+    /// The range of the outermost ring. Farther points are ignored.
+    pub max_range: f64,
+}
+
+/// A ScanContext-style global descriptor of a LiDAR scan.
+///
+/// The scan is split in a polar grid of rings and sectors around the sensor and each cell
+/// holds the maximum height of its points above the lowest point of the scan, zero if empty.
+/// A rotation of the scan about the vertical axis shifts the sectors, so the descriptors are
+/// compared over all the sector shifts, which also gives a coarse estimate of the yaw.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobalDescriptor {
+    num_rings: usize,
+    num_sectors: usize,
+    cells: Vec<f32>,
+    ring_key: Vec<f32>,
+}
+
+impl GlobalDescriptor {
+    /// Get the number of rings of the descriptor.
+    pub fn num_rings(&self) -> usize {
+        self.num_rings
+    }
+
+    /// Get the number of sectors of the descriptor.
+    pub fn num_sectors(&self) -> usize {
+        self.num_sectors
+    }
+
+    /// Get the cells of the polar grid, in row-major order with one row per ring.
+    pub fn cells(&self) -> &[f32] {
+        &self.cells
+    }
+
+    /// Get the mean of the cells of each ring, which is invariant to the yaw.
+    pub fn ring_key(&self) -> &[f32] {
+        &self.ring_key
+    }
+
+    /// Compute the distance to another descriptor over all the sector shifts.
+    ///
+    /// For each shift, the distance is the mean cosine distance between the corresponding
+    /// sectors that are occupied in both descriptors. The smallest distance is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - A descriptor with the same configuration.
+    ///
+    /// # Returns
+    ///
+    /// The distance in `[0, 1]` and the yaw of the other scan relative to this one in
+    /// radians, in `(-PI, PI]`, with a resolution of one sector. Descriptors without
+    /// common occupied sectors are at distance 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the descriptors have different configurations.
+    pub fn distance(&self, other: &GlobalDescriptor) -> (f64, f64) {
+        assert_eq!(self.num_rings, other.num_rings);
+        assert_eq!(self.num_sectors, other.num_sectors);
+
+        let sector = |cells: &[f32], j: usize| {
+            (0..self.num_rings)
+                .map(|r| cells[r * self.num_sectors + j] as f64)
+                .collect::<Vec<_>>()
+        };
+        let sectors_a = (0..self.num_sectors)
+            .map(|j| sector(&self.cells, j))
+            .collect::<Vec<_>>();
+        let sectors_b = (0..self.num_sectors)
+            .map(|j| sector(&other.cells, j))
+            .collect::<Vec<_>>();
+        let norms_a = sectors_a.iter().map(|s| norm(s)).collect::<Vec<_>>();
+        let norms_b = sectors_b.iter().map(|s| norm(s)).collect::<Vec<_>>();
+
+        let mut best = (1.0, 0);
+        for shift in 0..self.num_sectors {
+            let mut sum = 0.0;
+            let mut count = 0;
+            for j in 0..self.num_sectors {
+                let k = (j + shift) % self.num_sectors;
+                if norms_a[j] == 0.0 || norms_b[k] == 0.0 {
+                    continue;
+                }
+                let dot = sectors_a[j]
+                    .iter()
+                    .zip(sectors_b[k].iter())
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>();
+                sum += 1.0 - dot / (norms_a[j] * norms_b[k]);
+                count += 1;
+            }
+            if count > 0 && sum / (count as f64) < best.0 {
+                best = (sum / count as f64, shift);
+            }
+        }
+
+        let sector_angle = std::f64::consts::TAU / self.num_sectors as f64;
+        let mut yaw = best.1 as f64 * sector_angle;
+        if yaw > std::f64::consts::PI {
+            yaw -= std::f64::consts::TAU;
+        }
+        (best.0, yaw)
+    }
+}
+
+/// Compute the global descriptor of a LiDAR scan for place recognition.
+///
+/// # Arguments
+///
+/// * `cloud` - The LiDAR scan in the sensor frame, with the z axis up.
+/// * `config` - The configuration of the polar grid.
+///
+/// # Returns
+///
+/// The global descriptor of the scan.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::lidar::{compute_global_descriptor, GlobalDescriptorConfig};
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let config = GlobalDescriptorConfig {
+///     num_rings: 4,
+///     num_sectors: 8,
+///     max_range: 10.0,
+/// };
+/// let cloud = PointCloud::new(vec![[1.0, 0.5, 0.0], [1.0, 0.5, 2.0]], None, None);
+/// let descriptor = compute_global_descriptor(&cloud, &config);
+/// assert_eq!(descriptor.cells().iter().filter(|c| **c > 0.0).count(), 1);
+/// assert_eq!(descriptor.distance(&descriptor), (0.0, 0.0));
+/// ```
+pub fn compute_global_descriptor(
+    cloud: &PointCloud,
+    config: &GlobalDescriptorConfig,
+) -> GlobalDescriptor {
+    let (num_rings, num_sectors) = (config.num_rings, config.num_sectors);
+    let points = cloud
+        .points()
+        .iter()
+        .filter(|p| (p[0] * p[0] + p[1] * p[1]).sqrt() < config.max_range)
+        .collect::<Vec<_>>();
+    let min_height = points.iter().map(|p| p[2]).fold(f64::INFINITY, f64::min);
+
+    let mut cells = vec![0.0f32; num_rings * num_sectors];
+    for p in points {
+        let range = (p[0] * p[0] + p[1] * p[1]).sqrt();
+        let azimuth = p[1].atan2(p[0]) + std::f64::consts::PI;
+        let ring = ((range / config.max_range * num_rings as f64) as usize).min(num_rings - 1);
+        let sector =
+            ((azimuth / std::f64::consts::TAU * num_sectors as f64) as usize).min(num_sectors - 1);
+        let cell = &mut cells[ring * num_sectors + sector];
+        *cell = cell.max((p[2] - min_height) as f32);
+    }
+
+    let ring_key = cells
+        .chunks(num_sectors)
+        .map(|ring| ring.iter().sum::<f32>() / num_sectors as f32)
+        .collect();
+
+    GlobalDescriptor {
+        num_rings,
+        num_sectors,
+        cells,
+        ring_key,
+    }
+}
+
+/// A candidate scan returned by [`DescriptorDatabase::query`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DescriptorMatch {
+    /// The index of the scan in the database.
+    pub index: usize,
+    /// The distance between the descriptors.
+    pub distance: f64,
+    /// The coarse yaw of the query scan relative to the database scan in radians.
+    pub yaw: f64,
+}
+
+/// A database of global descriptors to detect loop-closure candidates.
+///
+/// The candidates are only similar places: they are meant to be verified with a global
+/// registration and ICP, initialized with the coarse yaw of the match.
+#[derive(Debug, Clone, Default)]
+pub struct DescriptorDatabase {
+    descriptors: Vec<GlobalDescriptor>,
+}
+
+impl DescriptorDatabase {
+    /// Create an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a descriptor to the database.
+    ///
+    /// # Returns
+    ///
+    /// The index of the descriptor in the database.
+    pub fn insert(&mut self, descriptor: GlobalDescriptor) -> usize {
+        self.descriptors.push(descriptor);
+        self.descriptors.len() - 1
+    }
+
+    /// Get the number of descriptors in the database.
+    pub fn len(&self) -> usize {
+        self.descriptors.len()
+    }
+
+    /// Check if the database is empty.
+    pub fn is_empty(&self) -> bool {
+        self.descriptors.is_empty()
+    }
+
+    /// Get as reference the descriptors of the database.
+    pub fn descriptors(&self) -> &[GlobalDescriptor] {
+        &self.descriptors
+    }
+
+    /// Find the scans of the database most similar to a query.
+    ///
+    /// # Arguments
+    ///
+    /// * `descriptor` - The descriptor of the query scan.
+    /// * `k` - The maximum number of candidates.
+    ///
+    /// # Returns
+    ///
+    /// The `k` nearest candidates sorted by increasing distance.
+    pub fn query(&self, descriptor: &GlobalDescriptor, k: usize) -> Vec<DescriptorMatch> {
+        let mut matches = self
+            .descriptors
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| {
+                let (distance, yaw) = candidate.distance(descriptor);
+                DescriptorMatch {
+                    index,
+                    distance,
+                    yaw,
+                }
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by(|a, b| {
+            a.distance
+                .total_cmp(&b.distance)
+                .then(a.index.cmp(&b.index))
+        });
+        matches.truncate(k);
+        matches
+    }
+}
+
+fn norm(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn config() -> GlobalDescriptorConfig {
+        GlobalDescriptorConfig {
+            num_rings: 20,
+            num_sectors: 60,
+            max_range: 20.0,
+        }
+    }
+
+    /// A place made of a flat ground and buildings of random footprints and heights.
+    fn place(rng: &mut StdRng) -> Vec<([f64; 2], [f64; 2], f64)> {
+        (0..12)
+            .map(|_| {
+                let range: f64 = rng.random_range(4.0..18.0);
+                let azimuth: f64 = rng.random_range(-std::f64::consts::PI..std::f64::consts::PI);
+                let center = [range * azimuth.cos(), range * azimuth.sin()];
+                let half_size = [rng.random_range(0.5..2.0), rng.random_range(0.5..2.0)];
+                (center, half_size, rng.random_range(1.0..8.0))
+            })
+            .collect()
+    }
+
+    /// Sample a scan of a place from a sensor with a yaw and a small offset.
+    fn scan(
+        buildings: &[([f64; 2], [f64; 2], f64)],
+        yaw: f64,
+        offset: [f64; 2],
+        rng: &mut StdRng,
+    ) -> PointCloud {
+        let mut points = Vec::new();
+        for _ in 0..20000 {
+            let p = [
+                rng.random_range(-20.0..20.0),
+                rng.random_range(-20.0..20.0),
+                0.0,
+            ];
+            points.push(p);
+        }
+        for (center, half_size, height) in buildings {
+            for _ in 0..400 {
+                let (u, z): (f64, f64) =
+                    (rng.random_range(-1.0..1.0), rng.random_range(0.0..*height));
+                let p = match rng.random_range(0..4) {
+                    0 => [center[0] + half_size[0], center[1] + u * half_size[1], z],
+                    1 => [center[0] - half_size[0], center[1] + u * half_size[1], z],
+                    2 => [center[0] + u * half_size[0], center[1] + half_size[1], z],
+                    _ => [center[0] + u * half_size[0], center[1] - half_size[1], z],
+                };
+                points.push(p);
+            }
+        }
+
+        // express the points in the frame of the sensor, with some noise
+        let (c, s) = (yaw.cos(), yaw.sin());
+        let points = points
+            .iter()
+            .map(|p| {
+                let (x, y) = (p[0] - offset[0], p[1] - offset[1]);
+                [
+                    c * x + s * y + rng.random_range(-0.05..0.05),
+                    -s * x + c * y + rng.random_range(-0.05..0.05),
+                    p[2] + rng.random_range(-0.05..0.05),
+                ]
+            })
+            .collect();
+        PointCloud::new(points, None, None)
+    }
+
+    #[test]
+    fn test_global_descriptor_yaw() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let buildings = place(&mut rng);
+        let a = compute_global_descriptor(&scan(&buildings, 0.0, [0.0; 2], &mut rng), &config());
+        let b = compute_global_descriptor(&scan(&buildings, 1.0, [0.0; 2], &mut rng), &config());
+
+        assert_eq!(a.ring_key().len(), 20);
+        // the ring keys are close up to the discretization of the buildings
+        let key_distance = a
+            .ring_key()
+            .iter()
+            .zip(b.ring_key().iter())
+            .map(|(ka, kb)| (ka - kb) * (ka - kb))
+            .sum::<f32>()
+            .sqrt();
+        let key_norm = a.ring_key().iter().map(|k| k * k).sum::<f32>().sqrt();
+        assert!(key_distance < 0.2 * key_norm);
+        // the sensor is rotated by +1 rad, so the scene is rotated by -1 rad in its frame
+        let (distance, yaw) = a.distance(&b);
+        assert!(distance < 0.2);
+        let sector_angle = std::f64::consts::TAU / 60.0;
+        assert!((yaw + 1.0).abs() <= 1.5 * sector_angle, "yaw {yaw}");
+    }
+
+    #[test]
+    fn test_descriptor_database_retrieval() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let places = (0..10).map(|_| place(&mut rng)).collect::<Vec<_>>();
+
+        let mut database = DescriptorDatabase::new();
+        for buildings in places.iter() {
+            let cloud = scan(buildings, 0.0, [0.0; 2], &mut rng);
+            database.insert(compute_global_descriptor(&cloud, &config()));
+        }
+        assert_eq!(database.len(), 10);
+
+        // revisit each place with a random yaw and a small offset
+        let mut num_correct = 0;
+        for (i, buildings) in places.iter().enumerate() {
+            let yaw = rng.random_range(-std::f64::consts::PI..std::f64::consts::PI);
+            let offset = [rng.random_range(-0.5..0.5), rng.random_range(-0.5..0.5)];
+            let cloud = scan(buildings, yaw, offset, &mut rng);
+            let matches = database.query(&compute_global_descriptor(&cloud, &config()), 3);
+            assert_eq!(matches.len(), 3);
+            assert!(matches[0].distance <= matches[1].distance);
+            if matches[0].index == i {
+                num_correct += 1;
+            }
+        }
+        // retrieval precision at one
+        assert!(num_correct >= 9, "{num_correct} correct matches");
+    }
+}
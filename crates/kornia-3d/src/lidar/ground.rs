@@ -0,0 +1,155 @@
+use super::{range_image::nearest_return_per_cell, LidarScanPattern};
+use crate::pointcloud::PointCloud;
+
+/// Separate the ground points of a LiDAR scan with slope tests on its range image.
+///
+/// The points are projected to the range image of the scan pattern, keeping the nearest
+/// point of each cell. Each column is then traversed from the lowest beam upwards: a cell
+/// is ground if the slope between it and the last ground cell of the column is below
+/// `max_ground_slope_deg`. The lowest cell of a column is ground if the slope to the next
+/// cell is below the threshold. The other points of a cell share the label of the cell.
+///
+/// # Arguments
+///
+/// * `cloud` - The LiDAR scan in the sensor frame, with the z axis up.
+/// * `scan_pattern` - The scan pattern of the LiDAR.
+/// * `max_ground_slope_deg` - The maximum slope of the ground in degrees.
+///
+/// # Returns
+///
+/// The sorted indices of the ground and of the non-ground points. The points outside of the
+/// field of view of the scan pattern are non-ground.
+pub fn remove_ground_range_image(
+    cloud: &PointCloud,
+    scan_pattern: &LidarScanPattern,
+    max_ground_slope_deg: f64,
+) -> (Vec<usize>, Vec<usize>) {
+    let (num_rows, num_cols) = (scan_pattern.num_beams, scan_pattern.num_columns);
+    let points = cloud.points();
+    let horizontal_range = |p: &[f64; 3]| (p[0] * p[0] + p[1] * p[1]).sqrt();
+
+    // range image with the nearest point of each cell
+    let image = nearest_return_per_cell(cloud, scan_pattern);
+
+    let max_slope = max_ground_slope_deg.to_radians();
+    let slope = |a: usize, b: usize| {
+        let (pa, pb) = (&points[a], &points[b]);
+        let dr = (horizontal_range(pb) - horizontal_range(pa)).abs();
+        (pb[2] - pa[2]).abs().atan2(dr)
+    };
+
+    // label the cells column by column from the lowest beam
+    let mut is_ground_cell = vec![false; num_rows * num_cols];
+    for col in 0..num_cols {
+        let column = (0..num_rows)
+            .filter_map(|row| image[row * num_cols + col].map(|i| (row, i)))
+            .collect::<Vec<_>>();
+
+        let mut last_ground = None;
+        for (k, &(row, i)) in column.iter().enumerate() {
+            let is_ground = match last_ground {
+                Some(g) => slope(g, i) <= max_slope,
+                None => {
+                    k == 0
+                        && column
+                            .get(1)
+                            .is_some_and(|&(_, j)| slope(i, j) <= max_slope)
+                }
+            };
+            if is_ground {
+                is_ground_cell[row * num_cols + col] = true;
+                last_ground = Some(i);
+            }
+        }
+    }
+
+    let mut ground = Vec::new();
+    let mut non_ground = Vec::new();
+    for (i, p) in points.iter().enumerate() {
+        match scan_pattern.project(p) {
+            Some((row, col)) if is_ground_cell[row * num_cols + col] => ground.push(i),
+            _ => non_ground.push(i),
+        }
+    }
+
+    (ground, non_ground)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENSOR_HEIGHT: f64 = 1.7;
+    const WALL_DISTANCE: f64 = 10.0;
+    const WALL_HEIGHT: f64 = 2.5;
+
+    fn scan_pattern() -> LidarScanPattern {
+        LidarScanPattern {
+            num_beams: 16,
+            num_columns: 360,
+            min_elevation_deg: -15.0,
+            max_elevation_deg: 15.0,
+        }
+    }
+
+    /// Ray cast a flat ground and a wall in front of the sensor.
+    fn synthetic_scan() -> (PointCloud, Vec<bool>) {
+        let pattern = scan_pattern();
+        let mut points = Vec::new();
+        let mut is_ground = Vec::new();
+        for row in 0..pattern.num_beams {
+            let elevation = (pattern.min_elevation_deg + 2.0 * row as f64).to_radians();
+            for col in 0..pattern.num_columns {
+                let azimuth = ((col as f64 + 0.5) - 180.0).to_radians();
+                let dir = [
+                    elevation.cos() * azimuth.cos(),
+                    elevation.cos() * azimuth.sin(),
+                    elevation.sin(),
+                ];
+
+                let mut hits = Vec::new();
+                if dir[2] < 0.0 {
+                    hits.push((-SENSOR_HEIGHT / dir[2], true));
+                }
+                if dir[0] > 0.0 {
+                    let t = WALL_DISTANCE / dir[0];
+                    let (y, z) = (t * dir[1], t * dir[2]);
+                    if y.abs() < 5.0 && z > -SENSOR_HEIGHT && z < WALL_HEIGHT - SENSOR_HEIGHT {
+                        hits.push((t, false));
+                    }
+                }
+                if let Some(&(t, ground)) = hits.iter().min_by(|a, b| a.0.total_cmp(&b.0)) {
+                    points.push([t * dir[0], t * dir[1], t * dir[2]]);
+                    is_ground.push(ground);
+                }
+            }
+        }
+        (PointCloud::new(points, None, None), is_ground)
+    }
+
+    #[test]
+    fn test_remove_ground_range_image() {
+        let (cloud, is_ground) = synthetic_scan();
+        assert!(is_ground.iter().any(|g| !g));
+
+        let (ground, non_ground) = remove_ground_range_image(&cloud, &scan_pattern(), 10.0);
+        assert_eq!(ground.len() + non_ground.len(), cloud.len());
+
+        // the foot of the wall is indistinguishable from the ground
+        for &i in ground.iter() {
+            let height = cloud.points()[i][2] + SENSOR_HEIGHT;
+            assert!(is_ground[i] || height < 0.3, "point {i} is not ground");
+        }
+        for &i in non_ground.iter() {
+            assert!(!is_ground[i], "point {i} is ground");
+        }
+    }
+
+    #[test]
+    fn test_remove_ground_range_image_outside_fov() {
+        let cloud = PointCloud::new(vec![[1.0, 0.0, 5.0], [0.0, 0.0, 0.0]], None, None);
+        let (ground, non_ground) = remove_ground_range_image(&cloud, &scan_pattern(), 10.0);
+        assert!(ground.is_empty());
+        assert_eq!(non_ground, vec![0, 1]);
+    }
+}
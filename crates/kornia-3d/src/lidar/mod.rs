@@ -0,0 +1,23 @@
+mod angular_resolution;
+pub use angular_resolution::*;
+
+mod deskew;
+pub use deskew::*;
+
+mod global_descriptor;
+pub use global_descriptor::*;
+
+mod ground;
+pub use ground::*;
+
+mod range_image;
+pub use range_image::*;
+
+mod rings;
+pub use rings::*;
+
+mod scan_pattern;
+pub use scan_pattern::*;
+
+mod vegetation;
+pub use vegetation::*;
@@ -0,0 +1,195 @@
+use super::LidarScanPattern;
+use crate::pointcloud::{PointCloud, TimestampedCloud};
+
+/// Project a LiDAR scan to its range image.
+///
+/// Each cell of the range image holds the range of the nearest point projected to it.
+///
+/// # Arguments
+///
+/// * `cloud` - The LiDAR scan in the sensor frame.
+/// * `scan_pattern` - The scan pattern of the LiDAR.
+///
+/// # Returns
+///
+/// The range image of `num_beams` rows and `num_columns` columns in row-major order, where
+/// the row 0 is the lowest beam. The empty cells are zero.
+pub fn project_range_image(cloud: &PointCloud, scan_pattern: &LidarScanPattern) -> Vec<f32> {
+    let points = cloud.points();
+    nearest_return_per_cell(cloud, scan_pattern)
+        .iter()
+        .map(|cell| cell.map_or(0.0, |i| squared_range(&points[i]).sqrt() as f32))
+        .collect()
+}
+
+/// Project a LiDAR scan to its intensity image.
+///
+/// Each cell of the intensity image holds the intensity of the nearest point projected to
+/// it, so that the image is consistent with the range image when a cell has multiple returns.
+/// The image can be used for feature detection and image-based matching of the scans.
+///
+/// # Arguments
+///
+/// * `cloud` - The LiDAR scan in the sensor frame, with the intensity of each point.
+/// * `scan_pattern` - The scan pattern of the LiDAR.
+///
+/// # Returns
+///
+/// The intensity image of `num_beams` rows and `num_columns` columns in row-major order,
+/// where the row 0 is the lowest beam. The empty cells are zero, as are all the cells if the
+/// cloud has no intensities.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::lidar::{project_intensity_image, LidarScanPattern};
+/// use kornia_3d::pointcloud::{PointCloud, TimestampedCloud};
+///
+/// let pattern = LidarScanPattern {
+///     num_beams: 3,
+///     num_columns: 4,
+///     min_elevation_deg: -10.0,
+///     max_elevation_deg: 10.0,
+/// };
+/// let scan = TimestampedCloud {
+///     timestamp_ns: None,
+///     cloud: PointCloud::new(vec![[1.0, 0.0, 0.0], [2.0, 0.0, 0.0]], None, None),
+///     intensities: Some(vec![0.5, 0.9]),
+/// };
+/// let image = project_intensity_image(&scan, &pattern);
+/// assert_eq!(image.len(), 12);
+/// assert_eq!(image[4 + 2], 0.5);
+/// ```
+pub fn project_intensity_image(
+    cloud: &TimestampedCloud,
+    scan_pattern: &LidarScanPattern,
+) -> Vec<f32> {
+    let cells = nearest_return_per_cell(&cloud.cloud, scan_pattern);
+    match &cloud.intensities {
+        Some(intensities) => cells
+            .iter()
+            .map(|cell| cell.map_or(0.0, |i| intensities[i]))
+            .collect(),
+        None => vec![0.0; cells.len()],
+    }
+}
+
+/// Find the index of the nearest point projected to each cell of the range image.
+pub(crate) fn nearest_return_per_cell(
+    cloud: &PointCloud,
+    scan_pattern: &LidarScanPattern,
+) -> Vec<Option<usize>> {
+    let points = cloud.points();
+    let mut image: Vec<Option<usize>> =
+        vec![None; scan_pattern.num_beams * scan_pattern.num_columns];
+    for (i, p) in points.iter().enumerate() {
+        let Some((row, col)) = scan_pattern.project(p) else {
+            continue;
+        };
+        let slot = &mut image[row * scan_pattern.num_columns + col];
+        if slot.map_or(true, |j| squared_range(p) < squared_range(&points[j])) {
+            *slot = Some(i);
+        }
+    }
+    image
+}
+
+fn squared_range(p: &[f64; 3]) -> f64 {
+    p[0] * p[0] + p[1] * p[1] + p[2] * p[2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn scan_pattern() -> LidarScanPattern {
+        LidarScanPattern {
+            num_beams: 16,
+            num_columns: 90,
+            min_elevation_deg: -15.0,
+            max_elevation_deg: 15.0,
+        }
+    }
+
+    #[test]
+    fn test_intensity_image_round_trip() {
+        let pattern = scan_pattern();
+
+        // one return at the center of each cell, with a second farther return in some cells
+        let mut points = Vec::new();
+        let mut intensities = Vec::new();
+        let mut expected = Vec::new();
+        for row in 0..pattern.num_beams {
+            for col in 0..pattern.num_columns {
+                let range = 5.0 + (row as f64 * 0.7 + col as f64 * 0.13).sin();
+                let intensity = ((row * pattern.num_columns + col) % 97) as f32 / 97.0;
+                points.push(pattern.unproject(row, col, range));
+                intensities.push(intensity);
+                expected.push((range, intensity));
+                if col % 3 == 0 {
+                    points.push(pattern.unproject(row, col, range + 2.0));
+                    intensities.push(1.5);
+                }
+            }
+        }
+        let scan = TimestampedCloud {
+            timestamp_ns: None,
+            cloud: PointCloud::new(points, None, None),
+            intensities: Some(intensities),
+        };
+
+        let range_image = project_range_image(&scan.cloud, &pattern);
+        let intensity_image = project_intensity_image(&scan, &pattern);
+        assert_eq!(range_image.len(), pattern.num_beams * pattern.num_columns);
+        assert_eq!(intensity_image.len(), range_image.len());
+
+        // back-project the images and compare with the nearest returns
+        for row in 0..pattern.num_beams {
+            for col in 0..pattern.num_columns {
+                let idx = row * pattern.num_columns + col;
+                let (range, intensity) = expected[idx];
+                assert_eq!(intensity_image[idx], intensity);
+
+                let p = pattern.unproject(row, col, range_image[idx] as f64);
+                let q = pattern.unproject(row, col, range);
+                for k in 0..3 {
+                    assert_relative_eq!(p[k], q[k], epsilon = 1e-5);
+                }
+                assert_eq!(pattern.project(&p), Some((row, col)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_intensity_image_empty_cells() {
+        let pattern = scan_pattern();
+        let scan = TimestampedCloud {
+            timestamp_ns: None,
+            cloud: PointCloud::new(
+                vec![pattern.unproject(3, 7, 2.0), [0.0, 0.0, 9.0]],
+                None,
+                None,
+            ),
+            intensities: Some(vec![0.25, 0.75]),
+        };
+
+        let image = project_intensity_image(&scan, &pattern);
+        for (idx, value) in image.iter().enumerate() {
+            let expected = if idx == 3 * pattern.num_columns + 7 {
+                0.25
+            } else {
+                0.0
+            };
+            assert_eq!(*value, expected);
+        }
+
+        let scan = TimestampedCloud {
+            intensities: None,
+            ..scan
+        };
+        assert!(project_intensity_image(&scan, &pattern)
+            .iter()
+            .all(|v| *v == 0.0));
+    }
+}
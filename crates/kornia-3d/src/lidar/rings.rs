@@ -0,0 +1,184 @@
+use crate::pointcloud::PointCloud;
+
+/// Maximum number of iterations of the k-means refinement.
+const MAX_KMEANS_ITERATIONS: usize = 100;
+
+/// Partition an unstructured scan of a spinning LiDAR into its beam rings.
+///
+/// The points of a ring share the elevation angle of their beam, so the rings are the
+/// clusters of a k-means on the elevation angles only. The k-means is initialized by
+/// splitting the sorted elevations at their `n_rings - 1` largest gaps, which already
+/// separates well spaced beams, and refined with Lloyd iterations. The beams need not be
+/// uniformly spaced, unlike with [`super::LidarScanPattern`].
+///
+/// # Arguments
+///
+/// * `cloud` - The LiDAR scan in the sensor frame, with the z axis up.
+/// * `n_rings` - The number of beams of the LiDAR.
+///
+/// # Returns
+///
+/// The sorted indices of the points of each ring, from the lowest beam to the highest.
+/// The points at the origin belong to no ring. With fewer distinct elevations than rings,
+/// the last rings are empty.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::lidar::segment_rings;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = vec![[10.0, 0.0, 1.0], [0.0, 5.0, -0.5], [-4.0, 0.0, 0.4], [0.0, -20.0, -1.0]];
+/// let rings = segment_rings(&PointCloud::new(points, None, None), 2);
+/// assert_eq!(rings, vec![vec![1, 3], vec![0, 2]]);
+/// ```
+pub fn segment_rings(cloud: &PointCloud, n_rings: usize) -> Vec<Vec<usize>> {
+    let mut rings = vec![Vec::new(); n_rings];
+    if n_rings == 0 {
+        return rings;
+    }
+
+    let mut elevations = cloud
+        .points()
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.iter().any(|x| *x != 0.0))
+        .map(|(i, p)| (p[2].atan2((p[0] * p[0] + p[1] * p[1]).sqrt()), i))
+        .collect::<Vec<_>>();
+    if elevations.is_empty() {
+        return rings;
+    }
+    elevations.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    // split at the largest gaps between consecutive elevations
+    let mut gaps = (1..elevations.len())
+        .map(|k| (elevations[k].0 - elevations[k - 1].0, k))
+        .filter(|(gap, _)| *gap > 0.0)
+        .collect::<Vec<_>>();
+    gaps.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    let mut splits = gaps
+        .iter()
+        .take(n_rings - 1)
+        .map(|(_, k)| *k)
+        .collect::<Vec<_>>();
+    splits.sort_unstable();
+
+    // the centers of the initial clusters, in increasing order
+    let mut centers = Vec::with_capacity(n_rings);
+    let mut start = 0;
+    for end in splits.into_iter().chain(std::iter::once(elevations.len())) {
+        let cluster = &elevations[start..end];
+        centers.push(cluster.iter().map(|(e, _)| e).sum::<f64>() / cluster.len() as f64);
+        start = end;
+    }
+
+    // Lloyd iterations, where each cluster is a range of the sorted elevations
+    let mut bounds = Vec::new();
+    for _ in 0..MAX_KMEANS_ITERATIONS {
+        let new_bounds = centers
+            .windows(2)
+            .map(|w| elevations.partition_point(|(e, _)| *e < 0.5 * (w[0] + w[1])))
+            .collect::<Vec<_>>();
+        if new_bounds == bounds {
+            break;
+        }
+        bounds = new_bounds;
+
+        let mut start = 0;
+        for (center, &end) in centers
+            .iter_mut()
+            .zip(bounds.iter().chain(std::iter::once(&elevations.len())))
+        {
+            let cluster = &elevations[start..end];
+            if !cluster.is_empty() {
+                *center = cluster.iter().map(|(e, _)| e).sum::<f64>() / cluster.len() as f64;
+            }
+            start = end;
+        }
+    }
+
+    let mut start = 0;
+    for (ring, &end) in rings
+        .iter_mut()
+        .zip(bounds.iter().chain(std::iter::once(&elevations.len())))
+    {
+        *ring = elevations[start..end].iter().map(|(_, i)| *i).collect();
+        ring.sort_unstable();
+        start = end;
+    }
+    rings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// A scan of random ranges with the given beam elevations in degrees, and the beam of
+    /// each point.
+    fn scan(elevations_deg: &[f64], noise_deg: f64) -> (PointCloud, Vec<usize>) {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut points = Vec::new();
+        let mut beams = Vec::new();
+        for col in 0..360 {
+            let azimuth = (col as f64).to_radians();
+            for (beam, elevation) in elevations_deg.iter().enumerate() {
+                // some returns are missing
+                if rng.random::<f64>() < 0.2 {
+                    continue;
+                }
+                let elevation = (elevation + rng.random_range(-noise_deg..=noise_deg)).to_radians();
+                let range = rng.random_range(2.0..60.0);
+                points.push([
+                    range * elevation.cos() * azimuth.cos(),
+                    range * elevation.cos() * azimuth.sin(),
+                    range * elevation.sin(),
+                ]);
+                beams.push(beam);
+            }
+        }
+        (PointCloud::new(points, None, None), beams)
+    }
+
+    fn check_rings(rings: &[Vec<usize>], beams: &[usize]) {
+        assert_eq!(rings.iter().map(|r| r.len()).sum::<usize>(), beams.len());
+        for (beam, ring) in rings.iter().enumerate() {
+            assert!(!ring.is_empty());
+            assert!(ring.iter().all(|&i| beams[i] == beam));
+        }
+    }
+
+    #[test]
+    fn test_segment_rings_uniform_beams() {
+        let elevations = (0..16).map(|b| -15.0 + 2.0 * b as f64).collect::<Vec<_>>();
+        let (cloud, beams) = scan(&elevations, 0.1);
+        let rings = segment_rings(&cloud, 16);
+        assert_eq!(rings.len(), 16);
+        check_rings(&rings, &beams);
+    }
+
+    #[test]
+    fn test_segment_rings_dense_center() {
+        // the beams are denser around the horizon, as on many 32 beams sensors
+        let elevations = (0..32)
+            .map(|b| {
+                let x = (b as f64 - 15.5) / 15.5;
+                20.0 * x * x.abs()
+            })
+            .collect::<Vec<_>>();
+        let (cloud, beams) = scan(&elevations, 0.005);
+        let rings = segment_rings(&cloud, 32);
+        check_rings(&rings, &beams);
+    }
+
+    #[test]
+    fn test_segment_rings_degenerate() {
+        let empty = PointCloud::new(vec![], None, None);
+        assert!(segment_rings(&empty, 0).is_empty());
+        assert_eq!(segment_rings(&empty, 2), vec![Vec::<usize>::new(); 2]);
+
+        // the origin is skipped and the extra rings are empty
+        let cloud = PointCloud::new(vec![[0.0; 3], [1.0, 0.0, 0.0], [2.0, 0.0, 0.0]], None, None);
+        assert_eq!(segment_rings(&cloud, 3), vec![vec![1, 2], vec![], vec![]]);
+    }
+}
@@ -0,0 +1,141 @@
+/// The scan pattern of a rotating multi-beam LiDAR.
+///
+/// The beams are assumed to be uniformly spaced in elevation and the columns uniformly
+/// spaced in azimuth over a full revolution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LidarScanPattern {
+    /// The number of beams, i.e. rows of the range image.
+    pub num_beams: usize,
+    /// The number of azimuth steps per revolution, i.e. columns of the range image.
+    pub num_columns: usize,
+    /// The elevation of the lowest beam in degrees.
+    pub min_elevation_deg: f64,
+    /// The elevation of the highest beam in degrees.
+    pub max_elevation_deg: f64,
+}
+
+impl LidarScanPattern {
+    /// Project a point in the sensor frame to its cell of the range image.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point in the sensor frame.
+    ///
+    /// # Returns
+    ///
+    /// The row and the column of the point, where the row 0 is the lowest beam, or `None`
+    /// if the point is at the origin or outside of the vertical field of view.
+    pub fn project(&self, point: &[f64; 3]) -> Option<(usize, usize)> {
+        if self.num_beams == 0 || self.num_columns == 0 {
+            return None;
+        }
+        let horizontal_range = (point[0] * point[0] + point[1] * point[1]).sqrt();
+        if horizontal_range == 0.0 && point[2] == 0.0 {
+            return None;
+        }
+
+        let elevation = point[2].atan2(horizontal_range).to_degrees();
+        let row = if self.num_beams == 1 {
+            0.0
+        } else {
+            let beam_step =
+                (self.max_elevation_deg - self.min_elevation_deg) / (self.num_beams - 1) as f64;
+            ((elevation - self.min_elevation_deg) / beam_step).round()
+        };
+        if row < 0.0 || row >= self.num_beams as f64 {
+            return None;
+        }
+
+        let azimuth = point[1].atan2(point[0]) + std::f64::consts::PI;
+        let col = (azimuth / std::f64::consts::TAU * self.num_columns as f64) as usize;
+
+        Some((row as usize, col % self.num_columns))
+    }
+
+    /// Get the direction of the center of a cell of the range image.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The row of the cell, where the row 0 is the lowest beam.
+    /// * `col` - The column of the cell.
+    ///
+    /// # Returns
+    ///
+    /// The elevation and the azimuth of the center of the cell in radians.
+    pub fn cell_angles(&self, row: usize, col: usize) -> (f64, f64) {
+        let beam_step = if self.num_beams > 1 {
+            (self.max_elevation_deg - self.min_elevation_deg) / (self.num_beams - 1) as f64
+        } else {
+            0.0
+        };
+        let elevation = (self.min_elevation_deg + row as f64 * beam_step).to_radians();
+        let azimuth = (col as f64 + 0.5) / self.num_columns as f64 * std::f64::consts::TAU
+            - std::f64::consts::PI;
+        (elevation, azimuth)
+    }
+
+    /// Back-project a cell of the range image to a point in the sensor frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The row of the cell, where the row 0 is the lowest beam.
+    /// * `col` - The column of the cell.
+    /// * `range` - The range of the point.
+    ///
+    /// # Returns
+    ///
+    /// The point at the given range along the direction of the center of the cell.
+    pub fn unproject(&self, row: usize, col: usize, range: f64) -> [f64; 3] {
+        let (elevation, azimuth) = self.cell_angles(row, col);
+        [
+            range * elevation.cos() * azimuth.cos(),
+            range * elevation.cos() * azimuth.sin(),
+            range * elevation.sin(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn scan_pattern() -> LidarScanPattern {
+        LidarScanPattern {
+            num_beams: 16,
+            num_columns: 360,
+            min_elevation_deg: -15.0,
+            max_elevation_deg: 15.0,
+        }
+    }
+
+    #[test]
+    fn test_scan_pattern_project() {
+        let pattern = scan_pattern();
+        assert_eq!(pattern.project(&[0.0, 0.0, 0.0]), None);
+        // above the field of view
+        assert_eq!(pattern.project(&[1.0, 0.0, 1.0]), None);
+
+        let (row, col) = pattern.project(&[1.0, 0.0, 0.0]).unwrap();
+        assert_eq!(row, 8);
+        assert_eq!(col, 180);
+        let (row, _) = pattern
+            .project(&[1.0, 0.0, -(15f64.to_radians().tan())])
+            .unwrap();
+        assert_eq!(row, 0);
+    }
+
+    #[test]
+    fn test_scan_pattern_unproject() {
+        let pattern = scan_pattern();
+        let (elevation, azimuth) = pattern.cell_angles(15, 0);
+        assert_relative_eq!(elevation, 15f64.to_radians());
+        assert_relative_eq!(azimuth, (-179.5f64).to_radians());
+
+        for (row, col) in [(0, 0), (8, 180), (15, 359), (3, 91)] {
+            let p = pattern.unproject(row, col, 4.0);
+            assert_relative_eq!(p.iter().map(|v| v * v).sum::<f64>().sqrt(), 4.0);
+            assert_eq!(pattern.project(&p), Some((row, col)));
+        }
+    }
+}
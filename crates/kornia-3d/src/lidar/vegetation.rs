@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use crate::{kdtree::KdTree, pointcloud::PointCloud};
+
+/// Maximum height above the ground of a ground point.
+const GROUND_HEIGHT: f64 = 0.2;
+
+/// Maximum height above the ground of low vegetation such as grass and shrubs.
+const LOW_VEGETATION_HEIGHT: f64 = 1.0;
+
+/// Maximum height above the ground of medium vegetation such as bushes and young trees.
+const MEDIUM_VEGETATION_HEIGHT: f64 = 3.0;
+
+/// Minimum fraction of ground returns in a column for the points above the ground to be
+/// vegetation.
+const MIN_PENETRATION_RATIO: f64 = 0.1;
+
+/// The class of a point of an outdoor LiDAR scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VegetationLabel {
+    /// At the height of the ground.
+    Ground,
+    /// Vegetation at most 1 m above the ground.
+    LowVegetation,
+    /// Vegetation between 1 m and 3 m above the ground.
+    MediumVegetation,
+    /// Vegetation more than 3 m above the ground.
+    HighVegetation,
+    /// An opaque structure above the ground, such as a roof.
+    NonVegetation,
+}
+
+/// Classify the points of a LiDAR scan into vegetation layers by their height above ground.
+///
+/// The height of a point is its elevation above the nearest ground point in the horizontal
+/// plane. The points within 0.2 m of the ground are ground, and the points above it are
+/// split into low, medium and high vegetation at 1 m and 3 m. Vegetation lets a part of the
+/// laser pulses through to the ground, unlike opaque structures, so the points above the
+/// ground of a vertical column of `voxel_size` are non-vegetation when less than a tenth of
+/// the returns of the column are ground returns.
+///
+/// # Arguments
+///
+/// * `cloud` - The LiDAR scan, with the z axis up.
+/// * `ground_cloud` - The ground points of the scan, e.g. from a ground segmentation.
+/// * `voxel_size` - The horizontal size of the columns of the density test.
+///
+/// # Returns
+///
+/// The label of each point of `cloud`. All the points are non-vegetation if there is no
+/// ground point or the voxel size is not positive.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::lidar::{classify_vegetation, VegetationLabel};
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let ground = PointCloud::new(vec![[0.0, 0.0, 0.0], [0.5, 0.5, 0.0]], None, None);
+/// let cloud = PointCloud::new(vec![[0.1, 0.1, 0.05], [0.4, 0.4, 5.0]], None, None);
+/// assert_eq!(
+///     classify_vegetation(&cloud, &ground, 1.0),
+///     vec![VegetationLabel::Ground, VegetationLabel::HighVegetation]
+/// );
+/// ```
+pub fn classify_vegetation(
+    cloud: &PointCloud,
+    ground_cloud: &PointCloud,
+    voxel_size: f64,
+) -> Vec<VegetationLabel> {
+    if ground_cloud.is_empty() || voxel_size.is_nan() || voxel_size <= 0.0 {
+        return vec![VegetationLabel::NonVegetation; cloud.len()];
+    }
+
+    // the ground in the horizontal plane
+    let ground_plane = ground_cloud
+        .points()
+        .iter()
+        .map(|p| [p[0], p[1], 0.0])
+        .collect::<Vec<_>>();
+    let index = KdTree::new(&ground_plane);
+    let heights = cloud
+        .points()
+        .iter()
+        .map(|p| {
+            index
+                .nearest_one(&[p[0], p[1], 0.0])
+                .map_or(f64::INFINITY, |nn| {
+                    p[2] - ground_cloud.points()[nn.index][2]
+                })
+        })
+        .collect::<Vec<_>>();
+
+    // the returns of each column, on the ground and above it
+    let column = |p: &[f64; 3]| {
+        [
+            (p[0] / voxel_size).floor() as i64,
+            (p[1] / voxel_size).floor() as i64,
+        ]
+    };
+    let mut returns: HashMap<[i64; 2], (usize, usize)> = HashMap::new();
+    for p in ground_cloud.points().iter() {
+        returns.entry(column(p)).or_default().0 += 1;
+    }
+    for (p, height) in cloud.points().iter().zip(heights.iter()) {
+        if *height > GROUND_HEIGHT {
+            returns.entry(column(p)).or_default().1 += 1;
+        }
+    }
+
+    cloud
+        .points()
+        .iter()
+        .zip(heights)
+        .map(|(p, height)| {
+            if height <= GROUND_HEIGHT {
+                return VegetationLabel::Ground;
+            }
+            let (num_ground, num_above) = returns.get(&column(p)).copied().unwrap_or_default();
+            let penetration = num_ground as f64 / (num_ground + num_above) as f64;
+            if penetration < MIN_PENETRATION_RATIO {
+                VegetationLabel::NonVegetation
+            } else if height <= LOW_VEGETATION_HEIGHT {
+                VegetationLabel::LowVegetation
+            } else if height <= MEDIUM_VEGETATION_HEIGHT {
+                VegetationLabel::MediumVegetation
+            } else {
+                VegetationLabel::HighVegetation
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_classify_vegetation_layers() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // a gently sloped terrain, hidden under a roof on x > 8
+        let terrain = |x: f64, y: f64| 0.05 * x + 0.02 * y;
+        let ground = (0..120 * 40)
+            .map(|i| {
+                let (x, y) = ((i % 120) as f64 * 0.1, (i / 120) as f64 * 0.1);
+                [x, y, terrain(x, y)]
+            })
+            .filter(|p| p[0] < 8.0)
+            .collect::<Vec<_>>();
+
+        // layers of vegetation in strips along x, and the flat roof
+        let mut points = ground.clone();
+        let mut expected = vec![VegetationLabel::Ground; ground.len()];
+        let layers = [
+            (0.0, 0.3..0.9, VegetationLabel::LowVegetation),
+            (2.0, 1.2..2.8, VegetationLabel::MediumVegetation),
+            (4.0, 4.0..9.0, VegetationLabel::HighVegetation),
+        ];
+        for (x0, heights, label) in layers {
+            for _ in 0..2000 {
+                let (x, y): (f64, f64) =
+                    (rng.random_range(x0..x0 + 2.0), rng.random_range(0.0..4.0));
+                let z = terrain(x, y) + rng.random_range(heights.clone());
+                points.push([x, y, z]);
+                expected.push(label);
+            }
+        }
+        for _ in 0..2000 {
+            let (x, y): (f64, f64) = (rng.random_range(8.5..12.0), rng.random_range(0.0..4.0));
+            points.push([x, y, 4.0]);
+            expected.push(VegetationLabel::NonVegetation);
+        }
+
+        let cloud = PointCloud::new(points, None, None);
+        let ground = PointCloud::new(ground, None, None);
+        let labels = classify_vegetation(&cloud, &ground, 0.5);
+        assert_eq!(labels, expected);
+    }
+
+    #[test]
+    fn test_classify_vegetation_no_ground() {
+        let cloud = PointCloud::new(vec![[0.0; 3], [1.0; 3]], None, None);
+        let empty = PointCloud::new(vec![], None, None);
+        assert_eq!(
+            classify_vegetation(&cloud, &empty, 0.5),
+            vec![VegetationLabel::NonVegetation; 2]
+        );
+    }
+}
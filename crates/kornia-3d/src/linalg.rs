@@ -1,3 +1,14 @@
+use kornia_linalg::{
+    eigen::{eigh2_f64, eigh3_f64},
+    DMat2, DMat3,
+};
+
+pub use kornia_linalg::error::LinalgError;
+
+/// The tolerance on the pivots of [`solve_linear`], relative to the largest entry of the
+/// matrix.
+pub const SOLVE_LINEAR_EPSILON: f64 = 1e-14;
+
 /// Transform a set of 3D points using a rotation and translation.
 ///
 /// # Arguments
@@ -9,6 +20,10 @@
 ///
 /// PRECONDITION: dst_points is a pre-allocated vector of the same size as source.
 ///
+/// # Errors
+///
+/// Returns [`LinalgError::LengthMismatch`] if `dst_points` and `src_points` have different lengths.
+///
 /// Example:
 ///
 /// ```
@@ -25,9 +40,12 @@ pub fn transform_points3d(
     dst_r_src: &[[f64; 3]; 3],
     dst_t_src: &[f64; 3],
     dst_points: &mut [[f64; 3]],
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), LinalgError> {
     if dst_points.len() != src_points.len() {
-        return Err("dst_points must have the same length as src_points".into());
+        return Err(LinalgError::LengthMismatch {
+            src: src_points.len(),
+            dst: dst_points.len(),
+        });
     }
 
     for (point_dst, point_src) in dst_points.iter_mut().zip(src_points.iter()) {
@@ -39,6 +57,85 @@ pub fn transform_points3d(
     Ok(())
 }
 
+/// Transform a set of 3D points into a vector, optionally resizing it to fit.
+///
+/// # Arguments
+///
+/// * `src_points` - A set of 3D points to be transformed.
+/// * `dst_r_src` - A 3x3 rotation matrix.
+/// * `dst_t_src` - A 3D translation vector.
+/// * `dst_points` - The vector to store the transformed 3D points.
+/// * `resize` - If true, `dst_points` is resized to the length of `src_points`. The vector
+///   is only reallocated if its capacity is not enough.
+///
+/// # Errors
+///
+/// Returns [`LinalgError::LengthMismatch`] if `resize` is false and the lengths differ.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::linalg::transform_points3d_into;
+///
+/// let src_points = vec![[2.0, 2.0, 2.0], [3.0, 4.0, 5.0]];
+/// let rotation = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let translation = [1.0, 0.0, 0.0];
+/// let mut dst_points = Vec::new();
+/// transform_points3d_into(&src_points, &rotation, &translation, &mut dst_points, true).unwrap();
+/// assert_eq!(dst_points, vec![[3.0, 2.0, 2.0], [4.0, 4.0, 5.0]]);
+/// ```
+pub fn transform_points3d_into(
+    src_points: &[[f64; 3]],
+    dst_r_src: &[[f64; 3]; 3],
+    dst_t_src: &[f64; 3],
+    dst_points: &mut Vec<[f64; 3]>,
+    resize: bool,
+) -> Result<(), LinalgError> {
+    if resize {
+        dst_points.resize(src_points.len(), [0.0; 3]);
+    }
+    transform_points3d(src_points, dst_r_src, dst_t_src, dst_points)
+}
+
+/// Transform a set of 3D points into a newly allocated vector.
+///
+/// # Arguments
+///
+/// * `src_points` - A set of 3D points to be transformed.
+/// * `dst_r_src` - A 3x3 rotation matrix.
+/// * `dst_t_src` - A 3D translation vector.
+///
+/// # Returns
+///
+/// The transformed 3D points.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::linalg::transform_points3d_vec;
+///
+/// let src_points = vec![[2.0, 2.0, 2.0]];
+/// let rotation = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let dst_points = transform_points3d_vec(&src_points, &rotation, &[0.0, 0.0, 1.0]);
+/// assert_eq!(dst_points, vec![[2.0, 2.0, 3.0]]);
+/// ```
+pub fn transform_points3d_vec(
+    src_points: &[[f64; 3]],
+    dst_r_src: &[[f64; 3]; 3],
+    dst_t_src: &[f64; 3],
+) -> Vec<[f64; 3]> {
+    src_points
+        .iter()
+        .map(|p| {
+            [
+                dot_product3(&dst_r_src[0], p) + dst_t_src[0],
+                dot_product3(&dst_r_src[1], p) + dst_t_src[1],
+                dot_product3(&dst_r_src[2], p) + dst_t_src[2],
+            ]
+        })
+        .collect()
+}
+
 /// Compute the dot product of two 3D vectors.
 ///
 /// # Arguments
@@ -291,9 +388,210 @@ pub fn normalize_mat33_inplace(m: &mut [[f64; 3]; 3]) {
     mat33_div_scalar_inplace(m, norm);
 }
 
+/// Compute the eigen decomposition of a symmetric 2x2 matrix.
+///
+/// The decomposition is the one of [`kornia_linalg::eigen::eigh2_f64`], on row-major arrays.
+///
+/// # Arguments
+///
+/// * `m` - The symmetric 2x2 matrix. Only the upper triangle is used.
+///
+/// # Returns
+///
+/// The eigenvalues sorted in ascending order and the corresponding unit eigenvectors as rows.
+///
+/// # Example
+///
+/// ```
+/// use kornia_3d::linalg::eigen_symmetric22;
+///
+/// let m = [[4.0, 0.0], [0.0, -2.0]];
+/// let (eigenvalues, eigenvectors) = eigen_symmetric22(&m);
+/// assert_eq!(eigenvalues, [-2.0, 4.0]);
+/// assert_eq!(eigenvectors[0][1].abs(), 1.0);
+/// ```
+pub fn eigen_symmetric22(m: &[[f64; 2]; 2]) -> ([f64; 2], [[f64; 2]; 2]) {
+    let symmetric = [[m[0][0], m[0][1]], [m[0][1], m[1][1]]];
+    let eigh = eigh2_f64(&DMat2::from_cols_array_2d(&symmetric));
+    let eigenvectors = eigh.eigenvectors();
+    (
+        eigh.eigenvalues().to_array(),
+        [0, 1].map(|i| eigenvectors.col(i).to_array()),
+    )
+}
+
+/// Compute the eigen decomposition of a symmetric 3x3 matrix.
+///
+/// The decomposition is the one of [`kornia_linalg::eigen::eigh3_f64`], on row-major arrays.
+///
+/// # Arguments
+///
+/// * `m` - The symmetric 3x3 matrix. Only the upper triangle is used.
+///
+/// # Returns
+///
+/// The eigenvalues sorted in ascending order and the corresponding unit eigenvectors as rows.
+///
+/// # Example
+///
+/// ```
+/// use kornia_3d::linalg::eigen_symmetric33;
+///
+/// let m = [[2.0, 0.0, 0.0], [0.0, 3.0, 0.0], [0.0, 0.0, 1.0]];
+/// let (eigenvalues, eigenvectors) = eigen_symmetric33(&m);
+/// assert_eq!(eigenvalues, [1.0, 2.0, 3.0]);
+/// assert_eq!(eigenvectors[0], [0.0, 0.0, 1.0]);
+/// ```
+pub fn eigen_symmetric33(m: &[[f64; 3]; 3]) -> ([f64; 3], [[f64; 3]; 3]) {
+    // the symmetric matrix of the upper triangle, whose rows are also its columns
+    let symmetric = std::array::from_fn(|i| std::array::from_fn(|j| m[i.min(j)][i.max(j)]));
+    let eigh = eigh3_f64(&DMat3::from_cols_array_2d(&symmetric));
+    let eigenvectors = eigh.eigenvectors();
+    (
+        eigh.eigenvalues().to_array(),
+        [0, 1, 2].map(|i| eigenvectors.col(i).to_array()),
+    )
+}
+
+/// Compute the eigen decomposition of a symmetric NxN matrix.
+///
+/// The 2x2 and 3x3 matrices are decomposed by [`eigen_symmetric22`] and
+/// [`eigen_symmetric33`]. The larger matrices use the cyclic Jacobi method, which is
+/// accurate for the small matrices used in covariance analysis and least squares fitting.
+///
+/// # Arguments
+///
+/// * `m` - The symmetric NxN matrix. Only the upper triangle is used.
+///
+/// # Returns
+///
+/// The eigenvalues sorted in ascending order and the corresponding unit eigenvectors as rows.
+pub fn eigen_symmetric<const N: usize>(m: &[[f64; N]; N]) -> ([f64; N], [[f64; N]; N]) {
+    // N is known at compile time, so only one branch is kept
+    if N == 2 {
+        let (eigenvalues, eigenvectors) =
+            eigen_symmetric22(&std::array::from_fn(|i| std::array::from_fn(|j| m[i][j])));
+        return resize_eigen(&eigenvalues, &eigenvectors);
+    }
+    if N == 3 {
+        let (eigenvalues, eigenvectors) =
+            eigen_symmetric33(&std::array::from_fn(|i| std::array::from_fn(|j| m[i][j])));
+        return resize_eigen(&eigenvalues, &eigenvectors);
+    }
+
+    let mut a = *m;
+    for i in 0..N {
+        for j in 0..i {
+            a[i][j] = m[j][i];
+        }
+    }
+    // the columns of v are the eigenvectors
+    let mut v = [[0.0; N]; N];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for _ in 0..100 {
+        let mut off = 0.0;
+        let mut diag = 0.0;
+        for (i, row) in a.iter().enumerate() {
+            diag += row[i] * row[i];
+            off += row[i + 1..].iter().map(|x| x * x).sum::<f64>();
+        }
+        if off <= f64::EPSILON * f64::EPSILON * diag || off < f64::MIN_POSITIVE {
+            break;
+        }
+
+        for p in 0..N {
+            for q in p + 1..N {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                // rotation that zeroes the (p, q) element
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (row_p, row_q) = (a[p], a[q]);
+                for k in 0..N {
+                    a[p][k] = c * row_p[k] - s * row_q[k];
+                    a[q][k] = s * row_p[k] + c * row_q[k];
+                }
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut order = [0; N];
+    for (i, o) in order.iter_mut().enumerate() {
+        *o = i;
+    }
+    order.sort_by(|&i, &j| a[i][i].total_cmp(&a[j][j]));
+
+    let eigenvalues = order.map(|i| a[i][i]);
+    let eigenvectors = order.map(|i| {
+        let mut e = [0.0; N];
+        for (k, value) in e.iter_mut().enumerate() {
+            *value = v[k][i];
+        }
+        e
+    });
+
+    (eigenvalues, eigenvectors)
+}
+
+/// Copy an eigen decomposition of size M into arrays of size N, for N == M.
+fn resize_eigen<const M: usize, const N: usize>(
+    eigenvalues: &[f64; M],
+    eigenvectors: &[[f64; M]; M],
+) -> ([f64; N], [[f64; N]; N]) {
+    (
+        std::array::from_fn(|i| eigenvalues[i]),
+        std::array::from_fn(|i| std::array::from_fn(|j| eigenvectors[i][j])),
+    )
+}
+
+/// Solve a square linear system `A x = b` with Gaussian elimination and partial pivoting.
+///
+/// The system is solved by [`kornia_linalg::solve::solve_linear`] with the tolerance
+/// [`SOLVE_LINEAR_EPSILON`] on the pivots.
+///
+/// # Arguments
+///
+/// * `a` - The NxN matrix of the system.
+/// * `b` - The right hand side vector.
+///
+/// # Returns
+///
+/// The solution of the system, or `None` if the matrix is singular.
+///
+/// # Example
+///
+/// ```
+/// use kornia_3d::linalg::solve_linear;
+///
+/// let a = [[2.0, 0.0], [0.0, 4.0]];
+/// let x = solve_linear(&a, &[1.0, 2.0]).unwrap();
+/// assert_eq!(x, [0.5, 0.5]);
+/// ```
+pub fn solve_linear<const N: usize>(a: &[[f64; N]; N], b: &[f64; N]) -> Option<[f64; N]> {
+    kornia_linalg::solve::solve_linear(a, b, SOLVE_LINEAR_EPSILON).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use approx::assert_relative_eq;
 
     #[test]
     fn test_dot_product3() {
@@ -387,6 +685,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_eigen_symmetric33() {
+        let m = [[4.0, 1.0, -2.0], [1.0, 3.0, 0.5], [-2.0, 0.5, 1.0]];
+        let (eigenvalues, eigenvectors) = eigen_symmetric33(&m);
+
+        assert!(eigenvalues[0] <= eigenvalues[1] && eigenvalues[1] <= eigenvalues[2]);
+        assert_relative_eq!(
+            eigenvalues.iter().sum::<f64>(),
+            m[0][0] + m[1][1] + m[2][2],
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            eigenvalues.iter().product::<f64>(),
+            det_mat33(&m),
+            epsilon = 1e-12
+        );
+
+        for (lambda, v) in eigenvalues.iter().zip(eigenvectors.iter()) {
+            let mut mv = [0.0; 3];
+            mat33_mul_vec3(&m, v, &mut mv);
+            assert_relative_eq!(dot_product3(v, v), 1.0, epsilon = 1e-12);
+            for k in 0..3 {
+                assert_relative_eq!(mv[k], lambda * v[k], epsilon = 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_eigen_symmetric_dispatch() {
+        let m2 = [[2.0, -0.5], [-0.5, 1.0]];
+        assert_eq!(eigen_symmetric(&m2), eigen_symmetric22(&m2));
+        let (eigenvalues, _) = eigen_symmetric22(&m2);
+        assert_relative_eq!(eigenvalues[0] + eigenvalues[1], 3.0, epsilon = 1e-12);
+        assert_relative_eq!(eigenvalues[0] * eigenvalues[1], 1.75, epsilon = 1e-12);
+
+        let m3 = [[4.0, 1.0, -2.0], [1.0, 3.0, 0.5], [-2.0, 0.5, 1.0]];
+        assert_eq!(eigen_symmetric(&m3), eigen_symmetric33(&m3));
+    }
+
+    #[test]
+    fn test_solve_linear() {
+        let a = [[0.0, 2.0, 1.0], [1.0, -1.0, 0.0], [3.0, 0.0, 4.0]];
+        let expected = [1.0, -2.0, 0.5];
+        let mut b = [0.0; 3];
+        mat33_mul_vec3(&a, &expected, &mut b);
+
+        let x = solve_linear(&a, &b).expect("non singular");
+        for k in 0..3 {
+            assert_relative_eq!(x[k], expected[k], epsilon = 1e-12);
+        }
+
+        let singular = [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]];
+        assert!(solve_linear(&singular, &b).is_none());
+    }
+
     #[test]
     fn test_transform_points_identity() -> Result<(), Box<dyn std::error::Error>> {
         let src_points = vec![[2.0, 2.0, 2.0], [3.0, 4.0, 5.0]];
@@ -400,6 +753,59 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_transform_points_length_mismatch() {
+        let src_points = vec![[2.0, 2.0, 2.0], [3.0, 4.0, 5.0]];
+        let rotation = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let mut dst_points = vec![[0.0; 3]; 3];
+        let err = transform_points3d(&src_points, &rotation, &[0.0; 3], &mut dst_points)
+            .expect_err("lengths differ");
+        assert!(matches!(
+            err,
+            LinalgError::LengthMismatch { src: 2, dst: 3 }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Length mismatch: src_points has 2 points but dst_points has 3"
+        );
+
+        let mut dst_points = Vec::new();
+        let err =
+            transform_points3d_into(&src_points, &rotation, &[0.0; 3], &mut dst_points, false)
+                .expect_err("resize not permitted");
+        assert!(matches!(
+            err,
+            LinalgError::LengthMismatch { src: 2, dst: 0 }
+        ));
+    }
+
+    #[test]
+    fn test_transform_points_into_resize() -> Result<(), Box<dyn std::error::Error>> {
+        let src_points = vec![[2.0, 2.0, 2.0], [3.0, 4.0, 5.0]];
+        let rotation = [[1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]];
+        let translation = [1.0, 2.0, 3.0];
+        let expected = transform_points3d_vec(&src_points, &rotation, &translation);
+
+        // grows and shrinks to the source length
+        let mut dst_points = vec![[9.0; 3]; 5];
+        transform_points3d_into(&src_points, &rotation, &translation, &mut dst_points, true)?;
+        assert_eq!(dst_points, expected);
+
+        let mut dst_points = vec![[9.0; 3]; 1];
+        transform_points3d_into(&src_points, &rotation, &translation, &mut dst_points, true)?;
+        assert_eq!(dst_points, expected);
+
+        // no reallocation when the capacity suffices
+        let mut dst_points = Vec::with_capacity(16);
+        let ptr = dst_points.as_ptr();
+        transform_points3d_into(&src_points, &rotation, &translation, &mut dst_points, true)?;
+        assert_eq!(dst_points.as_ptr(), ptr);
+        assert_eq!(dst_points.capacity(), 16);
+        assert_eq!(dst_points, expected);
+
+        Ok(())
+    }
+
     #[test]
     fn test_transform_points_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
         let src_points = vec![[2.0, 2.0, 2.0], [3.0, 4.0, 5.0]];
@@ -0,0 +1,245 @@
+use crate::{
+    pointcloud::PointCloud,
+    voxel::{Aabb, VoxelGrid},
+};
+
+/// The label of a voxel in a free space map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FreespaceLabel {
+    /// A ray of the sensor went through the voxel.
+    Free,
+    /// A ray of the sensor ended in the voxel.
+    Occupied,
+    /// No ray of the sensor reached the voxel, e.g. behind an obstacle.
+    Unknown,
+}
+
+/// Compute the free space indicator of a LiDAR scan.
+///
+/// A ray is cast from the viewpoint through each point of the scan and the voxels it
+/// traverses are visited with the algorithm of Amanatides and Woo. The voxels before the
+/// point are free, the voxel of the point is occupied and the voxels beyond it are unknown,
+/// as are the voxels not reached by any ray. A voxel hit by a point is occupied even if
+/// another ray traverses it.
+///
+/// # Arguments
+///
+/// * `cloud` - The LiDAR scan.
+/// * `viewpoint` - The origin of the rays, i.e. the sensor center, in the frame of the scan.
+/// * `resolution` - The edge length of a voxel.
+/// * `bbox` - The region to map. Its `min` corner is the corner of the voxel `(0, 0, 0)`.
+///   The viewpoint and the points may be outside of it.
+///
+/// # Returns
+///
+/// The labeled voxel grid covering the region.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::mapping::{compute_free_space, FreespaceLabel};
+/// use kornia_3d::pointcloud::PointCloud;
+/// use kornia_3d::voxel::Aabb;
+///
+/// let cloud = PointCloud::new(vec![[2.5, 0.5, 0.5]], None, None);
+/// let bbox = Aabb { min: [0.0; 3], max: [4.0, 1.0, 1.0] };
+/// let grid = compute_free_space(&cloud, [0.5, 0.5, 0.5], 1.0, bbox);
+/// assert_eq!(grid.dims(), [4, 1, 1]);
+/// assert_eq!(
+///     grid.cells(),
+///     &[
+///         FreespaceLabel::Free,
+///         FreespaceLabel::Free,
+///         FreespaceLabel::Occupied,
+///         FreespaceLabel::Unknown
+///     ]
+/// );
+/// ```
+pub fn compute_free_space(
+    cloud: &PointCloud,
+    viewpoint: [f64; 3],
+    resolution: f64,
+    bbox: Aabb,
+) -> VoxelGrid<FreespaceLabel> {
+    let dims =
+        [0, 1, 2].map(|k| ((bbox.max[k] - bbox.min[k]) / resolution).ceil().max(0.0) as usize);
+    let mut grid = VoxelGrid::filled(dims, resolution, FreespaceLabel::Unknown);
+    if grid.is_empty() {
+        return grid;
+    }
+
+    // work in voxel units with the grid spanning [0, dims]
+    let to_grid = |p: &[f64; 3]| [0, 1, 2].map(|k| (p[k] - bbox.min[k]) / resolution);
+    let voxel_of = |g: &[f64; 3]| -> Option<[usize; 3]> {
+        let v = [0, 1, 2].map(|k| g[k].floor());
+        (0..3)
+            .all(|k| v[k] >= 0.0 && v[k] < dims[k] as f64)
+            .then(|| v.map(|x| x as usize))
+    };
+
+    let origin = to_grid(&viewpoint);
+    for point in cloud.points() {
+        let end = to_grid(point);
+        let end_voxel = voxel_of(&end);
+        traverse_ray(&origin, &end, dims, |voxel| {
+            if Some(voxel) == end_voxel {
+                return false;
+            }
+            grid.set(voxel[0], voxel[1], voxel[2], FreespaceLabel::Free);
+            true
+        });
+    }
+
+    // the hits take precedence over the rays passing through
+    for point in cloud.points() {
+        if let Some(v) = voxel_of(&to_grid(point)) {
+            grid.set(v[0], v[1], v[2], FreespaceLabel::Occupied);
+        }
+    }
+
+    grid
+}
+
+/// Visit the voxels traversed by the segment from `origin` to `end`, clipped to the grid.
+///
+/// The coordinates are in voxel units with the grid spanning `[0, dims]`. The voxels are
+/// visited in order from the origin while `visit` returns `true`.
+fn traverse_ray(
+    origin: &[f64; 3],
+    end: &[f64; 3],
+    dims: [usize; 3],
+    mut visit: impl FnMut([usize; 3]) -> bool,
+) {
+    let direction = [0, 1, 2].map(|k| end[k] - origin[k]);
+
+    // clip the segment to the grid with the slab method
+    let (mut t_enter, mut t_exit) = (0.0f64, 1.0f64);
+    for k in 0..3 {
+        if direction[k] == 0.0 {
+            if origin[k] < 0.0 || origin[k] >= dims[k] as f64 {
+                return;
+            }
+            continue;
+        }
+        let t0 = -origin[k] / direction[k];
+        let t1 = (dims[k] as f64 - origin[k]) / direction[k];
+        t_enter = t_enter.max(t0.min(t1));
+        t_exit = t_exit.min(t0.max(t1));
+    }
+    if t_enter > t_exit {
+        return;
+    }
+
+    let start = [0, 1, 2].map(|k| origin[k] + t_enter * direction[k]);
+    let mut voxel = [0, 1, 2].map(|k| (start[k].floor() as isize).clamp(0, dims[k] as isize - 1));
+    let step = direction.map(|d| if d > 0.0 { 1 } else { -1 });
+    let mut t_max = [0, 1, 2].map(|k| {
+        if direction[k] == 0.0 {
+            f64::INFINITY
+        } else {
+            let boundary = voxel[k] as f64 + if direction[k] > 0.0 { 1.0 } else { 0.0 };
+            (boundary - origin[k]) / direction[k]
+        }
+    });
+    let t_delta = direction.map(|d| 1.0 / d.abs());
+
+    loop {
+        if !visit(voxel.map(|v| v as usize)) {
+            return;
+        }
+        let axis = (0..3)
+            .min_by(|&a, &b| t_max[a].total_cmp(&t_max[b]))
+            .unwrap_or(0);
+        if t_max[axis] > t_exit {
+            return;
+        }
+        voxel[axis] += step[axis];
+        if voxel[axis] < 0 || voxel[axis] >= dims[axis] as isize {
+            return;
+        }
+        t_max[axis] += t_delta[axis];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traverse_ray() {
+        let mut visited = Vec::new();
+        traverse_ray(&[0.5, 0.5, 0.5], &[3.5, 2.5, 0.5], [4, 4, 1], |v| {
+            visited.push(v);
+            true
+        });
+        assert_eq!(visited.first(), Some(&[0, 0, 0]));
+        assert_eq!(visited.last(), Some(&[3, 2, 0]));
+        // consecutive voxels share a face
+        for pair in visited.windows(2) {
+            let steps = (0..3)
+                .map(|k| pair[0][k].abs_diff(pair[1][k]))
+                .sum::<usize>();
+            assert_eq!(steps, 1);
+        }
+
+        // a ray from outside of the grid is clipped to it
+        visited.clear();
+        traverse_ray(&[-5.5, 1.5, 0.5], &[1.5, 1.5, 0.5], [4, 4, 1], |v| {
+            visited.push(v);
+            true
+        });
+        assert_eq!(visited, vec![[0, 1, 0], [1, 1, 0]]);
+
+        // a ray missing the grid visits nothing
+        traverse_ray(
+            &[-1.0, -1.0, 0.5],
+            &[-1.0, 5.0, 0.5],
+            [4, 4, 1],
+            |_| panic!(),
+        );
+    }
+
+    #[test]
+    fn test_free_space_behind_wall() {
+        // a wall at x = 3 seen from the origin, in a region of 8 x 8 x 1 voxels
+        let points = (0..40)
+            .map(|i| [3.05, -2.0 + 0.1 * i as f64, 0.5])
+            .collect::<Vec<_>>();
+        let cloud = PointCloud::new(points, None, None);
+        let bbox = Aabb {
+            min: [-4.0, -4.0, 0.0],
+            max: [4.0, 4.0, 1.0],
+        };
+        let grid = compute_free_space(&cloud, [0.0, 0.0, 0.5], 1.0, bbox);
+        assert_eq!(grid.dims(), [8, 8, 1]);
+
+        let label = |x: f64, y: f64| *grid.get((x + 4.0) as usize, (y + 4.0) as usize, 0);
+        for y in [-1.5, -0.5, 0.5, 1.5] {
+            assert_eq!(label(1.5, y), FreespaceLabel::Free);
+            assert_eq!(label(3.5, y), FreespaceLabel::Occupied);
+        }
+        assert_eq!(label(0.5, 0.5), FreespaceLabel::Free);
+        // behind the viewpoint and on the sides nothing is observed
+        assert_eq!(label(-2.5, 0.5), FreespaceLabel::Unknown);
+        assert_eq!(label(0.5, 3.5), FreespaceLabel::Unknown);
+
+        let num_occupied = grid
+            .cells()
+            .iter()
+            .filter(|l| **l == FreespaceLabel::Occupied)
+            .count();
+        assert_eq!(num_occupied, 4);
+    }
+
+    #[test]
+    fn test_free_space_points_outside() {
+        // the point is beyond the region, so the rays only free the voxels
+        let cloud = PointCloud::new(vec![[10.0, 0.5, 0.5]], None, None);
+        let bbox = Aabb {
+            min: [0.0; 3],
+            max: [3.0, 1.0, 1.0],
+        };
+        let grid = compute_free_space(&cloud, [-2.0, 0.5, 0.5], 1.0, bbox);
+        assert_eq!(grid.cells(), &[FreespaceLabel::Free; 3]);
+    }
+}
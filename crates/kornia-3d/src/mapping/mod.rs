@@ -0,0 +1,5 @@
+mod free_space;
+mod voxel_gaussian;
+
+pub use free_space::*;
+pub use voxel_gaussian::*;
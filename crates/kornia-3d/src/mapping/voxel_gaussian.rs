@@ -0,0 +1,629 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    linalg::{dot_product3, eigen_symmetric33, solve_linear},
+    pointcloud::PointCloud,
+    transforms::{axis_angle_to_rotation_matrix, RigidTransform3},
+    voxel::Aabb,
+};
+
+/// Name of the file storing the parameters of a saved map.
+const HEADER_FILE: &str = "map.bin";
+
+/// Minimum number of points of a voxel to use its distribution in the registration.
+const MIN_VOXEL_POINTS: usize = 5;
+
+/// Ratio between the smallest and the largest variance of a regularized voxel covariance.
+const COVARIANCE_EPSILON: f64 = 1e-3;
+
+/// Error types for the voxel Gaussian map.
+#[derive(Debug, thiserror::Error)]
+pub enum VoxelMapError {
+    /// Failed to read or write a tile file
+    #[error("Failed to read or write a tile file")]
+    Io(#[from] std::io::Error),
+
+    /// Failed to serialize or deserialize a tile
+    #[error("Failed to serialize or deserialize a tile")]
+    Serialization(#[from] bincode::Error),
+
+    /// The voxel size or the tile size is not positive
+    #[error("The voxel size and the tile size must be positive")]
+    InvalidSize,
+}
+
+/// The statistics of the points falling in a voxel.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VoxelStats {
+    /// The number of points.
+    pub count: usize,
+    /// The mean of the points.
+    pub mean: [f64; 3],
+    /// The sum of the outer products of the deviations of the points from their mean.
+    pub m2: [[f64; 3]; 3],
+}
+
+impl VoxelStats {
+    /// The statistics of a single point.
+    pub fn from_point(p: &[f64; 3]) -> Self {
+        Self {
+            count: 1,
+            mean: *p,
+            m2: [[0.0; 3]; 3],
+        }
+    }
+
+    /// Compute the covariance of the points, normalized by their number.
+    pub fn covariance(&self) -> [[f64; 3]; 3] {
+        let num = self.count.max(1) as f64;
+        self.m2.map(|row| row.map(|v| v / num))
+    }
+
+    /// Merge the statistics of another set of points.
+    ///
+    /// The sets are combined with the parallel-axis formulas of Chan et al.: the merged mean is
+    /// the weighted mean of the means, and the deviation of the means adds
+    /// `d d^T na nb / (na + nb)` to the summed outer products, so that merging gives the
+    /// statistics of the union of the sets.
+    pub fn merge(&mut self, other: &VoxelStats) {
+        if other.count == 0 {
+            return;
+        }
+        let (na, nb) = (self.count as f64, other.count as f64);
+        let n = na + nb;
+        let d = [0, 1, 2].map(|k| other.mean[k] - self.mean[k]);
+        for a in 0..3 {
+            for b in 0..3 {
+                self.m2[a][b] += other.m2[a][b] + d[a] * d[b] * na * nb / n;
+            }
+        }
+        self.mean = [0, 1, 2].map(|k| self.mean[k] + d[k] * nb / n);
+        self.count += other.count;
+    }
+}
+
+/// The parameters of a saved map.
+#[derive(Serialize, Deserialize)]
+struct MapHeader {
+    voxel_size: f64,
+    tile_size: usize,
+}
+
+/// A sparse map of the Gaussian distributions of the points in each voxel.
+///
+/// Each occupied voxel stores the number, the mean and the covariance of the points observed
+/// in it, which is the target of distribution-to-distribution registrations such as VGICP and
+/// NDT. The map is grouped in cubic tiles of `tile_size` voxels along each axis, so that a
+/// large map is saved as one file per tile and only the tiles around the sensor are loaded
+/// in a later session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoxelGaussianMap {
+    voxel_size: f64,
+    tile_size: usize,
+    voxels: HashMap<[i64; 3], VoxelStats>,
+}
+
+impl VoxelGaussianMap {
+    /// Create a new empty map.
+    ///
+    /// # Arguments
+    ///
+    /// * `voxel_size` - The edge length of a voxel.
+    /// * `tile_size` - The number of voxels along each axis of a tile.
+    pub fn new(voxel_size: f64, tile_size: usize) -> Result<Self, VoxelMapError> {
+        if voxel_size <= 0.0 || tile_size == 0 {
+            return Err(VoxelMapError::InvalidSize);
+        }
+        Ok(Self {
+            voxel_size,
+            tile_size,
+            voxels: HashMap::new(),
+        })
+    }
+
+    /// The edge length of a voxel.
+    pub fn voxel_size(&self) -> f64 {
+        self.voxel_size
+    }
+
+    /// The number of voxels along each axis of a tile.
+    pub fn tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    /// The number of occupied voxels.
+    pub fn len(&self) -> usize {
+        self.voxels.len()
+    }
+
+    /// Check if the map has no occupied voxel.
+    pub fn is_empty(&self) -> bool {
+        self.voxels.is_empty()
+    }
+
+    /// Get the statistics of a voxel, or `None` if no point was observed in it.
+    pub fn get(&self, key: &[i64; 3]) -> Option<&VoxelStats> {
+        self.voxels.get(key)
+    }
+
+    /// Iterate over the occupied voxels and their statistics, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[i64; 3], &VoxelStats)> {
+        self.voxels.iter()
+    }
+
+    /// Compute the key of the voxel containing a point.
+    pub fn voxel_key(&self, p: &[f64; 3]) -> [i64; 3] {
+        p.map(|x| (x / self.voxel_size).floor() as i64)
+    }
+
+    /// Compute the key of the tile containing a voxel.
+    pub fn tile_key(&self, voxel: &[i64; 3]) -> [i64; 3] {
+        voxel.map(|v| v.div_euclid(self.tile_size as i64))
+    }
+
+    /// The keys of the tiles with at least one occupied voxel, in increasing order.
+    pub fn tiles(&self) -> Vec<[i64; 3]> {
+        let mut tiles = self
+            .voxels
+            .keys()
+            .map(|v| self.tile_key(v))
+            .collect::<Vec<_>>();
+        tiles.sort_unstable();
+        tiles.dedup();
+        tiles
+    }
+
+    /// Merge a new observation into the map.
+    ///
+    /// The points are transformed to the map frame and the statistics of the new points of
+    /// each voxel are merged into the existing ones with [`VoxelStats::merge`], so that the
+    /// map is the same as if all the observations had been inserted at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `cloud` - The observed points in the sensor frame.
+    /// * `pose` - The transformation from the sensor to the map frame.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_3d::mapping::VoxelGaussianMap;
+    /// use kornia_3d::pointcloud::PointCloud;
+    /// use kornia_3d::transforms::RigidTransform3;
+    ///
+    /// let mut map = VoxelGaussianMap::new(1.0, 16).unwrap();
+    /// let cloud = PointCloud::new(vec![[0.25, 0.5, 0.5], [0.75, 0.5, 0.5]], None, None);
+    /// map.update(&cloud, &RigidTransform3::identity());
+    /// let stats = map.get(&[0, 0, 0]).unwrap();
+    /// assert_eq!(stats.count, 2);
+    /// assert_eq!(stats.mean, [0.5, 0.5, 0.5]);
+    /// assert_eq!(stats.covariance()[0][0], 0.0625);
+    /// ```
+    pub fn update(&mut self, cloud: &PointCloud, pose: &RigidTransform3) {
+        let mut batch = HashMap::<[i64; 3], VoxelStats>::new();
+        for p in cloud.points().iter().map(|p| pose.apply(p)) {
+            let point = VoxelStats::from_point(&p);
+            batch
+                .entry(self.voxel_key(&p))
+                .and_modify(|stats| stats.merge(&point))
+                .or_insert(point);
+        }
+        for (key, stats) in batch {
+            self.voxels
+                .entry(key)
+                .and_modify(|existing| existing.merge(&stats))
+                .or_insert(stats);
+        }
+    }
+
+    /// Save the map to a directory, one file per tile.
+    ///
+    /// The directory is created if needed and the tile files already in it are overwritten.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory of the map.
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<(), VoxelMapError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let header = MapHeader {
+            voxel_size: self.voxel_size,
+            tile_size: self.tile_size,
+        };
+        bincode::serialize_into(
+            BufWriter::new(File::create(dir.join(HEADER_FILE))?),
+            &header,
+        )?;
+
+        let mut tiles = HashMap::<[i64; 3], Vec<([i64; 3], VoxelStats)>>::new();
+        for (key, stats) in self.voxels.iter() {
+            tiles
+                .entry(self.tile_key(key))
+                .or_default()
+                .push((*key, *stats));
+        }
+        for (tile, voxels) in tiles {
+            let writer = BufWriter::new(File::create(dir.join(tile_file_name(&tile)))?);
+            bincode::serialize_into(writer, &voxels)?;
+        }
+        Ok(())
+    }
+
+    /// Load all the tiles of a map saved with [`VoxelGaussianMap::save`].
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory of the map.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, VoxelMapError> {
+        let dir = dir.as_ref();
+        let mut map = Self::load_header(dir)?;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let is_tile = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("tile_"));
+            if is_tile {
+                map.load_tile(&path)?;
+            }
+        }
+        Ok(map)
+    }
+
+    /// Load the tiles of a saved map intersecting a region, e.g. the bounding box of the
+    /// current sensor range.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory of the map.
+    /// * `bbox` - The region to load, in the map frame.
+    ///
+    /// # Returns
+    ///
+    /// The map restricted to the saved tiles intersecting the region.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_3d::mapping::VoxelGaussianMap;
+    /// use kornia_3d::pointcloud::PointCloud;
+    /// use kornia_3d::transforms::RigidTransform3;
+    /// use kornia_3d::voxel::Aabb;
+    ///
+    /// // two points in the tiles of 4 m at the origin and 8 m away along x
+    /// let mut map = VoxelGaussianMap::new(1.0, 4).unwrap();
+    /// let cloud = PointCloud::new(vec![[0.5, 0.5, 0.5], [8.5, 0.5, 0.5]], None, None);
+    /// map.update(&cloud, &RigidTransform3::identity());
+    ///
+    /// let dir = std::env::temp_dir().join("kornia_voxel_gaussian_map_example");
+    /// map.save(&dir).unwrap();
+    /// let bbox = Aabb { min: [-1.0; 3], max: [3.0; 3] };
+    /// let region = VoxelGaussianMap::load_region(&dir, &bbox).unwrap();
+    /// assert_eq!(region.tiles(), vec![[0, 0, 0]]);
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn load_region(dir: impl AsRef<Path>, bbox: &Aabb) -> Result<Self, VoxelMapError> {
+        let dir = dir.as_ref();
+        let mut map = Self::load_header(dir)?;
+        let tile_extent = map.voxel_size * map.tile_size as f64;
+        let first = bbox.min.map(|x| (x / tile_extent).floor() as i64);
+        let last = bbox.max.map(|x| (x / tile_extent).floor() as i64);
+        for x in first[0]..=last[0] {
+            for y in first[1]..=last[1] {
+                for z in first[2]..=last[2] {
+                    let path = dir.join(tile_file_name(&[x, y, z]));
+                    if path.exists() {
+                        map.load_tile(&path)?;
+                    }
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    /// Register a point cloud to the map by minimizing the Mahalanobis distances of its points
+    /// to the distributions of their voxels.
+    ///
+    /// Each transformed point is matched to the distribution of the voxel containing it,
+    /// provided the voxel has at least 5 points. The covariances are regularized by clamping
+    /// their variances to a thousandth of the largest one, so that the points slide along
+    /// the planar voxels. The pose is refined with Gauss-Newton steps.
+    ///
+    /// # Arguments
+    ///
+    /// * `cloud` - The points in the sensor frame.
+    /// * `initial` - The initial guess of the transformation from the sensor to the map frame,
+    ///   within about a voxel of the solution.
+    /// * `max_iterations` - Maximum number of Gauss-Newton iterations.
+    ///
+    /// # Returns
+    ///
+    /// The transformation from the sensor to the map frame. The last estimate is returned
+    /// when the matched points stop constraining the pose.
+    pub fn register(
+        &self,
+        cloud: &PointCloud,
+        initial: &RigidTransform3,
+        max_iterations: usize,
+    ) -> RigidTransform3 {
+        let informations = self
+            .voxels
+            .iter()
+            .filter(|(_, stats)| stats.count >= MIN_VOXEL_POINTS)
+            .map(|(key, stats)| (*key, (stats.mean, regularized_information(stats))))
+            .collect::<HashMap<_, _>>();
+
+        let mut transform = *initial;
+        for _ in 0..max_iterations {
+            // accumulate the normal equations of the linearized residuals
+            let mut jtwj = [[0.0; 6]; 6];
+            let mut jtwr = [0.0; 6];
+            let mut num_matches = 0;
+            for q in cloud.points().iter().map(|p| transform.apply(p)) {
+                let Some((mean, information)) = informations.get(&self.voxel_key(&q)) else {
+                    continue;
+                };
+                let residual = [0, 1, 2].map(|k| q[k] - mean[k]);
+                // the jacobian of the point for a left perturbation, [-[q]x | I]
+                let jacobian = [
+                    [0.0, q[2], -q[1], 1.0, 0.0, 0.0],
+                    [-q[2], 0.0, q[0], 0.0, 1.0, 0.0],
+                    [q[1], -q[0], 0.0, 0.0, 0.0, 1.0],
+                ];
+                let mut wj = [[0.0; 6]; 3];
+                for a in 0..3 {
+                    for c in 0..6 {
+                        wj[a][c] = (0..3).map(|b| information[a][b] * jacobian[b][c]).sum();
+                    }
+                }
+                for r in 0..6 {
+                    for c in 0..6 {
+                        jtwj[r][c] += (0..3).map(|a| jacobian[a][r] * wj[a][c]).sum::<f64>();
+                    }
+                    jtwr[r] -= (0..3).map(|a| wj[a][r] * residual[a]).sum::<f64>();
+                }
+                num_matches += 1;
+            }
+            if num_matches < 6 {
+                break;
+            }
+            let Some(delta) = solve_linear(&jtwj, &jtwr) else {
+                break;
+            };
+
+            // compose the delta on the left of the current transformation
+            let omega = [delta[0], delta[1], delta[2]];
+            let angle = dot_product3(&omega, &omega).sqrt();
+            let rotation = axis_angle_to_rotation_matrix(&omega, angle)
+                .unwrap_or(RigidTransform3::identity().rotation);
+            let step = RigidTransform3::new(rotation, [delta[3], delta[4], delta[5]]);
+            transform = step.compose(&transform);
+            if delta.iter().all(|d| d.abs() < 1e-12) {
+                break;
+            }
+        }
+        transform
+    }
+
+    /// Read the parameters of a saved map and create the empty map.
+    fn load_header(dir: &Path) -> Result<Self, VoxelMapError> {
+        let reader = BufReader::new(File::open(dir.join(HEADER_FILE))?);
+        let header: MapHeader = bincode::deserialize_from(reader)?;
+        Self::new(header.voxel_size, header.tile_size)
+    }
+
+    /// Read the voxels of a tile file into the map.
+    fn load_tile(&mut self, path: &Path) -> Result<(), VoxelMapError> {
+        let reader = BufReader::new(File::open(path)?);
+        let voxels: Vec<([i64; 3], VoxelStats)> = bincode::deserialize_from(reader)?;
+        self.voxels.extend(voxels);
+        Ok(())
+    }
+}
+
+/// The name of the file of a tile.
+fn tile_file_name(tile: &[i64; 3]) -> String {
+    format!("tile_{}_{}_{}.bin", tile[0], tile[1], tile[2])
+}
+
+/// Compute the inverse of the covariance of a voxel with its variances clamped from below.
+fn regularized_information(stats: &VoxelStats) -> [[f64; 3]; 3] {
+    let (variances, axes) = eigen_symmetric33(&stats.covariance());
+    let floor = (COVARIANCE_EPSILON * variances[2]).max(f64::MIN_POSITIVE);
+    let mut information = [[0.0; 3]; 3];
+    for (variance, axis) in variances.iter().zip(axes.iter()) {
+        let weight = 1.0 / variance.max(floor);
+        for a in 0..3 {
+            for b in 0..3 {
+                information[a][b] += weight * axis[a] * axis[b];
+            }
+        }
+    }
+    information
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::axis_angle_to_rotation_matrix;
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// A corner of a room: a floor and two walls, sampled at random. The planes are in the
+    /// middle of the voxels of 0.5 m, away from their faces.
+    fn room(num_points: usize, seed: u64) -> Vec<[f64; 3]> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..num_points)
+            .map(|i| {
+                let (u, v): (f64, f64) = (rng.random_range(0.0..8.0), rng.random_range(0.0..3.0));
+                match i % 3 {
+                    0 => [u, v * 8.0 / 3.0, 0.25],
+                    1 => [u, 0.25, v],
+                    _ => [0.25, u, v],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_update_matches_batch() -> Result<(), Box<dyn std::error::Error>> {
+        let first = room(3000, 0);
+        let second = room(3000, 1);
+        let pose = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.3)?,
+            [1.0, -2.0, 0.5],
+        );
+
+        // the second scan is observed from another pose, and inserted in a separate update
+        let mut merged = VoxelGaussianMap::new(0.5, 4)?;
+        merged.update(&PointCloud::new(first.clone(), None, None), &pose);
+        let second_in_sensor = second.iter().map(|p| pose.apply(p)).collect::<Vec<_>>();
+        merged.update(
+            &PointCloud::new(second_in_sensor, None, None),
+            &RigidTransform3::identity(),
+        );
+
+        let all = first
+            .iter()
+            .chain(second.iter())
+            .map(|p| pose.apply(p))
+            .collect::<Vec<_>>();
+        let mut batch = VoxelGaussianMap::new(0.5, 4)?;
+        batch.update(
+            &PointCloud::new(all.clone(), None, None),
+            &RigidTransform3::identity(),
+        );
+
+        assert_eq!(merged.len(), batch.len());
+        for (key, stats) in batch.iter() {
+            let other = merged.get(key).ok_or("missing voxel")?;
+            assert_eq!(other.count, stats.count);
+            let (cov, batch_cov) = (other.covariance(), stats.covariance());
+            for a in 0..3 {
+                assert_relative_eq!(other.mean[a], stats.mean[a], epsilon = 1e-12);
+                for b in 0..3 {
+                    assert_relative_eq!(cov[a][b], batch_cov[a][b], epsilon = 1e-12);
+                }
+            }
+        }
+
+        // the statistics of a voxel are those of its points
+        let key = batch.voxel_key(&all[0]);
+        let points = all
+            .iter()
+            .filter(|p| batch.voxel_key(p) == key)
+            .collect::<Vec<_>>();
+        let num = points.len() as f64;
+        let mean = [0, 1, 2].map(|k| points.iter().map(|p| p[k]).sum::<f64>() / num);
+        let variance_x = points.iter().map(|p| (p[0] - mean[0]).powi(2)).sum::<f64>() / num;
+        let stats = batch.get(&key).ok_or("missing voxel")?;
+        assert_eq!(stats.count, points.len());
+        for (a, b) in stats.mean.iter().zip(mean.iter()) {
+            assert_relative_eq!(a, b, epsilon = 1e-12);
+        }
+        assert_relative_eq!(stats.covariance()[0][0], variance_x, epsilon = 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_region_tiles() -> Result<(), Box<dyn std::error::Error>> {
+        let mut map = VoxelGaussianMap::new(0.5, 4)?;
+        map.update(
+            &PointCloud::new(room(5000, 0), None, None),
+            &RigidTransform3::identity(),
+        );
+
+        let tmp_dir = tempfile::tempdir()?;
+        map.save(tmp_dir.path())?;
+        assert_eq!(VoxelGaussianMap::load(tmp_dir.path())?, map);
+
+        // the tiles are 2 m wide: the box spans the tiles 0 to 1 along x, 1 to 2 along y
+        // and 0 along z
+        let bbox = Aabb {
+            min: [1.5, 2.5, -1.0],
+            max: [3.5, 4.5, 1.0],
+        };
+        let region = VoxelGaussianMap::load_region(tmp_dir.path(), &bbox)?;
+        let expected = map
+            .tiles()
+            .into_iter()
+            .filter(|t| (0..=1).contains(&t[0]) && (1..=2).contains(&t[1]) && t[2] == 0)
+            .collect::<Vec<_>>();
+        assert_eq!(expected.len(), 4);
+        assert_eq!(region.tiles(), expected);
+
+        // the voxels of the loaded tiles are complete
+        for (key, stats) in map.iter() {
+            if expected.contains(&map.tile_key(key)) {
+                assert_eq!(region.get(key), Some(stats));
+            }
+        }
+        assert_eq!(
+            region.len(),
+            map.iter()
+                .filter(|(key, _)| expected.contains(&map.tile_key(key)))
+                .count()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_register_region_matches_full_map() -> Result<(), Box<dyn std::error::Error>> {
+        let mut map = VoxelGaussianMap::new(0.5, 4)?;
+        map.update(
+            &PointCloud::new(room(60_000, 0), None, None),
+            &RigidTransform3::identity(),
+        );
+        let tmp_dir = tempfile::tempdir()?;
+        map.save(tmp_dir.path())?;
+
+        // a scan of the corner, observed from a sensor at a known pose
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.1, 0.2, 1.0], 0.03)?,
+            [0.5, 0.5, 0.2],
+        );
+        let scan = room(6000, 1)
+            .into_iter()
+            .filter(|p| p[0] < 3.0 && p[1] < 3.0)
+            .map(|p| truth.inverse().apply(&p))
+            .collect::<Vec<_>>();
+        let scan = PointCloud::new(scan, None, None);
+        let initial = RigidTransform3::new(truth.rotation, [0.4, 0.6, 0.15]);
+
+        let full = VoxelGaussianMap::load(tmp_dir.path())?;
+        let bbox = Aabb {
+            min: [-1.0; 3],
+            max: [4.0; 3],
+        };
+        let region = VoxelGaussianMap::load_region(tmp_dir.path(), &bbox)?;
+        assert!(region.len() < full.len());
+
+        let from_full = full.register(&scan, &initial, 30);
+        let from_region = region.register(&scan, &initial, 30);
+        assert_eq!(from_full, from_region);
+
+        let error = from_full.compose(&truth.inverse());
+        assert!(error.rotation_angle() < 2e-3);
+        assert!(error.translation.iter().all(|t| t.abs() < 0.01));
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_invalid_size() {
+        assert!(matches!(
+            VoxelGaussianMap::new(0.0, 4),
+            Err(VoxelMapError::InvalidSize)
+        ));
+        assert!(matches!(
+            VoxelGaussianMap::new(1.0, 0),
+            Err(VoxelMapError::InvalidSize)
+        ));
+    }
+}
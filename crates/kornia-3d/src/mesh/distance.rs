@@ -0,0 +1,458 @@
+use crate::{
+    bvh::Bvh,
+    linalg::{cross_vec3, dot_product3},
+};
+
+/// Compute the closest point of a triangle to a query point.
+///
+/// The query is projected onto the plane of the triangle and the projection is clamped to
+/// the triangle: depending on the region of the plane it falls in, the closest point is a
+/// vertex, a point of an edge or the projection itself in the interior.
+///
+/// # Arguments
+///
+/// * `query` - The query point.
+/// * `a`, `b`, `c` - The vertices of the triangle.
+///
+/// # Returns
+///
+/// The point of the triangle closest to the query.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::mesh::closest_point_on_triangle;
+///
+/// let (a, b, c) = ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+/// let p = closest_point_on_triangle(&[0.2, 0.2, 1.0], &a, &b, &c);
+/// assert!((p[0] - 0.2).abs() < 1e-12 && (p[1] - 0.2).abs() < 1e-12 && p[2] == 0.0);
+/// assert_eq!(closest_point_on_triangle(&[2.0, -1.0, 0.0], &a, &b, &c), b);
+/// ```
+pub fn closest_point_on_triangle(
+    query: &[f64; 3],
+    a: &[f64; 3],
+    b: &[f64; 3],
+    c: &[f64; 3],
+) -> [f64; 3] {
+    let sub = |u: &[f64; 3], v: &[f64; 3]| [u[0] - v[0], u[1] - v[1], u[2] - v[2]];
+    let lerp = |u: &[f64; 3], v: &[f64; 3], t: f64| {
+        [
+            u[0] + t * (v[0] - u[0]),
+            u[1] + t * (v[1] - u[1]),
+            u[2] + t * (v[2] - u[2]),
+        ]
+    };
+    let (ab, ac, ap) = (sub(b, a), sub(c, a), sub(query, a));
+
+    // vertex region of a
+    let (d1, d2) = (dot_product3(&ab, &ap), dot_product3(&ac, &ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return *a;
+    }
+
+    // vertex region of b
+    let bp = sub(query, b);
+    let (d3, d4) = (dot_product3(&ab, &bp), dot_product3(&ac, &bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return *b;
+    }
+
+    // edge region of ab
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return lerp(a, b, d1 / (d1 - d3));
+    }
+
+    // vertex region of c
+    let cp = sub(query, c);
+    let (d5, d6) = (dot_product3(&ab, &cp), dot_product3(&ac, &cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return *c;
+    }
+
+    // edge region of ac
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return lerp(a, c, d2 / (d2 - d6));
+    }
+
+    // edge region of bc
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return lerp(b, c, (d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    // interior, with the barycentric coordinates of the projection
+    let denom = va + vb + vc;
+    if denom <= 0.0 {
+        // degenerate triangle whose vertices are aligned, the edges already covered it
+        return *a;
+    }
+    let (v, w) = (vb / denom, vc / denom);
+    [
+        a[0] + ab[0] * v + ac[0] * w,
+        a[1] + ab[1] * v + ac[1] * w,
+        a[2] + ab[2] * v + ac[2] * w,
+    ]
+}
+
+/// Compute the distance from a query point to the surface of a triangle mesh.
+///
+/// # Arguments
+///
+/// * `query` - The query point.
+/// * `vertices` - The vertices of the mesh.
+/// * `faces` - The triangles of the mesh as indices into the vertices.
+/// * `bvh` - An optional BVH built over the same faces to skip the far triangles. Without
+///   it, all the triangles are visited.
+///
+/// # Returns
+///
+/// The Euclidean distance from the query to the closest point of the surface, or infinity
+/// if the mesh has no faces.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::bvh::Bvh;
+/// use kornia_3d::mesh::point_to_mesh_distance;
+///
+/// let vertices = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+/// let faces = vec![[0, 1, 2]];
+/// let bvh = Bvh::new(&vertices, &faces);
+/// assert_eq!(point_to_mesh_distance([0.2, 0.2, 0.5], &vertices, &faces, Some(&bvh)), 0.5);
+/// assert_eq!(point_to_mesh_distance([-3.0, 0.0, 4.0], &vertices, &faces, None), 5.0);
+/// ```
+pub fn point_to_mesh_distance(
+    query: [f64; 3],
+    vertices: &[[f64; 3]],
+    faces: &[[usize; 3]],
+    bvh: Option<&Bvh>,
+) -> f64 {
+    let sq_distance = |t: usize| {
+        let [a, b, c] = faces[t];
+        let p = closest_point_on_triangle(&query, &vertices[a], &vertices[b], &vertices[c]);
+        let d = [p[0] - query[0], p[1] - query[1], p[2] - query[2]];
+        dot_product3(&d, &d)
+    };
+
+    let nearest = match bvh {
+        Some(bvh) => bvh.nearest_triangle(&query, sq_distance),
+        None => (0..faces.len())
+            .map(|t| (t, sq_distance(t)))
+            .min_by(|a, b| a.1.total_cmp(&b.1)),
+    };
+    nearest.map_or(f64::INFINITY, |(_, d)| d.sqrt())
+}
+
+/// Compute the signed distance from query points to the surface of a triangle mesh.
+///
+/// The unsigned distance is the distance to the closest point of the surface, found with a
+/// [`Bvh`] built over the faces. The sign comes from the generalized winding number of the
+/// mesh around the query, the sum of the signed solid angles of the triangles divided by
+/// `4π`: it is one inside a closed mesh with outward facing triangles, minus one inside a
+/// closed mesh with inward facing triangles and zero outside, and degrades gracefully for
+/// meshes with holes. A query whose winding number is at least one half in magnitude is
+/// inside.
+///
+/// # Arguments
+///
+/// * `vertices` - The vertices of the mesh.
+/// * `faces` - The triangles of the mesh as indices into the vertices.
+/// * `query_points` - The points to evaluate.
+///
+/// # Returns
+///
+/// The signed distance of each query point, negative inside the mesh and positive outside,
+/// or infinity if the mesh has no faces.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::mesh::mesh_to_sdf;
+///
+/// // a tetrahedron with outward facing triangles
+/// let vertices = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let faces = vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+/// let sdf = mesh_to_sdf(&vertices, &faces, &[[0.1, 0.1, 0.2], [0.2, 0.2, -0.5]]);
+/// assert!((sdf[0] + 0.1).abs() < 1e-12);
+/// assert!((sdf[1] - 0.5).abs() < 1e-12);
+/// ```
+pub fn mesh_to_sdf(
+    vertices: &[[f64; 3]],
+    faces: &[[usize; 3]],
+    query_points: &[[f64; 3]],
+) -> Vec<f64> {
+    let bvh = Bvh::new(vertices, faces);
+    query_points
+        .iter()
+        .map(|query| {
+            let distance = point_to_mesh_distance(*query, vertices, faces, Some(&bvh));
+            if winding_number(query, vertices, faces).abs() >= 0.5 {
+                -distance
+            } else {
+                distance
+            }
+        })
+        .collect()
+}
+
+/// Project points onto the closest points of the surface of a triangle mesh.
+///
+/// Each point is replaced by the closest point of its nearest triangle, found with the
+/// [`Bvh`]. The signed distance from the point to the surface is returned for diagnostics,
+/// e.g. to check the tolerances of a scanned part against its CAD model, with the sign of
+/// [`mesh_to_sdf`].
+///
+/// # Arguments
+///
+/// * `cloud` - The points to project.
+/// * `vertices` - The vertices of the mesh.
+/// * `faces` - The triangles of the mesh as indices into the vertices.
+/// * `bvh` - A BVH built over the same faces.
+///
+/// # Returns
+///
+/// The projected points and the signed distances of the points to the surface, negative
+/// inside the mesh. The points are unchanged, at an infinite distance, if the mesh has no
+/// faces.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::bvh::Bvh;
+/// use kornia_3d::mesh::snap_to_surface;
+///
+/// // a tetrahedron with outward facing triangles
+/// let vertices = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let faces = vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+/// let bvh = Bvh::new(&vertices, &faces);
+///
+/// let (snapped, distances) = snap_to_surface(&[[0.2, 0.2, -0.1]], &vertices, &faces, &bvh);
+/// assert!((snapped[0][0] - 0.2).abs() < 1e-12 && snapped[0][2] == 0.0);
+/// assert!((distances[0] - 0.1).abs() < 1e-12);
+/// ```
+pub fn snap_to_surface(
+    cloud: &[[f64; 3]],
+    vertices: &[[f64; 3]],
+    faces: &[[usize; 3]],
+    bvh: &Bvh,
+) -> (Vec<[f64; 3]>, Vec<f64>) {
+    let closest_point = |query: &[f64; 3], t: usize| {
+        let [a, b, c] = faces[t];
+        closest_point_on_triangle(query, &vertices[a], &vertices[b], &vertices[c])
+    };
+
+    cloud
+        .iter()
+        .map(|query| {
+            let sq_distance = |t: usize| {
+                let p = closest_point(query, t);
+                let d = [p[0] - query[0], p[1] - query[1], p[2] - query[2]];
+                dot_product3(&d, &d)
+            };
+            let Some((t, sq_distance)) = bvh.nearest_triangle(query, sq_distance) else {
+                return (*query, f64::INFINITY);
+            };
+            let distance = sq_distance.sqrt();
+            let signed_distance = if winding_number(query, vertices, faces).abs() >= 0.5 {
+                -distance
+            } else {
+                distance
+            };
+            (closest_point(query, t), signed_distance)
+        })
+        .unzip()
+}
+
+/// Compute the generalized winding number of a triangle mesh around a point.
+///
+/// The signed solid angle of each triangle is computed with the formula of Van Oosterom and
+/// Strackee.
+fn winding_number(query: &[f64; 3], vertices: &[[f64; 3]], faces: &[[usize; 3]]) -> f64 {
+    let solid_angles = faces
+        .iter()
+        .map(|face| {
+            let [a, b, c] = face.map(|v| {
+                let p = vertices[v];
+                [p[0] - query[0], p[1] - query[1], p[2] - query[2]]
+            });
+            let [la, lb, lc] = [a, b, c].map(|u| dot_product3(&u, &u).sqrt());
+            let mut bc = [0.0; 3];
+            cross_vec3(&b, &c, &mut bc);
+            let numerator = dot_product3(&a, &bc);
+            let denominator = la * lb * lc
+                + dot_product3(&a, &b) * lc
+                + dot_product3(&a, &c) * lb
+                + dot_product3(&b, &c) * la;
+            2.0 * numerator.atan2(denominator)
+        })
+        .sum::<f64>();
+    solid_angles / (4.0 * std::f64::consts::PI)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::PI;
+
+    /// A unit sphere triangulated along its parallels and meridians.
+    fn unit_sphere(num_parallels: usize, num_meridians: usize) -> (Vec<[f64; 3]>, Vec<[usize; 3]>) {
+        let mut vertices = vec![[0.0, 0.0, 1.0], [0.0, 0.0, -1.0]];
+        for i in 1..num_parallels {
+            let polar = PI * i as f64 / num_parallels as f64;
+            for j in 0..num_meridians {
+                let azimuth = 2.0 * PI * j as f64 / num_meridians as f64;
+                vertices.push([
+                    polar.sin() * azimuth.cos(),
+                    polar.sin() * azimuth.sin(),
+                    polar.cos(),
+                ]);
+            }
+        }
+
+        let vertex = |i: usize, j: usize| 2 + (i - 1) * num_meridians + j % num_meridians;
+        let mut faces = Vec::new();
+        for j in 0..num_meridians {
+            faces.push([0, vertex(1, j), vertex(1, j + 1)]);
+            faces.push([
+                1,
+                vertex(num_parallels - 1, j + 1),
+                vertex(num_parallels - 1, j),
+            ]);
+            for i in 1..num_parallels - 1 {
+                faces.push([vertex(i, j), vertex(i + 1, j), vertex(i + 1, j + 1)]);
+                faces.push([vertex(i, j), vertex(i + 1, j + 1), vertex(i, j + 1)]);
+            }
+        }
+        (vertices, faces)
+    }
+
+    #[test]
+    fn test_closest_point_on_triangle_regions() {
+        let (a, b, c) = ([0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, 0.0]);
+        let closest = |q: [f64; 3]| closest_point_on_triangle(&q, &a, &b, &c);
+        // vertices
+        assert_eq!(closest([-1.0, -1.0, 3.0]), a);
+        assert_eq!(closest([3.0, -0.5, 0.0]), b);
+        assert_eq!(closest([-0.5, 3.0, -1.0]), c);
+        // edges
+        assert_eq!(closest([1.0, -1.0, 0.0]), [1.0, 0.0, 0.0]);
+        assert_eq!(closest([-1.0, 1.5, 0.0]), [0.0, 1.5, 0.0]);
+        let p = closest([2.0, 2.0, 1.0]);
+        assert_relative_eq!(p[0], 1.0);
+        assert_relative_eq!(p[1], 1.0);
+        // interior
+        assert_eq!(closest([0.5, 0.5, -2.0]), [0.5, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_point_to_mesh_distance_sphere() {
+        let (vertices, faces) = unit_sphere(64, 128);
+        let bvh = Bvh::new(&vertices, &faces);
+        // the chords of the tessellation are at most this far inside the sphere
+        let tessellation_error = 1.0 - (PI / 64.0).cos();
+
+        let directions = [
+            [0.0, 0.0, 1.0],
+            [1.0, 2.0, 3.0],
+            [-0.3, 0.8, -0.1],
+            [0.5, -0.5, -2.0],
+        ];
+        for direction in directions {
+            let norm = dot_product3(&direction, &direction).sqrt();
+            for radius in [0.0, 0.5, 0.99, 1.0, 1.3, 4.0] {
+                let query = direction.map(|x| radius * x / norm);
+                let distance = point_to_mesh_distance(query, &vertices, &faces, Some(&bvh));
+                assert!(
+                    (distance - (radius - 1.0f64).abs()).abs() <= tessellation_error,
+                    "distance {distance} at radius {radius}"
+                );
+                let brute_force = point_to_mesh_distance(query, &vertices, &faces, None);
+                assert_eq!(distance, brute_force);
+            }
+        }
+
+        assert_eq!(
+            point_to_mesh_distance([0.0; 3], &vertices, &[], None),
+            f64::INFINITY
+        );
+    }
+
+    #[test]
+    fn test_mesh_to_sdf_sphere() {
+        let (vertices, faces) = unit_sphere(32, 64);
+        let tessellation_error = 1.0 - (PI / 32.0).cos();
+
+        let direction = [0.3, -0.8, 0.5];
+        let norm = dot_product3(&direction, &direction).sqrt();
+        let radii = [0.0, 0.4, 0.9, 1.1, 2.0];
+        let queries = radii
+            .iter()
+            .map(|r| direction.map(|x| r * x / norm))
+            .collect::<Vec<_>>();
+
+        let sdf = mesh_to_sdf(&vertices, &faces, &queries);
+        for (distance, radius) in sdf.iter().zip(radii.iter()) {
+            assert!(
+                (distance - (radius - 1.0)).abs() <= tessellation_error,
+                "distance {distance} at radius {radius}"
+            );
+        }
+        // the interior points are negative
+        assert!(sdf[..3].iter().all(|d| *d < 0.0));
+        assert!(sdf[3..].iter().all(|d| *d > 0.0));
+
+        // the sign does not depend on the orientation of the triangles
+        let flipped = faces.iter().map(|f| [f[0], f[2], f[1]]).collect::<Vec<_>>();
+        assert_eq!(mesh_to_sdf(&vertices, &flipped, &queries), sdf);
+        assert_eq!(
+            mesh_to_sdf(&vertices, &[], &queries[..1]),
+            vec![f64::INFINITY]
+        );
+    }
+
+    #[test]
+    fn test_snap_to_surface_sphere() {
+        let (vertices, faces) = unit_sphere(32, 64);
+        let bvh = Bvh::new(&vertices, &faces);
+        let tessellation_error = 1.0 - (PI / 32.0).cos();
+
+        // noisy samples of the sphere
+        let cloud = (0..100)
+            .map(|i| {
+                let (polar, azimuth) = (0.1 + 0.03 * i as f64, 0.7 * i as f64);
+                let radius = 1.0 + 0.05 * (1.3 * i as f64).sin();
+                [
+                    radius * polar.sin() * azimuth.cos(),
+                    radius * polar.sin() * azimuth.sin(),
+                    radius * polar.cos(),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let (snapped, distances) = snap_to_surface(&cloud, &vertices, &faces, &bvh);
+        for ((p, q), d) in cloud.iter().zip(snapped.iter()).zip(distances.iter()) {
+            // the projection is on the surface, at the signed distance from the point
+            assert_relative_eq!(
+                point_to_mesh_distance(*q, &vertices, &faces, Some(&bvh)),
+                0.0,
+                epsilon = 1e-12
+            );
+            let offset = [0, 1, 2].map(|k| p[k] - q[k]);
+            assert_relative_eq!(
+                dot_product3(&offset, &offset).sqrt(),
+                d.abs(),
+                epsilon = 1e-12
+            );
+            let radius = dot_product3(p, p).sqrt();
+            assert!((d - (radius - 1.0)).abs() <= tessellation_error);
+        }
+
+        let empty = Bvh::new(&vertices, &[]);
+        let (snapped, distances) = snap_to_surface(&cloud[..1], &vertices, &[], &empty);
+        assert_eq!(snapped, cloud[..1].to_vec());
+        assert_eq!(distances, vec![f64::INFINITY]);
+    }
+}
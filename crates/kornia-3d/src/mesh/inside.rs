@@ -0,0 +1,272 @@
+use super::{Mesh, MeshError};
+use crate::{
+    bvh::Bvh,
+    linalg::{cross_vec3, dot_product3},
+};
+
+/// Maximum number of ray directions tried for a point before considering it on the surface.
+const MAX_RAYS: usize = 16;
+
+/// Tolerance on the barycentric coordinates of a hit to be considered on an edge.
+const EDGE_TOLERANCE: f64 = 1e-9;
+
+/// Classify points as inside or outside of a closed mesh.
+///
+/// A ray is cast from each point and the number of triangles it crosses is counted with
+/// a [`Bvh`]: a point is inside if the count is odd. A ray hitting an edge or a vertex of
+/// a triangle, or grazing a triangle, could be counted zero or two times, so it is
+/// discarded and a ray in another direction is cast. The points on the surface, for which
+/// every ray hits the mesh at their origin, are inside.
+///
+/// # Arguments
+///
+/// * `points` - The points to classify.
+/// * `mesh` - The closed mesh.
+///
+/// # Returns
+///
+/// Whether each point is inside of the mesh, or [`MeshError::NotWatertight`] if the mesh
+/// is not closed, see [`Mesh::is_watertight`], since its inside is then not defined.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::mesh::{points_inside_mesh, Mesh};
+///
+/// let vertices = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let faces = vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+/// let mesh = Mesh::new(vertices, faces);
+/// let inside = points_inside_mesh(&[[0.1, 0.1, 0.1], [0.5, 0.5, 0.5]], &mesh).unwrap();
+/// assert_eq!(inside, vec![true, false]);
+/// ```
+pub fn points_inside_mesh(points: &[[f64; 3]], mesh: &Mesh) -> Result<Vec<bool>, MeshError> {
+    if !mesh.is_watertight() {
+        return Err(MeshError::NotWatertight);
+    }
+
+    let vertices = mesh.vertices();
+    let faces = mesh.faces();
+    let bvh = Bvh::new(vertices, faces);
+    let (min, max) = bvh.bounds();
+    let scale = (0..3).map(|k| max[k] - min[k]).fold(0.0, f64::max);
+    let directions = ray_directions();
+
+    let classify = |point: &[f64; 3]| {
+        for direction in directions.iter() {
+            let mut crossings = 0;
+            let mut ambiguous = false;
+            bvh.ray_triangles(point, direction, |t| {
+                if ambiguous {
+                    return;
+                }
+                let [a, b, c] = faces[t].map(|v| vertices[v]);
+                match intersect(point, direction, &a, &b, &c, scale) {
+                    Hit::Miss => {}
+                    Hit::Crossing => crossings += 1,
+                    Hit::Ambiguous => ambiguous = true,
+                }
+            });
+            if !ambiguous {
+                return crossings % 2 == 1;
+            }
+        }
+        true
+    };
+
+    Ok(points.iter().map(classify).collect())
+}
+
+/// The intersection of a ray with a triangle.
+enum Hit {
+    Miss,
+    Crossing,
+    /// The ray hits an edge or a vertex, is in the plane of the triangle, or starts on it.
+    Ambiguous,
+}
+
+/// Intersect a ray with a triangle with the Möller–Trumbore algorithm.
+fn intersect(
+    origin: &[f64; 3],
+    direction: &[f64; 3],
+    a: &[f64; 3],
+    b: &[f64; 3],
+    c: &[f64; 3],
+    scale: f64,
+) -> Hit {
+    let sub = |u: &[f64; 3], v: &[f64; 3]| [u[0] - v[0], u[1] - v[1], u[2] - v[2]];
+    let (ab, ac) = (sub(b, a), sub(c, a));
+    let mut p = [0.0; 3];
+    cross_vec3(direction, &ac, &mut p);
+    let det = dot_product3(&ab, &p);
+
+    // the ray is parallel to the triangle
+    let mut normal = [0.0; 3];
+    cross_vec3(&ab, &ac, &mut normal);
+    let normal_norm = dot_product3(&normal, &normal).sqrt();
+    if det.abs() <= EDGE_TOLERANCE * normal_norm {
+        let offset = dot_product3(&sub(origin, a), &normal);
+        return if offset.abs() <= EDGE_TOLERANCE * normal_norm * scale {
+            Hit::Ambiguous
+        } else {
+            Hit::Miss
+        };
+    }
+
+    let ao = sub(origin, a);
+    let u = dot_product3(&ao, &p) / det;
+    let mut q = [0.0; 3];
+    cross_vec3(&ao, &ab, &mut q);
+    let v = dot_product3(direction, &q) / det;
+    let w = 1.0 - u - v;
+    let t = dot_product3(&ac, &q) / det;
+
+    let outside = u < -EDGE_TOLERANCE || v < -EDGE_TOLERANCE || w < -EDGE_TOLERANCE;
+    if outside || t < -EDGE_TOLERANCE * scale {
+        return Hit::Miss;
+    }
+    let on_edge = u <= EDGE_TOLERANCE || v <= EDGE_TOLERANCE || w <= EDGE_TOLERANCE;
+    if on_edge || t <= EDGE_TOLERANCE * scale {
+        return Hit::Ambiguous;
+    }
+    Hit::Crossing
+}
+
+/// Unit directions spread over the sphere, away from the axes and the diagonals that
+/// the edges of meshes are often aligned with.
+fn ray_directions() -> Vec<[f64; 3]> {
+    let golden = std::f64::consts::PI * (3.0 - 5.0f64.sqrt());
+    (0..MAX_RAYS)
+        .map(|i| {
+            let z = 1.0 - 2.0 * (i as f64 + 0.37) / MAX_RAYS as f64;
+            let r = (1.0 - z * z).sqrt();
+            let theta = golden * i as f64 + 0.1;
+            [r * theta.cos(), r * theta.sin(), z]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// The unit cube with its triangles oriented outwards.
+    fn unit_cube() -> Mesh {
+        let vertices = (0..8)
+            .map(|i| [(i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64])
+            .collect();
+        let faces = vec![
+            [0, 2, 1],
+            [1, 2, 3],
+            [4, 5, 6],
+            [5, 7, 6],
+            [0, 1, 4],
+            [1, 5, 4],
+            [2, 6, 3],
+            [3, 6, 7],
+            [0, 4, 2],
+            [2, 4, 6],
+            [1, 3, 5],
+            [3, 7, 5],
+        ];
+        Mesh::new(vertices, faces)
+    }
+
+    /// A sphere of unit radius triangulated along its parallels and meridians.
+    fn uv_sphere(num_parallels: usize, num_meridians: usize) -> Mesh {
+        let pi = std::f64::consts::PI;
+        let mut vertices = vec![[0.0, 0.0, 1.0], [0.0, 0.0, -1.0]];
+        for i in 1..num_parallels {
+            let theta = pi * i as f64 / num_parallels as f64;
+            for j in 0..num_meridians {
+                let phi = 2.0 * pi * j as f64 / num_meridians as f64;
+                vertices.push([
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                ]);
+            }
+        }
+        let ring = |i: usize, j: usize| 2 + (i - 1) * num_meridians + j % num_meridians;
+        let mut faces = Vec::new();
+        for j in 0..num_meridians {
+            faces.push([0, ring(1, j), ring(1, j + 1)]);
+            faces.push([
+                1,
+                ring(num_parallels - 1, j + 1),
+                ring(num_parallels - 1, j),
+            ]);
+            for i in 1..num_parallels - 1 {
+                faces.push([ring(i, j), ring(i + 1, j), ring(i + 1, j + 1)]);
+                faces.push([ring(i, j), ring(i + 1, j + 1), ring(i, j + 1)]);
+            }
+        }
+        Mesh::new(vertices, faces)
+    }
+
+    #[test]
+    fn test_points_inside_cube() -> Result<(), MeshError> {
+        let cube = unit_cube();
+        let mut rng = StdRng::seed_from_u64(0);
+        let points = (0..1000)
+            .map(|_| [0, 1, 2].map(|_| rng.random_range(-0.5..1.5)))
+            .collect::<Vec<_>>();
+        let inside = points_inside_mesh(&points, &cube)?;
+        for (p, inside) in points.iter().zip(inside) {
+            assert_eq!(inside, p.iter().all(|x| (0.0..=1.0).contains(x)), "{p:?}");
+        }
+
+        // close to the faces, in front of their diagonals and of the vertices
+        let points = [
+            [1.0 - 1e-7, 0.5, 0.5],
+            [1.0 + 1e-7, 0.5, 0.5],
+            [0.3, 0.3, 1.0 - 1e-7],
+            [0.3, 0.3, 1.0 + 1e-7],
+            [0.5, 0.5, -1e-7],
+            [0.5, 0.5, 1e-7],
+            [1e-7, 1e-7, 1e-7],
+            [-1e-7, -1e-7, -1e-7],
+            [2.0, 2.0, 2.0],
+            [0.5, 0.5, 0.5],
+        ];
+        let inside = points_inside_mesh(&points, &cube)?;
+        assert_eq!(
+            inside,
+            vec![true, false, true, false, false, true, true, false, false, true]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_points_inside_sphere() -> Result<(), MeshError> {
+        let sphere = uv_sphere(32, 64);
+        let mut rng = StdRng::seed_from_u64(1);
+        // away from the facets, between the inscribed and the circumscribed spheres
+        let points = (0..2000)
+            .map(|_| [0, 1, 2].map(|_| rng.random_range(-1.5..1.5)))
+            .filter(|p| {
+                let r = dot_product3(p, p).sqrt();
+                !(0.99..=1.0).contains(&r)
+            })
+            .collect::<Vec<_>>();
+        let inside = points_inside_mesh(&points, &sphere)?;
+        for (p, inside) in points.iter().zip(inside) {
+            assert_eq!(inside, dot_product3(p, p) < 1.0, "{p:?}");
+        }
+
+        // the poles and the axis are on vertices and edges of the triangulation
+        let inside = points_inside_mesh(&[[0.0, 0.0, 0.5], [0.0, 0.0, 1.5]], &sphere)?;
+        assert_eq!(inside, vec![true, false]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_points_inside_open_mesh() {
+        let cube = unit_cube();
+        let open = Mesh::new(cube.vertices().to_vec(), cube.faces()[1..].to_vec());
+        assert!(matches!(
+            points_inside_mesh(&[[0.5; 3]], &open),
+            Err(MeshError::NotWatertight)
+        ));
+    }
+}
@@ -0,0 +1,22 @@
+mod distance;
+pub use distance::*;
+
+mod inside;
+pub use inside::*;
+
+mod properties;
+pub use properties::*;
+
+mod registration;
+pub use registration::*;
+
+mod triangle_mesh;
+pub use triangle_mesh::*;
+
+/// Error types for the mesh module.
+#[derive(Debug, thiserror::Error)]
+pub enum MeshError {
+    /// The mesh has boundary or non-manifold edges
+    #[error("The mesh is not watertight")]
+    NotWatertight,
+}
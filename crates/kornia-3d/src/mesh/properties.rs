@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+
+use super::Mesh;
+use crate::linalg::{cross_vec3, dot_product3};
+
+/// Compute the normals of the vertices of a mesh and store them in the mesh.
+///
+/// The normal of a vertex is the average of the normals of its triangles weighted by
+/// their areas, i.e. the normalized sum of the cross products of their edges. The
+/// triangles are counter-clockwise around their normal.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh. The vertices without triangles get a zero normal.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::mesh::{compute_vertex_normals, Mesh};
+///
+/// let vertices = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+/// let mut mesh = Mesh::new(vertices, vec![[0, 1, 2]]);
+/// compute_vertex_normals(&mut mesh);
+/// assert_eq!(mesh.vertex_normals(), Some(&[[0.0, 0.0, 1.0]; 3][..]));
+/// ```
+pub fn compute_vertex_normals(mesh: &mut Mesh) {
+    let mut normals = vec![[0.0; 3]; mesh.vertices().len()];
+    for f in mesh.faces() {
+        // twice the area times the unit normal
+        let n = face_cross(mesh, f);
+        for &v in f {
+            for k in 0..3 {
+                normals[v][k] += n[k];
+            }
+        }
+    }
+    for n in normals.iter_mut() {
+        let norm = dot_product3(n, n).sqrt();
+        if norm > 0.0 {
+            *n = n.map(|x| x / norm);
+        }
+    }
+    mesh.set_vertex_normals(normals);
+}
+
+/// Compute the surface area of a mesh.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh.
+///
+/// # Returns
+///
+/// The sum of the areas of the triangles.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::mesh::{surface_area, Mesh};
+///
+/// let vertices = vec![[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 3.0, 0.0]];
+/// assert_eq!(surface_area(&Mesh::new(vertices, vec![[0, 1, 2]])), 3.0);
+/// ```
+pub fn surface_area(mesh: &Mesh) -> f64 {
+    mesh.faces()
+        .iter()
+        .map(|f| {
+            let n = face_cross(mesh, f);
+            0.5 * dot_product3(&n, &n).sqrt()
+        })
+        .sum()
+}
+
+/// Compute the volume enclosed by a mesh.
+///
+/// The volume is the sum of the signed volumes of the tetrahedra formed by the origin and
+/// each triangle, positive for the triangles counter-clockwise when seen from outside.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh.
+///
+/// # Returns
+///
+/// The enclosed volume, negative if the triangles are oriented inwards, or `None` if the
+/// mesh is not watertight, see [`Mesh::is_watertight`].
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::mesh::{enclosed_volume, Mesh};
+///
+/// // a tetrahedron with its triangles oriented outwards
+/// let vertices = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let faces = vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+/// let volume = enclosed_volume(&Mesh::new(vertices, faces)).unwrap();
+/// assert!((volume - 1.0 / 6.0).abs() < 1e-12);
+/// ```
+pub fn enclosed_volume(mesh: &Mesh) -> Option<f64> {
+    if !mesh.is_watertight() {
+        return None;
+    }
+    let volume = mesh
+        .faces()
+        .iter()
+        .map(|f| {
+            let [a, b, c] = f.map(|v| mesh.vertices()[v]);
+            let mut bc = [0.0; 3];
+            cross_vec3(&b, &c, &mut bc);
+            dot_product3(&a, &bc) / 6.0
+        })
+        .sum();
+    Some(volume)
+}
+
+/// Compute the Gaussian and mean curvatures at the vertices of a triangle mesh.
+///
+/// The curvatures are the discrete operators of Meyer et al., integrated over the mixed
+/// Voronoi area `A` of each vertex, the Voronoi region of the vertex in its non-obtuse
+/// triangles and a fraction of the area of its obtuse ones. The Gaussian curvature is the
+/// angle defect of Gauss-Bonnet, `K = (2π - Σθ) / A` for the angles `θ` of the triangles at
+/// the vertex. The mean curvature is half the norm of the cotangent Laplacian of the
+/// positions, `Δx = 1 / (2A) Σ (cot α + cot β) (x - x_j)` over the neighbors `x_j` with the
+/// angles `α` and `β` opposite to their edge, signed positive where `Δx` points along the
+/// normal of the vertex, e.g. on a sphere with its triangles oriented outwards.
+///
+/// # Arguments
+///
+/// * `vertices` - The vertices of the mesh.
+/// * `faces` - The triangles of the mesh as indices into the vertices, counter-clockwise
+///   around their normal.
+///
+/// # Returns
+///
+/// The Gaussian curvature and the mean curvature of each vertex. Both are zero at the
+/// vertices on the boundary of the mesh, whose neighborhood is incomplete, and at the
+/// vertices without a triangle of positive area.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::mesh::mesh_curvatures;
+///
+/// // a flat fan of six triangles around a vertex
+/// let mut vertices = vec![[0.0, 0.0, 0.0]];
+/// vertices.extend((0..6).map(|i| {
+///     let angle = i as f64 * std::f64::consts::PI / 3.0;
+///     [angle.cos(), angle.sin(), 0.0]
+/// }));
+/// let faces = (0..6).map(|i| [0, 1 + i, 1 + (i + 1) % 6]).collect::<Vec<_>>();
+/// let (gaussian, mean) = mesh_curvatures(&vertices, &faces);
+/// assert!(gaussian[0].abs() < 1e-12 && mean[0].abs() < 1e-12);
+/// ```
+pub fn mesh_curvatures(vertices: &[[f64; 3]], faces: &[[usize; 3]]) -> (Vec<f64>, Vec<f64>) {
+    let sub = |u: &[f64; 3], v: &[f64; 3]| [u[0] - v[0], u[1] - v[1], u[2] - v[2]];
+    let num_vertices = vertices.len();
+    let mut angle_sums = vec![0.0; num_vertices];
+    let mut areas = vec![0.0; num_vertices];
+    let mut laplacians = vec![[0.0; 3]; num_vertices];
+    let mut normals = vec![[0.0; 3]; num_vertices];
+    let mut edge_counts: HashMap<(usize, usize), usize> = HashMap::new();
+
+    for face in faces.iter() {
+        for k in 0..3 {
+            let (a, b) = (face[k], face[(k + 1) % 3]);
+            *edge_counts.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+
+        let p = face.map(|v| vertices[v]);
+        let mut n = [0.0; 3];
+        cross_vec3(&sub(&p[1], &p[0]), &sub(&p[2], &p[0]), &mut n);
+        let area = 0.5 * dot_product3(&n, &n).sqrt();
+        if area <= 0.0 {
+            continue;
+        }
+
+        // the angle at each corner and its cotangent
+        let corner = |i: usize| {
+            let (u, v) = (sub(&p[(i + 1) % 3], &p[i]), sub(&p[(i + 2) % 3], &p[i]));
+            let cos = dot_product3(&u, &v);
+            ((2.0 * area).atan2(cos), cos / (2.0 * area))
+        };
+        let corners = [0, 1, 2].map(corner);
+        let (angles, cot) = (corners.map(|c| c.0), corners.map(|c| c.1));
+        let is_obtuse = angles.iter().any(|a| *a > std::f64::consts::FRAC_PI_2);
+
+        for i in 0..3 {
+            let (j, k) = ((i + 1) % 3, (i + 2) % 3);
+            let v = face[i];
+            angle_sums[v] += angles[i];
+            for c in 0..3 {
+                normals[v][c] += n[c];
+            }
+
+            // the edge opposite to the corner i weighs its cotangent
+            let (vj, vk) = (face[j], face[k]);
+            let e = sub(&p[j], &p[k]);
+            for c in 0..3 {
+                laplacians[vj][c] += cot[i] * e[c];
+                laplacians[vk][c] -= cot[i] * e[c];
+            }
+
+            // the mixed Voronoi area of the corner
+            areas[v] += if !is_obtuse {
+                let (eij, eik) = (sub(&p[j], &p[i]), sub(&p[k], &p[i]));
+                (dot_product3(&eik, &eik) * cot[j] + dot_product3(&eij, &eij) * cot[k]) / 8.0
+            } else if angles[i] > std::f64::consts::FRAC_PI_2 {
+                area / 2.0
+            } else {
+                area / 4.0
+            };
+        }
+    }
+
+    let mut on_boundary = vec![false; num_vertices];
+    for ((a, b), count) in edge_counts {
+        if count != 2 {
+            on_boundary[a] = true;
+            on_boundary[b] = true;
+        }
+    }
+
+    let mut gaussian = vec![0.0; num_vertices];
+    let mut mean = vec![0.0; num_vertices];
+    for v in 0..num_vertices {
+        if on_boundary[v] || areas[v] <= 0.0 {
+            continue;
+        }
+        gaussian[v] = (2.0 * std::f64::consts::PI - angle_sums[v]) / areas[v];
+        let laplacian = laplacians[v].map(|x| x / (2.0 * areas[v]));
+        let norm = dot_product3(&laplacian, &laplacian).sqrt();
+        let sign = if dot_product3(&laplacian, &normals[v]) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        mean[v] = sign * 0.5 * norm;
+    }
+    (gaussian, mean)
+}
+
+/// The cross product of the edges of a triangle, of norm twice its area.
+fn face_cross(mesh: &Mesh, face: &[usize; 3]) -> [f64; 3] {
+    let [a, b, c] = face.map(|v| mesh.vertices()[v]);
+    let mut n = [0.0; 3];
+    cross_vec3(
+        &[b[0] - a[0], b[1] - a[1], b[2] - a[2]],
+        &[c[0] - a[0], c[1] - a[1], c[2] - a[2]],
+        &mut n,
+    );
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::collections::HashMap;
+
+    /// The unit cube with its triangles oriented outwards.
+    fn unit_cube() -> Mesh {
+        let vertices = (0..8)
+            .map(|i| [(i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64])
+            .collect();
+        let faces = vec![
+            [0, 2, 1],
+            [1, 2, 3],
+            [4, 5, 6],
+            [5, 7, 6],
+            [0, 1, 4],
+            [1, 5, 4],
+            [2, 6, 3],
+            [3, 6, 7],
+            [0, 4, 2],
+            [2, 4, 6],
+            [1, 3, 5],
+            [3, 7, 5],
+        ];
+        Mesh::new(vertices, faces)
+    }
+
+    /// An icosahedron subdivided `levels` times and projected onto the unit sphere.
+    fn icosphere(levels: usize) -> Mesh {
+        let t = (1.0 + 5.0f64.sqrt()) / 2.0;
+        let mut vertices = vec![
+            [-1.0, t, 0.0],
+            [1.0, t, 0.0],
+            [-1.0, -t, 0.0],
+            [1.0, -t, 0.0],
+            [0.0, -1.0, t],
+            [0.0, 1.0, t],
+            [0.0, -1.0, -t],
+            [0.0, 1.0, -t],
+            [t, 0.0, -1.0],
+            [t, 0.0, 1.0],
+            [-t, 0.0, -1.0],
+            [-t, 0.0, 1.0],
+        ];
+        let mut faces = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+        let normalize = |v: [f64; 3]| {
+            let norm = dot_product3(&v, &v).sqrt();
+            v.map(|x| x / norm)
+        };
+        vertices = vertices.into_iter().map(normalize).collect();
+
+        for _ in 0..levels {
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: usize, b: usize, vertices: &mut Vec<[f64; 3]>| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let (pa, pb) = (vertices[a], vertices[b]);
+                    vertices.push(normalize([0, 1, 2].map(|k| pa[k] + pb[k])));
+                    vertices.len() - 1
+                })
+            };
+            faces = faces
+                .iter()
+                .flat_map(|&[a, b, c]| {
+                    let ab = midpoint(a, b, &mut vertices);
+                    let bc = midpoint(b, c, &mut vertices);
+                    let ca = midpoint(c, a, &mut vertices);
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+        Mesh::new(vertices, faces)
+    }
+
+    #[test]
+    fn test_unit_cube() {
+        let mut cube = unit_cube();
+        assert!(cube.is_watertight());
+        assert_relative_eq!(surface_area(&cube), 6.0, epsilon = 1e-12);
+        assert_relative_eq!(enclosed_volume(&cube).unwrap_or(0.0), 1.0, epsilon = 1e-12);
+
+        // the normals point outwards from the center
+        compute_vertex_normals(&mut cube);
+        let normals = cube.vertex_normals().unwrap_or(&[]);
+        assert_eq!(normals.len(), 8);
+        for (v, n) in cube.vertices().iter().zip(normals.iter()) {
+            assert_relative_eq!(dot_product3(n, n), 1.0, epsilon = 1e-12);
+            let outwards = v.map(|x| x - 0.5);
+            assert!(dot_product3(n, &outwards) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_icosphere() {
+        let mut sphere = icosphere(4);
+        assert_eq!(sphere.len(), 20 * 256);
+        assert!(sphere.is_watertight());
+        let pi = std::f64::consts::PI;
+        assert_relative_eq!(surface_area(&sphere), 4.0 * pi, max_relative = 1e-2);
+        let volume = enclosed_volume(&sphere).unwrap_or(0.0);
+        assert_relative_eq!(volume, 4.0 / 3.0 * pi, max_relative = 1e-2);
+
+        // the normals are close to radial
+        compute_vertex_normals(&mut sphere);
+        let normals = sphere.vertex_normals().unwrap_or(&[]);
+        for (v, n) in sphere.vertices().iter().zip(normals.iter()) {
+            assert!(dot_product3(v, n) > 0.999);
+        }
+    }
+
+    #[test]
+    fn test_mesh_curvatures_sphere() {
+        let sphere = icosphere(4);
+        for radius in [1.0, 2.5] {
+            let vertices = sphere
+                .vertices()
+                .iter()
+                .map(|v| v.map(|x| radius * x))
+                .collect::<Vec<_>>();
+            let (gaussian, mean) = mesh_curvatures(&vertices, sphere.faces());
+            for (k, h) in gaussian.iter().zip(mean.iter()) {
+                assert_relative_eq!(*k, 1.0 / (radius * radius), max_relative = 5e-2);
+                assert_relative_eq!(*h, 1.0 / radius, max_relative = 2e-2);
+            }
+
+            // the inward oriented sphere has a negative mean curvature
+            let flipped = sphere
+                .faces()
+                .iter()
+                .map(|f| [f[0], f[2], f[1]])
+                .collect::<Vec<_>>();
+            let (_, mean) = mesh_curvatures(&vertices, &flipped);
+            assert!(mean.iter().all(|h| *h < 0.0));
+        }
+    }
+
+    #[test]
+    fn test_mesh_curvatures_plane() {
+        // an irregular triangulation of a tilted plane
+        let n = 10;
+        let vertices = (0..n * n)
+            .map(|i| {
+                let (x, y) = ((i % n) as f64, (i / n) as f64);
+                let (x, y) = (x + 0.3 * (1.7 * y).sin(), y + 0.3 * (2.3 * x).cos());
+                [x, y, 0.2 * x - 0.4 * y]
+            })
+            .collect::<Vec<_>>();
+        let faces = (0..(n - 1) * (n - 1))
+            .flat_map(|c| {
+                let v = c / (n - 1) * n + c % (n - 1);
+                [[v, v + 1, v + n + 1], [v, v + n + 1, v + n]]
+            })
+            .collect::<Vec<_>>();
+
+        let (gaussian, mean) = mesh_curvatures(&vertices, &faces);
+        for (k, h) in gaussian.iter().zip(mean.iter()) {
+            assert_relative_eq!(*k, 0.0, epsilon = 1e-12);
+            assert_relative_eq!(*h, 0.0, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_not_watertight() {
+        let cube = unit_cube();
+        let faces = cube.faces()[1..].to_vec();
+        let open = Mesh::new(cube.vertices().to_vec(), faces);
+        assert!(!open.is_watertight());
+        assert_eq!(enclosed_volume(&open), None);
+        assert_relative_eq!(surface_area(&open), 5.5, epsilon = 1e-12);
+        assert!(!Mesh::new(vec![], vec![]).is_watertight());
+    }
+}
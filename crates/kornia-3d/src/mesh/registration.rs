@@ -0,0 +1,318 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    kdtree::KdTree,
+    linalg::{cross_vec3, dot_product3, solve_linear},
+    stats::entropy_convergence_criterion,
+    transforms::{axis_angle_to_rotation_matrix, RigidTransform3},
+};
+
+/// Seed of the sampling of the mesh surface, fixed for reproducibility.
+const SAMPLING_SEED: u64 = 0;
+
+/// Parameters of [`mesh_to_cloud_icp`].
+#[derive(Debug, Clone)]
+pub struct MeshIcpParams {
+    /// The number of points sampled on the mesh surface.
+    pub n_samples: usize,
+    /// Maximum number of iterations to perform.
+    pub max_iterations: usize,
+    /// Convergence tolerance as the difference in RMSE between two consecutive iterations.
+    pub tolerance: f64,
+    /// Maximum distance between a cloud point and its nearest mesh sample to be considered a
+    /// correspondence.
+    pub max_correspondence_distance: f64,
+    /// Convergence threshold on the Kullback-Leibler divergence between the residual
+    /// distributions of two consecutive iterations, in nats, see
+    /// [`entropy_convergence_criterion`]. A non-positive threshold disables the criterion.
+    pub entropy_threshold: f64,
+}
+
+/// Register a template mesh to a point cloud with point-to-plane ICP.
+///
+/// The mesh surface is sampled uniformly, each triangle being drawn with a probability
+/// proportional to its area, and each sample takes the normal of its triangle. Each cloud
+/// point is matched to its nearest sample and the distance to the tangent plane of the
+/// sample is minimized, which converges faster than the point-to-point distance and lets
+/// the scan slide along the flat faces of the model. This is how a CAD model is fitted to a
+/// LiDAR scan of the part for inspection.
+///
+/// The registration starts from the identity, so the mesh must be roughly aligned with the
+/// cloud beforehand.
+///
+/// # Arguments
+///
+/// * `mesh_verts` - The vertices of the mesh.
+/// * `mesh_faces` - The triangles of the mesh as indices into the vertices.
+/// * `cloud` - The points of the scan.
+/// * `params` - The parameters of the registration.
+///
+/// # Returns
+///
+/// The rotation and translation from the mesh to the cloud frame. The identity is returned
+/// if the mesh or the cloud is empty, and the last estimate if the correspondences stop
+/// constraining the pose.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::mesh::{mesh_to_cloud_icp, MeshIcpParams};
+///
+/// // a tetrahedron and its vertices shifted along x
+/// let verts = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let faces = [[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+/// let cloud = (0..400)
+///     .map(|i| {
+///         let (u, v) = ((i % 20) as f64 / 20.0, (i / 20) as f64 / 20.0);
+///         // points on the three faces through the origin
+///         match i % 3 {
+///             0 => [0.02 + u * (1.0 - v), v, 0.0],
+///             1 => [0.02 + u * (1.0 - v), 0.0, v],
+///             _ => [0.02, u * (1.0 - v), v],
+///         }
+///     })
+///     .collect::<Vec<_>>();
+/// let params = MeshIcpParams {
+///     n_samples: 5000,
+///     max_iterations: 30,
+///     tolerance: 1e-9,
+///     max_correspondence_distance: 0.2,
+///     entropy_threshold: 0.0,
+/// };
+/// let (_, translation) = mesh_to_cloud_icp(&verts, &faces, &cloud, &params);
+/// assert!((translation[0] - 0.02).abs() < 2e-3);
+/// ```
+pub fn mesh_to_cloud_icp(
+    mesh_verts: &[[f64; 3]],
+    mesh_faces: &[[usize; 3]],
+    cloud: &[[f64; 3]],
+    params: &MeshIcpParams,
+) -> ([[f64; 3]; 3], [f64; 3]) {
+    let identity = RigidTransform3::identity();
+    let (samples, normals) = sample_mesh_surface(mesh_verts, mesh_faces, params.n_samples);
+    if samples.is_empty() || cloud.is_empty() {
+        return (identity.rotation, identity.translation);
+    }
+
+    // estimate the transformation from the cloud to the mesh frame, whose planes are fixed
+    let kdtree = KdTree::new(&samples);
+    let mut mesh_from_cloud = identity;
+    let mut prev_rmse = f64::INFINITY;
+    let mut prev_residuals = Vec::new();
+    for _ in 0..params.max_iterations {
+        // accumulate the normal equations of the linearized residuals
+        let mut jtj = [[0.0; 6]; 6];
+        let mut jtr = [0.0; 6];
+        let mut residuals = Vec::new();
+        for p in cloud.iter().map(|p| mesh_from_cloud.apply(p)) {
+            let Some(nn) = kdtree.nearest_one(&p) else {
+                continue;
+            };
+            if nn.distance > params.max_correspondence_distance {
+                continue;
+            }
+            let (q, n) = (&samples[nn.index], &normals[nn.index]);
+            let residual = dot_product3(&[p[0] - q[0], p[1] - q[1], p[2] - q[2]], n);
+            let mut torque = [0.0; 3];
+            cross_vec3(&p, n, &mut torque);
+            let jacobian = [torque[0], torque[1], torque[2], n[0], n[1], n[2]];
+            for r in 0..6 {
+                for c in 0..6 {
+                    jtj[r][c] += jacobian[r] * jacobian[c];
+                }
+                jtr[r] -= jacobian[r] * residual;
+            }
+            residuals.push(residual.abs());
+        }
+        if residuals.len() < 6 {
+            break;
+        }
+        let Some(delta) = solve_linear(&jtj, &jtr) else {
+            break;
+        };
+
+        // compose the delta on the left of the current transformation
+        let omega = [delta[0], delta[1], delta[2]];
+        let angle = dot_product3(&omega, &omega).sqrt();
+        let rotation = axis_angle_to_rotation_matrix(&omega, angle).unwrap_or(identity.rotation);
+        let step = RigidTransform3::new(rotation, [delta[3], delta[4], delta[5]]);
+        mesh_from_cloud = step.compose(&mesh_from_cloud);
+
+        let rmse = (residuals.iter().map(|r| r * r).sum::<f64>() / residuals.len() as f64).sqrt();
+        if (prev_rmse - rmse).abs() < params.tolerance
+            || entropy_convergence_criterion(&prev_residuals, &residuals, params.entropy_threshold)
+        {
+            break;
+        }
+        prev_rmse = rmse;
+        prev_residuals = residuals;
+    }
+
+    let cloud_from_mesh = mesh_from_cloud.inverse();
+    (cloud_from_mesh.rotation, cloud_from_mesh.translation)
+}
+
+/// Sample points uniformly on the surface of a mesh with the normals of their triangles.
+fn sample_mesh_surface(
+    verts: &[[f64; 3]],
+    faces: &[[usize; 3]],
+    num_samples: usize,
+) -> (Vec<[f64; 3]>, Vec<[f64; 3]>) {
+    // the cumulative areas and the unit normals of the triangles
+    let mut cumulative_areas = Vec::with_capacity(faces.len());
+    let mut face_normals = Vec::with_capacity(faces.len());
+    let mut total_area = 0.0;
+    for f in faces {
+        let [a, b, c] = f.map(|v| verts[v]);
+        let mut n = [0.0; 3];
+        cross_vec3(
+            &[b[0] - a[0], b[1] - a[1], b[2] - a[2]],
+            &[c[0] - a[0], c[1] - a[1], c[2] - a[2]],
+            &mut n,
+        );
+        let norm = dot_product3(&n, &n).sqrt();
+        total_area += 0.5 * norm;
+        cumulative_areas.push(total_area);
+        face_normals.push(if norm > 0.0 {
+            n.map(|x| x / norm)
+        } else {
+            [0.0; 3]
+        });
+    }
+    if total_area <= 0.0 {
+        return (Vec::new(), Vec::new());
+    }
+
+    let mut rng = StdRng::seed_from_u64(SAMPLING_SEED);
+    (0..num_samples)
+        .map(|_| {
+            let target_area = rng.random::<f64>() * total_area;
+            let i = cumulative_areas
+                .partition_point(|area| *area < target_area)
+                .min(faces.len() - 1);
+            let [a, b, c] = faces[i].map(|v| verts[v]);
+
+            // uniform barycentric coordinates
+            let (r1, r2): (f64, f64) = (rng.random::<f64>().sqrt(), rng.random());
+            let (wa, wb, wc) = (1.0 - r1, r1 * (1.0 - r2), r1 * r2);
+            let p = [0, 1, 2].map(|k| wa * a[k] + wb * b[k] + wc * c[k]);
+            (p, face_normals[i])
+        })
+        .unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A closed box of the given size with its corner at the origin.
+    fn box_mesh(size: [f64; 3]) -> (Vec<[f64; 3]>, Vec<[usize; 3]>) {
+        let verts = (0..8)
+            .map(|i| [0, 1, 2].map(|k| if (i >> k) & 1 == 1 { size[k] } else { 0.0 }))
+            .collect();
+        let faces = vec![
+            [0, 2, 1],
+            [1, 2, 3],
+            [4, 5, 6],
+            [5, 7, 6],
+            [0, 1, 4],
+            [1, 5, 4],
+            [2, 6, 3],
+            [3, 6, 7],
+            [0, 4, 2],
+            [2, 4, 6],
+            [1, 3, 5],
+            [3, 7, 5],
+        ];
+        (verts, faces)
+    }
+
+    #[test]
+    fn test_sample_mesh_surface() {
+        let (verts, faces) = box_mesh([2.0, 1.0, 0.5]);
+        let (samples, normals) = sample_mesh_surface(&verts, &faces, 7000);
+        assert_eq!(samples.len(), 7000);
+
+        // the samples lie on the faces, in proportion to their areas
+        let mut on_top = 0;
+        for (p, n) in samples.iter().zip(normals.iter()) {
+            assert!((dot_product3(n, n) - 1.0).abs() < 1e-12);
+            assert!(p
+                .iter()
+                .zip([2.0, 1.0, 0.5])
+                .all(|(x, s)| (-1e-12..=s + 1e-12).contains(x)));
+            if (p[2] - 0.5).abs() < 1e-12 {
+                on_top += 1;
+                // the normals point outwards
+                assert_eq!(*n, [0.0, 0.0, 1.0]);
+            }
+        }
+        // the top face covers 2 of the 7 square units of the box
+        assert!((on_top as f64 / 7000.0 - 2.0 / 7.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_mesh_to_cloud_icp() -> Result<(), Box<dyn std::error::Error>> {
+        let (verts, faces) = box_mesh([2.0, 1.0, 0.5]);
+        let pose = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.3, -0.2, 1.0], 0.08)?,
+            [0.05, -0.03, 0.04],
+        );
+
+        // a noisy scan of the part in its pose
+        let mut rng = StdRng::seed_from_u64(1);
+        let (scan, _) = sample_mesh_surface(&verts, &faces, 3000);
+        let cloud = scan
+            .iter()
+            .map(|p| {
+                let q = pose.apply(p);
+                q.map(|x| x + rng.random_range(-1e-3..1e-3))
+            })
+            .collect::<Vec<_>>();
+
+        let params = MeshIcpParams {
+            n_samples: 20_000,
+            max_iterations: 50,
+            tolerance: 1e-10,
+            max_correspondence_distance: 0.3,
+            entropy_threshold: 0.0,
+        };
+        let (rotation, translation) = mesh_to_cloud_icp(&verts, &faces, &cloud, &params);
+        let error = RigidTransform3::new(rotation, translation).compose(&pose.inverse());
+        assert!(error.rotation_angle() < 2e-3);
+        assert!(error.translation.iter().all(|t| t.abs() < 2e-3));
+
+        // stopped once the residual distribution settles
+        let params = MeshIcpParams {
+            tolerance: 0.0,
+            entropy_threshold: 1e-3,
+            ..params
+        };
+        let (rotation, translation) = mesh_to_cloud_icp(&verts, &faces, &cloud, &params);
+        let error = RigidTransform3::new(rotation, translation).compose(&pose.inverse());
+        assert!(error.rotation_angle() < 2e-3);
+        assert!(error.translation.iter().all(|t| t.abs() < 2e-3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mesh_to_cloud_icp_empty() {
+        let params = MeshIcpParams {
+            n_samples: 100,
+            max_iterations: 10,
+            tolerance: 1e-6,
+            max_correspondence_distance: 1.0,
+            entropy_threshold: 0.0,
+        };
+        let (verts, faces) = box_mesh([1.0; 3]);
+        let identity = RigidTransform3::identity();
+        assert_eq!(
+            mesh_to_cloud_icp(&verts, &faces, &[], &params),
+            (identity.rotation, identity.translation)
+        );
+        assert_eq!(
+            mesh_to_cloud_icp(&verts, &[], &[[0.0; 3]], &params),
+            (identity.rotation, identity.translation)
+        );
+    }
+}
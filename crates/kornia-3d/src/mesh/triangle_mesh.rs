@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+/// A triangle mesh with shared vertices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mesh {
+    // The vertices of the mesh.
+    vertices: Vec<[f64; 3]>,
+    // The triangles of the mesh as indices into the vertices.
+    faces: Vec<[usize; 3]>,
+    // The normals of the vertices, if computed.
+    vertex_normals: Option<Vec<[f64; 3]>>,
+}
+
+impl Mesh {
+    /// Create a new mesh from its vertices and its triangles.
+    ///
+    /// The triangles are indices into the vertices, counter-clockwise when seen from the
+    /// side their normal points to.
+    pub fn new(vertices: Vec<[f64; 3]>, faces: Vec<[usize; 3]>) -> Self {
+        Self {
+            vertices,
+            faces,
+            vertex_normals: None,
+        }
+    }
+
+    /// Get the number of triangles of the mesh.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Check if the mesh has no triangles.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.faces.is_empty()
+    }
+
+    /// Get as reference the vertices of the mesh.
+    pub fn vertices(&self) -> &[[f64; 3]] {
+        &self.vertices
+    }
+
+    /// Get as reference the triangles of the mesh.
+    pub fn faces(&self) -> &[[usize; 3]] {
+        &self.faces
+    }
+
+    /// Get as reference the normals of the vertices, if computed.
+    ///
+    /// See [`super::compute_vertex_normals`].
+    pub fn vertex_normals(&self) -> Option<&[[f64; 3]]> {
+        self.vertex_normals.as_deref()
+    }
+
+    /// Set the normals of the vertices.
+    pub fn set_vertex_normals(&mut self, normals: Vec<[f64; 3]>) {
+        self.vertex_normals = Some(normals);
+    }
+
+    /// Check if the mesh is closed, i.e. if each edge is shared by exactly two triangles.
+    ///
+    /// A mesh without triangles is not watertight.
+    pub fn is_watertight(&self) -> bool {
+        let mut counts = HashMap::<(usize, usize), usize>::new();
+        for f in self.faces.iter() {
+            for (a, b) in [(f[0], f[1]), (f[1], f[2]), (f[2], f[0])] {
+                *counts.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+            }
+        }
+        !counts.is_empty() && counts.values().all(|&c| c == 2)
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{kdtree::KdTree, linalg::dot_product3};
+
+/// Maximum number of query spheres used to march along the bounding box of the cloud.
+const MAX_MARCHING_STEPS: f64 = 256.0;
+
+/// Pick the point of a cloud hit by a ray, e.g. the point under the mouse cursor.
+///
+/// Among the points within `max_perpendicular_dist` of the ray and in front of its origin,
+/// the one closest to the origin along the ray is picked. The candidates are gathered by
+/// marching query spheres along the ray through the bounding box of the cloud, stopping at
+/// the first sphere with a candidate.
+///
+/// # Arguments
+///
+/// * `tree` - The KD-tree of the point cloud.
+/// * `ray_origin` - The origin of the ray, e.g. the camera center.
+/// * `ray_direction` - The direction of the ray, not necessarily normalized.
+/// * `max_perpendicular_dist` - The maximum distance from a point to the ray.
+///
+/// # Returns
+///
+/// The index of the picked point, or `None` if no point is close enough to the ray.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::kdtree::KdTree;
+/// use kornia_3d::picking::pick_point;
+///
+/// let points = vec![[0.0, 0.0, 5.0], [0.0, 0.01, 2.0], [1.0, 0.0, 1.0]];
+/// let tree = KdTree::new(&points);
+/// assert_eq!(pick_point(&tree, &[0.0; 3], &[0.0, 0.0, 1.0], 0.05), Some(1));
+/// ```
+pub fn pick_point(
+    tree: &KdTree,
+    ray_origin: &[f64; 3],
+    ray_direction: &[f64; 3],
+    max_perpendicular_dist: f64,
+) -> Option<usize> {
+    let mut picked = None;
+    march_cylinder(
+        tree,
+        ray_origin,
+        ray_direction,
+        max_perpendicular_dist,
+        f64::INFINITY,
+        |candidates| {
+            picked = candidates
+                .iter()
+                .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+                .map(|&(_, index)| index);
+            picked.is_none()
+        },
+    );
+    picked
+}
+
+/// Find the points of a cloud inside a finite cylinder.
+///
+/// # Arguments
+///
+/// * `tree` - The KD-tree of the point cloud.
+/// * `origin` - The center of the base of the cylinder.
+/// * `direction` - The direction of the axis of the cylinder, not necessarily normalized.
+/// * `radius` - The radius of the cylinder.
+/// * `length` - The length of the cylinder along its axis.
+///
+/// # Returns
+///
+/// The sorted indices of the points inside the cylinder, boundary included.
+pub fn points_in_cylinder(
+    tree: &KdTree,
+    origin: &[f64; 3],
+    direction: &[f64; 3],
+    radius: f64,
+    length: f64,
+) -> Vec<usize> {
+    let mut inside = Vec::new();
+    march_cylinder(tree, origin, direction, radius, length, |candidates| {
+        inside.extend(candidates.iter().map(|&(_, index)| index));
+        true
+    });
+    inside.sort_unstable();
+    inside
+}
+
+/// March query spheres along the axis of a cylinder through the bounding box of the tree.
+///
+/// The axis is split into consecutive segments, each covered by a query sphere. For each
+/// segment, `visit` receives the `(t, index)` pairs of the points inside the cylinder whose
+/// projection `t` on the axis falls in the segment, and returns whether to continue.
+fn march_cylinder(
+    tree: &KdTree,
+    origin: &[f64; 3],
+    direction: &[f64; 3],
+    radius: f64,
+    length: f64,
+    mut visit: impl FnMut(&[(f64, usize)]) -> bool,
+) {
+    let norm = dot_product3(direction, direction).sqrt();
+    if norm == 0.0 || !norm.is_finite() || radius.is_nan() || radius < 0.0 {
+        return;
+    }
+    let dir = [
+        direction[0] / norm,
+        direction[1] / norm,
+        direction[2] / norm,
+    ];
+
+    let Some((min, max)) = tree.bounds() else {
+        return;
+    };
+    let min = min.map(|v| v - radius);
+    let max = max.map(|v| v + radius);
+
+    // clip the axis to the bounding box inflated by the radius (slab method)
+    let (mut t_enter, mut t_exit) = (0.0f64, length);
+    for k in 0..3 {
+        if dir[k].abs() < 1e-15 {
+            if origin[k] < min[k] || origin[k] > max[k] {
+                return;
+            }
+            continue;
+        }
+        let t0 = (min[k] - origin[k]) / dir[k];
+        let t1 = (max[k] - origin[k]) / dir[k];
+        t_enter = t_enter.max(t0.min(t1));
+        t_exit = t_exit.min(t0.max(t1));
+    }
+    if t_enter > t_exit {
+        return;
+    }
+
+    // segments not much shorter than the radius, and not too many of them
+    let diagonal =
+        ((max[0] - min[0]).powi(2) + (max[1] - min[1]).powi(2) + (max[2] - min[2]).powi(2)).sqrt();
+    let step = (2.0 * radius).max(diagonal / MAX_MARCHING_STEPS);
+
+    let mut candidates = Vec::new();
+    let mut t_start = t_enter;
+    while t_start <= t_exit {
+        let mut t_end = (t_start + step).min(t_exit);
+        if t_end <= t_start {
+            // the step is below the floating point resolution at this distance
+            t_end = t_exit;
+        }
+        let t_mid = 0.5 * (t_start + t_end);
+        let center = [
+            origin[0] + t_mid * dir[0],
+            origin[1] + t_mid * dir[1],
+            origin[2] + t_mid * dir[2],
+        ];
+        let sphere_radius = (0.25 * (t_end - t_start).powi(2) + radius * radius).sqrt();
+
+        candidates.clear();
+        let is_last = t_end >= t_exit;
+        for neighbor in tree.within_radius(&center, sphere_radius) {
+            let p = &tree.points()[neighbor.index];
+            let d = [p[0] - origin[0], p[1] - origin[1], p[2] - origin[2]];
+            let t = dot_product3(&d, &dir);
+            // each point belongs to a single segment
+            let in_segment = t >= t_start && (t < t_end || (is_last && t <= t_end));
+            if !in_segment {
+                continue;
+            }
+            let perpendicular_sq = (dot_product3(&d, &d) - t * t).max(0.0);
+            if perpendicular_sq <= radius * radius {
+                candidates.push((t, neighbor.index));
+            }
+        }
+
+        if !candidates.is_empty() && !visit(&candidates) {
+            return;
+        }
+        if is_last {
+            break;
+        }
+        t_start = t_end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn brute_force_cylinder(
+        points: &[[f64; 3]],
+        origin: &[f64; 3],
+        direction: &[f64; 3],
+        radius: f64,
+        length: f64,
+    ) -> Vec<(f64, usize)> {
+        let norm = dot_product3(direction, direction).sqrt();
+        let dir = direction.map(|v| v / norm);
+        points
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| {
+                let d = [p[0] - origin[0], p[1] - origin[1], p[2] - origin[2]];
+                let t = dot_product3(&d, &dir);
+                let perpendicular_sq = (dot_product3(&d, &d) - t * t).max(0.0);
+                (t >= 0.0 && t <= length && perpendicular_sq <= radius * radius).then_some((t, i))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pick_point_analytic() {
+        let points = vec![
+            [0.0, 0.0, -1.0], // behind the origin
+            [0.0, 0.05, 3.0],
+            [0.0, 0.0, 4.0],
+            [0.2, 0.0, 1.0], // too far from the ray
+        ];
+        let tree = KdTree::new(&points);
+
+        let origin = [0.0, 0.0, 0.0];
+        assert_eq!(pick_point(&tree, &origin, &[0.0, 0.0, 2.0], 0.1), Some(1));
+        assert_eq!(pick_point(&tree, &origin, &[0.0, 0.0, 1.0], 0.01), Some(2));
+        assert_eq!(pick_point(&tree, &origin, &[0.0, 0.0, -1.0], 0.1), Some(0));
+        assert_eq!(pick_point(&tree, &origin, &[1.0, 0.0, 0.0], 0.1), None);
+        assert_eq!(pick_point(&tree, &origin, &[0.0, 0.0, 0.0], 0.1), None);
+        assert_eq!(
+            pick_point(&KdTree::new(&[]), &origin, &[0.0, 0.0, 1.0], 0.1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pick_point_brute_force() {
+        let mut rng = StdRng::seed_from_u64(21);
+        let points = (0..5000)
+            .map(|_| {
+                [
+                    rng.random_range(-1.0..1.0),
+                    rng.random_range(-1.0..1.0),
+                    rng.random_range(-1.0..1.0),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let tree = KdTree::new(&points);
+
+        for _ in 0..50 {
+            let origin: [f64; 3] = [
+                rng.random_range(-2.0..2.0),
+                rng.random_range(-2.0..2.0),
+                rng.random_range(-2.0..2.0),
+            ];
+            let direction = [
+                -origin[0],
+                -origin[1],
+                -origin[2] + rng.random_range(-0.5..0.5),
+            ];
+            let max_dist = rng.random_range(0.001..0.05);
+
+            let expected = brute_force_cylinder(&points, &origin, &direction, max_dist, f64::MAX)
+                .into_iter()
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, i)| i);
+            assert_eq!(pick_point(&tree, &origin, &direction, max_dist), expected);
+        }
+    }
+
+    #[test]
+    fn test_points_in_cylinder_brute_force() {
+        let mut rng = StdRng::seed_from_u64(22);
+        let points = (0..3000)
+            .map(|_| {
+                [
+                    rng.random_range(0.0..2.0),
+                    rng.random_range(0.0..1.0),
+                    rng.random_range(0.0..1.0),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let tree = KdTree::new(&points);
+
+        for _ in 0..20 {
+            let origin = [
+                rng.random_range(0.0..2.0),
+                rng.random_range(0.0..1.0),
+                rng.random_range(0.0..1.0),
+            ];
+            let direction = [
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+            ];
+            let radius = rng.random_range(0.05..0.3);
+            let length = rng.random_range(0.1..2.0);
+
+            let mut expected = brute_force_cylinder(&points, &origin, &direction, radius, length)
+                .into_iter()
+                .map(|(_, i)| i)
+                .collect::<Vec<_>>();
+            expected.sort_unstable();
+            assert_eq!(
+                points_in_cylinder(&tree, &origin, &direction, radius, length),
+                expected
+            );
+        }
+    }
+}
@@ -51,6 +51,50 @@ impl PointCloud {
     }
 }
 
+/// A point cloud captured at a given time with optional per-point intensities.
+#[derive(Debug, Clone)]
+pub struct TimestampedCloud {
+    /// The acquisition timestamp in nanoseconds, if known.
+    pub timestamp_ns: Option<u64>,
+    /// The point cloud.
+    pub cloud: PointCloud,
+    /// The intensity (reflectance) of each point, if available.
+    pub intensities: Option<Vec<f32>>,
+}
+
+/// A point cloud organized as an image, with one point per pixel in row-major order.
+#[derive(Debug, Clone)]
+pub struct OrganizedPointCloud {
+    /// The width of the image.
+    pub width: usize,
+    /// The height of the image.
+    pub height: usize,
+    /// The point of each pixel, zero if the pixel is invalid.
+    pub points: Vec<[f64; 3]>,
+    /// Whether each pixel has a valid point.
+    pub valid: Vec<bool>,
+}
+
+impl OrganizedPointCloud {
+    /// Get the point of a pixel, if it is valid.
+    pub fn get(&self, x: usize, y: usize) -> Option<&[f64; 3]> {
+        let idx = y * self.width + x;
+        self.valid[idx].then(|| &self.points[idx])
+    }
+
+    /// Convert to an unorganized point cloud with only the valid points.
+    pub fn to_pointcloud(&self) -> PointCloud {
+        let points = self
+            .points
+            .iter()
+            .zip(self.valid.iter())
+            .filter(|(_, valid)| **valid)
+            .map(|(p, _)| *p)
+            .collect();
+        PointCloud::new(points, None, None)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
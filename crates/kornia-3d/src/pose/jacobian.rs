@@ -0,0 +1,308 @@
+use crate::{
+    kdtree::KdTree,
+    linalg::{cross_vec3, mat33_mul_vec3},
+    transforms::{se3_exp, RigidTransform3},
+};
+
+/// Compute the Gauss-Newton approximation of the Hessian of the point to point ICP cost.
+///
+/// The cost is the sum of the squared norms of the residuals `e_i = R * p_i + t - q_i` of
+/// the correspondences. The pose is perturbed on the left by `ξ = (ω, τ)`, i.e.
+/// `R' = exp(ω) * R` and `t' = exp(ω) * t + τ`, as in the ICP variants, so that the
+/// Jacobian of each residual is the 3x6 matrix `J_i = [-[R * p_i + t]_x, I]`. The rows of
+/// the stacked Jacobian `J` are the 6-DOF gradients of the residual components.
+///
+/// `J^T * J` is the matrix of the normal equations of a Gauss-Newton step, and its inverse
+/// scaled by the noise variance is the covariance of the estimated pose.
+///
+/// # Arguments
+///
+/// * `src` - The source points.
+/// * `correspondences` - The pairs of source and target indices. The Jacobian of the point
+///   to point residuals does not depend on the target points.
+/// * `r` - The rotation from the source to the target frame.
+/// * `t` - The translation from the source to the target frame.
+///
+/// # Returns
+///
+/// The symmetric 6x6 matrix `J^T * J`, with the rotation parameters first.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pose::icp_jacobian;
+///
+/// let src = vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let jtj = icp_jacobian(&src, &[(0, 0), (1, 1), (2, 2)], &identity, &[0.0; 3]);
+/// // each correspondence constrains the translation along the three axes
+/// assert_eq!(jtj[3][3], 3.0);
+/// assert_eq!(jtj[0][0], 2.0);
+/// ```
+pub fn icp_jacobian(
+    src: &[[f64; 3]],
+    correspondences: &[(usize, usize)],
+    r: &[[f64; 3]; 3],
+    t: &[f64; 3],
+) -> [[f64; 6]; 6] {
+    let mut jtj = [[0.0; 6]; 6];
+    for &(i, _) in correspondences {
+        let mut p = [0.0; 3];
+        mat33_mul_vec3(r, &src[i], &mut p);
+        let p = [p[0] + t[0], p[1] + t[1], p[2] + t[2]];
+
+        // the gradient of the k-th residual component is [p x e_k, e_k]
+        for k in 0..3 {
+            let mut axis = [0.0; 3];
+            axis[k] = 1.0;
+            let mut torque = [0.0; 3];
+            cross_vec3(&p, &axis, &mut torque);
+            let gradient = [torque[0], torque[1], torque[2], axis[0], axis[1], axis[2]];
+            for a in 0..6 {
+                for b in 0..6 {
+                    jtj[a][b] += gradient[a] * gradient[b];
+                }
+            }
+        }
+    }
+    jtj
+}
+
+/// Compute the point to point ICP cost of a pose perturbed by a twist.
+///
+/// The pose is perturbed on the left by the exponential [`se3_exp`] of the twist,
+/// `T' = exp(ξ) * T`, with the rotation parameters first as in [`icp_jacobian`], and the
+/// cost is the sum of the squared distances from the transformed source points to their
+/// nearest target point. Central differences of the cost verify the gradient and the
+/// Gauss-Newton Hessian `2 * J^T * J` numerically, and a sweep of the twist plots the cost
+/// landscape around a local minimum.
+///
+/// # Arguments
+///
+/// * `src` - The source points.
+/// * `dst` - The target points.
+/// * `r` - The rotation from the source to the target frame.
+/// * `t` - The translation from the source to the target frame.
+/// * `twist_perturbation` - The rotation vector and the translation of the perturbation.
+///
+/// # Returns
+///
+/// The sum of the squared nearest neighbor distances, zero if there is no source point and
+/// infinite if there is no target point.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pose::icp_cost_at_perturbation;
+///
+/// let src = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]];
+/// let dst = vec![[0.0, 0.0, 0.5], [1.0, 0.0, 0.5]];
+/// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let cost = icp_cost_at_perturbation(&src, &dst, &identity, &[0.0; 3], [0.0; 6]);
+/// assert_eq!(cost, 0.5);
+/// let twist = [0.0, 0.0, 0.0, 0.0, 0.0, 0.5];
+/// assert_eq!(icp_cost_at_perturbation(&src, &dst, &identity, &[0.0; 3], twist), 0.0);
+/// ```
+pub fn icp_cost_at_perturbation(
+    src: &[[f64; 3]],
+    dst: &[[f64; 3]],
+    r: &[[f64; 3]; 3],
+    t: &[f64; 3],
+    twist_perturbation: [f64; 6],
+) -> f64 {
+    if src.is_empty() {
+        return 0.0;
+    }
+    if dst.is_empty() {
+        return f64::INFINITY;
+    }
+    let transform = se3_exp(&twist_perturbation).compose(&RigidTransform3::new(*r, *t));
+    let index = KdTree::new(dst);
+    src.iter()
+        .filter_map(|p| index.nearest_one(&transform.apply(p)))
+        .map(|nn| nn.distance * nn.distance)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{linalg::solve_linear, transforms::axis_angle_to_rotation_matrix};
+    use approx::assert_relative_eq;
+
+    fn points() -> Vec<[f64; 3]> {
+        (0..20)
+            .map(|i| {
+                let i = i as f64;
+                [(0.9 * i).sin(), (1.7 * i).cos() + 0.5, 0.3 * i - 2.0]
+            })
+            .collect()
+    }
+
+    /// The residuals of the correspondences for the pose perturbed on the left.
+    fn residuals(
+        src: &[[f64; 3]],
+        dst: &[[f64; 3]],
+        r: &[[f64; 3]; 3],
+        t: &[f64; 3],
+        delta: &[f64; 6],
+    ) -> Vec<f64> {
+        let omega = [delta[0], delta[1], delta[2]];
+        let angle = omega.iter().map(|w| w * w).sum::<f64>().sqrt();
+        let rr_delta = if angle > 0.0 {
+            axis_angle_to_rotation_matrix(&omega, angle).unwrap()
+        } else {
+            [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]
+        };
+        src.iter()
+            .zip(dst.iter())
+            .flat_map(|(p, q)| {
+                let mut x = [0.0; 3];
+                mat33_mul_vec3(r, p, &mut x);
+                let x = [x[0] + t[0], x[1] + t[1], x[2] + t[2]];
+                let mut y = [0.0; 3];
+                mat33_mul_vec3(&rr_delta, &x, &mut y);
+                [0, 1, 2].map(|k| y[k] + delta[3 + k] - q[k])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_icp_jacobian_finite_differences() -> Result<(), Box<dyn std::error::Error>> {
+        let src = points();
+        let dst = src
+            .iter()
+            .map(|p| [p[0] + 0.1, p[1] - 0.2, p[2] + 0.05])
+            .collect::<Vec<_>>();
+        let r = axis_angle_to_rotation_matrix(&[0.3, -1.0, 0.5], 0.7)?;
+        let t = [0.4, 1.2, -0.3];
+        let correspondences = (0..src.len()).map(|i| (i, i)).collect::<Vec<_>>();
+        let jtj = icp_jacobian(&src, &correspondences, &r, &t);
+
+        // numerical Jacobian with central differences
+        let h = 1e-6;
+        let columns = (0..6)
+            .map(|c| {
+                let mut plus = [0.0; 6];
+                let mut minus = [0.0; 6];
+                plus[c] = h;
+                minus[c] = -h;
+                let e_plus = residuals(&src, &dst, &r, &t, &plus);
+                let e_minus = residuals(&src, &dst, &r, &t, &minus);
+                e_plus
+                    .iter()
+                    .zip(e_minus.iter())
+                    .map(|(a, b)| (a - b) / (2.0 * h))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        for a in 0..6 {
+            for b in 0..6 {
+                let expected = columns[a]
+                    .iter()
+                    .zip(columns[b].iter())
+                    .map(|(x, y)| x * y)
+                    .sum::<f64>();
+                assert_relative_eq!(jtj[a][b], expected, epsilon = 1e-5, max_relative = 1e-6);
+                assert_eq!(jtj[a][b], jtj[b][a]);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_icp_jacobian_gauss_newton_step() -> Result<(), Box<dyn std::error::Error>> {
+        // a single Gauss-Newton step recovers a small pure translation exactly
+        let src = points();
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let offset = [0.01, -0.02, 0.03];
+        let dst = src
+            .iter()
+            .map(|p| [p[0] + offset[0], p[1] + offset[1], p[2] + offset[2]])
+            .collect::<Vec<_>>();
+        let correspondences = (0..src.len()).map(|i| (i, i)).collect::<Vec<_>>();
+        let jtj = icp_jacobian(&src, &correspondences, &identity, &[0.0; 3]);
+
+        // J^T e accumulated from the same gradients
+        let mut jte = [0.0; 6];
+        for (p, q) in src.iter().zip(dst.iter()) {
+            for k in 0..3 {
+                let mut axis = [0.0; 3];
+                axis[k] = 1.0;
+                let mut torque = [0.0; 3];
+                cross_vec3(p, &axis, &mut torque);
+                let gradient = [torque[0], torque[1], torque[2], axis[0], axis[1], axis[2]];
+                for a in 0..6 {
+                    jte[a] -= gradient[a] * (p[k] - q[k]);
+                }
+            }
+        }
+        let delta = solve_linear(&jtj, &jte).ok_or("singular system")?;
+        for k in 0..3 {
+            assert_relative_eq!(delta[k], 0.0, epsilon = 1e-9);
+            assert_relative_eq!(delta[3 + k], offset[k], epsilon = 1e-9);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_icp_cost_at_perturbation_hessian() -> Result<(), Box<dyn std::error::Error>> {
+        // at a perfect registration the Hessian of the cost is 2 * J^T * J
+        let src = points();
+        let r = axis_angle_to_rotation_matrix(&[0.3, -1.0, 0.5], 0.7)?;
+        let t = [0.4, 1.2, -0.3];
+        let transform = RigidTransform3::new(r, t);
+        let dst = src.iter().map(|p| transform.apply(p)).collect::<Vec<_>>();
+        let correspondences = (0..src.len()).map(|i| (i, i)).collect::<Vec<_>>();
+        let jtj = icp_jacobian(&src, &correspondences, &r, &t);
+
+        let h = 1e-4;
+        let cost = |a: usize, sa: f64, b: usize, sb: f64| {
+            let mut twist = [0.0; 6];
+            twist[a] += sa * h;
+            twist[b] += sb * h;
+            icp_cost_at_perturbation(&src, &dst, &r, &t, twist)
+        };
+        assert!(icp_cost_at_perturbation(&src, &dst, &r, &t, [0.0; 6]) < 1e-24);
+        for (a, row) in jtj.iter().enumerate() {
+            for (b, jtj_ab) in row.iter().enumerate() {
+                let hessian =
+                    (cost(a, 1.0, b, 1.0) - cost(a, 1.0, b, -1.0) - cost(a, -1.0, b, 1.0)
+                        + cost(a, -1.0, b, -1.0))
+                        / (4.0 * h * h);
+                assert_relative_eq!(hessian, 2.0 * jtj_ab, epsilon = 1e-5, max_relative = 1e-5);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_icp_cost_at_perturbation_degenerate() {
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let twist = [0.1, 0.0, 0.0, 0.0, 1.0, 0.0];
+        assert_eq!(
+            icp_cost_at_perturbation(&[], &points(), &identity, &[0.0; 3], twist),
+            0.0
+        );
+        assert_eq!(
+            icp_cost_at_perturbation(&points(), &[], &identity, &[0.0; 3], twist),
+            f64::INFINITY
+        );
+    }
+
+    #[test]
+    fn test_icp_jacobian_degenerate() {
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        assert_eq!(
+            icp_jacobian(&points(), &[], &identity, &[0.0; 3]),
+            [[0.0; 6]; 6]
+        );
+
+        // collinear points do not constrain the rotation about their line
+        let line = (0..5).map(|i| [i as f64, 0.0, 0.0]).collect::<Vec<_>>();
+        let correspondences = (0..5).map(|i| (i, i)).collect::<Vec<_>>();
+        let jtj = icp_jacobian(&line, &correspondences, &identity, &[0.0; 3]);
+        assert_eq!(jtj[0], [0.0; 6]);
+        assert_eq!(jtj[3][3], 5.0);
+    }
+}
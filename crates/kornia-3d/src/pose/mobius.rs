@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, seq::IndexedRandom, Rng, SeedableRng};
+
+use super::fit_rigid_transform_3d;
+use crate::{
+    kdtree::{KdTree, Neighbor},
+    linalg::{cross_vec3, dot_product3, transform_points3d_vec},
+};
+
+/// Seed of the random sampling, fixed for reproducibility.
+const MOBIUS_SEED: u64 = 0;
+
+/// Maximum number of source points casting votes for each sampled transformation.
+const VOTING_POINTS: usize = 100;
+
+/// Regularization of the inverse deviation weighting the votes of a transformation.
+const DEVIATION_EPSILON: f64 = 1e-2;
+
+/// Number of voting points on which a transformation is tested before voting.
+const PRETEST_POINTS: usize = 10;
+
+/// Deviation on the tested points above which a transformation does not vote, its votes
+/// weighing little against those of the transformations close to the true one.
+const MAX_PRETEST_DEVIATION: f64 = 0.5;
+
+/// Fraction of the largest vote a correspondence needs to belong to the peak.
+const PEAK_FRACTION: f64 = 0.5;
+
+/// Number of refinements of the transformation of the peak on its nearest neighbors.
+const REFINE_ITERATIONS: usize = 5;
+
+/// Estimate the rigid transformation between two point clouds with Möbius voting.
+///
+/// Möbius voting (Lipman and Funkhouser 2009) is a global registration that needs neither an
+/// initial guess nor descriptors. A triplet of points and its image determine a Möbius
+/// transformation, which is rigid when the triplets have the same pairwise distances, so
+/// that the triplets are matched by their distances. For each of the `n_samples` samples:
+///
+/// 1. A random source triplet and a random destination anchor are drawn, and the destination
+///    triplets from the anchor whose pairwise distances match the source ones are
+///    enumerated with radius queries on a kd-tree.
+/// 2. Each matched pair of triplets gives the transformation mapping them, with
+///    [`rigid_transform_3d`](super::rigid_transform_3d).
+/// 3. The transformation votes, for a subset of the source points, for the correspondence
+///    of each transformed point with its nearest destination point within the tolerance.
+///    The weight of the votes is the inverse of the deviation of the transformation, the
+///    mean distance of the transformed points to the destination, relative to the
+///    tolerance and capped at one, and the transformations deviating by more than half the
+///    tolerance on a first few points do not vote.
+///
+/// The votes of the transformations near the true one add up on the same correspondences,
+/// while the others scatter. The peak of the correspondence space is the set of mutual best
+/// correspondences with at least half the largest vote, from which the transformation is
+/// fitted and refined on its nearest neighbors. The distance tolerance is the mean spacing
+/// of the destination points.
+///
+/// # Arguments
+///
+/// * `src` - The source points.
+/// * `dst` - The destination points.
+/// * `n_samples` - The number of sampled pairs of a source triplet and a destination anchor.
+///
+/// # Returns
+///
+/// The rotation and translation from the source to the destination frame, or `None` if a
+/// cloud has fewer than three points or if the peak has fewer than three correspondences.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pose::mobius_voting_register;
+///
+/// let src = (0..100)
+///     .map(|i| {
+///         let (x, y) = ((i % 10) as f64 * 0.1, (i / 10) as f64 * 0.1);
+///         [x, y, 0.3 * (3.0 * x).sin() * y + 0.1 * x * x]
+///     })
+///     .collect::<Vec<_>>();
+/// let dst = src.iter().map(|p| [p[0] + 1.0, p[1], p[2]]).collect::<Vec<_>>();
+/// let (_, translation) = mobius_voting_register(&src, &dst, 300).unwrap();
+/// assert!((translation[0] - 1.0).abs() < 1e-9);
+/// ```
+pub fn mobius_voting_register(
+    src: &[[f64; 3]],
+    dst: &[[f64; 3]],
+    n_samples: usize,
+) -> Option<([[f64; 3]; 3], [f64; 3])> {
+    if src.len() < 3 || dst.len() < 3 {
+        return None;
+    }
+    let dst_kdtree = KdTree::new(dst);
+    let delta = mean_spacing(dst, &dst_kdtree)?;
+
+    let mut rng = StdRng::seed_from_u64(MOBIUS_SEED);
+    let voters =
+        rand::seq::index::sample(&mut rng, src.len(), VOTING_POINTS.min(src.len())).into_vec();
+    let voting_points = voters.iter().map(|&i| src[i]).collect::<Vec<_>>();
+
+    let mut votes: HashMap<(usize, usize), f64> = HashMap::new();
+    for _ in 0..n_samples {
+        let triplet = [0, 1, 2].map(|_| src[rng.random_range(0..src.len())]);
+        let [a, b, c] = triplet;
+        let mut normal = [0.0; 3];
+        cross_vec3(&sub(&b, &a), &sub(&c, &a), &mut normal);
+        if dot_product3(&normal, &normal).sqrt() <= delta * delta {
+            continue;
+        }
+        let (ab, ac, bc) = (distance(&a, &b), distance(&a, &c), distance(&b, &c));
+
+        // the destination triplets from a random anchor with the same pairwise distances
+        let Some(anchor) = dst.choose(&mut rng) else {
+            continue;
+        };
+        let shell = |radius: f64| {
+            dst_kdtree
+                .within_radius(anchor, radius + delta)
+                .into_iter()
+                .filter(move |n| n.distance >= radius - delta)
+                .map(|n| dst[n.index])
+                .collect::<Vec<_>>()
+        };
+        let candidates_c = shell(ac);
+        for b_dst in shell(ab) {
+            for c_dst in candidates_c.iter() {
+                if (distance(&b_dst, c_dst) - bc).abs() > delta {
+                    continue;
+                }
+                let Some((rotation, translation)) =
+                    fit_rigid_transform_3d(&triplet, &[*anchor, b_dst, *c_dst])
+                else {
+                    continue;
+                };
+
+                // vote for the correspondences of the transformed source points, weighted by
+                // the inverse of the deviation of the transformation
+                let transformed = transform_points3d_vec(&voting_points, &rotation, &translation);
+                let deviation_of = |neighbors: &[Option<Neighbor>]| {
+                    neighbors
+                        .iter()
+                        .map(|n| n.map_or(1.0, |n| (n.distance / delta).min(1.0)))
+                        .sum::<f64>()
+                        / neighbors.len() as f64
+                };
+                let mut neighbors = transformed
+                    .iter()
+                    .take(PRETEST_POINTS)
+                    .map(|q| dst_kdtree.nearest_one(q))
+                    .collect::<Vec<_>>();
+                if deviation_of(&neighbors) > MAX_PRETEST_DEVIATION {
+                    continue;
+                }
+                neighbors.extend(
+                    transformed
+                        .iter()
+                        .skip(PRETEST_POINTS)
+                        .map(|q| dst_kdtree.nearest_one(q)),
+                );
+                let deviation = deviation_of(&neighbors);
+                let weight = 1.0 / (DEVIATION_EPSILON + deviation);
+                for (i, n) in voters.iter().zip(neighbors.iter()) {
+                    if let Some(n) = n.filter(|n| n.distance < delta) {
+                        *votes.entry((*i, n.index)).or_default() += weight;
+                    }
+                }
+            }
+        }
+    }
+
+    // the mutual best correspondences of the peak
+    let mut best_of_src: HashMap<usize, (usize, f64)> = HashMap::new();
+    let mut best_of_dst: HashMap<usize, (usize, f64)> = HashMap::new();
+    for (&(i, j), &vote) in votes.iter() {
+        for (best, key, other) in [(&mut best_of_src, i, j), (&mut best_of_dst, j, i)] {
+            let entry = best.entry(key).or_insert((other, vote));
+            if vote > entry.1 || (vote == entry.1 && other < entry.0) {
+                *entry = (other, vote);
+            }
+        }
+    }
+    let max_vote = votes.values().fold(0.0, |acc: f64, v| acc.max(*v));
+    let mut peak = best_of_src
+        .iter()
+        .filter(|(i, (j, vote))| {
+            *vote >= PEAK_FRACTION * max_vote && best_of_dst.get(j).is_some_and(|(k, _)| k == *i)
+        })
+        .map(|(i, (j, _))| (*i, *j))
+        .collect::<Vec<_>>();
+    peak.sort_unstable();
+    let (peak_src, peak_dst): (Vec<_>, Vec<_>) =
+        peak.iter().map(|(i, j)| (src[*i], dst[*j])).unzip();
+    let (mut rotation, mut translation) = fit_rigid_transform_3d(&peak_src, &peak_dst)?;
+
+    // refine the transformation on the nearest neighbors within the tolerance
+    for _ in 0..REFINE_ITERATIONS {
+        let (inlier_src, inlier_dst): (Vec<_>, Vec<_>) = src
+            .iter()
+            .zip(transform_points3d_vec(src, &rotation, &translation))
+            .filter_map(|(p, q)| {
+                let n = dst_kdtree.nearest_one(&q)?;
+                (n.distance <= delta).then_some((*p, dst[n.index]))
+            })
+            .unzip();
+        let Some(refined) = fit_rigid_transform_3d(&inlier_src, &inlier_dst) else {
+            break;
+        };
+        (rotation, translation) = refined;
+    }
+
+    Some((rotation, translation))
+}
+
+/// Compute the mean distance of the points to their nearest neighbor.
+fn mean_spacing(points: &[[f64; 3]], kdtree: &KdTree) -> Option<f64> {
+    let spacing = points
+        .iter()
+        .filter_map(|p| kdtree.nearest_n(p, 2).get(1).map(|n| n.distance))
+        .sum::<f64>()
+        / points.len() as f64;
+    (spacing > 0.0).then_some(spacing)
+}
+
+fn sub(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    let d = sub(a, b);
+    dot_product3(&d, &d).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::{axis_angle_to_rotation_matrix, RigidTransform3};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Sample a bumpy patch, with no symmetry.
+    fn patch(rng: &mut StdRng, n: usize) -> Vec<[f64; 3]> {
+        (0..n)
+            .map(|_| {
+                let (u, v): (f64, f64) = (rng.random_range(0.0..1.0), rng.random_range(0.0..0.6));
+                [
+                    u,
+                    v,
+                    0.3 * (3.0 * u).sin() * (2.0 * v + 0.5).cos() + 0.2 * u * u,
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mobius_voting_register() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(7);
+        let src = patch(&mut rng, 100);
+        // a large rotation, out of reach of a local registration
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.3, -0.5, 1.0], 2.0)?,
+            [1.0, -2.0, 0.5],
+        );
+        let mut dst = src.iter().map(|p| truth.apply(p)).collect::<Vec<_>>();
+        // the destination points in another order, with outliers
+        dst.reverse();
+        dst.extend((0..10).map(|_| [0, 1, 2].map(|_| rng.random_range(-1.0..2.0))));
+
+        let (rotation, translation) =
+            mobius_voting_register(&src, &dst, 300).ok_or("no transformation")?;
+        for (row, expected) in rotation.iter().zip(truth.rotation.iter()) {
+            for (r, e) in row.iter().zip(expected.iter()) {
+                assert!((r - e).abs() < 1e-9);
+            }
+        }
+        for (t, e) in translation.iter().zip(truth.translation.iter()) {
+            assert!((t - e).abs() < 1e-9);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_mobius_voting_register_degenerate() {
+        let points = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]];
+        assert!(mobius_voting_register(&points, &points, 10).is_none());
+        // no sample gives no vote
+        let mut rng = StdRng::seed_from_u64(1);
+        let src = patch(&mut rng, 20);
+        assert!(mobius_voting_register(&src, &src, 0).is_none());
+    }
+}
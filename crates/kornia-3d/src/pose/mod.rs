@@ -3,3 +3,21 @@ pub use affine::*;
 
 mod homography;
 pub use homography::*;
+
+mod jacobian;
+pub use jacobian::*;
+
+mod mobius;
+pub use mobius::*;
+
+mod rigid;
+pub use rigid::*;
+
+mod scale;
+pub use scale::*;
+
+mod super4pcs;
+pub use super4pcs::*;
+
+mod synthetic;
+pub use synthetic::*;
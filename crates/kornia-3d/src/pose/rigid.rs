@@ -0,0 +1,399 @@
+use crate::linalg::{eigen_symmetric, LinalgError};
+
+/// A rotation and a translation from the source to the target frame.
+pub type Pose = ([[f64; 3]; 3], [f64; 3]);
+
+/// Compute the rigid transformation between corresponding 3d points.
+///
+/// The rotation is the unit quaternion maximizing the alignment of the centered points, the
+/// eigenvector of the largest eigenvalue of the 4x4 symmetric matrix of Horn's method, so
+/// that it is always a proper rotation. Three non-collinear correspondences make the minimal
+/// problem, and more correspondences are fitted in the least squares sense.
+///
+/// # Arguments
+///
+/// * `x1` - The source 3d points.
+/// * `x2` - The corresponding destination 3d points.
+///
+/// # Returns
+///
+/// The rotation and translation from the source to the destination points, or `None` if
+/// there are fewer than three correspondences or if they are collinear.
+///
+/// # Errors
+///
+/// Returns [`LinalgError::LengthMismatch`] if `x1` and `x2` have different lengths.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pose::rigid_transform_3d;
+///
+/// let x1 = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+/// // a quarter turn about z and a translation
+/// let x2 = [[1.0, 2.0, 3.0], [1.0, 3.0, 3.0], [0.0, 2.0, 3.0]];
+/// let (rotation, translation) = rigid_transform_3d(&x1, &x2)?.unwrap();
+/// assert!((rotation[1][0] - 1.0).abs() < 1e-12);
+/// assert!((translation[2] - 3.0).abs() < 1e-12);
+/// # Ok::<(), kornia_3d::linalg::LinalgError>(())
+/// ```
+pub fn rigid_transform_3d(x1: &[[f64; 3]], x2: &[[f64; 3]]) -> Result<Option<Pose>, LinalgError> {
+    if x1.len() != x2.len() {
+        return Err(LinalgError::LengthMismatch {
+            src: x1.len(),
+            dst: x2.len(),
+        });
+    }
+    Ok(fit_rigid_transform_3d(x1, x2))
+}
+
+/// Compute the rigid transformation between corresponding 3d points of the same length.
+///
+/// The unchecked [`rigid_transform_3d`], for the correspondences paired by construction.
+pub(crate) fn fit_rigid_transform_3d(
+    x1: &[[f64; 3]],
+    x2: &[[f64; 3]],
+) -> Option<([[f64; 3]; 3], [f64; 3])> {
+    debug_assert_eq!(x1.len(), x2.len());
+    weighted_rigid_transform_3d(x1, x2, &vec![1.0; x1.len()])
+}
+
+/// Compute the rigid transformation between corresponding 3d points of given weights.
+///
+/// The weighted least squares variant of [`rigid_transform_3d`], the correspondences with a
+/// zero weight being ignored.
+///
+/// # Returns
+///
+/// The rotation and translation from the source to the destination points, or `None` if
+/// there are fewer than three correspondences of positive weight or if they are collinear.
+pub(crate) fn weighted_rigid_transform_3d(
+    x1: &[[f64; 3]],
+    x2: &[[f64; 3]],
+    weights: &[f64],
+) -> Option<([[f64; 3]; 3], [f64; 3])> {
+    if weights.iter().filter(|w| **w > 0.0).count() < 3 {
+        return None;
+    }
+    let total = weights.iter().sum::<f64>();
+
+    let mut c1 = [0.0; 3];
+    let mut c2 = [0.0; 3];
+    for ((p, q), w) in x1.iter().zip(x2.iter()).zip(weights.iter()) {
+        for k in 0..3 {
+            c1[k] += w * p[k] / total;
+            c2[k] += w * q[k] / total;
+        }
+    }
+
+    // the weighted cross-covariance of the centered points
+    let mut s = [[0.0; 3]; 3];
+    for ((p, q), w) in x1.iter().zip(x2.iter()).zip(weights.iter()) {
+        for a in 0..3 {
+            for b in 0..3 {
+                s[a][b] += w * (p[a] - c1[a]) * (q[b] - c2[b]);
+            }
+        }
+    }
+
+    let n = [
+        [
+            s[0][0] + s[1][1] + s[2][2],
+            s[1][2] - s[2][1],
+            s[2][0] - s[0][2],
+            s[0][1] - s[1][0],
+        ],
+        [
+            s[1][2] - s[2][1],
+            s[0][0] - s[1][1] - s[2][2],
+            s[0][1] + s[1][0],
+            s[2][0] + s[0][2],
+        ],
+        [
+            s[2][0] - s[0][2],
+            s[0][1] + s[1][0],
+            -s[0][0] + s[1][1] - s[2][2],
+            s[1][2] + s[2][1],
+        ],
+        [
+            s[0][1] - s[1][0],
+            s[2][0] + s[0][2],
+            s[1][2] + s[2][1],
+            -s[0][0] - s[1][1] + s[2][2],
+        ],
+    ];
+    let (eigenvalues, eigenvectors) = eigen_symmetric(&n);
+
+    // collinear points leave the rotation about their line undetermined
+    let scale = eigenvalues.iter().fold(0.0f64, |acc, e| acc.max(e.abs()));
+    if scale <= 0.0 || eigenvalues[3] - eigenvalues[2] <= 1e-9 * scale {
+        return None;
+    }
+
+    let [w, x, y, z] = eigenvectors[3];
+    let rotation = [
+        [
+            w * w + x * x - y * y - z * z,
+            2.0 * (x * y - w * z),
+            2.0 * (x * z + w * y),
+        ],
+        [
+            2.0 * (x * y + w * z),
+            w * w - x * x + y * y - z * z,
+            2.0 * (y * z - w * x),
+        ],
+        [
+            2.0 * (x * z - w * y),
+            2.0 * (y * z + w * x),
+            w * w - x * x - y * y + z * z,
+        ],
+    ];
+    let translation = [0, 1, 2].map(|k| {
+        c2[k] - (rotation[k][0] * c1[0] + rotation[k][1] * c1[1] + rotation[k][2] * c1[2])
+    });
+
+    Some((rotation, translation))
+}
+
+/// Compute the translation between corresponding 3d points of a known rotation.
+///
+/// When the rotation is fixed, e.g. by the gravity measured by an IMU, the least squares
+/// translation is closed form, `t = mean(x2) - R * mean(x1)`, and a single correspondence
+/// determines it.
+///
+/// # Arguments
+///
+/// * `src` - The source 3d points.
+/// * `dst` - The corresponding destination 3d points.
+/// * `r_known` - The rotation from the source to the destination points.
+///
+/// # Returns
+///
+/// The translation from the source to the destination points, zero if there is no
+/// correspondence.
+///
+/// # Errors
+///
+/// Returns [`LinalgError::LengthMismatch`] if `src` and `dst` have different lengths.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pose::fit_translation_only;
+///
+/// let src = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]];
+/// // a quarter turn about z and a translation
+/// let r = [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+/// let dst = [[1.0, 2.0, 3.0], [1.0, 3.0, 3.0]];
+/// assert_eq!(fit_translation_only(&src, &dst, &r)?, [1.0, 2.0, 3.0]);
+/// # Ok::<(), kornia_3d::linalg::LinalgError>(())
+/// ```
+pub fn fit_translation_only(
+    src: &[[f64; 3]],
+    dst: &[[f64; 3]],
+    r_known: &[[f64; 3]; 3],
+) -> Result<[f64; 3], LinalgError> {
+    if src.len() != dst.len() {
+        return Err(LinalgError::LengthMismatch {
+            src: src.len(),
+            dst: dst.len(),
+        });
+    }
+    let num = src.len();
+    if num == 0 {
+        return Ok([0.0; 3]);
+    }
+
+    let mut c1 = [0.0; 3];
+    let mut c2 = [0.0; 3];
+    for (p, q) in src.iter().zip(dst.iter()) {
+        for k in 0..3 {
+            c1[k] += p[k] / num as f64;
+            c2[k] += q[k] / num as f64;
+        }
+    }
+    Ok([0, 1, 2]
+        .map(|k| c2[k] - (r_known[k][0] * c1[0] + r_known[k][1] * c1[1] + r_known[k][2] * c1[2])))
+}
+
+/// Compute the rotation between corresponding 3d points of a known translation.
+///
+/// When the translation is measured, e.g. by an external tracker in a hand-eye calibration,
+/// the known translation is subtracted from the destination points and the least squares
+/// rotation about the origin is `R = V * U^T` from the SVD `H = U * S * V^T` of the
+/// cross-covariance `H = Σ src_i * (dst_i - t)^T`, with the sign of the last singular vector
+/// flipped if needed so that it is a proper rotation.
+///
+/// # Arguments
+///
+/// * `src` - The source 3d points.
+/// * `dst` - The corresponding destination 3d points.
+/// * `t_known` - The translation from the source to the destination points.
+///
+/// # Returns
+///
+/// The rotation from the source to the destination points, the identity if there is no
+/// correspondence.
+///
+/// # Errors
+///
+/// Returns [`LinalgError::LengthMismatch`] if `src` and `dst` have different lengths.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pose::fit_rotation_only;
+///
+/// let src = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+/// // a quarter turn about z and a translation
+/// let dst = [[1.0, 3.0, 3.0], [0.0, 2.0, 3.0]];
+/// let rotation = fit_rotation_only(&src, &dst, &[1.0, 2.0, 3.0])?;
+/// assert!((rotation[1][0] - 1.0).abs() < 1e-12);
+/// assert!((rotation[2][2] - 1.0).abs() < 1e-12);
+/// # Ok::<(), kornia_3d::linalg::LinalgError>(())
+/// ```
+pub fn fit_rotation_only(
+    src: &[[f64; 3]],
+    dst: &[[f64; 3]],
+    t_known: &[f64; 3],
+) -> Result<[[f64; 3]; 3], LinalgError> {
+    if src.len() != dst.len() {
+        return Err(LinalgError::LengthMismatch {
+            src: src.len(),
+            dst: dst.len(),
+        });
+    }
+    let mut rotation = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    if src.is_empty() {
+        return Ok(rotation);
+    }
+
+    // the cross-covariance of the source and the untranslated destination points
+    let hh = faer::Mat::<f64>::from_fn(3, 3, |a, b| {
+        src.iter()
+            .zip(dst.iter())
+            .map(|(p, q)| p[a] * (q[b] - t_known[b]))
+            .sum()
+    });
+
+    let svd = hh.svd();
+    let (u_t, v) = (svd.u().transpose(), svd.v());
+    let rr = v * u_t;
+
+    // flip the last singular vector if R is a reflection
+    let sign = if rr.determinant() < 0.0 { -1.0 } else { 1.0 };
+    let correction = faer::Mat::<f64>::from_fn(3, 3, |i, j| match (i, j) {
+        (2, 2) => sign,
+        (i, j) if i == j => 1.0,
+        _ => 0.0,
+    });
+    let rr = v * correction * u_t;
+
+    for (i, row) in rotation.iter_mut().enumerate() {
+        for (j, r) in row.iter_mut().enumerate() {
+            *r = rr.read(i, j);
+        }
+    }
+    Ok(rotation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{linalg::transform_points3d_vec, transforms::axis_angle_to_rotation_matrix};
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_rigid_transform_3d() -> Result<(), Box<dyn std::error::Error>> {
+        let x1 = (0..10)
+            .map(|i| {
+                let i = i as f64;
+                [(0.7 * i).sin(), (1.3 * i).cos(), 0.2 * i]
+            })
+            .collect::<Vec<_>>();
+        for angle in [0.0, 0.5, 3.0] {
+            let rotation = axis_angle_to_rotation_matrix(&[0.3, -0.5, 1.0], angle)?;
+            let translation = [0.5, -1.0, 2.0];
+            let x2 = transform_points3d_vec(&x1, &rotation, &translation);
+
+            // the minimal problem and the least squares problem
+            for num in [3, x1.len()] {
+                let (r, t) = rigid_transform_3d(&x1[..num], &x2[..num])?.ok_or("no solution")?;
+                for i in 0..3 {
+                    assert_relative_eq!(t[i], translation[i], epsilon = 1e-9);
+                    for j in 0..3 {
+                        assert_relative_eq!(r[i][j], rotation[i][j], epsilon = 1e-9);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rigid_transform_3d_degenerate() {
+        let x1 = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [2.0, 0.0, 0.0]];
+        let x2 = [[0.0, 0.0, 1.0], [0.0, 1.0, 1.0], [0.0, 2.0, 1.0]];
+        assert!(matches!(rigid_transform_3d(&x1, &x2), Ok(None)));
+        assert!(matches!(rigid_transform_3d(&x1[..2], &x2[..2]), Ok(None)));
+        assert!(matches!(
+            rigid_transform_3d(&x1, &x2[..2]),
+            Err(LinalgError::LengthMismatch { src: 3, dst: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_fit_translation_only() -> Result<(), Box<dyn std::error::Error>> {
+        let src = (0..10)
+            .map(|i| {
+                let i = i as f64;
+                [(0.7 * i).sin(), (1.3 * i).cos(), 0.2 * i]
+            })
+            .collect::<Vec<_>>();
+        let rotation = axis_angle_to_rotation_matrix(&[0.3, -0.5, 1.0], 0.5)?;
+        let translation = [0.5, -1.0, 2.0];
+        let dst = transform_points3d_vec(&src, &rotation, &translation);
+
+        let t = fit_translation_only(&src, &dst, &rotation)?;
+        for k in 0..3 {
+            assert_relative_eq!(t[k], translation[k], epsilon = 1e-12);
+        }
+        assert_eq!(fit_translation_only(&[], &[], &rotation)?, [0.0; 3]);
+        assert!(matches!(
+            fit_translation_only(&src[..3], &dst, &rotation),
+            Err(LinalgError::LengthMismatch { src: 3, dst: 10 })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fit_rotation_only() -> Result<(), Box<dyn std::error::Error>> {
+        let src = (0..10)
+            .map(|i| {
+                let i = i as f64;
+                [(0.7 * i).sin(), (1.3 * i).cos(), 0.2 * i]
+            })
+            .collect::<Vec<_>>();
+        let translation = [0.5, -1.0, 2.0];
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        // the identity and a quarter turn about an oblique axis
+        for rotation in [
+            identity,
+            axis_angle_to_rotation_matrix(&[0.3, -0.5, 1.0], std::f64::consts::FRAC_PI_2)?,
+        ] {
+            let dst = transform_points3d_vec(&src, &rotation, &translation);
+            let r = fit_rotation_only(&src, &dst, &translation)?;
+            for i in 0..3 {
+                for j in 0..3 {
+                    assert_relative_eq!(r[i][j], rotation[i][j], epsilon = 1e-9);
+                }
+            }
+        }
+        assert_eq!(fit_rotation_only(&[], &[], &translation)?, identity);
+        assert!(matches!(
+            fit_rotation_only(&[], &src, &translation),
+            Err(LinalgError::LengthMismatch { src: 0, dst: 10 })
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{kdtree::KdTree, linalg::dot_product3};
+
+/// Maximum number of iterations of the scale estimation.
+const SCALE_MAX_ITERATIONS: usize = 50;
+
+/// Relative change of the scale below which the estimation has converged.
+const SCALE_TOLERANCE: f64 = 1e-12;
+
+/// Estimate the scale between two scans of a known relative rotation with ICP.
+///
+/// For corresponding points, the least squares scale of `min_s Σ ‖s * R * p_src + t -
+/// p_dst‖²` is closed form, `s = Σ p_src_centered · (R^T * p_dst_centered) / Σ
+/// ‖p_src_centered‖²`, with the points centered on the centroids of the correspondences and
+/// `t` following from the centroids. The scans have different resolutions and no
+/// correspondence, so that the scale and the translation are estimated as in ICP:
+///
+/// 1. The scale is initialized with the ratio of the RMS distances of the scans to their
+///    centroid, which does not depend on the sampling, and the translation with the
+///    centroids.
+/// 2. Each point of the sparser scan is matched to its nearest point of the denser one,
+///    the source being transformed, so that the matches lie close to the surface and the
+///    sampling of the coarse scan does not bias the scale.
+/// 3. The scale and the translation are solved in closed form from the matches.
+///
+/// The steps 2 and 3 are repeated until the scale converges. This recovers the scale of a
+/// CAD model in a unit or to a manufacturing tolerance differing from the scan of the part.
+///
+/// # Arguments
+///
+/// * `src` - The source points, e.g. sampled on the model.
+/// * `dst` - The destination points, e.g. of the scan.
+/// * `r_known` - The rotation from the source to the destination frame.
+///
+/// # Returns
+///
+/// The scale from the source to the destination frame, one if either scan is empty or has
+/// all its points at the same position.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pose::estimate_scale;
+///
+/// // a model in millimeters and its scan in meters
+/// let src = (0..100)
+///     .map(|i| [(i % 10) as f64 * 10.0, (i / 10) as f64 * 10.0, (i % 7) as f64])
+///     .collect::<Vec<_>>();
+/// let dst = src.iter().map(|p| p.map(|x| x * 1e-3)).collect::<Vec<_>>();
+/// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let scale = estimate_scale(&src, &dst, &identity);
+/// assert!((scale - 1e-3).abs() < 1e-12);
+/// ```
+pub fn estimate_scale(src: &[[f64; 3]], dst: &[[f64; 3]], r_known: &[[f64; 3]; 3]) -> f64 {
+    let (Some(src_centroid), Some(dst_centroid)) = (centroid(src), centroid(dst)) else {
+        return 1.0;
+    };
+    let rms = |points: &[[f64; 3]], c: &[f64; 3]| {
+        let sum = points
+            .iter()
+            .map(|p| {
+                let d = sub(p, c);
+                dot_product3(&d, &d)
+            })
+            .sum::<f64>();
+        (sum / points.len() as f64).sqrt()
+    };
+    let (src_rms, dst_rms) = (rms(src, &src_centroid), rms(dst, &dst_centroid));
+    if !(src_rms > 0.0 && dst_rms > 0.0) {
+        return 1.0;
+    }
+
+    // the source rotated in the destination frame, so that x = s * R * p + t = s * p' + t
+    let rotated = src.iter().map(|p| rotate(r_known, p)).collect::<Vec<_>>();
+    let mut scale = dst_rms / src_rms;
+    let mut translation = sub(
+        &dst_centroid,
+        &rotate(r_known, &src_centroid).map(|x| scale * x),
+    );
+
+    // the points of the sparser scan matched to the denser one, close to the surface
+    let src_is_denser = src.len() >= dst.len();
+    let index = KdTree::new(if src_is_denser { &rotated } else { dst });
+    for _ in 0..SCALE_MAX_ITERATIONS {
+        let matches = if src_is_denser {
+            dst.iter()
+                .filter_map(|q| {
+                    let x = [0, 1, 2].map(|k| (q[k] - translation[k]) / scale);
+                    index.nearest_one(&x).map(|n| (rotated[n.index], *q))
+                })
+                .collect::<Vec<_>>()
+        } else {
+            rotated
+                .iter()
+                .filter_map(|p| {
+                    let x = [0, 1, 2].map(|k| scale * p[k] + translation[k]);
+                    index.nearest_one(&x).map(|n| (*p, dst[n.index]))
+                })
+                .collect::<Vec<_>>()
+        };
+        let (matched_src, matched_dst): (Vec<_>, Vec<_>) = matches.into_iter().unzip();
+        let (Some(cp), Some(cq)) = (centroid(&matched_src), centroid(&matched_dst)) else {
+            break;
+        };
+
+        // the closed form scale of the centered correspondences
+        let (mut num, mut den) = (0.0, 0.0);
+        for (p, q) in matched_src.iter().zip(matched_dst.iter()) {
+            let (p, q) = (sub(p, &cp), sub(q, &cq));
+            num += dot_product3(&p, &q);
+            den += dot_product3(&p, &p);
+        }
+        if den.is_nan() || den <= 0.0 {
+            break;
+        }
+        let new_scale = num / den;
+        translation = [0, 1, 2].map(|k| cq[k] - new_scale * cp[k]);
+
+        let change = (new_scale - scale).abs();
+        scale = new_scale;
+        if change <= SCALE_TOLERANCE * scale.abs() {
+            break;
+        }
+    }
+
+    scale
+}
+
+fn centroid(points: &[[f64; 3]]) -> Option<[f64; 3]> {
+    if points.is_empty() {
+        return None;
+    }
+    let mut c = [0.0; 3];
+    for p in points.iter() {
+        for k in 0..3 {
+            c[k] += p[k] / points.len() as f64;
+        }
+    }
+    Some(c)
+}
+
+fn rotate(r: &[[f64; 3]; 3], p: &[f64; 3]) -> [f64; 3] {
+    [0, 1, 2].map(|k| dot_product3(&r[k], p))
+}
+
+fn sub(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Sample a bumpy patch, with no symmetry.
+    fn patch(rng: &mut StdRng, n: usize) -> Vec<[f64; 3]> {
+        (0..n)
+            .map(|_| {
+                let (u, v): (f64, f64) = (rng.random_range(0.0..1.0), rng.random_range(0.0..0.6));
+                [
+                    u,
+                    v,
+                    0.3 * (3.0 * u).sin() * (2.0 * v + 0.5).cos() + 0.2 * u * u,
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_estimate_scale_different_resolutions() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(11);
+        // a dense model and a sparse scan of the same part, 2% larger
+        let model = patch(&mut rng, 10000);
+        let r = axis_angle_to_rotation_matrix(&[0.3, -0.5, 1.0], 0.8)?;
+        let scan = patch(&mut rng, 800)
+            .iter()
+            .map(|p| {
+                let q = rotate(&r, p);
+                [1.02 * q[0] + 0.5, 1.02 * q[1] - 1.0, 1.02 * q[2] + 2.0]
+            })
+            .collect::<Vec<_>>();
+
+        let scale = estimate_scale(&model, &scan, &r);
+        assert!((scale - 1.02).abs() < 2e-3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate_scale_same_points() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(12);
+        let src = patch(&mut rng, 500);
+        let r = axis_angle_to_rotation_matrix(&[1.0, 0.2, 0.0], 2.0)?;
+        let dst = src
+            .iter()
+            .map(|p| rotate(&r, p).map(|x| 25.4 * x - 3.0))
+            .collect::<Vec<_>>();
+        assert!((estimate_scale(&src, &dst, &r) - 25.4).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate_scale_degenerate() {
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let points = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]];
+        assert_eq!(estimate_scale(&[], &points, &identity), 1.0);
+        assert_eq!(estimate_scale(&points, &[], &identity), 1.0);
+        assert_eq!(estimate_scale(&[[1.0; 3]; 3], &points, &identity), 1.0);
+    }
+}
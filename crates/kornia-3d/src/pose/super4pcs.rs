@@ -0,0 +1,387 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::fit_rigid_transform_3d;
+use crate::{
+    kdtree::KdTree,
+    linalg::{cross_vec3, dot_product3, transform_points3d_vec},
+};
+
+/// Seed of the random sampling, fixed for reproducibility.
+const SUPER4PCS_SEED: u64 = 0;
+
+/// Probability of drawing at least one base inside the overlap, which sets the number of trials.
+const SUCCESS_PROBABILITY: f64 = 0.99;
+
+/// Minimum number of bases tried, so that a base without congruent set is not fatal.
+const MIN_TRIALS: usize = 10;
+
+/// Maximum number of bases tried, whatever the overlap.
+const MAX_TRIALS: usize = 200;
+
+/// Number of refinements of the best transformation on its inliers.
+const REFINE_ITERATIONS: usize = 5;
+
+/// Number of random triangles drawn to select the widest first three points of a base.
+const BASE_ATTEMPTS: usize = 100;
+
+/// Parameters of [`super4pcs_register`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Super4PcsParams {
+    /// The approximation accuracy: the tolerance on the distances when matching the bases and
+    /// the maximum distance between an aligned source point and its nearest destination point
+    /// to count it in the overlap.
+    pub delta: f64,
+    /// The expected fraction of the source overlapping the destination, in `(0, 1]`.
+    pub overlap: f64,
+    /// The number of points randomly sampled in each cloud.
+    pub samples: usize,
+}
+
+/// Estimate the rigid transformation between two point clouds with Super4PCS.
+///
+/// The Smart Indexed 4-Points Congruent Sets algorithm (Mellado et al. 2014) is a global
+/// registration that needs neither an initial guess nor descriptors. At each trial:
+///
+/// 1. A wide, nearly coplanar base of four points is drawn in the source. The two diagonals
+///    of the base are the segments `b0 b1` and `b2 b3`, whose lengths, angle and the ratios
+///    at which they intersect are invariant under rigid transformations.
+/// 2. The pairs of destination points at the lengths of the diagonals are extracted with
+///    radius queries on a kd-tree.
+/// 3. The pairs are indexed by their intermediate points at the intersection ratios, so that
+///    the congruent sets are the pairs of pairs whose intermediate points coincide and whose
+///    angle is the angle of the base, found in O(n²) expected time instead of enumerating
+///    all the quadruplets.
+/// 4. Each congruent set gives a rigid transformation, scored by the fraction of source
+///    samples within `delta` of the destination, the largest common pointset.
+///
+/// The number of trials grows as the overlap decreases and the search stops as soon as the
+/// expected overlap is reached. The best transformation is refined on its inliers, a few
+/// times as they are updated.
+///
+/// # Arguments
+///
+/// * `src` - The source points.
+/// * `dst` - The destination points.
+/// * `params` - The parameters of the registration.
+///
+/// # Returns
+///
+/// The rotation and translation from the source to the destination frame, or `None` if a
+/// cloud has fewer than four points or if no congruent set was found.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pose::{super4pcs_register, Super4PcsParams};
+///
+/// let src = (0..100)
+///     .map(|i| {
+///         let (x, y) = ((i % 10) as f64 * 0.1, (i / 10) as f64 * 0.1);
+///         [x, y, 0.3 * (3.0 * x).sin() * y]
+///     })
+///     .collect::<Vec<_>>();
+/// let dst = src.iter().map(|p| [p[0] + 1.0, p[1], p[2]]).collect::<Vec<_>>();
+/// let params = Super4PcsParams { delta: 0.01, overlap: 1.0, samples: 100 };
+/// let (_, translation) = super4pcs_register(&src, &dst, params).unwrap();
+/// assert!((translation[0] - 1.0).abs() < 1e-9);
+/// ```
+pub fn super4pcs_register(
+    src: &[[f64; 3]],
+    dst: &[[f64; 3]],
+    params: Super4PcsParams,
+) -> Option<([[f64; 3]; 3], [f64; 3])> {
+    if src.len() < 4 || dst.len() < 4 {
+        return None;
+    }
+
+    let mut rng = StdRng::seed_from_u64(SUPER4PCS_SEED);
+    let mut sample = |points: &[[f64; 3]]| {
+        rand::seq::index::sample(&mut rng, points.len(), params.samples.min(points.len()))
+            .iter()
+            .map(|i| points[i])
+            .collect::<Vec<_>>()
+    };
+    let src_samples = sample(src);
+    let dst_samples = sample(dst);
+    let dst_kdtree = KdTree::new(&dst_samples);
+
+    // the bases span the part of the source expected to overlap the destination
+    let (min, max) = KdTree::new(&src_samples).bounds()?;
+    let extent = sub(&max, &min);
+    let diameter = dot_product3(&extent, &extent).sqrt();
+    let max_base_distance = params.overlap * diameter;
+
+    // the fraction of the source samples within delta of the destination
+    let overlap_of = |rotation: &[[f64; 3]; 3], translation: &[f64; 3]| {
+        let num_inliers = transform_points3d_vec(&src_samples, rotation, translation)
+            .iter()
+            .filter(|p| {
+                dst_kdtree
+                    .nearest_one(p)
+                    .is_some_and(|n| n.distance <= params.delta)
+            })
+            .count();
+        num_inliers as f64 / src_samples.len() as f64
+    };
+
+    let overlap4 = params.overlap.clamp(0.0, 1.0).powi(4);
+    let num_trials = ((1.0 - SUCCESS_PROBABILITY).ln() / (1.0 - overlap4).ln()).ceil();
+    let num_trials = if num_trials.is_finite() {
+        (num_trials as usize).clamp(MIN_TRIALS, MAX_TRIALS)
+    } else {
+        MIN_TRIALS
+    };
+
+    let mut best: Option<(f64, [[f64; 3]; 3], [f64; 3])> = None;
+    for _ in 0..num_trials {
+        let Some(base) = select_base(&src_samples, max_base_distance, &mut rng) else {
+            continue;
+        };
+        for quad in congruent_sets(&base, &dst_samples, &dst_kdtree, params.delta) {
+            let Some((rotation, translation)) = fit_rigid_transform_3d(&base.points, &quad) else {
+                continue;
+            };
+            let overlap = overlap_of(&rotation, &translation);
+            if best.map_or(true, |(o, _, _)| overlap > o) {
+                best = Some((overlap, rotation, translation));
+            }
+        }
+        if best.is_some_and(|(o, _, _)| o >= params.overlap) {
+            break;
+        }
+    }
+
+    // refine the best transformation on its nearest neighbors within delta
+    let (_, mut rotation, mut translation) = best.filter(|(o, _, _)| *o > 0.0)?;
+    for _ in 0..REFINE_ITERATIONS {
+        let (inlier_src, inlier_dst): (Vec<_>, Vec<_>) = src_samples
+            .iter()
+            .zip(transform_points3d_vec(
+                &src_samples,
+                &rotation,
+                &translation,
+            ))
+            .filter_map(|(p, q)| {
+                let n = dst_kdtree.nearest_one(&q)?;
+                (n.distance <= params.delta).then_some((*p, dst_samples[n.index]))
+            })
+            .unzip();
+        let Some(refined) = fit_rigid_transform_3d(&inlier_src, &inlier_dst) else {
+            break;
+        };
+        (rotation, translation) = refined;
+    }
+
+    Some((rotation, translation))
+}
+
+/// A coplanar base of four points and its affine invariants.
+struct Base {
+    /// The points, whose diagonals are the segments `b0 b1` and `b2 b3`.
+    points: [[f64; 3]; 4],
+    /// The ratio at which the second diagonal intersects the first one, from `b0`.
+    r1: f64,
+    /// The ratio at which the first diagonal intersects the second one, from `b2`.
+    r2: f64,
+}
+
+/// Select a wide, nearly coplanar base of four points whose diagonals intersect.
+fn select_base(points: &[[f64; 3]], max_distance: f64, rng: &mut StdRng) -> Option<Base> {
+    let within = |a: &[f64; 3], b: &[f64; 3]| squared_distance(a, b) <= max_distance.powi(2);
+
+    // the widest triangle among random draws
+    let mut triangle: Option<(f64, [usize; 3])> = None;
+    for _ in 0..BASE_ATTEMPTS {
+        let idx = [0, 1, 2].map(|_| rng.random_range(0..points.len()));
+        let [a, b, c] = idx.map(|i| points[i]);
+        if !(within(&a, &b) && within(&b, &c) && within(&a, &c)) {
+            continue;
+        }
+        let mut normal = [0.0; 3];
+        cross_vec3(&sub(&b, &a), &sub(&c, &a), &mut normal);
+        let area = dot_product3(&normal, &normal).sqrt();
+        if area > 0.0 && triangle.map_or(true, |(best, _)| area > best) {
+            triangle = Some((area, idx));
+        }
+    }
+    let (area, idx) = triangle?;
+    let [a, b, c] = idx.map(|i| points[i]);
+    let mut normal = [0.0; 3];
+    cross_vec3(&sub(&b, &a), &sub(&c, &a), &mut normal);
+    let normal = normal.map(|v| v / area);
+
+    // the fourth point closest to the plane of the triangle making a convex quadrilateral
+    let mut base: Option<(f64, Base)> = None;
+    for (i, d) in points.iter().enumerate() {
+        if idx.contains(&i) || !(within(d, &a) && within(d, &b) && within(d, &c)) {
+            continue;
+        }
+        let distance = dot_product3(&sub(d, &a), &normal).abs();
+        if base.as_ref().is_some_and(|(best, _)| distance >= *best) {
+            continue;
+        }
+        // the three ways of pairing the points into two diagonals
+        for [p, q, r, s] in [[a, b, c, *d], [a, c, b, *d], [a, *d, b, c]] {
+            let Some((r1, r2)) = intersection_ratios(&p, &q, &r, &s) else {
+                continue;
+            };
+            if (0.0..=1.0).contains(&r1) && (0.0..=1.0).contains(&r2) {
+                let points = [p, q, r, s];
+                base = Some((distance, Base { points, r1, r2 }));
+                break;
+            }
+        }
+    }
+    base.map(|(_, base)| base)
+}
+
+/// Find the sets of four destination points congruent to a base.
+fn congruent_sets(
+    base: &Base,
+    points: &[[f64; 3]],
+    kdtree: &KdTree,
+    delta: f64,
+) -> Vec<[[f64; 3]; 4]> {
+    let [b0, b1, b2, b3] = base.points;
+    let (d1, d2) = (
+        squared_distance(&b0, &b1).sqrt(),
+        squared_distance(&b2, &b3).sqrt(),
+    );
+    let base_angle = angle(&sub(&b1, &b0), &sub(&b3, &b2));
+    let angle_tolerance = 2.0 * delta / d1.min(d2);
+
+    // the ordered pairs of points at a given distance
+    let pairs = |length: f64| {
+        points
+            .iter()
+            .enumerate()
+            .flat_map(|(i, p)| {
+                kdtree
+                    .within_radius(p, length + delta)
+                    .into_iter()
+                    .filter(move |n| n.index != i && n.distance >= length - delta)
+                    .map(move |n| (i, n.index))
+            })
+            .collect::<Vec<_>>()
+    };
+    let pairs1 = pairs(d1);
+    let pairs2 = pairs(d2);
+    if pairs1.is_empty() || pairs2.is_empty() {
+        return Vec::new();
+    }
+
+    // index the first pairs by their intermediate points
+    let lerp = |(i, j): (usize, usize), r: f64| {
+        [0, 1, 2].map(|k| points[i][k] + r * (points[j][k] - points[i][k]))
+    };
+    let intermediates = pairs1
+        .iter()
+        .map(|pair| lerp(*pair, base.r1))
+        .collect::<Vec<_>>();
+    let intermediates_kdtree = KdTree::new(&intermediates);
+
+    let mut sets = Vec::new();
+    for &(k, l) in pairs2.iter() {
+        let e2 = lerp((k, l), base.r2);
+        for n in intermediates_kdtree.within_radius(&e2, delta) {
+            let (i, j) = pairs1[n.index];
+            if i == k || i == l || j == k || j == l {
+                continue;
+            }
+            let pair_angle = angle(&sub(&points[j], &points[i]), &sub(&points[l], &points[k]));
+            if (pair_angle - base_angle).abs() <= angle_tolerance {
+                sets.push([points[i], points[j], points[k], points[l]]);
+            }
+        }
+    }
+    sets
+}
+
+/// Compute the ratios along the lines `p q` and `r s` of their closest points.
+fn intersection_ratios(
+    p: &[f64; 3],
+    q: &[f64; 3],
+    r: &[f64; 3],
+    s: &[f64; 3],
+) -> Option<(f64, f64)> {
+    let (u, v, w) = (sub(q, p), sub(s, r), sub(p, r));
+    let (a, b, c) = (
+        dot_product3(&u, &u),
+        dot_product3(&u, &v),
+        dot_product3(&v, &v),
+    );
+    let (d, e) = (dot_product3(&u, &w), dot_product3(&v, &w));
+    let denominator = a * c - b * b;
+    if denominator <= 1e-12 * a * c {
+        return None;
+    }
+    Some(((b * e - c * d) / denominator, (a * e - b * d) / denominator))
+}
+
+/// Compute the angle between two vectors.
+fn angle(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    let norms = (dot_product3(a, a) * dot_product3(b, b)).sqrt();
+    (dot_product3(a, b) / norms).clamp(-1.0, 1.0).acos()
+}
+
+fn sub(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn squared_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    let d = sub(a, b);
+    dot_product3(&d, &d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::axis_angle_to_rotation_matrix;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_super4pcs_register() -> Result<(), Box<dyn std::error::Error>> {
+        // a terrain patch
+        let mut rng = StdRng::seed_from_u64(3);
+        let src = (0..400)
+            .map(|_| {
+                let (x, y): (f64, f64) = (rng.random_range(0.0..2.0), rng.random_range(0.0..1.5));
+                [x, y, 0.3 * (2.0 * x).sin() * (3.0 * y).cos()]
+            })
+            .collect::<Vec<_>>();
+
+        let rotation = axis_angle_to_rotation_matrix(&[0.4, -0.2, 1.0], 2.5)?;
+        let translation = [1.0, 4.0, -2.0];
+        let mut dst = transform_points3d_vec(&src, &rotation, &translation);
+        // 20% of the destination is clutter, and the source points it replaced may have a
+        // wrong neighbor within delta
+        for p in dst.iter_mut().skip(320) {
+            *p = [0, 1, 2].map(|_| rng.random_range(-5.0..5.0));
+        }
+
+        let params = Super4PcsParams {
+            delta: 0.01,
+            overlap: 0.8,
+            samples: 400,
+        };
+        let (r, t) = super4pcs_register(&src, &dst, params).ok_or("no registration")?;
+        for i in 0..3 {
+            assert_relative_eq!(t[i], translation[i], epsilon = 1e-3);
+            for j in 0..3 {
+                assert_relative_eq!(r[i][j], rotation[i][j], epsilon = 1e-3);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_super4pcs_register_degenerate() {
+        let params = Super4PcsParams {
+            delta: 0.01,
+            overlap: 1.0,
+            samples: 10,
+        };
+        let points = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        assert!(super4pcs_register(&points, &points, params).is_none());
+    }
+}
@@ -0,0 +1,147 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::linalg::transform_points3d_vec;
+
+/// Generate the target of a registration problem with known correspondences.
+///
+/// The source points are transformed by the ground-truth rotation and translation, each
+/// coordinate is perturbed by a zero-mean Gaussian noise, and a fraction of the target points,
+/// chosen at random, are replaced by outliers drawn uniformly in the bounding box of the
+/// target. The `i`-th target point corresponds to the `i`-th source point unless it is an
+/// outlier. The generator is seeded so that a test or a benchmark sees the same data on
+/// every run.
+///
+/// # Arguments
+///
+/// * `src` - The source points.
+/// * `r` - The ground-truth rotation from the source to the target frame.
+/// * `t` - The ground-truth translation from the source to the target frame.
+/// * `noise_std` - The standard deviation of the noise of each coordinate.
+/// * `outlier_fraction` - The fraction of the target points replaced by outliers, clamped to
+///   `[0, 1]`.
+/// * `seed` - The seed of the random generator.
+///
+/// # Returns
+///
+/// The source points and the target points, in the same order.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pose::generate_ground_truth_correspondences;
+///
+/// let src = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let r = [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+/// let t = [1.0, 2.0, 3.0];
+///
+/// let (_, dst) = generate_ground_truth_correspondences(&src, &r, &t, 0.0, 0.0, 0);
+/// assert_eq!(dst[1], [1.0, 3.0, 3.0]);
+/// ```
+pub fn generate_ground_truth_correspondences(
+    src: &[[f64; 3]],
+    r: &[[f64; 3]; 3],
+    t: &[f64; 3],
+    noise_std: f64,
+    outlier_fraction: f64,
+    seed: u64,
+) -> (Vec<[f64; 3]>, Vec<[f64; 3]>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut dst = transform_points3d_vec(src, r, t);
+    if noise_std > 0.0 {
+        for p in dst.iter_mut() {
+            *p = p.map(|x| x + noise_std * standard_normal(&mut rng));
+        }
+    }
+
+    let num_outliers = (outlier_fraction.clamp(0.0, 1.0) * dst.len() as f64).round() as usize;
+    if num_outliers > 0 {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for p in dst.iter() {
+            for k in 0..3 {
+                min[k] = min[k].min(p[k]);
+                max[k] = max[k].max(p[k]);
+            }
+        }
+        for i in rand::seq::index::sample(&mut rng, dst.len(), num_outliers).into_vec() {
+            dst[i] = [0, 1, 2].map(|k| min[k] + (max[k] - min[k]) * rng.random::<f64>());
+        }
+    }
+
+    (src.to_vec(), dst)
+}
+
+/// Draw a sample of the standard normal distribution with the Box-Muller transform.
+///
+/// # Arguments
+///
+/// * `rng` - The random generator, seeded for reproducible samples.
+///
+/// # Returns
+///
+/// A sample of zero mean and unit variance.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pose::standard_normal;
+/// use rand::{rngs::StdRng, SeedableRng};
+///
+/// let mut rng = StdRng::seed_from_u64(0);
+/// let mean = (0..10000).map(|_| standard_normal(&mut rng)).sum::<f64>() / 10000.0;
+/// assert!(mean.abs() < 0.05);
+/// ```
+pub fn standard_normal(rng: &mut impl Rng) -> f64 {
+    let u1: f64 = rng.random_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.random();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::axis_angle_to_rotation_matrix;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_generate_ground_truth_correspondences() -> Result<(), Box<dyn std::error::Error>> {
+        let src = (0..2000)
+            .map(|i| {
+                let i = i as f64;
+                [(0.7 * i).sin(), (1.3 * i).cos(), 0.001 * i]
+            })
+            .collect::<Vec<_>>();
+        let r = axis_angle_to_rotation_matrix(&[0.3, -0.5, 1.0], 0.8)?;
+        let t = [0.5, -1.0, 2.0];
+        let exact = transform_points3d_vec(&src, &r, &t);
+
+        let noise_std = 0.01;
+        let (points, dst) = generate_ground_truth_correspondences(&src, &r, &t, noise_std, 0.2, 4);
+        assert_eq!(points, src);
+        assert_eq!(dst.len(), src.len());
+
+        // the inliers are within a few standard deviations of their exact position
+        let residuals = exact
+            .iter()
+            .zip(dst.iter())
+            .map(|(p, q)| (0..3).map(|k| q[k] - p[k]).collect::<Vec<_>>())
+            .filter(|d| d.iter().all(|x| x.abs() < 5.0 * noise_std))
+            .collect::<Vec<_>>();
+        let num_outliers = src.len() - residuals.len();
+        assert!((395..=400).contains(&num_outliers));
+        let variance =
+            residuals.iter().flatten().map(|x| x * x).sum::<f64>() / (3 * residuals.len()) as f64;
+        assert_relative_eq!(variance.sqrt(), noise_std, epsilon = 5e-4);
+
+        // reproducible from the seed
+        let (_, again) = generate_ground_truth_correspondences(&src, &r, &t, noise_std, 0.2, 4);
+        assert_eq!(again, dst);
+        let (_, other) = generate_ground_truth_correspondences(&src, &r, &t, noise_std, 0.2, 5);
+        assert_ne!(other, dst);
+
+        // without noise nor outliers, the target is the transformed source
+        let (_, clean) = generate_ground_truth_correspondences(&src, &r, &t, 0.0, 0.0, 4);
+        assert_eq!(clean, exact);
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{
+    kdtree::KdTree,
+    linalg::{dot_product3, eigen_symmetric33},
+    pointcloud::PointCloud,
+};
+
+/// Number of neighbors of the one-ring of each point, which drives the contraction.
+const NUM_NEIGHBORS: usize = 16;
+
+/// Radius of the balls sampling the contracted cloud into skeleton nodes, as a fraction of
+/// the diagonal of the bounding box of the cloud.
+const SAMPLE_RADIUS_FACTOR: f64 = 0.05;
+
+/// Extract the curve skeleton of a point cloud by Laplacian contraction.
+///
+/// The method follows Au et al. 2008, "Skeleton Extraction by Mesh Contraction", on the
+/// k-nearest neighbor graph of the points instead of a mesh:
+///
+/// 1. Contraction: at each iteration, each point moves by `contraction_factor` of its
+///    Laplacian, the offset to the centroid of its current neighbors. The component of the
+///    offset along the principal direction of the neighborhood is removed, so that the
+///    cross-sections collapse onto the medial curves while the branches keep their length.
+/// 2. Sampling: the contracted points are covered with balls whose centers are the skeleton
+///    nodes, each node being moved to the centroid of the points it covers.
+/// 3. Connectivity: two nodes are connected when the neighbor graph of the original cloud
+///    connects their points, and the cycles left by thick regions are broken with a minimum
+///    spanning tree of the connections.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud, sampled on the surface or in the volume of the shape.
+/// * `contraction_factor` - The fraction of the Laplacian each point moves by at each
+///   iteration, in `(0, 1]`.
+/// * `iterations` - The number of contraction iterations.
+///
+/// # Returns
+///
+/// The skeleton nodes and the edges between them as pairs of node indices, with the smaller
+/// index first. The skeleton of each connected component of the cloud is a tree.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pointcloud::PointCloud;
+/// use kornia_3d::skeleton::extract_skeleton;
+///
+/// // a tube along the x axis
+/// let points = (0..2000)
+///     .map(|i| {
+///         let angle = (i % 20) as f64 * std::f64::consts::PI / 10.0;
+///         [(i / 20) as f64 * 0.02, 0.05 * angle.cos(), 0.05 * angle.sin()]
+///     })
+///     .collect::<Vec<_>>();
+/// let (nodes, edges) = extract_skeleton(&PointCloud::new(points, None, None), 0.5, 20);
+/// assert_eq!(edges.len(), nodes.len() - 1);
+/// assert!(nodes.iter().all(|n| n[1].abs() < 0.01 && n[2].abs() < 0.01));
+/// ```
+pub fn extract_skeleton(
+    cloud: &PointCloud,
+    contraction_factor: f64,
+    iterations: usize,
+) -> (Vec<[f64; 3]>, Vec<(usize, usize)>) {
+    let points = cloud.points();
+    if points.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+
+    // the one-ring of each point in the original cloud, without the point itself
+    let kdtree = KdTree::new(points);
+    let one_rings = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            kdtree
+                .nearest_n(p, NUM_NEIGHBORS + 1)
+                .iter()
+                .map(|n| n.index)
+                .filter(|&j| j != i)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let contracted = contract(points, contraction_factor, iterations);
+
+    // cover the contracted points with balls, in the order of the points
+    let (min, max) = kdtree.bounds().unwrap_or(([0.0; 3], [0.0; 3]));
+    let diagonal = (0..3)
+        .map(|k| (max[k] - min[k]).powi(2))
+        .sum::<f64>()
+        .sqrt();
+    let sample_radius = SAMPLE_RADIUS_FACTOR * diagonal;
+    let contracted_kdtree = KdTree::new(&contracted);
+    let mut centers = Vec::new();
+    let mut covered = vec![false; contracted.len()];
+    for (i, p) in contracted.iter().enumerate() {
+        if covered[i] {
+            continue;
+        }
+        centers.push(*p);
+        for n in contracted_kdtree.within_radius(p, sample_radius) {
+            covered[n.index] = true;
+        }
+    }
+
+    // assign each point to its nearest ball and move the nodes to the centroids
+    let centers_kdtree = KdTree::new(&centers);
+    let labels = contracted
+        .iter()
+        .map(|p| centers_kdtree.nearest_one(p).map_or(0, |n| n.index))
+        .collect::<Vec<_>>();
+    let mut sums = vec![([0.0; 3], 0usize); centers.len()];
+    for (p, &label) in contracted.iter().zip(labels.iter()) {
+        let (sum, count) = &mut sums[label];
+        sum.iter_mut().zip(p.iter()).for_each(|(s, v)| *s += v);
+        *count += 1;
+    }
+    let nodes = sums
+        .iter()
+        .zip(centers.iter())
+        .map(|((sum, count), center)| {
+            if *count == 0 {
+                *center
+            } else {
+                sum.map(|s| s / *count as f64)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // the connections between the nodes through the original neighbor graph
+    let mut connections = one_rings
+        .iter()
+        .enumerate()
+        .flat_map(|(i, ring)| {
+            let labels = &labels;
+            ring.iter()
+                .map(move |&j| (labels[i].min(labels[j]), labels[i].max(labels[j])))
+        })
+        .filter(|(a, b)| a != b)
+        .collect::<Vec<_>>();
+    connections.sort_unstable();
+    connections.dedup();
+
+    // Kruskal's minimum spanning tree weighted by the lengths of the connections
+    let length = |(a, b): &(usize, usize)| {
+        let d = [
+            nodes[*a][0] - nodes[*b][0],
+            nodes[*a][1] - nodes[*b][1],
+            nodes[*a][2] - nodes[*b][2],
+        ];
+        dot_product3(&d, &d)
+    };
+    connections.sort_by(|x, y| length(x).total_cmp(&length(y)).then(x.cmp(y)));
+    let mut parents = (0..nodes.len()).collect::<Vec<_>>();
+    let mut edges = Vec::new();
+    for (a, b) in connections {
+        let (root_a, root_b) = (find_root(&mut parents, a), find_root(&mut parents, b));
+        if root_a != root_b {
+            parents[root_a] = root_b;
+            edges.push((a, b));
+        }
+    }
+    edges.sort_unstable();
+
+    (nodes, edges)
+}
+
+/// Contract the points towards the medial curves of the shape.
+fn contract(points: &[[f64; 3]], contraction_factor: f64, iterations: usize) -> Vec<[f64; 3]> {
+    let mut current = points.to_vec();
+    for _ in 0..iterations {
+        let kdtree = KdTree::new(&current);
+        current = current
+            .iter()
+            .map(|p| {
+                let neighbors = kdtree.nearest_n(p, NUM_NEIGHBORS + 1);
+                let num = neighbors.len() as f64;
+                let mut centroid = [0.0; 3];
+                for n in neighbors.iter() {
+                    for k in 0..3 {
+                        centroid[k] += current[n.index][k] / num;
+                    }
+                }
+                let mut covariance = [[0.0; 3]; 3];
+                for n in neighbors.iter() {
+                    let d = [0, 1, 2].map(|k| current[n.index][k] - centroid[k]);
+                    for a in 0..3 {
+                        for b in 0..3 {
+                            covariance[a][b] += d[a] * d[b] / num;
+                        }
+                    }
+                }
+
+                // remove the component of the Laplacian along the principal direction
+                let (_, eigenvectors) = eigen_symmetric33(&covariance);
+                let axis = eigenvectors[2];
+                let mut laplacian = [0, 1, 2].map(|k| centroid[k] - p[k]);
+                let along = dot_product3(&laplacian, &axis);
+                for k in 0..3 {
+                    laplacian[k] -= along * axis[k];
+                }
+                [0, 1, 2].map(|k| p[k] + contraction_factor * laplacian[k])
+            })
+            .collect();
+    }
+    current
+}
+
+/// Find the root of the set of an element, with path halving.
+fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tube of the given radius around the segment from the origin to `end`.
+    fn tube(end: [f64; 3], radius: f64) -> Vec<[f64; 3]> {
+        let length = dot_product3(&end, &end).sqrt();
+        let axis = end.map(|v| v / length);
+        // two unit vectors orthogonal to the axis, the tubes lie in the xy plane
+        let u = [-axis[1], axis[0], 0.0];
+        let v = [0.0, 0.0, 1.0];
+        let num_rings = (length / 0.02) as usize;
+        let mut points = Vec::new();
+        for i in 0..=num_rings {
+            let t = i as f64 * 0.02;
+            for j in 0..16 {
+                let angle = j as f64 * std::f64::consts::PI / 8.0;
+                let (c, s) = (radius * angle.cos(), radius * angle.sin());
+                points.push([0, 1, 2].map(|k| t * axis[k] + c * u[k] + s * v[k]));
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn test_extract_skeleton_y_shape() {
+        // three branches at 120 degrees from the origin
+        let tips = (0..3)
+            .map(|b| {
+                let angle = (90.0 + 120.0 * b as f64).to_radians();
+                [angle.cos(), angle.sin(), 0.0]
+            })
+            .collect::<Vec<_>>();
+        let points = tips
+            .iter()
+            .flat_map(|tip| tube(*tip, 0.05))
+            .collect::<Vec<_>>();
+        let (nodes, edges) = extract_skeleton(&PointCloud::new(points, None, None), 0.5, 20);
+
+        // the skeleton is a tree
+        assert!(nodes.len() > 10);
+        assert_eq!(edges.len(), nodes.len() - 1);
+        assert!(edges.iter().all(|(a, b)| a < b && *b < nodes.len()));
+
+        // the nodes lie on the axes of the branches
+        for node in nodes.iter() {
+            let distance = tips
+                .iter()
+                .map(|tip| {
+                    let t = dot_product3(node, tip).clamp(0.0, 1.0);
+                    (0..3)
+                        .map(|k| (node[k] - t * tip[k]).powi(2))
+                        .sum::<f64>()
+                        .sqrt()
+                })
+                .fold(f64::INFINITY, f64::min);
+            assert!(distance < 0.02, "{node:?} {distance}");
+        }
+
+        // three branches: three leaves at the tips and a single junction
+        let mut degrees = vec![0; nodes.len()];
+        for (a, b) in edges.iter() {
+            degrees[*a] += 1;
+            degrees[*b] += 1;
+        }
+        let leaves = (0..nodes.len())
+            .filter(|&i| degrees[i] == 1)
+            .collect::<Vec<_>>();
+        assert_eq!(leaves.len(), 3);
+        for tip in tips.iter() {
+            assert!(leaves.iter().any(|&i| {
+                (0..3).map(|k| (nodes[i][k] - tip[k]).powi(2)).sum::<f64>() < 0.15f64.powi(2)
+            }));
+        }
+        assert_eq!(degrees.iter().filter(|d| **d >= 3).count(), 1);
+    }
+
+    #[test]
+    fn test_extract_skeleton_degenerate() {
+        let (nodes, edges) = extract_skeleton(&PointCloud::new(vec![], None, None), 0.5, 10);
+        assert!(nodes.is_empty() && edges.is_empty());
+
+        let cloud = PointCloud::new(vec![[1.0, 2.0, 3.0]], None, None);
+        let (nodes, edges) = extract_skeleton(&cloud, 0.5, 10);
+        assert_eq!(nodes, vec![[1.0, 2.0, 3.0]]);
+        assert!(edges.is_empty());
+    }
+}
@@ -0,0 +1,952 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{kdtree::KdTree, pointcloud::PointCloud};
+
+/// Number of bins of the residual histograms of [`entropy_convergence_criterion`].
+const RESIDUAL_HISTOGRAM_BINS: usize = 16;
+
+/// Minimum number of samples of the Lilliefors test of [`is_gaussian`].
+const LILLIEFORS_MIN_SAMPLES: usize = 5;
+
+/// Fit a Gaussian model to the residuals of a registration.
+///
+/// The residuals are the distances from each transformed source point to its nearest
+/// neighbor in the destination. A Gaussian residual distribution suggests a good
+/// registration, while a heavy-tailed one suggests outliers or a systematic error.
+///
+/// # Arguments
+///
+/// * `src_transformed` - The source points transformed into the destination frame.
+/// * `dst` - The destination points.
+///
+/// # Returns
+///
+/// The mean, the standard deviation, the skewness and the excess kurtosis of the
+/// residuals. All the moments are zero if there are no residuals.
+pub fn fit_residual_distribution(
+    src_transformed: &[[f64; 3]],
+    dst: &[[f64; 3]],
+) -> (f64, f64, f64, f64) {
+    let kdtree = KdTree::new(dst);
+    let residuals = src_transformed
+        .iter()
+        .filter_map(|p| kdtree.nearest_one(p).map(|n| n.distance))
+        .collect::<Vec<_>>();
+    moments(&residuals)
+}
+
+/// Compute the mean, standard deviation, skewness and excess kurtosis of a sample.
+fn moments(samples: &[f64]) -> (f64, f64, f64, f64) {
+    if samples.is_empty() {
+        return (0.0, 0.0, 0.0, 0.0);
+    }
+
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+
+    let (mut m2, mut m3, mut m4) = (0.0, 0.0, 0.0);
+    for x in samples {
+        let d = x - mean;
+        let d2 = d * d;
+        m2 += d2;
+        m3 += d2 * d;
+        m4 += d2 * d2;
+    }
+    m2 /= n;
+    m3 /= n;
+    m4 /= n;
+
+    let std_dev = m2.sqrt();
+    if m2 < f64::EPSILON * mean.abs().max(1.0) {
+        return (mean, std_dev, 0.0, 0.0);
+    }
+    let skewness = m3 / (m2 * std_dev);
+    let kurtosis = m4 / (m2 * m2) - 3.0;
+
+    (mean, std_dev, skewness, kurtosis)
+}
+
+/// Check if a sample is drawn from a Gaussian distribution.
+///
+/// The samples are standardized with their own mean and standard deviation and compared
+/// to the standard normal distribution with the Lilliefors test. Its statistic is the one
+/// of the Kolmogorov-Smirnov test, but since the mean and the standard deviation are
+/// estimated from the samples it is smaller than for a fully known distribution, and its
+/// p-value follows the approximation of Dallal and Wilkinson (1986) instead of the
+/// Kolmogorov distribution.
+///
+/// # Arguments
+///
+/// * `residuals` - The samples to test.
+/// * `significance` - The significance level of the test, e.g. 0.05.
+///
+/// # Returns
+///
+/// `false` if the Gaussian hypothesis is rejected at the given significance level, or if
+/// there are fewer than five samples or they have no spread.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::stats::is_gaussian;
+///
+/// let residuals = vec![1.0; 100];
+/// assert!(!is_gaussian(&residuals, 0.05));
+/// ```
+pub fn is_gaussian(residuals: &[f64], significance: f64) -> bool {
+    let (mean, std_dev, _, _) = moments(residuals);
+    if residuals.len() < LILLIEFORS_MIN_SAMPLES || std_dev.is_nan() || std_dev <= 0.0 {
+        return false;
+    }
+
+    let mut standardized = residuals
+        .iter()
+        .map(|x| (x - mean) / std_dev)
+        .collect::<Vec<_>>();
+    standardized.sort_by(|a, b| a.total_cmp(b));
+
+    let n = standardized.len() as f64;
+    let statistic = standardized
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            let cdf = normal_cdf(x);
+            (cdf - i as f64 / n).max((i + 1) as f64 / n - cdf)
+        })
+        .fold(0.0, f64::max);
+
+    lilliefors_p_value(statistic, n) > significance
+}
+
+/// Cumulative distribution function of the standard normal distribution.
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Error function with the Abramowitz and Stegun 7.1.26 approximation (|error| < 1.5e-7).
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    y.copysign(x)
+}
+
+/// P-value of the Lilliefors statistic for `n` samples, see `lillie.test` of the R package
+/// `nortest`.
+///
+/// The approximation of Dallal and Wilkinson is accurate for p-values below 0.1, and the
+/// larger ones are interpolated on the modified statistic of Stephens (1974).
+fn lilliefors_p_value(statistic: f64, n: f64) -> f64 {
+    // the approximation is fitted up to 100 samples, and scaled beyond
+    let (d, m) = if n <= 100.0 {
+        (statistic, n)
+    } else {
+        (statistic * (n / 100.0).powf(0.49), 100.0)
+    };
+    let p_value = (-7.01256 * d * d * (m + 2.78019) + 2.99587 * d * (m + 2.78019).sqrt()
+        - 0.122119
+        + 0.974598 / m.sqrt()
+        + 1.67997 / m)
+        .exp();
+    if p_value <= 0.1 {
+        return p_value;
+    }
+
+    let sqrt_n = n.sqrt();
+    let k = (sqrt_n - 0.01 + 0.85 / sqrt_n) * statistic;
+    let p_value = if k <= 0.302 {
+        1.0
+    } else if k <= 0.5 {
+        2.76773 - 19.828315 * k + 80.709644 * k.powi(2) - 138.55152 * k.powi(3)
+            + 81.218052 * k.powi(4)
+    } else if k <= 0.9 {
+        -4.901232 + 40.662806 * k - 97.490286 * k.powi(2) + 94.029866 * k.powi(3)
+            - 32.355711 * k.powi(4)
+    } else if k <= 1.31 {
+        6.198765 - 19.558097 * k + 23.186922 * k.powi(2) - 12.234627 * k.powi(3)
+            + 2.423045 * k.powi(4)
+    } else {
+        0.0
+    };
+    p_value.clamp(0.0, 1.0)
+}
+
+/// Test whether registration residuals are consistent with a zero-mean Gaussian noise.
+///
+/// The statistic `Σ (r_i / σ)²` follows a chi-squared distribution with one degree of
+/// freedom per residual if the residuals are drawn from a zero-mean Gaussian with standard
+/// deviation `σ`. The p-value is the probability of a statistic at least as large, so a
+/// p-value below e.g. 0.05 flags a registration with residuals larger than the expected
+/// noise as suspicious.
+///
+/// # Arguments
+///
+/// * `residuals` - The residuals of the registration.
+/// * `expected_sigma` - The standard deviation of the expected noise.
+///
+/// # Returns
+///
+/// The chi-squared statistic and its p-value. The p-value is one if there are no residuals
+/// and zero if `expected_sigma` is not positive.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::stats::alignment_chi_squared_test;
+///
+/// let residuals = vec![0.05; 100];
+/// let (statistic, p_value) = alignment_chi_squared_test(&residuals, 0.01);
+/// assert_eq!(statistic.round(), 2500.0);
+/// assert!(p_value < 0.05);
+/// ```
+pub fn alignment_chi_squared_test(residuals: &[f64], expected_sigma: f64) -> (f64, f64) {
+    if residuals.is_empty() {
+        return (0.0, 1.0);
+    }
+    if expected_sigma.is_nan() || expected_sigma <= 0.0 {
+        return (f64::INFINITY, 0.0);
+    }
+
+    let statistic = residuals
+        .iter()
+        .map(|r| (r / expected_sigma).powi(2))
+        .sum::<f64>();
+    let dof = residuals.len() as f64;
+
+    (statistic, chi_squared_sf(statistic, dof))
+}
+
+/// Survival function of the chi-squared distribution with `dof` degrees of freedom.
+fn chi_squared_sf(x: f64, dof: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    regularized_gamma_q(0.5 * dof, 0.5 * x).clamp(0.0, 1.0)
+}
+
+/// Upper regularized incomplete gamma function `Q(a, x)`.
+///
+/// Evaluated with the series expansion of `P(a, x)` for `x < a + 1` and with the continued
+/// fraction of `Q(a, x)` otherwise.
+fn regularized_gamma_q(a: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 1000;
+    const EPS: f64 = 1e-15;
+    const TINY: f64 = 1e-300;
+
+    let log_prefactor = a * x.ln() - x - ln_gamma(a);
+
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        for n in 1..MAX_ITERATIONS {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term.abs() < sum.abs() * EPS {
+                break;
+            }
+        }
+        1.0 - sum * log_prefactor.exp()
+    } else {
+        // modified Lentz evaluation of the continued fraction
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / TINY;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for n in 1..MAX_ITERATIONS {
+            let an = -(n as f64) * (n as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = b + an / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPS {
+                break;
+            }
+        }
+        h * log_prefactor.exp()
+    }
+}
+
+/// Natural logarithm of the gamma function with the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // reflection formula
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFS
+        .iter()
+        .enumerate()
+        .skip(1)
+        .fold(COEFFS[0], |sum, (i, c)| sum + c / (x + i as f64));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Compute the map entropy of a point cloud as a quality measure of a reconstruction.
+///
+/// The map entropy is the Shannon entropy of the voxel occupancy distribution
+/// `H = -Σ p_i ln(p_i)`, where `p_i` is the fraction of the points in the voxel `i`. A higher
+/// entropy means a more uniformly distributed coverage, its maximum `ln(n)` being reached when
+/// the points are evenly spread over `n` voxels.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud of the map.
+/// * `voxel_size` - The edge length of the voxels.
+///
+/// # Returns
+///
+/// The entropy in nats. It is zero for an empty cloud or a non-positive voxel size.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pointcloud::PointCloud;
+/// use kornia_3d::stats::map_entropy;
+///
+/// let cloud = PointCloud::new(vec![[0.1, 0.1, 0.1], [1.1, 0.1, 0.1]], None, None);
+/// assert!((map_entropy(&cloud, 1.0) - 2.0f64.ln()).abs() < 1e-12);
+/// ```
+pub fn map_entropy(cloud: &PointCloud, voxel_size: f64) -> f64 {
+    voxel_entropy(cloud.points().iter(), voxel_size)
+}
+
+/// Compute the information gained by merging a point cloud into a map.
+///
+/// The information gain is the increase of the map entropy, see [`map_entropy`], when the
+/// points of `cloud_b` are merged into `cloud_a`. It is positive when `cloud_b` covers
+/// voxels that `cloud_a` does not observe or observes sparsely, which makes it a score of
+/// the candidate views in exploration planning, and negative when `cloud_b` only densifies
+/// the well observed voxels.
+///
+/// # Arguments
+///
+/// * `cloud_a` - The point cloud of the current map.
+/// * `cloud_b` - The point cloud to merge, in the frame of the map.
+/// * `voxel_size` - The edge length of the voxels.
+///
+/// # Returns
+///
+/// The entropy of the merged clouds minus the entropy of `cloud_a`, in nats.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pointcloud::PointCloud;
+/// use kornia_3d::stats::map_information_gain;
+///
+/// let map = PointCloud::new(vec![[0.1, 0.1, 0.1]], None, None);
+/// let scan = PointCloud::new(vec![[1.1, 0.1, 0.1]], None, None);
+/// assert!((map_information_gain(&map, &scan, 1.0) - 2.0f64.ln()).abs() < 1e-12);
+/// ```
+pub fn map_information_gain(cloud_a: &PointCloud, cloud_b: &PointCloud, voxel_size: f64) -> f64 {
+    let merged = cloud_a.points().iter().chain(cloud_b.points().iter());
+    voxel_entropy(merged, voxel_size) - map_entropy(cloud_a, voxel_size)
+}
+
+/// Compute the fraction of a reference cloud covered by the observed points.
+///
+/// A reference point is covered when an observed point lies within `coverage_radius` of it,
+/// found with a kd-tree of the observed cloud. In exploration planning, the reference is a
+/// prior model of the scene, e.g. a map from a previous mission or a CAD model, and the
+/// scene is sufficiently observed once the coverage reaches a target, while
+/// [`missing_regions`] gives the reference points left to observe from the next viewpoint.
+///
+/// # Arguments
+///
+/// * `observed_cloud` - The point cloud observed so far, in the frame of the reference.
+/// * `reference_cloud` - The point cloud of the scene to cover.
+/// * `coverage_radius` - The distance within which an observed point covers a reference one.
+///
+/// # Returns
+///
+/// The fraction of the reference points covered, in `[0, 1]`. It is one for an empty
+/// reference cloud, which has nothing left to observe.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pointcloud::PointCloud;
+/// use kornia_3d::stats::coverage_metric;
+///
+/// let reference = PointCloud::new((0..4).map(|i| [i as f64, 0.0, 0.0]).collect(), None, None);
+/// let observed = PointCloud::new(vec![[0.05, 0.0, 0.0], [1.0, 0.05, 0.0]], None, None);
+/// assert_eq!(coverage_metric(&observed, &reference, 0.1), 0.5);
+/// ```
+pub fn coverage_metric(
+    observed_cloud: &PointCloud,
+    reference_cloud: &PointCloud,
+    coverage_radius: f64,
+) -> f64 {
+    if reference_cloud.is_empty() {
+        return 1.0;
+    }
+    let num_missing = uncovered_points(observed_cloud, reference_cloud, coverage_radius).count();
+    1.0 - num_missing as f64 / reference_cloud.len() as f64
+}
+
+/// Find the points of a reference cloud not covered by the observed points.
+///
+/// The reference points without an observed point within `coverage_radius`, see
+/// [`coverage_metric`]. They are the regions of the scene left to observe, e.g. to score the
+/// candidate viewpoints by the number of missing points in their field of view.
+///
+/// # Arguments
+///
+/// * `observed_cloud` - The point cloud observed so far, in the frame of the reference.
+/// * `reference_cloud` - The point cloud of the scene to cover.
+/// * `coverage_radius` - The distance within which an observed point covers a reference one.
+///
+/// # Returns
+///
+/// The uncovered reference points, in the order of the reference cloud.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pointcloud::PointCloud;
+/// use kornia_3d::stats::missing_regions;
+///
+/// let reference = PointCloud::new((0..4).map(|i| [i as f64, 0.0, 0.0]).collect(), None, None);
+/// let observed = PointCloud::new(vec![[0.05, 0.0, 0.0], [1.0, 0.05, 0.0]], None, None);
+/// let missing = missing_regions(&observed, &reference, 0.1);
+/// assert_eq!(missing, vec![[2.0, 0.0, 0.0], [3.0, 0.0, 0.0]]);
+/// ```
+pub fn missing_regions(
+    observed_cloud: &PointCloud,
+    reference_cloud: &PointCloud,
+    coverage_radius: f64,
+) -> Vec<[f64; 3]> {
+    uncovered_points(observed_cloud, reference_cloud, coverage_radius)
+        .copied()
+        .collect()
+}
+
+/// Compute the normalised information distance between the voxel occupancies of two clouds.
+///
+/// The voxels of the bounding box of both clouds are the samples of two binary variables,
+/// the occupancy of the voxel by `cloud_a` and by `cloud_b`. The distance is
+/// `NID = 1 - I(A;B) / max(H(A), H(B))`, where `I` is the mutual information of the joint
+/// occupancy distribution and `H` the entropy of each occupancy. Unlike the fitness scores
+/// based on point distances, it makes no assumption on the noise or the sampling of the
+/// clouds, only on their support.
+///
+/// The occupancy of a surface is sparse in its bounding box, in which case the distance is
+/// zero for identical occupancies and tends to one for disjoint clouds. Two clouds filling
+/// complementary halves of their bounding box are however perfectly dependent, with a zero
+/// distance.
+///
+/// # Arguments
+///
+/// * `cloud_a` - The first point cloud.
+/// * `cloud_b` - The second point cloud, in the frame of the first.
+/// * `voxel_size` - The edge length of the voxels.
+///
+/// # Returns
+///
+/// The distance in `[0, 1]`. It is one if either cloud is empty or the voxel size is not
+/// positive, and zero if both clouds occupy their whole bounding box.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pointcloud::PointCloud;
+/// use kornia_3d::stats::normalised_information_distance;
+///
+/// let points = vec![[0.1, 0.1, 0.1], [1.1, 0.1, 0.1], [1.1, 1.1, 0.1]];
+/// let cloud = PointCloud::new(points.clone(), None, None);
+/// assert!(normalised_information_distance(&cloud, &cloud, 1.0).abs() < 1e-12);
+///
+/// let moved = PointCloud::new(points.iter().map(|p| [p[0], p[1], p[2] + 5.0]).collect(), None, None);
+/// assert!(normalised_information_distance(&cloud, &moved, 1.0) > 0.5);
+/// ```
+pub fn normalised_information_distance(
+    cloud_a: &PointCloud,
+    cloud_b: &PointCloud,
+    voxel_size: f64,
+) -> f64 {
+    if cloud_a.is_empty() || cloud_b.is_empty() || voxel_size.is_nan() || voxel_size <= 0.0 {
+        return 1.0;
+    }
+
+    let occupied = |cloud: &PointCloud| {
+        cloud
+            .points()
+            .iter()
+            .map(|p| [0, 1, 2].map(|k| (p[k] / voxel_size).floor() as i64))
+            .collect::<HashSet<_>>()
+    };
+    let voxels_a = occupied(cloud_a);
+    let voxels_b = occupied(cloud_b);
+
+    // the number of voxels of the bounding box of both occupancies
+    let mut min_key = [i64::MAX; 3];
+    let mut max_key = [i64::MIN; 3];
+    for key in voxels_a.iter().chain(voxels_b.iter()) {
+        for k in 0..3 {
+            min_key[k] = min_key[k].min(key[k]);
+            max_key[k] = max_key[k].max(key[k]);
+        }
+    }
+    let num_voxels = (0..3)
+        .map(|k| (max_key[k] - min_key[k] + 1) as f64)
+        .product::<f64>();
+
+    // the joint occupancy distribution
+    let both = voxels_a.intersection(&voxels_b).count() as f64;
+    let only_a = voxels_a.len() as f64 - both;
+    let only_b = voxels_b.len() as f64 - both;
+    let neither = num_voxels - both - only_a - only_b;
+
+    let entropy = |counts: &[f64]| {
+        counts
+            .iter()
+            .filter(|&&count| count > 0.0)
+            .map(|&count| {
+                let p = count / num_voxels;
+                -p * p.ln()
+            })
+            .sum::<f64>()
+            .max(0.0)
+    };
+    let entropy_a = entropy(&[both + only_a, only_b + neither]);
+    let entropy_b = entropy(&[both + only_b, only_a + neither]);
+    let joint_entropy = entropy(&[both, only_a, only_b, neither]);
+
+    let max_entropy = entropy_a.max(entropy_b);
+    if max_entropy <= 0.0 {
+        return 0.0;
+    }
+    let mutual_information = entropy_a + entropy_b - joint_entropy;
+    (1.0 - mutual_information / max_entropy).clamp(0.0, 1.0)
+}
+
+/// Check the convergence of an iterative registration from the distribution of its residuals.
+///
+/// The residuals of two consecutive iterations are binned into a shared histogram over their
+/// joint range, each bin count being smoothed by half a sample so that an empty bin does not
+/// make the divergence infinite, and the Kullback-Leibler divergence of the current
+/// distribution from the previous one is estimated from the histograms. Unlike the change of
+/// the RMSE, it detects the iterations that reshape the residuals without changing their
+/// spread, e.g. when the registration trades the fit of one part of the scan for another.
+///
+/// The threshold is taken as an argument, rather than fixed, so that each registration
+/// passes the `entropy_threshold` of its parameters, e.g. [`crate::mesh::MeshIcpParams`] or
+/// the parameters of the ICP variants of `kornia-icp`.
+///
+/// # Arguments
+///
+/// * `prev_residuals` - The residuals of the previous iteration.
+/// * `curr_residuals` - The residuals of the current iteration.
+/// * `threshold` - The divergence below which the registration has converged, in nats.
+///
+/// # Returns
+///
+/// `true` if the divergence is below the threshold. It is `false` if either set of residuals
+/// is empty, so a non-positive threshold disables the criterion.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::stats::entropy_convergence_criterion;
+///
+/// let prev = (0..100).map(|i| i as f64 * 0.01).collect::<Vec<_>>();
+/// let curr = prev.iter().map(|r| r * 0.999).collect::<Vec<_>>();
+/// assert!(entropy_convergence_criterion(&prev, &curr, 1e-2));
+/// assert!(!entropy_convergence_criterion(&prev, &curr, 0.0));
+/// ```
+pub fn entropy_convergence_criterion(
+    prev_residuals: &[f64],
+    curr_residuals: &[f64],
+    threshold: f64,
+) -> bool {
+    if prev_residuals.is_empty() || curr_residuals.is_empty() {
+        return false;
+    }
+
+    let (min, max) = prev_residuals
+        .iter()
+        .chain(curr_residuals.iter())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &r| {
+            (min.min(r), max.max(r))
+        });
+    let histogram = |residuals: &[f64]| {
+        let mut counts = [0.5; RESIDUAL_HISTOGRAM_BINS];
+        for r in residuals {
+            let bin = if max > min {
+                ((r - min) / (max - min) * RESIDUAL_HISTOGRAM_BINS as f64) as usize
+            } else {
+                0
+            };
+            counts[bin.min(RESIDUAL_HISTOGRAM_BINS - 1)] += 1.0;
+        }
+        let total = counts.iter().sum::<f64>();
+        counts.map(|count| count / total)
+    };
+    let prev = histogram(prev_residuals);
+    let curr = histogram(curr_residuals);
+
+    let divergence = curr
+        .iter()
+        .zip(prev.iter())
+        .map(|(p, q)| p * (p / q).ln())
+        .sum::<f64>();
+    divergence < threshold
+}
+
+/// Iterate over the reference points without an observed point within the radius.
+fn uncovered_points<'a>(
+    observed_cloud: &PointCloud,
+    reference_cloud: &'a PointCloud,
+    coverage_radius: f64,
+) -> impl Iterator<Item = &'a [f64; 3]> {
+    let kdtree = KdTree::new(observed_cloud.points());
+    reference_cloud.points().iter().filter(move |p| {
+        !kdtree
+            .nearest_one(p)
+            .is_some_and(|n| n.distance <= coverage_radius)
+    })
+}
+
+/// Compute the Shannon entropy of the voxel occupancy distribution of a set of points.
+fn voxel_entropy<'a>(points: impl Iterator<Item = &'a [f64; 3]>, voxel_size: f64) -> f64 {
+    if voxel_size.is_nan() || voxel_size <= 0.0 {
+        return 0.0;
+    }
+
+    let mut counts: HashMap<[i64; 3], usize> = HashMap::new();
+    for p in points {
+        let key = [0, 1, 2].map(|k| (p[k] / voxel_size).floor() as i64);
+        *counts.entry(key).or_default() += 1;
+    }
+
+    let total = counts.values().sum::<usize>() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.ln()
+        })
+        .sum::<f64>()
+        .max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn gaussian_samples(rng: &mut StdRng, n: usize) -> Vec<f64> {
+        // Box-Muller transform
+        (0..n)
+            .map(|_| {
+                let u1: f64 = rng.random_range(f64::EPSILON..1.0);
+                let u2: f64 = rng.random();
+                (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fit_residual_distribution() {
+        // well separated pairs with known residuals of 0.01 and 0.03
+        let mut src = Vec::new();
+        let mut dst = Vec::new();
+        for i in 0..100 {
+            let p = [i as f64, 0.0, 0.0];
+            let d = if i % 2 == 0 { 0.01 } else { 0.03 };
+            src.push(p);
+            dst.push([p[0], p[1], p[2] + d]);
+        }
+
+        let (mean, std_dev, skewness, kurtosis) = fit_residual_distribution(&src, &dst);
+        assert_relative_eq!(mean, 0.02, epsilon = 1e-12);
+        assert_relative_eq!(std_dev, 0.01, epsilon = 1e-12);
+        assert_relative_eq!(skewness, 0.0, epsilon = 1e-9);
+        // a symmetric two-point distribution has an excess kurtosis of -2
+        assert_relative_eq!(kurtosis, -2.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_is_gaussian() {
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let gaussian = gaussian_samples(&mut rng, 1000)
+            .iter()
+            .map(|x| 0.05 + 0.01 * x)
+            .collect::<Vec<_>>();
+        assert!(is_gaussian(&gaussian, 0.01));
+
+        let (_, _, skewness, kurtosis) = moments(&gaussian);
+        assert!(skewness.abs() < 0.3);
+        assert!(kurtosis.abs() < 0.5);
+
+        // heavy tailed: exponential samples
+        let exponential = (0..5000)
+            .map(|_| -rng.random_range(f64::EPSILON..1.0).ln())
+            .collect::<Vec<_>>();
+        assert!(!is_gaussian(&exponential, 0.01));
+
+        // uniform samples
+        let uniform = (0..5000)
+            .map(|_| rng.random_range(0.0..1.0))
+            .collect::<Vec<_>>();
+        assert!(!is_gaussian(&uniform, 0.01));
+    }
+
+    #[test]
+    fn test_lilliefors_p_value() {
+        // the critical values of Dallal and Wilkinson for 20 samples
+        assert_relative_eq!(lilliefors_p_value(0.192, 20.0), 0.05, epsilon = 5e-3);
+        assert_relative_eq!(lilliefors_p_value(0.223, 20.0), 0.01, epsilon = 1e-3);
+        // the asymptotic critical value at 5%
+        let n = 1000.0f64;
+        assert_relative_eq!(
+            lilliefors_p_value(0.895 / n.sqrt(), n),
+            0.05,
+            epsilon = 1e-2
+        );
+        // the p-value decreases with the statistic
+        let p_values = [0.05, 0.1, 0.15, 0.2, 0.3].map(|d| lilliefors_p_value(d, 20.0));
+        assert!(p_values.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(p_values[0], 1.0);
+    }
+
+    #[test]
+    fn test_normal_cdf() {
+        assert_relative_eq!(normal_cdf(0.0), 0.5, epsilon = 1e-7);
+        assert_relative_eq!(normal_cdf(1.959964), 0.975, epsilon = 1e-6);
+        assert_relative_eq!(normal_cdf(-1.0), 0.158655, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_chi_squared_sf() {
+        // with two degrees of freedom the survival function is exp(-x / 2)
+        assert_relative_eq!(chi_squared_sf(2.0, 2.0), (-1.0f64).exp(), epsilon = 1e-12);
+        // tabulated 95th percentiles
+        assert_relative_eq!(chi_squared_sf(3.841459, 1.0), 0.05, epsilon = 1e-6);
+        assert_relative_eq!(chi_squared_sf(18.307038, 10.0), 0.05, epsilon = 1e-6);
+        assert_relative_eq!(chi_squared_sf(124.342113, 100.0), 0.05, epsilon = 1e-6);
+        assert_relative_eq!(ln_gamma(5.0), 24.0f64.ln(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_alignment_chi_squared_test() {
+        let sigma = 0.01;
+
+        // residuals at the expected noise level are consistent
+        let residuals = (0..1000)
+            .map(|i| if i % 2 == 0 { sigma } else { -sigma })
+            .collect::<Vec<_>>();
+        let (statistic, p_value) = alignment_chi_squared_test(&residuals, sigma);
+        assert_relative_eq!(statistic, 1000.0, epsilon = 1e-9);
+        assert!(p_value > 0.4 && p_value < 0.6);
+
+        // residuals twice as large as the expected noise are suspicious
+        let residuals = residuals.iter().map(|r| 2.0 * r).collect::<Vec<_>>();
+        let (statistic, p_value) = alignment_chi_squared_test(&residuals, sigma);
+        assert_relative_eq!(statistic, 4000.0, epsilon = 1e-9);
+        assert!(p_value < 1e-6);
+
+        assert_eq!(alignment_chi_squared_test(&[], sigma), (0.0, 1.0));
+        assert_eq!(alignment_chi_squared_test(&[0.1], 0.0).1, 0.0);
+    }
+
+    #[test]
+    fn test_map_entropy() {
+        // the same number of points in each of 8 voxels
+        let uniform = (0..8 * 5)
+            .map(|i| {
+                let v = i % 8;
+                let offset = 0.1 + 0.15 * (i / 8) as f64;
+                [
+                    (v & 1) as f64 + offset,
+                    ((v >> 1) & 1) as f64 + offset,
+                    (v >> 2) as f64 + offset,
+                ]
+            })
+            .collect::<Vec<_>>();
+        let uniform = PointCloud::new(uniform, None, None);
+        assert_relative_eq!(map_entropy(&uniform, 1.0), 8.0f64.ln(), epsilon = 1e-12);
+
+        // a coarser voxel holds all the points
+        assert_relative_eq!(map_entropy(&uniform, 4.0), 0.0);
+
+        // an uneven coverage has a lower entropy than an even one
+        let uneven = PointCloud::new(
+            vec![[0.5; 3], [0.6; 3], [0.7; 3], [1.5, 0.5, 0.5]],
+            None,
+            None,
+        );
+        let expected = -(0.75f64 * 0.75f64.ln() + 0.25 * 0.25f64.ln());
+        assert_relative_eq!(map_entropy(&uneven, 1.0), expected, epsilon = 1e-12);
+        assert!(map_entropy(&uneven, 1.0) < 2.0f64.ln());
+
+        // negative coordinates are binned with the floor
+        let negative = PointCloud::new(vec![[-0.5, 0.0, 0.0], [0.5, 0.0, 0.0]], None, None);
+        assert_relative_eq!(map_entropy(&negative, 1.0), 2.0f64.ln(), epsilon = 1e-12);
+
+        let empty = PointCloud::new(vec![], None, None);
+        assert_eq!(map_entropy(&empty, 1.0), 0.0);
+        assert_eq!(map_entropy(&uniform, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_map_information_gain() {
+        let cloud = |x0: f64| {
+            PointCloud::new(
+                (0..10).map(|i| [x0 + i as f64 + 0.5, 0.5, 0.5]).collect(),
+                None,
+                None,
+            )
+        };
+        let map = cloud(0.0);
+
+        // a scan of a new area doubles the number of evenly covered voxels
+        let gain = map_information_gain(&map, &cloud(10.0), 1.0);
+        assert_relative_eq!(gain, 2.0f64.ln(), epsilon = 1e-12);
+
+        // a scan of a half known area gains less
+        let partial = map_information_gain(&map, &cloud(5.0), 1.0);
+        assert!(partial > 0.0 && partial < gain);
+
+        // revisiting the same area gains nothing
+        assert_relative_eq!(map_information_gain(&map, &map, 1.0), 0.0, epsilon = 1e-12);
+        let empty = PointCloud::new(vec![], None, None);
+        assert_relative_eq!(map_information_gain(&map, &empty, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_coverage_metric() {
+        // a grid of 20 x 20 reference points, half of it observed with some noise
+        let mut rng = StdRng::seed_from_u64(3);
+        let reference = (0..400)
+            .map(|i| [(i % 20) as f64 * 0.1, (i / 20) as f64 * 0.1, 0.0])
+            .collect::<Vec<_>>();
+        let observed = reference
+            .iter()
+            .filter(|p| p[0] < 0.95)
+            .map(|p| [0, 1, 2].map(|k| p[k] + rng.random_range(-0.01..0.01)))
+            .collect::<Vec<_>>();
+        let reference = PointCloud::new(reference, None, None);
+        let observed = PointCloud::new(observed, None, None);
+
+        assert_relative_eq!(coverage_metric(&observed, &reference, 0.03), 0.5);
+        let missing = missing_regions(&observed, &reference, 0.03);
+        assert_eq!(missing.len(), 200);
+        assert!(missing.iter().all(|p| p[0] > 0.95));
+
+        // a radius larger than the gap covers the next column
+        assert_relative_eq!(coverage_metric(&observed, &reference, 0.12), 0.55);
+        // observing the whole reference covers it
+        assert_eq!(coverage_metric(&reference, &reference, 0.0), 1.0);
+        assert!(missing_regions(&reference, &reference, 0.0).is_empty());
+
+        let empty = PointCloud::new(vec![], None, None);
+        assert_eq!(coverage_metric(&empty, &reference, 1.0), 0.0);
+        assert_eq!(
+            missing_regions(&empty, &reference, 1.0),
+            *reference.points()
+        );
+        assert_eq!(coverage_metric(&observed, &empty, 1.0), 1.0);
+        assert!(missing_regions(&observed, &empty, 1.0).is_empty());
+        assert_eq!(coverage_metric(&observed, &reference, -1.0), 0.0);
+    }
+
+    #[test]
+    fn test_normalised_information_distance() {
+        // a sphere surface, sampled on a latitude-longitude grid
+        let sphere = |x0: f64| {
+            let points = (0..100 * 50)
+                .map(|i| {
+                    let theta = (i % 100) as f64 * std::f64::consts::TAU / 100.0;
+                    let phi = ((i / 100) as f64 + 0.5) * std::f64::consts::PI / 50.0;
+                    [
+                        x0 + phi.sin() * theta.cos(),
+                        phi.sin() * theta.sin(),
+                        phi.cos(),
+                    ]
+                })
+                .collect();
+            PointCloud::new(points, None, None)
+        };
+        let cloud = sphere(0.0);
+
+        let identical = normalised_information_distance(&cloud, &cloud, 0.1);
+        assert_relative_eq!(identical, 0.0, epsilon = 1e-12);
+
+        // the upper halves of the sphere and of a distant one
+        let upper_halves = cloud
+            .points()
+            .iter()
+            .chain(sphere(3.0).points().iter())
+            .filter(|p| p[2] > 0.0)
+            .copied()
+            .collect();
+        let upper_halves = PointCloud::new(upper_halves, None, None);
+        let half_overlapping = normalised_information_distance(&cloud, &upper_halves, 0.1);
+        assert!(half_overlapping > 0.5 && half_overlapping < 0.9);
+
+        let disjoint = normalised_information_distance(&cloud, &sphere(3.0), 0.1);
+        assert!(disjoint > 0.95 && disjoint <= 1.0);
+        assert!(disjoint > half_overlapping);
+
+        let empty = PointCloud::new(vec![], None, None);
+        assert_relative_eq!(normalised_information_distance(&cloud, &empty, 0.1), 1.0);
+        assert_relative_eq!(normalised_information_distance(&cloud, &cloud, 0.0), 1.0);
+    }
+
+    #[test]
+    fn test_entropy_convergence_criterion() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let prev = gaussian_samples(&mut rng, 2000)
+            .iter()
+            .map(|x| x.abs())
+            .collect::<Vec<_>>();
+
+        // the same distribution, from other samples
+        let curr = gaussian_samples(&mut rng, 2000)
+            .iter()
+            .map(|x| x.abs())
+            .collect::<Vec<_>>();
+        assert!(entropy_convergence_criterion(&prev, &curr, 1e-2));
+        assert!(!entropy_convergence_criterion(&prev, &curr, 0.0));
+
+        // the residuals of half of the samples vanish while the RMSE is kept
+        let rmse = |r: &[f64]| (r.iter().map(|x| x * x).sum::<f64>() / r.len() as f64).sqrt();
+        let mut reshaped = prev.clone();
+        for (i, r) in reshaped.iter_mut().enumerate() {
+            *r = if i % 2 == 0 { 0.0 } else { *r * 2.0f64.sqrt() };
+        }
+        assert_relative_eq!(rmse(&reshaped), rmse(&prev), epsilon = 0.05);
+        assert!(!entropy_convergence_criterion(&prev, &reshaped, 1e-2));
+
+        assert!(!entropy_convergence_criterion(&[], &curr, 1.0));
+        assert!(entropy_convergence_criterion(&[0.5; 10], &[0.5; 10], 1e-9));
+    }
+}
@@ -0,0 +1,504 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    linalg::{cross_vec3, dot_product3},
+    mesh::Mesh,
+    pointcloud::PointCloud,
+};
+
+/// Reconstruct a surface from a point cloud with the ball pivoting algorithm.
+///
+/// A ball of radius `ρ` touching three points without containing any other point seeds a
+/// triangle, and the ball then pivots around each edge of the front of the mesh until it
+/// touches another point, which forms a new triangle with the edge, as described in
+/// Bernardini et al. 1999. The edges around which the ball touches no point, or only
+/// points inside the mesh, are left on the boundary. The passes with the next radii pivot
+/// again around the boundary edges and seed the remaining points, filling the regions
+/// too sparse for the smaller balls. The regions too sparse for the largest ball are left
+/// as holes.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud, sampled on the surface.
+/// * `normals` - The unit normal of each point, consistently oriented outwards.
+/// * `radii` - The radii of the ball, in increasing order. The smallest one is typically
+///   slightly larger than the spacing of the points.
+///
+/// # Returns
+///
+/// The mesh with the points as vertices. The triangles are oriented along the normals.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pointcloud::PointCloud;
+/// use kornia_3d::surface::ball_pivoting;
+///
+/// let points = (0..25).map(|i| [(i % 5) as f64, (i / 5) as f64, 0.0]).collect();
+/// let cloud = PointCloud::new(points, None, None);
+/// let mesh = ball_pivoting(&cloud, &[[0.0, 0.0, 1.0]; 25], &[1.0]);
+/// // two triangles per square of the grid
+/// assert_eq!(mesh.len(), 32);
+/// ```
+pub fn ball_pivoting(cloud: &PointCloud, normals: &[[f64; 3]], radii: &[f64]) -> Mesh {
+    let points = cloud.points();
+    let mut pivoting = BallPivoting {
+        points,
+        normals,
+        grid: SpatialHashGrid::new(points, 1.0),
+        radius: 0.0,
+        faces: Vec::new(),
+        face_set: HashSet::new(),
+        edge_count: HashMap::new(),
+        front: HashMap::new(),
+        boundary: Vec::new(),
+        front_degree: vec![0; points.len()],
+        used: vec![false; points.len()],
+    };
+
+    for &radius in radii.iter().filter(|r| **r > 0.0) {
+        pivoting.radius = radius;
+        pivoting.grid = SpatialHashGrid::new(points, 2.0 * radius);
+
+        // pivot again around the boundary with the larger ball
+        let mut queue = Vec::new();
+        for (i, j, opposite) in std::mem::take(&mut pivoting.boundary) {
+            match pivoting.ball_center(i, j, opposite) {
+                Some(center) => {
+                    pivoting
+                        .front
+                        .insert((i, j), FrontEdge { opposite, center });
+                    queue.push((i, j));
+                }
+                None => pivoting.boundary.push((i, j, opposite)),
+            }
+        }
+
+        let mut next_seed = 0;
+        loop {
+            while let Some(edge) = queue.pop() {
+                pivoting.expand(edge, &mut queue);
+            }
+            match pivoting.find_seed(&mut next_seed) {
+                Some(edges) => queue.extend(edges),
+                None => break,
+            }
+        }
+    }
+
+    Mesh::new(points.to_vec(), pivoting.faces)
+}
+
+/// An edge of the front, oriented as in its triangle.
+struct FrontEdge {
+    // the third vertex of the triangle of the edge
+    opposite: usize,
+    // the center of the ball touching the triangle
+    center: [f64; 3],
+}
+
+/// The state of the ball pivoting.
+struct BallPivoting<'a> {
+    points: &'a [[f64; 3]],
+    normals: &'a [[f64; 3]],
+    grid: SpatialHashGrid,
+    radius: f64,
+    faces: Vec<[usize; 3]>,
+    face_set: HashSet<[usize; 3]>,
+    // the number of triangles sharing each undirected edge
+    edge_count: HashMap<(usize, usize), u8>,
+    // the edges to pivot around
+    front: HashMap<(usize, usize), FrontEdge>,
+    // the edges around which the ball did not touch any valid point
+    boundary: Vec<(usize, usize, usize)>,
+    // the number of front and boundary edges of each point
+    front_degree: Vec<usize>,
+    used: Vec<bool>,
+}
+
+impl BallPivoting<'_> {
+    /// Pivot the ball around a front edge and add the triangle it touches.
+    fn expand(&mut self, edge: (usize, usize), queue: &mut Vec<(usize, usize)>) {
+        let Some(front_edge) = self.front.remove(&edge) else {
+            return;
+        };
+        let (i, j) = edge;
+        self.front_degree[i] -= 1;
+        self.front_degree[j] -= 1;
+
+        let Some((k, center)) = self.pivot(i, j, &front_edge) else {
+            self.add_boundary(i, j, front_edge.opposite);
+            return;
+        };
+
+        // a point inside the mesh cannot be touched again
+        let on_front = !self.used[k] || self.front_degree[k] > 0;
+        if !on_front || self.count(i, k) >= 2 || self.count(k, j) >= 2 || self.has_face(j, i, k) {
+            self.add_boundary(i, j, front_edge.opposite);
+            return;
+        }
+
+        self.add_face([j, i, k]);
+        for (a, b, opposite) in [(i, k, j), (k, j, i)] {
+            // an edge already on the front in the other direction is glued to it
+            if self.front.remove(&(b, a)).is_some() {
+                self.front_degree[a] -= 1;
+                self.front_degree[b] -= 1;
+                continue;
+            }
+            if let Some(position) = self.boundary.iter().position(|&(x, y, _)| (x, y) == (b, a)) {
+                self.boundary.swap_remove(position);
+                self.front_degree[a] -= 1;
+                self.front_degree[b] -= 1;
+                continue;
+            }
+            // the ball of the triangle touches its three edges
+            self.front.insert((a, b), FrontEdge { opposite, center });
+            self.front_degree[a] += 1;
+            self.front_degree[b] += 1;
+            queue.push((a, b));
+        }
+    }
+
+    /// Find the first point touched by the ball pivoting around the edge `(i, j)`.
+    ///
+    /// The new triangle is `(j, i, k)` and the ball rotates around the edge away from the
+    /// triangle of the edge. Returns the point and the center of the ball touching it.
+    fn pivot(&self, i: usize, j: usize, edge: &FrontEdge) -> Option<(usize, [f64; 3])> {
+        let (pi, pj) = (&self.points[i], &self.points[j]);
+        let middle = [0, 1, 2].map(|k| 0.5 * (pi[k] + pj[k]));
+        let axis = normalize(&sub(pj, pi))?;
+        let start = reject(&sub(&edge.center, &middle), &axis);
+        let mut tangent = [0.0; 3];
+        cross_vec3(&axis, &start, &mut tangent);
+        // the ball rolls away from the opposite point
+        let away = sub(&middle, &self.points[edge.opposite]);
+        let direction = if dot_product3(&tangent, &away) >= 0.0 {
+            1.0
+        } else {
+            -1.0
+        };
+
+        let mut best: Option<(f64, usize, [f64; 3])> = None;
+        for k in self.grid.within(self.points, &middle, 2.0 * self.radius) {
+            if k == i || k == j || k == edge.opposite {
+                continue;
+            }
+            let Some(center) = self.ball_center(j, i, k) else {
+                continue;
+            };
+            let end = reject(&sub(&center, &middle), &axis);
+            let mut cross = [0.0; 3];
+            cross_vec3(&start, &end, &mut cross);
+            let angle = (direction * dot_product3(&cross, &axis))
+                .atan2(dot_product3(&start, &end))
+                .rem_euclid(2.0 * std::f64::consts::PI);
+            if best.as_ref().map_or(true, |b| angle < b.0) && self.is_empty_ball(&center, [i, j, k])
+            {
+                best = Some((angle, k, center));
+            }
+        }
+        best.map(|(_, k, center)| (k, center))
+    }
+
+    /// Find a triangle of unused points touched by an empty ball and add it to the mesh.
+    ///
+    /// Returns the edges of the triangle to pivot around.
+    fn find_seed(&mut self, next: &mut usize) -> Option<[(usize, usize); 3]> {
+        while *next < self.points.len() {
+            let i = *next;
+            *next += 1;
+            if self.used[i] {
+                continue;
+            }
+
+            let mut neighbors = self
+                .grid
+                .within(self.points, &self.points[i], 2.0 * self.radius)
+                .into_iter()
+                .filter(|&k| k != i && !self.used[k])
+                .collect::<Vec<_>>();
+            neighbors.sort_by(|&a, &b| {
+                sq_distance(&self.points[i], &self.points[a])
+                    .total_cmp(&sq_distance(&self.points[i], &self.points[b]))
+            });
+
+            for (n, &a) in neighbors.iter().enumerate() {
+                for &b in neighbors.iter().skip(n + 1) {
+                    // orient the triangle along the normal of the point
+                    let face = match face_normal(self.points, [i, a, b]) {
+                        Some(normal) if dot_product3(&normal, &self.normals[i]) >= 0.0 => [i, a, b],
+                        Some(_) => [i, b, a],
+                        None => continue,
+                    };
+                    let Some(center) = self.ball_center(face[0], face[1], face[2]) else {
+                        continue;
+                    };
+                    if !self.is_empty_ball(&center, face) {
+                        continue;
+                    }
+
+                    self.add_face(face);
+                    let edges = [(face[0], face[1]), (face[1], face[2]), (face[2], face[0])];
+                    for (e, &(x, y)) in edges.iter().enumerate() {
+                        let opposite = face[(e + 2) % 3];
+                        self.front.insert((x, y), FrontEdge { opposite, center });
+                        self.front_degree[x] += 1;
+                        self.front_degree[y] += 1;
+                    }
+                    // retry the point in case it is not surrounded yet
+                    *next = i;
+                    return Some(edges);
+                }
+            }
+        }
+        None
+    }
+
+    /// The center of the ball touching the triangle `(a, b, c)` on the side of its normal,
+    /// if the ball is large enough and the normal agrees with the normals of the points.
+    fn ball_center(&self, a: usize, b: usize, c: usize) -> Option<[f64; 3]> {
+        let normal = face_normal(self.points, [a, b, c])?;
+        if [a, b, c]
+            .iter()
+            .any(|&v| dot_product3(&normal, &self.normals[v]) < 0.0)
+        {
+            return None;
+        }
+
+        // the circumcenter of the triangle
+        let (pa, pb, pc) = (&self.points[a], &self.points[b], &self.points[c]);
+        let (ab, ac) = (sub(pb, pa), sub(pc, pa));
+        let mut n = [0.0; 3];
+        cross_vec3(&ab, &ac, &mut n);
+        let sq_norm = dot_product3(&n, &n);
+        let mut u = [0.0; 3];
+        let mut v = [0.0; 3];
+        cross_vec3(&n, &ab, &mut u);
+        cross_vec3(&ac, &n, &mut v);
+        let (sq_ac, sq_ab) = (dot_product3(&ac, &ac), dot_product3(&ab, &ab));
+        let offset = [0, 1, 2].map(|k| (sq_ac * u[k] + sq_ab * v[k]) / (2.0 * sq_norm));
+        let sq_height = self.radius * self.radius - dot_product3(&offset, &offset);
+        if sq_height < 0.0 {
+            return None;
+        }
+        let height = sq_height.sqrt();
+        Some([0, 1, 2].map(|k| pa[k] + offset[k] + height * normal[k]))
+    }
+
+    /// Check that the ball at a center contains no point other than the given ones.
+    fn is_empty_ball(&self, center: &[f64; 3], touching: [usize; 3]) -> bool {
+        let sq_radius = (self.radius * (1.0 - 1e-9)).powi(2);
+        self.grid
+            .within(self.points, center, self.radius)
+            .into_iter()
+            .all(|k| touching.contains(&k) || sq_distance(center, &self.points[k]) >= sq_radius)
+    }
+
+    fn add_face(&mut self, face: [usize; 3]) {
+        for (a, b) in [(face[0], face[1]), (face[1], face[2]), (face[2], face[0])] {
+            *self.edge_count.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+        }
+        for v in face {
+            self.used[v] = true;
+        }
+        let mut sorted = face;
+        sorted.sort();
+        self.face_set.insert(sorted);
+        self.faces.push(face);
+    }
+
+    fn add_boundary(&mut self, i: usize, j: usize, opposite: usize) {
+        self.boundary.push((i, j, opposite));
+        self.front_degree[i] += 1;
+        self.front_degree[j] += 1;
+    }
+
+    fn count(&self, a: usize, b: usize) -> u8 {
+        self.edge_count
+            .get(&(a.min(b), a.max(b)))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn has_face(&self, a: usize, b: usize, c: usize) -> bool {
+        let mut sorted = [a, b, c];
+        sorted.sort();
+        self.face_set.contains(&sorted)
+    }
+}
+
+/// A spatial hash grid bucketing the points in cubic cells.
+struct SpatialHashGrid {
+    cell_size: f64,
+    cells: HashMap<[i64; 3], Vec<usize>>,
+}
+
+impl SpatialHashGrid {
+    fn new(points: &[[f64; 3]], cell_size: f64) -> Self {
+        let mut cells = HashMap::<[i64; 3], Vec<usize>>::new();
+        for (i, p) in points.iter().enumerate() {
+            cells
+                .entry(p.map(|x| (x / cell_size).floor() as i64))
+                .or_default()
+                .push(i);
+        }
+        Self { cell_size, cells }
+    }
+
+    /// The indices of the points within a radius of a query point.
+    fn within(&self, points: &[[f64; 3]], query: &[f64; 3], radius: f64) -> Vec<usize> {
+        let lo = query.map(|x| ((x - radius) / self.cell_size).floor() as i64);
+        let hi = query.map(|x| ((x + radius) / self.cell_size).floor() as i64);
+        let sq_radius = radius * radius;
+        let mut found = Vec::new();
+        for x in lo[0]..=hi[0] {
+            for y in lo[1]..=hi[1] {
+                for z in lo[2]..=hi[2] {
+                    let Some(cell) = self.cells.get(&[x, y, z]) else {
+                        continue;
+                    };
+                    found.extend(
+                        cell.iter()
+                            .copied()
+                            .filter(|&i| sq_distance(query, &points[i]) <= sq_radius),
+                    );
+                }
+            }
+        }
+        found.sort_unstable();
+        found
+    }
+}
+
+/// The unit normal of a triangle, counter-clockwise.
+fn face_normal(points: &[[f64; 3]], face: [usize; 3]) -> Option<[f64; 3]> {
+    let [a, b, c] = face.map(|v| points[v]);
+    let mut n = [0.0; 3];
+    cross_vec3(&sub(&b, &a), &sub(&c, &a), &mut n);
+    normalize(&n)
+}
+
+fn sub(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn sq_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    let d = sub(a, b);
+    dot_product3(&d, &d)
+}
+
+fn normalize(v: &[f64; 3]) -> Option<[f64; 3]> {
+    let norm = dot_product3(v, v).sqrt();
+    (norm > 1e-12).then(|| v.map(|x| x / norm))
+}
+
+/// The component of a vector orthogonal to a unit axis.
+fn reject(v: &[f64; 3], axis: &[f64; 3]) -> [f64; 3] {
+    let d = dot_product3(v, axis);
+    [v[0] - d * axis[0], v[1] - d * axis[1], v[2] - d * axis[2]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Fibonacci sphere of unit radius with its outward normals.
+    fn sphere(num_points: usize) -> Vec<[f64; 3]> {
+        let golden = std::f64::consts::PI * (3.0 - 5.0f64.sqrt());
+        (0..num_points)
+            .map(|i| {
+                let z = 1.0 - 2.0 * (i as f64 + 0.5) / num_points as f64;
+                let r = (1.0 - z * z).sqrt();
+                let theta = golden * i as f64;
+                [r * theta.cos(), r * theta.sin(), z]
+            })
+            .collect()
+    }
+
+    /// The number of triangles sharing each edge of a mesh.
+    fn edge_counts(mesh: &Mesh) -> HashMap<(usize, usize), usize> {
+        let mut counts = HashMap::new();
+        for f in mesh.faces() {
+            for (i, j) in [(f[0], f[1]), (f[1], f[2]), (f[2], f[0])] {
+                *counts.entry((i.min(j), i.max(j))).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    fn euler_characteristic(mesh: &Mesh) -> i64 {
+        let vertices = mesh.faces().iter().flatten().collect::<HashSet<_>>();
+        vertices.len() as i64 - edge_counts(mesh).len() as i64 + mesh.len() as i64
+    }
+
+    #[test]
+    fn test_ball_pivoting_sphere() {
+        let normals = sphere(2000);
+        let cloud = PointCloud::new(normals.clone(), None, None);
+        let mesh = ball_pivoting(&cloud, &normals, &[0.06, 0.09]);
+
+        // the surface is closed and manifold
+        let counts = edge_counts(&mesh);
+        assert!(counts.values().all(|&c| c <= 2));
+        let num_boundary = counts.values().filter(|&&c| c == 1).count();
+        assert!(num_boundary < 30, "{num_boundary} boundary edges");
+        let euler = euler_characteristic(&mesh);
+        assert!((euler - 2).abs() <= 4, "Euler characteristic {euler}");
+
+        // the triangles are close to the sphere and oriented outwards
+        let mut sq_sum = 0.0;
+        for f in mesh.faces() {
+            let [a, b, c] = f.map(|v| mesh.vertices()[v]);
+            let centroid = [0, 1, 2].map(|k| (a[k] + b[k] + c[k]) / 3.0);
+            sq_sum += (dot_product3(&centroid, &centroid).sqrt() - 1.0).powi(2);
+            let normal = face_normal(mesh.vertices(), *f).unwrap_or([0.0; 3]);
+            assert!(dot_product3(&normal, &centroid) > 0.0);
+        }
+        let rms = (sq_sum / mesh.len() as f64).sqrt();
+        // below the spacing of the points
+        assert!(rms < 0.01, "{rms}");
+    }
+
+    #[test]
+    fn test_ball_pivoting_undersampled() {
+        // a cap of the sphere is sampled ten times more sparsely
+        let normals = sphere(2000)
+            .into_iter()
+            .enumerate()
+            .filter(|(i, p)| p[2] < 0.6 || i % 10 == 0)
+            .map(|(_, p)| p)
+            .collect::<Vec<_>>();
+        let cloud = PointCloud::new(normals.clone(), None, None);
+        let radii = [0.06, 0.09];
+        let mesh = ball_pivoting(&cloud, &normals, &radii);
+
+        let counts = edge_counts(&mesh);
+        assert!(counts.values().all(|&c| c <= 2));
+        // the cap is a hole rather than long triangles
+        let num_boundary = counts.values().filter(|&&c| c == 1).count();
+        assert!(num_boundary > 20, "{num_boundary} boundary edges");
+        for f in mesh.faces() {
+            for (a, b) in [(f[0], f[1]), (f[1], f[2]), (f[2], f[0])] {
+                let length = sq_distance(&mesh.vertices()[a], &mesh.vertices()[b]).sqrt();
+                assert!(length <= 2.0 * radii[1]);
+            }
+        }
+        // the dense part is still meshed
+        let covered = mesh.faces().iter().flatten().collect::<HashSet<_>>();
+        assert!((0..normals.len())
+            .filter(|&i| normals[i][2] < 0.5)
+            .all(|i| covered.contains(&i)));
+    }
+
+    #[test]
+    fn test_spatial_hash_grid() {
+        let points = sphere(500);
+        let grid = SpatialHashGrid::new(&points, 0.3);
+        let query = [0.5, 0.5, 0.5];
+        let expected = (0..points.len())
+            .filter(|&i| sq_distance(&query, &points[i]) <= 0.25)
+            .collect::<Vec<_>>();
+        assert_eq!(grid.within(&points, &query, 0.5), expected);
+    }
+}
@@ -0,0 +1,456 @@
+use std::collections::{HashMap, VecDeque};
+use std::f64::consts::PI;
+
+use crate::{
+    kdtree::KdTree,
+    linalg::{cross_vec3, dot_product3},
+    mesh::Mesh,
+    pointcloud::PointCloud,
+};
+
+/// Maximum angle between the normals of a point and of the points it is connected to.
+const MAX_SURFACE_ANGLE: f64 = PI / 4.0;
+
+/// Minimum angle of a triangle.
+const MIN_TRIANGLE_ANGLE: f64 = PI / 18.0;
+
+/// Maximum angle of a triangle.
+const MAX_TRIANGLE_ANGLE: f64 = 2.0 * PI / 3.0;
+
+/// Tolerance on the angles when comparing the sectors around a point.
+const ANGLE_EPSILON: f64 = 1e-9;
+
+/// Triangulate a point cloud with the greedy projection algorithm.
+///
+/// The surface grows from seed points along the neighbors of the points already meshed, as
+/// described in Marton et al. 2009. The neighbors of each point, at most `max_nn` within
+/// `mu` times the distance to its nearest neighbor and within `search_radius`, are projected
+/// onto its tangent plane and the consecutive neighbors in the Delaunay triangulation of
+/// the projection form a triangle with the point if the triangle:
+///
+/// * has all its angles between 10 and 120 degrees,
+/// * joins points whose normals differ by less than 45 degrees,
+/// * does not overlap the triangles already around each of its vertices, in the tangent
+///   plane of the vertex, so that the neighbors hidden behind them are skipped,
+/// * does not make an edge shared by more than two triangles.
+///
+/// The points whose neighbors leave a gap larger than the maximum angle are left on the
+/// boundary of the mesh.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud, typically registered and downsampled.
+/// * `normals` - The unit normal of each point, consistently oriented.
+/// * `search_radius` - The maximum length of an edge.
+/// * `mu` - The maximum length of an edge from a point relative to the distance to its
+///   nearest neighbor, typically 2.5, to adapt to the local density.
+/// * `max_nn` - The maximum number of neighbors of a point.
+///
+/// # Returns
+///
+/// The mesh with the points as vertices. The triangles are oriented along the normals.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pointcloud::PointCloud;
+/// use kornia_3d::surface::greedy_triangulation;
+///
+/// let points = (0..25).map(|i| [(i % 5) as f64, (i / 5) as f64, 0.0]).collect();
+/// let cloud = PointCloud::new(points, None, None);
+/// let mesh = greedy_triangulation(&cloud, &[[0.0, 0.0, 1.0]; 25], 2.0, 2.5, 8);
+/// // two triangles per square of the grid
+/// assert_eq!(mesh.len(), 32);
+/// ```
+pub fn greedy_triangulation(
+    cloud: &PointCloud,
+    normals: &[[f64; 3]],
+    search_radius: f64,
+    mu: f64,
+    max_nn: usize,
+) -> Mesh {
+    let points = cloud.points();
+    let kdtree = KdTree::new(points);
+    let frames = normals.iter().map(tangent_frame).collect::<Vec<_>>();
+    let mut triangulation = Triangulation {
+        points,
+        frames: &frames,
+        faces: Vec::new(),
+        incident: vec![Vec::new(); points.len()],
+        edges: HashMap::new(),
+    };
+
+    let mut queued = vec![false; points.len()];
+    let mut queue = VecDeque::new();
+    for seed in 0..points.len() {
+        if queued[seed] {
+            continue;
+        }
+        queued[seed] = true;
+        queue.push_back(seed);
+
+        while let Some(p) = queue.pop_front() {
+            let neighbors = kdtree
+                .nearest_n(&points[p], max_nn + 1)
+                .into_iter()
+                .filter(|n| n.index != p && n.distance > 0.0)
+                .collect::<Vec<_>>();
+            let Some(nearest) = neighbors.first() else {
+                continue;
+            };
+            let radius = search_radius.min(mu * nearest.distance);
+            let candidates = neighbors
+                .iter()
+                .filter(|n| {
+                    n.distance <= radius
+                        && dot_product3(&normals[p], &normals[n.index]) >= MAX_SURFACE_ANGLE.cos()
+                })
+                .map(|n| n.index)
+                .collect::<Vec<_>>();
+
+            triangulation.fan(p, &candidates);
+
+            for &q in candidates.iter() {
+                if !queued[q] {
+                    queued[q] = true;
+                    queue.push_back(q);
+                }
+            }
+        }
+    }
+
+    Mesh::new(points.to_vec(), triangulation.faces)
+}
+
+/// The state of the greedy triangulation.
+struct Triangulation<'a> {
+    points: &'a [[f64; 3]],
+    // the tangent frame (u, v, normal) of each point
+    frames: &'a [[[f64; 3]; 3]],
+    faces: Vec<[usize; 3]>,
+    // the triangles incident to each point
+    incident: Vec<Vec<usize>>,
+    // the number of triangles sharing each edge
+    edges: HashMap<(usize, usize), u8>,
+}
+
+impl Triangulation<'_> {
+    /// Add the triangles between a point and its consecutive neighbors around it.
+    ///
+    /// The consecutive neighbors are the neighbors of the point in the Delaunay
+    /// triangulation of the neighborhood projected onto the tangent plane of the point.
+    fn fan(&mut self, p: usize, candidates: &[usize]) {
+        // skip the neighbors hidden behind the triangles already around the point
+        let covered = self.covered_sectors(p);
+        let around = candidates
+            .iter()
+            .map(|&q| (self.angle_at(p, q), q, self.project(p, q)))
+            .filter(|&(angle, _, _)| {
+                !covered
+                    .iter()
+                    .any(|&(start, span)| strictly_inside(angle, start, span))
+            })
+            .collect::<Vec<_>>();
+        let scale = around
+            .iter()
+            .map(|(_, _, x)| x[0] * x[0] + x[1] * x[1])
+            .fold(0.0, f64::max);
+
+        // the triangles with an empty circumcircle, counter-clockwise around the point
+        let mut triangles = Vec::new();
+        for (i, &(angle_a, a, xa)) in around.iter().enumerate() {
+            for &(angle_b, b, xb) in around.iter().skip(i + 1) {
+                let ((angle_a, a, xa), (b, xb)) = if xa[0] * xb[1] - xa[1] * xb[0] > 0.0 {
+                    ((angle_a, a, xa), (b, xb))
+                } else {
+                    ((angle_b, b, xb), (a, xa))
+                };
+                let span = (self.angle_at(p, b) - angle_a).rem_euclid(2.0 * PI);
+                if !(MIN_TRIANGLE_ANGLE..=MAX_TRIANGLE_ANGLE).contains(&span) {
+                    continue;
+                }
+                let empty = around.iter().all(|&(_, r, xr)| {
+                    r == a || r == b || in_circle(&xa, &xb, &xr) <= 1e-9 * scale * scale
+                });
+                if empty {
+                    triangles.push((angle_a, a, b));
+                }
+            }
+        }
+        triangles.sort_by(|x, y| x.0.total_cmp(&y.0).then(x.1.cmp(&y.1)).then(x.2.cmp(&y.2)));
+
+        for (_, a, b) in triangles {
+            self.try_add(p, a, b);
+        }
+    }
+
+    /// Add the triangle `(p, a, b)`, counter-clockwise around the normal of `p`, if valid.
+    fn try_add(&mut self, p: usize, a: usize, b: usize) {
+        let face = [p, a, b];
+        let edge_count =
+            |i: usize, j: usize| self.edges.get(&(i.min(j), i.max(j))).copied().unwrap_or(0);
+        if edge_count(p, a) >= 2 || edge_count(a, b) >= 2 || edge_count(b, p) >= 2 {
+            return;
+        }
+        if self.incident[p]
+            .iter()
+            .any(|&f| self.faces[f].contains(&a) && self.faces[f].contains(&b))
+        {
+            return;
+        }
+
+        // all the angles of the triangle are bounded
+        let (pp, pa, pb) = (&self.points[p], &self.points[a], &self.points[b]);
+        let valid_angles = [(pp, pa, pb), (pa, pb, pp), (pb, pp, pa)]
+            .iter()
+            .all(|(v, x, y)| {
+                let angle = vertex_angle(v, x, y);
+                (MIN_TRIANGLE_ANGLE..=MAX_TRIANGLE_ANGLE).contains(&angle)
+            });
+        if !valid_angles {
+            return;
+        }
+
+        // no overlap with the triangles around each vertex
+        for (v, x, y) in [(p, a, b), (a, b, p), (b, p, a)] {
+            let sector = self.sector_at(v, x, y);
+            if self
+                .covered_sectors(v)
+                .iter()
+                .any(|covered| sectors_overlap(sector, *covered))
+            {
+                return;
+            }
+        }
+
+        let index = self.faces.len();
+        self.faces.push(face);
+        for v in face {
+            self.incident[v].push(index);
+        }
+        for (i, j) in [(p, a), (a, b), (b, p)] {
+            *self.edges.entry((i.min(j), i.max(j))).or_insert(0) += 1;
+        }
+    }
+
+    /// Angle of a point around another one, in the tangent plane of the latter.
+    fn angle_at(&self, center: usize, q: usize) -> f64 {
+        let [u, v, _] = &self.frames[center];
+        let c = &self.points[center];
+        let d = [
+            self.points[q][0] - c[0],
+            self.points[q][1] - c[1],
+            self.points[q][2] - c[2],
+        ];
+        dot_product3(&d, v)
+            .atan2(dot_product3(&d, u))
+            .rem_euclid(2.0 * PI)
+    }
+
+    /// Coordinates of a point in the tangent plane of another one.
+    fn project(&self, center: usize, q: usize) -> [f64; 2] {
+        let [u, v, _] = &self.frames[center];
+        let c = &self.points[center];
+        let d = [
+            self.points[q][0] - c[0],
+            self.points[q][1] - c[1],
+            self.points[q][2] - c[2],
+        ];
+        [dot_product3(&d, u), dot_product3(&d, v)]
+    }
+
+    /// The sector spanned at a vertex by a triangle, as its start angle and its span.
+    fn sector_at(&self, center: usize, a: usize, b: usize) -> (f64, f64) {
+        let (angle_a, angle_b) = (self.angle_at(center, a), self.angle_at(center, b));
+        let span = (angle_b - angle_a).rem_euclid(2.0 * PI);
+        if span <= PI {
+            (angle_a, span)
+        } else {
+            (angle_b, 2.0 * PI - span)
+        }
+    }
+
+    /// The sectors spanned at a vertex by its triangles.
+    fn covered_sectors(&self, center: usize) -> Vec<(f64, f64)> {
+        self.incident[center]
+            .iter()
+            .map(|&f| {
+                let others = self.faces[f]
+                    .iter()
+                    .copied()
+                    .filter(|&v| v != center)
+                    .collect::<Vec<_>>();
+                self.sector_at(center, others[0], others[1])
+            })
+            .collect()
+    }
+}
+
+/// The tangent frame `[u, v, normal]` of a normal, with `u × v = normal`.
+fn tangent_frame(normal: &[f64; 3]) -> [[f64; 3]; 3] {
+    let axis = (0..3)
+        .min_by(|&a, &b| normal[a].abs().total_cmp(&normal[b].abs()))
+        .unwrap_or(0);
+    let mut e = [0.0; 3];
+    e[axis] = 1.0;
+    let d = dot_product3(&e, normal);
+    let u = [
+        e[0] - d * normal[0],
+        e[1] - d * normal[1],
+        e[2] - d * normal[2],
+    ];
+    let norm = dot_product3(&u, &u).sqrt();
+    let u = u.map(|x| x / norm);
+    let mut v = [0.0; 3];
+    cross_vec3(normal, &u, &mut v);
+    [u, v, *normal]
+}
+
+/// The angle at the vertex `v` of the triangle `(v, x, y)`.
+fn vertex_angle(v: &[f64; 3], x: &[f64; 3], y: &[f64; 3]) -> f64 {
+    let a = [x[0] - v[0], x[1] - v[1], x[2] - v[2]];
+    let b = [y[0] - v[0], y[1] - v[1], y[2] - v[2]];
+    let mut c = [0.0; 3];
+    cross_vec3(&a, &b, &mut c);
+    dot_product3(&c, &c).sqrt().atan2(dot_product3(&a, &b))
+}
+
+/// The in-circle determinant of the point `r` and the counter-clockwise triangle
+/// `(0, a, b)`, positive if the point is inside the circumcircle of the triangle.
+fn in_circle(a: &[f64; 2], b: &[f64; 2], r: &[f64; 2]) -> f64 {
+    let (la, lb, lr) = (
+        a[0] * a[0] + a[1] * a[1],
+        b[0] * b[0] + b[1] * b[1],
+        r[0] * r[0] + r[1] * r[1],
+    );
+    a[1] * (b[0] * lr - lb * r[0])
+        - a[0] * (b[1] * lr - lb * r[1])
+        - la * (b[0] * r[1] - b[1] * r[0])
+}
+
+/// Check if an angle is strictly inside the sector starting at `start` of width `span`.
+fn strictly_inside(angle: f64, start: f64, span: f64) -> bool {
+    let offset = (angle - start).rem_euclid(2.0 * PI);
+    offset > ANGLE_EPSILON && offset < span - ANGLE_EPSILON
+}
+
+/// Check if two sectors share more than a boundary ray.
+fn sectors_overlap(a: (f64, f64), b: (f64, f64)) -> bool {
+    let same_start = (a.0 - b.0)
+        .rem_euclid(2.0 * PI)
+        .min((b.0 - a.0).rem_euclid(2.0 * PI))
+        <= ANGLE_EPSILON;
+    same_start || strictly_inside(b.0, a.0, a.1) || strictly_inside(a.0, b.0, b.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// The number of triangles sharing each edge of a mesh.
+    fn edge_counts(mesh: &Mesh) -> HashMap<(usize, usize), usize> {
+        let mut counts = HashMap::new();
+        for f in mesh.faces() {
+            for (i, j) in [(f[0], f[1]), (f[1], f[2]), (f[2], f[0])] {
+                *counts.entry((i.min(j), i.max(j))).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Check that the mesh is manifold, without degenerate triangles and oriented along the
+    /// normals, and return its number of boundary edges.
+    fn check_mesh(mesh: &Mesh, normals: &[[f64; 3]]) -> usize {
+        let counts = edge_counts(mesh);
+        assert!(counts.values().all(|&c| c <= 2));
+
+        for f in mesh.faces() {
+            let [a, b, c] = f.map(|v| mesh.vertices()[v]);
+            for (v, x, y) in [(a, b, c), (b, c, a), (c, a, b)] {
+                assert!(vertex_angle(&v, &x, &y) >= MIN_TRIANGLE_ANGLE - 1e-9);
+            }
+            let mut normal = [0.0; 3];
+            cross_vec3(
+                &[b[0] - a[0], b[1] - a[1], b[2] - a[2]],
+                &[c[0] - a[0], c[1] - a[1], c[2] - a[2]],
+                &mut normal,
+            );
+            let mean = [0, 1, 2].map(|k| f.iter().map(|&v| normals[v][k]).sum::<f64>());
+            assert!(dot_product3(&normal, &mean) > 0.0);
+        }
+
+        counts.values().filter(|&&c| c == 1).count()
+    }
+
+    #[test]
+    fn test_greedy_triangulation_plane() {
+        // a jittered grid of 30 x 30 points
+        let mut rng = StdRng::seed_from_u64(0);
+        let points = (0..900)
+            .map(|i| {
+                [
+                    (i % 30) as f64 * 0.05 + rng.random_range(-0.01..0.01),
+                    (i / 30) as f64 * 0.05 + rng.random_range(-0.01..0.01),
+                    0.0,
+                ]
+            })
+            .collect::<Vec<_>>();
+        let normals = vec![[0.0, 0.0, 1.0]; 900];
+        let cloud = PointCloud::new(points, None, None);
+
+        let mesh = greedy_triangulation(&cloud, &normals, 0.2, 2.5, 12);
+        let num_boundary = check_mesh(&mesh, &normals);
+        // the outer boundary has about 4 x 29 edges
+        assert!(num_boundary < 200, "{num_boundary} boundary edges");
+        // a full triangulation of the grid has 2 x 29 x 29 triangles
+        assert!(mesh.len() > 1500, "{} triangles", mesh.len());
+    }
+
+    #[test]
+    fn test_greedy_triangulation_sphere() {
+        let num_points = 1500;
+        let golden = PI * (3.0 - 5.0f64.sqrt());
+        let normals = (0..num_points)
+            .map(|i| {
+                let z = 1.0 - 2.0 * (i as f64 + 0.5) / num_points as f64;
+                let r = (1.0 - z * z).sqrt();
+                let theta = golden * i as f64;
+                [r * theta.cos(), r * theta.sin(), z]
+            })
+            .collect::<Vec<_>>();
+        let cloud = PointCloud::new(normals.clone(), None, None);
+
+        let mesh = greedy_triangulation(&cloud, &normals, 0.3, 2.5, 12);
+        let num_boundary = check_mesh(&mesh, &normals);
+        let num_edges = edge_counts(&mesh).len();
+        // a closed triangulation has 2 x (n - 2) triangles
+        assert!(
+            mesh.len() > 2 * (num_points - 2) * 9 / 10,
+            "{} triangles",
+            mesh.len()
+        );
+        assert!(
+            num_boundary * 20 < num_edges,
+            "{num_boundary} boundary edges out of {num_edges}"
+        );
+    }
+
+    #[test]
+    fn test_greedy_triangulation_rejects_far_points() {
+        // two separate patches are not connected
+        let points = (0..50)
+            .map(|i| {
+                let offset = if i < 25 { 0.0 } else { 10.0 };
+                [(i % 5) as f64 + offset, ((i % 25) / 5) as f64, 0.0]
+            })
+            .collect::<Vec<_>>();
+        let cloud = PointCloud::new(points, None, None);
+        let mesh = greedy_triangulation(&cloud, &[[0.0, 0.0, 1.0]; 50], 2.0, 2.5, 8);
+        assert_eq!(mesh.len(), 64);
+        assert!(mesh.faces().iter().all(|f| {
+            let patch = f[0] / 25;
+            f.iter().all(|v| v / 25 == patch)
+        }));
+    }
+}
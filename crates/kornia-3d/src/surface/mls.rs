@@ -0,0 +1,370 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    kdtree::{KdTree, Neighbor},
+    linalg::{cross_vec3, dot_product3, eigen_symmetric33, solve_linear},
+    pointcloud::PointCloud,
+};
+
+/// Minimum number of neighbors to fit the quadratic polynomial, fewer fall back to the plane.
+const MIN_POLYNOMIAL_NEIGHBORS: usize = 6;
+
+/// The result of the moving least squares upsampling.
+#[derive(Debug, Clone)]
+pub struct MlsUpsampled {
+    /// The upsampled point cloud, with the original points first.
+    pub cloud: PointCloud,
+    /// The interpolated intensity of each point, if intensities were given.
+    pub intensities: Option<Vec<f32>>,
+}
+
+/// A local MLS surface patch: a height field over the tangent plane of a point.
+struct MlsPatch {
+    origin: [f64; 3],
+    tu: [f64; 3],
+    tv: [f64; 3],
+    normal: [f64; 3],
+    // coefficients of h(u, v) = c0 u² + c1 uv + c2 v² + c3 u + c4 v + c5
+    coeffs: [f64; 6],
+}
+
+impl MlsPatch {
+    /// Evaluate the point and the surface normal of the patch at tangential coordinates.
+    fn evaluate(&self, u: f64, v: f64) -> ([f64; 3], [f64; 3]) {
+        let c = &self.coeffs;
+        let h = c[0] * u * u + c[1] * u * v + c[2] * v * v + c[3] * u + c[4] * v + c[5];
+        let dh_du = 2.0 * c[0] * u + c[1] * v + c[3];
+        let dh_dv = c[1] * u + 2.0 * c[2] * v + c[4];
+
+        let mut point = [0.0; 3];
+        let mut normal = [0.0; 3];
+        for k in 0..3 {
+            point[k] = self.origin[k] + u * self.tu[k] + v * self.tv[k] + h * self.normal[k];
+            normal[k] = self.normal[k] - dh_du * self.tu[k] - dh_dv * self.tv[k];
+        }
+        let norm = dot_product3(&normal, &normal).sqrt();
+        normal.iter_mut().for_each(|n| *n /= norm);
+
+        (point, normal)
+    }
+}
+
+/// Upsample a point cloud by sampling the moving least squares (MLS) surface of each point.
+///
+/// For each point, its neighbors within `search_radius` are weighted with a Gaussian of the
+/// distance and fitted with a plane, and then with a quadratic height field over that
+/// plane. New points are drawn at random tangential offsets within half the search radius
+/// and projected back onto the fitted polynomial. Points with fewer than six neighbors are
+/// sampled on the fitted plane, and points with fewer than three neighbors are not
+/// upsampled.
+///
+/// The colors and intensities of the new points are interpolated from their neighbors
+/// within `search_radius`, with the same Gaussian weights. If the
+/// cloud has normals, the new points get the normal of the fitted surface, oriented as the
+/// normal of their source point.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `intensities` - The optional intensity of each point.
+/// * `upsample_factor` - The ratio between the number of output and input points.
+/// * `search_radius` - The radius of the neighborhood used to fit the surface.
+/// * `seed` - The seed of the random generator used to draw the tangential offsets.
+///
+/// # Returns
+///
+/// The original points followed by the `upsample_factor - 1` new points of each point.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::pointcloud::PointCloud;
+/// use kornia_3d::surface::mls_upsample;
+///
+/// let points = (0..100).map(|i| [(i % 10) as f64 * 0.1, (i / 10) as f64 * 0.1, 0.0]).collect();
+/// let upsampled = mls_upsample(&PointCloud::new(points, None, None), None, 4, 0.25, 0);
+/// assert_eq!(upsampled.cloud.len(), 400);
+/// ```
+pub fn mls_upsample(
+    cloud: &PointCloud,
+    intensities: Option<&[f32]>,
+    upsample_factor: usize,
+    search_radius: f64,
+    seed: u64,
+) -> MlsUpsampled {
+    let points = cloud.points();
+    let kdtree = KdTree::new(points);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut out_points = points.clone();
+    let mut out_colors = cloud.colors().cloned();
+    let mut out_normals = cloud.normals().cloned();
+    let mut out_intensities = intensities.map(|i| i.to_vec());
+
+    let weight = |distance: f64| (-(distance * distance) / (search_radius * search_radius)).exp();
+    let num_new = upsample_factor.saturating_sub(1);
+
+    for (i, point) in points.iter().enumerate() {
+        if num_new == 0 {
+            break;
+        }
+        let neighbors = kdtree.within_radius(point, search_radius);
+        let Some(patch) = fit_mls_patch(points, point, &neighbors, &weight, search_radius) else {
+            continue;
+        };
+
+        for _ in 0..num_new {
+            // uniform sample in the disk of half the search radius
+            let r = 0.5 * search_radius * rng.random::<f64>().sqrt();
+            let theta = rng.random_range(0.0..std::f64::consts::TAU);
+            let (new_point, mut new_normal) = patch.evaluate(r * theta.cos(), r * theta.sin());
+
+            // interpolation weights of the neighbors of the new point
+            let new_neighbors = kdtree.within_radius(&new_point, search_radius);
+            let weights = new_neighbors
+                .iter()
+                .map(|n| weight(n.distance))
+                .collect::<Vec<_>>();
+            let weight_sum = weights.iter().sum::<f64>();
+
+            if let (Some(out_colors), Some(colors)) = (out_colors.as_mut(), cloud.colors()) {
+                let mut color = [0.0; 3];
+                for (n, w) in new_neighbors.iter().zip(weights.iter()) {
+                    for k in 0..3 {
+                        color[k] += w * colors[n.index][k] as f64 / weight_sum;
+                    }
+                }
+                out_colors.push(color.map(|c| c.round().clamp(0.0, 255.0) as u8));
+            }
+
+            if let (Some(out_intensities), Some(intensities)) =
+                (out_intensities.as_mut(), intensities)
+            {
+                let intensity = new_neighbors
+                    .iter()
+                    .zip(weights.iter())
+                    .map(|(n, w)| w * intensities[n.index] as f64)
+                    .sum::<f64>()
+                    / weight_sum;
+                out_intensities.push(intensity as f32);
+            }
+
+            if let (Some(out_normals), Some(normals)) = (out_normals.as_mut(), cloud.normals()) {
+                if dot_product3(&new_normal, &normals[i]) < 0.0 {
+                    new_normal = new_normal.map(|n| -n);
+                }
+                out_normals.push(new_normal);
+            }
+
+            out_points.push(new_point);
+        }
+    }
+
+    MlsUpsampled {
+        cloud: PointCloud::new(out_points, out_colors, out_normals),
+        intensities: out_intensities,
+    }
+}
+
+/// Fit the weighted plane and quadratic height field of the neighborhood of a point.
+fn fit_mls_patch(
+    points: &[[f64; 3]],
+    point: &[f64; 3],
+    neighbors: &[Neighbor],
+    weight: &impl Fn(f64) -> f64,
+    search_radius: f64,
+) -> Option<MlsPatch> {
+    if neighbors.len() < 3 {
+        return None;
+    }
+
+    // weighted centroid and covariance
+    let weights = neighbors
+        .iter()
+        .map(|n| weight(n.distance))
+        .collect::<Vec<_>>();
+    let weight_sum = weights.iter().sum::<f64>();
+    let mut centroid = [0.0; 3];
+    for (n, w) in neighbors.iter().zip(weights.iter()) {
+        for k in 0..3 {
+            centroid[k] += w * points[n.index][k] / weight_sum;
+        }
+    }
+    let mut covariance = [[0.0; 3]; 3];
+    for (n, w) in neighbors.iter().zip(weights.iter()) {
+        let p = &points[n.index];
+        let d = [p[0] - centroid[0], p[1] - centroid[1], p[2] - centroid[2]];
+        for r in 0..3 {
+            for c in 0..3 {
+                covariance[r][c] += w * d[r] * d[c];
+            }
+        }
+    }
+
+    // the normal of the plane is the direction of least variance
+    let (_, eigenvectors) = eigen_symmetric33(&covariance);
+    let normal = eigenvectors[0];
+    let tu = eigenvectors[2];
+    let mut tv = [0.0; 3];
+    cross_vec3(&normal, &tu, &mut tv);
+
+    // project the point onto the plane
+    let offset = dot_product3(
+        &[
+            point[0] - centroid[0],
+            point[1] - centroid[1],
+            point[2] - centroid[2],
+        ],
+        &normal,
+    );
+    let origin = [
+        point[0] - offset * normal[0],
+        point[1] - offset * normal[1],
+        point[2] - offset * normal[2],
+    ];
+
+    let mut coeffs = [0.0; 6];
+    if neighbors.len() >= MIN_POLYNOMIAL_NEIGHBORS {
+        // weighted least squares in coordinates scaled by the search radius
+        let mut ata = [[0.0; 6]; 6];
+        let mut atb = [0.0; 6];
+        for (n, w) in neighbors.iter().zip(weights.iter()) {
+            let p = &points[n.index];
+            let d = [p[0] - origin[0], p[1] - origin[1], p[2] - origin[2]];
+            let u = dot_product3(&d, &tu) / search_radius;
+            let v = dot_product3(&d, &tv) / search_radius;
+            let h = dot_product3(&d, &normal) / search_radius;
+            let row = [u * u, u * v, v * v, u, v, 1.0];
+            for r in 0..6 {
+                for c in 0..6 {
+                    ata[r][c] += w * row[r] * row[c];
+                }
+                atb[r] += w * row[r] * h;
+            }
+        }
+        if let Some(c) = solve_linear(&ata, &atb) {
+            coeffs = [
+                c[0] / search_radius,
+                c[1] / search_radius,
+                c[2] / search_radius,
+                c[3],
+                c[4],
+                c[5] * search_radius,
+            ];
+        }
+    }
+
+    Some(MlsPatch {
+        origin,
+        tu,
+        tv,
+        normal,
+        coeffs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    // the plane z = 0.3 x - 0.2 y + 1
+    fn plane_height(x: f64, y: f64) -> f64 {
+        0.3 * x - 0.2 * y + 1.0
+    }
+
+    #[test]
+    fn test_mls_upsample_plane() {
+        let points = (0..225)
+            .map(|i| {
+                let (x, y) = ((i % 15) as f64 * 0.2, (i / 15) as f64 * 0.2);
+                [x, y, plane_height(x, y)]
+            })
+            .collect::<Vec<_>>();
+        let normal = {
+            let norm = (0.3f64 * 0.3 + 0.2 * 0.2 + 1.0).sqrt();
+            [-0.3 / norm, 0.2 / norm, 1.0 / norm]
+        };
+        let cloud = PointCloud::new(points.clone(), None, Some(vec![normal; points.len()]));
+
+        let upsampled = mls_upsample(&cloud, None, 5, 0.5, 7);
+        assert_eq!(upsampled.cloud.len(), 5 * points.len());
+        assert_eq!(&upsampled.cloud.points()[..points.len()], &points[..]);
+
+        for p in upsampled.cloud.points() {
+            assert_relative_eq!(p[2], plane_height(p[0], p[1]), epsilon = 1e-6);
+        }
+
+        let normals = upsampled.cloud.normals().unwrap();
+        assert_eq!(normals.len(), upsampled.cloud.len());
+        for n in normals {
+            assert_relative_eq!(dot_product3(n, &normal), 1.0, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_mls_upsample_sphere() {
+        let radius = 2.0;
+        let num_points = 2000;
+        let golden = std::f64::consts::PI * (3.0 - 5.0f64.sqrt());
+        let points = (0..num_points)
+            .map(|i| {
+                let z = 1.0 - 2.0 * (i as f64 + 0.5) / num_points as f64;
+                let r = (1.0 - z * z).sqrt();
+                let theta = golden * i as f64;
+                [
+                    radius * r * theta.cos(),
+                    radius * r * theta.sin(),
+                    radius * z,
+                ]
+            })
+            .collect::<Vec<_>>();
+        let cloud = PointCloud::new(points, None, None);
+
+        let upsampled = mls_upsample(&cloud, None, 3, 0.4, 1);
+        assert_eq!(upsampled.cloud.len(), 3 * num_points);
+        for p in upsampled.cloud.points() {
+            let distance = dot_product3(p, p).sqrt();
+            assert_relative_eq!(distance, radius, epsilon = 5e-3);
+        }
+    }
+
+    #[test]
+    fn test_mls_upsample_interpolates_attributes() {
+        let points = (0..100)
+            .map(|i| [(i % 10) as f64 * 0.1, (i / 10) as f64 * 0.1, 0.0])
+            .collect::<Vec<_>>();
+        // colors and intensities increasing along x
+        let colors = points
+            .iter()
+            .map(|p| [(p[0] * 200.0) as u8, 50, 100])
+            .collect::<Vec<_>>();
+        let intensities = points.iter().map(|p| p[0] as f32).collect::<Vec<_>>();
+        let cloud = PointCloud::new(points.clone(), Some(colors.clone()), None);
+
+        let upsampled = mls_upsample(&cloud, Some(&intensities), 3, 0.25, 3);
+        let out_colors = upsampled.cloud.colors().unwrap();
+        let out_intensities = upsampled.intensities.unwrap();
+        assert_eq!(out_colors.len(), upsampled.cloud.len());
+        assert_eq!(out_intensities.len(), upsampled.cloud.len());
+        assert!(upsampled.cloud.normals().is_none());
+
+        for ((p, c), intensity) in upsampled
+            .cloud
+            .points()
+            .iter()
+            .zip(out_colors.iter())
+            .zip(out_intensities.iter())
+            .skip(points.len())
+        {
+            assert_eq!(c[1], 50);
+            assert_eq!(c[2], 100);
+            assert!(c[0] <= 180);
+            // away from the borders the interpolation follows the linear gradient
+            if p[0] > 0.25 && p[0] < 0.65 && p[1] > 0.25 && p[1] < 0.65 {
+                assert!((*intensity as f64 - p[0]).abs() < 0.03);
+            }
+            assert!((*intensity as f64) >= 0.0 && (*intensity as f64) <= 0.9);
+        }
+    }
+}
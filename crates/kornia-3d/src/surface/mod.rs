@@ -0,0 +1,13 @@
+#[cfg(feature = "meshing")]
+mod ball_pivoting;
+#[cfg(feature = "meshing")]
+pub use ball_pivoting::*;
+
+mod greedy_projection;
+pub use greedy_projection::*;
+
+mod mls;
+pub use mls::*;
+
+mod upsample;
+pub use upsample::*;
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use crate::{
+    kdtree::KdTree,
+    linalg::{cross_vec3, dot_product3},
+};
+
+/// Number of points, the point itself included, expected within the density radius.
+const DENSITY_NEIGHBORS: usize = 8;
+
+/// Radius of the neighborhood fitting the tangent plane, relative to the density radius.
+const SUPPORT_RADIUS_FACTOR: f64 = 2.0;
+
+/// Upsample the under-sampled regions of a point cloud on its tangent planes.
+///
+/// The local density at a point is the number of points within the radius
+/// `r = sqrt(8 / (pi * target_density))` divided by the area `pi * r^2` of the disk, so that
+/// a point is under-sampled when fewer than eight points, itself included, lie within `r`.
+/// For each under-sampled point:
+///
+/// 1. The tangent plane is fitted through the centroid of the neighbors within `2 * r`,
+///    with the normal of the point.
+/// 2. New points are sampled on a ring of radius `r / 2` around the point in the tangent
+///    plane, the candidates farthest from the existing points first, until the point has
+///    eight neighbors.
+/// 3. Each new point is projected onto the surface along the normal, by the mean height of
+///    the neighbors above it, weighted by a Gaussian of their tangential distance.
+///
+/// The points added for a point count in the density of the next ones, so that the holes
+/// are filled without piling up points. The new points stay within `r` of their point as
+/// long as the surface is smooth at that scale.
+///
+/// # Arguments
+///
+/// * `points` - The points of the cloud.
+/// * `normals` - The normal of each point. The points without a normal of positive length
+///   are not upsampled.
+/// * `target_density` - The minimum number of points per unit area.
+///
+/// # Returns
+///
+/// The original points followed by the new points.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::surface::upsample_cloud;
+///
+/// // a grid of 100 points per unit area, upsampled to 200
+/// let points = (0..100)
+///     .map(|i| [(i % 10) as f64 * 0.1, (i / 10) as f64 * 0.1, 0.0])
+///     .collect::<Vec<_>>();
+/// let normals = vec![[0.0, 0.0, 1.0]; points.len()];
+/// let upsampled = upsample_cloud(&points, &normals, 200.0);
+/// assert_eq!(upsampled[..100], points[..]);
+/// assert!(upsampled.len() > 200);
+/// ```
+pub fn upsample_cloud(
+    points: &[[f64; 3]],
+    normals: &[[f64; 3]],
+    target_density: f64,
+) -> Vec<[f64; 3]> {
+    let mut out = points.to_vec();
+    if points.is_empty() || !(target_density > 0.0 && target_density.is_finite()) {
+        return out;
+    }
+    let radius = (DENSITY_NEIGHBORS as f64 / (PI * target_density)).sqrt();
+    let kdtree = KdTree::new(points);
+
+    // the new points, hashed in cells of the density radius
+    let cell_of = |p: &[f64; 3]| p.map(|x| (x / radius).floor() as i64);
+    let mut grid: HashMap<[i64; 3], Vec<[f64; 3]>> = HashMap::new();
+
+    for (point, normal) in points.iter().zip(normals.iter()) {
+        let norm = dot_product3(normal, normal).sqrt();
+        if !(norm > 0.0 && norm.is_finite()) {
+            continue;
+        }
+        let normal = normal.map(|x| x / norm);
+
+        let cell = cell_of(point);
+        let nearby_new = (0..27)
+            .filter_map(|k| {
+                grid.get(&[
+                    cell[0] + k % 3 - 1,
+                    cell[1] + (k / 3) % 3 - 1,
+                    cell[2] + k / 9 - 1,
+                ])
+            })
+            .flatten()
+            .filter(|q| distance(point, q) <= radius)
+            .copied()
+            .collect::<Vec<_>>();
+        let count = kdtree.within_radius(point, radius).len() + nearby_new.len();
+        if count >= DENSITY_NEIGHBORS {
+            continue;
+        }
+
+        // the tangent plane through the centroid of the support
+        let support = kdtree
+            .within_radius(point, SUPPORT_RADIUS_FACTOR * radius)
+            .iter()
+            .map(|n| points[n.index])
+            .collect::<Vec<_>>();
+        let mut centroid = [0.0; 3];
+        for q in support.iter() {
+            for k in 0..3 {
+                centroid[k] += q[k] / support.len() as f64;
+            }
+        }
+        let offset = dot_product3(&sub(point, &centroid), &normal);
+        let origin = [0, 1, 2].map(|k| point[k] - offset * normal[k]);
+        let (u, v) = tangent_basis(&normal);
+
+        // the candidates of the ring farthest from the existing points first
+        let mut candidates = (0..DENSITY_NEIGHBORS)
+            .map(|i| {
+                let theta = 2.0 * PI * i as f64 / DENSITY_NEIGHBORS as f64;
+                let (c, s) = (0.5 * radius * theta.cos(), 0.5 * radius * theta.sin());
+                [0, 1, 2].map(|k| origin[k] + c * u[k] + s * v[k])
+            })
+            .collect::<Vec<_>>();
+        let mut existing = support
+            .iter()
+            .chain(nearby_new.iter())
+            .copied()
+            .collect::<Vec<_>>();
+        for _ in count..DENSITY_NEIGHBORS {
+            let clearance = |c: &[f64; 3]| {
+                existing
+                    .iter()
+                    .map(|q| distance(c, q))
+                    .fold(f64::INFINITY, f64::min)
+            };
+            let Some(best) = (0..candidates.len())
+                .max_by(|a, b| clearance(&candidates[*a]).total_cmp(&clearance(&candidates[*b])))
+            else {
+                break;
+            };
+            let sample = candidates.swap_remove(best);
+
+            // the mean height of the support above the sample
+            let (mut height, mut weight_sum) = (0.0, 0.0);
+            for q in support.iter() {
+                let d = sub(q, &sample);
+                let h = dot_product3(&d, &normal);
+                let tangential2 = dot_product3(&d, &d) - h * h;
+                let w = (-tangential2 / (radius * radius)).exp();
+                height += w * h;
+                weight_sum += w;
+            }
+            if weight_sum > 0.0 {
+                height /= weight_sum;
+            }
+            let new_point = [0, 1, 2].map(|k| sample[k] + height * normal[k]);
+
+            existing.push(sample);
+            grid.entry(cell_of(&new_point)).or_default().push(new_point);
+            out.push(new_point);
+        }
+    }
+
+    out
+}
+
+/// Two unit vectors completing the unit normal into an orthonormal basis.
+fn tangent_basis(normal: &[f64; 3]) -> ([f64; 3], [f64; 3]) {
+    let axis = if normal[0].abs() < 0.9 {
+        [1.0, 0.0, 0.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    let mut u = [0.0; 3];
+    cross_vec3(normal, &axis, &mut u);
+    let norm = dot_product3(&u, &u).sqrt();
+    let u = u.map(|x| x / norm);
+    let mut v = [0.0; 3];
+    cross_vec3(normal, &u, &mut v);
+    (u, v)
+}
+
+fn sub(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    let d = sub(a, b);
+    dot_product3(&d, &d).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn height(x: f64, y: f64) -> f64 {
+        0.3 * (x * x + y * y)
+    }
+
+    /// A grid of 100 points per unit area on a paraboloid, with a hole.
+    fn paraboloid_with_hole() -> (Vec<[f64; 3]>, Vec<[f64; 3]>) {
+        let mut points = Vec::new();
+        let mut normals = Vec::new();
+        for i in 0..21 {
+            for j in 0..21 {
+                let (x, y) = (i as f64 * 0.1 - 1.0, j as f64 * 0.1 - 1.0);
+                if (x - 0.3).powi(2) + (y - 0.2).powi(2) < 0.25f64.powi(2) {
+                    continue;
+                }
+                points.push([x, y, height(x, y)]);
+                let n = [-0.6 * x, -0.6 * y, 1.0];
+                let norm = dot_product3(&n, &n).sqrt();
+                normals.push(n.map(|v| v / norm));
+            }
+        }
+        (points, normals)
+    }
+
+    #[test]
+    fn test_upsample_cloud_density() {
+        let (points, normals) = paraboloid_with_hole();
+        let target_density = 300.0;
+        let upsampled = upsample_cloud(&points, &normals, target_density);
+        assert_eq!(upsampled[..points.len()], points[..]);
+        assert!(upsampled.len() > points.len());
+
+        // the density at each point is at least the target
+        let radius = (DENSITY_NEIGHBORS as f64 / (PI * target_density)).sqrt();
+        let kdtree = KdTree::new(&upsampled);
+        for p in points.iter() {
+            let density = kdtree.within_radius(p, radius).len() as f64 / (PI * radius * radius);
+            assert!(density >= target_density);
+        }
+
+        // the new points lie on the surface
+        for p in upsampled[points.len()..].iter() {
+            assert!((p[2] - height(p[0], p[1])).abs() < 5e-3);
+        }
+    }
+
+    #[test]
+    fn test_upsample_cloud_dense_enough() {
+        let (points, normals) = paraboloid_with_hole();
+        // 8 points within a radius of three and a half grid spacings, even at the corners
+        assert_eq!(upsample_cloud(&points, &normals, 20.0), points);
+        assert_eq!(upsample_cloud(&points, &normals, 0.0), points);
+        // no normal, no upsampling
+        assert_eq!(upsample_cloud(&points, &[], 300.0), points);
+    }
+}
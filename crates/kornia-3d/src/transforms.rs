@@ -1,3 +1,186 @@
+use crate::linalg::{dot_product3, mat33_mul_vec3, matmul33, transpose_mat33};
+
+/// A rigid transformation of the 3D space, applied as `p' = R * p + t`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RigidTransform3 {
+    /// The rotation matrix.
+    pub rotation: [[f64; 3]; 3],
+    /// The translation vector.
+    pub translation: [f64; 3],
+}
+
+impl RigidTransform3 {
+    /// Create a rigid transformation from a rotation matrix and a translation vector.
+    pub fn new(rotation: [[f64; 3]; 3], translation: [f64; 3]) -> Self {
+        Self {
+            rotation,
+            translation,
+        }
+    }
+
+    /// The identity transformation.
+    pub fn identity() -> Self {
+        Self::new(
+            [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            [0.0; 3],
+        )
+    }
+
+    /// Apply the transformation to a point.
+    pub fn apply(&self, point: &[f64; 3]) -> [f64; 3] {
+        [
+            dot_product3(&self.rotation[0], point) + self.translation[0],
+            dot_product3(&self.rotation[1], point) + self.translation[1],
+            dot_product3(&self.rotation[2], point) + self.translation[2],
+        ]
+    }
+
+    /// Compose with another transformation, applying `other` first and then `self`.
+    pub fn compose(&self, other: &Self) -> Self {
+        let mut rotation = [[0.0; 3]; 3];
+        matmul33(&self.rotation, &other.rotation, &mut rotation);
+        Self::new(rotation, self.apply(&other.translation))
+    }
+
+    /// The inverse transformation, `p = R^T * (p' - t)`.
+    pub fn inverse(&self) -> Self {
+        let mut rotation = [[0.0; 3]; 3];
+        transpose_mat33(&self.rotation, &mut rotation);
+        let mut translation = [0.0; 3];
+        mat33_mul_vec3(&rotation, &self.translation, &mut translation);
+        Self::new(rotation, translation.map(|v| -v))
+    }
+
+    /// The angle of the rotation in radians, in `[0, PI]`.
+    pub fn rotation_angle(&self) -> f64 {
+        let r = &self.rotation;
+        let cos = 0.5 * (r[0][0] + r[1][1] + r[2][2] - 1.0);
+        cos.clamp(-1.0, 1.0).acos()
+    }
+
+    /// Interpolate between this transformation at `t = 0` and another at `t = 1`.
+    ///
+    /// The rotation is interpolated along the geodesic of SO(3), i.e. at a constant angular
+    /// velocity about a fixed axis, and the translation linearly, which models a sensor
+    /// moving at constant velocities.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The transformation at `t = 1`.
+    /// * `t` - The interpolation parameter, extrapolating outside of `[0, 1]`.
+    ///
+    /// # Returns
+    ///
+    /// The interpolated transformation.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_3d::transforms::{axis_angle_to_rotation_matrix, RigidTransform3};
+    ///
+    /// let end = RigidTransform3::new(
+    ///     axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 1.0).unwrap(),
+    ///     [2.0, 0.0, 0.0],
+    /// );
+    /// let middle = RigidTransform3::identity().interpolate(&end, 0.5);
+    /// assert!((middle.rotation_angle() - 0.5).abs() < 1e-12);
+    /// assert!((middle.translation[0] - 1.0).abs() < 1e-12);
+    /// ```
+    pub fn interpolate(&self, other: &Self, t: f64) -> Self {
+        // the relative rotation from this rotation to the other one
+        let mut inverse = [[0.0; 3]; 3];
+        transpose_mat33(&self.rotation, &mut inverse);
+        let mut relative = [[0.0; 3]; 3];
+        matmul33(&inverse, &other.rotation, &mut relative);
+
+        let rotation = match rotation_axis_angle(&relative) {
+            Some((axis, angle)) => {
+                let mut rotation = [[0.0; 3]; 3];
+                let delta = axis_angle_to_rotation_matrix(&axis, t * angle).unwrap_or([
+                    [1.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0],
+                    [0.0, 0.0, 1.0],
+                ]);
+                matmul33(&self.rotation, &delta, &mut rotation);
+                rotation
+            }
+            None => self.rotation,
+        };
+        let translation = [0, 1, 2]
+            .map(|k| self.translation[k] + t * (other.translation[k] - self.translation[k]));
+        Self::new(rotation, translation)
+    }
+
+    /// Project the rotation back onto SO(3), keeping the translation.
+    ///
+    /// Chaining many transformations accumulates rounding errors, and the product of the
+    /// rotations slowly drifts away from an orthonormal matrix. The rotation is replaced by
+    /// the closest rotation in the Frobenius norm, `U * diag(1, 1, det(U * V^T)) * V^T` from
+    /// the singular value decomposition `R = U * S * V^T`.
+    ///
+    /// # Returns
+    ///
+    /// The transformation with an orthonormal rotation of determinant 1.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_3d::transforms::RigidTransform3;
+    ///
+    /// let drifted = RigidTransform3::new(
+    ///     [[1.001, 0.002, 0.0], [-0.002, 0.999, 0.0], [0.0, 0.0, 1.0]],
+    ///     [1.0, 2.0, 3.0],
+    /// );
+    /// let pose = drifted.orthonormalized();
+    /// let r = &pose.rotation;
+    /// assert!((r[0][0] * r[0][0] + r[1][0] * r[1][0] + r[2][0] * r[2][0] - 1.0).abs() < 1e-12);
+    /// assert_eq!(pose.translation, [1.0, 2.0, 3.0]);
+    /// ```
+    pub fn orthonormalized(&self) -> Self {
+        let rotation = faer::Mat::<f64>::from_fn(3, 3, |i, j| self.rotation[i][j]);
+        let svd = rotation.svd();
+        let (u, v_t) = (svd.u(), svd.v().transpose());
+
+        // flip the weakest direction if the closest orthonormal matrix is a reflection
+        let sign = (u * v_t).determinant().signum();
+        let correction = faer::Mat::<f64>::from_fn(3, 3, |i, j| match (i, j) {
+            (2, 2) => sign,
+            (i, j) if i == j => 1.0,
+            _ => 0.0,
+        });
+        let closest = u * correction * v_t;
+        Self::new(
+            std::array::from_fn(|i| std::array::from_fn(|j| closest.read(i, j))),
+            self.translation,
+        )
+    }
+}
+
+/// Compute the unit axis and the angle of a rotation matrix, `None` for the identity.
+fn rotation_axis_angle(r: &[[f64; 3]; 3]) -> Option<([f64; 3], f64)> {
+    let cos = (0.5 * (r[0][0] + r[1][1] + r[2][2] - 1.0)).clamp(-1.0, 1.0);
+    let angle = cos.acos();
+    if angle < 1e-12 {
+        return None;
+    }
+
+    // twice the sine of the angle times the axis, which vanishes near a half turn
+    let skew = [r[2][1] - r[1][2], r[0][2] - r[2][0], r[1][0] - r[0][1]];
+    let norm = dot_product3(&skew, &skew).sqrt();
+    if norm > 1e-6 {
+        return Some((skew.map(|v| v / norm), angle));
+    }
+
+    // near a half turn R = 2 a a^T - I, the axis is the largest column of R + I
+    let k = (0..3)
+        .max_by(|&a, &b| r[a][a].total_cmp(&r[b][b]))
+        .unwrap_or(0);
+    let mut axis = [r[0][k], r[1][k], r[2][k]];
+    axis[k] += 1.0;
+    let norm = dot_product3(&axis, &axis).sqrt();
+    Some((axis.map(|v| v / norm), angle))
+}
+
 /// Compute the rotation matrix from an axis and angle.
 ///
 /// # Arguments
@@ -71,6 +254,77 @@ pub fn axis_angle_to_rotation_matrix(
     Ok([[m00, m01, m02], [m10, m11, m12], [m20, m21, m22]])
 }
 
+/// Compute the exponential map of a twist of se(3) as a rigid transformation.
+///
+/// The twist `ξ = (ω, τ)` has the rotation vector first, as the perturbations of
+/// [`icp_jacobian`](crate::pose::icp_jacobian). With `W = [ω]_x` and `θ = |ω|`, the rotation
+/// is `exp(W) = I + sin(θ) / θ * W + (1 - cos(θ)) / θ^2 * W^2` and the translation `V * τ`,
+/// with `V = I + (1 - cos(θ)) / θ^2 * W + (θ - sin(θ)) / θ^3 * W^2`, the motion of a
+/// constant twist during a unit of time. The coefficients are expanded in Taylor series
+/// for a small angle.
+///
+/// # Arguments
+///
+/// * `twist` - The rotation vector and the translational velocity.
+///
+/// # Returns
+///
+/// The rigid transformation of the twist.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::transforms::se3_exp;
+///
+/// // a quarter turn about z carries the velocity along x on a quarter circle
+/// let twist = [0.0, 0.0, std::f64::consts::FRAC_PI_2, 1.0, 0.0, 0.0];
+/// let transform = se3_exp(&twist);
+/// let radius = 1.0 / std::f64::consts::FRAC_PI_2;
+/// assert!((transform.translation[0] - radius).abs() < 1e-12);
+/// assert!((transform.translation[1] - radius).abs() < 1e-12);
+/// ```
+pub fn se3_exp(twist: &[f64; 6]) -> RigidTransform3 {
+    let omega = [twist[0], twist[1], twist[2]];
+    let tau = [twist[3], twist[4], twist[5]];
+    let theta2 = dot_product3(&omega, &omega);
+    let theta = theta2.sqrt();
+
+    let (a, b, c) = if theta < 1e-4 {
+        (
+            1.0 - theta2 / 6.0,
+            0.5 - theta2 / 24.0,
+            1.0 / 6.0 - theta2 / 120.0,
+        )
+    } else {
+        (
+            theta.sin() / theta,
+            (1.0 - theta.cos()) / theta2,
+            (theta - theta.sin()) / (theta2 * theta),
+        )
+    };
+
+    let w = [
+        [0.0, -omega[2], omega[1]],
+        [omega[2], 0.0, -omega[0]],
+        [-omega[1], omega[0], 0.0],
+    ];
+    let mut w2 = [[0.0; 3]; 3];
+    matmul33(&w, &w, &mut w2);
+
+    let mut rotation = [[0.0; 3]; 3];
+    let mut v = [[0.0; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            let identity = if i == j { 1.0 } else { 0.0 };
+            rotation[i][j] = identity + a * w[i][j] + b * w2[i][j];
+            v[i][j] = identity + b * w[i][j] + c * w2[i][j];
+        }
+    }
+    let mut translation = [0.0; 3];
+    mat33_mul_vec3(&v, &tau, &mut translation);
+    RigidTransform3::new(rotation, translation)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,4 +343,159 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_se3_exp() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(se3_exp(&[0.0; 6]), RigidTransform3::identity());
+
+        // a pure translation, and the rotation of the rotation vector
+        let translation = se3_exp(&[0.0, 0.0, 0.0, 1.0, -2.0, 0.5]);
+        assert_eq!(translation.translation, [1.0, -2.0, 0.5]);
+        let axis = [0.3f64, -0.5, 1.0];
+        let norm = dot_product3(&axis, &axis).sqrt();
+        for angle in [1e-6, 1e-3, 0.7, 3.0] {
+            let omega = axis.map(|x| x * angle / norm);
+            let transform = se3_exp(&[omega[0], omega[1], omega[2], 0.0, 0.0, 0.0]);
+            let expected = axis_angle_to_rotation_matrix(&axis, angle)?;
+            for i in 0..3 {
+                for j in 0..3 {
+                    assert_relative_eq!(transform.rotation[i][j], expected[i][j], epsilon = 1e-14);
+                }
+            }
+        }
+
+        // the motion of a constant twist: two half steps make a full step
+        for twist in [
+            [0.3, -0.5, 1.0, 1.0, 2.0, -0.5],
+            [2e-5, 1e-5, -3e-5, 1.0, 2.0, -0.5],
+        ] {
+            let half = se3_exp(&twist.map(|x| 0.5 * x));
+            let (expected, actual) = (half.compose(&half), se3_exp(&twist));
+            for i in 0..3 {
+                assert_relative_eq!(
+                    actual.translation[i],
+                    expected.translation[i],
+                    epsilon = 1e-14
+                );
+                for j in 0..3 {
+                    assert_relative_eq!(
+                        actual.rotation[i][j],
+                        expected.rotation[i][j],
+                        epsilon = 1e-14
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rigid_transform3() -> Result<(), Box<dyn std::error::Error>> {
+        let a = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.3)?,
+            [1.0, 2.0, 3.0],
+        );
+        let b = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[1.0, 1.0, 0.0], -0.7)?,
+            [-0.5, 0.0, 0.25],
+        );
+        let p = [0.3, -1.2, 2.0];
+
+        let ab = a.compose(&b);
+        let (expected, actual) = (a.apply(&b.apply(&p)), ab.apply(&p));
+        for k in 0..3 {
+            assert_relative_eq!(actual[k], expected[k], epsilon = 1e-12);
+        }
+
+        let identity = a.compose(&a.inverse());
+        let expected = RigidTransform3::identity();
+        for i in 0..3 {
+            assert_relative_eq!(identity.translation[i], 0.0, epsilon = 1e-12);
+            for j in 0..3 {
+                assert_relative_eq!(
+                    identity.rotation[i][j],
+                    expected.rotation[i][j],
+                    epsilon = 1e-12
+                );
+            }
+        }
+
+        assert_relative_eq!(a.rotation_angle(), 0.3, epsilon = 1e-12);
+        assert_relative_eq!(b.inverse().rotation_angle(), 0.7, epsilon = 1e-12);
+        assert_eq!(RigidTransform3::identity().rotation_angle(), 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rigid_transform3_interpolate() -> Result<(), Box<dyn std::error::Error>> {
+        let axis = [0.48, -0.6, 0.64];
+        let a = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[1.0, 0.0, 0.0], 0.4)?,
+            [1.0, 2.0, 3.0],
+        );
+        for angle in [0.0, 0.7, std::f64::consts::PI] {
+            let delta =
+                RigidTransform3::new(axis_angle_to_rotation_matrix(&axis, angle)?, [0.0; 3]);
+            let b = RigidTransform3::new(a.compose(&delta).rotation, [3.0, -2.0, 1.0]);
+
+            // the ends and a constant angular velocity about the axis
+            for (t, expected) in [(0.0, &a), (1.0, &b)] {
+                let c = a.interpolate(&b, t);
+                for i in 0..3 {
+                    assert_relative_eq!(c.translation[i], expected.translation[i], epsilon = 1e-9);
+                    for j in 0..3 {
+                        assert_relative_eq!(
+                            c.rotation[i][j],
+                            expected.rotation[i][j],
+                            epsilon = 1e-9
+                        );
+                    }
+                }
+            }
+            let c = a.interpolate(&b, 0.25);
+            assert_relative_eq!(
+                a.inverse().compose(&c).rotation_angle(),
+                0.25 * angle,
+                epsilon = 1e-9
+            );
+            assert_relative_eq!(c.translation[0], 1.5, epsilon = 1e-12);
+            assert_relative_eq!(c.translation[1], 1.0, epsilon = 1e-12);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rigid_transform3_orthonormalized() -> Result<(), Box<dyn std::error::Error>> {
+        let rotation = axis_angle_to_rotation_matrix(&[0.48, -0.6, 0.64], 0.9)?;
+        let pose = RigidTransform3::new(rotation, [1.0, -2.0, 0.5]);
+
+        // a rotation is left untouched
+        let same = pose.orthonormalized();
+        for (row, expected) in same.rotation.iter().zip(rotation.iter()) {
+            for (r, e) in row.iter().zip(expected.iter()) {
+                assert_relative_eq!(r, e, epsilon = 1e-12);
+            }
+        }
+
+        // a perturbed rotation is projected back onto SO(3), close to the original one
+        let perturbed = RigidTransform3::new(
+            std::array::from_fn(|i| {
+                std::array::from_fn(|j| rotation[i][j] + 1e-4 * (i + 2 * j) as f64)
+            }),
+            pose.translation,
+        );
+        let projected = perturbed.orthonormalized();
+        let mut gram = [[0.0; 3]; 3];
+        let mut transposed = [[0.0; 3]; 3];
+        transpose_mat33(&projected.rotation, &mut transposed);
+        matmul33(&transposed, &projected.rotation, &mut gram);
+        for (i, row) in gram.iter().enumerate() {
+            for (j, g) in row.iter().enumerate() {
+                assert_relative_eq!(*g, if i == j { 1.0 } else { 0.0 }, epsilon = 1e-12);
+            }
+        }
+        assert!(pose.inverse().compose(&projected).rotation_angle() < 1e-3);
+        assert_eq!(projected.translation, pose.translation);
+        Ok(())
+    }
 }
@@ -0,0 +1,329 @@
+/// An axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    /// The corner with the smallest coordinates.
+    pub min: [f64; 3],
+    /// The corner with the largest coordinates.
+    pub max: [f64; 3],
+}
+
+/// A dense voxel grid, binary by default.
+///
+/// The voxels are stored with the x index varying fastest, then y, then z.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoxelGrid<T = bool> {
+    dims: [usize; 3],
+    voxel_size: f64,
+    cells: Vec<T>,
+}
+
+impl VoxelGrid {
+    /// Create a new empty voxel grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `dims` - The number of voxels along the x, y and z axes.
+    /// * `voxel_size` - The edge length of a voxel.
+    pub fn new(dims: [usize; 3], voxel_size: f64) -> Self {
+        Self::filled(dims, voxel_size, false)
+    }
+
+    /// Check if a voxel is occupied.
+    pub fn is_occupied(&self, x: usize, y: usize, z: usize) -> bool {
+        self.cells[self.index(x, y, z)]
+    }
+
+    /// Set the occupancy of a voxel.
+    pub fn set_occupied(&mut self, x: usize, y: usize, z: usize, occupied: bool) {
+        self.set(x, y, z, occupied);
+    }
+
+    /// Get as reference the occupancy of all the voxels in linear index order.
+    pub fn occupied(&self) -> &[bool] {
+        &self.cells
+    }
+}
+
+impl<T> VoxelGrid<T> {
+    /// Create a new voxel grid with all the voxels set to the same value.
+    ///
+    /// # Arguments
+    ///
+    /// * `dims` - The number of voxels along the x, y and z axes.
+    /// * `voxel_size` - The edge length of a voxel.
+    /// * `value` - The value of every voxel.
+    pub fn filled(dims: [usize; 3], voxel_size: f64, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            dims,
+            voxel_size,
+            cells: vec![value; dims[0] * dims[1] * dims[2]],
+        }
+    }
+
+    /// Get the number of voxels along the x, y and z axes.
+    pub fn dims(&self) -> [usize; 3] {
+        self.dims
+    }
+
+    /// Get the edge length of a voxel.
+    pub fn voxel_size(&self) -> f64 {
+        self.voxel_size
+    }
+
+    /// Get the total number of voxels.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Check if the grid has no voxels.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Get the linear index of a voxel.
+    #[inline]
+    pub fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        x + self.dims[0] * (y + self.dims[1] * z)
+    }
+
+    /// Get as reference the value of a voxel.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> &T {
+        &self.cells[self.index(x, y, z)]
+    }
+
+    /// Set the value of a voxel.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, value: T) {
+        let idx = self.index(x, y, z);
+        self.cells[idx] = value;
+    }
+
+    /// Get as reference the values of all the voxels in linear index order.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+}
+
+/// Compute the 3D Euclidean distance transform of a binary voxel grid.
+///
+/// The exact squared distances are computed with the separable algorithm of Meijster et al.:
+/// a linear two-pass scan along x, followed by the lower envelope of parabolas along y and
+/// then along z.
+///
+/// # Arguments
+///
+/// * `grid` - The voxel grid.
+///
+/// # Returns
+///
+/// The Euclidean distance from the center of each voxel to the center of the nearest
+/// occupied voxel, in the units of the voxel size and in linear index order. All the
+/// distances are infinite if no voxel is occupied.
+///
+/// Example:
+///
+/// ```
+/// use kornia_3d::voxel::{distance_transform_3d, VoxelGrid};
+///
+/// let mut grid = VoxelGrid::new([3, 1, 1], 0.5);
+/// grid.set_occupied(0, 0, 0, true);
+/// assert_eq!(distance_transform_3d(&grid), vec![0.0, 0.5, 1.0]);
+/// ```
+pub fn distance_transform_3d(grid: &VoxelGrid) -> Vec<f32> {
+    let [nx, ny, nz] = grid.dims();
+    let num_voxels = grid.len();
+    if num_voxels == 0 {
+        return Vec::new();
+    }
+
+    // any distance larger than the grid diagonal means no occupied voxel
+    let infinity = (nx + ny + nz) as f64;
+    let mut dist = vec![0.0f64; num_voxels];
+
+    // phase 1: distance to the nearest occupied voxel along x
+    for z in 0..nz {
+        for y in 0..ny {
+            let row = grid.index(0, y, z);
+            let occupied = &grid.occupied()[row..row + nx];
+            let line = &mut dist[row..row + nx];
+
+            line[0] = if occupied[0] { 0.0 } else { infinity };
+            for x in 1..nx {
+                line[x] = if occupied[x] { 0.0 } else { line[x - 1] + 1.0 };
+            }
+            for x in (0..nx - 1).rev() {
+                if line[x + 1] < line[x] {
+                    line[x] = line[x + 1] + 1.0;
+                }
+            }
+            line.iter_mut().for_each(|d| *d *= *d);
+        }
+    }
+
+    // phase 2: lower envelope of parabolas along y and then along z
+    let mut buffer = Vec::new();
+    let mut output = Vec::new();
+    for z in 0..nz {
+        for x in 0..nx {
+            let indices = (0..ny).map(|y| grid.index(x, y, z));
+            squared_distance_1d(&mut dist, indices, &mut buffer, &mut output);
+        }
+    }
+    for y in 0..ny {
+        for x in 0..nx {
+            let indices = (0..nz).map(|z| grid.index(x, y, z));
+            squared_distance_1d(&mut dist, indices, &mut buffer, &mut output);
+        }
+    }
+
+    let voxel_size = grid.voxel_size();
+    dist.iter()
+        .map(|d| {
+            let d = d.sqrt();
+            if d >= infinity {
+                f32::INFINITY
+            } else {
+                (d * voxel_size) as f32
+            }
+        })
+        .collect()
+}
+
+/// Replace the squared distances along a line by their lower envelope of parabolas.
+fn squared_distance_1d(
+    dist: &mut [f64],
+    indices: impl Iterator<Item = usize> + Clone,
+    f: &mut Vec<f64>,
+    output: &mut Vec<f64>,
+) {
+    f.clear();
+    f.extend(indices.clone().map(|i| dist[i]));
+    let n = f.len();
+
+    // first position where the parabola rooted at u is below the one rooted at i < u
+    let sep = |i: usize, u: usize| -> f64 {
+        let (fi, fu) = (f[i], f[u]);
+        let (i, u) = (i as f64, u as f64);
+        ((u * u - i * i + fu - fi) / (2.0 * (u - i))).floor() + 1.0
+    };
+
+    // roots of the parabolas in the lower envelope and the start of their segments
+    let mut s = vec![0usize; n];
+    let mut t = vec![0.0f64; n];
+    let mut q = 0usize;
+
+    for u in 1..n {
+        let mut empty = false;
+        while parabola(s[q], t[q], f) > parabola(u, t[q], f) {
+            if q == 0 {
+                empty = true;
+                break;
+            }
+            q -= 1;
+        }
+        if empty {
+            s[0] = u;
+        } else {
+            let w = sep(s[q], u);
+            if w < n as f64 {
+                q += 1;
+                s[q] = u;
+                t[q] = w;
+            }
+        }
+    }
+
+    output.clear();
+    output.resize(n, 0.0);
+    for x in (0..n).rev() {
+        output[x] = parabola(s[q], x as f64, f);
+        if q > 0 && x as f64 == t[q] {
+            q -= 1;
+        }
+    }
+
+    for (i, value) in indices.zip(output.iter()) {
+        dist[i] = *value;
+    }
+}
+
+/// Evaluate the parabola rooted at `i` with height `f[i]` at position `x`.
+#[inline]
+fn parabola(i: usize, x: f64, f: &[f64]) -> f64 {
+    (x - i as f64).powi(2) + f[i]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_distance_transform_3d_neighbors() {
+        let voxel_size = 0.2;
+        let mut grid = VoxelGrid::new([5, 5, 5], voxel_size);
+        grid.set_occupied(2, 2, 2, true);
+
+        let dist = distance_transform_3d(&grid);
+        assert_eq!(dist[grid.index(2, 2, 2)], 0.0);
+        for (x, y, z) in [
+            (1, 2, 2),
+            (3, 2, 2),
+            (2, 1, 2),
+            (2, 3, 2),
+            (2, 2, 1),
+            (2, 2, 3),
+        ] {
+            assert_relative_eq!(dist[grid.index(x, y, z)], voxel_size as f32);
+        }
+        assert_relative_eq!(
+            dist[grid.index(0, 0, 0)],
+            (12.0f64.sqrt() * voxel_size) as f32,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn test_distance_transform_3d_brute_force() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let dims = [7, 5, 6];
+        let mut grid = VoxelGrid::new(dims, 1.0);
+        let mut occupied = Vec::new();
+        for _ in 0..6 {
+            let v = [
+                rng.random_range(0..dims[0]),
+                rng.random_range(0..dims[1]),
+                rng.random_range(0..dims[2]),
+            ];
+            grid.set_occupied(v[0], v[1], v[2], true);
+            occupied.push(v);
+        }
+
+        let dist = distance_transform_3d(&grid);
+        for z in 0..dims[2] {
+            for y in 0..dims[1] {
+                for x in 0..dims[0] {
+                    let expected = occupied
+                        .iter()
+                        .map(|v| {
+                            let d2 = (x as f64 - v[0] as f64).powi(2)
+                                + (y as f64 - v[1] as f64).powi(2)
+                                + (z as f64 - v[2] as f64).powi(2);
+                            d2.sqrt()
+                        })
+                        .fold(f64::INFINITY, f64::min);
+                    assert_relative_eq!(dist[grid.index(x, y, z)], expected as f32, epsilon = 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_distance_transform_3d_empty() {
+        let grid = VoxelGrid::new([2, 2, 2], 1.0);
+        assert!(distance_transform_3d(&grid).iter().all(|d| d.is_infinite()));
+    }
+}
@@ -0,0 +1,54 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use kornia_icp::{IcpParams, SparseIcp};
+
+// sample a wavy surface on a regular grid
+fn wavy_surface(num_points: usize) -> Vec<[f64; 3]> {
+    let side = (num_points as f64).sqrt() as usize;
+    (0..side * side)
+        .map(|i| {
+            let (u, v) = (
+                (i % side) as f64 / side as f64,
+                (i / side) as f64 / side as f64,
+            );
+            [u, v, 0.1 * (6.0 * u).sin() * (4.0 * v).cos()]
+        })
+        .collect()
+}
+
+fn bench_sparse_icp(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sparse_icp");
+
+    let params = IcpParams {
+        max_iterations: 30,
+        tolerance: 1e-9,
+        entropy_threshold: 0.0,
+        max_correspondence_distance: 0.1,
+    };
+
+    for num_points in [10000, 50000].iter() {
+        let dst = wavy_surface(*num_points);
+        let src = dst
+            .iter()
+            .map(|p| [p[0] - 0.01, p[1] + 0.005, p[2]])
+            .collect::<Vec<_>>();
+
+        // the full ICP, all the points being anchors, against 1% and 0.2% of anchors
+        for anchor_ratio in [1.0, 0.01, 0.002] {
+            let parameter_string = format!("{}/{}", num_points, anchor_ratio);
+            group.bench_with_input(
+                BenchmarkId::new("register", &parameter_string),
+                &(&src, &dst),
+                |b, i| {
+                    let (src, dst) = (i.0, i.1);
+                    b.iter(|| {
+                        black_box(SparseIcp::register(src, dst, anchor_ratio, &params).unwrap());
+                    });
+                },
+            );
+        }
+    }
+}
+
+criterion_group!(benches, bench_sparse_icp);
+criterion_main!(benches);
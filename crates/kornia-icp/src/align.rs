@@ -0,0 +1,1019 @@
+use kornia_3d::{
+    density::{estimate_density, DensityStats},
+    features::{compute_fpfh, estimate_normals},
+    filters::{deduplicate, DedupPolicy},
+    linalg::transform_points3d_vec,
+    pointcloud::PointCloud,
+    transforms::RigidTransform3,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    estimate_overlap_adaptive, icp_multiscale, ops::fit_transformation, suggest_icp_params,
+    IcpError, LevelReport, MultiScaleConfig, MultiScaleLevel, SampleStats, SampleValidator,
+    SuggestedParams,
+};
+
+/// Number of points sampled to estimate the density of the target cloud.
+const DENSITY_SAMPLE_SIZE: usize = 1000;
+
+/// Seed of the density estimation, fixed for reproducibility.
+const DENSITY_SEED: u64 = 0;
+
+/// Seed of the RANSAC of the coarse alignment, fixed for reproducibility.
+const RANSAC_SEED: u64 = 0;
+
+/// Maximum number of levels of the multi-scale ICP of the `Auto` preset.
+const AUTO_LEVELS: usize = 3;
+
+/// Minimum trimming fraction of the `Robust` preset, whatever the estimated overlap.
+const MIN_TRIM_FRACTION: f64 = 0.5;
+
+/// Maximum relative difference between corresponding edge lengths of a RANSAC sample.
+const EDGE_LENGTH_TOLERANCE: f64 = 0.1;
+
+/// Maximum difference in radians between corresponding normal angles of a RANSAC sample.
+const NORMAL_ANGLE_TOLERANCE: f64 = 0.35;
+
+/// Preset registration pipelines of [`align`].
+///
+/// The presets are expressed in terms of the parameters suggested by [`suggest_icp_params`]
+/// from the median nearest neighbor spacing `s` of the target cloud, with the voxel size
+/// `v = 2 * s`. The supported initial offsets are given for clouds sampled from the same
+/// scene and are indicative: they depend on the geometry of the scene.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationPreset {
+    /// A single ICP level on clouds downsampled with voxels of `2 * v`.
+    ///
+    /// Converges for initial offsets up to about `5 * s` and 2 degrees.
+    Fast,
+    /// Multi-scale ICP on clouds downsampled with voxels of `4 * v`, `2 * v` and `v`.
+    ///
+    /// Converges for initial offsets up to about `15 * s` and 5 degrees.
+    Balanced,
+    /// FPFH and RANSAC coarse alignment followed by the multi-scale ICP of `Balanced`,
+    /// trimmed to the overlap of the clouds estimated after the coarse alignment.
+    ///
+    /// Does not need an initial guess as long as the clouds share enough distinctive
+    /// geometry, at the cost of the normal and descriptor computations.
+    Robust,
+    /// A single ICP level at full resolution, to refine an already accurate alignment.
+    ///
+    /// Converges for initial offsets up to about `s` and 0.5 degrees.
+    FineOnly,
+    /// Multi-scale ICP with up to three levels chosen from the density and the extents of
+    /// the target cloud by [`MultiScaleConfig::auto`].
+    ///
+    /// Converges for initial offsets up to about `15 * s` and 5 degrees.
+    Auto,
+}
+
+impl RegistrationPreset {
+    /// Get the multi-scale ICP configuration of the preset.
+    ///
+    /// The levels of `Auto` depend on the whole density statistics, see
+    /// [`MultiScaleConfig::auto`]: from the suggested parameters alone, they are the levels
+    /// of `Balanced`. The configuration does not trim the correspondences: the trimming
+    /// fraction of `Robust` is set by [`align`] from the overlap of the coarse alignment.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters suggested from the density of the target cloud.
+    ///
+    /// # Returns
+    ///
+    /// The levels of the multi-scale ICP, from the coarsest to the finest.
+    pub fn multiscale_config(&self, params: &SuggestedParams) -> MultiScaleConfig {
+        let v = params.voxel_size;
+        let level = |voxel_size: f64, max_iterations: usize| MultiScaleLevel {
+            voxel_size: Some(voxel_size),
+            max_correspondence_distance: 2.0 * voxel_size,
+            max_iterations,
+        };
+        let levels = match self {
+            Self::Fast => vec![level(2.0 * v, 30)],
+            Self::Balanced | Self::Robust | Self::Auto => {
+                vec![level(4.0 * v, 50), level(2.0 * v, 50), level(v, 50)]
+            }
+            Self::FineOnly => vec![MultiScaleLevel {
+                voxel_size: None,
+                max_correspondence_distance: params.max_correspondence_distance,
+                max_iterations: 50,
+            }],
+        };
+        MultiScaleConfig {
+            levels,
+            tolerance: 1e-4 * params.max_correspondence_distance,
+            entropy_threshold: 0.0,
+            trim_fraction: 1.0,
+        }
+    }
+
+    /// Get the coarse alignment parameters of the preset, if it has a coarse alignment.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters suggested from the density of the target cloud.
+    pub fn coarse_alignment_params(
+        &self,
+        params: &SuggestedParams,
+    ) -> Option<CoarseAlignmentParams> {
+        match self {
+            Self::Robust => {
+                let voxel_size = 4.0 * params.voxel_size;
+                Some(CoarseAlignmentParams {
+                    voxel_size,
+                    normal_radius: 2.0 * voxel_size,
+                    feature_radius: 5.0 * voxel_size,
+                    ransac_iterations: 20_000,
+                    inlier_threshold: 1.5 * voxel_size,
+                    seed: RANSAC_SEED,
+                    validator: SampleValidator {
+                        edge_tolerance: EDGE_LENGTH_TOLERANCE,
+                        normal_tolerance: NORMAL_ANGLE_TOLERANCE,
+                    },
+                })
+            }
+            Self::Fast | Self::Balanced | Self::FineOnly | Self::Auto => None,
+        }
+    }
+}
+
+/// Parameters of the feature-based coarse alignment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoarseAlignmentParams {
+    /// Voxel size used to downsample both clouds.
+    pub voxel_size: f64,
+    /// Radius of the neighborhood used to estimate the normals.
+    pub normal_radius: f64,
+    /// Radius of the neighborhood of the FPFH descriptors.
+    pub feature_radius: f64,
+    /// Number of RANSAC iterations.
+    pub ransac_iterations: usize,
+    /// Maximum distance between an aligned source point and its match to be an inlier.
+    pub inlier_threshold: f64,
+    /// Seed of the random generator of RANSAC.
+    pub seed: u64,
+    /// The pre-checks of the RANSAC samples, with the normals of the downsampled clouds.
+    pub validator: SampleValidator,
+}
+
+/// Summary of the feature-based coarse alignment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoarseAlignmentReport {
+    /// The parameters of the coarse alignment.
+    pub params: CoarseAlignmentParams,
+    /// The number of mutual nearest neighbors in the descriptor space.
+    pub num_correspondences: usize,
+    /// The number of correspondences consistent with the estimated transformation.
+    pub num_inliers: usize,
+    /// The estimated transformation from the source to the target frame.
+    pub transform: RigidTransform3,
+    /// The number of RANSAC samples rejected by each pre-check and evaluated fully.
+    pub samples: SampleStats,
+}
+
+/// The choices made at each stage of [`align`], to reproduce the registration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistrationReport {
+    /// The preset of the pipeline.
+    pub preset: RegistrationPreset,
+    /// The density of the target cloud.
+    pub density: DensityStats,
+    /// The parameters suggested from the density.
+    pub suggested: SuggestedParams,
+    /// The coarse alignment, if the preset has one.
+    pub coarse: Option<CoarseAlignmentReport>,
+    /// The configuration of the multi-scale ICP.
+    pub multiscale: MultiScaleConfig,
+    /// The summary of each level of the multi-scale ICP.
+    pub levels: Vec<LevelReport>,
+}
+
+/// Result of [`align`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistrationResult {
+    /// The estimated transformation from the source to the target frame.
+    pub transform: RigidTransform3,
+    /// The RMSE of the correspondences of the finest level.
+    pub rmse: f64,
+    /// The fraction of source points with a correspondence at the finest level.
+    pub fitness: f64,
+    /// The overlap of the source with the target at the final transformation, see
+    /// [`crate::estimate_overlap`].
+    pub overlap: f64,
+    /// The total number of ICP iterations.
+    pub num_iterations: usize,
+    /// The choices made at each stage of the pipeline.
+    pub report: RegistrationReport,
+}
+
+/// Register a source point cloud to a target point cloud with a preset pipeline.
+///
+/// The pipeline estimates the density of the target cloud to suggest the registration
+/// parameters, optionally computes a coarse alignment from FPFH descriptor matches with
+/// RANSAC, and refines the transformation with multi-scale ICP. The normals used by the
+/// descriptors are oriented towards the origin of each cloud, assumed to be the sensor center.
+/// All the random choices are seeded, so that the registration is deterministic.
+///
+/// # Arguments
+///
+/// * `source` - Source point cloud.
+/// * `target` - Target point cloud.
+/// * `preset` - The registration pipeline.
+///
+/// # Returns
+///
+/// The transformation from the source to the target frame and the report of the pipeline.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{align, RegistrationPreset};
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..2500)
+///     .map(|i| {
+///         let (u, v) = ((i % 50) as f64 * 0.02, (i / 50) as f64 * 0.02);
+///         [u, v, 0.2 * (3.0 * u).sin() * (2.0 * v).cos()]
+///     })
+///     .collect::<Vec<_>>();
+/// let moved = points.iter().map(|p| [p[0] + 0.005, p[1], p[2]]).collect();
+///
+/// let source = PointCloud::new(points, None, None);
+/// let target = PointCloud::new(moved, None, None);
+/// let result = align(&source, &target, RegistrationPreset::FineOnly).unwrap();
+/// assert!((result.transform.translation[0] - 0.005).abs() < 1e-4);
+/// assert_eq!(result.report.levels.len(), 1);
+/// ```
+pub fn align(
+    source: &PointCloud,
+    target: &PointCloud,
+    preset: RegistrationPreset,
+) -> Result<RegistrationResult, IcpError> {
+    if source.is_empty() || target.is_empty() {
+        return Err(IcpError::EmptyCloud);
+    }
+
+    let density = estimate_density(target, DENSITY_SAMPLE_SIZE, DENSITY_SEED)?;
+    let suggested = suggest_icp_params(&density);
+
+    let coarse = preset
+        .coarse_alignment_params(&suggested)
+        .map(|params| coarse_align(source, target, &params))
+        .transpose()?;
+    let initial = coarse
+        .as_ref()
+        .map_or(RigidTransform3::identity(), |c| c.transform);
+
+    let mut multiscale = match preset {
+        RegistrationPreset::Auto => MultiScaleConfig::auto(&density, AUTO_LEVELS),
+        _ => preset.multiscale_config(&suggested),
+    };
+    if coarse.is_some() {
+        // trim the correspondences outside the overlap of the coarsely aligned clouds
+        let overlap = estimate_overlap_adaptive(source, target, &initial)?;
+        multiscale.trim_fraction = overlap.clamp(MIN_TRIM_FRACTION, 1.0);
+    }
+    let (icp, levels) = icp_multiscale(
+        source,
+        target,
+        initial.rotation,
+        initial.translation,
+        &multiscale,
+    )?;
+
+    Ok(RegistrationResult {
+        transform: RigidTransform3::new(icp.rotation, icp.translation),
+        rmse: icp.rmse,
+        fitness: levels.last().map_or(0.0, |level| level.fitness),
+        overlap: icp.overlap.unwrap_or(0.0),
+        num_iterations: icp.num_iterations,
+        report: RegistrationReport {
+            preset,
+            density,
+            suggested,
+            coarse,
+            multiscale,
+            levels,
+        },
+    })
+}
+
+/// Estimate the rigid transformation between two point clouds from their FPFH descriptors.
+///
+/// The putative correspondences are the pairs of points whose descriptors are the nearest
+/// neighbor of each other. RANSAC draws three correspondences at a time, rejects the samples
+/// whose triangles are not congruent, fits the transformation of the sample and counts the
+/// correspondences within `inlier_thresh` of it. The best hypothesis is refined on its
+/// inliers. This is the coarse alignment of [`align`] on given points and descriptors.
+///
+/// This is the usual initialization of a fine registration with ICP.
+///
+/// # Arguments
+///
+/// * `src` - The source points.
+/// * `src_descs` - The FPFH descriptor of each source point.
+/// * `dst` - The destination points.
+/// * `dst_descs` - The FPFH descriptor of each destination point.
+/// * `ransac_iter` - The number of RANSAC iterations.
+/// * `inlier_thresh` - The maximum distance between an aligned source point and its
+///   corresponding destination point to be an inlier.
+///
+/// # Returns
+///
+/// The rotation and translation from the source to the destination frame, or `None` if no
+/// hypothesis has at least three inliers.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::feature_based_initial_alignment;
+///
+/// let src = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 2.0, 0.0], [0.0, 0.0, 3.0]];
+/// let dst = src.iter().map(|p| [p[0] + 1.0, p[1], p[2]]).collect::<Vec<_>>();
+/// // distinct descriptors for each point
+/// let descs = (0..4).map(|i| [i as f32; 33]).collect::<Vec<_>>();
+/// let (_, translation) =
+///     feature_based_initial_alignment(&src, &descs, &dst, &descs, 100, 0.01).unwrap();
+/// assert!((translation[0] - 1.0).abs() < 1e-9);
+/// ```
+pub fn feature_based_initial_alignment(
+    src: &[[f64; 3]],
+    src_descs: &[[f32; 33]],
+    dst: &[[f64; 3]],
+    dst_descs: &[[f32; 33]],
+    ransac_iter: usize,
+    inlier_thresh: f64,
+) -> Option<([[f64; 3]; 3], [f64; 3])> {
+    let (src_match, dst_match): (Vec<_>, Vec<_>) = mutual_nearest_descriptors(src_descs, dst_descs)
+        .into_iter()
+        .map(|(i, j)| (src[i], dst[j]))
+        .unzip();
+    let params = Ransac {
+        iterations: ransac_iter,
+        inlier_threshold: inlier_thresh,
+        seed: RANSAC_SEED,
+        validator: SampleValidator {
+            edge_tolerance: EDGE_LENGTH_TOLERANCE,
+            normal_tolerance: f64::INFINITY,
+        },
+    };
+    let (transform, _) = align_matches(&src_match, &dst_match, None, None, &params).ok()?;
+    Some((transform.rotation, transform.translation))
+}
+
+/// Estimate a coarse alignment from the mutual nearest neighbors in the FPFH space with RANSAC.
+fn coarse_align(
+    source: &PointCloud,
+    target: &PointCloud,
+    params: &CoarseAlignmentParams,
+) -> Result<CoarseAlignmentReport, IcpError> {
+    let describe = |cloud: &PointCloud| {
+        let downsampled = deduplicate(cloud, params.voxel_size, DedupPolicy::Centroid).0;
+        let normals = estimate_normals(&downsampled, params.normal_radius, &[0.0; 3]);
+        let descriptors = compute_fpfh(&downsampled, &normals, params.feature_radius);
+        (downsampled, normals, descriptors)
+    };
+    let (source_down, source_normals, source_descriptors) = describe(source);
+    let (target_down, target_normals, target_descriptors) = describe(target);
+
+    let matches = mutual_nearest_descriptors(&source_descriptors, &target_descriptors);
+    let source_match = matches
+        .iter()
+        .map(|&(i, _)| source_down.points()[i])
+        .collect::<Vec<_>>();
+    let target_match = matches
+        .iter()
+        .map(|&(_, j)| target_down.points()[j])
+        .collect::<Vec<_>>();
+    let source_match_normals = matches
+        .iter()
+        .map(|&(i, _)| source_normals[i])
+        .collect::<Vec<_>>();
+    let target_match_normals = matches
+        .iter()
+        .map(|&(_, j)| target_normals[j])
+        .collect::<Vec<_>>();
+    let num_correspondences = source_match.len();
+    let (transform, samples) = align_matches(
+        &source_match,
+        &target_match,
+        Some(&source_match_normals),
+        Some(&target_match_normals),
+        &Ransac::from(params),
+    )?;
+    let num_inliers = count_inliers(
+        &source_match,
+        &target_match,
+        &transform.rotation,
+        &transform.translation,
+        params.inlier_threshold,
+    );
+
+    log::debug!(
+        "Coarse alignment: {} inliers out of {} correspondences, {} of {} samples evaluated",
+        num_inliers,
+        num_correspondences,
+        samples.evaluated,
+        samples.num_samples()
+    );
+
+    Ok(CoarseAlignmentReport {
+        params: *params,
+        num_correspondences,
+        num_inliers,
+        transform,
+        samples,
+    })
+}
+
+/// The RANSAC of a coarse alignment.
+#[derive(Debug, Clone, Copy)]
+struct Ransac {
+    /// Number of samples.
+    iterations: usize,
+    /// Maximum distance between an aligned source point and its match to be an inlier.
+    inlier_threshold: f64,
+    /// Seed of the random generator.
+    seed: u64,
+    /// The pre-checks of the samples.
+    validator: SampleValidator,
+}
+
+impl From<&CoarseAlignmentParams> for Ransac {
+    fn from(params: &CoarseAlignmentParams) -> Self {
+        Self {
+            iterations: params.ransac_iterations,
+            inlier_threshold: params.inlier_threshold,
+            seed: params.seed,
+            validator: params.validator,
+        }
+    }
+}
+
+/// Estimate the transformation of putative correspondences with RANSAC, refined on the
+/// inliers of the best hypothesis.
+///
+/// # Returns
+///
+/// The transformation and the outcome of the samples, or an error if there are fewer than
+/// three correspondences or no hypothesis has at least three inliers.
+fn align_matches(
+    source_match: &[[f64; 3]],
+    target_match: &[[f64; 3]],
+    source_normals: Option<&[[f64; 3]]>,
+    target_normals: Option<&[[f64; 3]]>,
+    params: &Ransac,
+) -> Result<(RigidTransform3, SampleStats), IcpError> {
+    if source_match.len() < 3 {
+        return Err(IcpError::CoarseAlignmentFailed);
+    }
+
+    let (best, samples) = ransac(
+        source_match,
+        target_match,
+        source_normals,
+        target_normals,
+        params,
+    );
+    let Some((_, hypothesis)) = best.filter(|(n, _)| *n >= 3) else {
+        return Err(IcpError::CoarseAlignmentFailed);
+    };
+
+    // refine the transformation on the inliers of the best hypothesis
+    let threshold_sq = params.inlier_threshold * params.inlier_threshold;
+    let aligned =
+        transform_points3d_vec(source_match, &hypothesis.rotation, &hypothesis.translation);
+    let (inlier_source, inlier_target): (Vec<_>, Vec<_>) = aligned
+        .iter()
+        .zip(source_match.iter().zip(target_match.iter()))
+        .filter(|(p, (_, q))| {
+            (p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2) <= threshold_sq
+        })
+        .map(|(_, (s, t))| (*s, *t))
+        .unzip();
+    let mut rotation = [[0.0; 3]; 3];
+    let mut translation = [0.0; 3];
+    fit_transformation(
+        &inlier_source,
+        &inlier_target,
+        &mut rotation,
+        &mut translation,
+    );
+    Ok((RigidTransform3::new(rotation, translation), samples))
+}
+
+/// Find the transformation supported by the most correspondences with RANSAC.
+///
+/// The samples of three correspondences are pre-checked by the validator of the parameters
+/// before a transformation is fitted to them and scored.
+///
+/// # Returns
+///
+/// The number of inliers and the best transformation, if a sample was evaluated, and the
+/// outcome of the samples.
+fn ransac(
+    source_match: &[[f64; 3]],
+    target_match: &[[f64; 3]],
+    source_normals: Option<&[[f64; 3]]>,
+    target_normals: Option<&[[f64; 3]]>,
+    params: &Ransac,
+) -> (Option<(usize, RigidTransform3)>, SampleStats) {
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let mut best: Option<(usize, RigidTransform3)> = None;
+    let mut stats = SampleStats::default();
+    for _ in 0..params.iterations {
+        let sample = rand::seq::index::sample(&mut rng, source_match.len(), 3).into_vec();
+        let sample_source = sample.iter().map(|&i| source_match[i]).collect::<Vec<_>>();
+        let sample_target = sample.iter().map(|&i| target_match[i]).collect::<Vec<_>>();
+
+        // reject the samples that cannot be congruent
+        let sample_normals =
+            |normals: &[[f64; 3]]| sample.iter().map(|&i| normals[i]).collect::<Vec<_>>();
+        let sample_source_normals = source_normals.map(sample_normals);
+        let sample_target_normals = target_normals.map(sample_normals);
+        let outcome = params.validator.validate(
+            &sample_source,
+            &sample_target,
+            sample_source_normals.as_deref(),
+            sample_target_normals.as_deref(),
+        );
+        stats.record(outcome);
+        if outcome.is_err() {
+            continue;
+        }
+
+        let mut rotation = [[0.0; 3]; 3];
+        let mut translation = [0.0; 3];
+        fit_transformation(
+            &sample_source,
+            &sample_target,
+            &mut rotation,
+            &mut translation,
+        );
+
+        let num_inliers = count_inliers(
+            source_match,
+            target_match,
+            &rotation,
+            &translation,
+            params.inlier_threshold,
+        );
+        if best.map_or(true, |(n, _)| num_inliers > n) {
+            best = Some((num_inliers, RigidTransform3::new(rotation, translation)));
+        }
+    }
+    (best, stats)
+}
+
+/// Count the correspondences within the inlier threshold once transformed.
+fn count_inliers(
+    source_match: &[[f64; 3]],
+    target_match: &[[f64; 3]],
+    rotation: &[[f64; 3]; 3],
+    translation: &[f64; 3],
+    inlier_threshold: f64,
+) -> usize {
+    let threshold_sq = inlier_threshold * inlier_threshold;
+    transform_points3d_vec(source_match, rotation, translation)
+        .iter()
+        .zip(target_match.iter())
+        .filter(|(p, q)| {
+            (p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2) <= threshold_sq
+        })
+        .count()
+}
+
+/// Find the pairs of descriptors that are the nearest neighbor of each other.
+fn mutual_nearest_descriptors(source: &[[f32; 33]], target: &[[f32; 33]]) -> Vec<(usize, usize)> {
+    let distance = |a: &[f32; 33], b: &[f32; 33]| {
+        a.iter()
+            .zip(b.iter())
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f32>()
+    };
+    let nearest = |query: &[f32; 33], candidates: &[[f32; 33]]| {
+        candidates
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i, distance(query, c)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    };
+
+    let target_to_source = target
+        .iter()
+        .map(|t| nearest(t, source))
+        .collect::<Vec<_>>();
+    source
+        .iter()
+        .enumerate()
+        .filter_map(|(i, s)| {
+            let j = nearest(s, target)?;
+            (target_to_source[j] == Some(i)).then_some((i, j))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use kornia_3d::{
+        pose::generate_ground_truth_correspondences, transforms::axis_angle_to_rotation_matrix,
+    };
+    use rand::Rng;
+
+    /// Sample a room corner with a box and a ball, seen from the origin.
+    fn synthetic_scene(num_points: usize, seed: u64) -> Vec<[f64; 3]> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut points = Vec::with_capacity(num_points);
+        while points.len() < num_points {
+            let (u, v): (f64, f64) = (rng.random(), rng.random());
+            let point = match rng.random_range(0..16) {
+                // floor
+                0..=5 => [-1.5 + 3.5 * u, -1.5 + 3.5 * v, -1.0],
+                // walls
+                6..=8 => [2.0, -1.5 + 3.5 * u, -1.0 + 2.0 * v],
+                9..=11 => [-1.5 + 3.5 * u, 2.0, -1.0 + 2.0 * v],
+                // box on the floor
+                12..=13 => {
+                    let (x, y, z) = (0.3 + 0.6 * u, -0.8 + 0.6 * v, -1.0 + 0.6 * u);
+                    match rng.random_range(0..5) {
+                        0 => [x, y, -0.4],
+                        1 => [0.3, y, z],
+                        2 => [0.9, y, z],
+                        3 => [x, -0.8, -1.0 + 0.6 * v],
+                        _ => [x, -0.2, -1.0 + 0.6 * v],
+                    }
+                }
+                // ball on the floor
+                _ => {
+                    let z = 2.0 * u - 1.0;
+                    let r = (1.0 - z * z).sqrt();
+                    let theta = std::f64::consts::TAU * v;
+                    let p = [
+                        -0.6 + 0.4 * r * theta.cos(),
+                        0.8 + 0.4 * r * theta.sin(),
+                        -0.6 + 0.4 * z,
+                    ];
+                    if p[2] < -1.0 {
+                        continue;
+                    }
+                    p
+                }
+            };
+            let noise: [f64; 3] = [
+                rng.random_range(-1e-3..1e-3),
+                rng.random_range(-1e-3..1e-3),
+                rng.random_range(-1e-3..1e-3),
+            ];
+            points.push([
+                point[0] + noise[0],
+                point[1] + noise[1],
+                point[2] + noise[2],
+            ]);
+        }
+        points
+    }
+
+    /// Two independent samplings of the scene, the source being moved by the inverse of `pose`.
+    fn scene_pair(pose: &RigidTransform3) -> (PointCloud, PointCloud) {
+        let inverse = pose.inverse();
+        let source = synthetic_scene(20_000, 1)
+            .iter()
+            .map(|p| inverse.apply(p))
+            .collect();
+        let target = synthetic_scene(20_000, 2);
+        (
+            PointCloud::new(source, None, None),
+            PointCloud::new(target, None, None),
+        )
+    }
+
+    fn pose(axis: [f64; 3], angle_deg: f64, direction: [f64; 3], offset: f64) -> RigidTransform3 {
+        let norm = direction.iter().map(|v| v * v).sum::<f64>().sqrt();
+        RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&axis, angle_deg.to_radians()).unwrap(),
+            direction.map(|v| offset * v / norm),
+        )
+    }
+
+    /// Check the accuracy of a preset from initial offsets at the limit of its documented range.
+    fn check_preset(
+        preset: RegistrationPreset,
+        (max_offset_spacings, max_angle_deg): (f64, f64),
+        (max_error_spacings, max_error_deg): (f64, f64),
+    ) {
+        let poses = [
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0]),
+            ([1.0, 1.0, 0.0], [0.0, 1.0, 1.0]),
+            ([0.3, -0.5, 1.0], [-1.0, 0.5, -0.2]),
+        ];
+        let (source, target) = scene_pair(&RigidTransform3::identity());
+        let spacing = estimate_density(&target, DENSITY_SAMPLE_SIZE, DENSITY_SEED)
+            .unwrap()
+            .median_nn_dist;
+        for (axis, direction) in poses {
+            let gt = pose(
+                axis,
+                max_angle_deg,
+                direction,
+                max_offset_spacings * spacing,
+            );
+            let inverse = gt.inverse();
+            let moved = source.points().iter().map(|p| inverse.apply(p)).collect();
+            let moved = PointCloud::new(moved, None, None);
+
+            let result = align(&moved, &target, preset).unwrap();
+            let error = result.transform.compose(&gt.inverse());
+            let translation_error = error.translation.iter().map(|v| v * v).sum::<f64>().sqrt();
+            assert!(
+                error.rotation_angle().to_degrees() < max_error_deg,
+                "{preset:?}: rotation error {} deg",
+                error.rotation_angle().to_degrees()
+            );
+            assert!(
+                translation_error < max_error_spacings * spacing,
+                "{preset:?}: translation error {translation_error} for a spacing {spacing}"
+            );
+            assert!(
+                result.fitness > 0.9,
+                "{preset:?}: fitness {}",
+                result.fitness
+            );
+        }
+    }
+
+    #[test]
+    fn test_align_fast() {
+        check_preset(RegistrationPreset::Fast, (5.0, 2.0), (1.0, 1.0));
+    }
+
+    #[test]
+    fn test_align_balanced() {
+        check_preset(RegistrationPreset::Balanced, (15.0, 5.0), (0.3, 0.2));
+    }
+
+    #[test]
+    fn test_align_auto() {
+        // the same accuracy as the hand-tuned pyramid of `Balanced`
+        check_preset(RegistrationPreset::Auto, (15.0, 5.0), (0.3, 0.2));
+
+        let (source, target) = scene_pair(&RigidTransform3::identity());
+        let result = align(&source, &target, RegistrationPreset::Auto).unwrap();
+        let report = &result.report;
+        assert_eq!(
+            report.multiscale,
+            MultiScaleConfig::auto(&report.density, AUTO_LEVELS)
+        );
+        assert_eq!(report.levels.len(), report.multiscale.levels.len());
+        assert!(report.levels.len() > 1);
+        for (level, config) in report.levels.iter().zip(report.multiscale.levels.iter()) {
+            assert_eq!(level.level, *config);
+        }
+    }
+
+    #[test]
+    fn test_align_fine_only() {
+        check_preset(RegistrationPreset::FineOnly, (1.0, 0.5), (0.3, 0.2));
+    }
+
+    #[test]
+    fn test_align_robust() {
+        let poses = [
+            pose([0.2, -0.4, 1.0], 120.0, [1.0, 2.0, 0.5], 1.5),
+            pose([1.0, 0.0, 0.0], -90.0, [0.0, 0.0, 1.0], 0.5),
+            pose([1.0, 1.0, 1.0], 45.0, [-1.0, 0.0, 0.0], 3.0),
+        ];
+        for gt in poses {
+            let (source, target) = scene_pair(&gt);
+
+            let result = align(&source, &target, RegistrationPreset::Robust).unwrap();
+            let error = result.transform.compose(&gt.inverse());
+            assert!(error.rotation_angle().to_degrees() < 0.2);
+            assert!(error.translation.iter().all(|v| v.abs() < 0.01));
+
+            let coarse = result.report.coarse.as_ref().unwrap();
+            assert!(coarse.num_inliers >= 3);
+            let coarse_error = coarse.transform.compose(&gt.inverse());
+            assert!(coarse_error.rotation_angle().to_degrees() < 10.0);
+        }
+    }
+
+    #[test]
+    fn test_align_robust_partial_overlap() {
+        let gt = pose([0.2, -0.4, 1.0], 120.0, [1.0, 2.0, 0.5], 1.5);
+        let (source, target) = scene_pair(&gt);
+        // the target only sees the part of the scene with x < 1.0
+        let cropped = target
+            .points()
+            .iter()
+            .filter(|p| p[0] < 1.0)
+            .copied()
+            .collect::<Vec<_>>();
+        let target = PointCloud::new(cropped, None, None);
+        let expected_overlap = source
+            .points()
+            .iter()
+            .filter(|p| gt.apply(p)[0] < 1.0)
+            .count() as f64
+            / source.len() as f64;
+
+        let result = align(&source, &target, RegistrationPreset::Robust).unwrap();
+        let error = result.transform.compose(&gt.inverse());
+        // the crop makes the registration less accurate than with the full scene
+        assert!(error.rotation_angle().to_degrees() < 0.5);
+        assert!(error.translation.iter().all(|v| v.abs() < 0.02));
+
+        // the correspondences are trimmed to the overlap, which is reported
+        let trim_fraction = result.report.multiscale.trim_fraction;
+        assert!(trim_fraction < 1.0);
+        assert!((trim_fraction - expected_overlap).abs() < 0.1);
+        assert!((result.overlap - expected_overlap).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_align_matches_manual_pipeline() -> Result<(), Box<dyn std::error::Error>> {
+        let gt = pose([1.0, 0.0, 1.0], 3.0, [0.0, 1.0, -1.0], 0.1);
+        let (source, target) = scene_pair(&gt);
+        let result = align(&source, &target, RegistrationPreset::Balanced)?;
+
+        // compose the same pipeline by hand
+        let density = estimate_density(&target, DENSITY_SAMPLE_SIZE, DENSITY_SEED)?;
+        let suggested = suggest_icp_params(&density);
+        let config = RegistrationPreset::Balanced.multiscale_config(&suggested);
+        let identity = RigidTransform3::identity();
+        let (icp, levels) = icp_multiscale(
+            &source,
+            &target,
+            identity.rotation,
+            identity.translation,
+            &config,
+        )?;
+
+        let report = &result.report;
+        assert_eq!(report.preset, RegistrationPreset::Balanced);
+        assert_eq!(report.density, density);
+        assert_eq!(report.suggested, suggested);
+        assert!(report.coarse.is_none());
+        assert_eq!(report.multiscale, config);
+        assert_eq!(report.levels, levels);
+        assert_eq!(result.num_iterations, icp.num_iterations);
+        assert_relative_eq!(result.rmse, icp.rmse);
+        for i in 0..3 {
+            assert_relative_eq!(result.transform.translation[i], icp.translation[i]);
+            for j in 0..3 {
+                assert_relative_eq!(result.transform.rotation[i][j], icp.rotation[i][j]);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_ransac_sample_validator_outliers() -> Result<(), Box<dyn std::error::Error>> {
+        // 30 correspondences of a rigid motion among 300, with their normals
+        let gt = pose([0.3, 1.0, -0.2], 40.0, [1.0, -1.0, 0.5], 2.0);
+        let rotation = RigidTransform3::new(gt.rotation, [0.0; 3]);
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut random_unit = || {
+            let v: [f64; 3] = [0; 3].map(|_| rng.random_range(-1.0..1.0));
+            let norm = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+            v.map(|x| x / norm)
+        };
+        let source_normals = (0..300).map(|_| random_unit()).collect::<Vec<_>>();
+        let mut target_normals = (0..300).map(|_| random_unit()).collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(2);
+        let source = (0..300)
+            .map(|_| [0; 3].map(|_| rng.random_range(0.0..10.0)))
+            .collect::<Vec<_>>();
+        let mut target = (0..300)
+            .map(|_| [0; 3].map(|_| rng.random_range(0.0..10.0)))
+            .collect::<Vec<_>>();
+        for i in 0..30 {
+            target[i] = gt.apply(&source[i]);
+            target_normals[i] = rotation.apply(&source_normals[i]);
+        }
+
+        let params = |seed, validator| CoarseAlignmentParams {
+            voxel_size: 0.1,
+            normal_radius: 0.2,
+            feature_radius: 0.5,
+            ransac_iterations: 5000,
+            inlier_threshold: 0.05,
+            seed,
+            validator,
+        };
+        let validator = SampleValidator {
+            edge_tolerance: EDGE_LENGTH_TOLERANCE,
+            normal_tolerance: NORMAL_ANGLE_TOLERANCE,
+        };
+        let normals = (
+            Some(source_normals.as_slice()),
+            Some(target_normals.as_slice()),
+        );
+
+        let (mut successes, mut validated_successes, mut evaluated) = (0, 0, 0);
+        for seed in 0..10 {
+            let (best, stats) = ransac(
+                &source,
+                &target,
+                normals.0,
+                normals.1,
+                &Ransac::from(&params(seed, SampleValidator::disabled())),
+            );
+            // without pre-checks, every sample is evaluated
+            assert_eq!(stats.evaluated, 5000);
+            assert_eq!(stats.num_samples(), 5000);
+            successes += usize::from(best.is_some_and(|(n, _)| n == 30));
+
+            let (validated, stats) = ransac(
+                &source,
+                &target,
+                normals.0,
+                normals.1,
+                &Ransac::from(&params(seed, validator)),
+            );
+            assert_eq!(stats.num_samples(), 5000);
+            assert!(stats.rejected_edge_length > 0 && stats.rejected_normals > 0);
+            evaluated += stats.evaluated;
+            if let Some((_, transform)) = validated.filter(|(n, _)| *n == 30) {
+                validated_successes += 1;
+                let error = transform.compose(&gt.inverse());
+                assert!(error.rotation_angle() < 1e-6);
+            }
+        }
+        assert!(successes >= 8);
+        assert_eq!(validated_successes, successes);
+        // a small fraction of the samples is evaluated fully
+        assert!(evaluated < 10 * 5000 / 20);
+        Ok(())
+    }
+
+    #[test]
+    fn test_feature_based_initial_alignment() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(7);
+        let num_points = 200;
+        let src = (0..num_points)
+            .map(|_| [0, 1, 2].map(|_| rng.random_range(-5.0..5.0)))
+            .collect::<Vec<_>>();
+        let descs = (0..num_points)
+            .map(|_| std::array::from_fn(|_| rng.random_range(0.0..100.0f32)))
+            .collect::<Vec<_>>();
+
+        let rotation = axis_angle_to_rotation_matrix(&[0.2, 1.0, -0.4], 2.0)?;
+        let translation = [3.0, -1.0, 0.5];
+        // 30% of the matched points are elsewhere in the destination cloud
+        let (src, dst) =
+            generate_ground_truth_correspondences(&src, &rotation, &translation, 0.0, 0.3, 8);
+        // the descriptors of the destination points are perturbed
+        let dst_descs = descs
+            .iter()
+            .map(|d| d.map(|v| v + rng.random_range(-1.0..1.0f32)))
+            .collect::<Vec<_>>();
+
+        let (r, t) = feature_based_initial_alignment(&src, &descs, &dst, &dst_descs, 200, 0.05)
+            .ok_or("no alignment")?;
+        for i in 0..3 {
+            assert_relative_eq!(t[i], translation[i], epsilon = 1e-9);
+            for j in 0..3 {
+                assert_relative_eq!(r[i][j], rotation[i][j], epsilon = 1e-9);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_feature_based_initial_alignment_failure() {
+        let src = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]];
+        let descs = vec![[0.0; 33], [1.0; 33]];
+        assert!(feature_based_initial_alignment(&src, &descs, &src, &descs, 10, 0.1).is_none());
+
+        // the matches are not consistent with any rigid transformation
+        let src = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ];
+        let dst = vec![
+            [0.0, 0.0, 0.0],
+            [5.0, 0.0, 0.0],
+            [0.0, 2.0, 0.0],
+            [0.0, 0.0, 9.0],
+        ];
+        let descs = (0..4).map(|i| [i as f32; 33]).collect::<Vec<_>>();
+        assert!(feature_based_initial_alignment(&src, &descs, &dst, &descs, 100, 0.1).is_none());
+    }
+
+    #[test]
+    fn test_align_empty_cloud() {
+        let cloud = PointCloud::new(vec![[0.0; 3]; 10], None, None);
+        let empty = PointCloud::new(vec![], None, None);
+        assert!(matches!(
+            align(&empty, &cloud, RegistrationPreset::Fast),
+            Err(IcpError::EmptyCloud)
+        ));
+    }
+}
@@ -0,0 +1,414 @@
+use kornia_3d::transforms::RigidTransform3;
+
+use crate::{ops::fit_rigid_transform_weighted, validate_icp_result, IcpError};
+
+/// Weight of the uniform distribution modeling the outliers of the target in the mixture.
+const OUTLIER_WEIGHT: f64 = 0.1;
+
+/// Lower bound of the variance of the mixture, so that an exact alignment stays defined.
+const MIN_VARIANCE: f64 = 1e-12;
+
+/// Maximum number of conjugate gradient iterations of each solve of the drift.
+const CG_MAX_ITERATIONS: usize = 200;
+
+/// Relative residual at which the conjugate gradient stops.
+const CG_TOLERANCE: f64 = 1e-10;
+
+/// Rigid registration of point sets with a coherent drift of the Gaussian mixture.
+///
+/// Implements the rigid Coherent Point Drift (CPD) of Myronenko and Song (2010), in which the
+/// target points are samples of a Gaussian mixture centered on the transformed source points
+/// and of a uniform distribution of outliers, extended by a smooth drift of the centroids.
+/// The centroid of the source point `y_m` is `R * y_m + t + v_m`, where the drift `v = G * W`
+/// follows the Gaussian kernel `G_ij = exp(-|y_i - y_j|^2 / (2 * beta^2))` of the source
+/// points and is penalized by `lambda / 2 * tr(W^T * G * W)`, as in the motion coherence of
+/// the non-rigid CPD. The drift absorbs the smooth deformations and the correlated noise of
+/// the target, which would otherwise bias the rigid transformation.
+///
+/// The kernel and the probabilities of the correspondences are dense, so the memory grows
+/// with the square of the number of points, and the registration suits clouds of at most a
+/// few thousand points.
+pub struct CoherentDrift;
+
+impl CoherentDrift {
+    /// Register a source point set to a target point set with the EM algorithm.
+    ///
+    /// The E-step computes the posterior probability that each target point was drawn from
+    /// the Gaussian centered on each drifted source point. The M-step fits the rotation and
+    /// translation in closed form to the weighted targets of the source points, then solves
+    /// the drift with the conjugate gradient and updates the variance of the Gaussians. The
+    /// iterations start from the identity and a variance of the mean squared distance
+    /// between the point sets, and stop when the variance changes less than `tolerance`.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The source points.
+    /// * `dst` - The target points.
+    /// * `lambda` - The weight of the regularization of the drift. The larger, the closer to
+    ///   the rigid CPD.
+    /// * `beta` - The width of the Gaussian kernel of the drift. The larger, the smoother.
+    /// * `max_iter` - Maximum number of EM iterations.
+    /// * `tolerance` - The change of the variance of the mixture at which the iterations stop.
+    ///
+    /// # Returns
+    ///
+    /// The rotation and translation from the source to the target frame, the identity if
+    /// either set of points is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IcpError::Validation`] if the estimated transformation is not a valid rigid
+    /// transformation, e.g. after a numerical blowup.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_icp::CoherentDrift;
+    ///
+    /// // a twisted grid, shifted along x
+    /// let src = (0..100)
+    ///     .map(|i| {
+    ///         let (u, v) = ((i % 10) as f64 * 0.1, (i / 10) as f64 * 0.1);
+    ///         [u, v, u * v]
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// let dst = src.iter().map(|p| [p[0] + 0.05, p[1], p[2]]).collect::<Vec<_>>();
+    ///
+    /// let (_, translation) = CoherentDrift::register(&src, &dst, 2.0, 1.0, 100, 1e-10).unwrap();
+    /// assert!((translation[0] - 0.05).abs() < 1e-3);
+    /// ```
+    pub fn register(
+        src: &[[f64; 3]],
+        dst: &[[f64; 3]],
+        lambda: f64,
+        beta: f64,
+        max_iter: usize,
+        tolerance: f64,
+    ) -> Result<([[f64; 3]; 3], [f64; 3]), IcpError> {
+        let mut transform = RigidTransform3::identity();
+        if src.is_empty() || dst.is_empty() {
+            return Ok((transform.rotation, transform.translation));
+        }
+        let (num_src, num_dst) = (src.len(), dst.len());
+
+        let kernel = src
+            .iter()
+            .map(|p| {
+                src.iter()
+                    .map(|q| (-squared_distance(p, q) / (2.0 * beta * beta)).exp())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut coefficients = vec![[0.0; 3]; num_src];
+        let mut drift = vec![[0.0; 3]; num_src];
+
+        let mut variance = src
+            .iter()
+            .flat_map(|p| dst.iter().map(move |q| squared_distance(p, q)))
+            .sum::<f64>()
+            / (3 * num_src * num_dst) as f64;
+        for _ in 0..max_iter {
+            let centroids = src
+                .iter()
+                .zip(drift.iter())
+                .map(|(p, v)| add(&transform.apply(p), v))
+                .collect::<Vec<_>>();
+
+            // E-step: the posterior of each centroid for each target point
+            let outlier_density = (2.0 * std::f64::consts::PI * variance).powf(1.5)
+                * OUTLIER_WEIGHT
+                / (1.0 - OUTLIER_WEIGHT)
+                * num_src as f64
+                / num_dst as f64;
+            let mut p1 = vec![0.0; num_src];
+            let mut px = vec![[0.0; 3]; num_src];
+            let mut pt1_sq_norms = 0.0;
+            let mut likelihoods = vec![0.0; num_src];
+            for x in dst.iter() {
+                for (l, c) in likelihoods.iter_mut().zip(centroids.iter()) {
+                    *l = (-squared_distance(x, c) / (2.0 * variance)).exp();
+                }
+                let denominator = likelihoods.iter().sum::<f64>() + outlier_density;
+                for ((l, p1m), pxm) in likelihoods.iter().zip(p1.iter_mut()).zip(px.iter_mut()) {
+                    let posterior = l / denominator;
+                    *p1m += posterior;
+                    for k in 0..3 {
+                        pxm[k] += posterior * x[k];
+                    }
+                    pt1_sq_norms += posterior * dot(x, x);
+                }
+            }
+            let np = p1.iter().sum::<f64>();
+            if np <= 0.0 {
+                break;
+            }
+
+            // M-step: the rigid transformation to the weighted targets without the drift
+            let targets = px
+                .iter()
+                .zip(p1.iter())
+                .zip(drift.iter())
+                .map(|((pxm, p1m), v)| {
+                    let mean = pxm.map(|x| x / p1m.max(f64::MIN_POSITIVE));
+                    sub(&mean, v)
+                })
+                .collect::<Vec<_>>();
+            let Some(fitted) = fit_rigid_transform_weighted(src, &targets, &p1) else {
+                break;
+            };
+            transform = fitted;
+
+            // M-step: the drift solving (G + lambda * variance * diag(P1)^-1) * W = residuals
+            let regularization = p1
+                .iter()
+                .map(|p1m| lambda * variance / p1m.max(MIN_VARIANCE))
+                .collect::<Vec<_>>();
+            let residuals = src
+                .iter()
+                .zip(px.iter())
+                .zip(p1.iter())
+                .map(|((y, pxm), p1m)| {
+                    let rigid = transform.apply(y);
+                    [0, 1, 2].map(|k| (pxm[k] - p1m * rigid[k]) / p1m.max(MIN_VARIANCE))
+                })
+                .collect::<Vec<_>>();
+            coefficients = solve_drift(&kernel, &regularization, &residuals, coefficients);
+            let centroids = src
+                .iter()
+                .zip(mul_kernel(&kernel, &coefficients).iter())
+                .map(|(y, v)| add(&transform.apply(y), v))
+                .collect::<Vec<_>>();
+
+            // the rigid part of the drift moves to the transformation, so that the drift only
+            // holds the deformation
+            if let Some(fitted) = fit_rigid_transform_weighted(src, &centroids, &p1) {
+                transform = fitted;
+            }
+            drift = src
+                .iter()
+                .zip(centroids.iter())
+                .map(|(y, c)| sub(c, &transform.apply(y)))
+                .collect();
+
+            // M-step: the variance of the Gaussians around the new centroids
+            let mut weighted_sq_distances = pt1_sq_norms;
+            for ((centroid, pxm), p1m) in centroids.iter().zip(px.iter()).zip(p1.iter()) {
+                weighted_sq_distances += p1m * dot(centroid, centroid) - 2.0 * dot(pxm, centroid);
+            }
+            let prev_variance = variance;
+            variance = (weighted_sq_distances / (3.0 * np)).max(MIN_VARIANCE);
+            if (prev_variance - variance).abs() < tolerance {
+                break;
+            }
+        }
+
+        // guard against numerical blowups in the estimated transformation
+        validate_icp_result(
+            &transform.rotation,
+            &transform.translation,
+            f64::INFINITY,
+            f64::INFINITY,
+        )?;
+
+        Ok((transform.rotation, transform.translation))
+    }
+}
+
+/// Solve the linear system of the drift with the Jacobi preconditioned conjugate gradient.
+///
+/// The matrix of the system, the kernel plus the positive diagonal `regularization`, is
+/// symmetric positive definite, and the three coordinates are solved at once.
+fn solve_drift(
+    kernel: &[Vec<f64>],
+    regularization: &[f64],
+    rhs: &[[f64; 3]],
+    initial: Vec<[f64; 3]>,
+) -> Vec<[f64; 3]> {
+    let apply = |x: &[[f64; 3]]| {
+        mul_kernel(kernel, x)
+            .iter()
+            .zip(x.iter().zip(regularization.iter()))
+            .map(|(gx, (xi, r))| [0, 1, 2].map(|k| gx[k] + r * xi[k]))
+            .collect::<Vec<_>>()
+    };
+    let inner = |a: &[[f64; 3]], b: &[[f64; 3]]| {
+        a.iter().zip(b.iter()).map(|(x, y)| dot(x, y)).sum::<f64>()
+    };
+    let precondition = |r: &[[f64; 3]]| {
+        r.iter()
+            .zip(kernel.iter().zip(regularization.iter()))
+            .enumerate()
+            .map(|(i, (ri, (row, reg)))| ri.map(|x| x / (row[i] + reg)))
+            .collect::<Vec<_>>()
+    };
+
+    let mut x = initial;
+    let mut residual = rhs
+        .iter()
+        .zip(apply(&x).iter())
+        .map(|(b, a)| sub(b, a))
+        .collect::<Vec<_>>();
+    let rhs_norm = inner(rhs, rhs).sqrt();
+    let mut z = precondition(&residual);
+    let mut direction = z.clone();
+    let mut rz = inner(&residual, &z);
+    for _ in 0..CG_MAX_ITERATIONS {
+        if inner(&residual, &residual).sqrt() <= CG_TOLERANCE * rhs_norm {
+            break;
+        }
+        let ap = apply(&direction);
+        let curvature = inner(&direction, &ap);
+        if curvature <= 0.0 {
+            break;
+        }
+        let step = rz / curvature;
+        for ((xi, ri), (pi, api)) in x
+            .iter_mut()
+            .zip(residual.iter_mut())
+            .zip(direction.iter().zip(ap.iter()))
+        {
+            for k in 0..3 {
+                xi[k] += step * pi[k];
+                ri[k] -= step * api[k];
+            }
+        }
+        z = precondition(&residual);
+        let rz_next = inner(&residual, &z);
+        let beta = rz_next / rz;
+        rz = rz_next;
+        for (pi, zi) in direction.iter_mut().zip(z.iter()) {
+            for k in 0..3 {
+                pi[k] = zi[k] + beta * pi[k];
+            }
+        }
+    }
+    x
+}
+
+/// Multiply the coefficients of the drift by the kernel.
+fn mul_kernel(kernel: &[Vec<f64>], x: &[[f64; 3]]) -> Vec<[f64; 3]> {
+    kernel
+        .iter()
+        .map(|row| {
+            let mut out = [0.0; 3];
+            for (g, xi) in row.iter().zip(x.iter()) {
+                for k in 0..3 {
+                    out[k] += g * xi[k];
+                }
+            }
+            out
+        })
+        .collect()
+}
+
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn add(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn squared_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    let d = sub(a, b);
+    dot(&d, &d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const SEED: u64 = 11;
+
+    /// Sample a bumpy patch, with no symmetry.
+    fn patch(rng: &mut StdRng) -> Vec<[f64; 3]> {
+        (0..150)
+            .map(|_| {
+                let (u, v): (f64, f64) = (rng.random_range(0.0..1.0), rng.random_range(0.0..0.6));
+                [
+                    u,
+                    v,
+                    0.3 * (3.0 * u).sin() * (2.0 * v + 0.5).cos() + 0.2 * u * u,
+                ]
+            })
+            .collect()
+    }
+
+    fn rotation_error(a: &[[f64; 3]; 3], b: &[[f64; 3]; 3]) -> f64 {
+        (0..3)
+            .flat_map(|i| (0..3).map(move |j| (a[i][j] - b[i][j]).abs()))
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn test_coherent_drift_noise_and_outliers() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let src = patch(&mut rng);
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.3, -0.2, 1.0], 0.35)?,
+            [0.1, -0.05, 0.08],
+        );
+
+        // noisy target points and uniform outliers around them
+        let mut dst = src
+            .iter()
+            .map(|p| truth.apply(p).map(|x| x + rng.random_range(-0.01..0.01)))
+            .collect::<Vec<_>>();
+        dst.extend((0..20).map(|_| [0, 1, 2].map(|_| rng.random_range(-0.5..1.5))));
+
+        let (rotation, translation) = CoherentDrift::register(&src, &dst, 2.0, 0.5, 200, 1e-12)?;
+        assert!(rotation_error(&rotation, &truth.rotation) < 2e-2);
+        for (t, expected) in translation.iter().zip(truth.translation.iter()) {
+            assert!((t - expected).abs() < 2e-2);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_coherent_drift_smooth_deformation() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let src = patch(&mut rng);
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.2)?,
+            [0.05, 0.0, 0.0],
+        );
+
+        // a wave along z, without a rigid component over the patch, absorbed by the drift
+        let dst = src
+            .iter()
+            .map(|p| {
+                let mut q = truth.apply(p);
+                q[2] += 0.04 * (std::f64::consts::TAU * p[0]).cos();
+                q
+            })
+            .collect::<Vec<_>>();
+
+        let (rotation, translation) = CoherentDrift::register(&src, &dst, 2.0, 0.5, 200, 1e-12)?;
+        assert!(rotation_error(&rotation, &truth.rotation) < 2e-2);
+        for (t, expected) in translation.iter().zip(truth.translation.iter()).take(2) {
+            assert!((t - expected).abs() < 2e-2);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_coherent_drift_empty() -> Result<(), Box<dyn std::error::Error>> {
+        let points = vec![[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        let identity = RigidTransform3::identity();
+        assert_eq!(
+            CoherentDrift::register(&[], &points, 2.0, 1.0, 10, 1e-8)?,
+            (identity.rotation, identity.translation)
+        );
+        assert_eq!(
+            CoherentDrift::register(&points, &[], 2.0, 1.0, 10, 1e-8)?,
+            (identity.rotation, identity.translation)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+use kornia_3d::{
+    features::estimate_normals,
+    linalg::{dot_product3, mat33_mul_vec3, transform_points3d_vec},
+    pointcloud::PointCloud,
+};
+
+/// Parameters of [`icp_alignment_confidence`].
+#[derive(Debug, Clone)]
+pub struct AlignmentConfidenceParams {
+    /// Maximum distance between a source point and its nearest target point to be
+    /// considered a correspondence.
+    pub max_correspondence_distance: f64,
+    /// Radius of the neighborhood used to estimate the normals of both clouds.
+    pub normal_radius: f64,
+    /// Weight of the overlap score.
+    pub overlap_weight: f64,
+    /// Weight of the inverse RMSE score.
+    pub rmse_weight: f64,
+    /// Weight of the normal consistency score.
+    pub normal_weight: f64,
+}
+
+/// Compute the confidence of an alignment between two point clouds.
+///
+/// The confidence combines three scores in `[0, 1]`, computed on the correspondences
+/// between the transformed source points and their nearest target points within
+/// `max_correspondence_distance`, the inliers:
+///
+/// * the overlap `o`, the fraction of source points with an inlier correspondence,
+/// * the inverse RMSE `e = 1 - rmse / max_correspondence_distance` of the inliers,
+/// * the normal consistency `c`, the mean of `|R n_src · n_dst|` over the inliers, with the
+///   normals estimated within `normal_radius`,
+///
+/// into their weighted mean
+///
+/// `confidence = (w_o * o + w_e * e + w_c * c) / (w_o + w_e + w_c)`.
+///
+/// A SLAM back-end typically accepts a loop closure whose confidence exceeds a threshold.
+///
+/// # Arguments
+///
+/// * `src` - The source points.
+/// * `dst` - The target points.
+/// * `r` - The rotation from the source to the target frame.
+/// * `t` - The translation from the source to the target frame.
+/// * `params` - The parameters with the non-negative weights of the scores.
+///
+/// # Returns
+///
+/// The confidence in `[0, 1]`. It is zero without any inlier or if the weights sum to zero.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{icp_alignment_confidence, AlignmentConfidenceParams};
+///
+/// let points = (0..400)
+///     .map(|i| {
+///         let (u, v) = ((i % 20) as f64 * 0.05, (i / 20) as f64 * 0.05);
+///         [u, v, 0.2 * (3.0 * u).sin()]
+///     })
+///     .collect::<Vec<_>>();
+/// let params = AlignmentConfidenceParams {
+///     max_correspondence_distance: 0.1,
+///     normal_radius: 0.12,
+///     overlap_weight: 0.4,
+///     rmse_weight: 0.3,
+///     normal_weight: 0.3,
+/// };
+/// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let confidence = icp_alignment_confidence(&points, &points, &identity, &[0.0; 3], &params);
+/// assert!((confidence - 1.0).abs() < 1e-9);
+/// ```
+pub fn icp_alignment_confidence(
+    src: &[[f64; 3]],
+    dst: &[[f64; 3]],
+    r: &[[f64; 3]; 3],
+    t: &[f64; 3],
+    params: &AlignmentConfidenceParams,
+) -> f64 {
+    let weights = [
+        params.overlap_weight.max(0.0),
+        params.rmse_weight.max(0.0),
+        params.normal_weight.max(0.0),
+    ];
+    let sum_weights = weights.iter().sum::<f64>();
+    if src.is_empty() || dst.is_empty() || sum_weights <= 0.0 {
+        return 0.0;
+    }
+
+    let src_normals = estimate_normals(
+        &PointCloud::new(src.to_vec(), None, None),
+        params.normal_radius,
+        &[0.0; 3],
+    );
+    let dst_normals = estimate_normals(
+        &PointCloud::new(dst.to_vec(), None, None),
+        params.normal_radius,
+        &[0.0; 3],
+    );
+
+    let kdtree: ImmutableKdTree<f64, u32, 3, 32> = ImmutableKdTree::new_from_slice(dst);
+    let max_sq_distance = params.max_correspondence_distance.powi(2);
+
+    let mut num_inliers = 0;
+    let mut sum_sq_distances = 0.0;
+    let mut sum_consistency = 0.0;
+    let transformed = transform_points3d_vec(src, r, t);
+    for (p, n) in transformed.iter().zip(src_normals.iter()) {
+        let nn = kdtree.nearest_one::<kiddo::SquaredEuclidean>(p);
+        if nn.distance > max_sq_distance {
+            continue;
+        }
+        num_inliers += 1;
+        sum_sq_distances += nn.distance;
+        let mut rotated = [0.0; 3];
+        mat33_mul_vec3(r, n, &mut rotated);
+        sum_consistency += dot_product3(&rotated, &dst_normals[nn.item as usize])
+            .abs()
+            .min(1.0);
+    }
+    if num_inliers == 0 {
+        return 0.0;
+    }
+
+    let overlap = num_inliers as f64 / src.len() as f64;
+    let rmse = (sum_sq_distances / num_inliers as f64).sqrt();
+    let inverse_rmse = if params.max_correspondence_distance > 0.0 {
+        (1.0 - rmse / params.max_correspondence_distance).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let consistency = sum_consistency / num_inliers as f64;
+
+    let confidence =
+        (weights[0] * overlap + weights[1] * inverse_rmse + weights[2] * consistency) / sum_weights;
+    confidence.clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::{axis_angle_to_rotation_matrix, RigidTransform3};
+
+    const IDENTITY: [[f64; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    fn params() -> AlignmentConfidenceParams {
+        AlignmentConfidenceParams {
+            max_correspondence_distance: 0.1,
+            normal_radius: 0.12,
+            overlap_weight: 0.4,
+            rmse_weight: 0.3,
+            normal_weight: 0.3,
+        }
+    }
+
+    /// A bumpy surface sampled on the grid cells `[x0, x0 + 30) x [0, 30)`.
+    fn surface(x0: usize) -> Vec<[f64; 3]> {
+        (0..900)
+            .map(|i| {
+                let (u, v) = ((x0 + i % 30) as f64 * 0.05, (i / 30) as f64 * 0.05);
+                [u, v, 0.3 * (2.0 * u).sin() * (3.0 * v).cos()]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_confidence_of_alignments() -> Result<(), Box<dyn std::error::Error>> {
+        // two scans overlapping on two thirds of their extent
+        let target = surface(0);
+        let dst_r_src = axis_angle_to_rotation_matrix(&[0.2, 1.0, -0.3], 0.4)?;
+        let dst_t_src = [0.5, -0.2, 0.3];
+        let src_from_dst = RigidTransform3::new(dst_r_src, dst_t_src).inverse();
+        let source = surface(10)
+            .iter()
+            .map(|p| src_from_dst.apply(p))
+            .collect::<Vec<_>>();
+
+        let good = icp_alignment_confidence(&source, &target, &dst_r_src, &dst_t_src, &params());
+        // the overlap of two thirds bounds the confidence by 0.4 * 2 / 3 + 0.6
+        assert!(good > 0.8, "{good}");
+
+        // a misaligned loop closure is rejected
+        let wrong_t = [dst_t_src[0] + 0.05, dst_t_src[1], dst_t_src[2] + 0.05];
+        let misaligned =
+            icp_alignment_confidence(&source, &target, &dst_r_src, &wrong_t, &params());
+        assert!(misaligned < good - 0.1, "{misaligned} vs {good}");
+        let far = icp_alignment_confidence(&source, &target, &IDENTITY, &[5.0; 3], &params());
+        assert_eq!(far, 0.0);
+
+        // the overlap alone
+        let overlap_only = AlignmentConfidenceParams {
+            rmse_weight: 0.0,
+            normal_weight: 0.0,
+            ..params()
+        };
+        let overlap =
+            icp_alignment_confidence(&source, &target, &dst_r_src, &dst_t_src, &overlap_only);
+        assert!((overlap - 2.0 / 3.0).abs() < 0.05, "{overlap}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_confidence_degenerate_inputs() {
+        let points = surface(0);
+        assert_eq!(
+            icp_alignment_confidence(&[], &points, &IDENTITY, &[0.0; 3], &params()),
+            0.0
+        );
+        let no_weights = AlignmentConfidenceParams {
+            overlap_weight: 0.0,
+            rmse_weight: 0.0,
+            normal_weight: 0.0,
+            ..params()
+        };
+        assert_eq!(
+            icp_alignment_confidence(&points, &points, &IDENTITY, &[0.0; 3], &no_weights),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::{icp_multi_hypothesis::register_from, IcpParams};
+use kornia_3d::{
+    kdtree::KdTree,
+    linalg::dot_product3,
+    transforms::{axis_angle_to_rotation_matrix, RigidTransform3},
+};
+
+/// Seed of the sampling of the initial poses, fixed for reproducibility.
+const CONVERGENCE_SEED: u64 = 0;
+
+/// Number of ICP experiments of the estimate.
+const CONVERGENCE_TRIALS: usize = 100;
+
+/// Rotation error, in degrees, below which a registration has converged.
+const CONVERGED_ROTATION_DEG: f64 = 1.0;
+
+/// Translation error, relative to the maximum correspondence distance, below which a
+/// registration has converged.
+const CONVERGED_TRANSLATION_FRACTION: f64 = 0.1;
+
+/// Estimate the probability that ICP converges from an initial pose error.
+///
+/// The clouds are given registered, so that the ground truth is the identity. The estimate
+/// is a Monte Carlo experiment of 100 trials, run in parallel: each trial draws an initial
+/// pose within the error ball, with a rotation about a uniform random axis by an angle
+/// uniform in `[0, initial_pose_error_deg]` and a translation uniform in the ball of radius
+/// `initial_trans_error_m`, and runs the point to point ICP of
+/// [`multi_hypothesis_icp`](crate::multi_hypothesis_icp) from it. A trial converges when
+/// the registration is within one degree and a tenth of the maximum correspondence
+/// distance of the ground truth.
+///
+/// The rate as a function of the maximum correspondence distance is a diagnostic to choose
+/// it: a small distance rejects the outliers but loses the far correspondences of a poor
+/// initial guess, and ICP then stalls or converges to a local minimum.
+///
+/// # Arguments
+///
+/// * `src` - The source points.
+/// * `dst` - The target points, in the frame of the source.
+/// * `initial_pose_error_deg` - The largest rotation of the initial poses, in degrees.
+/// * `initial_trans_error_m` - The largest translation of the initial poses, in meters.
+/// * `params` - The parameters of each registration.
+/// * `max_samples` - The source is evenly subsampled to at most this number of points, or
+///   not at all if zero.
+///
+/// # Returns
+///
+/// The fraction of the trials converging to the ground truth, zero if either cloud is
+/// empty.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{estimate_convergence_probability, IcpParams};
+///
+/// let points = (0..200)
+///     .map(|i| {
+///         let (x, y) = ((i % 20) as f64 * 0.05, (i / 20) as f64 * 0.05);
+///         [x, y, 0.2 * (4.0 * x).sin() * (3.0 * y).cos()]
+///     })
+///     .collect::<Vec<_>>();
+/// let params = IcpParams {
+///     max_iterations: 50,
+///     tolerance: 1e-9,
+///     entropy_threshold: 0.0,
+///     max_correspondence_distance: 0.5,
+/// };
+/// let probability = estimate_convergence_probability(&points, &points, 2.0, 0.01, &params, 0);
+/// assert_eq!(probability, 1.0);
+/// ```
+pub fn estimate_convergence_probability(
+    src: &[[f64; 3]],
+    dst: &[[f64; 3]],
+    initial_pose_error_deg: f64,
+    initial_trans_error_m: f64,
+    params: &IcpParams,
+    max_samples: usize,
+) -> f64 {
+    if src.is_empty() || dst.is_empty() {
+        return 0.0;
+    }
+
+    let step = match max_samples {
+        0 => 1,
+        n => src.len().div_ceil(n).max(1),
+    };
+    let samples = src.iter().step_by(step).copied().collect::<Vec<_>>();
+    let index = KdTree::new(dst);
+
+    // the initial poses, drawn before the parallel trials for reproducibility
+    let mut rng = StdRng::seed_from_u64(CONVERGENCE_SEED);
+    let initial_poses = (0..CONVERGENCE_TRIALS)
+        .map(|_| {
+            let axis = random_unit_vector(&mut rng);
+            let angle = rng.random_range(0.0..=1.0) * initial_pose_error_deg.to_radians();
+            let rotation = axis_angle_to_rotation_matrix(&axis, angle)
+                .unwrap_or(RigidTransform3::identity().rotation);
+            let radius = initial_trans_error_m * rng.random_range(0.0..=1.0f64).cbrt();
+            let translation = random_unit_vector(&mut rng).map(|x| radius * x);
+            RigidTransform3::new(rotation, translation)
+        })
+        .collect::<Vec<_>>();
+
+    let max_translation_error = CONVERGED_TRANSLATION_FRACTION * params.max_correspondence_distance;
+    let converged = initial_poses
+        .into_par_iter()
+        .filter(|initial| {
+            let transform = register_from(&samples, dst, &index, *initial, params);
+            let t = transform.translation;
+            transform.rotation_angle() < CONVERGED_ROTATION_DEG.to_radians()
+                && dot_product3(&t, &t).sqrt() < max_translation_error
+        })
+        .count();
+
+    converged as f64 / CONVERGENCE_TRIALS as f64
+}
+
+/// Draw a direction uniformly on the unit sphere.
+fn random_unit_vector(rng: &mut StdRng) -> [f64; 3] {
+    let z: f64 = rng.random_range(-1.0..=1.0);
+    let phi = rng.random_range(0.0..std::f64::consts::TAU);
+    let r = (1.0 - z * z).sqrt();
+    [r * phi.cos(), r * phi.sin(), z]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sample a bumpy patch, with no symmetry.
+    fn patch(rng: &mut StdRng, n: usize) -> Vec<[f64; 3]> {
+        (0..n)
+            .map(|_| {
+                let (u, v): (f64, f64) = (rng.random_range(0.0..1.0), rng.random_range(0.0..0.6));
+                [
+                    u,
+                    v,
+                    0.3 * (3.0 * u).sin() * (2.0 * v + 0.5).cos() + 0.2 * u * u,
+                ]
+            })
+            .collect()
+    }
+
+    fn params(max_correspondence_distance: f64) -> IcpParams {
+        IcpParams {
+            max_iterations: 100,
+            tolerance: 1e-10,
+            entropy_threshold: 0.0,
+            max_correspondence_distance,
+        }
+    }
+
+    #[test]
+    fn test_estimate_convergence_probability() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let points = patch(&mut rng, 300);
+
+        // ICP always converges from a small error
+        let small = estimate_convergence_probability(&points, &points, 3.0, 0.02, &params(0.5), 0);
+        assert_eq!(small, 1.0);
+
+        // and not from an arbitrary orientation
+        let large = estimate_convergence_probability(&points, &points, 180.0, 0.5, &params(0.5), 0);
+        assert!(large < 0.9);
+
+        // a tight correspondence distance loses the far correspondences of the initial poses
+        let tight = estimate_convergence_probability(&points, &points, 20.0, 0.2, &params(0.01), 0);
+        let loose = estimate_convergence_probability(&points, &points, 20.0, 0.2, &params(1.0), 0);
+        assert!(tight < loose);
+    }
+
+    #[test]
+    fn test_estimate_convergence_probability_empty() {
+        let points = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]];
+        assert_eq!(
+            estimate_convergence_probability(&[], &points, 5.0, 0.1, &params(0.5), 0),
+            0.0
+        );
+        assert_eq!(
+            estimate_convergence_probability(&points, &[], 5.0, 0.1, &params(0.5), 0),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{ops::rotation_vector, robust_icp, RobustIcpParams};
+use kornia_3d::{
+    density::estimate_density, pointcloud::PointCloud, pose::standard_normal,
+    transforms::RigidTransform3,
+};
+
+/// Number of source points sampled to estimate the point spacing of the source.
+const DENSITY_SAMPLE_SIZE: usize = 1000;
+
+/// Standard deviation of the noise perturbations of the source points, as a fraction of the
+/// median nearest neighbor spacing of the source.
+const NOISE_SPACING_RATIO: f64 = 0.1;
+
+/// Estimate the covariance of the pose of a pairwise ICP registration by Monte Carlo sampling.
+///
+/// The registration of the source to the target is first solved with [`crate::robust_icp`]
+/// from the identity. The ICP is then run `n_samples` times on copies of the source whose
+/// coordinates are perturbed by a zero-mean Gaussian noise, with a standard deviation of a
+/// tenth of the median point spacing of the source. Each resulting pose is expressed as the perturbation `(ω, t)` applied on the
+/// left of the reference pose, and the sample covariance of these perturbations is returned.
+/// Unlike the inverse of the information matrix, see [`crate::compute_information_matrix`],
+/// the estimate does not rely on a linearization of the residuals and captures the effect of
+/// the changing correspondences, at the cost of `n_samples` registrations.
+///
+/// # Arguments
+///
+/// * `source` - Source point cloud, roughly aligned with the target.
+/// * `target` - Target point cloud.
+/// * `params` - The parameters of each registration.
+/// * `n_samples` - The number of perturbed registrations.
+/// * `seed` - The seed of the random generator of the perturbations.
+///
+/// # Returns
+///
+/// The 6x6 covariance of the pose, with the rotation parameters first, as expected by
+/// [`crate::PoseGraphEdge`] once inverted. It is zero if the source has fewer than two points,
+/// the target is empty, or fewer than two registrations succeed.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{icp_covariance_monte_carlo, KernelScale, RobustIcpParams, RobustKernel};
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..125)
+///     .map(|i| [(i % 5) as f64 * 0.1, (i / 5 % 5) as f64 * 0.1, (i / 25) as f64 * 0.1])
+///     .collect::<Vec<_>>();
+/// let cloud = PointCloud::new(points, None, None);
+/// let params = RobustIcpParams {
+///     max_iterations: 20,
+///     tolerance: 1e-12,
+///     entropy_threshold: 0.0,
+///     max_correspondence_distance: 0.05,
+///     kernel: RobustKernel::Huber,
+///     scale: KernelScale::Fixed(1.0),
+/// };
+/// let covariance = icp_covariance_monte_carlo(&cloud, &cloud, &params, 50, 0);
+/// assert!((0..6).all(|k| covariance[k][k] > 0.0));
+/// ```
+pub fn icp_covariance_monte_carlo(
+    source: &PointCloud,
+    target: &PointCloud,
+    params: &RobustIcpParams,
+    n_samples: usize,
+    seed: u64,
+) -> [[f64; 6]; 6] {
+    let mut covariance = [[0.0; 6]; 6];
+    if target.is_empty() {
+        return covariance;
+    }
+    let Ok(stats) = estimate_density(source, DENSITY_SAMPLE_SIZE, seed) else {
+        return covariance;
+    };
+    let noise_std = NOISE_SPACING_RATIO * stats.median_nn_dist;
+
+    let register = |source: &PointCloud| {
+        robust_icp(source, target, params)
+            .ok()
+            .map(|robust| RigidTransform3::new(robust.result.rotation, robust.result.translation))
+    };
+    let Some(reference) = register(source) else {
+        return covariance;
+    };
+    let reference_inverse = reference.inverse();
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let perturbations = (0..n_samples)
+        .filter_map(|_| {
+            let points = source
+                .points()
+                .iter()
+                .map(|p| p.map(|x| x + noise_std * standard_normal(&mut rng)))
+                .collect();
+            let pose = register(&PointCloud::new(points, None, None))?;
+            let delta = pose.compose(&reference_inverse);
+            let omega = rotation_vector(&delta.rotation);
+            Some([
+                omega[0],
+                omega[1],
+                omega[2],
+                delta.translation[0],
+                delta.translation[1],
+                delta.translation[2],
+            ])
+        })
+        .collect::<Vec<_>>();
+    if perturbations.len() < 2 {
+        return covariance;
+    }
+
+    let n = perturbations.len() as f64;
+    let mean: [f64; 6] =
+        std::array::from_fn(|k| perturbations.iter().map(|xi| xi[k]).sum::<f64>() / n);
+    for xi in perturbations.iter() {
+        let d: [f64; 6] = std::array::from_fn(|k| xi[k] - mean[k]);
+        for (row, da) in covariance.iter_mut().zip(d.iter()) {
+            for (value, db) in row.iter_mut().zip(d.iter()) {
+                *value += da * db / (n - 1.0);
+            }
+        }
+    }
+    covariance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute_information_matrix;
+    use kiddo::immutable::float::kdtree::ImmutableKdTree;
+    use kornia_3d::linalg::solve_linear;
+
+    fn params() -> RobustIcpParams {
+        // a Huber kernel wider than the residuals weights all the correspondences equally
+        RobustIcpParams {
+            max_iterations: 30,
+            tolerance: 1e-14,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.05,
+            kernel: crate::RobustKernel::Huber,
+            scale: crate::KernelScale::Fixed(1.0),
+        }
+    }
+
+    #[test]
+    fn test_icp_covariance_monte_carlo() {
+        // a grid elongated along x, so the rotations about x are the least constrained
+        let points = (0..12 * 6 * 4)
+            .map(|i| {
+                let (a, b, c) = (i % 12, i / 12 % 6, i / 72);
+                [a as f64 * 0.1 - 0.55, b as f64 * 0.1 - 0.25, c as f64 * 0.1]
+            })
+            .collect::<Vec<_>>();
+        let cloud = PointCloud::new(points.clone(), None, None);
+        let covariance = icp_covariance_monte_carlo(&cloud, &cloud, &params(), 400, 7);
+
+        // the linearized covariance is the noise variance times the inverse information
+        let index: ImmutableKdTree<f64, u32, 3, 32> = ImmutableKdTree::new_from_slice(&points);
+        let information =
+            compute_information_matrix(&cloud, &index, &RigidTransform3::identity(), 0.05);
+        let noise_variance = (NOISE_SPACING_RATIO * 0.1f64).powi(2);
+        for k in 0..6 {
+            let mut unit = [0.0; 6];
+            unit[k] = 1.0;
+            let column = solve_linear(&information, &unit).expect("the grid constrains the pose");
+            let expected = noise_variance * column[k];
+            assert!((covariance[k][k] / expected - 1.0).abs() < 0.2);
+            for (j, value) in covariance[k].iter().enumerate() {
+                assert!((value - covariance[j][k]).abs() < 1e-15);
+            }
+        }
+        assert!(covariance[0][0] > covariance[2][2]);
+
+        // the same samples from the same seed
+        let again = icp_covariance_monte_carlo(&cloud, &cloud, &params(), 400, 7);
+        assert_eq!(again, covariance);
+    }
+
+    #[test]
+    fn test_icp_covariance_monte_carlo_degenerate() {
+        let single = PointCloud::new(vec![[0.0; 3]], None, None);
+        let empty = PointCloud::new(vec![], None, None);
+        assert_eq!(
+            icp_covariance_monte_carlo(&single, &single, &params(), 10, 0),
+            [[0.0; 6]; 6]
+        );
+        let cloud = PointCloud::new(vec![[0.0; 3], [1.0, 0.0, 0.0]], None, None);
+        assert_eq!(
+            icp_covariance_monte_carlo(&cloud, &empty, &params(), 10, 0),
+            [[0.0; 6]; 6]
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use crate::ops::fit_transformation;
+
+/// Singular values of the cross-covariance below this fraction of the largest one are
+/// considered zero when computing its rank.
+const RANK_TOLERANCE: f64 = 1e-6;
+
+/// Diagnostics of the closed-form fit of a rigid transformation to correspondences.
+///
+/// The rotation is solved with the SVD of the 3x3 cross-covariance matrix `H` of the
+/// centered correspondences.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FitDiagnostics {
+    /// Whether the SVD solution was a reflection, i.e. `det(V U^T) < 0`, and was fixed into
+    /// a rotation. This usually indicates bad correspondences.
+    pub reflection_fixed: bool,
+    /// The ratio of the largest to the smallest singular value of `H`, infinite if `H` is
+    /// singular.
+    pub condition_number: f64,
+    /// The numerical rank of `H`, 2 for coplanar points and at most 1 for collinear points.
+    pub rank: usize,
+    /// The RMSE of the distances between the transformed source points and the destination
+    /// points.
+    pub residual_rmse: f64,
+}
+
+impl FitDiagnostics {
+    /// Create the diagnostics from the singular values of the cross-covariance matrix.
+    pub(crate) fn new(
+        reflection_fixed: bool,
+        singular_values: [f64; 3],
+        residual_rmse: f64,
+    ) -> Self {
+        let max = singular_values.iter().fold(0.0f64, |acc, s| acc.max(*s));
+        let min = singular_values
+            .iter()
+            .fold(f64::INFINITY, |acc, s| acc.min(*s));
+        let rank = singular_values
+            .iter()
+            .filter(|s| **s > RANK_TOLERANCE * max)
+            .count();
+        let condition_number = if min > 0.0 { max / min } else { f64::INFINITY };
+        Self {
+            reflection_fixed,
+            condition_number,
+            rank,
+            residual_rmse,
+        }
+    }
+}
+
+/// Compute the transformation between two sets of corresponding points with diagnostics.
+///
+/// The transformation minimizes the sum of the squared distances between the transformed
+/// source points and the destination points, as in the ICP variants.
+///
+/// # Arguments
+///
+/// * `points_in_src` - The points in the source frame.
+/// * `points_in_dst` - The corresponding points in the destination frame.
+/// * `dst_r_src` - The estimated rotation from the source to the destination frame.
+/// * `dst_t_src` - The estimated translation from the source to the destination frame.
+///
+/// # Returns
+///
+/// The diagnostics of the fit, to detect suspicious solves.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::fit_transformation_diag;
+///
+/// let src = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let dst = src.iter().map(|p| [p[0] + 1.0, p[1], p[2]]).collect::<Vec<_>>();
+/// let mut rotation = [[0.0; 3]; 3];
+/// let mut translation = [0.0; 3];
+/// let diagnostics = fit_transformation_diag(&src, &dst, &mut rotation, &mut translation);
+/// assert!((translation[0] - 1.0).abs() < 1e-9);
+/// assert!(!diagnostics.reflection_fixed);
+/// assert_eq!(diagnostics.rank, 3);
+/// assert!(diagnostics.residual_rmse < 1e-9);
+/// ```
+pub fn fit_transformation_diag(
+    points_in_src: &[[f64; 3]],
+    points_in_dst: &[[f64; 3]],
+    dst_r_src: &mut [[f64; 3]; 3],
+    dst_t_src: &mut [f64; 3],
+) -> FitDiagnostics {
+    fit_transformation(points_in_src, points_in_dst, dst_r_src, dst_t_src)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use kornia_3d::{linalg::transform_points3d_vec, transforms::axis_angle_to_rotation_matrix};
+
+    fn points() -> Vec<[f64; 3]> {
+        (0..50)
+            .map(|i| {
+                let i = i as f64;
+                [(0.7 * i).sin(), (1.3 * i).cos(), (0.3 * i).sin() * 0.5]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fit_diagnostics_reflection() {
+        // a mirrored set of correspondences is best explained by a reflection
+        let src = points();
+        let dst = src.iter().map(|p| [-p[0], p[1], p[2]]).collect::<Vec<_>>();
+        let mut rotation = [[0.0; 3]; 3];
+        let mut translation = [0.0; 3];
+        let diagnostics = fit_transformation_diag(&src, &dst, &mut rotation, &mut translation);
+        assert!(diagnostics.reflection_fixed);
+        assert_eq!(diagnostics.rank, 3);
+        assert!(diagnostics.residual_rmse > 0.1);
+
+        let mut rotation = [[0.0; 3]; 3];
+        let diagnostics = fit_transformation_diag(&src, &src, &mut rotation, &mut translation);
+        assert!(!diagnostics.reflection_fixed);
+        assert!(diagnostics.condition_number.is_finite());
+    }
+
+    #[test]
+    fn test_fit_diagnostics_coplanar() -> Result<(), Box<dyn std::error::Error>> {
+        let src = points()
+            .iter()
+            .map(|p| [p[0], p[1], 0.0])
+            .collect::<Vec<_>>();
+        let rotation = axis_angle_to_rotation_matrix(&[1.0, 1.0, 0.0], 0.4)?;
+        let dst = transform_points3d_vec(&src, &rotation, &[0.1, 0.2, 0.3]);
+        let mut r = [[0.0; 3]; 3];
+        let mut t = [0.0; 3];
+        let diagnostics = fit_transformation_diag(&src, &dst, &mut r, &mut t);
+        assert_eq!(diagnostics.rank, 2);
+        assert!(diagnostics.condition_number > 1e6);
+        assert!(!diagnostics.reflection_fixed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fit_diagnostics_residual() -> Result<(), Box<dyn std::error::Error>> {
+        let src = points();
+        let rotation = axis_angle_to_rotation_matrix(&[0.2, -0.5, 1.0], 0.3)?;
+        let mut dst = transform_points3d_vec(&src, &rotation, &[0.5, -0.1, 0.2]);
+        for (i, p) in dst.iter_mut().enumerate() {
+            p[i % 3] += 0.01 * (i as f64).sin();
+        }
+
+        let mut r = [[0.0; 3]; 3];
+        let mut t = [0.0; 3];
+        let diagnostics = fit_transformation_diag(&src, &dst, &mut r, &mut t);
+
+        // independent computation of the residual of the estimated transformation
+        let sum_sq = src
+            .iter()
+            .zip(dst.iter())
+            .map(|(p, q)| {
+                (0..3)
+                    .map(|i| {
+                        let x = r[i][0] * p[0] + r[i][1] * p[1] + r[i][2] * p[2] + t[i];
+                        (x - q[i]).powi(2)
+                    })
+                    .sum::<f64>()
+            })
+            .sum::<f64>();
+        let rmse = (sum_sq / src.len() as f64).sqrt();
+        assert_relative_eq!(diagnostics.residual_rmse, rmse, epsilon = 1e-12);
+        assert!(rmse > 0.0);
+        Ok(())
+    }
+}
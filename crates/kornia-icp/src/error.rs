@@ -0,0 +1,40 @@
+use kornia_3d::density::DensityError;
+
+use crate::IcpValidationError;
+
+/// An error type for the registration pipelines.
+#[derive(Debug, thiserror::Error)]
+pub enum IcpError {
+    /// A point cloud has no points.
+    #[error("The point cloud is empty")]
+    EmptyCloud,
+
+    /// The density of a point cloud cannot be estimated.
+    #[error("Failed to estimate the density: {0}")]
+    Density(#[from] DensityError),
+
+    /// Too few correspondences were found to estimate a transformation.
+    #[error("Not enough correspondences to estimate the transformation: {0}")]
+    NotEnoughCorrespondences(usize),
+
+    /// The correspondences do not constrain all the degrees of freedom of the pose.
+    #[error("The correspondences do not constrain all the degrees of freedom of the pose")]
+    DegenerateConstraints,
+
+    /// There is no reference to register a frame against, e.g. the state of an odometry
+    /// lost its previous frame.
+    #[error("There is no reference to register against")]
+    MissingReference,
+
+    /// The sliding window of an odometry keeps fewer than two keyframes.
+    #[error("The sliding window must keep at least two keyframes, got {0}")]
+    InvalidWindowSize(usize),
+
+    /// The coarse alignment did not find a transformation supported by the features.
+    #[error("The coarse alignment failed to find a consensus")]
+    CoarseAlignmentFailed,
+
+    /// The estimated transformation is not a valid rigid transformation.
+    #[error(transparent)]
+    Validation(#[from] IcpValidationError),
+}
@@ -0,0 +1,272 @@
+use std::path::Path;
+
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+use kornia_3d::{
+    io::ply::{write_ply_binary, write_ply_line_set_binary},
+    linalg::transform_points3d_vec,
+    pointcloud::PointCloud,
+    transforms::RigidTransform3,
+};
+
+/// The matched points of a registration.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Correspondences {
+    /// The pairs of source and target point indices.
+    pub pairs: Vec<(usize, usize)>,
+}
+
+impl From<Vec<(usize, usize)>> for Correspondences {
+    fn from(pairs: Vec<(usize, usize)>) -> Self {
+        Self { pairs }
+    }
+}
+
+/// Colormap used to encode the per-point residuals as colors.
+#[derive(Debug, Clone, Copy)]
+pub enum ResidualColormap {
+    /// The viridis colormap from dark purple (zero residual) to yellow (`max_residual`).
+    Viridis {
+        /// The residual mapped to the last color of the colormap.
+        max_residual: f64,
+    },
+    /// Grayscale colormap from black (zero residual) to white (`max_residual`).
+    Grayscale {
+        /// The residual mapped to white.
+        max_residual: f64,
+    },
+}
+
+impl ResidualColormap {
+    // the viridis colormap at 0.0, 0.1, ..., 1.0
+    const VIRIDIS: [[u8; 3]; 11] = [
+        [0x44, 0x01, 0x54],
+        [0x48, 0x24, 0x75],
+        [0x41, 0x44, 0x87],
+        [0x35, 0x5f, 0x8d],
+        [0x2a, 0x78, 0x8e],
+        [0x21, 0x91, 0x8c],
+        [0x22, 0xa8, 0x84],
+        [0x44, 0xbf, 0x70],
+        [0x7a, 0xd1, 0x51],
+        [0xbd, 0xdf, 0x26],
+        [0xfd, 0xe7, 0x25],
+    ];
+
+    /// Map a residual to a RGB color.
+    ///
+    /// Residuals above the maximum residual are saturated to the last color.
+    pub fn color(&self, residual: f64) -> [u8; 3] {
+        let (max_residual, is_viridis) = match self {
+            ResidualColormap::Viridis { max_residual } => (*max_residual, true),
+            ResidualColormap::Grayscale { max_residual } => (*max_residual, false),
+        };
+
+        let t = if max_residual > 0.0 {
+            (residual / max_residual).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        if !is_viridis {
+            let v = (t * 255.0).round() as u8;
+            return [v, v, v];
+        }
+
+        // linear interpolation between the control points
+        let pos = t * (Self::VIRIDIS.len() - 1) as f64;
+        let i0 = (pos.floor() as usize).min(Self::VIRIDIS.len() - 2);
+        let w = pos - i0 as f64;
+        let (c0, c1) = (Self::VIRIDIS[i0], Self::VIRIDIS[i0 + 1]);
+
+        [0, 1, 2].map(|k| ((1.0 - w) * c0[k] as f64 + w * c1[k] as f64).round() as u8)
+    }
+}
+
+/// Export the correspondences between two point clouds as a PLY line set.
+///
+/// The vertices of the line set are the source points transformed to the target frame
+/// followed by the target points. Each correspondence is written as an edge between the
+/// matched source and target points. The file can be opened in MeshLab or CloudCompare.
+///
+/// # Arguments
+///
+/// * `path` - The path to the output PLY file.
+/// * `source` - Source point cloud.
+/// * `target` - Target point cloud.
+/// * `correspondences` - The pairs of source and target point indices.
+/// * `transform` - The transformation from the source to the target frame.
+pub fn export_correspondences_ply(
+    path: impl AsRef<Path>,
+    source: &PointCloud,
+    target: &PointCloud,
+    correspondences: &Correspondences,
+    transform: &RigidTransform3,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let num_source = source.len();
+
+    // transform the source points to the target frame
+    let mut points =
+        transform_points3d_vec(source.points(), &transform.rotation, &transform.translation);
+    points.extend_from_slice(target.points());
+
+    // color the source in blue and the target in red
+    let mut colors = vec![[90, 145, 199]; num_source];
+    colors.resize(num_source + target.len(), [230, 70, 60]);
+
+    let mut edges = Vec::with_capacity(correspondences.pairs.len());
+    for &(i_src, i_dst) in correspondences.pairs.iter() {
+        if i_src >= num_source || i_dst >= target.len() {
+            return Err(format!(
+                "correspondence ({}, {}) out of bounds for clouds of size {} and {}",
+                i_src,
+                i_dst,
+                num_source,
+                target.len()
+            )
+            .into());
+        }
+        edges.push([i_src as u32, (num_source + i_dst) as u32]);
+    }
+
+    write_ply_line_set_binary(path, &PointCloud::new(points, Some(colors), None), &edges)?;
+
+    Ok(())
+}
+
+/// Export the transformed source cloud colored by the nearest neighbor residual.
+///
+/// Each source point is transformed to the target frame and colored according to the
+/// distance to its nearest neighbor in the target cloud.
+///
+/// # Arguments
+///
+/// * `path` - The path to the output PLY file.
+/// * `source` - Source point cloud.
+/// * `target_index` - KD-tree built from the target points.
+/// * `transform` - The transformation from the source to the target frame.
+/// * `colormap` - The colormap used to encode the residuals.
+pub fn export_residual_colored_cloud(
+    path: impl AsRef<Path>,
+    source: &PointCloud,
+    target_index: &ImmutableKdTree<f64, u32, 3, 32>,
+    transform: &RigidTransform3,
+    colormap: ResidualColormap,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let points =
+        transform_points3d_vec(source.points(), &transform.rotation, &transform.translation);
+
+    let colors = points
+        .iter()
+        .map(|p| {
+            let nn = target_index.nearest_one::<kiddo::SquaredEuclidean>(p);
+            colormap.color(nn.distance.sqrt())
+        })
+        .collect::<Vec<_>>();
+
+    write_ply_binary(
+        path,
+        &PointCloud::new(points, Some(colors), source.normals().cloned()),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::io::ply::{read_ply_binary, read_ply_line_set_binary, PlyType};
+
+    #[test]
+    fn test_export_correspondences_ply() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let file_path = tmp_dir.path().join("correspondences.ply");
+
+        let source = PointCloud::new(
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            None,
+            None,
+        );
+        let target = PointCloud::new(vec![[0.5, 0.0, 0.0], [1.5, 0.0, 0.0]], None, None);
+        let correspondences = Correspondences::from(vec![(0, 0), (1, 1), (2, 0)]);
+        let transform = RigidTransform3::new(
+            [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            [0.5, 0.0, 0.0],
+        );
+
+        export_correspondences_ply(&file_path, &source, &target, &correspondences, &transform)?;
+
+        let (vertices, edges) = read_ply_line_set_binary(&file_path, PlyType::XYZRgbNormals)?;
+        assert_eq!(vertices.len(), 5);
+        assert_eq!(edges.len(), correspondences.pairs.len());
+        assert_eq!(edges[0], [0, 3]);
+        assert_eq!(edges[2], [2, 3]);
+        assert_eq!(vertices.points()[0], [0.5, 0.0, 0.0]);
+
+        // out of bounds correspondences are rejected
+        let res = export_correspondences_ply(
+            &file_path,
+            &source,
+            &target,
+            &Correspondences::from(vec![(0, 2)]),
+            &transform,
+        );
+        assert!(res.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_residual_colored_cloud() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let file_path = tmp_dir.path().join("residuals.ply");
+
+        // source points at increasing distance from the single target point
+        let source = PointCloud::new(
+            (0..10).map(|i| [0.1 * i as f64, 0.0, 0.0]).collect(),
+            None,
+            None,
+        );
+        let target_points = vec![[0.0, 0.0, 0.0]];
+        let kdtree: ImmutableKdTree<f64, u32, 3, 32> =
+            ImmutableKdTree::new_from_slice(&target_points);
+
+        export_residual_colored_cloud(
+            &file_path,
+            &source,
+            &kdtree,
+            &RigidTransform3::identity(),
+            ResidualColormap::Viridis { max_residual: 1.0 },
+        )?;
+
+        let cloud = read_ply_binary(&file_path, PlyType::XYZRgbNormals)?;
+        assert_eq!(cloud.len(), source.len());
+
+        // the green channel of viridis increases monotonically with the residual
+        let colors = cloud.colors().ok_or("missing colors")?;
+        for pair in colors.windows(2) {
+            assert!(pair[1][1] >= pair[0][1]);
+        }
+        assert!(colors[9][1] > colors[0][1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_residual_colormap_viridis() {
+        let colormap = ResidualColormap::Viridis { max_residual: 2.0 };
+        assert_eq!(colormap.color(0.0), [0x44, 0x01, 0x54]);
+        assert_eq!(colormap.color(1.6), [0x7a, 0xd1, 0x51]);
+        assert_eq!(colormap.color(1.8), [0xbd, 0xdf, 0x26]);
+        assert_eq!(colormap.color(2.0), [0xfd, 0xe7, 0x25]);
+        // three quarters of the way from the stop at 0.8 to the one at 0.9
+        assert_eq!(colormap.color(1.75), [0xac, 0xdc, 0x31]);
+    }
+
+    #[test]
+    fn test_residual_colormap_saturation() {
+        let colormap = ResidualColormap::Grayscale { max_residual: 2.0 };
+        assert_eq!(colormap.color(0.0), [0, 0, 0]);
+        assert_eq!(colormap.color(1.0), [128, 128, 128]);
+        assert_eq!(colormap.color(10.0), [255, 255, 255]);
+    }
+}
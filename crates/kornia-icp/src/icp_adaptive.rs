@@ -0,0 +1,284 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+
+use crate::{
+    ops::{find_correspondences_within, fit_transformation},
+    validate_icp_result, ICPResult,
+};
+use kornia_3d::{
+    linalg::{mat33_mul_vec3, matmul33, transform_points3d_vec},
+    pointcloud::PointCloud,
+};
+
+/// Error types for the adaptive ICP.
+#[derive(Debug, thiserror::Error)]
+pub enum AdaptiveIcpError {
+    /// The initial factor is not positive.
+    #[error("The initial factor must be positive. Got:{0}")]
+    InvalidInitFactor(f64),
+
+    /// The decay factor is not in the open interval (0, 1).
+    #[error("The decay factor must be in (0, 1). Got:{0}")]
+    InvalidDecayFactor(f64),
+}
+
+/// Parameters of the adaptive ICP.
+#[derive(Debug, Clone)]
+pub struct AdaptiveIcpParams {
+    /// Initial maximum correspondence distance as a fraction of the target cloud diameter.
+    pub init_factor: f64,
+    /// Factor in (0, 1) applied to the maximum correspondence distance after each iteration.
+    pub decay_factor: f64,
+    /// The registration stops when the maximum correspondence distance falls below this floor.
+    pub min_distance: f64,
+    /// Maximum number of iterations to perform.
+    pub max_iterations: usize,
+}
+
+/// Iterative Closest Point (ICP) with a coarse-to-fine maximum correspondence distance.
+///
+/// The maximum correspondence distance starts at `init_factor` times the diameter of the
+/// target cloud, taken as the diagonal of its bounding box, and is multiplied by
+/// `decay_factor` after each iteration. Large distances let the first iterations recover
+/// large initial displacements, while small distances discard the outliers once the clouds
+/// are close.
+#[derive(Debug, Clone)]
+pub struct AdaptiveIcp {
+    params: AdaptiveIcpParams,
+}
+
+impl AdaptiveIcp {
+    /// Create a new adaptive ICP.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the adaptive ICP.
+    pub fn new(params: AdaptiveIcpParams) -> Result<Self, AdaptiveIcpError> {
+        if params.init_factor.is_nan() || params.init_factor <= 0.0 {
+            return Err(AdaptiveIcpError::InvalidInitFactor(params.init_factor));
+        }
+        if !(params.decay_factor > 0.0 && params.decay_factor < 1.0) {
+            return Err(AdaptiveIcpError::InvalidDecayFactor(params.decay_factor));
+        }
+        Ok(Self { params })
+    }
+
+    /// Get as reference the parameters of the adaptive ICP.
+    pub fn params(&self) -> &AdaptiveIcpParams {
+        &self.params
+    }
+
+    /// Register the source point cloud to the target point cloud.
+    ///
+    /// The iterations stop when the maximum correspondence distance falls below
+    /// `min_distance` or after `max_iterations`. Iterations with fewer than three
+    /// correspondences keep the current transformation and only shrink the distance.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Source point cloud.
+    /// * `target` - Target point cloud.
+    /// * `initial_rot` - Initial rotation matrix from the source to the target frame.
+    /// * `initial_trans` - Initial translation vector from the source to the target frame.
+    ///
+    /// # Returns
+    ///
+    /// The transformation from the source to the target frame, the number of iterations and
+    /// the RMSE of the correspondences of the last iteration.
+    pub fn register(
+        &self,
+        source: &PointCloud,
+        target: &PointCloud,
+        initial_rot: [[f64; 3]; 3],
+        initial_trans: [f64; 3],
+    ) -> Result<ICPResult, Box<dyn std::error::Error>> {
+        let mut result = ICPResult {
+            rotation: initial_rot,
+            translation: initial_trans,
+            num_iterations: 0,
+            rmse: f64::INFINITY,
+            diagnostics: None,
+            overlap: None,
+        };
+
+        let kdtree: ImmutableKdTree<f64, u32, 3, 32> =
+            ImmutableKdTree::new_from_slice(target.points());
+
+        let mut max_distance = self.params.init_factor * cloud_diameter(target.points());
+        let mut current_source =
+            transform_points3d_vec(source.points(), &result.rotation, &result.translation);
+
+        while result.num_iterations < self.params.max_iterations
+            && max_distance >= self.params.min_distance
+        {
+            let (current_source_match, current_target_match, distances) =
+                find_correspondences_within(
+                    &current_source,
+                    target.points(),
+                    &kdtree,
+                    max_distance,
+                );
+
+            log::debug!(
+                "Iteration: {} max distance: {} correspondences: {}",
+                result.num_iterations,
+                max_distance,
+                distances.len()
+            );
+
+            if distances.len() >= 3 {
+                let mut rr_delta = [[0.0; 3]; 3];
+                let mut tt_delta = [0.0; 3];
+                result.diagnostics = Some(fit_transformation(
+                    &current_source_match,
+                    &current_target_match,
+                    &mut rr_delta,
+                    &mut tt_delta,
+                ));
+
+                current_source = transform_points3d_vec(&current_source, &rr_delta, &tt_delta);
+
+                // compose the delta on the left of the current transformation
+                // R_new = R_delta * R_old
+                // t_new = R_delta * t_old + t_delta
+                let mut rotation = [[0.0; 3]; 3];
+                matmul33(&rr_delta, &result.rotation, &mut rotation);
+                let mut translation = [0.0; 3];
+                mat33_mul_vec3(&rr_delta, &result.translation, &mut translation);
+                result.rotation = rotation;
+                result.translation = [
+                    translation[0] + tt_delta[0],
+                    translation[1] + tt_delta[1],
+                    translation[2] + tt_delta[2],
+                ];
+
+                result.rmse = (distances.iter().sum::<f64>() / distances.len() as f64).sqrt();
+            }
+
+            result.num_iterations += 1;
+            max_distance *= self.params.decay_factor;
+        }
+
+        // guard against numerical blowups in the estimated transformation
+        validate_icp_result(
+            &result.rotation,
+            &result.translation,
+            f64::INFINITY,
+            f64::INFINITY,
+        )?;
+
+        Ok(result)
+    }
+}
+
+/// Compute the diagonal of the axis-aligned bounding box of a set of points.
+fn cloud_diameter(points: &[[f64; 3]]) -> f64 {
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for p in points {
+        for k in 0..3 {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+    if points.is_empty() {
+        return 0.0;
+    }
+    ((max[0] - min[0]).powi(2) + (max[1] - min[1]).powi(2) + (max[2] - min[2]).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const IDENTITY: [[f64; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    fn params() -> AdaptiveIcpParams {
+        AdaptiveIcpParams {
+            init_factor: 0.5,
+            decay_factor: 0.85,
+            min_distance: 1e-3,
+            max_iterations: 100,
+        }
+    }
+
+    #[test]
+    fn test_adaptive_icp() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let points_src = (0..500)
+            .map(|_| {
+                [
+                    rng.random_range(0.0..1.0),
+                    rng.random_range(0.0..0.6),
+                    rng.random_range(0.0..0.3),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let dst_r_src = axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.2)?;
+        let dst_t_src = [0.15, -0.1, 0.05];
+        let points_dst = transform_points3d_vec(&points_src, &dst_r_src, &dst_t_src);
+
+        let icp = AdaptiveIcp::new(params())?;
+        let result = icp.register(
+            &PointCloud::new(points_src, None, None),
+            &PointCloud::new(points_dst, None, None),
+            IDENTITY,
+            [0.0; 3],
+        )?;
+
+        for (t, expected) in result.translation.iter().zip(dst_t_src.iter()) {
+            assert_relative_eq!(t, expected, epsilon = 1e-6);
+        }
+        for (row, expected) in result.rotation.iter().zip(dst_r_src.iter()) {
+            for (r, e) in row.iter().zip(expected.iter()) {
+                assert_relative_eq!(r, e, epsilon = 1e-6);
+            }
+        }
+        assert!(result.rmse < 1e-6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_adaptive_icp_schedule() -> Result<(), Box<dyn std::error::Error>> {
+        // the diagonal of the unit cube is sqrt(3)
+        let points = (0..8)
+            .map(|i| [(i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64])
+            .collect::<Vec<_>>();
+        let cloud = PointCloud::new(points, None, None);
+
+        let icp = AdaptiveIcp::new(AdaptiveIcpParams {
+            init_factor: 1.0 / 3.0f64.sqrt(),
+            decay_factor: 0.5,
+            min_distance: 0.1,
+            max_iterations: 100,
+        })?;
+        let result = icp.register(&cloud, &cloud, IDENTITY, [0.0; 3])?;
+
+        // distances 1, 0.5, 0.25, 0.125 are above the floor
+        assert_eq!(result.num_iterations, 4);
+        assert_relative_eq!(result.rmse, 0.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_adaptive_icp_invalid_params() {
+        let mut invalid = params();
+        invalid.decay_factor = 1.0;
+        assert!(matches!(
+            AdaptiveIcp::new(invalid),
+            Err(AdaptiveIcpError::InvalidDecayFactor(_))
+        ));
+
+        let mut invalid = params();
+        invalid.init_factor = 0.0;
+        assert!(matches!(
+            AdaptiveIcp::new(invalid),
+            Err(AdaptiveIcpError::InvalidInitFactor(_))
+        ));
+    }
+}
@@ -0,0 +1,340 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+
+use crate::{
+    estimate_overlap, ops::fit_transformation_weighted, validate_icp_result, ICPResult, IcpError,
+};
+use kornia_3d::{
+    kdtree::KdTree,
+    linalg::{mat33_mul_vec3, matmul33, transform_points3d_vec},
+    pointcloud::PointCloud,
+    stats::entropy_convergence_criterion,
+    transforms::RigidTransform3,
+};
+
+/// Parameters of the density compensated ICP.
+#[derive(Debug, Clone)]
+pub struct DensityIcpParams {
+    /// Maximum number of iterations to perform.
+    pub max_iterations: usize,
+    /// Convergence tolerance as the difference in RMSE between two consecutive iterations.
+    pub tolerance: f64,
+    /// Convergence threshold on the Kullback-Leibler divergence between the residual
+    /// distributions of two consecutive iterations, in nats, see
+    /// [`entropy_convergence_criterion`]. A non-positive threshold disables the criterion.
+    pub entropy_threshold: f64,
+    /// Maximum distance between a source point and its nearest target point to be considered
+    /// a correspondence.
+    pub max_correspondence_distance: f64,
+    /// The number of neighbors to estimate the local density of the source points.
+    pub k: usize,
+}
+
+/// Point to point ICP with the correspondences weighted by the inverse sampling density of
+/// the source points.
+///
+/// A scanner samples the nearby surfaces more densely than the distant ones, so the plain
+/// ICP fits the nearby surfaces at the expense of the rest of the scene. The density of each
+/// source point is the number of its `k` nearest neighbors over the local area they span,
+/// the disk whose radius is the distance to the `k`-th neighbor, as the points of a scan
+/// sample surfaces. Weighting each correspondence by the inverse of this density makes every
+/// unit of surface contribute equally to the fit, whatever its sampling.
+///
+/// # Arguments
+///
+/// * `source` - Source point cloud.
+/// * `target` - Target point cloud.
+/// * `params` - The parameters of the registration.
+///
+/// # Returns
+///
+/// The transformation from the source to the target frame, the number of iterations and
+/// the RMSE of the correspondences of the last iteration.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{density_compensated_icp, DensityIcpParams};
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..900)
+///     .map(|i| {
+///         let (u, v) = ((i % 30) as f64 * 0.05, (i / 30) as f64 * 0.05);
+///         [u, v, 0.2 * (3.0 * u).sin() * (2.0 * v).cos()]
+///     })
+///     .collect::<Vec<_>>();
+/// let moved = points.iter().map(|p| [p[0] + 0.01, p[1], p[2]]).collect();
+///
+/// let params = DensityIcpParams {
+///     max_iterations: 50,
+///     tolerance: 1e-12,
+///     entropy_threshold: 0.0,
+///     max_correspondence_distance: 0.1,
+///     k: 8,
+/// };
+/// let src = PointCloud::new(points, None, None);
+/// let dst = PointCloud::new(moved, None, None);
+/// let result = density_compensated_icp(&src, &dst, &params).unwrap();
+/// assert!((result.translation[0] - 0.01).abs() < 1e-6);
+/// ```
+pub fn density_compensated_icp(
+    source: &PointCloud,
+    target: &PointCloud,
+    params: &DensityIcpParams,
+) -> Result<ICPResult, IcpError> {
+    if source.is_empty() || target.is_empty() {
+        return Err(IcpError::EmptyCloud);
+    }
+
+    let weights = inverse_density_weights(source.points(), params.k);
+
+    let mut result = ICPResult {
+        rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        translation: [0.0; 3],
+        num_iterations: 0,
+        rmse: f64::INFINITY,
+        diagnostics: None,
+        overlap: None,
+    };
+
+    let kdtree: ImmutableKdTree<f64, u32, 3, 32> = ImmutableKdTree::new_from_slice(target.points());
+    let max_sq_distance = params.max_correspondence_distance.powi(2);
+
+    let mut current_source = source.points().clone();
+    let mut prev_rmse = f64::INFINITY;
+    let mut prev_residuals = Vec::new();
+    while result.num_iterations < params.max_iterations {
+        // nearest neighbors within the maximum distance, the distances are squared
+        let mut points_in_src = Vec::new();
+        let mut points_in_dst = Vec::new();
+        let mut correspondence_weights = Vec::new();
+        let mut residuals = Vec::new();
+        for (p, w) in current_source.iter().zip(weights.iter()) {
+            let nn = kdtree.nearest_one::<kiddo::SquaredEuclidean>(p);
+            if nn.distance > max_sq_distance {
+                continue;
+            }
+            points_in_src.push(*p);
+            points_in_dst.push(target.points()[nn.item as usize]);
+            correspondence_weights.push(*w);
+            residuals.push(nn.distance.sqrt());
+        }
+
+        // the fit is undefined without enough weighted correspondences
+        let num_weighted = correspondence_weights.iter().filter(|w| **w > 0.0).count();
+        if num_weighted < 3 {
+            return Err(IcpError::NotEnoughCorrespondences(num_weighted));
+        }
+
+        let mut rr_delta = [[0.0; 3]; 3];
+        let mut tt_delta = [0.0; 3];
+        result.diagnostics = Some(fit_transformation_weighted(
+            &points_in_src,
+            &points_in_dst,
+            &correspondence_weights,
+            &mut rr_delta,
+            &mut tt_delta,
+        ));
+        current_source = transform_points3d_vec(&current_source, &rr_delta, &tt_delta);
+
+        // compose the delta on the left of the current transformation
+        let mut rotation = [[0.0; 3]; 3];
+        matmul33(&rr_delta, &result.rotation, &mut rotation);
+        let mut translation = [0.0; 3];
+        mat33_mul_vec3(&rr_delta, &result.translation, &mut translation);
+        result.rotation = rotation;
+        result.translation = [
+            translation[0] + tt_delta[0],
+            translation[1] + tt_delta[1],
+            translation[2] + tt_delta[2],
+        ];
+
+        let sum_sq_distances = residuals.iter().map(|r| r * r).sum::<f64>();
+        result.rmse = (sum_sq_distances / points_in_src.len() as f64).sqrt();
+        result.num_iterations += 1;
+        log::debug!("Iteration: {} rmse: {}", result.num_iterations, result.rmse);
+        if (prev_rmse - result.rmse).abs() < params.tolerance
+            || entropy_convergence_criterion(&prev_residuals, &residuals, params.entropy_threshold)
+        {
+            break;
+        }
+        prev_rmse = result.rmse;
+        prev_residuals = residuals;
+    }
+
+    result.overlap = Some(estimate_overlap(
+        source,
+        &kdtree,
+        &RigidTransform3::new(result.rotation, result.translation),
+        params.max_correspondence_distance,
+    ));
+
+    // guard against numerical blowups in the estimated transformation
+    validate_icp_result(
+        &result.rotation,
+        &result.translation,
+        f64::INFINITY,
+        f64::INFINITY,
+    )?;
+
+    Ok(result)
+}
+
+/// Compute the inverse of the local density of each point, normalized to a unit mean.
+///
+/// The local area of a point is the disk whose radius is the distance to its `k`-th nearest
+/// neighbor, and its density is `k` over this area. The points with coincident neighbors
+/// have an infinite density and a zero weight.
+fn inverse_density_weights(points: &[[f64; 3]], k: usize) -> Vec<f64> {
+    let kdtree = KdTree::new(points);
+    let weights = points
+        .iter()
+        .map(|p| {
+            // the point itself is its first neighbor
+            let radius = kdtree
+                .nearest_n(p, k + 1)
+                .last()
+                .map_or(0.0, |nn| nn.distance);
+            let density = k as f64 / (std::f64::consts::PI * radius * radius);
+            if density.is_finite() {
+                1.0 / density
+            } else {
+                0.0
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mean = weights.iter().sum::<f64>() / weights.len() as f64;
+    if mean > 0.0 {
+        weights.iter().map(|w| w / mean).collect()
+    } else {
+        weights
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{icp_vanilla, ICPConvergenceCriteria};
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn surface(x: f64, y: f64) -> [f64; 3] {
+        [x, y, 0.2 * (2.0 * x).sin() * (1.5 * y).cos()]
+    }
+
+    /// Samples of the surface on a grid, lifted in the corner patch `[0, 0.5]^2`.
+    fn scan(step: f64, extent: [f64; 2], rng: &mut StdRng) -> Vec<[f64; 3]> {
+        let num = [0, 1].map(|k| (extent[k] / step).round() as usize);
+        (0..num[0] * num[1])
+            .map(|i| {
+                let (x, y) = ((i % num[0]) as f64 * step, (i / num[0]) as f64 * step);
+                let mut p = surface(x, y);
+                p[2] += rng.random_range(-2e-3..2e-3);
+                if x < 0.5 && y < 0.5 {
+                    p[2] += 0.02;
+                }
+                p
+            })
+            .collect()
+    }
+
+    fn params() -> DensityIcpParams {
+        DensityIcpParams {
+            max_iterations: 100,
+            tolerance: 1e-12,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.1,
+            k: 8,
+        }
+    }
+
+    #[test]
+    fn test_density_compensated_icp_oversampled() -> Result<(), Box<dyn std::error::Error>> {
+        let target = (0..100 * 100)
+            .map(|i| surface((i % 100) as f64 * 0.02, (i / 100) as f64 * 0.02))
+            .collect::<Vec<_>>();
+        let target = PointCloud::new(target, None, None);
+
+        // the same scan, with the lifted patch sampled 16 times more densely
+        let mut rng = StdRng::seed_from_u64(0);
+        let uniform = scan(0.1, [2.0, 2.0], &mut rng);
+        let mut oversampled = uniform.clone();
+        oversampled.extend(scan(0.025, [0.5, 0.5], &mut rng));
+
+        // express the scans in their own frame
+        let dst_from_src = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.2, -0.1, 1.0], 0.02)?,
+            [0.02, -0.01, 0.01],
+        );
+        let src_from_dst = dst_from_src.inverse();
+        let to_source = |points: &[[f64; 3]]| {
+            PointCloud::new(
+                points.iter().map(|p| src_from_dst.apply(p)).collect(),
+                None,
+                None,
+            )
+        };
+        let uniform = to_source(&uniform);
+        let oversampled = to_source(&oversampled);
+
+        let translation_error = |result: &ICPResult, expected: &ICPResult| {
+            (0..3)
+                .map(|k| (result.translation[k] - expected.translation[k]).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        };
+        let reference = density_compensated_icp(&uniform, &target, &params())?;
+        let compensated = density_compensated_icp(&oversampled, &target, &params())?;
+        let error = translation_error(&compensated, &reference);
+        assert!(error < 2e-3);
+
+        // the plain ICP is pulled towards the lifted patch
+        let plain = icp_vanilla(
+            &oversampled,
+            &target,
+            [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            [0.0; 3],
+            ICPConvergenceCriteria {
+                max_iterations: 100,
+                tolerance: 1e-12,
+                entropy_threshold: 0.0,
+            },
+        )?;
+        assert!(translation_error(&plain, &reference) > 10.0 * error);
+        Ok(())
+    }
+
+    #[test]
+    fn test_inverse_density_weights() {
+        // a coarse grid next to a grid twice as dense
+        let points = (0..400)
+            .map(|i| {
+                let (u, v) = ((i % 20) as f64, (i / 20) as f64);
+                if i < 200 {
+                    [0.1 * u, 0.1 * v, 0.0]
+                } else {
+                    [0.05 * u, 1.5 + 0.05 * v, 0.0]
+                }
+            })
+            .collect::<Vec<_>>();
+        let weights = inverse_density_weights(&points, 4);
+        let (coarse, dense) = (weights[5 * 20 + 10], weights[200 + 5 * 20 + 10]);
+        assert!((coarse / dense - 4.0).abs() < 1e-9);
+
+        assert_eq!(inverse_density_weights(&[[0.0; 3]; 5], 2), vec![0.0; 5]);
+    }
+
+    #[test]
+    fn test_density_compensated_icp_errors() {
+        let empty = PointCloud::new(vec![], None, None);
+        let cloud = PointCloud::new(vec![[0.0; 3], [1.0, 0.0, 0.0]], None, None);
+        assert!(matches!(
+            density_compensated_icp(&empty, &cloud, &params()),
+            Err(IcpError::EmptyCloud)
+        ));
+        assert!(matches!(
+            density_compensated_icp(&cloud, &cloud, &params()),
+            Err(IcpError::NotEnoughCorrespondences(2))
+        ));
+    }
+}
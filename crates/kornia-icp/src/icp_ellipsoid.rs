@@ -0,0 +1,260 @@
+use crate::{
+    ops::fit_transformation, validate_icp_result, ICPConvergenceCriteria, ICPResult, IcpError,
+};
+use kornia_3d::{
+    fitting::Ellipsoid,
+    linalg::{mat33_mul_vec3, matmul33, transform_points3d_vec},
+    pointcloud::PointCloud,
+    stats::entropy_convergence_criterion,
+};
+
+/// Iterative Closest Ellipsoid (ICE) algorithm registering points to a set of ellipsoids.
+///
+/// The targets are shapes modelled as ellipsoids, e.g. anatomical structures in medical
+/// images. Each iteration projects each transformed source point onto the surface of its
+/// nearest ellipsoid and fits the rigid transformation between the points and their
+/// projections.
+///
+/// # Arguments
+///
+/// * `source` - Source point cloud, sampled on the surfaces of the shapes.
+/// * `target` - The ellipsoids in the target frame.
+/// * `initial_rot` - Initial rotation matrix from the source to the target frame.
+/// * `initial_trans` - Initial translation vector from the source to the target frame.
+/// * `criteria` - Convergence criteria.
+///
+/// # Returns
+///
+/// The transformation from the source to the target frame, the number of iterations and
+/// the RMSE of the distances from the source points to the ellipsoids of the last
+/// iteration.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{icp_ellipsoid, ICPConvergenceCriteria};
+/// use kornia_3d::{fitting::Ellipsoid, pointcloud::PointCloud};
+///
+/// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let ellipsoid = Ellipsoid { center: [0.0; 3], radii: [3.0, 2.0, 1.0], rotation: identity };
+/// // the ends of the axes shifted along x
+/// let points = vec![
+///     [3.2, 0.0, 0.0],
+///     [-2.8, 0.0, 0.0],
+///     [0.2, 2.0, 0.0],
+///     [0.2, -2.0, 0.0],
+///     [0.2, 0.0, 1.0],
+///     [0.2, 0.0, -1.0],
+/// ];
+/// let source = PointCloud::new(points, None, None);
+/// let criteria = ICPConvergenceCriteria {
+///     max_iterations: 100,
+///     tolerance: 1e-12,
+///     entropy_threshold: 0.0,
+/// };
+/// let result = icp_ellipsoid(&source, &[ellipsoid], identity, [0.0; 3], criteria).unwrap();
+/// assert!((result.translation[0] + 0.2).abs() < 1e-3);
+/// ```
+pub fn icp_ellipsoid(
+    source: &PointCloud,
+    target: &[Ellipsoid],
+    initial_rot: [[f64; 3]; 3],
+    initial_trans: [f64; 3],
+    criteria: ICPConvergenceCriteria,
+) -> Result<ICPResult, IcpError> {
+    if source.is_empty() {
+        return Err(IcpError::EmptyCloud);
+    }
+    if target.is_empty() {
+        return Err(IcpError::NotEnoughCorrespondences(0));
+    }
+
+    let mut result = ICPResult {
+        rotation: initial_rot,
+        translation: initial_trans,
+        num_iterations: 0,
+        rmse: f64::INFINITY,
+        diagnostics: None,
+        overlap: None,
+    };
+
+    let mut current_source =
+        transform_points3d_vec(source.points(), &result.rotation, &result.translation);
+
+    let mut prev_rmse = f64::INFINITY;
+    let mut prev_residuals = Vec::new();
+    while result.num_iterations < criteria.max_iterations {
+        // project each point onto the surface of its nearest ellipsoid
+        let mut residuals = Vec::with_capacity(current_source.len());
+        let projections = current_source
+            .iter()
+            .map(|p| {
+                let (closest, sq_distance) = target
+                    .iter()
+                    .map(|ellipsoid| {
+                        let q = ellipsoid.closest_point(p);
+                        let sq_distance =
+                            (p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2);
+                        (q, sq_distance)
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .unwrap_or((*p, 0.0));
+                residuals.push(sq_distance.sqrt());
+                closest
+            })
+            .collect::<Vec<_>>();
+
+        let mut rr_delta = [[0.0; 3]; 3];
+        let mut tt_delta = [0.0; 3];
+        result.diagnostics = Some(fit_transformation(
+            &current_source,
+            &projections,
+            &mut rr_delta,
+            &mut tt_delta,
+        ));
+        current_source = transform_points3d_vec(&current_source, &rr_delta, &tt_delta);
+
+        // compose the delta on the left of the current transformation
+        let mut rotation = [[0.0; 3]; 3];
+        matmul33(&rr_delta, &result.rotation, &mut rotation);
+        let mut translation = [0.0; 3];
+        mat33_mul_vec3(&rr_delta, &result.translation, &mut translation);
+        result.rotation = rotation;
+        result.translation = [
+            translation[0] + tt_delta[0],
+            translation[1] + tt_delta[1],
+            translation[2] + tt_delta[2],
+        ];
+
+        let sum_sq_distances = residuals.iter().map(|r| r * r).sum::<f64>();
+        result.rmse = (sum_sq_distances / current_source.len() as f64).sqrt();
+        result.num_iterations += 1;
+        log::debug!("Iteration: {} rmse: {}", result.num_iterations, result.rmse);
+        if (prev_rmse - result.rmse).abs() < criteria.tolerance
+            || entropy_convergence_criterion(
+                &prev_residuals,
+                &residuals,
+                criteria.entropy_threshold,
+            )
+        {
+            break;
+        }
+        prev_rmse = result.rmse;
+        prev_residuals = residuals;
+    }
+
+    // guard against numerical blowups in the estimated transformation
+    validate_icp_result(
+        &result.rotation,
+        &result.translation,
+        f64::INFINITY,
+        f64::INFINITY,
+    )?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use kornia_3d::transforms::{axis_angle_to_rotation_matrix, RigidTransform3};
+
+    const IDENTITY: [[f64; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    /// Sample the surface of an ellipsoid with a Fibonacci lattice.
+    fn sample(ellipsoid: &Ellipsoid, num_points: usize) -> Vec<[f64; 3]> {
+        let golden = std::f64::consts::PI * (3.0 - 5.0f64.sqrt());
+        (0..num_points)
+            .map(|i| {
+                let z = 1.0 - 2.0 * (i as f64 + 0.5) / num_points as f64;
+                let r = (1.0 - z * z).sqrt();
+                let theta = golden * i as f64;
+                let local = [
+                    ellipsoid.radii[0] * r * theta.cos(),
+                    ellipsoid.radii[1] * r * theta.sin(),
+                    ellipsoid.radii[2] * z,
+                ];
+                let mut p = [0.0; 3];
+                mat33_mul_vec3(&ellipsoid.rotation, &local, &mut p);
+                [0, 1, 2].map(|k| p[k] + ellipsoid.center[k])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_icp_ellipsoid() -> Result<(), Box<dyn std::error::Error>> {
+        // three organs modelled as ellipsoids
+        let organs = [
+            Ellipsoid {
+                center: [0.0, 0.0, 0.0],
+                radii: [3.0, 2.0, 1.5],
+                rotation: axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.3)?,
+            },
+            Ellipsoid {
+                center: [5.0, 1.0, 0.5],
+                radii: [1.5, 1.0, 0.8],
+                rotation: axis_angle_to_rotation_matrix(&[1.0, 0.0, 0.0], 0.7)?,
+            },
+            Ellipsoid {
+                center: [1.0, 4.0, -1.0],
+                radii: [2.0, 1.2, 0.6],
+                rotation: IDENTITY,
+            },
+        ];
+        let points = organs
+            .iter()
+            .flat_map(|organ| sample(organ, 100))
+            .collect::<Vec<_>>();
+
+        let dst_r_src = axis_angle_to_rotation_matrix(&[0.3, -0.2, 1.0], 0.15)?;
+        let dst_t_src = [0.2, -0.3, 0.1];
+        let src_from_dst = RigidTransform3::new(dst_r_src, dst_t_src).inverse();
+        let source = PointCloud::new(
+            points.iter().map(|p| src_from_dst.apply(p)).collect(),
+            None,
+            None,
+        );
+
+        let criteria = ICPConvergenceCriteria {
+            max_iterations: 100,
+            tolerance: 1e-14,
+            entropy_threshold: 0.0,
+        };
+        let result = icp_ellipsoid(&source, &organs, IDENTITY, [0.0; 3], criteria)?;
+        for (t, expected) in result.translation.iter().zip(dst_t_src.iter()) {
+            assert_relative_eq!(t, expected, epsilon = 1e-4);
+        }
+        for (row, expected) in result.rotation.iter().zip(dst_r_src.iter()) {
+            for (r, e) in row.iter().zip(expected.iter()) {
+                assert_relative_eq!(r, e, epsilon = 1e-4);
+            }
+        }
+        assert!(result.rmse < 1e-4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_icp_ellipsoid_errors() {
+        let criteria = ICPConvergenceCriteria {
+            max_iterations: 10,
+            tolerance: 1e-9,
+            entropy_threshold: 0.0,
+        };
+        let ellipsoid = Ellipsoid {
+            center: [0.0; 3],
+            radii: [1.0; 3],
+            rotation: IDENTITY,
+        };
+        let empty = PointCloud::new(vec![], None, None);
+        assert!(matches!(
+            icp_ellipsoid(&empty, &[ellipsoid], IDENTITY, [0.0; 3], criteria.clone()),
+            Err(IcpError::EmptyCloud)
+        ));
+        let cloud = PointCloud::new(vec![[1.0, 0.0, 0.0]], None, None);
+        assert!(matches!(
+            icp_ellipsoid(&cloud, &[], IDENTITY, [0.0; 3], criteria),
+            Err(IcpError::NotEnoughCorrespondences(0))
+        ));
+    }
+}
@@ -0,0 +1,249 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+
+use crate::{ops::fit_transformation_weighted, validate_icp_result, IcpError};
+use kornia_3d::{
+    linalg::{mat33_mul_vec3, matmul33, transform_points3d_vec},
+    pointcloud::PointCloud,
+    stats::entropy_convergence_criterion,
+};
+
+/// Parameters of the Iterative Dual Correspondences registration.
+#[derive(Debug, Clone)]
+pub struct IdcParams {
+    /// Maximum number of iterations to perform.
+    pub max_iterations: usize,
+    /// Convergence tolerance as the difference in RMSE between two consecutive iterations.
+    pub tolerance: f64,
+    /// Convergence threshold on the Kullback-Leibler divergence between the residual
+    /// distributions of two consecutive iterations, in nats, see
+    /// [`entropy_convergence_criterion`]. A non-positive threshold disables the criterion.
+    pub entropy_threshold: f64,
+    /// Maximum distance between two mutual nearest neighbors to be considered a correspondence.
+    pub max_correspondence_distance: f64,
+    /// Distances below this floor are clamped when weighting the correspondences, so that the
+    /// pairs matched by chance do not dominate the fit. Typically the point spacing.
+    pub min_distance: f64,
+}
+
+/// Point cloud registration with Iterative Dual Correspondences (IDC).
+///
+/// At each iteration, the nearest neighbors are searched in both directions, from the
+/// transformed source to the target and from the target to the transformed source, with one
+/// KD-tree per cloud. Only the mutual nearest neighbors are kept as correspondences, which
+/// discards most of the wrong matches in the non-overlapping regions. The correspondences are
+/// weighted by the reciprocal of the product of their source-to-target and target-to-source
+/// distances, so that the closest pairs drive the fit.
+#[derive(Debug, Clone, Copy)]
+pub struct IterativeDualCorrespondences;
+
+impl IterativeDualCorrespondences {
+    /// Register the source point cloud to the destination point cloud.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - Source point cloud.
+    /// * `dst` - Destination point cloud.
+    /// * `params` - The parameters of the registration.
+    ///
+    /// # Returns
+    ///
+    /// The rotation and translation from the source to the destination frame.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_icp::{IdcParams, IterativeDualCorrespondences};
+    /// use kornia_3d::pointcloud::PointCloud;
+    ///
+    /// let points = (0..400)
+    ///     .map(|i| {
+    ///         let (u, v) = ((i % 20) as f64 * 0.05, (i / 20) as f64 * 0.05);
+    ///         [u, v, 0.2 * (3.0 * u).sin() * (2.0 * v).cos()]
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// let moved = points.iter().map(|p| [p[0] + 0.01, p[1], p[2]]).collect();
+    ///
+    /// let params = IdcParams {
+    ///     max_iterations: 50,
+    ///     tolerance: 1e-9,
+    ///     entropy_threshold: 0.0,
+    ///     max_correspondence_distance: 0.1,
+    ///     min_distance: 0.05,
+    /// };
+    /// let src = PointCloud::new(points, None, None);
+    /// let dst = PointCloud::new(moved, None, None);
+    /// let (_, translation) = IterativeDualCorrespondences::register(&src, &dst, &params).unwrap();
+    /// assert!((translation[0] - 0.01).abs() < 1e-6);
+    /// ```
+    pub fn register(
+        src: &PointCloud,
+        dst: &PointCloud,
+        params: &IdcParams,
+    ) -> Result<([[f64; 3]; 3], [f64; 3]), IcpError> {
+        if src.is_empty() || dst.is_empty() {
+            return Err(IcpError::EmptyCloud);
+        }
+
+        let mut rotation = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let mut translation = [0.0; 3];
+
+        let dst_kdtree: ImmutableKdTree<f64, u32, 3, 32> =
+            ImmutableKdTree::new_from_slice(dst.points());
+        let max_sq_distance = params.max_correspondence_distance.powi(2);
+        let min_sq_distance = params.min_distance.powi(2);
+
+        let mut current_src = src.points().to_vec();
+        let mut prev_rmse = f64::INFINITY;
+        let mut prev_residuals = Vec::new();
+        for i in 0..params.max_iterations {
+            let src_kdtree: ImmutableKdTree<f64, u32, 3, 32> =
+                ImmutableKdTree::new_from_slice(&current_src);
+
+            // mutual nearest neighbors, the distances are squared
+            let mut points_in_src = Vec::new();
+            let mut points_in_dst = Vec::new();
+            let mut weights = Vec::new();
+            let mut residuals = Vec::new();
+            for (j, p) in current_src.iter().enumerate() {
+                let forward = dst_kdtree.nearest_one::<kiddo::SquaredEuclidean>(p);
+                if forward.distance > max_sq_distance {
+                    continue;
+                }
+                let q = &dst.points()[forward.item as usize];
+                let backward = src_kdtree.nearest_one::<kiddo::SquaredEuclidean>(q);
+                if backward.item as usize != j {
+                    continue;
+                }
+                // the product of the forward and backward distances
+                let distance_product = (forward.distance.max(min_sq_distance)
+                    * backward.distance.max(min_sq_distance))
+                .sqrt();
+                points_in_src.push(*p);
+                points_in_dst.push(*q);
+                weights.push(1.0 / distance_product);
+                residuals.push(forward.distance.sqrt());
+            }
+
+            if points_in_src.len() < 3 {
+                return Err(IcpError::NotEnoughCorrespondences(points_in_src.len()));
+            }
+
+            let mut rr_delta = [[0.0; 3]; 3];
+            let mut tt_delta = [0.0; 3];
+            fit_transformation_weighted(
+                &points_in_src,
+                &points_in_dst,
+                &weights,
+                &mut rr_delta,
+                &mut tt_delta,
+            );
+            current_src = transform_points3d_vec(&current_src, &rr_delta, &tt_delta);
+
+            // compose the delta on the left of the current transformation
+            let mut new_rotation = [[0.0; 3]; 3];
+            matmul33(&rr_delta, &rotation, &mut new_rotation);
+            let mut new_translation = [0.0; 3];
+            mat33_mul_vec3(&rr_delta, &translation, &mut new_translation);
+            rotation = new_rotation;
+            translation = [
+                new_translation[0] + tt_delta[0],
+                new_translation[1] + tt_delta[1],
+                new_translation[2] + tt_delta[2],
+            ];
+
+            let sum_sq_distances = residuals.iter().map(|r| r * r).sum::<f64>();
+            let rmse = (sum_sq_distances / points_in_src.len() as f64).sqrt();
+            log::debug!(
+                "Iteration: {} correspondences: {} rmse: {}",
+                i,
+                points_in_src.len(),
+                rmse
+            );
+            if (prev_rmse - rmse).abs() < params.tolerance
+                || entropy_convergence_criterion(
+                    &prev_residuals,
+                    &residuals,
+                    params.entropy_threshold,
+                )
+            {
+                break;
+            }
+            prev_rmse = rmse;
+            prev_residuals = residuals;
+        }
+
+        // guard against numerical blowups in the estimated transformation
+        validate_icp_result(&rotation, &translation, f64::INFINITY, f64::INFINITY)?;
+
+        Ok((rotation, translation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use kornia_3d::transforms::{axis_angle_to_rotation_matrix, RigidTransform3};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn params() -> IdcParams {
+        IdcParams {
+            max_iterations: 100,
+            tolerance: 1e-10,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.3,
+            min_distance: 0.02,
+        }
+    }
+
+    #[test]
+    fn test_idc_partial_overlap() -> Result<(), Box<dyn std::error::Error>> {
+        // a bumpy surface seen by two scans overlapping on the half of their extent
+        let mut rng = StdRng::seed_from_u64(3);
+        let points = (0..6000)
+            .map(|_| {
+                let (u, v): (f64, f64) = (rng.random_range(0.0..3.0), rng.random_range(0.0..2.0));
+                [u, v, 0.3 * (2.0 * u).sin() * (3.0 * v).cos()]
+            })
+            .collect::<Vec<_>>();
+
+        let dst_r_src = axis_angle_to_rotation_matrix(&[0.1, 0.2, 1.0], 0.1)?;
+        let dst_t_src = [0.08, -0.05, 0.03];
+
+        // express the source in its own frame
+        let src_from_dst = RigidTransform3::new(dst_r_src, dst_t_src).inverse();
+        let src_points = points
+            .iter()
+            .filter(|p| p[0] < 2.0)
+            .map(|p| src_from_dst.apply(p))
+            .collect();
+        let dst_points = points.iter().filter(|p| p[0] > 1.0).copied().collect();
+
+        let src = PointCloud::new(src_points, None, None);
+        let dst = PointCloud::new(dst_points, None, None);
+        let (rotation, translation) =
+            IterativeDualCorrespondences::register(&src, &dst, &params())?;
+
+        for i in 0..3 {
+            assert_relative_eq!(translation[i], dst_t_src[i], epsilon = 1e-6);
+            for j in 0..3 {
+                assert_relative_eq!(rotation[i][j], dst_r_src[i][j], epsilon = 1e-6);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_idc_errors() {
+        let cloud = PointCloud::new(vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]], None, None);
+        let empty = PointCloud::new(vec![], None, None);
+        assert!(matches!(
+            IterativeDualCorrespondences::register(&empty, &cloud, &params()),
+            Err(IcpError::EmptyCloud)
+        ));
+        assert!(matches!(
+            IterativeDualCorrespondences::register(&cloud, &cloud, &params()),
+            Err(IcpError::NotEnoughCorrespondences(2))
+        ));
+    }
+}
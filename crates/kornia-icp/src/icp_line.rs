@@ -0,0 +1,300 @@
+use kornia_3d::{
+    linalg::{cross_vec3, dot_product3},
+    stats::entropy_convergence_criterion,
+    transforms::RigidTransform3,
+};
+
+use crate::{ops::fit_rigid_transform, validate_icp_result, IcpError, IcpParams};
+
+/// Iterative Closest Line registration of wireframe models.
+///
+/// The edges of a CAD model or the lines extracted from a scan are registered as lines
+/// rather than points, which keeps the registration well posed on sparse wireframes.
+pub struct Icl;
+
+impl Icl {
+    /// Register two sets of 3d lines with the Iterative Closest Line algorithm.
+    ///
+    /// Each line is an `(origin, direction)` pair, the direction spanning the edge from its
+    /// origin. At each iteration:
+    ///
+    /// 1. Each transformed source line is matched to the target line with the smallest
+    ///    Plücker distance, `|d1 - d2|^2 + |m1 - m2|^2` with the unit directions `d` and the
+    ///    moments `m = o x d`, up to the orientation of the lines.
+    /// 2. `samples_per_edge` points are sampled along each matched source edge and paired
+    ///    with their projections on the target line.
+    /// 3. The transformation minimizing the distances of the pairs is solved in closed form
+    ///    with the Kabsch fit, which minimizes the point-to-line distances as the projections
+    ///    are updated.
+    ///
+    /// The registration starts from the identity and stops when the RMSE of the
+    /// point-to-line distances changes less than the tolerance.
+    ///
+    /// # Arguments
+    ///
+    /// * `src_lines` - The source lines.
+    /// * `dst_lines` - The target lines.
+    /// * `params` - The parameters of the registration. A correspondence is rejected when the
+    ///   RMS distance of the samples of its source edge to the target line exceeds the
+    ///   maximum correspondence distance.
+    /// * `samples_per_edge` - The number of points sampled along each source edge, at least two.
+    ///
+    /// # Returns
+    ///
+    /// The rotation and translation from the source to the target frame. The registration
+    /// stops at the last estimate when fewer than two lines are matched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IcpError::Validation`] if the estimated transformation is not a valid rigid
+    /// transformation, e.g. after a numerical blowup.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_icp::{Icl, IcpParams};
+    ///
+    /// // three edges of a corner, and the same edges shifted along z
+    /// let src = [
+    ///     ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0]),
+    ///     ([0.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ///     ([0.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+    /// ];
+    /// let dst = src.map(|(o, d)| ([o[0], o[1], o[2] + 0.05], d));
+    /// let params = IcpParams {
+    ///     max_iterations: 50,
+    ///     tolerance: 1e-12,
+    ///     entropy_threshold: 0.0,
+    ///     max_correspondence_distance: 0.5,
+    /// };
+    /// let (_, translation) = Icl::register(&src, &dst, &params, 10).unwrap();
+    /// assert!((translation[2] - 0.05).abs() < 1e-6);
+    /// ```
+    pub fn register(
+        src_lines: &[([f64; 3], [f64; 3])],
+        dst_lines: &[([f64; 3], [f64; 3])],
+        params: &IcpParams,
+        samples_per_edge: usize,
+    ) -> Result<([[f64; 3]; 3], [f64; 3]), IcpError> {
+        let num_samples = samples_per_edge.max(2);
+        let dst_plucker = dst_lines
+            .iter()
+            .map(|(o, d)| plucker(o, d))
+            .collect::<Vec<_>>();
+
+        let mut transform = RigidTransform3::identity();
+        let mut prev_rmse = f64::INFINITY;
+        let mut prev_residuals = Vec::new();
+        for _ in 0..params.max_iterations {
+            let mut points_in_src = Vec::new();
+            let mut points_in_dst = Vec::new();
+            let mut residuals = Vec::new();
+            let mut num_matches = 0;
+            for (origin, direction) in src_lines {
+                let o = transform.apply(origin);
+                let d = RigidTransform3::new(transform.rotation, [0.0; 3]).apply(direction);
+                let Some(line) = plucker(&o, &d) else {
+                    continue;
+                };
+                let Some(j) = closest_line(&line, &dst_plucker) else {
+                    continue;
+                };
+                let Some((target_direction, _)) = dst_plucker[j] else {
+                    continue;
+                };
+                let target_origin = dst_lines[j].0;
+
+                // the samples of the edge and their projections on the target line
+                let pairs = (0..num_samples)
+                    .map(|k| {
+                        let s = k as f64 / (num_samples - 1) as f64;
+                        let p = [0, 1, 2].map(|a| o[a] + s * d[a]);
+                        let offset = [0, 1, 2].map(|a| p[a] - target_origin[a]);
+                        let along = dot_product3(&offset, &target_direction);
+                        let q = [0, 1, 2].map(|a| target_origin[a] + along * target_direction[a]);
+                        (p, q)
+                    })
+                    .collect::<Vec<_>>();
+                let distances = pairs
+                    .iter()
+                    .map(|(p, q)| (0..3).map(|a| (p[a] - q[a]).powi(2)).sum::<f64>().sqrt())
+                    .collect::<Vec<_>>();
+                let sum_sq = distances.iter().map(|d| d * d).sum::<f64>();
+                if (sum_sq / num_samples as f64).sqrt() > params.max_correspondence_distance {
+                    continue;
+                }
+
+                let inverse = transform.inverse();
+                for (p, q) in pairs {
+                    points_in_src.push(inverse.apply(&p));
+                    points_in_dst.push(q);
+                }
+                residuals.extend(distances);
+                num_matches += 1;
+            }
+            if num_matches < 2 {
+                break;
+            }
+
+            // fit the whole transformation from the original source points
+            let Some(fitted) = fit_rigid_transform(&points_in_src, &points_in_dst) else {
+                break;
+            };
+            transform = fitted;
+
+            let sum_sq_distances = residuals.iter().map(|r| r * r).sum::<f64>();
+            let rmse = (sum_sq_distances / points_in_src.len() as f64).sqrt();
+            if (prev_rmse - rmse).abs() < params.tolerance
+                || entropy_convergence_criterion(
+                    &prev_residuals,
+                    &residuals,
+                    params.entropy_threshold,
+                )
+            {
+                break;
+            }
+            prev_rmse = rmse;
+            prev_residuals = residuals;
+        }
+
+        // guard against numerical blowups in the estimated transformation
+        validate_icp_result(
+            &transform.rotation,
+            &transform.translation,
+            f64::INFINITY,
+            f64::INFINITY,
+        )?;
+
+        Ok((transform.rotation, transform.translation))
+    }
+}
+
+/// Compute the unit direction and the moment of a line, or `None` for a zero direction.
+fn plucker(origin: &[f64; 3], direction: &[f64; 3]) -> Option<([f64; 3], [f64; 3])> {
+    let norm = dot_product3(direction, direction).sqrt();
+    if norm <= 0.0 {
+        return None;
+    }
+    let d = direction.map(|v| v / norm);
+    let mut m = [0.0; 3];
+    cross_vec3(origin, &d, &mut m);
+    Some((d, m))
+}
+
+/// Find the line with the smallest Plücker distance to a line, whatever their orientations.
+fn closest_line(
+    line: &([f64; 3], [f64; 3]),
+    candidates: &[Option<([f64; 3], [f64; 3])>],
+) -> Option<usize> {
+    let (d1, m1) = line;
+    candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(j, candidate)| {
+            let (d2, m2) = candidate.as_ref()?;
+            let distance = |sign: f64| {
+                (0..3)
+                    .map(|a| (d1[a] - sign * d2[a]).powi(2) + (m1[a] - sign * m2[a]).powi(2))
+                    .sum::<f64>()
+            };
+            Some((j, distance(1.0).min(distance(-1.0))))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(j, _)| j)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+
+    /// The twelve edges of an axis-aligned box from the origin to `size`.
+    fn box_edges(size: [f64; 3]) -> Vec<([f64; 3], [f64; 3])> {
+        let mut edges = Vec::new();
+        for axis in 0..3 {
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+            for corner in 0..4 {
+                let mut origin = [0.0; 3];
+                origin[u] = if corner & 1 == 1 { size[u] } else { 0.0 };
+                origin[v] = if corner & 2 == 2 { size[v] } else { 0.0 };
+                let mut direction = [0.0; 3];
+                direction[axis] = size[axis];
+                edges.push((origin, direction));
+            }
+        }
+        edges
+    }
+
+    #[test]
+    fn test_icl_register() -> Result<(), Box<dyn std::error::Error>> {
+        let src = box_edges([2.0, 1.0, 0.5]);
+        let pose = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.2, -0.5, 1.0], 0.1)?,
+            [0.1, -0.05, 0.08],
+        );
+
+        // the target edges are parametrized differently: reversed, and from another origin
+        let dst = src
+            .iter()
+            .enumerate()
+            .map(|(i, (o, d))| {
+                let end = [0, 1, 2].map(|a| o[a] + d[a]);
+                let start = [0, 1, 2].map(|a| o[a] + 0.3 * d[a]);
+                if i % 2 == 0 {
+                    (
+                        pose.apply(&end),
+                        RigidTransform3::new(pose.rotation, [0.0; 3]).apply(&d.map(|v| -v)),
+                    )
+                } else {
+                    (
+                        pose.apply(&start),
+                        RigidTransform3::new(pose.rotation, [0.0; 3]).apply(d),
+                    )
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let params = IcpParams {
+            max_iterations: 500,
+            tolerance: 1e-14,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.5,
+        };
+        let (rotation, translation) = Icl::register(&src, &dst, &params, 20)?;
+        let error = RigidTransform3::new(rotation, translation).compose(&pose.inverse());
+        assert!(error.rotation_angle() < 1e-6);
+        assert!(error.translation.iter().all(|t| t.abs() < 1e-6));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plucker_closest_line() {
+        let lines = [
+            ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0]),
+            ([0.0, 0.0, 0.0], [0.0, 0.0, 2.0]),
+        ]
+        .map(|(o, d)| plucker(&o, &d));
+        // the same line with the opposite orientation and another origin
+        let query = plucker(&[3.0, 1.0, 0.0], &[-2.0, 0.0, 0.0]).unwrap();
+        assert_eq!(closest_line(&query, &lines), Some(1));
+        assert!(plucker(&[1.0; 3], &[0.0; 3]).is_none());
+    }
+
+    #[test]
+    fn test_icl_register_no_match() -> Result<(), Box<dyn std::error::Error>> {
+        let params = IcpParams {
+            max_iterations: 10,
+            tolerance: 1e-9,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.1,
+        };
+        let src = [([0.0; 3], [1.0, 0.0, 0.0])];
+        let identity = RigidTransform3::identity();
+        assert_eq!(
+            Icl::register(&src, &src, &params, 5)?,
+            (identity.rotation, identity.translation)
+        );
+        Ok(())
+    }
+}
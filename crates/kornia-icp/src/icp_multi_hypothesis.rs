@@ -0,0 +1,294 @@
+use rayon::prelude::*;
+
+use kornia_3d::{
+    kdtree::KdTree, stats::entropy_convergence_criterion, transforms::RigidTransform3,
+};
+
+use crate::{ops::fit_rigid_transform, validate_icp_result, IcpError, IcpParams};
+
+/// Register a model to a scan from several initial guesses and keep the best registration.
+///
+/// ICP only converges to the pose nearest to its initial guess, so the pose of an object
+/// with symmetries or an unknown orientation, e.g. a part in a bin, is searched from several
+/// hypotheses. A point to point ICP is run from each hypothesis in parallel, and the
+/// registration with the best fitness, the fraction of the source points with a target
+/// point within the maximum correspondence distance, is kept, the lowest RMSE of these
+/// correspondences breaking the ties.
+///
+/// # Arguments
+///
+/// * `src` - The points of the model.
+/// * `dst` - The points of the scan.
+/// * `initial_guesses` - The hypotheses of the rotation and translation from the source to
+///   the target frame. The identity is used if there is none.
+/// * `params` - The parameters of each registration.
+/// * `max_samples` - The source is evenly subsampled to at most this number of points, or
+///   not at all if zero.
+///
+/// # Returns
+///
+/// The rotation and translation from the source to the target frame of the best
+/// registration, or the identity if either cloud is empty.
+///
+/// # Errors
+///
+/// Returns [`IcpError::Validation`] if the estimated transformation is not a valid rigid
+/// transformation, e.g. after a numerical blowup.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{multi_hypothesis_icp, IcpParams};
+///
+/// // an L of unequal legs seen rotated by a half turn about z
+/// let src = (0..30)
+///     .map(|k| [k as f64 * 0.01, 0.0, 0.0])
+///     .chain((1..12).map(|k| [0.0, k as f64 * 0.01, 0.0]))
+///     .collect::<Vec<_>>();
+/// let dst = src.iter().map(|p| [-p[0], -p[1], p[2]]).collect::<Vec<_>>();
+///
+/// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let half_turn = [[-1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let params = IcpParams {
+///     max_iterations: 50,
+///     tolerance: 1e-12,
+///     entropy_threshold: 0.0,
+///     max_correspondence_distance: 0.05,
+/// };
+/// let (rotation, _) = multi_hypothesis_icp(
+///     &src,
+///     &dst,
+///     &[(identity, [0.0; 3]), (half_turn, [0.003, 0.0, 0.0])],
+///     &params,
+///     0,
+/// )
+/// .unwrap();
+/// assert!((rotation[0][0] + 1.0).abs() < 1e-9);
+/// ```
+pub fn multi_hypothesis_icp(
+    src: &[[f64; 3]],
+    dst: &[[f64; 3]],
+    initial_guesses: &[([[f64; 3]; 3], [f64; 3])],
+    params: &IcpParams,
+    max_samples: usize,
+) -> Result<([[f64; 3]; 3], [f64; 3]), IcpError> {
+    let identity = RigidTransform3::identity();
+    if src.is_empty() || dst.is_empty() {
+        return Ok((identity.rotation, identity.translation));
+    }
+
+    let step = match max_samples {
+        0 => 1,
+        n => src.len().div_ceil(n).max(1),
+    };
+    let samples = src.iter().step_by(step).copied().collect::<Vec<_>>();
+    let index = KdTree::new(dst);
+
+    let guesses = match initial_guesses {
+        [] => vec![(identity.rotation, identity.translation)],
+        guesses => guesses.to_vec(),
+    };
+    let registrations = guesses
+        .par_iter()
+        .map(|(rotation, translation)| {
+            let initial = RigidTransform3::new(*rotation, *translation);
+            let transform = register_from(&samples, dst, &index, initial, params);
+            let (fitness, rmse) = fitness_score(&samples, &index, &transform, params);
+            (transform, fitness, rmse)
+        })
+        .collect::<Vec<_>>();
+
+    // the first of the best registrations, whatever the scheduling of the threads
+    let mut best: Option<(RigidTransform3, f64, f64)> = None;
+    for (transform, fitness, rmse) in registrations {
+        if best.map_or(true, |(_, f, r)| fitness > f || (fitness == f && rmse < r)) {
+            best = Some((transform, fitness, rmse));
+        }
+    }
+    let transform = best.map_or(identity, |(transform, _, _)| transform);
+
+    // guard against numerical blowups in the estimated transformation
+    validate_icp_result(
+        &transform.rotation,
+        &transform.translation,
+        f64::INFINITY,
+        f64::INFINITY,
+    )?;
+
+    Ok((transform.rotation, transform.translation))
+}
+
+/// Point to point ICP from an initial transformation.
+pub(crate) fn register_from(
+    samples: &[[f64; 3]],
+    dst: &[[f64; 3]],
+    index: &KdTree,
+    initial: RigidTransform3,
+    params: &IcpParams,
+) -> RigidTransform3 {
+    let mut transform = initial;
+    let mut prev_rmse = f64::INFINITY;
+    let mut prev_residuals = Vec::new();
+    for _ in 0..params.max_iterations {
+        let mut points_in_src = Vec::new();
+        let mut points_in_dst = Vec::new();
+        let mut residuals = Vec::new();
+        for p in samples.iter() {
+            let Some(nn) = index.nearest_one(&transform.apply(p)) else {
+                continue;
+            };
+            if nn.distance > params.max_correspondence_distance {
+                continue;
+            }
+            points_in_src.push(*p);
+            points_in_dst.push(dst[nn.index]);
+            residuals.push(nn.distance);
+        }
+        if points_in_src.len() < 3 {
+            break;
+        }
+
+        // fit the whole transformation from the original source points
+        let Some(fitted) = fit_rigid_transform(&points_in_src, &points_in_dst) else {
+            break;
+        };
+        transform = fitted;
+
+        let sum_sq_distances = residuals.iter().map(|r| r * r).sum::<f64>();
+        let rmse = (sum_sq_distances / points_in_src.len() as f64).sqrt();
+        if (prev_rmse - rmse).abs() < params.tolerance
+            || entropy_convergence_criterion(&prev_residuals, &residuals, params.entropy_threshold)
+        {
+            break;
+        }
+        prev_rmse = rmse;
+        prev_residuals = residuals;
+    }
+    transform
+}
+
+/// Compute the fraction of the samples with a target point within the maximum
+/// correspondence distance once transformed, and the RMSE of these correspondences.
+fn fitness_score(
+    samples: &[[f64; 3]],
+    index: &KdTree,
+    transform: &RigidTransform3,
+    params: &IcpParams,
+) -> (f64, f64) {
+    let distances = samples
+        .iter()
+        .filter_map(|p| index.nearest_one(&transform.apply(p)))
+        .map(|nn| nn.distance)
+        .filter(|d| *d <= params.max_correspondence_distance)
+        .collect::<Vec<_>>();
+    if distances.is_empty() {
+        return (0.0, f64::INFINITY);
+    }
+    let rmse = (distances.iter().map(|d| d * d).sum::<f64>() / distances.len() as f64).sqrt();
+    (distances.len() as f64 / samples.len() as f64, rmse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const SEED: u64 = 5;
+
+    /// Sample the faces of a bracket: an L profile of unequal legs extruded along z.
+    fn bracket(rng: &mut StdRng) -> Vec<[f64; 3]> {
+        (0..1500)
+            .map(|i| {
+                let z = rng.random_range(0.0..0.05);
+                match i % 2 {
+                    0 => [rng.random_range(0.0..0.3), 0.0, z],
+                    _ => [0.0, rng.random_range(0.0..0.12), z],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_multi_hypothesis_icp_bin_picking() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let model = bracket(&mut rng);
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 2.4)?,
+            [0.5, 0.3, 0.02],
+        );
+
+        // the part lying in a bin, next to another object
+        let mut scene = bracket(&mut rng)
+            .iter()
+            .map(|p| truth.apply(p))
+            .collect::<Vec<_>>();
+        for _ in 0..1000 {
+            let angle = rng.random_range(0.0..std::f64::consts::TAU);
+            scene.push([
+                0.1 + 0.05 * angle.cos(),
+                0.8 + 0.05 * angle.sin(),
+                rng.random_range(0.0..0.2),
+            ]);
+        }
+
+        // hypotheses every quarter turn about the vertical, at the center of the part
+        let center = [0.5, 0.3, 0.02];
+        let guesses = (0..4)
+            .map(|k| {
+                let rotation = axis_angle_to_rotation_matrix(
+                    &[0.0, 0.0, 1.0],
+                    k as f64 * std::f64::consts::FRAC_PI_2,
+                )?;
+                Ok((rotation, center))
+            })
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+
+        let params = IcpParams {
+            max_iterations: 100,
+            tolerance: 1e-10,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.05,
+        };
+        let (rotation, translation) = multi_hypothesis_icp(&model, &scene, &guesses, &params, 500)?;
+        let error = RigidTransform3::new(rotation, translation).compose(&truth.inverse());
+        assert!(error.rotation_angle() < 0.02);
+        assert!(error.translation.iter().all(|t| t.abs() < 0.01));
+
+        // a single hypothesis, off by more than a quarter turn, converges elsewhere
+        let (rotation, translation) =
+            multi_hypothesis_icp(&model, &scene, &guesses[..1], &params, 500)?;
+        let error = RigidTransform3::new(rotation, translation).compose(&truth.inverse());
+        assert!(error.rotation_angle() > 0.5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_hypothesis_icp_no_guess() -> Result<(), Box<dyn std::error::Error>> {
+        let params = IcpParams {
+            max_iterations: 20,
+            tolerance: 1e-12,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.5,
+        };
+        let src = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 2.0, 0.0],
+            [0.0, 0.0, 3.0],
+        ];
+        let dst = src
+            .iter()
+            .map(|p| [p[0] + 0.1, p[1], p[2]])
+            .collect::<Vec<_>>();
+        let (_, translation) = multi_hypothesis_icp(&src, &dst, &[], &params, 0)?;
+        assert!((translation[0] - 0.1).abs() < 1e-9);
+
+        let identity = RigidTransform3::identity();
+        assert_eq!(
+            multi_hypothesis_icp(&[], &dst, &[], &params, 0)?,
+            (identity.rotation, identity.translation)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,505 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+
+use crate::{
+    estimate_overlap,
+    ops::{find_correspondences_within, fit_transformation},
+    suggest_icp_params, validate_icp_result, ICPResult, IcpError,
+};
+use kornia_3d::{
+    density::DensityStats,
+    filters::{deduplicate, DedupPolicy},
+    linalg::{mat33_mul_vec3, matmul33, transform_points3d_vec},
+    pointcloud::PointCloud,
+    stats::entropy_convergence_criterion,
+    transforms::RigidTransform3,
+};
+
+/// A level of the multi-scale ICP.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MultiScaleLevel {
+    /// Voxel size used to downsample both clouds, or `None` to use them at full resolution.
+    pub voxel_size: Option<f64>,
+    /// Maximum distance between two points to be considered a correspondence.
+    pub max_correspondence_distance: f64,
+    /// Maximum number of iterations to perform at this level.
+    pub max_iterations: usize,
+}
+
+/// Configuration of the multi-scale ICP.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiScaleConfig {
+    /// The levels, from the coarsest to the finest.
+    pub levels: Vec<MultiScaleLevel>,
+    /// Convergence tolerance of each level as the difference in RMSE between two consecutive iterations.
+    pub tolerance: f64,
+    /// Convergence threshold on the Kullback-Leibler divergence between the residual
+    /// distributions of two consecutive iterations, in nats, see
+    /// [`entropy_convergence_criterion`]. A non-positive threshold disables the criterion.
+    pub entropy_threshold: f64,
+    /// The maximum fraction of the source points kept as correspondences at each iteration,
+    /// the closest ones, in `(0, 1]`. A fraction below one makes a trimmed ICP for partially
+    /// overlapping clouds, set to their overlap.
+    pub trim_fraction: f64,
+}
+
+/// Minimum number of points expected to survive the downsampling of the coarsest level of
+/// [`MultiScaleConfig::auto`].
+const AUTO_MIN_POINTS: f64 = 1000.0;
+
+/// Minimum number of voxels across the largest extent of the cloud at the coarsest level of
+/// [`MultiScaleConfig::auto`].
+const AUTO_MIN_VOXELS_ACROSS: f64 = 10.0;
+
+/// Minimum ratio between the voxel sizes of consecutive levels of [`MultiScaleConfig::auto`].
+const AUTO_MIN_LEVEL_RATIO: f64 = 1.5;
+
+impl MultiScaleConfig {
+    /// Choose the levels of the multi-scale ICP from the density of the target cloud.
+    ///
+    /// The voxel sizes form a geometric sequence from the voxel size `v = 2 * s` suggested by
+    /// [`suggest_icp_params`] from the median nearest neighbor spacing `s`, up to the coarsest
+    /// voxel size that keeps about a thousand points. The clouds are assumed to sample
+    /// surfaces, whose area is about `N * p95^2` for `N` points with the 95th percentile `p95`
+    /// of the nearest neighbor spacing, for regular and random samplings alike. Downsampling
+    /// them to voxels of size `w` then keeps about `N * (p95 / w)^2` points. The coarsest
+    /// voxels are also bounded to a tenth of the largest extent of the cloud, to preserve its
+    /// overall shape.
+    ///
+    /// Fewer levels than requested are used when consecutive voxel sizes would differ by
+    /// less than a factor 1.5, down to a single level. A cloud of at most a thousand points is
+    /// registered at full resolution. Each level searches the correspondences within two
+    /// voxels, as the presets of [`crate::align`].
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - The density statistics of the target cloud.
+    /// * `target_levels` - The maximum number of levels, at least one level is used.
+    ///
+    /// # Returns
+    ///
+    /// The configuration of the multi-scale ICP, from the coarsest to the finest level.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_icp::MultiScaleConfig;
+    /// use kornia_3d::{density::estimate_density, pointcloud::PointCloud};
+    ///
+    /// let points = (0..40_000)
+    ///     .map(|i| [(i % 200) as f64 * 0.01, (i / 200) as f64 * 0.01, 0.0])
+    ///     .collect();
+    /// let stats = estimate_density(&PointCloud::new(points, None, None), 1000, 0).unwrap();
+    /// let config = MultiScaleConfig::auto(&stats, 3);
+    /// assert_eq!(config.levels.len(), 3);
+    /// // about 1000 points survive at the coarsest level
+    /// assert!((config.levels[0].voxel_size.unwrap() - 0.0632).abs() < 1e-4);
+    /// assert!((config.levels[2].voxel_size.unwrap() - 0.02).abs() < 1e-9);
+    /// ```
+    pub fn auto(stats: &DensityStats, target_levels: usize) -> Self {
+        let suggested = suggest_icp_params(stats);
+        let finest = suggested.voxel_size;
+        let max_extent = stats.extent.iter().fold(0.0f64, |acc, e| acc.max(*e));
+        let coarsest = (stats.p95_nn_dist * (stats.num_points as f64 / AUTO_MIN_POINTS).sqrt())
+            .min(max_extent / AUTO_MIN_VOXELS_ACROSS);
+
+        let level = |voxel_size: f64| MultiScaleLevel {
+            voxel_size: Some(voxel_size),
+            max_correspondence_distance: 2.0 * voxel_size,
+            max_iterations: 50,
+        };
+        let levels = if stats.num_points as f64 <= AUTO_MIN_POINTS {
+            // downsampling would leave too few points
+            vec![MultiScaleLevel {
+                voxel_size: None,
+                max_correspondence_distance: suggested.max_correspondence_distance,
+                max_iterations: 50,
+            }]
+        } else if coarsest <= finest * AUTO_MIN_LEVEL_RATIO {
+            vec![level(coarsest.min(finest))]
+        } else {
+            let max_levels = 1 + ((coarsest / finest).ln() / AUTO_MIN_LEVEL_RATIO.ln()) as usize;
+            let num_levels = target_levels.clamp(1, max_levels);
+            if num_levels == 1 {
+                vec![level(finest)]
+            } else {
+                let ratio = (coarsest / finest).powf(1.0 / (num_levels - 1) as f64);
+                (0..num_levels)
+                    .rev()
+                    .map(|k| level(finest * ratio.powi(k as i32)))
+                    .collect()
+            }
+        };
+
+        Self {
+            levels,
+            tolerance: 1e-4 * suggested.max_correspondence_distance,
+            entropy_threshold: 0.0,
+            trim_fraction: 1.0,
+        }
+    }
+}
+
+/// Summary of the registration at a level of the multi-scale ICP.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelReport {
+    /// The level.
+    pub level: MultiScaleLevel,
+    /// The number of source points after downsampling.
+    pub num_source_points: usize,
+    /// The number of target points after downsampling.
+    pub num_target_points: usize,
+    /// The number of iterations performed at this level.
+    pub num_iterations: usize,
+    /// The RMSE of the correspondences of the last iteration.
+    pub rmse: f64,
+    /// The fraction of source points with a correspondence at the last iteration.
+    pub fitness: f64,
+}
+
+/// Coarse-to-fine point to point ICP on a pyramid of downsampled clouds.
+///
+/// At each level, both clouds are downsampled to the centroids of the occupied voxels and
+/// registered with correspondences closer than the maximum correspondence distance of the
+/// level, starting from the transformation estimated at the previous level. Coarse levels
+/// with large distances recover large displacements cheaply, while the fine levels refine
+/// the transformation. With a trimming fraction below one, only the closest correspondences
+/// are kept, up to that fraction of the source points.
+///
+/// # Arguments
+///
+/// * `source` - Source point cloud.
+/// * `target` - Target point cloud.
+/// * `initial_rot` - Initial rotation matrix from the source to the target frame.
+/// * `initial_trans` - Initial translation vector from the source to the target frame.
+/// * `config` - The levels of the pyramid and the convergence tolerance.
+///
+/// # Returns
+///
+/// The transformation from the source to the target frame with the total number of
+/// iterations, the RMSE and the overlap of the finest level, and the summary of each level.
+pub fn icp_multiscale(
+    source: &PointCloud,
+    target: &PointCloud,
+    initial_rot: [[f64; 3]; 3],
+    initial_trans: [f64; 3],
+    config: &MultiScaleConfig,
+) -> Result<(ICPResult, Vec<LevelReport>), IcpError> {
+    if source.is_empty() || target.is_empty() {
+        return Err(IcpError::EmptyCloud);
+    }
+
+    let mut result = ICPResult {
+        rotation: initial_rot,
+        translation: initial_trans,
+        num_iterations: 0,
+        rmse: f64::INFINITY,
+        diagnostics: None,
+        overlap: None,
+    };
+    let mut reports = Vec::with_capacity(config.levels.len());
+
+    for level in config.levels.iter() {
+        let (level_source, level_target) = match level.voxel_size {
+            Some(voxel_size) => (
+                deduplicate(source, voxel_size, DedupPolicy::Centroid).0,
+                deduplicate(target, voxel_size, DedupPolicy::Centroid).0,
+            ),
+            None => (source.clone(), target.clone()),
+        };
+
+        let kdtree: ImmutableKdTree<f64, u32, 3, 32> =
+            ImmutableKdTree::new_from_slice(level_target.points());
+        let mut current_source =
+            transform_points3d_vec(level_source.points(), &result.rotation, &result.translation);
+
+        let mut report = LevelReport {
+            level: *level,
+            num_source_points: level_source.len(),
+            num_target_points: level_target.len(),
+            num_iterations: 0,
+            rmse: f64::INFINITY,
+            fitness: 0.0,
+        };
+
+        let mut prev_residuals = Vec::new();
+        for _ in 0..level.max_iterations {
+            let (mut current_source_match, mut current_target_match, mut distances) =
+                find_correspondences_within(
+                    &current_source,
+                    level_target.points(),
+                    &kdtree,
+                    level.max_correspondence_distance,
+                );
+            let max_correspondences =
+                (config.trim_fraction * current_source.len() as f64).ceil() as usize;
+            if distances.len() > max_correspondences {
+                (current_source_match, current_target_match, distances) = trim_correspondences(
+                    current_source_match,
+                    current_target_match,
+                    distances,
+                    max_correspondences,
+                );
+            }
+            if distances.len() < 3 {
+                return Err(IcpError::NotEnoughCorrespondences(distances.len()));
+            }
+
+            let mut rr_delta = [[0.0; 3]; 3];
+            let mut tt_delta = [0.0; 3];
+            result.diagnostics = Some(fit_transformation(
+                &current_source_match,
+                &current_target_match,
+                &mut rr_delta,
+                &mut tt_delta,
+            ));
+            current_source = transform_points3d_vec(&current_source, &rr_delta, &tt_delta);
+
+            // compose the delta on the left of the current transformation
+            let mut rotation = [[0.0; 3]; 3];
+            matmul33(&rr_delta, &result.rotation, &mut rotation);
+            let mut translation = [0.0; 3];
+            mat33_mul_vec3(&rr_delta, &result.translation, &mut translation);
+            result.rotation = rotation;
+            result.translation = [
+                translation[0] + tt_delta[0],
+                translation[1] + tt_delta[1],
+                translation[2] + tt_delta[2],
+            ];
+
+            let rmse = (distances.iter().sum::<f64>() / distances.len() as f64).sqrt();
+            let residuals = distances.iter().map(|d| d.sqrt()).collect::<Vec<_>>();
+            let converged = (report.rmse - rmse).abs() < config.tolerance
+                || entropy_convergence_criterion(
+                    &prev_residuals,
+                    &residuals,
+                    config.entropy_threshold,
+                );
+            report.rmse = rmse;
+            report.fitness = distances.len() as f64 / current_source.len() as f64;
+            report.num_iterations += 1;
+            if converged {
+                break;
+            }
+            prev_residuals = residuals;
+        }
+
+        log::debug!(
+            "Level {:?}: {} iterations, rmse {}, fitness {}",
+            level.voxel_size,
+            report.num_iterations,
+            report.rmse,
+            report.fitness
+        );
+
+        result.num_iterations += report.num_iterations;
+        result.rmse = report.rmse;
+        result.overlap = Some(estimate_overlap(
+            &level_source,
+            &kdtree,
+            &RigidTransform3::new(result.rotation, result.translation),
+            level.max_correspondence_distance,
+        ));
+        reports.push(report);
+    }
+
+    // guard against numerical blowups in the estimated transformation
+    validate_icp_result(
+        &result.rotation,
+        &result.translation,
+        f64::INFINITY,
+        f64::INFINITY,
+    )?;
+
+    Ok((result, reports))
+}
+
+/// Keep the correspondences with the smallest distances.
+fn trim_correspondences(
+    source_match: Vec<[f64; 3]>,
+    target_match: Vec<[f64; 3]>,
+    distances: Vec<f64>,
+    num_kept: usize,
+) -> (Vec<[f64; 3]>, Vec<[f64; 3]>, Vec<f64>) {
+    let mut order = (0..distances.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| distances[a].total_cmp(&distances[b]));
+    order.truncate(num_kept);
+
+    let mut trimmed = (Vec::new(), Vec::new(), Vec::new());
+    for i in order {
+        trimmed.0.push(source_match[i]);
+        trimmed.1.push(target_match[i]);
+        trimmed.2.push(distances[i]);
+    }
+    trimmed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use kornia_3d::{density::estimate_density, transforms::axis_angle_to_rotation_matrix};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const IDENTITY: [[f64; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    #[test]
+    fn test_icp_multiscale() -> Result<(), Box<dyn std::error::Error>> {
+        // a bumpy surface densely sampled
+        let mut rng = StdRng::seed_from_u64(7);
+        let points = (0..5000)
+            .map(|_| {
+                let (u, v): (f64, f64) = (rng.random_range(0.0..2.0), rng.random_range(0.0..2.0));
+                [u, v, 0.3 * (2.0 * u).sin() * (3.0 * v).cos()]
+            })
+            .collect::<Vec<_>>();
+
+        let dst_r_src = axis_angle_to_rotation_matrix(&[0.2, 0.3, 1.0], 0.15)?;
+        let dst_t_src = [0.1, -0.15, 0.05];
+        let target = PointCloud::new(
+            transform_points3d_vec(&points, &dst_r_src, &dst_t_src),
+            None,
+            None,
+        );
+        let source = PointCloud::new(points, None, None);
+
+        let config = MultiScaleConfig {
+            levels: vec![
+                MultiScaleLevel {
+                    voxel_size: Some(0.1),
+                    max_correspondence_distance: 0.4,
+                    max_iterations: 50,
+                },
+                MultiScaleLevel {
+                    voxel_size: Some(0.05),
+                    max_correspondence_distance: 0.1,
+                    max_iterations: 50,
+                },
+                MultiScaleLevel {
+                    voxel_size: None,
+                    max_correspondence_distance: 0.05,
+                    max_iterations: 50,
+                },
+            ],
+            tolerance: 1e-9,
+            entropy_threshold: 0.0,
+            trim_fraction: 1.0,
+        };
+        let (result, reports) = icp_multiscale(&source, &target, IDENTITY, [0.0; 3], &config)?;
+
+        assert_eq!(reports.len(), 3);
+        assert!(reports[0].num_source_points < reports[1].num_source_points);
+        assert_eq!(reports[2].num_source_points, 5000);
+        assert_eq!(
+            result.num_iterations,
+            reports.iter().map(|r| r.num_iterations).sum::<usize>()
+        );
+        assert!(result.rmse < 1e-6);
+        assert_relative_eq!(reports[2].fitness, 1.0);
+        assert_eq!(result.overlap, Some(1.0));
+        for (t, expected) in result.translation.iter().zip(dst_t_src.iter()) {
+            assert_relative_eq!(t, expected, epsilon = 1e-6);
+        }
+        for (row, expected) in result.rotation.iter().zip(dst_r_src.iter()) {
+            for (r, e) in row.iter().zip(expected.iter()) {
+                assert_relative_eq!(r, e, epsilon = 1e-6);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiscale_config_auto() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(3);
+        let surface = |u: f64, v: f64| [u, v, 0.3 * (2.0 * u).sin() * (3.0 * v).cos()];
+        let random = |rng: &mut StdRng, n: usize| {
+            (0..n)
+                .map(|_| surface(rng.random_range(0.0..2.0), rng.random_range(0.0..2.0)))
+                .collect::<Vec<_>>()
+        };
+        let grid = |n: usize| {
+            (0..n * n)
+                .map(|i| {
+                    surface(
+                        2.0 * (i % n) as f64 / n as f64,
+                        2.0 * (i / n) as f64 / n as f64,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // clouds of very different densities, sampled randomly or on a grid
+        for points in [
+            random(&mut rng, 5_000),
+            random(&mut rng, 200_000),
+            grid(400),
+        ] {
+            let cloud = PointCloud::new(points, None, None);
+            let stats = estimate_density(&cloud, 1000, 0)?;
+            let config = MultiScaleConfig::auto(&stats, 3);
+            assert_eq!(config.levels.len(), 3);
+            assert_relative_eq!(
+                config.levels[2].voxel_size.unwrap(),
+                2.0 * stats.median_nn_dist,
+                epsilon = 1e-12
+            );
+
+            let mut prev_kept = 0;
+            for level in config.levels.iter() {
+                let voxel_size = level.voxel_size.unwrap();
+                let kept = deduplicate(&cloud, voxel_size, DedupPolicy::Centroid)
+                    .0
+                    .len();
+                let expected = (cloud.len() as f64 * (stats.p95_nn_dist / voxel_size).powi(2))
+                    .min(cloud.len() as f64);
+                assert!(
+                    kept as f64 > 0.5 * expected && (kept as f64) < 2.0 * expected,
+                    "{kept} points kept at {voxel_size} instead of {expected}"
+                );
+                assert!(kept > prev_kept);
+                assert_relative_eq!(level.max_correspondence_distance, 2.0 * voxel_size);
+                prev_kept = kept;
+            }
+
+            // about a thousand points survive at the coarsest level
+            let coarsest = config.levels[0].voxel_size.unwrap();
+            let kept = deduplicate(&cloud, coarsest, DedupPolicy::Centroid).0.len();
+            assert!((1000..2000).contains(&kept), "{kept}");
+        }
+
+        // a small cloud is registered at full resolution
+        let small = PointCloud::new(random(&mut rng, 500), None, None);
+        let config = MultiScaleConfig::auto(&estimate_density(&small, 1000, 0)?, 3);
+        assert_eq!(config.levels.len(), 1);
+        assert_eq!(config.levels[0].voxel_size, None);
+
+        // a single level is requested
+        let cloud = PointCloud::new(grid(100), None, None);
+        let config = MultiScaleConfig::auto(&estimate_density(&cloud, 1000, 0)?, 1);
+        assert_eq!(config.levels.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_icp_multiscale_errors() {
+        let cloud = PointCloud::new(vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]], None, None);
+        let empty = PointCloud::new(vec![], None, None);
+        let config = MultiScaleConfig {
+            levels: vec![MultiScaleLevel {
+                voxel_size: None,
+                max_correspondence_distance: 0.1,
+                max_iterations: 10,
+            }],
+            tolerance: 1e-6,
+            entropy_threshold: 0.0,
+            trim_fraction: 1.0,
+        };
+        assert!(matches!(
+            icp_multiscale(&cloud, &empty, IDENTITY, [0.0; 3], &config),
+            Err(IcpError::EmptyCloud)
+        ));
+        assert!(matches!(
+            icp_multiscale(&cloud, &cloud, IDENTITY, [0.0; 3], &config),
+            Err(IcpError::NotEnoughCorrespondences(2))
+        ));
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use kornia_3d::{
+    linalg::{dot_product3, eigen_symmetric33, solve_linear},
+    transforms::{axis_angle_to_rotation_matrix, RigidTransform3},
+};
+
+use crate::{validate_icp_result, IcpError};
+
+/// Minimum number of target points of a voxel to estimate its distribution.
+const MIN_CELL_POINTS: usize = 5;
+
+/// Ratio between the smallest and the largest variance of a regularized voxel covariance.
+const COVARIANCE_EPSILON: f64 = 1e-2;
+
+/// Maximum number of Newton iterations of a registration.
+const MAX_ITERATIONS: usize = 50;
+
+/// Maximum number of halvings of a Newton step that does not decrease the cost.
+const MAX_STEP_HALVINGS: usize = 10;
+
+/// The Gaussian distribution of the target points of a voxel.
+#[derive(Debug, Clone, Copy)]
+struct NdtCell {
+    mean: [f64; 3],
+    information: [[f64; 3]; 3],
+}
+
+/// Registration of point clouds with the normal distributions transform (NDT).
+///
+/// The target is represented by the Gaussian distribution of its points in each voxel of a
+/// regular grid. A transformed source point scores `exp(-0.5 d^T C^-1 d)` for the offset `d`
+/// from the mean of its voxel and its covariance `C`, and the pose maximizing the sum of the
+/// scores is searched with Newton's method. The score is smooth and bounded, so that the
+/// points without a nearby surface or far from it barely contribute and no correspondence
+/// search is needed.
+#[derive(Debug, Clone)]
+pub struct NdtIcpInit {
+    voxel_size: f64,
+    cells: HashMap<[i64; 3], NdtCell>,
+}
+
+impl NdtIcpInit {
+    /// Build the distributions of the target points on a voxel grid.
+    ///
+    /// The voxels with fewer than 5 points are ignored. The covariances are regularized by
+    /// clamping their variances to a hundredth of the largest one, so that the planar voxels
+    /// have a finite width across the surface.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target points.
+    /// * `voxel_size` - The edge length of the voxels, about the size of the basin of
+    ///   convergence of the registration.
+    ///
+    /// # Returns
+    ///
+    /// The registration to the target. It has no distribution if the voxel size is not
+    /// positive.
+    pub fn from_grid(target: &[[f64; 3]], voxel_size: f64) -> Self {
+        let mut cells = HashMap::new();
+        if voxel_size.is_nan() || voxel_size <= 0.0 {
+            return Self { voxel_size, cells };
+        }
+
+        let mut points: HashMap<[i64; 3], Vec<[f64; 3]>> = HashMap::new();
+        for p in target.iter() {
+            points.entry(voxel_key(p, voxel_size)).or_default().push(*p);
+        }
+        for (key, points) in points {
+            if points.len() < MIN_CELL_POINTS {
+                continue;
+            }
+            let num = points.len() as f64;
+            let mut mean = [0.0; 3];
+            for p in points.iter() {
+                for k in 0..3 {
+                    mean[k] += p[k] / num;
+                }
+            }
+            let mut covariance = [[0.0; 3]; 3];
+            for p in points.iter() {
+                for a in 0..3 {
+                    for b in 0..3 {
+                        covariance[a][b] += (p[a] - mean[a]) * (p[b] - mean[b]) / num;
+                    }
+                }
+            }
+            let information = regularized_information(&covariance);
+            cells.insert(key, NdtCell { mean, information });
+        }
+        Self { voxel_size, cells }
+    }
+
+    /// Register a point cloud to the target distributions.
+    ///
+    /// At each iteration, the gradient and the Hessian of the negated sum of the scores are
+    /// accumulated for a perturbation of the pose on the left, and the Newton step is taken,
+    /// halved until the cost decreases. The Hessian is not positive definite far from the
+    /// solution, in which case its positive part, the Gauss-Newton approximation weighted by
+    /// the scores, is used.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The source points.
+    /// * `initial_pose` - The initial guess of the rotation and translation from the source
+    ///   to the target frame, within about a voxel of the solution.
+    ///
+    /// # Returns
+    ///
+    /// The rotation and translation from the source to the target frame. The last estimate
+    /// is returned when no step decreases the cost.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IcpError::Validation`] if the estimated transformation is not a valid rigid
+    /// transformation, e.g. after a numerical blowup.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_icp::NdtIcpInit;
+    ///
+    /// // a corner of three planes, shifted along x
+    /// let target = (0..3000)
+    ///     .map(|i| {
+    ///         let (u, v) = ((i / 3 % 40) as f64 * 0.05 + 0.01, (i / 120) as f64 * 0.08 + 0.01);
+    ///         [[u, v, 0.25], [u, 0.25, v], [0.25, u, v]][i % 3]
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// let src = target.iter().map(|p| [p[0] - 0.05, p[1], p[2]]).collect::<Vec<_>>();
+    ///
+    /// let ndt = NdtIcpInit::from_grid(&target, 0.5);
+    /// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    /// let (_, translation) = ndt.register(&src, (identity, [0.0; 3])).unwrap();
+    /// assert!((translation[0] - 0.05).abs() < 1e-2);
+    /// ```
+    pub fn register(
+        &self,
+        src: &[[f64; 3]],
+        initial_pose: ([[f64; 3]; 3], [f64; 3]),
+    ) -> Result<([[f64; 3]; 3], [f64; 3]), IcpError> {
+        let mut transform = RigidTransform3::new(initial_pose.0, initial_pose.1);
+        let mut cost = self.cost(src, &transform);
+        for _ in 0..MAX_ITERATIONS {
+            let mut gradient = [0.0; 6];
+            let mut hessian = [[0.0; 6]; 6];
+            let mut gauss_newton = [[0.0; 6]; 6];
+            for q in src.iter().map(|p| transform.apply(p)) {
+                let Some(cell) = self.cells.get(&voxel_key(&q, self.voxel_size)) else {
+                    continue;
+                };
+                let d = [0, 1, 2].map(|k| q[k] - cell.mean[k]);
+                let wd = cell.information.map(|row| dot_product3(&row, &d));
+                let score = (-0.5 * dot_product3(&d, &wd)).exp();
+                if score == 0.0 {
+                    continue;
+                }
+
+                // the jacobian of the point for a left perturbation, [-[q]x | I]
+                let jacobian = [
+                    [0.0, q[2], -q[1], 1.0, 0.0, 0.0],
+                    [-q[2], 0.0, q[0], 0.0, 1.0, 0.0],
+                    [q[1], -q[0], 0.0, 0.0, 0.0, 1.0],
+                ];
+                let g: [f64; 6] =
+                    std::array::from_fn(|r| (0..3).map(|a| jacobian[a][r] * wd[a]).sum());
+                for r in 0..6 {
+                    gradient[r] += score * g[r];
+                    for c in 0..6 {
+                        let jtwj = (0..3)
+                            .map(|a| {
+                                jacobian[a][r]
+                                    * (0..3)
+                                        .map(|b| cell.information[a][b] * jacobian[b][c])
+                                        .sum::<f64>()
+                            })
+                            .sum::<f64>();
+                        gauss_newton[r][c] += score * jtwj;
+                        hessian[r][c] += score * (jtwj - g[r] * g[c]);
+                    }
+                }
+            }
+
+            // the Newton step if it descends, otherwise the Gauss-Newton step
+            let rhs = gradient.map(|g| -g);
+            let descends =
+                |delta: &[f64; 6]| (0..6).map(|k| delta[k] * gradient[k]).sum::<f64>() < 0.0;
+            let Some(delta) = solve_linear(&hessian, &rhs)
+                .filter(descends)
+                .or_else(|| solve_linear(&gauss_newton, &rhs).filter(descends))
+            else {
+                break;
+            };
+
+            // backtrack until the cost decreases
+            let mut scale = 1.0;
+            let mut accepted = None;
+            for _ in 0..=MAX_STEP_HALVINGS {
+                let candidate = perturb(&transform, &delta.map(|d| scale * d));
+                let candidate_cost = self.cost(src, &candidate);
+                if candidate_cost < cost {
+                    accepted = Some((candidate, candidate_cost));
+                    break;
+                }
+                scale *= 0.5;
+            }
+            let Some((candidate, candidate_cost)) = accepted else {
+                break;
+            };
+            transform = candidate;
+            cost = candidate_cost;
+            if delta.iter().all(|d| (scale * d).abs() < 1e-12) {
+                break;
+            }
+        }
+
+        // guard against numerical blowups in the estimated transformation
+        validate_icp_result(
+            &transform.rotation,
+            &transform.translation,
+            f64::INFINITY,
+            f64::INFINITY,
+        )?;
+
+        Ok((transform.rotation, transform.translation))
+    }
+
+    /// Compute the negated sum of the scores of the transformed source points.
+    fn cost(&self, src: &[[f64; 3]], transform: &RigidTransform3) -> f64 {
+        -src.iter()
+            .map(|p| transform.apply(p))
+            .filter_map(|q| {
+                let cell = self.cells.get(&voxel_key(&q, self.voxel_size))?;
+                let d = [0, 1, 2].map(|k| q[k] - cell.mean[k]);
+                let wd = cell.information.map(|row| dot_product3(&row, &d));
+                Some((-0.5 * dot_product3(&d, &wd)).exp())
+            })
+            .sum::<f64>()
+    }
+}
+
+/// Compute the key of the voxel containing a point.
+fn voxel_key(p: &[f64; 3], voxel_size: f64) -> [i64; 3] {
+    [0, 1, 2].map(|k| (p[k] / voxel_size).floor() as i64)
+}
+
+/// Compose a perturbation of the rotation vector and the translation on the left of a pose.
+fn perturb(transform: &RigidTransform3, delta: &[f64; 6]) -> RigidTransform3 {
+    let omega = [delta[0], delta[1], delta[2]];
+    let angle = dot_product3(&omega, &omega).sqrt();
+    let rotation = axis_angle_to_rotation_matrix(&omega, angle)
+        .unwrap_or(RigidTransform3::identity().rotation);
+    RigidTransform3::new(rotation, [delta[3], delta[4], delta[5]]).compose(transform)
+}
+
+/// Compute the inverse of a covariance with its variances clamped from below.
+fn regularized_information(covariance: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let (variances, axes) = eigen_symmetric33(covariance);
+    let floor = (COVARIANCE_EPSILON * variances[2]).max(f64::MIN_POSITIVE);
+    let mut information = [[0.0; 3]; 3];
+    for (variance, axis) in variances.iter().zip(axes.iter()) {
+        let weight = 1.0 / variance.max(floor);
+        for a in 0..3 {
+            for b in 0..3 {
+                information[a][b] += weight * axis[a] * axis[b];
+            }
+        }
+    }
+    information
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// A corner of a room: a floor and two walls, sampled at random. The planes are in the
+    /// middle of the voxels of 0.5 m, away from their faces.
+    fn room(num_points: usize, seed: u64) -> Vec<[f64; 3]> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..num_points)
+            .map(|i| {
+                let (u, v): (f64, f64) = (rng.random_range(0.0..4.0), rng.random_range(0.0..3.0));
+                match i % 3 {
+                    0 => [u, v * 4.0 / 3.0, 0.25],
+                    1 => [u, 0.25, v],
+                    _ => [0.25, u, v],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ndt_register() -> Result<(), Box<dyn std::error::Error>> {
+        let target = room(30_000, 0);
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.1, 0.2, 1.0], 0.05)?,
+            [0.1, -0.08, 0.05],
+        );
+        let inverse = truth.inverse();
+        let src = room(6000, 1)
+            .iter()
+            .map(|p| inverse.apply(p))
+            .collect::<Vec<_>>();
+
+        let ndt = NdtIcpInit::from_grid(&target, 0.5);
+        let identity = RigidTransform3::identity();
+        let (rotation, translation) =
+            ndt.register(&src, (identity.rotation, identity.translation))?;
+        let error = RigidTransform3::new(rotation, translation).compose(&inverse);
+        assert!(error.rotation_angle() < 5e-3);
+        assert!(error.translation.iter().all(|t| t.abs() < 0.01));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ndt_register_no_distribution() -> Result<(), Box<dyn std::error::Error>> {
+        let identity = RigidTransform3::identity();
+        let initial = (identity.rotation, [1.0, 2.0, 3.0]);
+        // too few points per voxel, and an invalid voxel size
+        let sparse = NdtIcpInit::from_grid(&[[0.0; 3], [1.0; 3]], 0.5);
+        assert_eq!(sparse.register(&[[0.0; 3]], initial)?, initial);
+        let invalid = NdtIcpInit::from_grid(&room(100, 0), 0.0);
+        assert_eq!(invalid.register(&[[0.0; 3]], initial)?, initial);
+        Ok(())
+    }
+}
@@ -0,0 +1,430 @@
+use kornia_3d::{kdtree::KdTree, linalg::solve_linear};
+
+/// Number of neighbors of each node in the deformation graph.
+const GRAPH_NEIGHBORS: usize = 6;
+
+/// Number of stiffness values of the schedule of [`NonRigidIcp::register`].
+const SCHEDULE_STEPS: usize = 5;
+
+/// Maximum number of conjugate gradient iterations of each linear solve.
+const CG_MAX_ITERATIONS: usize = 500;
+
+/// Relative residual at which the conjugate gradient stops.
+const CG_TOLERANCE: f64 = 1e-10;
+
+/// The deformation of the nodes stops being refined at a stiffness when the affine
+/// transformations change less than this.
+const CONVERGENCE_TOLERANCE: f64 = 1e-6;
+
+/// The affine transformation of a node, as the rows multiplying `[x, y, z, 1]`.
+type Affine = [[f64; 3]; 4];
+
+/// Non-rigid Iterative Closest Point for deformable registration.
+///
+/// Implements the optimal step NRICP of Amberg et al. (2007). Each source point is a node of
+/// a deformation graph, connecting it to its nearest neighbors, and carries its own affine
+/// transformation. At each iteration, the deformed nodes are matched to their nearest target
+/// points and the transformations minimize
+///
+/// `sum_i |X_i^T v_i - u_i|^2 + stiffness * sum_(i,j) |X_i - X_j|_F^2`
+///
+/// with `v_i = [x_i, y_i, z_i, 1]` the node, `u_i` its target point and the second sum over
+/// the edges of the graph. The regularization keeps the deformations of neighboring nodes
+/// similar. The sparse linear system is solved with a preconditioned conjugate gradient.
+///
+/// The stiffness is decreased along a schedule, so that the source is first aligned almost
+/// rigidly and then deforms locally. This continuation avoids the local minima of a low
+/// stiffness from the start.
+pub struct NonRigidIcp;
+
+impl NonRigidIcp {
+    /// Register a source point set to a target point set with a deformation.
+    ///
+    /// The stiffness follows the schedule [`NonRigidIcp::stiffness_schedule`] of five values
+    /// down to `stiffness`.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The source points, the nodes of the deformation.
+    /// * `dst` - The target points, covering the deformed source.
+    /// * `stiffness` - The final weight of the regularization. The larger, the more rigid the
+    ///   deformation.
+    /// * `max_iter` - Maximum number of iterations at each stiffness of the schedule.
+    ///
+    /// # Returns
+    ///
+    /// The warp field, i.e. the displacement of each source point. The displacements are
+    /// zero if the target is empty.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_icp::NonRigidIcp;
+    ///
+    /// let src = (0..100)
+    ///     .map(|i| [(i % 10) as f64 * 0.1, (i / 10) as f64 * 0.1, 0.0])
+    ///     .collect::<Vec<_>>();
+    /// let dst = src.iter().map(|p| [p[0], p[1], p[2] + 0.02]).collect::<Vec<_>>();
+    /// let warp = NonRigidIcp::register(&src, &dst, 1.0, 20);
+    /// assert!(warp.iter().all(|d| (d[2] - 0.02).abs() < 1e-6));
+    /// ```
+    pub fn register(
+        src: &[[f64; 3]],
+        dst: &[[f64; 3]],
+        stiffness: f64,
+        max_iter: usize,
+    ) -> Vec<[f64; 3]> {
+        let schedule = Self::stiffness_schedule(stiffness, SCHEDULE_STEPS);
+        Self::register_with_schedule(src, dst, &schedule, max_iter)
+    }
+
+    /// Compute a stiffness schedule halving the stiffness at each step.
+    ///
+    /// # Arguments
+    ///
+    /// * `final_stiffness` - The last stiffness of the schedule.
+    /// * `num_steps` - The number of stiffness values.
+    ///
+    /// # Returns
+    ///
+    /// The decreasing stiffness values, from `2^(num_steps - 1)` times the final stiffness
+    /// down to the final stiffness.
+    pub fn stiffness_schedule(final_stiffness: f64, num_steps: usize) -> Vec<f64> {
+        (0..num_steps)
+            .rev()
+            .map(|k| final_stiffness * 2f64.powi(k as i32))
+            .collect()
+    }
+
+    /// Register a source point set to a target point set along a stiffness schedule.
+    ///
+    /// The deformation estimated at each stiffness initializes the next one.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The source points, the nodes of the deformation.
+    /// * `dst` - The target points, covering the deformed source.
+    /// * `schedule` - The stiffness values, usually decreasing.
+    /// * `max_iter` - Maximum number of iterations at each stiffness.
+    ///
+    /// # Returns
+    ///
+    /// The warp field, i.e. the displacement of each source point.
+    pub fn register_with_schedule(
+        src: &[[f64; 3]],
+        dst: &[[f64; 3]],
+        schedule: &[f64],
+        max_iter: usize,
+    ) -> Vec<[f64; 3]> {
+        if src.is_empty() || dst.is_empty() {
+            return vec![[0.0; 3]; src.len()];
+        }
+
+        let nodes = src
+            .iter()
+            .map(|p| [p[0], p[1], p[2], 1.0])
+            .collect::<Vec<_>>();
+        let neighbors = deformation_graph(src);
+        let dst_kdtree = KdTree::new(dst);
+
+        let identity = [
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0],
+        ];
+        let mut transforms = vec![identity; src.len()];
+        for stiffness in schedule {
+            let system = System {
+                nodes: &nodes,
+                neighbors: &neighbors,
+                stiffness: *stiffness,
+            };
+            let preconditioner = system.block_jacobi();
+            for _ in 0..max_iter {
+                // match the deformed nodes to their nearest target points
+                let targets = nodes
+                    .iter()
+                    .zip(transforms.iter())
+                    .map(|(v, x)| {
+                        let p = deform(v, x);
+                        dst_kdtree.nearest_one(&p).map_or(p, |nn| dst[nn.index])
+                    })
+                    .collect::<Vec<_>>();
+
+                // the columns of the transformations are independent systems
+                let mut updated = transforms.clone();
+                for c in 0..3 {
+                    let rhs = nodes
+                        .iter()
+                        .zip(targets.iter())
+                        .map(|(v, u)| v.map(|x| x * u[c]))
+                        .collect::<Vec<_>>();
+                    let initial = transforms
+                        .iter()
+                        .map(|x| [x[0][c], x[1][c], x[2][c], x[3][c]])
+                        .collect::<Vec<_>>();
+                    let column = system.solve(&rhs, initial, &preconditioner);
+                    for (x, col) in updated.iter_mut().zip(column.iter()) {
+                        for r in 0..4 {
+                            x[r][c] = col[r];
+                        }
+                    }
+                }
+
+                let change = updated
+                    .iter()
+                    .zip(transforms.iter())
+                    .flat_map(|(a, b)| a.iter().flatten().zip(b.iter().flatten()))
+                    .map(|(a, b)| (a - b).abs())
+                    .fold(0.0, f64::max);
+                transforms = updated;
+                if change < CONVERGENCE_TOLERANCE {
+                    break;
+                }
+            }
+        }
+
+        nodes
+            .iter()
+            .zip(transforms.iter())
+            .zip(src.iter())
+            .map(|((v, x), p)| {
+                let q = deform(v, x);
+                [q[0] - p[0], q[1] - p[1], q[2] - p[2]]
+            })
+            .collect()
+    }
+}
+
+/// The normal equations of a column of the node transformations.
+struct System<'a> {
+    nodes: &'a [[f64; 4]],
+    neighbors: &'a [Vec<usize>],
+    stiffness: f64,
+}
+
+impl System<'_> {
+    /// Multiply a column of the transformations by the matrix of the normal equations.
+    fn apply(&self, x: &[[f64; 4]]) -> Vec<[f64; 4]> {
+        self.nodes
+            .iter()
+            .zip(self.neighbors.iter())
+            .enumerate()
+            .map(|(i, (v, neighbors))| {
+                let projection = (0..4).map(|r| v[r] * x[i][r]).sum::<f64>();
+                let mut y = v.map(|vr| vr * projection);
+                for j in neighbors {
+                    for r in 0..4 {
+                        y[r] += self.stiffness * (x[i][r] - x[*j][r]);
+                    }
+                }
+                y
+            })
+            .collect()
+    }
+
+    /// Compute the inverses of the 4x4 diagonal blocks of the matrix.
+    fn block_jacobi(&self) -> Vec<[[f64; 4]; 4]> {
+        self.nodes
+            .iter()
+            .zip(self.neighbors.iter())
+            .map(|(v, neighbors)| {
+                let mut block = [[0.0; 4]; 4];
+                for r in 0..4 {
+                    for c in 0..4 {
+                        block[r][c] = v[r] * v[c];
+                    }
+                    block[r][r] += self.stiffness * neighbors.len() as f64;
+                }
+                // the inverse by columns, the identity for a singular block
+                let mut inverse = [[0.0; 4]; 4];
+                for c in 0..4 {
+                    let mut unit = [0.0; 4];
+                    unit[c] = 1.0;
+                    let column = solve_linear(&block, &unit).unwrap_or(unit);
+                    for r in 0..4 {
+                        inverse[r][c] = column[r];
+                    }
+                }
+                inverse
+            })
+            .collect()
+    }
+
+    /// Solve the normal equations with the preconditioned conjugate gradient.
+    fn solve(
+        &self,
+        rhs: &[[f64; 4]],
+        initial: Vec<[f64; 4]>,
+        preconditioner: &[[[f64; 4]; 4]],
+    ) -> Vec<[f64; 4]> {
+        let dot = |a: &[[f64; 4]], b: &[[f64; 4]]| {
+            a.iter()
+                .zip(b.iter())
+                .map(|(x, y)| (0..4).map(|r| x[r] * y[r]).sum::<f64>())
+                .sum::<f64>()
+        };
+        let precondition = |r: &[[f64; 4]]| {
+            r.iter()
+                .zip(preconditioner.iter())
+                .map(|(ri, m)| m.map(|row| (0..4).map(|c| row[c] * ri[c]).sum::<f64>()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut x = initial;
+        let ax = self.apply(&x);
+        let mut residual = rhs
+            .iter()
+            .zip(ax.iter())
+            .map(|(b, a)| [0, 1, 2, 3].map(|r| b[r] - a[r]))
+            .collect::<Vec<_>>();
+        let rhs_norm = dot(rhs, rhs).sqrt();
+        let mut z = precondition(&residual);
+        let mut direction = z.clone();
+        let mut rz = dot(&residual, &z);
+        for _ in 0..CG_MAX_ITERATIONS {
+            if dot(&residual, &residual).sqrt() <= CG_TOLERANCE * rhs_norm {
+                break;
+            }
+            let ap = self.apply(&direction);
+            let curvature = dot(&direction, &ap);
+            if curvature <= 0.0 {
+                break;
+            }
+            let step = rz / curvature;
+            for ((xi, ri), (pi, api)) in x
+                .iter_mut()
+                .zip(residual.iter_mut())
+                .zip(direction.iter().zip(ap.iter()))
+            {
+                for r in 0..4 {
+                    xi[r] += step * pi[r];
+                    ri[r] -= step * api[r];
+                }
+            }
+            z = precondition(&residual);
+            let rz_next = dot(&residual, &z);
+            let beta = rz_next / rz;
+            rz = rz_next;
+            for (pi, zi) in direction.iter_mut().zip(z.iter()) {
+                for r in 0..4 {
+                    pi[r] = zi[r] + beta * pi[r];
+                }
+            }
+        }
+        x
+    }
+}
+
+/// Build the symmetric k nearest neighbors graph of the nodes.
+fn deformation_graph(points: &[[f64; 3]]) -> Vec<Vec<usize>> {
+    let kdtree = KdTree::new(points);
+    let mut neighbors = vec![Vec::new(); points.len()];
+    for (i, p) in points.iter().enumerate() {
+        for nn in kdtree.nearest_n(p, GRAPH_NEIGHBORS + 1) {
+            if nn.index != i {
+                neighbors[i].push(nn.index);
+                neighbors[nn.index].push(i);
+            }
+        }
+    }
+    for n in neighbors.iter_mut() {
+        n.sort_unstable();
+        n.dedup();
+    }
+    neighbors
+}
+
+/// Apply the affine transformation of a node.
+fn deform(v: &[f64; 4], x: &Affine) -> [f64; 3] {
+    [0, 1, 2].map(|c| (0..4).map(|r| v[r] * x[r][c]).sum::<f64>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A grid of the unit square at a height.
+    fn grid(num: usize, height: impl Fn(f64, f64) -> f64) -> Vec<[f64; 3]> {
+        let step = 1.0 / (num - 1) as f64;
+        (0..num * num)
+            .map(|i| {
+                let (x, y) = ((i % num) as f64 * step, (i / num) as f64 * step);
+                [x, y, height(x, y)]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nricp_translation() {
+        let src = grid(15, |x, y| 0.2 * (3.0 * x).sin() * (2.0 * y).cos());
+        let shift = [0.02, -0.01, 0.015];
+        let dst = src
+            .iter()
+            .map(|p| [p[0] + shift[0], p[1] + shift[1], p[2] + shift[2]])
+            .collect::<Vec<_>>();
+
+        // a translation does not stretch the graph whatever the stiffness
+        let warp = NonRigidIcp::register(&src, &dst, 10.0, 50);
+        assert_eq!(warp.len(), src.len());
+        for d in warp.iter() {
+            for k in 0..3 {
+                assert!((d[k] - shift[k]).abs() < 1e-5, "{d:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_nricp_bending() {
+        let bend = |x: f64, _: f64| 0.15 * (std::f64::consts::PI * x).sin();
+        let src = grid(12, |_, _| 0.0);
+        let dst = grid(60, bend);
+
+        // the distances of the deformed source to the bent surface
+        let residual = |stiffness: f64| {
+            let warp = NonRigidIcp::register(&src, &dst, stiffness, 30);
+            src.iter()
+                .zip(warp.iter())
+                .map(|(p, d)| {
+                    let q = [p[0] + d[0], p[1] + d[1], p[2] + d[2]];
+                    (q[2] - bend(q[0], q[1])).abs()
+                })
+                .sum::<f64>()
+                / src.len() as f64
+        };
+
+        // a flat sheet cannot fit the bend rigidly, unlike a flexible deformation
+        let flexible = residual(0.01);
+        let stiff = residual(100.0);
+        assert!(flexible < 5e-3, "{flexible}");
+        assert!(stiff > 2.0 * flexible, "{stiff} {flexible}");
+    }
+
+    #[test]
+    fn test_stiffness_schedule() {
+        assert_eq!(
+            NonRigidIcp::stiffness_schedule(0.5, 4),
+            vec![4.0, 2.0, 1.0, 0.5]
+        );
+        assert!(NonRigidIcp::stiffness_schedule(1.0, 0).is_empty());
+    }
+
+    #[test]
+    fn test_nricp_empty() {
+        let src = [[0.0; 3], [1.0; 3]];
+        assert_eq!(NonRigidIcp::register(&src, &[], 1.0, 10), vec![[0.0; 3]; 2]);
+        assert!(NonRigidIcp::register(&[], &src, 1.0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_deformation_graph_symmetric() {
+        let points = grid(5, |_, _| 0.0);
+        let neighbors = deformation_graph(&points);
+        for (i, n) in neighbors.iter().enumerate() {
+            assert!(n.len() >= GRAPH_NEIGHBORS);
+            assert!(!n.contains(&i));
+            assert!(n.iter().all(|j| neighbors[*j].contains(&i)));
+        }
+    }
+}
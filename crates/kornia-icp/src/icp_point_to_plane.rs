@@ -0,0 +1,327 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+
+use crate::{
+    estimate_overlap, ops::rotation_from_vector, validate_icp_result, ICPResult, IcpError,
+    SamplingStrategy,
+};
+use kornia_3d::{
+    linalg::{
+        cross_vec3, dot_product3, mat33_mul_vec3, matmul33, solve_linear, transform_points3d_vec,
+    },
+    pointcloud::PointCloud,
+    stats::entropy_convergence_criterion,
+    transforms::RigidTransform3,
+};
+
+/// Configuration of the point-to-plane ICP.
+#[derive(Debug, Clone)]
+pub struct PointToPlaneConfig {
+    /// Maximum number of iterations to perform.
+    pub max_iterations: usize,
+    /// Convergence tolerance as the difference in RMSE between two consecutive iterations.
+    pub tolerance: f64,
+    /// Convergence threshold on the Kullback-Leibler divergence between the residual
+    /// distributions of two consecutive iterations, in nats, see
+    /// [`entropy_convergence_criterion`]. A non-positive threshold disables the criterion.
+    pub entropy_threshold: f64,
+    /// Maximum distance between a source point and its nearest target point to be
+    /// considered a correspondence.
+    pub max_correspondence_distance: f64,
+    /// The strategy to select the source points, once before the iterations.
+    pub sampling: SamplingStrategy,
+}
+
+/// Iterative Closest Point (ICP) algorithm using point to plane distance.
+///
+/// Each iteration minimizes the sum of the squared distances from the transformed source
+/// points to the tangent planes of their nearest target points, linearized around the
+/// current transformation and solved as a 6x6 linear system.
+///
+/// # Arguments
+///
+/// * `source` - Source point cloud.
+/// * `source_normals` - The unit normal of each source point, only used for the sampling.
+/// * `target` - Target point cloud.
+/// * `target_normals` - The unit normal of each target point.
+/// * `initial_rot` - Initial rotation matrix from the source to the target frame.
+/// * `initial_trans` - Initial translation vector from the source to the target frame.
+/// * `config` - The configuration of the registration.
+///
+/// # Returns
+///
+/// The transformation from the source to the target frame, the number of iterations and
+/// the point-to-plane RMSE of the correspondences of the last iteration. The registration
+/// fails with [`IcpError::DegenerateConstraints`] if the correspondences leave a degree of
+/// freedom of the pose unconstrained.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{icp_point_to_plane, PointToPlaneConfig, SamplingStrategy};
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// // the three faces of a corner
+/// let mut points = Vec::new();
+/// let mut normals = Vec::new();
+/// for i in 0..400 {
+///     let (u, v) = ((i % 20) as f64 * 0.05, (i / 20) as f64 * 0.05);
+///     points.extend([[u, v, 0.0], [u, 0.0, v], [0.0, u, v]]);
+///     normals.extend([[0.0, 0.0, 1.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]]);
+/// }
+/// let moved = points.iter().map(|p| [p[0] - 0.01, p[1] + 0.02, p[2]]).collect();
+///
+/// let config = PointToPlaneConfig {
+///     max_iterations: 20,
+///     tolerance: 1e-12,
+///     entropy_threshold: 0.0,
+///     max_correspondence_distance: 0.1,
+///     sampling: SamplingStrategy::All,
+/// };
+/// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let source = PointCloud::new(moved, None, None);
+/// let target = PointCloud::new(points, None, None);
+/// let result =
+///     icp_point_to_plane(&source, &normals, &target, &normals, identity, [0.0; 3], &config)
+///         .unwrap();
+/// assert!((result.translation[0] - 0.01).abs() < 1e-9);
+/// assert!((result.translation[1] + 0.02).abs() < 1e-9);
+/// ```
+pub fn icp_point_to_plane(
+    source: &PointCloud,
+    source_normals: &[[f64; 3]],
+    target: &PointCloud,
+    target_normals: &[[f64; 3]],
+    initial_rot: [[f64; 3]; 3],
+    initial_trans: [f64; 3],
+    config: &PointToPlaneConfig,
+) -> Result<ICPResult, IcpError> {
+    if source.is_empty() || target.is_empty() {
+        return Err(IcpError::EmptyCloud);
+    }
+
+    let mut result = ICPResult {
+        rotation: initial_rot,
+        translation: initial_trans,
+        num_iterations: 0,
+        rmse: f64::INFINITY,
+        diagnostics: None,
+        overlap: None,
+    };
+
+    let kdtree: ImmutableKdTree<f64, u32, 3, 32> = ImmutableKdTree::new_from_slice(target.points());
+    let max_sq_distance = config.max_correspondence_distance.powi(2);
+
+    let samples = config
+        .sampling
+        .sample(source, source_normals)
+        .into_iter()
+        .map(|i| source.points()[i])
+        .collect::<Vec<_>>();
+    let mut current_source =
+        transform_points3d_vec(&samples, &result.rotation, &result.translation);
+
+    let mut prev_rmse = f64::INFINITY;
+    let mut prev_residuals = Vec::new();
+    while result.num_iterations < config.max_iterations {
+        // accumulate the normal equations of the linearized residuals
+        let mut jtj = [[0.0; 6]; 6];
+        let mut jtr = [0.0; 6];
+        let mut residuals = Vec::new();
+        for p in current_source.iter() {
+            let nn = kdtree.nearest_one::<kiddo::SquaredEuclidean>(p);
+            if nn.distance > max_sq_distance {
+                continue;
+            }
+            let q = &target.points()[nn.item as usize];
+            let n = &target_normals[nn.item as usize];
+            let residual = dot_product3(&[p[0] - q[0], p[1] - q[1], p[2] - q[2]], n);
+            let mut torque = [0.0; 3];
+            cross_vec3(p, n, &mut torque);
+            let jacobian = [torque[0], torque[1], torque[2], n[0], n[1], n[2]];
+            for r in 0..6 {
+                for c in 0..6 {
+                    jtj[r][c] += jacobian[r] * jacobian[c];
+                }
+                jtr[r] -= jacobian[r] * residual;
+            }
+            residuals.push(residual.abs());
+        }
+
+        let num_correspondences = residuals.len();
+        if num_correspondences < 6 {
+            return Err(IcpError::NotEnoughCorrespondences(num_correspondences));
+        }
+        let delta = solve_linear(&jtj, &jtr).ok_or(IcpError::DegenerateConstraints)?;
+
+        let rr_delta = rotation_from_vector(&[delta[0], delta[1], delta[2]]);
+        let tt_delta = [delta[3], delta[4], delta[5]];
+        current_source = transform_points3d_vec(&current_source, &rr_delta, &tt_delta);
+
+        // compose the delta on the left of the current transformation
+        let mut rotation = [[0.0; 3]; 3];
+        matmul33(&rr_delta, &result.rotation, &mut rotation);
+        let mut translation = [0.0; 3];
+        mat33_mul_vec3(&rr_delta, &result.translation, &mut translation);
+        result.rotation = rotation;
+        result.translation = [
+            translation[0] + tt_delta[0],
+            translation[1] + tt_delta[1],
+            translation[2] + tt_delta[2],
+        ];
+
+        let sum_sq_residuals = residuals.iter().map(|r| r * r).sum::<f64>();
+        result.rmse = (sum_sq_residuals / num_correspondences as f64).sqrt();
+        result.num_iterations += 1;
+        log::debug!(
+            "Iteration: {} correspondences: {} rmse: {}",
+            result.num_iterations,
+            num_correspondences,
+            result.rmse
+        );
+        if (prev_rmse - result.rmse).abs() < config.tolerance
+            || entropy_convergence_criterion(&prev_residuals, &residuals, config.entropy_threshold)
+        {
+            break;
+        }
+        prev_rmse = result.rmse;
+        prev_residuals = residuals;
+    }
+
+    result.overlap = Some(estimate_overlap(
+        source,
+        &kdtree,
+        &RigidTransform3::new(result.rotation, result.translation),
+        config.max_correspondence_distance,
+    ));
+
+    // guard against numerical blowups in the estimated transformation
+    validate_icp_result(
+        &result.rotation,
+        &result.translation,
+        f64::INFINITY,
+        f64::INFINITY,
+    )?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use kornia_3d::transforms::{axis_angle_to_rotation_matrix, RigidTransform3};
+
+    const IDENTITY: [[f64; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    fn config() -> PointToPlaneConfig {
+        PointToPlaneConfig {
+            max_iterations: 50,
+            tolerance: 1e-14,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.5,
+            sampling: SamplingStrategy::All,
+        }
+    }
+
+    #[test]
+    fn test_icp_point_to_plane_sphere() -> Result<(), Box<dyn std::error::Error>> {
+        // a Fibonacci sphere with its outward normals
+        let num_points = 3000;
+        let golden = std::f64::consts::PI * (3.0 - 5.0f64.sqrt());
+        let normals = (0..num_points)
+            .map(|i| {
+                let z = 1.0 - 2.0 * (i as f64 + 0.5) / num_points as f64;
+                let r = (1.0 - z * z).sqrt();
+                let theta = golden * i as f64;
+                [r * theta.cos(), r * theta.sin(), z]
+            })
+            .collect::<Vec<_>>();
+        // an ellipsoid breaks the rotational symmetry of the sphere
+        let points = normals
+            .iter()
+            .map(|n| [2.0 * n[0], 1.5 * n[1], n[2]])
+            .collect::<Vec<_>>();
+        let normals = normals
+            .iter()
+            .map(|n| {
+                let g = [n[0] / 2.0, n[1] / 1.5, n[2]];
+                let norm = dot_product3(&g, &g).sqrt();
+                [g[0] / norm, g[1] / norm, g[2] / norm]
+            })
+            .collect::<Vec<_>>();
+
+        let dst_r_src = axis_angle_to_rotation_matrix(&[0.2, 0.5, 1.0], 0.1)?;
+        let dst_t_src = [0.05, -0.08, 0.03];
+        let src_from_dst = RigidTransform3::new(dst_r_src, dst_t_src).inverse();
+        let source = PointCloud::new(
+            points.iter().map(|p| src_from_dst.apply(p)).collect(),
+            None,
+            None,
+        );
+        let target = PointCloud::new(points, None, None);
+
+        let result = icp_point_to_plane(
+            &source,
+            &normals,
+            &target,
+            &normals,
+            IDENTITY,
+            [0.0; 3],
+            &config(),
+        )?;
+        for (t, expected) in result.translation.iter().zip(dst_t_src.iter()) {
+            assert_relative_eq!(t, expected, epsilon = 1e-6);
+        }
+        for (row, expected) in result.rotation.iter().zip(dst_r_src.iter()) {
+            for (r, e) in row.iter().zip(expected.iter()) {
+                assert_relative_eq!(r, e, epsilon = 1e-6);
+            }
+        }
+        assert!(result.rmse < 1e-6);
+
+        // stopped once the residual distribution settles, the RMSE never meeting the tolerance
+        let settled = icp_point_to_plane(
+            &source,
+            &normals,
+            &target,
+            &normals,
+            IDENTITY,
+            [0.0; 3],
+            &PointToPlaneConfig {
+                tolerance: 0.0,
+                entropy_threshold: 1e-2,
+                ..config()
+            },
+        )?;
+        assert!(settled.num_iterations < config().max_iterations);
+        assert!(settled.rmse < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_icp_point_to_plane_errors() {
+        let empty = PointCloud::new(vec![], None, None);
+        let plane = (0..100)
+            .map(|i| [(i % 10) as f64 * 0.1, (i / 10) as f64 * 0.1, 0.0])
+            .collect::<Vec<_>>();
+        let normals = vec![[0.0, 0.0, 1.0]; 100];
+        let cloud = PointCloud::new(plane, None, None);
+        assert!(matches!(
+            icp_point_to_plane(&empty, &[], &cloud, &normals, IDENTITY, [0.0; 3], &config()),
+            Err(IcpError::EmptyCloud)
+        ));
+        // a plane cannot constrain the translations within it
+        assert!(matches!(
+            icp_point_to_plane(
+                &cloud,
+                &normals,
+                &cloud,
+                &normals,
+                IDENTITY,
+                [0.0; 3],
+                &config()
+            ),
+            Err(IcpError::DegenerateConstraints)
+        ));
+    }
+}
@@ -0,0 +1,490 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+
+use crate::{
+    estimate_overlap, ops::fit_transformation_weighted, validate_icp_result, ICPResult, IcpError,
+};
+use kornia_3d::{
+    linalg::{mat33_mul_vec3, matmul33, transform_points3d_vec},
+    pointcloud::PointCloud,
+    stats::entropy_convergence_criterion,
+    transforms::RigidTransform3,
+};
+
+/// Ratio between the standard deviation of normally distributed residuals and their median
+/// absolute deviation.
+const MAD_TO_SIGMA: f64 = 1.4826;
+
+/// Factor relaxing the outlier rejection when the kernel scale is adaptive.
+const RELAXED_REJECTION_FACTOR: f64 = 2.0;
+
+/// A robust kernel down-weighting the correspondences with large residuals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RobustKernel {
+    /// Weight one below the scale and decreasing as `scale / r` above it.
+    Huber,
+    /// Weight `(1 - (r / scale)^2)^2` below the scale and zero above it, which ignores the
+    /// gross outliers entirely.
+    Tukey,
+    /// Weight `1 / (1 + (r / scale)^2)`, which keeps decreasing with the residual without
+    /// ever ignoring a correspondence.
+    Cauchy,
+}
+
+impl RobustKernel {
+    /// Compute the weight of a correspondence in the iteratively reweighted fit.
+    ///
+    /// # Arguments
+    ///
+    /// * `residual` - The distance between the corresponding points.
+    /// * `scale` - The scale of the kernel.
+    pub fn weight(&self, residual: f64, scale: f64) -> f64 {
+        match self {
+            RobustKernel::Huber => {
+                if residual <= scale {
+                    1.0
+                } else {
+                    scale / residual
+                }
+            }
+            RobustKernel::Tukey => {
+                if residual < scale {
+                    (1.0 - (residual / scale).powi(2)).powi(2)
+                } else {
+                    0.0
+                }
+            }
+            RobustKernel::Cauchy => 1.0 / (1.0 + (residual / scale).powi(2)),
+        }
+    }
+
+    /// The ratio between the scale of the kernel and the standard deviation of the inlier
+    /// residuals giving 95% efficiency on normally distributed residuals.
+    pub fn tuning_constant(&self) -> f64 {
+        match self {
+            RobustKernel::Huber => 1.345,
+            RobustKernel::Tukey => 4.685,
+            RobustKernel::Cauchy => 2.385,
+        }
+    }
+}
+
+/// The re-estimation of the kernel scale at each iteration of [`robust_icp`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveScale {
+    /// The smallest scale, so that the kernel does not collapse on noise-free data.
+    pub min_scale: f64,
+    /// If set, the scale decreases at most by this factor in `(0, 1]` per iteration, for a
+    /// coarse to fine annealing down to `min_scale`.
+    pub annealing: Option<f64>,
+}
+
+/// The scale of the robust kernel of [`robust_icp`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KernelScale {
+    /// A scale tuned by hand for the noise level of the data.
+    Fixed(f64),
+    /// A scale re-estimated at each iteration from the residuals.
+    Adaptive(AdaptiveScale),
+}
+
+/// Parameters of the robust ICP.
+#[derive(Debug, Clone)]
+pub struct RobustIcpParams {
+    /// Maximum number of iterations to perform.
+    pub max_iterations: usize,
+    /// Convergence tolerance as the difference in RMSE between two consecutive iterations.
+    pub tolerance: f64,
+    /// Convergence threshold on the Kullback-Leibler divergence between the residual
+    /// distributions of two consecutive iterations, in nats, see
+    /// [`entropy_convergence_criterion`]. A non-positive threshold disables the criterion.
+    pub entropy_threshold: f64,
+    /// Maximum distance between a source point and its nearest target point to be considered
+    /// a correspondence.
+    pub max_correspondence_distance: f64,
+    /// The robust kernel weighting the correspondences.
+    pub kernel: RobustKernel,
+    /// The scale of the robust kernel.
+    pub scale: KernelScale,
+}
+
+/// Result of [`robust_icp`].
+#[derive(Debug, Clone)]
+pub struct RobustIcpResult {
+    /// The registration result.
+    pub result: ICPResult,
+    /// The kernel scale of each iteration.
+    pub scale_history: Vec<f64>,
+}
+
+/// Point to point ICP with the correspondences weighted by a robust kernel.
+///
+/// Each iteration solves a weighted closed-form fit, the weights being given by the kernel
+/// from the distances of the correspondences.
+///
+/// With a fixed scale, the correspondences farther than three robust standard deviations
+/// above the median distance are first rejected. With an adaptive scale, the kernel scale
+/// is re-estimated at each iteration as the tuning constant of the kernel times 1.4826 times
+/// the median absolute residual of the correspondences passing this rejection, floored at
+/// `min_scale` and optionally annealed. The kernel then down-weights the outliers at the
+/// scale of the inlier residuals, so that the rejection is relaxed to six robust standard
+/// deviations not to count them twice, and only discards the gross outliers.
+///
+/// # Arguments
+///
+/// * `source` - Source point cloud.
+/// * `target` - Target point cloud.
+/// * `params` - The parameters of the registration.
+///
+/// # Returns
+///
+/// The transformation from the source to the target frame, the number of iterations, the
+/// RMSE of the correspondences of the last iteration and the kernel scale of each
+/// iteration.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{robust_icp, AdaptiveScale, KernelScale, RobustIcpParams, RobustKernel};
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..400)
+///     .map(|i| {
+///         let (u, v) = ((i % 20) as f64 * 0.05, (i / 20) as f64 * 0.05);
+///         [u, v, 0.2 * (3.0 * u).sin() * (2.0 * v).cos()]
+///     })
+///     .collect::<Vec<_>>();
+/// let moved = points.iter().map(|p| [p[0] + 0.01, p[1], p[2]]).collect();
+///
+/// let params = RobustIcpParams {
+///     max_iterations: 50,
+///     tolerance: 1e-12,
+///     entropy_threshold: 0.0,
+///     max_correspondence_distance: 0.1,
+///     kernel: RobustKernel::Huber,
+///     scale: KernelScale::Adaptive(AdaptiveScale {
+///         min_scale: 1e-3,
+///         annealing: None,
+///     }),
+/// };
+/// let src = PointCloud::new(points, None, None);
+/// let dst = PointCloud::new(moved, None, None);
+/// let robust = robust_icp(&src, &dst, &params).unwrap();
+/// assert!((robust.result.translation[0] - 0.01).abs() < 1e-6);
+/// assert_eq!(robust.scale_history.len(), robust.result.num_iterations);
+/// ```
+pub fn robust_icp(
+    source: &PointCloud,
+    target: &PointCloud,
+    params: &RobustIcpParams,
+) -> Result<RobustIcpResult, IcpError> {
+    if source.is_empty() || target.is_empty() {
+        return Err(IcpError::EmptyCloud);
+    }
+
+    let mut result = ICPResult {
+        rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        translation: [0.0; 3],
+        num_iterations: 0,
+        rmse: f64::INFINITY,
+        diagnostics: None,
+        overlap: None,
+    };
+    let mut scale_history = Vec::new();
+
+    let kdtree: ImmutableKdTree<f64, u32, 3, 32> = ImmutableKdTree::new_from_slice(target.points());
+    let max_sq_distance = params.max_correspondence_distance.powi(2);
+
+    let mut current_source = source.points().to_vec();
+    let mut prev_rmse = f64::INFINITY;
+    let mut prev_residuals = Vec::new();
+    while result.num_iterations < params.max_iterations {
+        // nearest neighbors within the maximum distance
+        let mut points_in_src = Vec::new();
+        let mut points_in_dst = Vec::new();
+        let mut residuals = Vec::new();
+        for p in current_source.iter() {
+            let nn = kdtree.nearest_one::<kiddo::SquaredEuclidean>(p);
+            if nn.distance > max_sq_distance {
+                continue;
+            }
+            points_in_src.push(*p);
+            points_in_dst.push(target.points()[nn.item as usize]);
+            residuals.push(nn.distance.sqrt());
+        }
+        if residuals.is_empty() {
+            return Err(IcpError::NotEnoughCorrespondences(0));
+        }
+
+        let (scale, max_residual) = match params.scale {
+            KernelScale::Fixed(scale) => (scale, mad_rejection_threshold(&residuals, 1.0)),
+            KernelScale::Adaptive(adaptive) => {
+                // the scale of the residuals of the inliers, the kernel weighting the others
+                let threshold = mad_rejection_threshold(&residuals, 1.0);
+                let inliers = residuals
+                    .iter()
+                    .copied()
+                    .filter(|r| *r <= threshold)
+                    .collect::<Vec<_>>();
+                let sigma = MAD_TO_SIGMA * median(&inliers);
+                let mut scale = params.kernel.tuning_constant() * sigma;
+                if let (Some(annealing), Some(prev_scale)) =
+                    (adaptive.annealing, scale_history.last())
+                {
+                    scale = scale.min(annealing * prev_scale);
+                }
+                (
+                    scale.max(adaptive.min_scale),
+                    mad_rejection_threshold(&residuals, RELAXED_REJECTION_FACTOR),
+                )
+            }
+        };
+        scale_history.push(scale);
+
+        let weights = residuals
+            .iter()
+            .map(|r| {
+                if *r > max_residual {
+                    0.0
+                } else {
+                    params.kernel.weight(*r, scale)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // the fit is undefined without enough weighted correspondences
+        let num_weighted = weights.iter().filter(|w| **w > 0.0).count();
+        if num_weighted < 3 {
+            return Err(IcpError::NotEnoughCorrespondences(num_weighted));
+        }
+
+        let mut rr_delta = [[0.0; 3]; 3];
+        let mut tt_delta = [0.0; 3];
+        result.diagnostics = Some(fit_transformation_weighted(
+            &points_in_src,
+            &points_in_dst,
+            &weights,
+            &mut rr_delta,
+            &mut tt_delta,
+        ));
+        current_source = transform_points3d_vec(&current_source, &rr_delta, &tt_delta);
+
+        // compose the delta on the left of the current transformation
+        let mut rotation = [[0.0; 3]; 3];
+        matmul33(&rr_delta, &result.rotation, &mut rotation);
+        let mut translation = [0.0; 3];
+        mat33_mul_vec3(&rr_delta, &result.translation, &mut translation);
+        result.rotation = rotation;
+        result.translation = [
+            translation[0] + tt_delta[0],
+            translation[1] + tt_delta[1],
+            translation[2] + tt_delta[2],
+        ];
+
+        let sum_sq_residuals = residuals.iter().map(|r| r * r).sum::<f64>();
+        result.rmse = (sum_sq_residuals / residuals.len() as f64).sqrt();
+        result.num_iterations += 1;
+        log::debug!(
+            "Iteration: {} rmse: {} scale: {}",
+            result.num_iterations,
+            result.rmse,
+            scale
+        );
+        if (prev_rmse - result.rmse).abs() < params.tolerance
+            || entropy_convergence_criterion(&prev_residuals, &residuals, params.entropy_threshold)
+        {
+            break;
+        }
+        prev_rmse = result.rmse;
+        prev_residuals = residuals;
+    }
+
+    result.overlap = Some(estimate_overlap(
+        source,
+        &kdtree,
+        &RigidTransform3::new(result.rotation, result.translation),
+        params.max_correspondence_distance,
+    ));
+
+    // guard against numerical blowups in the estimated transformation
+    validate_icp_result(
+        &result.rotation,
+        &result.translation,
+        f64::INFINITY,
+        f64::INFINITY,
+    )?;
+
+    Ok(RobustIcpResult {
+        result,
+        scale_history,
+    })
+}
+
+/// Compute the median of non-empty values.
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted[sorted.len() / 2]
+}
+
+/// Compute the residual above which a correspondence is rejected, three robust standard
+/// deviations above the median residual times a relaxation factor.
+fn mad_rejection_threshold(residuals: &[f64], relaxation: f64) -> f64 {
+    let median_residual = median(residuals);
+    let deviations = residuals
+        .iter()
+        .map(|r| (r - median_residual).abs())
+        .collect::<Vec<_>>();
+    median_residual + relaxation * 3.0 * MAD_TO_SIGMA * median(&deviations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// A wavy surface with 30% of its points replaced by outliers off the surface, and the
+    /// noisy surface in another pose.
+    fn outlier_scene(truth: &RigidTransform3, noise: f64) -> (PointCloud, PointCloud) {
+        let mut rng = StdRng::seed_from_u64(0);
+        let surface = (0..2500)
+            .map(|i| {
+                let (u, v) = ((i % 50) as f64 * 0.04, (i / 50) as f64 * 0.04);
+                [u, v, 0.3 * (2.0 * u).sin() * (1.5 * v).cos()]
+            })
+            .collect::<Vec<_>>();
+        let source = surface
+            .iter()
+            .map(|p| {
+                if rng.random::<f64>() < 0.3 {
+                    [p[0], p[1], p[2] + rng.random_range(0.05..0.25)]
+                } else {
+                    *p
+                }
+            })
+            .collect::<Vec<_>>();
+        let target = surface
+            .iter()
+            .map(|p| truth.apply(p).map(|x| x + rng.random_range(-noise..noise)))
+            .collect::<Vec<_>>();
+        (
+            PointCloud::new(source, None, None),
+            PointCloud::new(target, None, None),
+        )
+    }
+
+    fn pose_error(result: &ICPResult, truth: &RigidTransform3) -> (f64, f64) {
+        let error =
+            RigidTransform3::new(result.rotation, result.translation).compose(&truth.inverse());
+        let translation = error.translation.iter().map(|t| t * t).sum::<f64>().sqrt();
+        (error.rotation_angle(), translation)
+    }
+
+    #[test]
+    fn test_robust_kernel_weights() {
+        assert_eq!(RobustKernel::Huber.weight(0.5, 1.0), 1.0);
+        assert_eq!(RobustKernel::Huber.weight(4.0, 1.0), 0.25);
+        assert_eq!(RobustKernel::Tukey.weight(0.0, 1.0), 1.0);
+        assert_eq!(RobustKernel::Tukey.weight(0.5, 1.0), 0.5625);
+        assert_eq!(RobustKernel::Tukey.weight(1.5, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_robust_icp_adaptive_matches_fixed() -> Result<(), Box<dyn std::error::Error>> {
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.3, -0.2, 1.0], 0.03)?,
+            [0.03, -0.02, 0.01],
+        );
+        let noise = 0.005;
+        let (source, target) = outlier_scene(&truth, noise);
+
+        let params = |kernel, scale| RobustIcpParams {
+            max_iterations: 100,
+            tolerance: 1e-12,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.3,
+            kernel,
+            scale,
+        };
+        for kernel in [
+            RobustKernel::Huber,
+            RobustKernel::Tukey,
+            RobustKernel::Cauchy,
+        ] {
+            // a scale tuned for the noise level
+            let fixed = robust_icp(
+                &source,
+                &target,
+                &params(kernel, KernelScale::Fixed(4.0 * noise)),
+            )?;
+            // without knowing the noise level
+            let adaptive = robust_icp(
+                &source,
+                &target,
+                &params(
+                    kernel,
+                    KernelScale::Adaptive(AdaptiveScale {
+                        min_scale: 1e-4,
+                        annealing: None,
+                    }),
+                ),
+            )?;
+
+            let (fixed_rotation, fixed_translation) = pose_error(&fixed.result, &truth);
+            let (rotation, translation) = pose_error(&adaptive.result, &truth);
+            assert!(rotation < 1.5 * fixed_rotation.max(1e-4));
+            assert!(translation < 1.5 * fixed_translation.max(1e-4));
+            assert_eq!(adaptive.scale_history.len(), adaptive.result.num_iterations);
+            assert!(fixed.scale_history.iter().all(|s| *s == 4.0 * noise));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_robust_icp_annealing() -> Result<(), Box<dyn std::error::Error>> {
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.02)?,
+            [0.02, 0.01, 0.0],
+        );
+        let (source, target) = outlier_scene(&truth, 0.002);
+        let min_scale = 0.01;
+        let params = RobustIcpParams {
+            max_iterations: 50,
+            tolerance: 1e-12,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.3,
+            kernel: RobustKernel::Tukey,
+            scale: KernelScale::Adaptive(AdaptiveScale {
+                min_scale,
+                annealing: Some(0.8),
+            }),
+        };
+        let robust = robust_icp(&source, &target, &params)?;
+
+        // the scale decreases down to the minimum
+        let scales = &robust.scale_history;
+        assert_eq!(scales.len(), robust.result.num_iterations);
+        assert!(scales.windows(2).all(|w| w[1] <= w[0]));
+        assert!(scales.iter().all(|s| *s >= min_scale));
+        assert_eq!(scales.last(), Some(&min_scale));
+        Ok(())
+    }
+
+    #[test]
+    fn test_robust_icp_empty() {
+        let params = RobustIcpParams {
+            max_iterations: 10,
+            tolerance: 1e-9,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.1,
+            kernel: RobustKernel::Huber,
+            scale: KernelScale::Fixed(0.01),
+        };
+        let empty = PointCloud::new(vec![], None, None);
+        let cloud = PointCloud::new(vec![[0.0; 3]; 10], None, None);
+        assert!(matches!(
+            robust_icp(&empty, &cloud, &params),
+            Err(IcpError::EmptyCloud)
+        ));
+    }
+}
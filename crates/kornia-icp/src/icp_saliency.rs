@@ -0,0 +1,325 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+
+use crate::{
+    estimate_overlap, ops::fit_transformation_weighted, validate_icp_result, ICPResult, IcpError,
+};
+use kornia_3d::{
+    features::{compute_point_saliency, estimate_normals},
+    linalg::{mat33_mul_vec3, matmul33, transform_points3d_vec},
+    pointcloud::PointCloud,
+    stats::entropy_convergence_criterion,
+    transforms::RigidTransform3,
+};
+
+/// Parameters of the saliency weighted ICP.
+#[derive(Debug, Clone)]
+pub struct SaliencyIcpParams {
+    /// Maximum number of iterations to perform.
+    pub max_iterations: usize,
+    /// Convergence tolerance as the difference in RMSE between two consecutive iterations.
+    pub tolerance: f64,
+    /// Convergence threshold on the Kullback-Leibler divergence between the residual
+    /// distributions of two consecutive iterations, in nats, see
+    /// [`entropy_convergence_criterion`]. A non-positive threshold disables the criterion.
+    pub entropy_threshold: f64,
+    /// Maximum distance between a source point and its nearest target point to be considered
+    /// a correspondence.
+    pub max_correspondence_distance: f64,
+    /// The radius of the neighborhoods to estimate the source normals, when the source point
+    /// cloud has none.
+    pub normal_radius: f64,
+    /// The source points with a saliency below this threshold are excluded from the
+    /// registration. Zero keeps all the points.
+    pub min_saliency: f64,
+}
+
+/// Point to point ICP with the correspondences weighted by the saliency of the source points.
+///
+/// The saliency of the source points is computed once with
+/// [`kornia_3d::features::compute_point_saliency`]. The points of flat surfaces carry little
+/// information to constrain the registration and have a saliency close to zero, so they
+/// barely contribute to the fit. Excluding them with `min_saliency` reduces the number of
+/// nearest neighbor searches of each iteration without changing the solution.
+///
+/// # Arguments
+///
+/// * `source` - Source point cloud. Its normals are estimated if it has none.
+/// * `target` - Target point cloud.
+/// * `k` - The number of neighbors to compute the saliency.
+/// * `params` - The parameters of the registration.
+///
+/// # Returns
+///
+/// The transformation from the source to the target frame, the number of iterations and
+/// the RMSE of the retained correspondences of the last iteration.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{saliency_weighted_icp, SaliencyIcpParams};
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// let points = (0..900)
+///     .map(|i| {
+///         let (u, v) = ((i % 30) as f64 * 0.05, (i / 30) as f64 * 0.05);
+///         [u, v, 0.2 * (3.0 * u).sin() * (2.0 * v).cos()]
+///     })
+///     .collect::<Vec<_>>();
+/// let moved = points.iter().map(|p| [p[0] + 0.01, p[1], p[2]]).collect();
+///
+/// let params = SaliencyIcpParams {
+///     max_iterations: 50,
+///     tolerance: 1e-12,
+///     entropy_threshold: 0.0,
+///     max_correspondence_distance: 0.1,
+///     normal_radius: 0.12,
+///     min_saliency: 0.0,
+/// };
+/// let src = PointCloud::new(points, None, None);
+/// let dst = PointCloud::new(moved, None, None);
+/// let result = saliency_weighted_icp(&src, &dst, 10, &params).unwrap();
+/// assert!((result.translation[0] - 0.01).abs() < 1e-6);
+/// ```
+pub fn saliency_weighted_icp(
+    source: &PointCloud,
+    target: &PointCloud,
+    k: usize,
+    params: &SaliencyIcpParams,
+) -> Result<ICPResult, IcpError> {
+    if source.is_empty() || target.is_empty() {
+        return Err(IcpError::EmptyCloud);
+    }
+
+    let saliency = match source.normals() {
+        Some(normals) => compute_point_saliency(source.points(), normals, k),
+        None => {
+            let normals = estimate_normals(source, params.normal_radius, &[0.0; 3]);
+            compute_point_saliency(source.points(), &normals, k)
+        }
+    };
+
+    // keep the salient source points only
+    let (points, weights): (Vec<_>, Vec<_>) = source
+        .points()
+        .iter()
+        .zip(saliency.iter())
+        .filter(|(_, s)| **s >= params.min_saliency)
+        .map(|(p, s)| (*p, *s))
+        .unzip();
+    log::debug!(
+        "Salient points: {} out of {}",
+        points.len(),
+        source.points().len()
+    );
+
+    let mut result = ICPResult {
+        rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        translation: [0.0; 3],
+        num_iterations: 0,
+        rmse: f64::INFINITY,
+        diagnostics: None,
+        overlap: None,
+    };
+
+    let kdtree: ImmutableKdTree<f64, u32, 3, 32> = ImmutableKdTree::new_from_slice(target.points());
+    let max_sq_distance = params.max_correspondence_distance.powi(2);
+
+    let mut current_source = points;
+    let mut prev_rmse = f64::INFINITY;
+    let mut prev_residuals = Vec::new();
+    while result.num_iterations < params.max_iterations {
+        // nearest neighbors within the maximum distance, the distances are squared
+        let mut points_in_src = Vec::new();
+        let mut points_in_dst = Vec::new();
+        let mut correspondence_weights = Vec::new();
+        let mut residuals = Vec::new();
+        for (p, w) in current_source.iter().zip(weights.iter()) {
+            let nn = kdtree.nearest_one::<kiddo::SquaredEuclidean>(p);
+            if nn.distance > max_sq_distance {
+                continue;
+            }
+            points_in_src.push(*p);
+            points_in_dst.push(target.points()[nn.item as usize]);
+            correspondence_weights.push(*w);
+            residuals.push(nn.distance.sqrt());
+        }
+
+        // the fit is undefined without enough salient correspondences
+        let num_salient = correspondence_weights.iter().filter(|w| **w > 0.0).count();
+        if num_salient < 3 {
+            return Err(IcpError::NotEnoughCorrespondences(num_salient));
+        }
+
+        let mut rr_delta = [[0.0; 3]; 3];
+        let mut tt_delta = [0.0; 3];
+        result.diagnostics = Some(fit_transformation_weighted(
+            &points_in_src,
+            &points_in_dst,
+            &correspondence_weights,
+            &mut rr_delta,
+            &mut tt_delta,
+        ));
+        current_source = transform_points3d_vec(&current_source, &rr_delta, &tt_delta);
+
+        // compose the delta on the left of the current transformation
+        let mut rotation = [[0.0; 3]; 3];
+        matmul33(&rr_delta, &result.rotation, &mut rotation);
+        let mut translation = [0.0; 3];
+        mat33_mul_vec3(&rr_delta, &result.translation, &mut translation);
+        result.rotation = rotation;
+        result.translation = [
+            translation[0] + tt_delta[0],
+            translation[1] + tt_delta[1],
+            translation[2] + tt_delta[2],
+        ];
+
+        let sum_sq_distances = residuals.iter().map(|r| r * r).sum::<f64>();
+        result.rmse = (sum_sq_distances / points_in_src.len() as f64).sqrt();
+        result.num_iterations += 1;
+        log::debug!("Iteration: {} rmse: {}", result.num_iterations, result.rmse);
+        if (prev_rmse - result.rmse).abs() < params.tolerance
+            || entropy_convergence_criterion(&prev_residuals, &residuals, params.entropy_threshold)
+        {
+            break;
+        }
+        prev_rmse = result.rmse;
+        prev_residuals = residuals;
+    }
+
+    result.overlap = Some(estimate_overlap(
+        source,
+        &kdtree,
+        &RigidTransform3::new(result.rotation, result.translation),
+        params.max_correspondence_distance,
+    ));
+
+    // guard against numerical blowups in the estimated transformation
+    validate_icp_result(
+        &result.rotation,
+        &result.translation,
+        f64::INFINITY,
+        f64::INFINITY,
+    )?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use kornia_3d::transforms::{axis_angle_to_rotation_matrix, RigidTransform3};
+
+    /// A floor with boxes of different sizes, sampled on a grid.
+    fn scene() -> Vec<[f64; 3]> {
+        let step = 0.04;
+        let boxes = [
+            ([0.6, 0.6], [0.4, 0.6], 0.3),
+            ([1.8, 0.8], [0.6, 0.3], 0.5),
+            ([1.0, 1.8], [0.3, 0.3], 0.2),
+        ];
+        let inside = |x: f64, y: f64| {
+            boxes.iter().find(|(corner, size, _)| {
+                x >= corner[0]
+                    && x <= corner[0] + size[0]
+                    && y >= corner[1]
+                    && y <= corner[1] + size[1]
+            })
+        };
+
+        let mut points = Vec::new();
+        for i in 0..70 {
+            for j in 0..70 {
+                let (x, y) = (i as f64 * step, j as f64 * step);
+                let z = inside(x, y).map_or(0.0, |(_, _, height)| *height);
+                points.push([x, y, z]);
+            }
+        }
+        // the walls of the boxes
+        for (corner, size, height) in boxes.iter() {
+            let num_z = (height / step) as usize;
+            for h in 1..num_z {
+                let z = h as f64 * step;
+                for a in 0..=(size[0] / step) as usize {
+                    let x = corner[0] + a as f64 * step;
+                    points.push([x, corner[1], z]);
+                    points.push([x, corner[1] + size[1], z]);
+                }
+                for b in 1..(size[1] / step) as usize {
+                    let y = corner[1] + b as f64 * step;
+                    points.push([corner[0], y, z]);
+                    points.push([corner[0] + size[0], y, z]);
+                }
+            }
+        }
+        points
+    }
+
+    fn params(min_saliency: f64) -> SaliencyIcpParams {
+        SaliencyIcpParams {
+            max_iterations: 200,
+            tolerance: 1e-14,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.2,
+            normal_radius: 0.1,
+            min_saliency,
+        }
+    }
+
+    #[test]
+    fn test_saliency_weighted_icp() -> Result<(), Box<dyn std::error::Error>> {
+        let points = scene();
+        let dst_r_src = axis_angle_to_rotation_matrix(&[0.2, -0.1, 1.0], 0.05)?;
+        let dst_t_src = [0.04, -0.03, 0.02];
+
+        // express the source in its own frame
+        let src_from_dst = RigidTransform3::new(dst_r_src, dst_t_src).inverse();
+        let source = PointCloud::new(
+            points.iter().map(|p| src_from_dst.apply(p)).collect(),
+            None,
+            None,
+        );
+        let target = PointCloud::new(points, None, None);
+
+        for min_saliency in [0.0, 0.05] {
+            let result = saliency_weighted_icp(&source, &target, 10, &params(min_saliency))?;
+            for (t, expected) in result.translation.iter().zip(dst_t_src.iter()) {
+                assert_relative_eq!(t, expected, epsilon = 1e-6);
+            }
+            for (row, expected) in result.rotation.iter().zip(dst_r_src.iter()) {
+                for (r, e) in row.iter().zip(expected.iter()) {
+                    assert_relative_eq!(r, e, epsilon = 1e-6);
+                }
+            }
+            assert!(result.diagnostics.is_some());
+        }
+
+        // the flat points make most of the scene and are excluded from the registration
+        let normals = estimate_normals(&source, 0.1, &[0.0; 3]);
+        let saliency = compute_point_saliency(source.points(), &normals, 10);
+        let num_salient = saliency.iter().filter(|s| **s >= 0.05).count();
+        assert!(num_salient * 4 < saliency.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_saliency_weighted_icp_errors() {
+        let empty = PointCloud::new(vec![], None, None);
+        let plane = PointCloud::new(
+            (0..100)
+                .map(|i| [(i % 10) as f64 * 0.1, (i / 10) as f64 * 0.1, 0.0])
+                .collect(),
+            None,
+            None,
+        );
+        assert!(matches!(
+            saliency_weighted_icp(&empty, &plane, 10, &params(0.0)),
+            Err(IcpError::EmptyCloud)
+        ));
+        // a plane has no salient point
+        assert!(matches!(
+            saliency_weighted_icp(&plane, &plane, 10, &params(0.0)),
+            Err(IcpError::NotEnoughCorrespondences(0))
+        ));
+    }
+}
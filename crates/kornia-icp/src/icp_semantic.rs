@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use kornia_3d::{
+    kdtree::KdTree, pointcloud::PointCloud, stats::entropy_convergence_criterion,
+    transforms::RigidTransform3,
+};
+
+use crate::{ops::fit_rigid_transform, validate_icp_result, IcpError, IcpParams};
+
+/// Point to point ICP restricted to the correspondences between points of the same class.
+///
+/// In multi-class outdoor scenes, the nearest neighbor of a point is often on another object,
+/// e.g. a pole next to a facade. Matching each source point only to the target points of the
+/// same class label, road to road and building to building, removes most of these false
+/// correspondences and widens the basin of convergence.
+pub struct SemanticIcp;
+
+impl SemanticIcp {
+    /// Register two semantically labelled point clouds.
+    ///
+    /// A KD-tree is built for the target points of each class. At each iteration, each
+    /// transformed source point is matched to the nearest target point with the same label,
+    /// within the maximum correspondence distance, and the transformation is solved in closed
+    /// form with the Kabsch fit. The source points of a class absent from the target
+    /// are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The source point cloud.
+    /// * `src_labels` - The class label of each source point.
+    /// * `dst` - The target point cloud.
+    /// * `dst_labels` - The class label of each target point.
+    /// * `params` - The parameters of the registration.
+    /// * `max_samples` - The source is evenly subsampled to at most this number of points, or
+    ///   not at all if zero.
+    ///
+    /// # Returns
+    ///
+    /// The rotation and translation from the source to the target frame. The registration
+    /// starts from the identity and stops at the last estimate when fewer than three points
+    /// are matched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of labels of a cloud is not its number of points.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IcpError::Validation`] if the estimated transformation is not a valid rigid
+    /// transformation, e.g. after a numerical blowup.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_3d::pointcloud::PointCloud;
+    /// use kornia_icp::{IcpParams, SemanticIcp};
+    ///
+    /// // a corner of three planes, each with its own label, shifted along x
+    /// let points = (0..300)
+    ///     .map(|i| {
+    ///         let (u, v) = ((i / 3 % 10) as f64 * 0.1, (i / 30) as f64 * 0.1);
+    ///         [[u, v, 0.0], [u, 0.0, v], [0.0, u, v]][i % 3]
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// let labels = (0..300).map(|i| (i % 3) as u32).collect::<Vec<_>>();
+    /// let moved = points.iter().map(|p| [p[0] + 0.03, p[1], p[2]]).collect();
+    ///
+    /// let params = IcpParams {
+    ///     max_iterations: 50,
+    ///     tolerance: 1e-12,
+    ///     entropy_threshold: 0.0,
+    ///     max_correspondence_distance: 0.2,
+    /// };
+    /// let (_, translation) = SemanticIcp::register(
+    ///     &PointCloud::new(points, None, None),
+    ///     &labels,
+    ///     &PointCloud::new(moved, None, None),
+    ///     &labels,
+    ///     &params,
+    ///     0,
+    /// )
+    /// .unwrap();
+    /// assert!((translation[0] - 0.03).abs() < 1e-9);
+    /// ```
+    pub fn register(
+        src: &PointCloud,
+        src_labels: &[u32],
+        dst: &PointCloud,
+        dst_labels: &[u32],
+        params: &IcpParams,
+        max_samples: usize,
+    ) -> Result<([[f64; 3]; 3], [f64; 3]), IcpError> {
+        assert_eq!(src.len(), src_labels.len());
+        assert_eq!(dst.len(), dst_labels.len());
+
+        // the target points of each class and their KD-tree
+        let mut classes = HashMap::<u32, Vec<[f64; 3]>>::new();
+        for (p, label) in dst.points().iter().zip(dst_labels.iter()) {
+            classes.entry(*label).or_default().push(*p);
+        }
+        let indices = classes
+            .iter()
+            .map(|(label, points)| (*label, KdTree::new(points)))
+            .collect::<HashMap<_, _>>();
+
+        let step = match max_samples {
+            0 => 1,
+            n => src.len().div_ceil(n).max(1),
+        };
+        let samples = src
+            .points()
+            .iter()
+            .zip(src_labels.iter())
+            .step_by(step)
+            .filter(|(_, label)| indices.contains_key(*label))
+            .collect::<Vec<_>>();
+
+        let mut transform = RigidTransform3::identity();
+        let mut prev_rmse = f64::INFINITY;
+        let mut prev_residuals = Vec::new();
+        for _ in 0..params.max_iterations {
+            let mut points_in_src = Vec::new();
+            let mut points_in_dst = Vec::new();
+            let mut residuals = Vec::new();
+            for (p, label) in samples.iter() {
+                let q = transform.apply(p);
+                let Some(nn) = indices[*label].nearest_one(&q) else {
+                    continue;
+                };
+                if nn.distance > params.max_correspondence_distance {
+                    continue;
+                }
+                points_in_src.push(**p);
+                points_in_dst.push(classes[*label][nn.index]);
+                residuals.push(nn.distance);
+            }
+            if points_in_src.len() < 3 {
+                break;
+            }
+
+            // fit the whole transformation from the original source points
+            let Some(fitted) = fit_rigid_transform(&points_in_src, &points_in_dst) else {
+                break;
+            };
+            transform = fitted;
+
+            let sum_sq_distances = residuals.iter().map(|r| r * r).sum::<f64>();
+            let rmse = (sum_sq_distances / points_in_src.len() as f64).sqrt();
+            if (prev_rmse - rmse).abs() < params.tolerance
+                || entropy_convergence_criterion(
+                    &prev_residuals,
+                    &residuals,
+                    params.entropy_threshold,
+                )
+            {
+                break;
+            }
+            prev_rmse = rmse;
+            prev_residuals = residuals;
+        }
+
+        // guard against numerical blowups in the estimated transformation
+        validate_icp_result(
+            &transform.rotation,
+            &transform.translation,
+            f64::INFINITY,
+            f64::INFINITY,
+        )?;
+
+        Ok((transform.rotation, transform.translation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const GROUND: u32 = 0;
+    const POLE: u32 = 1;
+    const TREE: u32 = 2;
+
+    /// A street: a randomly sampled ground, and a row of poles alternating with tree trunks
+    /// every meter along x.
+    fn street(ground_x: std::ops::Range<f64>, seed: u64) -> (Vec<[f64; 3]>, Vec<u32>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut points = Vec::new();
+        let mut labels = Vec::new();
+        for _ in 0..1500 {
+            points.push([
+                rng.random_range(ground_x.clone()),
+                rng.random_range(0.0..4.0),
+                0.0,
+            ]);
+            labels.push(GROUND);
+        }
+        for k in 1..10 {
+            for h in 1..30 {
+                points.push([k as f64, 2.0, h as f64 * 0.1]);
+                labels.push(if k % 2 == 0 { POLE } else { TREE });
+            }
+        }
+        (points, labels)
+    }
+
+    #[test]
+    fn test_semantic_icp_street() -> Result<(), Box<dyn std::error::Error>> {
+        let (src_points, src_labels) = street(1.0..9.0, 0);
+        let (dst_points, dst_labels) = street(-1.0..11.0, 1);
+
+        // the poles are closer to the shifted trees than to the shifted poles
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.01)?,
+            [0.7, 0.05, 0.0],
+        );
+        let dst_points = dst_points
+            .iter()
+            .map(|p| truth.apply(p))
+            .collect::<Vec<_>>();
+        let src = PointCloud::new(src_points, None, None);
+        let dst = PointCloud::new(dst_points, None, None);
+
+        let params = IcpParams {
+            max_iterations: 100,
+            tolerance: 1e-12,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 1.0,
+        };
+        let (rotation, translation) =
+            SemanticIcp::register(&src, &src_labels, &dst, &dst_labels, &params, 0)?;
+        let error = RigidTransform3::new(rotation, translation).compose(&truth.inverse());
+        // up to the point to point bias of the independent samplings of the ground
+        assert!(error.rotation_angle() < 5e-3);
+        assert!(error.translation.iter().all(|t| t.abs() < 0.05));
+
+        // without the labels, the poles snap onto the trees
+        let (rotation, translation) = SemanticIcp::register(
+            &src,
+            &vec![0; src.len()],
+            &dst,
+            &vec![0; dst.len()],
+            &params,
+            0,
+        )?;
+        let error = RigidTransform3::new(rotation, translation).compose(&truth.inverse());
+        assert!(error.translation[0].abs() > 0.5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_semantic_icp_missing_class() -> Result<(), Box<dyn std::error::Error>> {
+        let params = IcpParams {
+            max_iterations: 10,
+            tolerance: 1e-9,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 1.0,
+        };
+        let cloud = PointCloud::new(vec![[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]], None, None);
+        let identity = RigidTransform3::identity();
+        // no target point has the labels of the source
+        assert_eq!(
+            SemanticIcp::register(&cloud, &[1, 1, 1], &cloud, &[2, 2, 2], &params, 10)?,
+            (identity.rotation, identity.translation)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use kornia_3d::{
+    kdtree::KdTree, linalg::dot_product3, pose::fit_rotation_only, transforms::RigidTransform3,
+};
+
+use crate::{validate_icp_result, IcpError};
+
+/// Compute the rotation aligning two sets of surface normals with ICP on SO(3).
+///
+/// The normals are points on the unit sphere, so the registration has no translation and
+/// only depends on the orientations of the surfaces, which makes it a pre-alignment of the
+/// rotation before a full ICP for objects with distinctive surface orientations. At each
+/// iteration:
+///
+/// 1. Each rotated source normal is matched to the nearest destination normal.
+/// 2. The rotation mapping the source normals to their matches is solved in closed form
+///    with [`fit_rotation_only`], which averages the rotations of the pairs.
+/// 3. The source normals are rotated by the new estimate.
+///
+/// The registration starts from the identity and stops when the correspondences no longer
+/// change.
+///
+/// # Arguments
+///
+/// * `src_normals` - The normals of the source surface.
+/// * `dst_normals` - The normals of the destination surface.
+/// * `max_iter` - The maximum number of iterations.
+///
+/// # Returns
+///
+/// The rotation from the source to the destination frame, the identity if either set has
+/// no normal of positive length.
+///
+/// # Errors
+///
+/// Returns [`IcpError::Validation`] if the estimated transformation is not a valid rigid
+/// transformation, e.g. after a numerical blowup.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::so3_icp;
+///
+/// // the faces of a box, turned a little about z
+/// let (c, s) = (0.1f64.cos(), 0.1f64.sin());
+/// let src = [
+///     [1.0, 0.0, 0.0],
+///     [-1.0, 0.0, 0.0],
+///     [0.0, 1.0, 0.0],
+///     [0.0, -1.0, 0.0],
+///     [0.0, 0.0, 1.0],
+/// ];
+/// let dst = src.map(|n| [c * n[0] - s * n[1], s * n[0] + c * n[1], n[2]]);
+/// let rotation = so3_icp(&src, &dst, 10).unwrap();
+/// assert!((rotation[1][0] - s).abs() < 1e-12);
+/// ```
+pub fn so3_icp(
+    src_normals: &[[f64; 3]],
+    dst_normals: &[[f64; 3]],
+    max_iter: usize,
+) -> Result<[[f64; 3]; 3], IcpError> {
+    let mut transform = RigidTransform3::identity();
+    let src = unit_normals(src_normals);
+    let dst = unit_normals(dst_normals);
+    if src.is_empty() || dst.is_empty() {
+        return Ok(transform.rotation);
+    }
+
+    let tree = KdTree::new(&dst);
+    let mut prev_matches = Vec::new();
+    for _ in 0..max_iter {
+        // on the unit sphere the nearest normal is the one of smallest angle
+        let matches = src
+            .iter()
+            .filter_map(|n| tree.nearest_one(&transform.apply(n)))
+            .map(|neighbor| neighbor.index)
+            .collect::<Vec<_>>();
+        if matches == prev_matches {
+            break;
+        }
+
+        // fit the whole rotation from the original source normals
+        let matched = matches.iter().map(|&j| dst[j]).collect::<Vec<_>>();
+        let Ok(rotation) = fit_rotation_only(&src, &matched, &[0.0; 3]) else {
+            break;
+        };
+        transform = RigidTransform3::new(rotation, [0.0; 3]);
+        prev_matches = matches;
+    }
+
+    // guard against numerical blowups in the estimated transformation
+    validate_icp_result(
+        &transform.rotation,
+        &transform.translation,
+        f64::INFINITY,
+        f64::INFINITY,
+    )?;
+
+    Ok(transform.rotation)
+}
+
+/// Normalize the normals, dropping those of zero or non-finite length.
+fn unit_normals(normals: &[[f64; 3]]) -> Vec<[f64; 3]> {
+    normals
+        .iter()
+        .filter_map(|n| {
+            let norm = dot_product3(n, n).sqrt();
+            (norm > 0.0 && norm.is_finite()).then(|| n.map(|x| x / norm))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_so3_icp() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(17);
+        // the normals of an irregular object, clustered around a few surface orientations
+        let orientations = [
+            [1.0, 0.2, 0.0],
+            [-0.3, 1.0, 0.1],
+            [0.1, -0.4, 1.0],
+            [-1.0, -1.0, 0.5],
+        ];
+        let src = (0..200)
+            .map(|i| {
+                let o: [f64; 3] = orientations[i % orientations.len()];
+                o.map(|x| x + rng.random_range(-0.1..0.1))
+            })
+            .collect::<Vec<_>>();
+
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.4, -0.2, 1.0], 0.25)?,
+            [0.0; 3],
+        );
+        // the destination normals are not scaled to unit length
+        let dst = src
+            .iter()
+            .map(|n| truth.apply(n).map(|x| 2.0 * x))
+            .collect::<Vec<_>>();
+
+        let rotation = so3_icp(&src, &dst, 50)?;
+        for (row, expected) in rotation.iter().zip(truth.rotation.iter()) {
+            for (r, e) in row.iter().zip(expected.iter()) {
+                assert!((r - e).abs() < 1e-9);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_so3_icp_empty() -> Result<(), Box<dyn std::error::Error>> {
+        let identity = RigidTransform3::identity().rotation;
+        let normals = [[0.0, 0.0, 1.0], [1.0, 0.0, 0.0]];
+        assert_eq!(so3_icp(&[], &normals, 10)?, identity);
+        assert_eq!(so3_icp(&normals, &[[0.0; 3]], 10)?, identity);
+        assert_eq!(so3_icp(&normals, &normals, 0)?, identity);
+        Ok(())
+    }
+}
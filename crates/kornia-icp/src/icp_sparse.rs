@@ -0,0 +1,206 @@
+use kornia_3d::{
+    filters::farthest_point_sample, kdtree::KdTree, stats::entropy_convergence_criterion,
+    transforms::RigidTransform3,
+};
+
+use crate::{ops::fit_rigid_transform, validate_icp_result, IcpError, IcpParams};
+
+/// Point to point ICP on a small subset of anchor points of the source.
+///
+/// The cost of an ICP iteration is dominated by the nearest neighbor queries of the source
+/// points. On densely sampled surfaces, a few hundred well spread points constrain the pose
+/// as well as the whole cloud, so registering only these anchors is several times faster at
+/// a similar accuracy.
+pub struct SparseIcp;
+
+impl SparseIcp {
+    /// Register two point clouds from a subset of anchor points of the source.
+    ///
+    /// `anchor_ratio * src.len()` anchors, at least three, are selected by
+    /// [`farthest_point_sample`], so that they are evenly spread over the source. At each
+    /// iteration, only the transformed anchors are matched to their nearest target point
+    /// within the maximum correspondence distance, and the transformation is solved in closed
+    /// form from the anchors with the Kabsch fit. The fitted transformation then
+    /// applies to all the source points.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The source points.
+    /// * `dst` - The target points.
+    /// * `anchor_ratio` - The fraction of the source points used as anchors, in `(0, 1]`.
+    /// * `params` - The parameters of the registration.
+    ///
+    /// # Returns
+    ///
+    /// The rotation and translation from the source to the target frame. The registration
+    /// starts from the identity and stops at the last estimate when fewer than three anchors
+    /// are matched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IcpError::Validation`] if the estimated transformation is not a valid rigid
+    /// transformation, e.g. after a numerical blowup.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_icp::{IcpParams, SparseIcp};
+    ///
+    /// // a corner of three planes, shifted along x
+    /// let src = (0..1200)
+    ///     .map(|i| {
+    ///         let (u, v) = ((i / 3 % 20) as f64 * 0.05, (i / 60) as f64 * 0.05);
+    ///         [[u, v, 0.0], [u, 0.0, v], [0.0, u, v]][i % 3]
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// let dst = src.iter().map(|p| [p[0] + 0.02, p[1], p[2]]).collect::<Vec<_>>();
+    ///
+    /// let params = IcpParams {
+    ///     max_iterations: 50,
+    ///     tolerance: 1e-12,
+    ///     entropy_threshold: 0.0,
+    ///     max_correspondence_distance: 0.1,
+    /// };
+    /// let (_, translation) = SparseIcp::register(&src, &dst, 0.1, &params).unwrap();
+    /// assert!((translation[0] - 0.02).abs() < 1e-9);
+    /// ```
+    pub fn register(
+        src: &[[f64; 3]],
+        dst: &[[f64; 3]],
+        anchor_ratio: f64,
+        params: &IcpParams,
+    ) -> Result<([[f64; 3]; 3], [f64; 3]), IcpError> {
+        let mut transform = RigidTransform3::identity();
+        if src.is_empty() || dst.is_empty() {
+            return Ok((transform.rotation, transform.translation));
+        }
+
+        let num_anchors = ((anchor_ratio * src.len() as f64).ceil() as usize).clamp(3, src.len());
+        let anchors = if num_anchors < src.len() {
+            farthest_point_sample(src, num_anchors)
+                .into_iter()
+                .map(|i| src[i])
+                .collect::<Vec<_>>()
+        } else {
+            src.to_vec()
+        };
+        let index = KdTree::new(dst);
+
+        let mut prev_rmse = f64::INFINITY;
+        let mut prev_residuals = Vec::new();
+        for _ in 0..params.max_iterations {
+            let mut points_in_src = Vec::new();
+            let mut points_in_dst = Vec::new();
+            let mut residuals = Vec::new();
+            for p in anchors.iter() {
+                let Some(nn) = index.nearest_one(&transform.apply(p)) else {
+                    continue;
+                };
+                if nn.distance > params.max_correspondence_distance {
+                    continue;
+                }
+                points_in_src.push(*p);
+                points_in_dst.push(dst[nn.index]);
+                residuals.push(nn.distance);
+            }
+            if points_in_src.len() < 3 {
+                break;
+            }
+
+            // fit the whole transformation from the original anchors
+            let Some(fitted) = fit_rigid_transform(&points_in_src, &points_in_dst) else {
+                break;
+            };
+            transform = fitted;
+
+            let sum_sq_distances = residuals.iter().map(|r| r * r).sum::<f64>();
+            let rmse = (sum_sq_distances / points_in_src.len() as f64).sqrt();
+            if (prev_rmse - rmse).abs() < params.tolerance
+                || entropy_convergence_criterion(
+                    &prev_residuals,
+                    &residuals,
+                    params.entropy_threshold,
+                )
+            {
+                break;
+            }
+            prev_rmse = rmse;
+            prev_residuals = residuals;
+        }
+
+        // guard against numerical blowups in the estimated transformation
+        validate_icp_result(
+            &transform.rotation,
+            &transform.translation,
+            f64::INFINITY,
+            f64::INFINITY,
+        )?;
+
+        Ok((transform.rotation, transform.translation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const SEED: u64 = 3;
+
+    /// Sample the floor and two walls of a room, with small bumps on the floor.
+    fn room(num_points: usize, rng: &mut StdRng) -> Vec<[f64; 3]> {
+        (0..num_points)
+            .map(|i| {
+                let (u, v): (f64, f64) = (rng.random_range(0.0..4.0), rng.random_range(0.0..3.0));
+                match i % 3 {
+                    0 => [u, v, 0.1 * (u * 2.0).sin() * (v * 3.0).cos()],
+                    1 => [u, 0.0, v],
+                    _ => [0.0, u, v],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sparse_icp_matches_full_icp() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let dst = room(6000, &mut rng);
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.2, -0.3, 1.0], 0.05)?,
+            [0.08, -0.05, 0.04],
+        );
+        let inverse = truth.inverse();
+        let src = dst.iter().map(|p| inverse.apply(p)).collect::<Vec<_>>();
+
+        let params = IcpParams {
+            max_iterations: 100,
+            tolerance: 1e-12,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 0.5,
+        };
+        for anchor_ratio in [1.0, 0.05] {
+            let (rotation, translation) = SparseIcp::register(&src, &dst, anchor_ratio, &params)?;
+            let error = RigidTransform3::new(rotation, translation).compose(&inverse);
+            assert!(error.rotation_angle() < 1e-6, "anchor ratio {anchor_ratio}");
+            assert!(error.translation.iter().all(|t| t.abs() < 1e-6));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sparse_icp_empty() -> Result<(), Box<dyn std::error::Error>> {
+        let params = IcpParams {
+            max_iterations: 10,
+            tolerance: 1e-9,
+            entropy_threshold: 0.0,
+            max_correspondence_distance: 1.0,
+        };
+        let identity = RigidTransform3::identity();
+        assert_eq!(
+            SparseIcp::register(&[], &[[0.0; 3]], 0.5, &params)?,
+            (identity.rotation, identity.translation)
+        );
+        Ok(())
+    }
+}
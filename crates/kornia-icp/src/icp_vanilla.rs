@@ -2,8 +2,15 @@ use core::f64;
 
 use kiddo::immutable::float::kdtree::ImmutableKdTree;
 
-use crate::ops::{find_correspondences, fit_transformation, update_transformation};
-use kornia_3d::{linalg::transform_points3d, pointcloud::PointCloud};
+use crate::{
+    ops::{find_correspondences, fit_transformation_unit_scale, update_transformation},
+    validate_icp_result, FitDiagnostics,
+};
+use kornia_3d::{
+    linalg::{transform_points3d, transform_points3d_vec},
+    pointcloud::PointCloud,
+    stats::entropy_convergence_criterion,
+};
 
 /// Result of the ICP algorithm.
 ///
@@ -18,6 +25,11 @@ pub struct ICPResult {
     pub num_iterations: usize,
     /// last computed RMSE.
     pub rmse: f64,
+    /// Diagnostics of the closed-form fit of the last iteration, if the variant solves one.
+    pub diagnostics: Option<FitDiagnostics>,
+    /// The fraction of source points with a target neighbor within the maximum
+    /// correspondence distance at the final transformation, if the variant has one.
+    pub overlap: Option<f64>,
 }
 
 /// Structure to define the ICP parameters.
@@ -27,6 +39,10 @@ pub struct ICPConvergenceCriteria {
     pub max_iterations: usize,
     /// Convergence tolerance as the difference in RMSE between two consecutive iterations.
     pub tolerance: f64,
+    /// Convergence threshold on the Kullback-Leibler divergence between the residual
+    /// distributions of two consecutive iterations, in nats, see
+    /// [`entropy_convergence_criterion`]. A non-positive threshold disables the criterion.
+    pub entropy_threshold: f64,
 }
 
 /// Iterative Closest Point (ICP) algorithm using point to point distance.
@@ -55,24 +71,19 @@ pub fn icp_vanilla(
         translation: initial_trans,
         num_iterations: 0,
         rmse: f64::INFINITY,
+        diagnostics: None,
+        overlap: None,
     };
 
     // build kdtree for target points to speed up the nearest neighbor search
     let kdtree: ImmutableKdTree<f64, u32, 3, 32> = ImmutableKdTree::new_from_slice(target.points());
 
-    // perform transformation using the initial rotation and translation
-    let mut transformed_points = vec![[0.0; 3]; source.points().len()];
-    transform_points3d(
-        source.points(),
-        &result.rotation,
-        &result.translation,
-        &mut transformed_points,
-    )?;
-
-    // initialize current source with the initial source point cloud
-    let mut current_source = transformed_points;
+    // initialize current source with the source transformed by the initial rotation and translation
+    let mut current_source =
+        transform_points3d_vec(source.points(), &result.rotation, &result.translation);
 
     // main icp loop
+    let mut prev_residuals = Vec::new();
     for i in 0..criteria.max_iterations {
         // NOTE: for debugging purposes, we measure the time taken for each iteration
         log::debug!("Iteration: {}", i);
@@ -91,12 +102,12 @@ pub fn icp_vanilla(
         // compute transformation between current source and closest points
         let mut rr_delta = [[0.0; 3]; 3];
         let mut tt_delta = [0.0; 3];
-        fit_transformation(
+        result.diagnostics = Some(fit_transformation_unit_scale(
             &current_source_match,
             &current_target_match,
             &mut rr_delta,
             &mut tt_delta,
-        );
+        ));
 
         // transform current source using the computed transformation
         let mut transformed_points = vec![[0.0; 3]; current_source.len()];
@@ -119,12 +130,19 @@ pub fn icp_vanilla(
 
         // compute error between transformed source and target
         let rmse = (distances.iter().sum::<f64>() / distances.len() as f64).sqrt();
+        let residuals = distances.iter().map(|d| d.sqrt()).collect::<Vec<_>>();
 
         // update the result structure
         result.num_iterations += 1;
 
         // check convergence and exit if below tolerance
-        if (result.rmse - rmse).abs() < criteria.tolerance {
+        if (result.rmse - rmse).abs() < criteria.tolerance
+            || entropy_convergence_criterion(
+                &prev_residuals,
+                &residuals,
+                criteria.entropy_threshold,
+            )
+        {
             log::debug!("ICP converged in {} iterations with error {}", i, rmse);
             result.rmse = rmse;
             break;
@@ -132,6 +150,7 @@ pub fn icp_vanilla(
 
         // update the result structure
         result.rmse = rmse;
+        prev_residuals = residuals;
 
         // swap current source with transformed points for the next iteration
         current_source = transformed_points;
@@ -140,6 +159,14 @@ pub fn icp_vanilla(
         log::debug!("elapsed: {:?}", elapsed);
     }
 
+    // guard against numerical blowups in the estimated transformation
+    validate_icp_result(
+        &result.rotation,
+        &result.translation,
+        f64::INFINITY,
+        f64::INFINITY,
+    )?;
+
     Ok(result)
 }
 
@@ -148,28 +175,24 @@ mod tests {
 
     use super::{icp_vanilla, ICPConvergenceCriteria};
     use kornia_3d::{
-        linalg::transform_points3d, pointcloud::PointCloud,
+        pointcloud::PointCloud, pose::generate_ground_truth_correspondences,
         transforms::axis_angle_to_rotation_matrix,
     };
+    use rand::{rngs::StdRng, Rng, SeedableRng};
 
     #[test]
     fn test_icp_vanilla() -> Result<(), Box<dyn std::error::Error>> {
         let num_points = 100;
+        let mut rng = StdRng::seed_from_u64(0);
         let points_src = (0..num_points)
-            .map(|_| {
-                [
-                    rand::random::<f64>(),
-                    rand::random::<f64>(),
-                    rand::random::<f64>(),
-                ]
-            })
+            .map(|_| rng.random::<[f64; 3]>())
             .collect::<Vec<_>>();
 
         let dst_r_src = axis_angle_to_rotation_matrix(&[1.0, 0.0, 0.0], 0.1)?;
         let dst_t_src = [0.1, 0.1, 0.1];
 
-        let mut points_dst = vec![[0.0; 3]; points_src.len()];
-        transform_points3d(&points_src, &dst_r_src, &dst_t_src, &mut points_dst)?;
+        let (points_src, points_dst) =
+            generate_ground_truth_correspondences(&points_src, &dst_r_src, &dst_t_src, 0.0, 0.0, 0);
 
         let src_pcl = PointCloud::new(points_src, None, None);
         let dst_pcl = PointCloud::new(points_dst, None, None);
@@ -185,6 +208,7 @@ mod tests {
             ICPConvergenceCriteria {
                 max_iterations: 100,
                 tolerance: 1e-6,
+                entropy_threshold: 0.0,
             },
         )?;
 
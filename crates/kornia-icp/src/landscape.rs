@@ -0,0 +1,234 @@
+use rayon::prelude::*;
+
+use kornia_3d::{
+    kdtree::KdTree,
+    linalg::dot_product3,
+    pose::Pose,
+    transforms::{axis_angle_to_rotation_matrix, RigidTransform3},
+};
+
+/// Compute the fitness score of a registration.
+///
+/// The score is the RMSE of the distances from the transformed source points to their
+/// nearest target point, which the point to point ICP minimizes. Lower is better.
+///
+/// # Arguments
+///
+/// * `src` - The source points.
+/// * `dst` - The target points.
+/// * `r` - The rotation from the source to the target frame.
+/// * `t` - The translation from the source to the target frame.
+///
+/// # Returns
+///
+/// The RMSE of the nearest neighbor distances, infinite if either set of points is empty.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::icp_fitness_score;
+///
+/// let src = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]];
+/// let dst = vec![[0.0, 0.0, 0.5], [1.0, 0.0, 0.5]];
+/// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// assert_eq!(icp_fitness_score(&src, &dst, &identity, &[0.0; 3]), 0.5);
+/// assert_eq!(icp_fitness_score(&src, &dst, &identity, &[0.0, 0.0, 0.5]), 0.0);
+/// ```
+pub fn icp_fitness_score(
+    src: &[[f64; 3]],
+    dst: &[[f64; 3]],
+    r: &[[f64; 3]; 3],
+    t: &[f64; 3],
+) -> f64 {
+    if src.is_empty() || dst.is_empty() {
+        return f64::INFINITY;
+    }
+    rmse_to(src, &KdTree::new(dst), &RigidTransform3::new(*r, *t))
+}
+
+/// Evaluate the fitness of the registrations on a grid of poses around the identity.
+///
+/// The rotation vectors of the grid have each of their components evenly spaced in
+/// `[-rotation_range_deg, rotation_range_deg]`, in degrees, and the translations each of
+/// their components evenly spaced in `[-translation_range, translation_range]`, a single
+/// step being the zero component. Each pose is scored by [`icp_fitness_score`] in parallel.
+/// The sorted scores show the shape of the energy that ICP descends: several poses scoring
+/// close to the best one reveal local minima, e.g. the symmetries of the object, from which
+/// ICP could converge to the wrong pose.
+///
+/// # Arguments
+///
+/// * `src` - The source points.
+/// * `dst` - The target points.
+/// * `rotation_range_deg` - The largest rotation about each axis, in degrees.
+/// * `rotation_steps` - The number of values of each rotation component.
+/// * `translation_range` - The largest translation along each axis.
+/// * `translation_steps` - The number of values of each translation component.
+/// * `top_k` - The number of best poses to return.
+///
+/// # Returns
+///
+/// The `top_k` best poses, as the rotation and translation from the source to the target
+/// frame, with their score in increasing order. It is empty if either set of points is
+/// empty or a number of steps is zero.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::fitness_landscape;
+///
+/// let src = (0..20).map(|i| [i as f64 * 0.1, (i % 3) as f64 * 0.2, 0.0]).collect::<Vec<_>>();
+/// let dst = src.iter().map(|p| [p[0] + 0.1, p[1], p[2]]).collect::<Vec<_>>();
+///
+/// let landscape = fitness_landscape(&src, &dst, 10.0, 3, 0.1, 3, 5);
+/// let ((_, translation), score) = landscape[0];
+/// assert_eq!(translation, [0.1, 0.0, 0.0]);
+/// assert!(score < 1e-12);
+/// ```
+pub fn fitness_landscape(
+    src: &[[f64; 3]],
+    dst: &[[f64; 3]],
+    rotation_range_deg: f64,
+    rotation_steps: usize,
+    translation_range: f64,
+    translation_steps: usize,
+    top_k: usize,
+) -> Vec<(Pose, f64)> {
+    if src.is_empty() || dst.is_empty() || rotation_steps == 0 || translation_steps == 0 {
+        return Vec::new();
+    }
+
+    let angles = grid_values(rotation_range_deg.to_radians(), rotation_steps);
+    let offsets = grid_values(translation_range, translation_steps);
+    let cube = |values: &[f64]| {
+        let n = values.len();
+        (0..n * n * n)
+            .map(|i| [values[i % n], values[i / n % n], values[i / (n * n)]])
+            .collect::<Vec<_>>()
+    };
+    let rotations = cube(&angles)
+        .into_iter()
+        .map(|omega| {
+            let angle = dot_product3(&omega, &omega).sqrt();
+            axis_angle_to_rotation_matrix(&omega, angle)
+                .unwrap_or(RigidTransform3::identity().rotation)
+        })
+        .collect::<Vec<_>>();
+    let translations = cube(&offsets);
+
+    let index = KdTree::new(dst);
+    let mut landscape = rotations
+        .par_iter()
+        .flat_map_iter(|rotation| {
+            translations.iter().map(|translation| {
+                let transform = RigidTransform3::new(*rotation, *translation);
+                ((*rotation, *translation), rmse_to(src, &index, &transform))
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // a stable sort keeps the order of the grid between equal scores
+    landscape.sort_by(|a, b| a.1.total_cmp(&b.1));
+    landscape.truncate(top_k);
+    landscape
+}
+
+/// The values evenly spaced in `[-range, range]`, or zero for a single step.
+fn grid_values(range: f64, steps: usize) -> Vec<f64> {
+    if steps == 1 {
+        return vec![0.0];
+    }
+    (0..steps)
+        .map(|i| -range + 2.0 * range * i as f64 / (steps - 1) as f64)
+        .collect()
+}
+
+/// Compute the RMSE of the distances from the transformed points to their nearest target.
+fn rmse_to(src: &[[f64; 3]], index: &KdTree, transform: &RigidTransform3) -> f64 {
+    let sum_sq = src
+        .iter()
+        .filter_map(|p| index.nearest_one(&transform.apply(p)))
+        .map(|nn| nn.distance * nn.distance)
+        .sum::<f64>();
+    (sum_sq / src.len() as f64).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sample a rectangular plate centered on the origin in the xy plane.
+    fn plate() -> Vec<[f64; 3]> {
+        (0..21 * 11)
+            .map(|i| {
+                [
+                    (i % 21) as f64 * 0.1 - 1.0,
+                    (i / 21) as f64 * 0.1 - 0.5,
+                    0.0,
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fitness_landscape_unique_minimum() -> Result<(), Box<dyn std::error::Error>> {
+        // an L of unequal legs, moved by a pose of the grid
+        let src = (0..30)
+            .map(|k| [k as f64 * 0.05, 0.0, 0.0])
+            .chain((1..12).map(|k| [0.0, k as f64 * 0.05, 0.0]))
+            .chain((1..8).map(|k| [0.0, 0.0, k as f64 * 0.05]))
+            .collect::<Vec<_>>();
+        let r = axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 20f64.to_radians())?;
+        let t = [0.0, -0.2, 0.2];
+        let dst = kornia_3d::linalg::transform_points3d_vec(&src, &r, &t);
+
+        let landscape = fitness_landscape(&src, &dst, 20.0, 5, 0.2, 5, 10);
+        assert_eq!(landscape.len(), 10);
+        assert!(landscape.windows(2).all(|w| w[0].1 <= w[1].1));
+
+        let ((rotation, translation), score) = landscape[0];
+        assert!(score < 1e-9);
+        assert_eq!(translation, t);
+        for i in 0..3 {
+            for j in 0..3 {
+                assert!((rotation[i][j] - r[i][j]).abs() < 1e-12);
+            }
+        }
+        // the other poses are clearly worse
+        assert!(landscape[1].1 > 0.01);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fitness_landscape_symmetries() {
+        // the half turns about the axes of the plate map it onto itself
+        let points = plate();
+        let landscape = fitness_landscape(&points, &points, 180.0, 3, 0.0, 1, 27);
+        assert_eq!(landscape.len(), 27);
+        let minima = landscape.iter().filter(|(_, score)| *score < 1e-9).count();
+        assert_eq!(minima, 7);
+        assert_eq!(
+            landscape
+                .iter()
+                .filter(|(pose, _)| pose.0[0][0] > 0.99 && pose.0[1][1] > 0.99)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_fitness_landscape_empty() {
+        let points = plate();
+        assert!(fitness_landscape(&[], &points, 10.0, 3, 0.1, 3, 5).is_empty());
+        assert!(fitness_landscape(&points, &points, 10.0, 0, 0.1, 3, 5).is_empty());
+        assert_eq!(
+            icp_fitness_score(
+                &points,
+                &[],
+                &RigidTransform3::identity().rotation,
+                &[0.0; 3]
+            ),
+            f64::INFINITY
+        );
+    }
+}
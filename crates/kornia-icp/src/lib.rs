@@ -1,7 +1,109 @@
 #![deny(missing_docs)]
 #![doc = env!("CARGO_PKG_DESCRIPTION")]
 
+mod align;
+pub use align::*;
+
+mod coherent_drift;
+pub use coherent_drift::*;
+
+mod confidence;
+pub use confidence::*;
+
+mod convergence;
+pub use convergence::*;
+
+mod covariance;
+pub use covariance::*;
+
+mod diagnostics;
+pub use diagnostics::*;
+
+mod error;
+pub use error::*;
+
+mod export;
+pub use export::*;
+
+mod icp_adaptive;
+pub use icp_adaptive::*;
+
+mod icp_density;
+pub use icp_density::*;
+
+mod icp_ellipsoid;
+pub use icp_ellipsoid::*;
+
+mod icp_idc;
+pub use icp_idc::*;
+
+mod icp_line;
+pub use icp_line::*;
+
+mod icp_multi_hypothesis;
+pub use icp_multi_hypothesis::*;
+
+mod icp_multiscale;
+pub use icp_multiscale::*;
+
+mod icp_ndt;
+pub use icp_ndt::*;
+
+mod icp_non_rigid;
+pub use icp_non_rigid::*;
+
+mod icp_point_to_plane;
+pub use icp_point_to_plane::*;
+
+mod icp_robust;
+pub use icp_robust::*;
+
+mod icp_saliency;
+pub use icp_saliency::*;
+
+mod icp_semantic;
+pub use icp_semantic::*;
+
+mod icp_so3;
+pub use icp_so3::*;
+
+mod icp_sparse;
+pub use icp_sparse::*;
+
 mod icp_vanilla;
 pub use icp_vanilla::*;
 
+mod landscape;
+pub use landscape::*;
+
+mod multiview;
+pub use multiview::*;
+
+mod odometry;
+pub use odometry::*;
+
 mod ops;
+
+mod pca_alignment;
+pub use pca_alignment::*;
+
+mod overlap;
+pub use overlap::*;
+
+mod params;
+pub use params::*;
+
+mod planes;
+pub use planes::*;
+
+mod pose_graph;
+pub use pose_graph::*;
+
+mod sample_validator;
+pub use sample_validator::*;
+
+mod sampling;
+pub use sampling::*;
+
+mod validation;
+pub use validation::*;
@@ -0,0 +1,214 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+use kornia_3d::{pointcloud::PointCloud, transforms::RigidTransform3};
+
+use crate::{robust_icp, EdgeResidual, IcpError, PoseGraph, PoseGraphKernel, RobustIcpParams};
+
+/// Parameters of [`register_multiview`].
+#[derive(Debug, Clone)]
+pub struct MultiviewParams {
+    /// The parameters of the pairwise registrations. Their maximum correspondence distance
+    /// also selects the inlier correspondences of the information matrices of the edges.
+    pub pairwise: RobustIcpParams,
+    /// The robust kernel of the pose graph edges, or `None` for plain least squares.
+    pub kernel: Option<PoseGraphKernel>,
+    /// Maximum number of sweeps of the pose graph optimization.
+    pub max_sweeps: usize,
+    /// The chi-square value above which an edge is an outlier, e.g. 22.46 for the 99.9%
+    /// quantile with 6 degrees of freedom.
+    pub outlier_threshold: f64,
+}
+
+/// Result of [`register_multiview`].
+#[derive(Debug, Clone)]
+pub struct MultiviewResult {
+    /// The optimized pose of each scan, from its frame to the frame of the first scan.
+    pub poses: Vec<RigidTransform3>,
+    /// The residual of each pairwise registration at the optimized poses, in the order of
+    /// the pairs.
+    pub edge_residuals: Vec<EdgeResidual>,
+    /// The sorted indices of the pairs whose registration is inconsistent with the
+    /// optimized poses, e.g. a wrong loop closure.
+    pub outlier_edges: Vec<usize>,
+}
+
+/// Register several scans jointly with pairwise registrations and a pose graph.
+///
+/// Each pair `(from, to)` is registered with [`robust_icp`], the scan `to` being moved onto
+/// the scan `from` from the relative pose of their initial poses. The registrations are the
+/// edges of a [`PoseGraph`], weighted by their information matrices, whose poses are
+/// optimized with the first scan held. The residuals of the edges at the optimized poses
+/// then tell which pairwise registrations were wrong, see [`PoseGraph::edge_residuals`].
+///
+/// # Arguments
+///
+/// * `scans` - The scans, each in its own frame.
+/// * `initial_poses` - The initial pose of each scan, from its frame to a common frame.
+/// * `pairs` - The pairs of scans to register, e.g. the consecutive scans and the loop
+///   closures.
+/// * `params` - The parameters of the registration.
+///
+/// PRECONDITION: there is one initial pose per scan and the pairs are indices of scans.
+///
+/// # Returns
+///
+/// The optimized poses with the residual of each pair, or an error if a pairwise
+/// registration fails.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{register_multiview, KernelScale, MultiviewParams, RobustIcpParams, RobustKernel};
+/// use kornia_3d::{pointcloud::PointCloud, transforms::RigidTransform3};
+///
+/// let points = (0..125)
+///     .map(|i| [(i % 5) as f64 * 0.1, (i / 5 % 5) as f64 * 0.1, (i / 25) as f64 * 0.13])
+///     .collect::<Vec<_>>();
+/// let scans = vec![PointCloud::new(points, None, None); 3];
+/// let params = MultiviewParams {
+///     pairwise: RobustIcpParams {
+///         max_iterations: 20,
+///         tolerance: 1e-12,
+///         entropy_threshold: 0.0,
+///         max_correspondence_distance: 0.05,
+///         kernel: RobustKernel::Huber,
+///         scale: KernelScale::Fixed(0.05),
+///     },
+///     kernel: None,
+///     max_sweeps: 10,
+///     outlier_threshold: 22.46,
+/// };
+/// let initial_poses = vec![RigidTransform3::identity(); 3];
+/// let result = register_multiview(&scans, &initial_poses, &[(0, 1), (1, 2), (2, 0)], &params)?;
+/// assert_eq!(result.edge_residuals.len(), 3);
+/// assert!(result.outlier_edges.is_empty());
+/// # Ok::<(), kornia_icp::IcpError>(())
+/// ```
+pub fn register_multiview(
+    scans: &[PointCloud],
+    initial_poses: &[RigidTransform3],
+    pairs: &[(usize, usize)],
+    params: &MultiviewParams,
+) -> Result<MultiviewResult, IcpError> {
+    let indices = scans
+        .iter()
+        .map(|scan| ImmutableKdTree::<f64, u32, 3, 32>::new_from_slice(scan.points()))
+        .collect::<Vec<_>>();
+
+    let mut graph = PoseGraph::new(initial_poses.to_vec());
+    for &(from, to) in pairs {
+        // move the scan onto the other one from their initial relative pose
+        let initial = initial_poses[from].inverse().compose(&initial_poses[to]);
+        let moved = PointCloud::new(
+            scans[to]
+                .points()
+                .iter()
+                .map(|p| initial.apply(p))
+                .collect(),
+            None,
+            None,
+        );
+        let robust = robust_icp(&moved, &scans[from], &params.pairwise)?;
+        let correction = RigidTransform3::new(robust.result.rotation, robust.result.translation);
+
+        graph.add_registration_edge(
+            from,
+            to,
+            &scans[to],
+            &indices[from],
+            correction.compose(&initial),
+            params.pairwise.max_correspondence_distance,
+        );
+    }
+
+    graph.optimize(&[0], params.max_sweeps, params.kernel);
+    Ok(MultiviewResult {
+        edge_residuals: graph.edge_residuals(),
+        outlier_edges: graph.flag_outlier_edges(params.outlier_threshold),
+        poses: graph.poses,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KernelScale, RobustKernel};
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn params(kernel: Option<PoseGraphKernel>) -> MultiviewParams {
+        MultiviewParams {
+            pairwise: RobustIcpParams {
+                max_iterations: 50,
+                tolerance: 1e-12,
+                entropy_threshold: 0.0,
+                max_correspondence_distance: 0.2,
+                kernel: RobustKernel::Huber,
+                scale: KernelScale::Fixed(0.05),
+            },
+            kernel,
+            max_sweeps: 50,
+            outlier_threshold: 22.46,
+        }
+    }
+
+    #[test]
+    fn test_register_multiview() -> Result<(), Box<dyn std::error::Error>> {
+        // a bumpy patch, with no symmetry, seen from four poses
+        let mut rng = StdRng::seed_from_u64(0);
+        let points = (0..400)
+            .map(|_| {
+                let (u, v): (f64, f64) = (rng.random_range(0.0..1.0), rng.random_range(0.0..0.6));
+                [
+                    u,
+                    v,
+                    0.3 * (3.0 * u).sin() * (2.0 * v + 0.5).cos() + 0.2 * u * u,
+                ]
+            })
+            .collect::<Vec<_>>();
+        let mut truth = vec![RigidTransform3::identity()];
+        for i in 1..4 {
+            let angle = 0.02 * i as f64;
+            truth.push(RigidTransform3::new(
+                axis_angle_to_rotation_matrix(&[0.2, -0.4, 1.0], angle)?,
+                [0.02 * i as f64, -0.01, 0.01 * i as f64],
+            ));
+        }
+        let scans = truth
+            .iter()
+            .map(|pose| {
+                let world_to_scan = pose.inverse();
+                PointCloud::new(
+                    points.iter().map(|p| world_to_scan.apply(p)).collect(),
+                    None,
+                    None,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let pairs = [(0, 1), (1, 2), (2, 3), (3, 0), (0, 2)];
+        let kernel = PoseGraphKernel {
+            kernel: RobustKernel::Cauchy,
+            scale: 3.55,
+        };
+        let initial_poses = vec![RigidTransform3::identity(); 4];
+        let result = register_multiview(&scans, &initial_poses, &pairs, &params(Some(kernel)))?;
+
+        for (pose, expected) in result.poses.iter().zip(truth.iter()) {
+            let error = pose.compose(&expected.inverse());
+            assert!(error.rotation_angle() < 1e-6);
+            assert!(error.translation.iter().all(|t| t.abs() < 1e-6));
+        }
+        assert_eq!(result.edge_residuals.len(), pairs.len());
+        assert!(result.edge_residuals.iter().all(|r| r.chi2 < 1e-6));
+        assert!(result.outlier_edges.is_empty());
+
+        // an empty scan cannot be registered
+        let mut with_empty = scans.clone();
+        with_empty[3] = PointCloud::new(vec![], None, None);
+        assert!(matches!(
+            register_multiview(&with_empty, &initial_poses, &pairs, &params(None)),
+            Err(IcpError::EmptyCloud)
+        ));
+        Ok(())
+    }
+}
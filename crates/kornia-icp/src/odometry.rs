@@ -0,0 +1,532 @@
+use std::collections::VecDeque;
+
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+
+use crate::{
+    compute_information_matrix, icp_multiscale, ICPResult, IcpError, LevelReport, MultiScaleConfig,
+    MultiScaleLevel, PoseGraph, PoseGraphEdge,
+};
+use kornia_3d::{
+    filters::{deduplicate, DedupPolicy},
+    pointcloud::PointCloud,
+    transforms::RigidTransform3,
+};
+
+/// Configuration of the fixed-lag sliding-window refinement of [`IcpOdometry`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlidingWindowConfig {
+    /// The number of keyframes kept in the window, at least two.
+    pub window_size: usize,
+    /// A frame becomes a keyframe when it moved more than this distance from the last
+    /// keyframe.
+    pub keyframe_translation: f64,
+    /// A frame becomes a keyframe when it rotated more than this angle in radians from the
+    /// last keyframe.
+    pub keyframe_rotation: f64,
+    /// Voxel size used to downsample the local map fused from the keyframes.
+    pub map_voxel_size: f64,
+}
+
+/// Configuration of the [`IcpOdometry`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OdometryConfig {
+    /// Voxel size used to downsample each frame, or `None` to use the frames at full
+    /// resolution.
+    pub voxel_size: Option<f64>,
+    /// Maximum distance between two points to be considered a correspondence.
+    pub max_correspondence_distance: f64,
+    /// Maximum number of iterations of each registration.
+    pub max_iterations: usize,
+    /// Convergence tolerance as the difference in RMSE between two consecutive iterations.
+    pub tolerance: f64,
+    /// The sliding-window refinement, or `None` for a frame-to-frame odometry.
+    pub window: Option<SlidingWindowConfig>,
+}
+
+/// Statistics of the registration of a frame by the [`IcpOdometry`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameStats {
+    /// The RMSE of the correspondences of the last iteration, zero for the first frame.
+    pub rmse: f64,
+    /// The fraction of the points of the frame with a correspondence, one for the first
+    /// frame.
+    pub overlap: f64,
+    /// Whether the frame was added to the sliding window as a keyframe.
+    pub keyframe: bool,
+    /// The distance by which the refinement of the window moved the pose of the frame, zero
+    /// if it is not a keyframe.
+    pub correction_translation: f64,
+    /// The angle in radians by which the refinement of the window rotated the pose of the
+    /// frame, zero if it is not a keyframe.
+    pub correction_rotation: f64,
+}
+
+/// A keyframe of the sliding window.
+#[derive(Debug, Clone)]
+struct Keyframe {
+    /// The index of the frame.
+    frame: usize,
+    /// The downsampled points of the frame, in its own frame.
+    cloud: PointCloud,
+}
+
+/// Maximum number of sweeps of the pose graph optimization of the window.
+const WINDOW_OPTIMIZATION_SWEEPS: usize = 20;
+
+/// Minimum fraction of the points of a keyframe matched by a pairwise registration for it
+/// to constrain the window.
+const MIN_KEYFRAME_OVERLAP: f64 = 0.3;
+
+/// Point to point ICP odometry of a sequence of scans.
+///
+/// Each frame is registered with a constant velocity prediction as initial guess, either
+/// against the previous frame, or with a sliding window against a local map fused from the
+/// last keyframes. The sliding window bounds the drift of the frame-to-frame odometry: a
+/// frame is registered against the points of several earlier frames, and when a keyframe
+/// is added the poses of the window are jointly refined by a small [`PoseGraph`] of the
+/// pairwise registrations of the keyframes, the oldest keyframe being held.
+///
+/// The poses are the transformations from the frame of each scan to the frame of the first
+/// scan.
+#[derive(Debug, Clone)]
+pub struct IcpOdometry {
+    config: OdometryConfig,
+    poses: Vec<RigidTransform3>,
+    frame_stats: Vec<FrameStats>,
+    previous: Option<PointCloud>,
+    keyframes: VecDeque<Keyframe>,
+    keyframe_edges: Vec<PoseGraphEdge>,
+    local_map: Option<PointCloud>,
+}
+
+impl IcpOdometry {
+    /// Create an odometry without frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration of the odometry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IcpError::InvalidWindowSize`] if the sliding window keeps fewer than two
+    /// keyframes.
+    pub fn new(config: OdometryConfig) -> Result<Self, IcpError> {
+        if let Some(window) = config.window.filter(|window| window.window_size < 2) {
+            return Err(IcpError::InvalidWindowSize(window.window_size));
+        }
+        Ok(Self {
+            config,
+            poses: Vec::new(),
+            frame_stats: Vec::new(),
+            previous: None,
+            keyframes: VecDeque::new(),
+            keyframe_edges: Vec::new(),
+            local_map: None,
+        })
+    }
+
+    /// Get as reference the configuration of the odometry.
+    pub fn config(&self) -> &OdometryConfig {
+        &self.config
+    }
+
+    /// The pose of each processed frame, with the refinements of the sliding window.
+    pub fn poses(&self) -> &[RigidTransform3] {
+        &self.poses
+    }
+
+    /// The statistics of each processed frame.
+    pub fn frame_stats(&self) -> &[FrameStats] {
+        &self.frame_stats
+    }
+
+    /// Register a new frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `cloud` - The points of the frame, in the frame of the sensor.
+    ///
+    /// # Returns
+    ///
+    /// The pose of the frame, from its frame to the frame of the first frame. The first
+    /// frame is at the identity.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_icp::{IcpOdometry, OdometryConfig};
+    /// use kornia_3d::pointcloud::PointCloud;
+    ///
+    /// // the corner of a room, seen from a sensor moving along x
+    /// let frame = |x: f64| {
+    ///     let points = (0..1200)
+    ///         .map(|i| {
+    ///             let (u, v) = ((i / 3 % 20) as f64 * 0.2, (i / 60) as f64 * 0.2);
+    ///             [[u, v, 0.0], [u, 0.0, v], [0.0, u, v]][i % 3]
+    ///         })
+    ///         .map(|p| [p[0] - x, p[1], p[2]])
+    ///         .collect();
+    ///     PointCloud::new(points, None, None)
+    /// };
+    /// let config = OdometryConfig {
+    ///     voxel_size: None,
+    ///     max_correspondence_distance: 0.5,
+    ///     max_iterations: 50,
+    ///     tolerance: 1e-9,
+    ///     window: None,
+    /// };
+    ///
+    /// let mut odometry = IcpOdometry::new(config).unwrap();
+    /// odometry.process(&frame(0.0)).unwrap();
+    /// let pose = odometry.process(&frame(0.05)).unwrap();
+    /// assert!((pose.translation[0] - 0.05).abs() < 1e-6);
+    /// assert_eq!(odometry.frame_stats().len(), 2);
+    /// ```
+    pub fn process(&mut self, cloud: &PointCloud) -> Result<RigidTransform3, IcpError> {
+        if cloud.is_empty() {
+            return Err(IcpError::EmptyCloud);
+        }
+        let cloud = match self.config.voxel_size {
+            Some(voxel_size) => deduplicate(cloud, voxel_size, DedupPolicy::Centroid).0,
+            None => cloud.clone(),
+        };
+
+        let Some(last) = self.poses.last().copied() else {
+            // the first frame anchors the world frame
+            self.poses.push(RigidTransform3::identity());
+            self.frame_stats.push(FrameStats {
+                rmse: 0.0,
+                overlap: 1.0,
+                keyframe: self.config.window.is_some(),
+                correction_translation: 0.0,
+                correction_rotation: 0.0,
+            });
+            if self.config.window.is_some() {
+                self.add_keyframe(&cloud)?;
+            }
+            self.previous = Some(cloud);
+            return Ok(RigidTransform3::identity());
+        };
+
+        // constant velocity prediction
+        let predicted = match self.poses.len() {
+            1 => last,
+            n => last.compose(&self.poses[n - 2].inverse().compose(&last)),
+        };
+
+        let (pose, result) = match (&self.local_map, &self.previous) {
+            (Some(local_map), _) => {
+                let (result, _) = self.register(&cloud, local_map, &predicted)?;
+                (
+                    RigidTransform3::new(result.rotation, result.translation),
+                    result,
+                )
+            }
+            (None, Some(previous)) => {
+                let initial = last.inverse().compose(&predicted);
+                let (result, _) = self.register(&cloud, previous, &initial)?;
+                let relative = RigidTransform3::new(result.rotation, result.translation);
+                (last.compose(&relative), result)
+            }
+            (None, None) => return Err(IcpError::MissingReference),
+        };
+        // the poses are chained over the whole sequence, and the rounding errors of the
+        // products of rotations would otherwise grow until the registrations reject the
+        // predicted rotations
+        let pose = pose.orthonormalized();
+        self.poses.push(pose);
+
+        let mut stats = FrameStats {
+            rmse: result.rmse,
+            overlap: result.overlap.unwrap_or(0.0),
+            keyframe: false,
+            correction_translation: 0.0,
+            correction_rotation: 0.0,
+        };
+        if let (Some(window), Some(keyframe)) = (self.config.window, self.keyframes.back()) {
+            let motion = self.poses[keyframe.frame].inverse().compose(&pose);
+            let distance = motion.translation.iter().map(|t| t * t).sum::<f64>().sqrt();
+            if distance > window.keyframe_translation
+                || motion.rotation_angle() > window.keyframe_rotation
+            {
+                self.add_keyframe(&cloud)?;
+                let correction = pose.inverse().compose(&self.poses[self.poses.len() - 1]);
+                stats.keyframe = true;
+                stats.correction_translation = correction
+                    .translation
+                    .iter()
+                    .map(|t| t * t)
+                    .sum::<f64>()
+                    .sqrt();
+                stats.correction_rotation = correction.rotation_angle();
+            }
+        }
+        self.frame_stats.push(stats);
+        self.previous = Some(cloud);
+
+        Ok(self.poses[self.poses.len() - 1])
+    }
+
+    /// Register a cloud against a target with a single level of [`icp_multiscale`].
+    fn register(
+        &self,
+        source: &PointCloud,
+        target: &PointCloud,
+        initial: &RigidTransform3,
+    ) -> Result<(ICPResult, Vec<LevelReport>), IcpError> {
+        let config = MultiScaleConfig {
+            levels: vec![MultiScaleLevel {
+                voxel_size: None,
+                max_correspondence_distance: self.config.max_correspondence_distance,
+                max_iterations: self.config.max_iterations,
+            }],
+            tolerance: self.config.tolerance,
+            entropy_threshold: 0.0,
+            trim_fraction: 1.0,
+        };
+        icp_multiscale(
+            source,
+            target,
+            initial.rotation,
+            initial.translation,
+            &config,
+        )
+    }
+
+    /// Add the last frame to the window, refine the poses of the window and rebuild the
+    /// local map.
+    fn add_keyframe(&mut self, cloud: &PointCloud) -> Result<(), IcpError> {
+        let Some(window) = self.config.window else {
+            return Ok(());
+        };
+        let frame = self.poses.len() - 1;
+
+        // register the new keyframe against the keyframes of the window, the edges being
+        // between frame indices
+        let new_edges = self
+            .keyframes
+            .iter()
+            .filter_map(|keyframe| {
+                let initial = self.poses[keyframe.frame]
+                    .inverse()
+                    .compose(&self.poses[frame]);
+                let (result, _) = self.register(cloud, &keyframe.cloud, &initial).ok()?;
+                if result.overlap.unwrap_or(0.0) < MIN_KEYFRAME_OVERLAP {
+                    return None;
+                }
+                let measurement = RigidTransform3::new(result.rotation, result.translation);
+                let index: ImmutableKdTree<f64, u32, 3, 32> =
+                    ImmutableKdTree::new_from_slice(keyframe.cloud.points());
+                Some(PoseGraphEdge {
+                    from: keyframe.frame,
+                    to: frame,
+                    measurement,
+                    information: compute_information_matrix(
+                        cloud,
+                        &index,
+                        &measurement,
+                        self.config.max_correspondence_distance,
+                    ),
+                })
+            })
+            .collect::<Vec<_>>();
+        self.keyframe_edges.extend(new_edges);
+
+        self.keyframes.push_back(Keyframe {
+            frame,
+            cloud: cloud.clone(),
+        });
+        while self.keyframes.len() > window.window_size {
+            if let Some(oldest) = self.keyframes.pop_front() {
+                self.keyframe_edges
+                    .retain(|edge| edge.from != oldest.frame && edge.to != oldest.frame);
+            }
+        }
+
+        // jointly refine the poses of the window, the oldest keyframe anchoring the gauge
+        if self.keyframes.len() > 1 {
+            let node = |frame: usize| self.keyframes.iter().position(|k| k.frame == frame);
+            let mut graph = PoseGraph::new(
+                self.keyframes
+                    .iter()
+                    .map(|keyframe| self.poses[keyframe.frame])
+                    .collect(),
+            );
+            for edge in self.keyframe_edges.iter() {
+                if let (Some(from), Some(to)) = (node(edge.from), node(edge.to)) {
+                    graph.add_edge(from, to, edge.measurement, edge.information);
+                }
+            }
+            graph.optimize(&[0], WINDOW_OPTIMIZATION_SWEEPS, None);
+            for (keyframe, pose) in self.keyframes.iter().zip(graph.poses) {
+                self.poses[keyframe.frame] = pose.orthonormalized();
+            }
+        }
+
+        // fuse the keyframes into the local map, in the world frame
+        let points = self
+            .keyframes
+            .iter()
+            .flat_map(|keyframe| {
+                let pose = self.poses[keyframe.frame];
+                keyframe.cloud.points().iter().map(move |p| pose.apply(p))
+            })
+            .collect();
+        let fused = PointCloud::new(points, None, None);
+        self.local_map = Some(deduplicate(&fused, window.map_voxel_size, DedupPolicy::Centroid).0);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const SEED: u64 = 7;
+    const NUM_FRAMES: usize = 100;
+
+    /// Sample a scan of a corridor along x, with a ground, walls at `y = ±4` and poles
+    /// every 3 meters along the walls, within 10 meters of the sensor.
+    fn corridor_scan(pose: &RigidTransform3, rng: &mut StdRng) -> PointCloud {
+        let [x, y, _] = pose.translation;
+        let mut world = Vec::new();
+        for _ in 0..150 {
+            world.push([
+                x + rng.random_range(-10.0..10.0),
+                y + rng.random_range(-4.0..4.0),
+                0.0,
+            ]);
+        }
+        for _ in 0..100 {
+            let side = if rng.random::<bool>() { 4.0 } else { -4.0 };
+            world.push([
+                x + rng.random_range(-10.0..10.0),
+                side,
+                rng.random_range(0.0..3.0),
+            ]);
+        }
+        let first_pole = ((x - 10.0) / 3.0).ceil() as i64;
+        for k in first_pole..=((x + 10.0) / 3.0).floor() as i64 {
+            for side in [-3.0, 3.0] {
+                for _ in 0..6 {
+                    let angle = rng.random_range(0.0..std::f64::consts::TAU);
+                    world.push([
+                        k as f64 * 3.0 + 0.15 * angle.cos(),
+                        side + 0.15 * angle.sin(),
+                        rng.random_range(0.0..3.0),
+                    ]);
+                }
+            }
+        }
+
+        let inverse = pose.inverse();
+        let points = world
+            .iter()
+            .map(|p| {
+                let q = inverse.apply(p);
+                [0, 1, 2].map(|a| q[a] + rng.random_range(-0.02..0.02))
+            })
+            .collect();
+        PointCloud::new(points, None, None)
+    }
+
+    fn run(
+        window: Option<SlidingWindowConfig>,
+        truth: &[RigidTransform3],
+    ) -> Result<IcpOdometry, Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let mut odometry = IcpOdometry::new(OdometryConfig {
+            voxel_size: Some(0.2),
+            max_correspondence_distance: 0.5,
+            max_iterations: 30,
+            tolerance: 1e-6,
+            window,
+        })?;
+        for pose in truth {
+            odometry.process(&corridor_scan(pose, &mut rng))?;
+        }
+        Ok(odometry)
+    }
+
+    #[test]
+    fn test_odometry_sliding_window_drift() -> Result<(), Box<dyn std::error::Error>> {
+        let truth = (0..NUM_FRAMES)
+            .map(|i| {
+                let yaw = 0.1 * (i as f64 / 15.0).sin();
+                Ok(RigidTransform3::new(
+                    axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], yaw)?,
+                    [0.15 * i as f64, 0.5 * (i as f64 / 20.0).sin(), 0.0],
+                ))
+            })
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+        let drift = |odometry: &IcpOdometry| {
+            let error = odometry.poses()[NUM_FRAMES - 1].compose(&truth[NUM_FRAMES - 1].inverse());
+            error.translation.iter().map(|t| t * t).sum::<f64>().sqrt()
+        };
+
+        let frame_to_frame = run(None, &truth)?;
+        assert_eq!(frame_to_frame.poses().len(), NUM_FRAMES);
+        assert!(frame_to_frame.frame_stats().iter().all(|s| !s.keyframe));
+
+        let windowed = run(
+            Some(SlidingWindowConfig {
+                window_size: 5,
+                keyframe_translation: 1.0,
+                keyframe_rotation: 0.1,
+                map_voxel_size: 0.2,
+            }),
+            &truth,
+        )?;
+        let stats = windowed.frame_stats();
+        assert_eq!(stats.len(), NUM_FRAMES);
+        assert!(stats.iter().filter(|s| s.keyframe).count() >= 10);
+        assert!(stats.iter().all(|s| s.overlap > 0.5));
+
+        let (drift_frame_to_frame, drift_windowed) = (drift(&frame_to_frame), drift(&windowed));
+        assert!(
+            drift_windowed < 0.7 * drift_frame_to_frame,
+            "windowed drift {drift_windowed}, frame to frame drift {drift_frame_to_frame}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_odometry_empty_frame() -> Result<(), IcpError> {
+        let mut odometry = IcpOdometry::new(OdometryConfig {
+            voxel_size: None,
+            max_correspondence_distance: 0.5,
+            max_iterations: 10,
+            tolerance: 1e-6,
+            window: None,
+        })?;
+        assert!(matches!(
+            odometry.process(&PointCloud::new(Vec::new(), None, None)),
+            Err(IcpError::EmptyCloud)
+        ));
+        assert!(odometry.poses().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_odometry_invalid_window_size() {
+        let config = OdometryConfig {
+            voxel_size: None,
+            max_correspondence_distance: 0.5,
+            max_iterations: 10,
+            tolerance: 1e-6,
+            window: Some(SlidingWindowConfig {
+                window_size: 1,
+                keyframe_translation: 1.0,
+                keyframe_rotation: 0.1,
+                map_voxel_size: 0.2,
+            }),
+        };
+        assert!(matches!(
+            IcpOdometry::new(config),
+            Err(IcpError::InvalidWindowSize(1))
+        ));
+    }
+}
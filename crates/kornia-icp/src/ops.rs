@@ -1,26 +1,225 @@
 use kiddo::immutable::float::kdtree::ImmutableKdTree;
-use kornia_3d::linalg;
+use kornia_3d::{
+    linalg,
+    transforms::{axis_angle_to_rotation_matrix, RigidTransform3},
+};
+
+use crate::FitDiagnostics;
 
 /// Compute the transformation between two point clouds.
+///
+/// The rotation is `R = V * diag(1, 1, d) * U^T` from the SVD `H = U * Σ * V^T` of the
+/// covariance matrix, `d = ±1` excluding reflections. The singular values of `R` are all
+/// one, so the fit carries no scale and composing many incremental fits does not drift in
+/// scale.
 pub(crate) fn fit_transformation(
     points_in_src: &[[f64; 3]],
     points_in_dst: &[[f64; 3]],
     dst_r_src: &mut [[f64; 3]; 3],
     dst_t_src: &mut [f64; 3],
-) {
+) -> FitDiagnostics {
     assert_eq!(points_in_src.len(), points_in_dst.len());
 
     // compute centroids
     let (src_centroid, dst_centroid) = compute_centroids(points_in_src, points_in_dst);
 
     // compute covariance matrix
+    let hh = cross_covariance(points_in_src, points_in_dst, &src_centroid, &dst_centroid);
+
+    let (reflection_fixed, singular_values) =
+        solve_transformation(&hh, &src_centroid, &dst_centroid, dst_r_src, dst_t_src);
+    let residual_rmse = residual_rmse(points_in_src, points_in_dst, None, dst_r_src, dst_t_src);
+    diagnose(reflection_fixed, singular_values, residual_rmse)
+}
+
+/// Compute the transformation between two point clouds, constrained to a unit scale.
+///
+/// The rotation of the Kabsch fit [`fit_transformation`] is already orthogonal, so this is
+/// the same fit under the name of its constraint: a scale difference between the clouds is
+/// not absorbed by the rotation, and composing many incremental fits does not drift in
+/// scale.
+pub(crate) fn fit_transformation_unit_scale(
+    points_in_src: &[[f64; 3]],
+    points_in_dst: &[[f64; 3]],
+    dst_r_src: &mut [[f64; 3]; 3],
+    dst_t_src: &mut [f64; 3],
+) -> FitDiagnostics {
+    fit_transformation(points_in_src, points_in_dst, dst_r_src, dst_t_src)
+}
+
+/// Fit the rigid transformation between corresponding points with the Kabsch fit.
+///
+/// Returns `None` if the points are collinear, i.e. if the cross-covariance matrix has a
+/// rank below two, which leaves the rotation about their line undetermined.
+pub(crate) fn fit_rigid_transform(
+    points_in_src: &[[f64; 3]],
+    points_in_dst: &[[f64; 3]],
+) -> Option<RigidTransform3> {
+    let mut rotation = [[0.0; 3]; 3];
+    let mut translation = [0.0; 3];
+    let diagnostics = fit_transformation(
+        points_in_src,
+        points_in_dst,
+        &mut rotation,
+        &mut translation,
+    );
+    (diagnostics.rank >= 2).then(|| RigidTransform3::new(rotation, translation))
+}
+
+/// Fit the rigid transformation between weighted corresponding points with the Kabsch fit.
+///
+/// Returns `None` if fewer than three points have a positive weight or if they are
+/// collinear.
+pub(crate) fn fit_rigid_transform_weighted(
+    points_in_src: &[[f64; 3]],
+    points_in_dst: &[[f64; 3]],
+    weights: &[f64],
+) -> Option<RigidTransform3> {
+    if weights.iter().filter(|w| **w > 0.0).count() < 3 {
+        return None;
+    }
+    let mut rotation = [[0.0; 3]; 3];
+    let mut translation = [0.0; 3];
+    let diagnostics = fit_transformation_weighted(
+        points_in_src,
+        points_in_dst,
+        weights,
+        &mut rotation,
+        &mut translation,
+    );
+    (diagnostics.rank >= 2).then(|| RigidTransform3::new(rotation, translation))
+}
+
+/// Compute the covariance matrix of the centered correspondences.
+fn cross_covariance(
+    points_in_src: &[[f64; 3]],
+    points_in_dst: &[[f64; 3]],
+    src_centroid: &faer::Col<f64>,
+    dst_centroid: &faer::Col<f64>,
+) -> faer::Mat<f64> {
     let mut hh = faer::Mat::<f64>::zeros(3, 3);
     for (p_in_src, p_in_dst) in points_in_src.iter().zip(points_in_dst.iter()) {
-        let p_src = faer::col![p_in_src[0], p_in_src[1], p_in_src[2]] - &src_centroid;
-        let p_dst = faer::col![p_in_dst[0], p_in_dst[1], p_in_dst[2]] - &dst_centroid;
+        let p_src = faer::col![p_in_src[0], p_in_src[1], p_in_src[2]] - src_centroid;
+        let p_dst = faer::col![p_in_dst[0], p_in_dst[1], p_in_dst[2]] - dst_centroid;
+        hh += p_src * p_dst.transpose();
+    }
+    hh
+}
+
+/// Compute the transformation between two point clouds with weighted correspondences.
+///
+/// The transformation minimizes the weighted sum of the squared distances between the
+/// transformed source points and the destination points.
+pub(crate) fn fit_transformation_weighted(
+    points_in_src: &[[f64; 3]],
+    points_in_dst: &[[f64; 3]],
+    weights: &[f64],
+    dst_r_src: &mut [[f64; 3]; 3],
+    dst_t_src: &mut [f64; 3],
+) -> FitDiagnostics {
+    assert_eq!(points_in_src.len(), points_in_dst.len());
+    assert_eq!(points_in_src.len(), weights.len());
+
+    // compute the weighted centroids
+    let weight_sum = weights.iter().sum::<f64>();
+    let mut src_centroid = [0.0; 3];
+    let mut dst_centroid = [0.0; 3];
+    for ((p_in_src, p_in_dst), w) in points_in_src
+        .iter()
+        .zip(points_in_dst.iter())
+        .zip(weights.iter())
+    {
+        for k in 0..3 {
+            src_centroid[k] += w * p_in_src[k] / weight_sum;
+            dst_centroid[k] += w * p_in_dst[k] / weight_sum;
+        }
+    }
+
+    // compute the weighted covariance matrix
+    let mut hh = faer::Mat::<f64>::zeros(3, 3);
+    for ((p_in_src, p_in_dst), w) in points_in_src
+        .iter()
+        .zip(points_in_dst.iter())
+        .zip(weights.iter())
+    {
+        let p_src = faer::col![
+            w * (p_in_src[0] - src_centroid[0]),
+            w * (p_in_src[1] - src_centroid[1]),
+            w * (p_in_src[2] - src_centroid[2])
+        ];
+        let p_dst = faer::col![
+            p_in_dst[0] - dst_centroid[0],
+            p_in_dst[1] - dst_centroid[1],
+            p_in_dst[2] - dst_centroid[2]
+        ];
         hh += p_src * p_dst.transpose();
     }
 
+    let src_centroid = faer::col![src_centroid[0], src_centroid[1], src_centroid[2]];
+    let dst_centroid = faer::col![dst_centroid[0], dst_centroid[1], dst_centroid[2]];
+    let (reflection_fixed, singular_values) =
+        solve_transformation(&hh, &src_centroid, &dst_centroid, dst_r_src, dst_t_src);
+    let residual_rmse = residual_rmse(
+        points_in_src,
+        points_in_dst,
+        Some(weights),
+        dst_r_src,
+        dst_t_src,
+    );
+    diagnose(reflection_fixed, singular_values, residual_rmse)
+}
+
+/// Build the diagnostics of a fit and report the suspicious solves.
+fn diagnose(
+    reflection_fixed: bool,
+    singular_values: [f64; 3],
+    residual_rmse: f64,
+) -> FitDiagnostics {
+    let diagnostics = FitDiagnostics::new(reflection_fixed, singular_values, residual_rmse);
+    if diagnostics.reflection_fixed {
+        log::warn!("WARNING: det(R) < 0.0, fixing it...");
+    }
+    diagnostics
+}
+
+/// Compute the (weighted) RMSE of the distances between the transformed source points and
+/// the destination points.
+fn residual_rmse(
+    points_in_src: &[[f64; 3]],
+    points_in_dst: &[[f64; 3]],
+    weights: Option<&[f64]>,
+    dst_r_src: &[[f64; 3]; 3],
+    dst_t_src: &[f64; 3],
+) -> f64 {
+    let mut sum_sq_distances = 0.0;
+    let mut sum_weights = 0.0;
+    for (i, (p_in_src, p_in_dst)) in points_in_src.iter().zip(points_in_dst.iter()).enumerate() {
+        let w = weights.map_or(1.0, |weights| weights[i]);
+        let mut p = [0.0; 3];
+        linalg::mat33_mul_vec3(dst_r_src, p_in_src, &mut p);
+        sum_sq_distances += w
+            * (0..3)
+                .map(|k| (p[k] + dst_t_src[k] - p_in_dst[k]).powi(2))
+                .sum::<f64>();
+        sum_weights += w;
+    }
+    if sum_weights > 0.0 {
+        (sum_sq_distances / sum_weights).sqrt()
+    } else {
+        0.0
+    }
+}
+
+/// Solve the rotation from the covariance matrix with the SVD and the translation from the centroids.
+///
+/// Returns whether a reflection was fixed and the singular values of the covariance matrix.
+fn solve_transformation(
+    hh: &faer::Mat<f64>,
+    src_centroid: &faer::Col<f64>,
+    dst_centroid: &faer::Col<f64>,
+    dst_r_src: &mut [[f64; 3]; 3],
+    dst_t_src: &mut [f64; 3],
+) -> (bool, [f64; 3]) {
     // solve the linear system H * x = 0 to find the rotation
     let svd = hh.svd();
     let (u_t, v) = (svd.u().transpose(), svd.v());
@@ -29,8 +228,8 @@ pub(crate) fn fit_transformation(
     let mut rr = v * u_t;
 
     // fix the determinant of R in case it is negative as it's a reflection matrix
-    if rr.determinant() < 0.0 {
-        log::warn!("WARNING: det(R) < 0.0, fixing it...");
+    let reflection_fixed = rr.determinant() < 0.0;
+    if reflection_fixed {
         let v_neg = {
             let mut v_neg = v.to_owned();
             v_neg.col_mut(2).copy_from(-v.col(2));
@@ -44,12 +243,22 @@ pub(crate) fn fit_transformation(
     let t = dst_centroid - &rr * src_centroid;
 
     // copy results back to output
-    for i in 0..3 {
-        for j in 0..3 {
-            dst_r_src[i][j] = rr.read(i, j);
+    for (i, (row, t_i)) in dst_r_src.iter_mut().zip(dst_t_src.iter_mut()).enumerate() {
+        for (j, r_ij) in row.iter_mut().enumerate() {
+            *r_ij = rr.read(i, j);
         }
-        dst_t_src[i] = t[i];
+        *t_i = t[i];
     }
+
+    let singular_values = svd.s_diagonal();
+    (
+        reflection_fixed,
+        [
+            singular_values.read(0),
+            singular_values.read(1),
+            singular_values.read(2),
+        ],
+    )
 }
 
 /// Compute the centroids of two sets of points.
@@ -120,6 +329,69 @@ pub(crate) fn find_correspondences(
     (points_in_src, points_in_dst, distances)
 }
 
+/// Find the nearest target point of each source point closer than a maximum distance.
+///
+/// The returned distances are squared.
+pub(crate) fn find_correspondences_within(
+    source: &[[f64; 3]],
+    target: &[[f64; 3]],
+    kdtree: &ImmutableKdTree<f64, u32, 3, 32>,
+    max_distance: f64,
+) -> (Vec<[f64; 3]>, Vec<[f64; 3]>, Vec<f64>) {
+    let max_sq_distance = max_distance * max_distance;
+
+    let mut points_in_src = Vec::new();
+    let mut points_in_dst = Vec::new();
+    let mut distances = Vec::new();
+    for p in source {
+        let nn = kdtree.nearest_one::<kiddo::SquaredEuclidean>(p);
+        if nn.distance <= max_sq_distance {
+            points_in_src.push(*p);
+            points_in_dst.push(target[nn.item as usize]);
+            distances.push(nn.distance);
+        }
+    }
+
+    (points_in_src, points_in_dst, distances)
+}
+
+/// Compute the rotation matrix of a rotation vector, i.e. the axis scaled by the angle.
+pub(crate) fn rotation_from_vector(omega: &[f64; 3]) -> [[f64; 3]; 3] {
+    let angle = linalg::dot_product3(omega, omega).sqrt();
+    if angle < 1e-12 {
+        return [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    }
+    axis_angle_to_rotation_matrix(omega, angle).unwrap_or([
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, 0.0, 1.0],
+    ])
+}
+
+/// Compute the rotation vector, i.e. the axis scaled by the angle, of a rotation matrix.
+pub(crate) fn rotation_vector(r: &[[f64; 3]; 3]) -> [f64; 3] {
+    let cos = (0.5 * (r[0][0] + r[1][1] + r[2][2] - 1.0)).clamp(-1.0, 1.0);
+    let angle = cos.acos();
+    // twice the sine of the angle times the axis
+    let skew = [r[2][1] - r[1][2], r[0][2] - r[2][0], r[1][0] - r[0][1]];
+    if angle < 1e-6 {
+        return skew.map(|v| 0.5 * v);
+    }
+    if std::f64::consts::PI - angle > 1e-6 {
+        let scale = angle / (2.0 * angle.sin());
+        return skew.map(|v| scale * v);
+    }
+
+    // near a half turn R = 2 a a^T - I, the axis is the largest column of R + I
+    let k = (0..3)
+        .max_by(|&a, &b| r[a][a].total_cmp(&r[b][b]))
+        .unwrap_or(0);
+    let mut axis = [r[0][k], r[1][k], r[2][k]];
+    axis[k] += 1.0;
+    let norm = axis.iter().map(|v| v * v).sum::<f64>().sqrt();
+    axis.map(|v| angle * v / norm)
+}
+
 pub(crate) fn update_transformation(
     rr: &mut [[f64; 3]; 3],
     tt: &mut [f64; 3],
@@ -140,38 +412,28 @@ mod tests {
     use super::*;
     use approx::assert_relative_eq;
     use kiddo::immutable::float::kdtree::ImmutableKdTree;
-    use kornia_3d::{linalg::transform_points3d, transforms::axis_angle_to_rotation_matrix};
-
-    fn create_random_points(num_points: usize) -> Vec<[f64; 3]> {
-        (0..num_points)
-            .map(|_| {
-                [
-                    rand::random::<f64>(),
-                    rand::random::<f64>(),
-                    rand::random::<f64>(),
-                ]
-            })
-            .collect()
+    use kornia_3d::{
+        linalg::transform_points3d_vec, pose::generate_ground_truth_correspondences,
+        transforms::axis_angle_to_rotation_matrix,
+    };
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const SEED: u64 = 0;
+
+    fn create_random_points(num_points: usize, rng: &mut StdRng) -> Vec<[f64; 3]> {
+        (0..num_points).map(|_| rng.random::<[f64; 3]>()).collect()
     }
 
-    fn create_random_rotation(factor: f64) -> Result<[[f64; 3]; 3], &'static str> {
-        let (axis, angle) = (
-            [
-                rand::random::<f64>(),
-                rand::random::<f64>(),
-                rand::random::<f64>(),
-            ],
-            rand::random::<f64>() * factor,
-        );
+    fn create_random_rotation(
+        factor: f64,
+        rng: &mut StdRng,
+    ) -> Result<[[f64; 3]; 3], &'static str> {
+        let (axis, angle) = (rng.random::<[f64; 3]>(), rng.random::<f64>() * factor);
         axis_angle_to_rotation_matrix(&axis, angle)
     }
 
-    fn create_random_translation(factor: f64) -> [f64; 3] {
-        [
-            rand::random::<f64>() * factor,
-            rand::random::<f64>() * factor,
-            rand::random::<f64>() * factor,
-        ]
+    fn create_random_translation(factor: f64, rng: &mut StdRng) -> [f64; 3] {
+        rng.random::<[f64; 3]>().map(|x| x * factor)
     }
 
     #[test]
@@ -190,7 +452,8 @@ mod tests {
     #[test]
     fn test_fit_transformation_identity() {
         let num_points = 30;
-        let points_src = create_random_points(num_points);
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let points_src = create_random_points(num_points, &mut rng);
         let points_dst = points_src.clone();
 
         let expected_rotation = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
@@ -214,19 +477,21 @@ mod tests {
     #[test]
     fn test_fit_transformation_rotation() -> Result<(), Box<dyn std::error::Error>> {
         let num_points = 30;
-        let points_src = create_random_points(num_points);
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let points_src = create_random_points(num_points, &mut rng);
 
         let expected_rotation =
             axis_angle_to_rotation_matrix(&[1.0, 0.0, 0.0], std::f64::consts::PI / 2.0)?;
         let expected_translation = [0.0, 0.0, 0.0];
 
-        let mut points_dst = vec![[0.0; 3]; points_src.len()];
-        transform_points3d(
+        let (_, points_dst) = generate_ground_truth_correspondences(
             &points_src,
             &expected_rotation,
             &expected_translation,
-            &mut points_dst,
-        )?;
+            0.0,
+            0.0,
+            SEED,
+        );
 
         let mut rotation = [[0.0; 3]; 3];
         let mut translation = [0.0; 3];
@@ -252,29 +517,31 @@ mod tests {
         let translation_factor = 0.1;
         let rotation_factor = 0.1;
 
-        let points_src = create_random_points(num_points);
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let points_src = create_random_points(num_points, &mut rng);
 
         for _ in 0..num_test {
             // create random rotation and translation
-            let expected_rotation = create_random_rotation(rotation_factor)?;
-            let expected_translation = create_random_translation(translation_factor);
+            let expected_rotation = create_random_rotation(rotation_factor, &mut rng)?;
+            let expected_translation = create_random_translation(translation_factor, &mut rng);
 
             // transform points
-            let mut points_dst = vec![[0.0; 3]; num_points];
-            transform_points3d(
+            let (_, points_dst) = generate_ground_truth_correspondences(
                 &points_src,
                 &expected_rotation,
                 &expected_translation,
-                &mut points_dst,
-            )?;
+                0.0,
+                0.0,
+                SEED,
+            );
 
             let mut rotation = [[0.0; 3]; 3];
             let mut translation = [0.0; 3];
 
             fit_transformation(&points_src, &points_dst, &mut rotation, &mut translation);
 
-            let mut points_src_fit = vec![[0.0; 3]; num_points];
-            transform_points3d(&points_src, &rotation, &translation, &mut points_src_fit)?;
+            // verify the fit by transforming the source with the estimated transformation
+            let points_src_fit = transform_points3d_vec(&points_src, &rotation, &translation);
 
             for (res, exp) in points_src_fit.iter().zip(points_dst.iter()) {
                 for (r, e) in res.iter().zip(exp.iter()) {
@@ -285,6 +552,120 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_fit_transformation_unit_determinant() -> Result<(), Box<dyn std::error::Error>> {
+        let num_points = 30;
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let points_src = create_random_points(num_points, &mut rng);
+
+        for angle in [0.0, 0.3, 3.0] {
+            let expected_rotation = axis_angle_to_rotation_matrix(&[0.4, -1.0, 0.2], angle)?;
+            let expected_translation = [0.1, -0.2, 0.3];
+            // noisy correspondences
+            let (_, points_dst) = generate_ground_truth_correspondences(
+                &points_src,
+                &expected_rotation,
+                &expected_translation,
+                0.003,
+                0.0,
+                SEED,
+            );
+
+            let mut rotation = [[0.0; 3]; 3];
+            let mut translation = [0.0; 3];
+            let diagnostics =
+                fit_transformation(&points_src, &points_dst, &mut rotation, &mut translation);
+            assert!(!diagnostics.reflection_fixed);
+            assert_relative_eq!(linalg::det_mat33(&rotation), 1.0, epsilon = 1e-12);
+            for (row, expected) in rotation.iter().zip(expected_rotation.iter()) {
+                for (r, e) in row.iter().zip(expected.iter()) {
+                    assert_relative_eq!(r, e, epsilon = 1e-2);
+                }
+            }
+        }
+
+        // mirrored points are fitted with a proper rotation
+        let mirrored = points_src
+            .iter()
+            .map(|p| [-p[0], p[1], p[2]])
+            .collect::<Vec<_>>();
+        let mut rotation = [[0.0; 3]; 3];
+        let mut translation = [0.0; 3];
+        let diagnostics =
+            fit_transformation(&points_src, &mirrored, &mut rotation, &mut translation);
+        assert!(diagnostics.reflection_fixed);
+        assert_relative_eq!(linalg::det_mat33(&rotation), 1.0, epsilon = 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fit_transformation_unit_scale() -> Result<(), Box<dyn std::error::Error>> {
+        let num_points = 30;
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let points_src = create_random_points(num_points, &mut rng);
+
+        // the target is rotated and scaled up by 2
+        let expected_rotation = axis_angle_to_rotation_matrix(&[0.4, -1.0, 0.2], 0.3)?;
+        let mut points_dst = vec![[0.0; 3]; num_points];
+        linalg::transform_points3d(&points_src, &expected_rotation, &[0.0; 3], &mut points_dst)?;
+        for p in points_dst.iter_mut() {
+            *p = p.map(|x| 2.0 * x);
+        }
+
+        let mut rotation = [[0.0; 3]; 3];
+        let mut translation = [0.0; 3];
+        fit_transformation_unit_scale(&points_src, &points_dst, &mut rotation, &mut translation);
+
+        // the rotation is recovered without the scale
+        for (row, expected) in rotation.iter().zip(expected_rotation.iter()) {
+            for (r, e) in row.iter().zip(expected.iter()) {
+                assert_relative_eq!(r, e, epsilon = 1e-9);
+            }
+        }
+        for row in rotation.iter() {
+            assert_relative_eq!(linalg::dot_product3(row, row), 1.0, epsilon = 1e-12);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fit_transformation_weighted() -> Result<(), Box<dyn std::error::Error>> {
+        let num_points = 30;
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let mut points_src = create_random_points(num_points, &mut rng);
+
+        let expected_rotation = axis_angle_to_rotation_matrix(&[0.0, 1.0, 1.0], 0.3)?;
+        let expected_translation = [0.1, -0.2, 0.3];
+        let mut points_dst =
+            transform_points3d_vec(&points_src, &expected_rotation, &expected_translation);
+
+        // outliers with a zero weight do not contribute to the fit
+        let mut weights = (0..num_points).map(|i| 1.0 + i as f64).collect::<Vec<_>>();
+        for _ in 0..5 {
+            points_src.push([5.0, -3.0, 2.0]);
+            points_dst.push([-4.0, 1.0, 0.5]);
+            weights.push(0.0);
+        }
+
+        let mut rotation = [[0.0; 3]; 3];
+        let mut translation = [0.0; 3];
+        fit_transformation_weighted(
+            &points_src,
+            &points_dst,
+            &weights,
+            &mut rotation,
+            &mut translation,
+        );
+
+        for i in 0..3 {
+            assert_relative_eq!(translation[i], expected_translation[i], epsilon = 1e-6);
+            for j in 0..3 {
+                assert_relative_eq!(rotation[i][j], expected_rotation[i][j], epsilon = 1e-6);
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_find_correspondences() -> Result<(), Box<dyn std::error::Error>> {
         let points_src = vec![
@@ -309,4 +690,29 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_rotation_from_vector() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = axis_angle_to_rotation_matrix(&[0.0, 0.6, 0.8], 0.5)?;
+        let rotation = rotation_from_vector(&[0.0, 0.3, 0.4]);
+        for (res, exp) in rotation.iter().zip(expected.iter()) {
+            for (r, e) in res.iter().zip(exp.iter()) {
+                assert_relative_eq!(r, e, epsilon = 1e-12);
+            }
+        }
+        assert_eq!(rotation_from_vector(&[0.0; 3])[1], [0.0, 1.0, 0.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rotation_vector() -> Result<(), Box<dyn std::error::Error>> {
+        let axis = [0.48, -0.6, 0.64];
+        for angle in [0.0, 1e-8, 0.3, 2.0, std::f64::consts::PI - 1e-9] {
+            let omega = rotation_vector(&axis_angle_to_rotation_matrix(&axis, angle)?);
+            for k in 0..3 {
+                assert_relative_eq!(omega[k], angle * axis[k], epsilon = 1e-6);
+            }
+        }
+        Ok(())
+    }
 }
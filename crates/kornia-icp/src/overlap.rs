@@ -0,0 +1,199 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+use kornia_3d::{
+    density::{estimate_density, DensityStats},
+    pointcloud::PointCloud,
+    transforms::RigidTransform3,
+};
+
+use crate::IcpError;
+
+/// Number of points sampled to estimate the density of each cloud.
+const DENSITY_SAMPLE_SIZE: usize = 1000;
+
+/// Seed of the density estimation, fixed for reproducibility.
+const DENSITY_SEED: u64 = 0;
+
+/// Ratio between the adaptive distance threshold and the 95th percentile of the nearest
+/// neighbor spacing.
+const ADAPTIVE_THRESHOLD_FACTOR: f64 = 1.5;
+
+/// Estimate the fraction of a source cloud overlapping a target cloud under a transformation.
+///
+/// A source point overlaps the target when its transformed position has a target neighbor
+/// within the distance threshold. The overlap sets the trimming fraction of trimmed ICP and
+/// validates a registration.
+///
+/// # Arguments
+///
+/// * `source` - Source point cloud.
+/// * `target_index` - KD-tree built from the target points.
+/// * `transform` - The transformation from the source to the target frame.
+/// * `distance_threshold` - The maximum distance to the nearest target point.
+///
+/// # Returns
+///
+/// The fraction of the source points overlapping the target, zero for an empty source.
+///
+/// Example:
+///
+/// ```
+/// use kiddo::immutable::float::kdtree::ImmutableKdTree;
+/// use kornia_icp::estimate_overlap;
+/// use kornia_3d::{pointcloud::PointCloud, transforms::RigidTransform3};
+///
+/// let source = PointCloud::new(vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]], None, None);
+/// let target: ImmutableKdTree<f64, u32, 3, 32> =
+///     ImmutableKdTree::new_from_slice(&[[2.0, 0.0, 0.0], [5.0, 0.0, 0.0]]);
+/// let transform = RigidTransform3::new(
+///     [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+///     [1.0, 0.0, 0.0],
+/// );
+/// assert_eq!(estimate_overlap(&source, &target, &transform, 0.1), 0.5);
+/// ```
+pub fn estimate_overlap(
+    source: &PointCloud,
+    target_index: &ImmutableKdTree<f64, u32, 3, 32>,
+    transform: &RigidTransform3,
+    distance_threshold: f64,
+) -> f64 {
+    if source.is_empty() || target_index.size() == 0 {
+        return 0.0;
+    }
+
+    let max_sq_distance = distance_threshold * distance_threshold;
+    let num_overlapping = source
+        .points()
+        .iter()
+        .filter(|p| {
+            let nn = target_index.nearest_one::<kiddo::SquaredEuclidean>(&transform.apply(p));
+            nn.distance <= max_sq_distance
+        })
+        .count();
+
+    num_overlapping as f64 / source.len() as f64
+}
+
+/// Compute the distance threshold of [`estimate_overlap_adaptive`] from the density of a
+/// cloud.
+///
+/// Two samplings of the same surface are within about the nearest neighbor spacing of each
+/// other, so that the threshold is 1.5 times the 95th percentile of the spacing.
+///
+/// # Arguments
+///
+/// * `stats` - The density statistics of the sparsest cloud.
+pub fn adaptive_overlap_threshold(stats: &DensityStats) -> f64 {
+    ADAPTIVE_THRESHOLD_FACTOR * stats.p95_nn_dist
+}
+
+/// Estimate the overlap of two clouds with a distance threshold derived from their density.
+///
+/// The threshold is [`adaptive_overlap_threshold`] of the sparsest of the two clouds, so
+/// that the overlap does not depend on the sampling density, unlike a fixed threshold.
+///
+/// # Arguments
+///
+/// * `source` - Source point cloud.
+/// * `target` - Target point cloud.
+/// * `transform` - The transformation from the source to the target frame.
+///
+/// # Returns
+///
+/// The fraction of the source points overlapping the target, see [`estimate_overlap`].
+pub fn estimate_overlap_adaptive(
+    source: &PointCloud,
+    target: &PointCloud,
+    transform: &RigidTransform3,
+) -> Result<f64, IcpError> {
+    if source.is_empty() || target.is_empty() {
+        return Err(IcpError::EmptyCloud);
+    }
+
+    let source_stats = estimate_density(source, DENSITY_SAMPLE_SIZE, DENSITY_SEED)?;
+    let target_stats = estimate_density(target, DENSITY_SAMPLE_SIZE, DENSITY_SEED)?;
+    let sparsest = if source_stats.p95_nn_dist > target_stats.p95_nn_dist {
+        &source_stats
+    } else {
+        &target_stats
+    };
+
+    let target_index: ImmutableKdTree<f64, u32, 3, 32> =
+        ImmutableKdTree::new_from_slice(target.points());
+    Ok(estimate_overlap(
+        source,
+        &target_index,
+        transform,
+        adaptive_overlap_threshold(sparsest),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_estimate_overlap_half_planes() {
+        // two grids of the unit square, the target shifted by half the square and slightly
+        // lifted, so that exactly the columns with x >= 0.5 overlap
+        let grid = |dx: f64, dz: f64| {
+            (0..2500)
+                .map(|i| [(i % 50) as f64 * 0.02 + dx, (i / 50) as f64 * 0.02, dz])
+                .collect::<Vec<_>>()
+        };
+        let source = PointCloud::new(grid(0.0, 0.0), None, None);
+        let target_index: ImmutableKdTree<f64, u32, 3, 32> =
+            ImmutableKdTree::new_from_slice(&grid(0.5, 0.01));
+
+        let identity = RigidTransform3::identity();
+        assert_relative_eq!(
+            estimate_overlap(&source, &target_index, &identity, 0.015),
+            0.5
+        );
+        // moving the source onto the target makes them fully overlap
+        let shift = RigidTransform3::new(identity.rotation, [0.5, 0.0, 0.01]);
+        assert_relative_eq!(estimate_overlap(&source, &target_index, &shift, 0.015), 1.0);
+        let away = RigidTransform3::new(identity.rotation, [5.0, 0.0, 0.0]);
+        assert_eq!(estimate_overlap(&source, &target_index, &away, 0.015), 0.0);
+    }
+
+    #[test]
+    fn test_estimate_overlap_adaptive_densities() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut plane = |num_points: usize, dx: f64| {
+            let points = (0..num_points)
+                .map(|_| {
+                    let (x, y): (f64, f64) =
+                        (rng.random_range(0.0..2.0), rng.random_range(0.0..2.0));
+                    [x + dx, y, 0.0]
+                })
+                .collect::<Vec<_>>();
+            PointCloud::new(points, None, None)
+        };
+
+        // independent random samplings of two half-overlapping planes at different densities
+        let identity = RigidTransform3::identity();
+        let mut overlaps = Vec::new();
+        for num_points in [2_500, 10_000, 40_000] {
+            let source = plane(num_points, 0.0);
+            let target = plane(num_points, 1.0);
+            overlaps.push(estimate_overlap_adaptive(&source, &target, &identity)?);
+        }
+        for overlap in overlaps.iter() {
+            assert!((overlap - 0.5).abs() < 0.05, "{overlaps:?}");
+        }
+        assert!(overlaps.windows(2).all(|w| (w[0] - w[1]).abs() < 0.03));
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate_overlap_adaptive_empty() {
+        let empty = PointCloud::new(vec![], None, None);
+        let cloud = PointCloud::new(vec![[0.0; 3]; 10], None, None);
+        assert!(matches!(
+            estimate_overlap_adaptive(&empty, &cloud, &RigidTransform3::identity()),
+            Err(IcpError::EmptyCloud)
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+use kornia_3d::density::DensityStats;
+
+/// Parameters of the point to point ICP variants.
+#[derive(Debug, Clone)]
+pub struct IcpParams {
+    /// Maximum number of iterations to perform.
+    pub max_iterations: usize,
+    /// Convergence tolerance as the difference in RMSE between two consecutive iterations.
+    pub tolerance: f64,
+    /// Convergence threshold on the Kullback-Leibler divergence between the residual
+    /// distributions of two consecutive iterations, in nats, see
+    /// [`kornia_3d::stats::entropy_convergence_criterion`]. A non-positive threshold disables
+    /// the criterion.
+    pub entropy_threshold: f64,
+    /// Maximum distance between a source point and its nearest target point to be considered
+    /// a correspondence.
+    pub max_correspondence_distance: f64,
+}
+
+/// Parameters suggested for the registration of a point cloud from its density.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SuggestedParams {
+    /// Maximum distance between two points to be considered a correspondence.
+    pub max_correspondence_distance: f64,
+    /// Voxel size used to downsample the point cloud.
+    pub voxel_size: f64,
+    /// Radius of the neighborhood used to estimate the normals.
+    pub normal_radius: f64,
+}
+
+/// Suggest the registration parameters from the density of a point cloud.
+///
+/// The heuristics are expressed in terms of the median nearest neighbor spacing `s`:
+///
+/// * `max_correspondence_distance = 3 * s`, large enough to tolerate a few points of misalignment.
+/// * `voxel_size = 2 * s`, to roughly halve the point count per axis without losing structure.
+/// * `normal_radius = 4 * s`, to include about a dozen neighbors on a locally planar surface.
+///
+/// All the suggestions scale linearly with the cloud.
+///
+/// If more than half of the sampled points have an exact duplicate, the median spacing is
+/// zero. The spacing is then estimated from the bounding box, as the largest extent divided
+/// by the square root of the number of points, i.e. the spacing of the points sampled on a
+/// square surface of that size. The suggestions are positive unless all the points coincide.
+///
+/// # Arguments
+///
+/// * `stats` - The density statistics of the point cloud.
+///
+/// # Returns
+///
+/// The suggested parameters.
+pub fn suggest_icp_params(stats: &DensityStats) -> SuggestedParams {
+    let spacing = if stats.median_nn_dist > 0.0 {
+        stats.median_nn_dist
+    } else {
+        let max_extent = stats.extent.iter().fold(0.0f64, |acc, e| acc.max(*e));
+        max_extent / (stats.num_points as f64).sqrt()
+    };
+    SuggestedParams {
+        max_correspondence_distance: 3.0 * spacing,
+        voxel_size: 2.0 * spacing,
+        normal_radius: 4.0 * spacing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use kornia_3d::{density::estimate_density, pointcloud::PointCloud};
+
+    #[test]
+    fn test_suggest_icp_params_scales_linearly() -> Result<(), Box<dyn std::error::Error>> {
+        let points = (0..400)
+            .map(|i| {
+                let (u, v) = ((i % 20) as f64, (i / 20) as f64);
+                [0.1 * u, 0.1 * v, 0.01 * (u * v).sin()]
+            })
+            .collect::<Vec<_>>();
+
+        let cloud = PointCloud::new(points.clone(), None, None);
+        let base = suggest_icp_params(&estimate_density(&cloud, 100, 3)?);
+
+        let scale = 7.5;
+        let scaled_points = points
+            .iter()
+            .map(|p| [scale * p[0], scale * p[1], scale * p[2]])
+            .collect::<Vec<_>>();
+        let scaled_cloud = PointCloud::new(scaled_points, None, None);
+        let scaled = suggest_icp_params(&estimate_density(&scaled_cloud, 100, 3)?);
+
+        assert_relative_eq!(
+            scaled.max_correspondence_distance,
+            scale * base.max_correspondence_distance,
+            epsilon = 1e-9
+        );
+        assert_relative_eq!(scaled.voxel_size, scale * base.voxel_size, epsilon = 1e-9);
+        assert_relative_eq!(
+            scaled.normal_radius,
+            scale * base.normal_radius,
+            epsilon = 1e-9
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_suggest_icp_params_duplicate_points() -> Result<(), Box<dyn std::error::Error>> {
+        // every point of a 20x20 grid with a spacing of 0.1 is duplicated
+        let points = (0..400)
+            .flat_map(|i| {
+                let (u, v) = ((i % 20) as f64, (i / 20) as f64);
+                [[0.1 * u, 0.1 * v, 0.0]; 2]
+            })
+            .collect::<Vec<_>>();
+        let cloud = PointCloud::new(points, None, None);
+
+        let stats = estimate_density(&cloud, 100, 3)?;
+        assert_eq!(stats.median_nn_dist, 0.0);
+
+        let params = suggest_icp_params(&stats);
+        let spacing = 1.9 / 800f64.sqrt();
+        assert_relative_eq!(params.max_correspondence_distance, 3.0 * spacing);
+        assert_relative_eq!(params.voxel_size, 2.0 * spacing);
+        assert_relative_eq!(params.normal_radius, 4.0 * spacing);
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+use kornia_3d::{
+    linalg::{det_mat33, eigen_symmetric33},
+    pointcloud::PointCloud,
+    transforms::RigidTransform3,
+};
+
+use crate::IcpError;
+
+/// Maximum number of source points used to score a candidate.
+const SCORE_SAMPLES: usize = 500;
+
+/// Two principal variances closer than this ratio make their axes ambiguous.
+const AXIS_AMBIGUITY_RATIO: f64 = 0.9;
+
+/// A candidate transformation of [`coarse_align_pca`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PcaCandidate {
+    /// The transformation from the source to the target frame.
+    pub transform: RigidTransform3,
+    /// The RMSE of the distances from the subsampled transformed source points to their
+    /// nearest target points.
+    pub rmse: f64,
+}
+
+/// The result of [`coarse_align_pca`].
+#[derive(Debug, Clone)]
+pub struct PcaAlignment {
+    /// The candidate transformations, sorted by increasing RMSE.
+    pub candidates: Vec<PcaCandidate>,
+    /// Whether two principal variances of a cloud are too close to tell their axes apart, in
+    /// which case the candidates also swap the axes.
+    pub ambiguous_axes: bool,
+}
+
+/// Coarse alignment of two point clouds by matching their centroids and principal axes.
+///
+/// The principal axes are the eigenvectors of the covariance of each cloud, sorted by
+/// increasing variance, and are only defined up to their signs. The proper rotations mapping
+/// the axes of the source onto the axes of the target give four candidates. When two
+/// principal variances of a cloud are within 10% of each other, the matching of their axes
+/// is ambiguous and the candidates are all the 24 proper rotations mapping the source axes
+/// onto the target axes in any order.
+///
+/// Each candidate is scored by the nearest neighbor RMSE of up to 500 evenly subsampled
+/// source points, so that the best one can be refined with ICP. No descriptor is needed, but
+/// the clouds must cover the same part of the shape.
+///
+/// # Arguments
+///
+/// * `source` - The source point cloud.
+/// * `target` - The target point cloud.
+///
+/// # Returns
+///
+/// The candidates sorted by increasing RMSE.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::coarse_align_pca;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// // a box with distinct extents, and its mirror about the origin
+/// let points = (0..1000)
+///     .map(|i| [(i % 10) as f64 * 0.4, (i / 10 % 10) as f64 * 0.2, (i / 100) as f64 * 0.1])
+///     .collect::<Vec<_>>();
+/// let turned = points.iter().map(|p| [-p[0], -p[1], p[2]]).collect();
+/// let alignment = coarse_align_pca(
+///     &PointCloud::new(points, None, None),
+///     &PointCloud::new(turned, None, None),
+/// )
+/// .unwrap();
+/// assert_eq!(alignment.candidates.len(), 4);
+/// assert!(!alignment.ambiguous_axes);
+/// assert!(alignment.candidates[0].rmse < 1e-9);
+/// ```
+pub fn coarse_align_pca(
+    source: &PointCloud,
+    target: &PointCloud,
+) -> Result<PcaAlignment, IcpError> {
+    if source.is_empty() || target.is_empty() {
+        return Err(IcpError::EmptyCloud);
+    }
+
+    let (source_centroid, source_variances, source_axes) = principal_axes(source.points());
+    let (target_centroid, target_variances, target_axes) = principal_axes(target.points());
+    let ambiguous_axes = is_ambiguous(&source_variances) || is_ambiguous(&target_variances);
+
+    let permutations: &[[usize; 3]] = if ambiguous_axes {
+        &[
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ]
+    } else {
+        &[[0, 1, 2]]
+    };
+    let kdtree: ImmutableKdTree<f64, u32, 3, 32> = ImmutableKdTree::new_from_slice(target.points());
+    let step = source.len().div_ceil(SCORE_SAMPLES);
+    let samples = source.points().iter().step_by(step).collect::<Vec<_>>();
+
+    let mut candidates = Vec::new();
+    for permutation in permutations {
+        for signs in [[1.0, 1.0], [1.0, -1.0], [-1.0, 1.0], [-1.0, -1.0]] {
+            // R = sum_i s_i * t_perm(i) * e_i^T, with the last sign making R proper
+            let mut rotation = [[0.0; 3]; 3];
+            for (i, sign) in [signs[0], signs[1], 1.0].iter().enumerate() {
+                let t = &target_axes[permutation[i]];
+                for a in 0..3 {
+                    for b in 0..3 {
+                        rotation[a][b] += sign * t[a] * source_axes[i][b];
+                    }
+                }
+            }
+            if det_mat33(&rotation) < 0.0 {
+                // flip the sign of the last axis
+                let t = &target_axes[permutation[2]];
+                for a in 0..3 {
+                    for b in 0..3 {
+                        rotation[a][b] -= 2.0 * t[a] * source_axes[2][b];
+                    }
+                }
+            }
+
+            let rotated_centroid = RigidTransform3::new(rotation, [0.0; 3]).apply(&source_centroid);
+            let transform = RigidTransform3::new(
+                rotation,
+                [0, 1, 2].map(|k| target_centroid[k] - rotated_centroid[k]),
+            );
+
+            let sum_sq_distances = samples
+                .iter()
+                .map(|p| {
+                    kdtree
+                        .nearest_one::<kiddo::SquaredEuclidean>(&transform.apply(p))
+                        .distance
+                })
+                .sum::<f64>();
+            candidates.push(PcaCandidate {
+                transform,
+                rmse: (sum_sq_distances / samples.len() as f64).sqrt(),
+            });
+        }
+    }
+    candidates.sort_by(|a, b| a.rmse.total_cmp(&b.rmse));
+
+    Ok(PcaAlignment {
+        candidates,
+        ambiguous_axes,
+    })
+}
+
+/// Compute the centroid, the principal variances in increasing order and the principal axes
+/// as rows of a point cloud.
+fn principal_axes(points: &[[f64; 3]]) -> ([f64; 3], [f64; 3], [[f64; 3]; 3]) {
+    let num = points.len() as f64;
+    let mut centroid = [0.0; 3];
+    for p in points {
+        for k in 0..3 {
+            centroid[k] += p[k] / num;
+        }
+    }
+    let mut covariance = [[0.0; 3]; 3];
+    for p in points {
+        let d = [0, 1, 2].map(|k| p[k] - centroid[k]);
+        for a in 0..3 {
+            for b in 0..3 {
+                covariance[a][b] += d[a] * d[b] / num;
+            }
+        }
+    }
+    let (variances, axes) = eigen_symmetric33(&covariance);
+    (centroid, variances, axes)
+}
+
+/// Whether two consecutive principal variances are too close to tell their axes apart.
+fn is_ambiguous(variances: &[f64; 3]) -> bool {
+    variances
+        .windows(2)
+        .any(|w| w[0] >= AXIS_AMBIGUITY_RATIO * w[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// An elongated part: a bar with a block at one end and a peg on its side.
+    fn part(num_points: usize) -> Vec<[f64; 3]> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut points = Vec::new();
+        while points.len() < num_points {
+            let p: [f64; 3] = [
+                rng.random_range(0.0..4.0),
+                rng.random_range(-0.8..0.8),
+                rng.random_range(-0.3..1.2),
+            ];
+            let bar = p[1].abs() < 0.4 && p[2] < 0.3;
+            let block = p[0] > 3.2 && p[2] < 1.2;
+            let peg = (1.0..1.4).contains(&p[0]) && p[1] > 0.4 && p[2] < 0.1;
+            if bar || block || peg {
+                points.push(p);
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn test_coarse_align_pca() -> Result<(), Box<dyn std::error::Error>> {
+        let points = part(5000);
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.3, 1.0, -0.5], 150f64.to_radians())?,
+            [2.0, -1.0, 0.5],
+        );
+        let source = PointCloud::new(points.clone(), None, None);
+        let target = PointCloud::new(points.iter().map(|p| truth.apply(p)).collect(), None, None);
+
+        let alignment = coarse_align_pca(&source, &target)?;
+        assert!(!alignment.ambiguous_axes);
+        assert_eq!(alignment.candidates.len(), 4);
+        assert!(alignment
+            .candidates
+            .windows(2)
+            .all(|w| w[0].rmse <= w[1].rmse));
+        for candidate in alignment.candidates.iter() {
+            assert!((det_mat33(&candidate.transform.rotation) - 1.0).abs() < 1e-9);
+        }
+
+        // the best scored candidate is the true transformation
+        let best = &alignment.candidates[0];
+        let error = truth.inverse().compose(&best.transform);
+        assert!(error.rotation_angle() < 3f64.to_radians());
+        assert!(error.translation.iter().all(|t| t.abs() < 0.1));
+        assert!(alignment.candidates[1].rmse > 2.0 * best.rmse);
+        Ok(())
+    }
+
+    #[test]
+    fn test_coarse_align_pca_ambiguous() -> Result<(), Box<dyn std::error::Error>> {
+        // a cube has no principal axis
+        let points = (0..1000)
+            .map(|i| [(i % 10) as f64, (i / 10 % 10) as f64, (i / 100) as f64])
+            .collect::<Vec<_>>();
+        let truth = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], std::f64::consts::FRAC_PI_2)?,
+            [1.0, 2.0, 3.0],
+        );
+        let source = PointCloud::new(points.clone(), None, None);
+        let target = PointCloud::new(points.iter().map(|p| truth.apply(p)).collect(), None, None);
+
+        let alignment = coarse_align_pca(&source, &target)?;
+        assert!(alignment.ambiguous_axes);
+        assert_eq!(alignment.candidates.len(), 24);
+        assert!(alignment.candidates[0].rmse < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_coarse_align_pca_empty() {
+        let empty = PointCloud::new(vec![], None, None);
+        let cloud = PointCloud::new(vec![[0.0; 3]], None, None);
+        assert!(matches!(
+            coarse_align_pca(&empty, &cloud),
+            Err(IcpError::EmptyCloud)
+        ));
+    }
+}
@@ -0,0 +1,451 @@
+use kornia_3d::{
+    fitting::PlaneModel,
+    linalg::{dot_product3, eigen_symmetric33, mat33_mul_vec3},
+    transforms::RigidTransform3,
+};
+
+use crate::{ops::fit_transformation, IcpError};
+
+/// Eigenvalues of the normal equations of the translation below this fraction of the
+/// largest one leave their direction unconstrained.
+const UNCONSTRAINED_TOLERANCE: f64 = 1e-6;
+
+/// A source plane and its corresponding target plane.
+type PlanePair = (PlaneModel, PlaneModel);
+
+/// The correspondences between the planes of [`register_planes`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaneCorrespondences<'a> {
+    /// The pairs of source and target plane indices.
+    ///
+    /// The normals of corresponding planes must be oriented consistently, e.g. towards the
+    /// sensor.
+    Given(&'a [(usize, usize)]),
+    /// Match each source plane to the most similar target plane, and conversely.
+    ///
+    /// The planes are compared in the same frame, which assumes a small motion between the
+    /// scans. The normals of the planes may have any orientation.
+    Auto {
+        /// Maximum angle in degrees between the normals of matched planes.
+        max_angle_deg: f64,
+        /// Maximum difference between the offsets of matched planes.
+        max_offset: f64,
+    },
+}
+
+/// Result of [`register_planes`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaneRegistration {
+    /// The estimated transformation from the source to the target frame.
+    pub transform: RigidTransform3,
+    /// The pairs of source and target plane indices used for the registration.
+    pub correspondences: Vec<(usize, usize)>,
+    /// The unit directions of the target frame along which the planes do not constrain the
+    /// translation, whose component along them is set to zero.
+    pub unconstrained_directions: Vec<[f64; 3]>,
+}
+
+/// Register two scans from corresponding planes.
+///
+/// Man-made environments such as Manhattan-world indoor scans are well described by a few
+/// large planes, whose correspondences are robust to a sparse overlap between the scans.
+/// A source plane `n_s · x + d_s = 0` maps to the target plane of normal `n_d = R * n_s`
+/// and offset `d_d = d_s - n_d · t`, so that:
+///
+/// * the rotation aligns the normals, solved with the Kabsch algorithm,
+/// * the translation solves the offset equations `n_d · t = d_s - d_d` in the least squares
+///   sense.
+///
+/// The rotation needs two planes with non-parallel normals and the translation needs three
+/// planes with independent normals: with fewer, e.g. a floor and a wall, the directions
+/// along which the translation is unconstrained are reported.
+///
+/// # Arguments
+///
+/// * `planes_src` - The planes of the source scan.
+/// * `planes_dst` - The planes of the target scan.
+/// * `correspondences` - The given correspondences or the parameters of the matching.
+///
+/// # Returns
+///
+/// The transformation from the source to the target frame with the correspondences used and
+/// the unconstrained directions of the translation.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::{register_planes, PlaneCorrespondences};
+/// use kornia_3d::fitting::PlaneModel;
+///
+/// // a floor and two walls, the wall facing the x axis being closer in the target frame
+/// let planes_src = [
+///     PlaneModel { normal: [0.0, 0.0, 1.0], d: 1.0 },
+///     PlaneModel { normal: [-1.0, 0.0, 0.0], d: 3.0 },
+///     PlaneModel { normal: [0.0, -1.0, 0.0], d: 2.0 },
+/// ];
+/// let planes_dst = [
+///     PlaneModel { normal: [0.0, 0.0, 1.0], d: 1.0 },
+///     PlaneModel { normal: [-1.0, 0.0, 0.0], d: 2.5 },
+///     PlaneModel { normal: [0.0, -1.0, 0.0], d: 2.0 },
+/// ];
+/// let correspondences = [(0, 0), (1, 1), (2, 2)];
+/// let result =
+///     register_planes(&planes_src, &planes_dst, PlaneCorrespondences::Given(&correspondences))
+///         .unwrap();
+/// assert!((result.transform.translation[0] + 0.5).abs() < 1e-9);
+/// assert!(result.unconstrained_directions.is_empty());
+/// ```
+pub fn register_planes(
+    planes_src: &[PlaneModel],
+    planes_dst: &[PlaneModel],
+    correspondences: PlaneCorrespondences,
+) -> Result<PlaneRegistration, IcpError> {
+    // the pairs of planes with consistently oriented normals
+    let (correspondences, pairs) = match correspondences {
+        PlaneCorrespondences::Given(correspondences) => {
+            let pairs = correspondences
+                .iter()
+                .map(|&(i, j)| (planes_src[i], planes_dst[j]))
+                .collect::<Vec<_>>();
+            (correspondences.to_vec(), pairs)
+        }
+        PlaneCorrespondences::Auto {
+            max_angle_deg,
+            max_offset,
+        } => match_planes(planes_src, planes_dst, max_angle_deg, max_offset),
+    };
+    if pairs.len() < 2 {
+        return Err(IcpError::NotEnoughCorrespondences(pairs.len()));
+    }
+
+    // the normals and their opposites have a zero centroid, which reduces the point
+    // alignment to the Kabsch algorithm on the normals
+    let (normals_src, normals_dst): (Vec<_>, Vec<_>) = pairs
+        .iter()
+        .flat_map(|(src, dst)| {
+            [
+                (src.normal, dst.normal),
+                (src.normal.map(|v| -v), dst.normal.map(|v| -v)),
+            ]
+        })
+        .unzip();
+    let mut rotation = [[0.0; 3]; 3];
+    let mut unused = [0.0; 3];
+    let diagnostics = fit_transformation(&normals_src, &normals_dst, &mut rotation, &mut unused);
+    if diagnostics.rank < 2 {
+        return Err(IcpError::DegenerateConstraints);
+    }
+
+    // normal equations of the offset equations n_d · t = d_s - d_d
+    let mut ata = [[0.0; 3]; 3];
+    let mut atb = [0.0; 3];
+    for (src, dst) in pairs.iter() {
+        let mut n = [0.0; 3];
+        mat33_mul_vec3(&rotation, &src.normal, &mut n);
+        let b = src.d - dst.d;
+        for r in 0..3 {
+            for c in 0..3 {
+                ata[r][c] += n[r] * n[c];
+            }
+            atb[r] += n[r] * b;
+        }
+    }
+
+    // minimum norm solution in the constrained directions
+    let (eigenvalues, eigenvectors) = eigen_symmetric33(&ata);
+    let max_eigenvalue = eigenvalues[2];
+    let mut translation = [0.0; 3];
+    let mut unconstrained_directions = Vec::new();
+    for (eigenvalue, direction) in eigenvalues.iter().zip(eigenvectors.iter()) {
+        if *eigenvalue <= UNCONSTRAINED_TOLERANCE * max_eigenvalue {
+            unconstrained_directions.push(*direction);
+            continue;
+        }
+        let component = dot_product3(direction, &atb) / eigenvalue;
+        for k in 0..3 {
+            translation[k] += component * direction[k];
+        }
+    }
+
+    Ok(PlaneRegistration {
+        transform: RigidTransform3::new(rotation, translation),
+        correspondences,
+        unconstrained_directions,
+    })
+}
+
+/// Match the planes of two scans by the similarity of their normals and offsets.
+///
+/// Returns the mutual best matches within the thresholds, with the target planes oriented
+/// like their source planes.
+fn match_planes(
+    planes_src: &[PlaneModel],
+    planes_dst: &[PlaneModel],
+    max_angle_deg: f64,
+    max_offset: f64,
+) -> (Vec<(usize, usize)>, Vec<PlanePair>) {
+    let max_angle = max_angle_deg.to_radians();
+
+    // the cost of matching two planes, with the target plane oriented like the source plane
+    let cost = |src: &PlaneModel, dst: &PlaneModel| {
+        let cos = dot_product3(&src.normal, &dst.normal);
+        let oriented = if cos < 0.0 {
+            PlaneModel {
+                normal: dst.normal.map(|v| -v),
+                d: -dst.d,
+            }
+        } else {
+            *dst
+        };
+        let angle = cos.abs().min(1.0).acos();
+        let offset = (src.d - oriented.d).abs();
+        if angle > max_angle || offset > max_offset {
+            return None;
+        }
+        let score = angle / max_angle.max(f64::EPSILON) + offset / max_offset.max(f64::EPSILON);
+        Some((score, oriented))
+    };
+    let best = |costs: &mut dyn Iterator<Item = (usize, f64)>| {
+        costs.min_by(|a, b| a.1.total_cmp(&b.1)).map(|(k, _)| k)
+    };
+
+    let best_dst = planes_src
+        .iter()
+        .map(|src| {
+            best(
+                &mut planes_dst
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(j, dst)| cost(src, dst).map(|(score, _)| (j, score))),
+            )
+        })
+        .collect::<Vec<_>>();
+    let best_src = planes_dst
+        .iter()
+        .map(|dst| {
+            best(
+                &mut planes_src
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, src)| cost(src, dst).map(|(score, _)| (i, score))),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    best_dst
+        .iter()
+        .enumerate()
+        .filter_map(|(i, j)| {
+            let j = (*j)?;
+            (best_src[j] == Some(i)).then_some(j)?;
+            let (_, oriented) = cost(&planes_src[i], &planes_dst[j])?;
+            Some(((i, j), (planes_src[i], oriented)))
+        })
+        .unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use kornia_3d::{fitting::fit_plane_ransac, transforms::axis_angle_to_rotation_matrix};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// A floor and two orthogonal walls of a room, with normals towards the sensor at the
+    /// origin.
+    fn room() -> Vec<PlaneModel> {
+        vec![
+            PlaneModel {
+                normal: [0.0, 0.0, 1.0],
+                d: 1.2,
+            },
+            PlaneModel {
+                normal: [-1.0, 0.0, 0.0],
+                d: 3.0,
+            },
+            PlaneModel {
+                normal: [0.0, -1.0, 0.0],
+                d: 2.0,
+            },
+        ]
+    }
+
+    /// Express the planes of the target frame in the source frame.
+    fn to_source(planes: &[PlaneModel], dst_from_src: &RigidTransform3) -> Vec<PlaneModel> {
+        let r = &dst_from_src.rotation;
+        planes
+            .iter()
+            .map(|plane| {
+                let n = &plane.normal;
+                PlaneModel {
+                    normal: [0, 1, 2].map(|k| r[0][k] * n[0] + r[1][k] * n[1] + r[2][k] * n[2]),
+                    d: plane.d + dot_product3(n, &dst_from_src.translation),
+                }
+            })
+            .collect()
+    }
+
+    fn check_pose(estimated: &RigidTransform3, expected: &RigidTransform3, epsilon: f64) {
+        for i in 0..3 {
+            assert_relative_eq!(
+                estimated.translation[i],
+                expected.translation[i],
+                epsilon = epsilon
+            );
+            for j in 0..3 {
+                assert_relative_eq!(
+                    estimated.rotation[i][j],
+                    expected.rotation[i][j],
+                    epsilon = epsilon
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_register_planes_three_orthogonal() -> Result<(), Box<dyn std::error::Error>> {
+        let gt = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.1, -0.2, 1.0], 0.6)?,
+            [0.4, -0.3, 0.1],
+        );
+        let planes_dst = room();
+        let planes_src = to_source(&planes_dst, &gt);
+
+        let correspondences = [(0, 0), (1, 1), (2, 2)];
+        let result = register_planes(
+            &planes_src,
+            &planes_dst,
+            PlaneCorrespondences::Given(&correspondences),
+        )?;
+        check_pose(&result.transform, &gt, 1e-9);
+        assert!(result.unconstrained_directions.is_empty());
+        assert_eq!(result.correspondences, correspondences);
+        Ok(())
+    }
+
+    #[test]
+    fn test_register_planes_two_planes() -> Result<(), Box<dyn std::error::Error>> {
+        let gt = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.3, 1.0], -0.4)?,
+            [0.4, -0.3, 0.1],
+        );
+        // the floor and the wall facing the x axis
+        let planes_dst = room()[..2].to_vec();
+        let planes_src = to_source(&planes_dst, &gt);
+
+        let result = register_planes(
+            &planes_src,
+            &planes_dst,
+            PlaneCorrespondences::Given(&[(0, 0), (1, 1)]),
+        )?;
+
+        // the rotation is constrained, the translation along the wall is not
+        check_pose(
+            &RigidTransform3::new(result.transform.rotation, [0.0; 3]),
+            &RigidTransform3::new(gt.rotation, [0.0; 3]),
+            1e-9,
+        );
+        assert_eq!(result.unconstrained_directions.len(), 1);
+        let direction = result.unconstrained_directions[0];
+        assert_relative_eq!(direction[1].abs(), 1.0, epsilon = 1e-9);
+        assert_relative_eq!(result.transform.translation[0], 0.4, epsilon = 1e-9);
+        assert_relative_eq!(result.transform.translation[1], 0.0, epsilon = 1e-9);
+        assert_relative_eq!(result.transform.translation[2], 0.1, epsilon = 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_register_planes_from_scans() -> Result<(), Box<dyn std::error::Error>> {
+        // sample the room in the target frame and the same room moved in the source frame
+        let gt = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.2, 0.1, 1.0], 0.1)?,
+            [0.15, -0.1, 0.05],
+        );
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut scan = |dst_from_scan: &RigidTransform3| {
+            let scan_from_dst = dst_from_scan.inverse();
+            (0..6000)
+                .map(|i| {
+                    let (u, v): (f64, f64) =
+                        (rng.random_range(-1.5..1.5), rng.random_range(-1.0..1.0));
+                    let p = match i % 3 {
+                        0 => [u, v, -1.2],
+                        1 => [3.0, u, v],
+                        _ => [u, 2.0, v],
+                    };
+                    scan_from_dst.apply(&p)
+                })
+                .collect::<Vec<_>>()
+        };
+        let points_dst = scan(&RigidTransform3::identity());
+        let points_src = scan(&gt);
+
+        // extract the planes one after the other, with the normals towards the sensor
+        let extract = |points: &[[f64; 3]], seed: u64| {
+            let mut remaining = points.to_vec();
+            let mut planes = Vec::new();
+            while remaining.len() > 100 {
+                let (mut plane, inliers) = fit_plane_ransac(&remaining, 1e-3, 200, seed).unwrap();
+                if plane.d < 0.0 {
+                    plane.normal = plane.normal.map(|v| -v);
+                    plane.d = -plane.d;
+                }
+                planes.push(plane);
+                let mut is_inlier = vec![false; remaining.len()];
+                inliers.iter().for_each(|&i| is_inlier[i] = true);
+                remaining = remaining
+                    .iter()
+                    .zip(is_inlier.iter())
+                    .filter(|(_, inlier)| !**inlier)
+                    .map(|(p, _)| *p)
+                    .collect();
+            }
+            planes
+        };
+        let planes_src = extract(&points_src, 1);
+        let mut planes_dst = extract(&points_dst, 2);
+        assert_eq!(planes_src.len(), 3);
+        // an extra plane without a counterpart and a flipped normal
+        planes_dst.push(PlaneModel {
+            normal: [0.0, 0.0, -1.0],
+            d: 1.5,
+        });
+        planes_dst[1].normal = planes_dst[1].normal.map(|v| -v);
+        planes_dst[1].d = -planes_dst[1].d;
+
+        let result = register_planes(
+            &planes_src,
+            &planes_dst,
+            PlaneCorrespondences::Auto {
+                max_angle_deg: 15.0,
+                max_offset: 0.5,
+            },
+        )?;
+        assert_eq!(result.correspondences.len(), 3);
+        assert!(result.unconstrained_directions.is_empty());
+        check_pose(&result.transform, &gt, 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_register_planes_degenerate() {
+        let planes = room();
+        assert!(matches!(
+            register_planes(&planes, &planes, PlaneCorrespondences::Given(&[(0, 0)])),
+            Err(IcpError::NotEnoughCorrespondences(1))
+        ));
+
+        // parallel planes do not constrain the rotation about their normal
+        let mut parallel = planes[0];
+        parallel.d = -1.0;
+        let planes = [planes[0], parallel];
+        assert!(matches!(
+            register_planes(
+                &planes,
+                &planes,
+                PlaneCorrespondences::Given(&[(0, 0), (1, 1)])
+            ),
+            Err(IcpError::DegenerateConstraints)
+        ));
+    }
+}
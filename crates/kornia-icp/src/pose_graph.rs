@@ -0,0 +1,708 @@
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+use kornia_3d::{
+    linalg::{cross_vec3, solve_linear},
+    pointcloud::PointCloud,
+    transforms::RigidTransform3,
+};
+
+use crate::{
+    ops::{rotation_from_vector, rotation_vector},
+    RobustKernel,
+};
+
+/// Step of the finite differences of the Jacobians of [`PoseGraph::optimize`].
+const FINITE_DIFFERENCE_STEP: f64 = 1e-7;
+
+/// The optimization stops when no parameter of a pose changes more than this in a sweep.
+const CONVERGENCE_STEP: f64 = 1e-10;
+
+/// A relative pose measurement between two nodes of a [`PoseGraph`], e.g. a pairwise
+/// registration of two scans.
+#[derive(Debug, Clone)]
+pub struct PoseGraphEdge {
+    /// The index of the first node.
+    pub from: usize,
+    /// The index of the second node.
+    pub to: usize,
+    /// The measured pose of the second node in the frame of the first node.
+    pub measurement: RigidTransform3,
+    /// The 6x6 information matrix of the measurement, the inverse of its covariance, with the
+    /// rotation parameters first.
+    pub information: [[f64; 6]; 6],
+}
+
+/// The residual of an edge of a [`PoseGraph`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeResidual {
+    /// The angle in radians of the rotation between the measured and the estimated relative
+    /// poses.
+    pub rotation_error: f64,
+    /// The norm of the translation between the measured and the estimated relative poses.
+    pub translation_error: f64,
+    /// The squared Mahalanobis norm of the SE(3) error under the information matrix of the
+    /// edge. It follows a chi-square distribution with 6 degrees of freedom for an inlier
+    /// measurement with a correct information matrix.
+    pub chi2: f64,
+}
+
+/// A robust kernel down-weighting the edges with large residuals in [`PoseGraph::optimize`].
+///
+/// The kernel weighs each edge by its Mahalanobis norm `sqrt(chi2)`, so that a wrong
+/// pairwise registration, e.g. a false loop closure, does not drag the whole graph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoseGraphKernel {
+    /// The robust kernel.
+    pub kernel: RobustKernel,
+    /// The scale of the kernel on the Mahalanobis norm of the edges, e.g. 3.55 or 4.74 for
+    /// the 95% or 99.9% quantiles of the norm of an inlier edge with 6 degrees of freedom.
+    pub scale: f64,
+}
+
+/// A graph of the absolute poses of scans connected by relative pose measurements.
+///
+/// The poses are the transformations from the frame of each scan to the world frame.
+#[derive(Debug, Clone)]
+pub struct PoseGraph {
+    /// The pose of each node.
+    pub poses: Vec<RigidTransform3>,
+    /// The relative pose measurements between the nodes.
+    pub edges: Vec<PoseGraphEdge>,
+}
+
+impl PoseGraph {
+    /// Create a pose graph without edges.
+    pub fn new(poses: Vec<RigidTransform3>) -> Self {
+        Self {
+            poses,
+            edges: Vec::new(),
+        }
+    }
+
+    /// Add a relative pose measurement between two nodes.
+    ///
+    /// PRECONDITION: `from` and `to` are indices of nodes of the graph.
+    pub fn add_edge(
+        &mut self,
+        from: usize,
+        to: usize,
+        measurement: RigidTransform3,
+        information: [[f64; 6]; 6],
+    ) {
+        self.edges.push(PoseGraphEdge {
+            from,
+            to,
+            measurement,
+            information,
+        });
+    }
+
+    /// Add the result of a pairwise registration as a relative pose measurement.
+    ///
+    /// The information matrix of the measurement is computed from the inlier
+    /// correspondences of the registration with [`compute_information_matrix`], so that the
+    /// well constrained registrations weigh more than the degenerate ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The node of the target scan.
+    /// * `to` - The node of the source scan.
+    /// * `source` - The source scan.
+    /// * `target_index` - KD-tree built from the points of the target scan.
+    /// * `transform` - The registered transformation from the source to the target frame.
+    /// * `max_distance` - The maximum distance of an inlier correspondence.
+    ///
+    /// PRECONDITION: `from` and `to` are indices of nodes of the graph.
+    pub fn add_registration_edge(
+        &mut self,
+        from: usize,
+        to: usize,
+        source: &PointCloud,
+        target_index: &ImmutableKdTree<f64, u32, 3, 32>,
+        transform: RigidTransform3,
+        max_distance: f64,
+    ) {
+        let information =
+            compute_information_matrix(source, target_index, &transform, max_distance);
+        self.add_edge(from, to, transform, information);
+    }
+
+    /// Compute the residual of each edge for the current poses.
+    ///
+    /// The error of an edge is the SE(3) transformation `E = Z^-1 * T_from^-1 * T_to` between
+    /// the measurement `Z` and the relative pose of the nodes, parametrized as
+    /// `ξ = (ω, t)` with `ω` the rotation vector of `E` and `t` its translation.
+    ///
+    /// # Returns
+    ///
+    /// The residual of each edge, in the order of the edges.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_icp::PoseGraph;
+    /// use kornia_3d::transforms::RigidTransform3;
+    ///
+    /// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    /// let mut information = [[0.0; 6]; 6];
+    /// (0..6).for_each(|i| information[i][i] = 100.0);
+    ///
+    /// let poses = vec![RigidTransform3::identity(), RigidTransform3::new(identity, [1.0, 0.0, 0.0])];
+    /// let mut graph = PoseGraph::new(poses);
+    /// graph.add_edge(0, 1, RigidTransform3::new(identity, [0.9, 0.0, 0.0]), information);
+    /// let residuals = graph.edge_residuals();
+    /// assert!((residuals[0].translation_error - 0.1).abs() < 1e-12);
+    /// assert!((residuals[0].chi2 - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn edge_residuals(&self) -> Vec<EdgeResidual> {
+        self.edges
+            .iter()
+            .map(|edge| {
+                let error = edge_error(&self.poses[edge.from], &self.poses[edge.to], edge);
+                let xi = error_vector(&error);
+                EdgeResidual {
+                    rotation_error: error.rotation_angle(),
+                    translation_error: error.translation.iter().map(|v| v * v).sum::<f64>().sqrt(),
+                    chi2: mahalanobis(&xi, &edge.information),
+                }
+            })
+            .collect()
+    }
+
+    /// Optimize the poses of the nodes to agree with the measurements.
+    ///
+    /// The sum of the chi-square values of the edges is minimized by block Gauss-Seidel
+    /// sweeps: each free node is updated in turn by a Gauss-Newton step on its 6 degrees of
+    /// freedom, the other nodes being held, with the Jacobians of its edges computed by
+    /// finite differences of a perturbation `(ω, t)` on the left of its pose. This is meant
+    /// for the small graphs of a sliding window, the fixed nodes anchoring the gauge.
+    ///
+    /// With a robust kernel, the normal equations of each edge are weighted by the kernel of
+    /// its Mahalanobis norm at the current poses, as in an iteratively reweighted least
+    /// squares, so that the outlier edges are down-weighted instead of spreading their error
+    /// over the graph. They can then be found with [`PoseGraph::flag_outlier_edges`].
+    ///
+    /// # Arguments
+    ///
+    /// * `fixed` - The indices of the nodes whose poses are held, at least one.
+    /// * `max_iterations` - Maximum number of sweeps over the free nodes.
+    /// * `kernel` - The robust kernel of the edges, or `None` for plain least squares.
+    ///
+    /// # Returns
+    ///
+    /// The sum of the chi-square values of the edges at the optimized poses, without the
+    /// kernel.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_icp::PoseGraph;
+    /// use kornia_3d::transforms::RigidTransform3;
+    ///
+    /// let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    /// let mut information = [[0.0; 6]; 6];
+    /// (0..6).for_each(|i| information[i][i] = 100.0);
+    ///
+    /// let mut graph = PoseGraph::new(vec![RigidTransform3::identity(); 2]);
+    /// graph.add_edge(0, 1, RigidTransform3::new(identity, [1.0, 0.0, 0.0]), information);
+    /// let chi2 = graph.optimize(&[0], 10, None);
+    /// assert!(chi2 < 1e-12);
+    /// assert!((graph.poses[1].translation[0] - 1.0).abs() < 1e-6);
+    /// ```
+    pub fn optimize(
+        &mut self,
+        fixed: &[usize],
+        max_iterations: usize,
+        kernel: Option<PoseGraphKernel>,
+    ) -> f64 {
+        for _ in 0..max_iterations {
+            let mut max_step = 0.0f64;
+            for node in 0..self.poses.len() {
+                if fixed.contains(&node) {
+                    continue;
+                }
+
+                // accumulate the normal equations of the edges of the node
+                let mut jtj = [[0.0; 6]; 6];
+                let mut jtr = [0.0; 6];
+                for edge in self.edges.iter() {
+                    if edge.from != node && edge.to != node {
+                        continue;
+                    }
+                    let error_at = |pose: &RigidTransform3| {
+                        let (from, to) = if edge.from == node {
+                            (pose, &self.poses[edge.to])
+                        } else {
+                            (&self.poses[edge.from], pose)
+                        };
+                        error_vector(&edge_error(from, to, edge))
+                    };
+                    let xi = error_at(&self.poses[node]);
+                    let mut jacobian = [[0.0; 6]; 6];
+                    for (c, column) in (0..6).zip(unit_perturbations(self.poses[node])) {
+                        let perturbed = error_at(&column);
+                        for (row, (p, x)) in jacobian.iter_mut().zip(perturbed.iter().zip(xi)) {
+                            row[c] = (p - x) / FINITE_DIFFERENCE_STEP;
+                        }
+                    }
+                    let weight = kernel.map_or(1.0, |k| {
+                        k.kernel
+                            .weight(mahalanobis(&xi, &edge.information).sqrt(), k.scale)
+                    });
+
+                    // w * J^T * Ω
+                    let jt_info: [[f64; 6]; 6] = std::array::from_fn(|a| {
+                        std::array::from_fn(|b| {
+                            weight
+                                * (0..6)
+                                    .map(|k| jacobian[k][a] * edge.information[k][b])
+                                    .sum::<f64>()
+                        })
+                    });
+                    for ((jtj_row, jtr_value), jt_info_row) in
+                        jtj.iter_mut().zip(jtr.iter_mut()).zip(jt_info.iter())
+                    {
+                        for (b, value) in jtj_row.iter_mut().enumerate() {
+                            *value += (0..6).map(|k| jt_info_row[k] * jacobian[k][b]).sum::<f64>();
+                        }
+                        *jtr_value -= (0..6).map(|k| jt_info_row[k] * xi[k]).sum::<f64>();
+                    }
+                }
+                let Some(delta) = solve_linear(&jtj, &jtr) else {
+                    continue;
+                };
+
+                self.poses[node] = perturb(&self.poses[node], &delta);
+                max_step = delta.iter().fold(max_step, |m, d| m.max(d.abs()));
+            }
+            if max_step < CONVERGENCE_STEP {
+                break;
+            }
+        }
+
+        self.edge_residuals().iter().map(|r| r.chi2).sum()
+    }
+
+    /// Find the edges inconsistent with the current poses.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The chi-square value above which an edge is an outlier, e.g. 12.59 or
+    ///   22.46 for the 95% or 99.9% quantiles with 6 degrees of freedom.
+    ///
+    /// # Returns
+    ///
+    /// The sorted indices of the edges with a chi-square value above the threshold.
+    pub fn flag_outlier_edges(&self, threshold: f64) -> Vec<usize> {
+        self.edge_residuals()
+            .iter()
+            .enumerate()
+            .filter(|(_, residual)| residual.chi2 > threshold)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// Compute the information matrix of a pairwise registration.
+///
+/// The information is the sum of the outer products `J^T J` of the Jacobians of the
+/// residuals of the inlier correspondences with respect to a perturbation `(ω, t)` of the
+/// transformation, as in Open3D. For a transformed source point `p` without normal, the
+/// point-to-point Jacobian is `[-[p]x, I]`. When the source has normals, the point-to-plane
+/// Jacobian `[p x n, n]` with the transformed normal `n` is used instead, which reveals the
+/// directions along which the surfaces can slide, e.g. along a corridor.
+///
+/// The matrix is proportional to the number of inlier correspondences and has the rotation
+/// parameters first, as the edges of a [`PoseGraph`].
+///
+/// # Arguments
+///
+/// * `source` - Source point cloud.
+/// * `target_index` - KD-tree built from the target points.
+/// * `transform` - The transformation from the source to the target frame.
+/// * `max_distance` - The maximum distance between a transformed source point and its
+///   nearest target point to be an inlier correspondence.
+///
+/// # Returns
+///
+/// The 6x6 information matrix of the transformation.
+///
+/// Example:
+///
+/// ```
+/// use kiddo::immutable::float::kdtree::ImmutableKdTree;
+/// use kornia_icp::compute_information_matrix;
+/// use kornia_3d::{pointcloud::PointCloud, transforms::RigidTransform3};
+///
+/// let points = vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let target: ImmutableKdTree<f64, u32, 3, 32> = ImmutableKdTree::new_from_slice(&points);
+/// let source = PointCloud::new(points, None, None);
+/// let information =
+///     compute_information_matrix(&source, &target, &RigidTransform3::identity(), 0.1);
+/// // each correspondence constrains the three translations
+/// assert_eq!(information[3][3], 3.0);
+/// assert_eq!(information[0][0], 2.0);
+/// ```
+pub fn compute_information_matrix(
+    source: &PointCloud,
+    target_index: &ImmutableKdTree<f64, u32, 3, 32>,
+    transform: &RigidTransform3,
+    max_distance: f64,
+) -> [[f64; 6]; 6] {
+    let mut information = [[0.0; 6]; 6];
+    let mut accumulate = |jacobian: &[f64; 6]| {
+        for (row, ja) in information.iter_mut().zip(jacobian.iter()) {
+            for (value, jb) in row.iter_mut().zip(jacobian.iter()) {
+                *value += ja * jb;
+            }
+        }
+    };
+
+    let max_sq_distance = max_distance * max_distance;
+    let rotation = RigidTransform3::new(transform.rotation, [0.0; 3]);
+    for (i, point) in source.points().iter().enumerate() {
+        let p = transform.apply(point);
+        if target_index
+            .nearest_one::<kiddo::SquaredEuclidean>(&p)
+            .distance
+            > max_sq_distance
+        {
+            continue;
+        }
+        match source.normals() {
+            Some(normals) => {
+                let n = rotation.apply(&normals[i]);
+                let mut torque = [0.0; 3];
+                cross_vec3(&p, &n, &mut torque);
+                accumulate(&[torque[0], torque[1], torque[2], n[0], n[1], n[2]]);
+            }
+            None => {
+                accumulate(&[0.0, p[2], -p[1], 1.0, 0.0, 0.0]);
+                accumulate(&[-p[2], 0.0, p[0], 0.0, 1.0, 0.0]);
+                accumulate(&[p[1], -p[0], 0.0, 0.0, 0.0, 1.0]);
+            }
+        }
+    }
+
+    information
+}
+
+/// The SE(3) error `Z^-1 * T_from^-1 * T_to` of an edge for the poses of its nodes.
+fn edge_error(
+    from: &RigidTransform3,
+    to: &RigidTransform3,
+    edge: &PoseGraphEdge,
+) -> RigidTransform3 {
+    let relative = from.inverse().compose(to);
+    edge.measurement.inverse().compose(&relative)
+}
+
+/// Parametrize an SE(3) error as `ξ = (ω, t)`.
+fn error_vector(error: &RigidTransform3) -> [f64; 6] {
+    let omega = rotation_vector(&error.rotation);
+    [
+        omega[0],
+        omega[1],
+        omega[2],
+        error.translation[0],
+        error.translation[1],
+        error.translation[2],
+    ]
+}
+
+/// Compute the squared Mahalanobis norm of an error vector.
+fn mahalanobis(xi: &[f64; 6], information: &[[f64; 6]; 6]) -> f64 {
+    let mut chi2 = 0.0;
+    for a in 0..6 {
+        for b in 0..6 {
+            chi2 += xi[a] * information[a][b] * xi[b];
+        }
+    }
+    chi2
+}
+
+/// Apply a perturbation `(ω, t)` on the left of a pose.
+fn perturb(pose: &RigidTransform3, delta: &[f64; 6]) -> RigidTransform3 {
+    let step = RigidTransform3::new(
+        rotation_from_vector(&[delta[0], delta[1], delta[2]]),
+        [delta[3], delta[4], delta[5]],
+    );
+    step.compose(pose)
+}
+
+/// The poses perturbed by the finite difference step along each of the 6 parameters.
+fn unit_perturbations(pose: RigidTransform3) -> [RigidTransform3; 6] {
+    [0, 1, 2, 3, 4, 5].map(|k| {
+        let mut delta = [0.0; 6];
+        delta[k] = FINITE_DIFFERENCE_STEP;
+        perturb(&pose, &delta)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use kornia_3d::{linalg::eigen_symmetric, transforms::axis_angle_to_rotation_matrix};
+
+    /// Sample the faces of an axis-aligned box from the origin to `size`, with their inward
+    /// normals.
+    fn box_faces(size: [f64; 3], faces: &[(usize, bool)]) -> PointCloud {
+        let mut points = Vec::new();
+        let mut normals = Vec::new();
+        for &(axis, far) in faces {
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+            for i in 0..40 {
+                for j in 0..40 {
+                    let mut p = [0.0; 3];
+                    p[axis] = if far { size[axis] } else { 0.0 };
+                    p[u] = size[u] * (i as f64 + 0.5) / 40.0;
+                    p[v] = size[v] * (j as f64 + 0.5) / 40.0;
+                    let mut n = [0.0; 3];
+                    n[axis] = if far { -1.0 } else { 1.0 };
+                    points.push(p);
+                    normals.push(n);
+                }
+            }
+        }
+        PointCloud::new(points, None, Some(normals))
+    }
+
+    fn information(sigma_rotation: f64, sigma_translation: f64) -> [[f64; 6]; 6] {
+        let mut information = [[0.0; 6]; 6];
+        for i in 0..3 {
+            information[i][i] = 1.0 / sigma_rotation.powi(2);
+            information[3 + i][3 + i] = 1.0 / sigma_translation.powi(2);
+        }
+        information
+    }
+
+    /// The largest rotation angle and translation norm between two sets of poses.
+    fn max_pose_error(poses: &[RigidTransform3], truth: &[RigidTransform3]) -> (f64, f64) {
+        poses.iter().zip(truth.iter()).fold(
+            (0.0f64, 0.0f64),
+            |(rotation, translation), (pose, expected)| {
+                let error = pose.compose(&expected.inverse());
+                let norm = error.translation.iter().map(|t| t * t).sum::<f64>().sqrt();
+                (rotation.max(error.rotation_angle()), translation.max(norm))
+            },
+        )
+    }
+
+    #[test]
+    fn test_edge_residuals_loop() -> Result<(), Box<dyn std::error::Error>> {
+        // the scans of a loop around a building
+        let num_poses = 8;
+        let truth = (0..num_poses)
+            .map(|i| {
+                let yaw = 2.0 * std::f64::consts::PI * i as f64 / num_poses as f64;
+                let rotation = axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], yaw)?;
+                Ok(RigidTransform3::new(
+                    rotation,
+                    [10.0 * yaw.cos(), 10.0 * yaw.sin(), 0.1 * i as f64],
+                ))
+            })
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+
+        // the initial poses, drifted away from the ground truth but the first one
+        let drift = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.3, 0.2, 1.0], 0.02)?,
+            [0.1, -0.05, 0.02],
+        );
+        let mut poses = vec![truth[0]];
+        poses.extend(truth.iter().skip(1).map(|pose| drift.compose(pose)));
+
+        // the odometry and loop closure measurements with small errors
+        let mut graph = PoseGraph::new(poses);
+        let corrupted = 5;
+        for e in 0..num_poses + 4 {
+            let (from, to) = if e < num_poses {
+                (e, (e + 1) % num_poses)
+            } else {
+                (e - num_poses, e - num_poses + 4)
+            };
+            let mut measurement = truth[from].inverse().compose(&truth[to]);
+            let noise = 0.005 * ((e as f64) * 1.7).sin();
+            let rotation = axis_angle_to_rotation_matrix(&[1.0, 0.5, -0.3], 0.3 * noise)?;
+            measurement =
+                measurement.compose(&RigidTransform3::new(rotation, [noise, -noise, 0.0]));
+            if e == corrupted {
+                // a wrong pairwise registration, off by a meter
+                measurement = measurement.compose(&RigidTransform3::new(
+                    axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.1)?,
+                    [1.0, 0.0, 0.0],
+                ));
+            }
+            graph.add_edge(from, to, measurement, information(0.01, 0.02));
+        }
+
+        // the least squares spread the error of the corrupted edge over the loop
+        let mut least_squares = graph.clone();
+        least_squares.optimize(&[0], 100, None);
+        let (_, least_squares_error) = max_pose_error(&least_squares.poses, &truth);
+        assert!(least_squares_error > 0.5);
+
+        // the robust kernel keeps the poses near the ground truth
+        let kernel = PoseGraphKernel {
+            kernel: RobustKernel::Cauchy,
+            scale: 3.55,
+        };
+        graph.optimize(&[0], 100, Some(kernel));
+        let (rotation_error, translation_error) = max_pose_error(&graph.poses, &truth);
+        assert!(rotation_error < 0.01);
+        assert!(translation_error < 0.1);
+
+        let residuals = graph.edge_residuals();
+        assert_eq!(residuals.len(), num_poses + 4);
+        for (e, residual) in residuals.iter().enumerate() {
+            if e == corrupted {
+                assert!(residual.translation_error > 0.9);
+                assert_relative_eq!(residual.rotation_error, 0.1, epsilon = 0.01);
+            } else {
+                assert!(residual.translation_error < 0.02);
+                assert!(residual.rotation_error < 0.005);
+            }
+        }
+        assert_eq!(graph.flag_outlier_edges(22.46), vec![corrupted]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_edge_residuals_chi2() -> Result<(), Box<dyn std::error::Error>> {
+        // a rotation error of 0.02 rad about z with a rotation sigma of 0.01 rad
+        let rotation = axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.02)?;
+        let mut graph = PoseGraph::new(vec![
+            RigidTransform3::identity(),
+            RigidTransform3::new(rotation, [0.0; 3]),
+        ]);
+        graph.add_edge(0, 1, RigidTransform3::identity(), information(0.01, 1.0));
+        let residuals = graph.edge_residuals();
+        assert_relative_eq!(residuals[0].rotation_error, 0.02, epsilon = 1e-12);
+        assert_relative_eq!(residuals[0].translation_error, 0.0, epsilon = 1e-12);
+        assert_relative_eq!(residuals[0].chi2, 4.0, epsilon = 1e-9);
+        assert!(graph.flag_outlier_edges(4.5).is_empty());
+        assert_eq!(graph.flag_outlier_edges(3.5), vec![0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimize_loop() -> Result<(), Box<dyn std::error::Error>> {
+        // a square loop of exact measurements, and poses drifting along the loop
+        let num_poses = 4;
+        let truth = (0..num_poses)
+            .map(|i| {
+                let yaw = std::f64::consts::FRAC_PI_2 * i as f64;
+                let rotation = axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], yaw)?;
+                Ok(RigidTransform3::new(rotation, [yaw.cos(), yaw.sin(), 0.0]))
+            })
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+        let drift = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.3, 0.2, 1.0], 0.05)?,
+            [0.1, -0.05, 0.02],
+        );
+        let mut poses = vec![truth[0]];
+        poses.extend(truth.iter().skip(1).map(|pose| drift.compose(pose)));
+
+        let mut graph = PoseGraph::new(poses);
+        for i in 0..num_poses {
+            let j = (i + 1) % num_poses;
+            let measurement = truth[i].inverse().compose(&truth[j]);
+            graph.add_edge(i, j, measurement, information(0.01, 0.02));
+        }
+        assert!(graph.edge_residuals().iter().any(|r| r.chi2 > 1.0));
+
+        let chi2 = graph.optimize(&[0], 100, None);
+        assert!(chi2 < 1e-9);
+        for (pose, expected) in graph.poses.iter().zip(truth.iter()) {
+            let error = pose.compose(&expected.inverse());
+            assert!(error.rotation_angle() < 1e-6);
+            assert!(error.translation.iter().all(|t| t.abs() < 1e-6));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_information_matrix_corridor() {
+        // the floor and the walls of a corridor along x, centered on the origin
+        let corridor = box_faces([10.0, 2.0, 3.0], &[(1, false), (1, true), (2, false)]);
+        let points = corridor
+            .points()
+            .iter()
+            .map(|p| [p[0] - 5.0, p[1] - 1.0, p[2]])
+            .collect::<Vec<_>>();
+        let target: ImmutableKdTree<f64, u32, 3, 32> = ImmutableKdTree::new_from_slice(&points);
+        let source = PointCloud::new(points, None, corridor.normals().cloned());
+
+        let information =
+            compute_information_matrix(&source, &target, &RigidTransform3::identity(), 0.1);
+        let (eigenvalues, eigenvectors) = eigen_symmetric(&information);
+        // the translation along the corridor is not constrained
+        assert!(eigenvalues[0].abs() < 1e-9 * eigenvalues[5]);
+        assert_relative_eq!(eigenvectors[0][3].abs(), 1.0, epsilon = 1e-9);
+        assert!(eigenvalues[1] > 1e-3 * eigenvalues[5]);
+    }
+
+    #[test]
+    fn test_information_matrix_well_constrained() {
+        // the six faces of a room
+        let faces = [0, 1, 2].map(|axis| [(axis, false), (axis, true)]).concat();
+        let room = box_faces([4.0, 3.0, 2.5], &faces);
+        let target: ImmutableKdTree<f64, u32, 3, 32> =
+            ImmutableKdTree::new_from_slice(room.points());
+
+        let information =
+            compute_information_matrix(&room, &target, &RigidTransform3::identity(), 0.1);
+        let (eigenvalues, _) = eigen_symmetric(&information);
+        assert!(eigenvalues[0] > 0.0);
+        assert!(eigenvalues[5] / eigenvalues[0] < 100.0);
+
+        // the point-to-point information is well conditioned too
+        let points = PointCloud::new(room.points().to_vec(), None, None);
+        let information =
+            compute_information_matrix(&points, &target, &RigidTransform3::identity(), 0.1);
+        let (eigenvalues, _) = eigen_symmetric(&information);
+        assert!(eigenvalues[0] > 0.0);
+        assert!(eigenvalues[5] / eigenvalues[0] < 100.0);
+    }
+
+    #[test]
+    fn test_information_matrix_scales_with_correspondences() {
+        let room = box_faces([4.0, 3.0, 2.5], &[(0, false), (1, false), (2, false)]);
+        let target: ImmutableKdTree<f64, u32, 3, 32> =
+            ImmutableKdTree::new_from_slice(room.points());
+        let transform = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.01).unwrap(),
+            [0.01, 0.0, 0.0],
+        );
+
+        // the same correspondences three times
+        let tripled = PointCloud::new(
+            room.points().repeat(3),
+            None,
+            room.normals().map(|n| n.repeat(3)),
+        );
+        let single = compute_information_matrix(&room, &target, &transform, 0.1);
+        let triple = compute_information_matrix(&tripled, &target, &transform, 0.1);
+        for i in 0..6 {
+            for j in 0..6 {
+                assert_relative_eq!(
+                    triple[i][j],
+                    3.0 * single[i][j],
+                    epsilon = 1e-9,
+                    max_relative = 1e-12
+                );
+            }
+        }
+
+        // the correspondences farther than the maximum distance are ignored
+        let far = RigidTransform3::new(transform.rotation, [10.0, 10.0, 10.0]);
+        assert_eq!(
+            compute_information_matrix(&room, &target, &far, 0.1),
+            [[0.0; 6]; 6]
+        );
+
+        // the registration edges are weighted by their information
+        let mut graph = PoseGraph::new(vec![RigidTransform3::identity(); 2]);
+        graph.add_registration_edge(0, 1, &room, &target, transform, 0.1);
+        assert_eq!(graph.edges[0].information, single);
+    }
+}
@@ -0,0 +1,265 @@
+use kornia_3d::linalg::dot_product3;
+
+/// The pre-check of a [`SampleValidator`] that rejected a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleRejection {
+    /// The lengths of corresponding edges differ.
+    EdgeLength,
+    /// The angles between corresponding normals and edges differ.
+    Normals,
+}
+
+/// The outcome of the samples of a RANSAC checked by a [`SampleValidator`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SampleStats {
+    /// The number of samples rejected by the edge length check.
+    pub rejected_edge_length: usize,
+    /// The number of samples rejected by the normal consistency check.
+    pub rejected_normals: usize,
+    /// The number of samples that passed the checks and were evaluated fully.
+    pub evaluated: usize,
+}
+
+impl SampleStats {
+    /// The total number of samples drawn.
+    pub fn num_samples(&self) -> usize {
+        self.rejected_edge_length + self.rejected_normals + self.evaluated
+    }
+
+    /// Count the outcome of the checks of a sample.
+    pub fn record(&mut self, outcome: Result<(), SampleRejection>) {
+        match outcome {
+            Ok(()) => self.evaluated += 1,
+            Err(SampleRejection::EdgeLength) => self.rejected_edge_length += 1,
+            Err(SampleRejection::Normals) => self.rejected_normals += 1,
+        }
+    }
+}
+
+/// Cheap pre-checks of the correspondences sampled by a RANSAC registration.
+///
+/// A rigid transformation preserves the distances between points and the angles between
+/// normals and edges, so a sample of correspondences whose source and target polygons are
+/// not congruent cannot be all inliers. Rejecting it before fitting and scoring a
+/// transformation saves most of the work of the RANSAC iterations at high outlier ratios.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleValidator {
+    /// The maximum relative difference of the lengths of corresponding edges: the shorter
+    /// edge must be at least `1 - edge_tolerance` times the longer one. Infinite to disable
+    /// the check.
+    pub edge_tolerance: f64,
+    /// The maximum difference in radians of the angles between the normals of the two ends of
+    /// corresponding edges, and between each normal and its edge, taken regardless of the
+    /// orientations of the normals. Infinite to disable the check.
+    pub normal_tolerance: f64,
+}
+
+impl SampleValidator {
+    /// A validator accepting every sample.
+    pub fn disabled() -> Self {
+        Self {
+            edge_tolerance: f64::INFINITY,
+            normal_tolerance: f64::INFINITY,
+        }
+    }
+
+    /// Check whether a sample of correspondences can be consistent with a rigid transformation.
+    ///
+    /// The edge lengths are checked first, then the normals if given.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source points of the sample.
+    /// * `target` - The target points of the sample, in the same order.
+    /// * `source_normals` - The unit normals of the source points, if any.
+    /// * `target_normals` - The unit normals of the target points, if any. The normals are
+    ///   only checked when both are given.
+    ///
+    /// # Returns
+    ///
+    /// The check that rejected the sample, if any.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use kornia_icp::{SampleRejection, SampleValidator};
+    ///
+    /// let validator = SampleValidator { edge_tolerance: 0.1, normal_tolerance: 0.2 };
+    /// let source = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+    /// // the same triangle translated, and the triangle scaled by two
+    /// let moved = source.map(|p| [p[0] + 5.0, p[1], p[2]]);
+    /// let scaled = source.map(|p| p.map(|v| 2.0 * v));
+    /// assert_eq!(validator.validate(&source, &moved, None, None), Ok(()));
+    /// assert_eq!(
+    ///     validator.validate(&source, &scaled, None, None),
+    ///     Err(SampleRejection::EdgeLength)
+    /// );
+    /// ```
+    pub fn validate(
+        &self,
+        source: &[[f64; 3]],
+        target: &[[f64; 3]],
+        source_normals: Option<&[[f64; 3]]>,
+        target_normals: Option<&[[f64; 3]]>,
+    ) -> Result<(), SampleRejection> {
+        let pairs = (0..source.len())
+            .flat_map(|a| (a + 1..source.len()).map(move |b| (a, b)))
+            .collect::<Vec<_>>();
+
+        if self.edge_tolerance.is_finite() {
+            let is_consistent = pairs.iter().all(|&(a, b)| {
+                let ls = distance(&source[a], &source[b]);
+                let lt = distance(&target[a], &target[b]);
+                ls.min(lt) >= (1.0 - self.edge_tolerance) * ls.max(lt)
+            });
+            if !is_consistent {
+                return Err(SampleRejection::EdgeLength);
+            }
+        }
+
+        if let (Some(source_normals), Some(target_normals), true) = (
+            source_normals,
+            target_normals,
+            self.normal_tolerance.is_finite(),
+        ) {
+            let is_consistent = pairs.iter().all(|&(a, b)| {
+                let source_angles = edge_angles(
+                    &source[a],
+                    &source[b],
+                    &source_normals[a],
+                    &source_normals[b],
+                );
+                let target_angles = edge_angles(
+                    &target[a],
+                    &target[b],
+                    &target_normals[a],
+                    &target_normals[b],
+                );
+                source_angles
+                    .iter()
+                    .zip(target_angles.iter())
+                    .all(|(s, t)| (s - t).abs() <= self.normal_tolerance)
+            });
+            if !is_consistent {
+                return Err(SampleRejection::Normals);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compute the distance between two points.
+fn distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+/// Compute the unsigned angles between the normals of the ends of an edge, and between each
+/// normal and the edge.
+fn edge_angles(a: &[f64; 3], b: &[f64; 3], na: &[f64; 3], nb: &[f64; 3]) -> [f64; 3] {
+    let length = distance(a, b);
+    let edge = if length > 0.0 {
+        [0, 1, 2].map(|k| (b[k] - a[k]) / length)
+    } else {
+        [0.0; 3]
+    };
+    let angle = |u: &[f64; 3], v: &[f64; 3]| dot_product3(u, v).abs().min(1.0).acos();
+    [angle(na, nb), angle(na, &edge), angle(nb, &edge)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::{axis_angle_to_rotation_matrix, RigidTransform3};
+
+    #[test]
+    fn test_validate_normals() -> Result<(), Box<dyn std::error::Error>> {
+        let pose = RigidTransform3::new(
+            axis_angle_to_rotation_matrix(&[0.6, 0.0, 0.8], 1.2)?,
+            [1.0, -2.0, 0.5],
+        );
+        let rotation = RigidTransform3::new(pose.rotation, [0.0; 3]);
+        let source = [[0.0, 0.0, 0.0], [1.0, 0.2, 0.0], [0.3, 1.0, 0.4]];
+        let source_normals = [[0.0, 0.0, 1.0], [0.6, 0.0, 0.8], [0.0, 1.0, 0.0]];
+        let target = source.map(|p| pose.apply(&p));
+        // the orientations of the normals are arbitrary
+        let mut target_normals = source_normals.map(|n| rotation.apply(&n));
+        target_normals[1] = target_normals[1].map(|v| -v);
+
+        let validator = SampleValidator {
+            edge_tolerance: 0.05,
+            normal_tolerance: 0.05,
+        };
+        assert_eq!(
+            validator.validate(
+                &source,
+                &target,
+                Some(&source_normals),
+                Some(&target_normals)
+            ),
+            Ok(())
+        );
+
+        // a congruent triangle with a normal tilted by 0.3 rad
+        target_normals[2] = rotation.apply(&[0.0, 0.3f64.cos(), 0.3f64.sin()]);
+        assert_eq!(
+            validator.validate(
+                &source,
+                &target,
+                Some(&source_normals),
+                Some(&target_normals)
+            ),
+            Err(SampleRejection::Normals)
+        );
+        assert_eq!(validator.validate(&source, &target, None, None), Ok(()));
+        assert_eq!(
+            SampleValidator::disabled().validate(
+                &source,
+                &target,
+                Some(&source_normals),
+                Some(&target_normals)
+            ),
+            Ok(())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_edge_tolerance() {
+        let source = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        // one edge is 5% longer
+        let target = [[0.0, 0.0, 0.0], [1.05, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        let validator = |edge_tolerance| SampleValidator {
+            edge_tolerance,
+            normal_tolerance: f64::INFINITY,
+        };
+        assert_eq!(
+            validator(0.1).validate(&source, &target, None, None),
+            Ok(())
+        );
+        assert_eq!(
+            validator(0.01).validate(&source, &target, None, None),
+            Err(SampleRejection::EdgeLength)
+        );
+
+        // degenerate samples pass when the check is disabled
+        let collapsed = [[0.0; 3]; 3];
+        assert_eq!(
+            validator(f64::INFINITY).validate(&collapsed, &target, None, None),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_sample_stats() {
+        let mut stats = SampleStats::default();
+        stats.record(Ok(()));
+        stats.record(Err(SampleRejection::EdgeLength));
+        stats.record(Err(SampleRejection::EdgeLength));
+        stats.record(Err(SampleRejection::Normals));
+        assert_eq!(stats.evaluated, 1);
+        assert_eq!(stats.rejected_edge_length, 2);
+        assert_eq!(stats.rejected_normals, 1);
+        assert_eq!(stats.num_samples(), 4);
+    }
+}
@@ -0,0 +1,375 @@
+use kornia_3d::{
+    linalg::{cross_vec3, eigen_symmetric},
+    pointcloud::PointCloud,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Weight of the constraint matrix of the whole cloud, in points, regularizing the one of the
+/// selection in [`stable_sample`].
+const STABLE_SAMPLE_REGULARIZATION: f64 = 1e-3;
+
+/// The strategy to select the source points used by the registration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SamplingStrategy {
+    /// Use all the points.
+    All,
+    /// Draw the points uniformly at random.
+    Random {
+        /// The number of points to draw.
+        num_samples: usize,
+        /// The seed of the random generator.
+        seed: u64,
+    },
+    /// Select the points that best constrain the pose, see [`stable_sample`].
+    Stable {
+        /// The number of points to select.
+        num_samples: usize,
+    },
+}
+
+impl SamplingStrategy {
+    /// Select the points of a cloud.
+    ///
+    /// # Arguments
+    ///
+    /// * `cloud` - The point cloud.
+    /// * `normals` - The unit normal of each point.
+    ///
+    /// # Returns
+    ///
+    /// The sorted indices of the selected points.
+    pub fn sample(&self, cloud: &PointCloud, normals: &[[f64; 3]]) -> Vec<usize> {
+        match *self {
+            SamplingStrategy::All => (0..cloud.len()).collect(),
+            SamplingStrategy::Random { num_samples, seed } => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let num_samples = num_samples.min(cloud.len());
+                let mut indices =
+                    rand::seq::index::sample(&mut rng, cloud.len(), num_samples).into_vec();
+                indices.sort_unstable();
+                indices
+            }
+            SamplingStrategy::Stable { num_samples } => stable_sample(cloud, normals, num_samples),
+        }
+    }
+}
+
+/// Select the points of a cloud that best constrain a point-to-plane registration.
+///
+/// Each point constrains the pose along its constraint vector `[p × n, n]`, the gradient
+/// of its point-to-plane residual with respect to the rotation and the translation. Random
+/// sampling can drop the few points constraining a weak degree of freedom, e.g. the
+/// translation along a corridor. Following the geometrically stable sampling of Gelfand et
+/// al. 2003, the points are selected greedily along the eigenvectors of the 6x6 constraint
+/// matrix of the selection: the eigenvector of its smallest eigenvalue gets its most
+/// constraining point left, which keeps the condition number of the constraint matrix of
+/// the selection low. The constraint matrix of the whole cloud, scaled down to a fraction of
+/// a point, regularizes the one of the selection so that the first points follow the
+/// weakest directions of the cloud. The points are centered and scaled by their mean distance
+/// to the centroid so that the rotations and translations are comparable.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `normals` - The unit normal of each point.
+/// * `n` - The number of points to select.
+///
+/// # Returns
+///
+/// The sorted indices of the selected points, all of them if `n` is larger than the cloud.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::stable_sample;
+/// use kornia_3d::pointcloud::PointCloud;
+///
+/// // a large floor and a single point on a wall
+/// let mut points = (0..100).map(|i| [(i % 10) as f64, (i / 10) as f64, 0.0]).collect::<Vec<_>>();
+/// let mut normals = vec![[0.0, 0.0, 1.0]; 100];
+/// points.push([10.0, 5.0, 1.0]);
+/// normals.push([-1.0, 0.0, 0.0]);
+///
+/// let cloud = PointCloud::new(points, None, None);
+/// assert!(stable_sample(&cloud, &normals, 10).contains(&100));
+/// ```
+pub fn stable_sample(cloud: &PointCloud, normals: &[[f64; 3]], n: usize) -> Vec<usize> {
+    let constraints = constraint_vectors(cloud, normals);
+    if n >= constraints.len() {
+        return (0..constraints.len()).collect();
+    }
+
+    // the constraint matrix of the selection, regularized by the one of the whole cloud
+    let full = constraint_matrix(constraints.iter());
+    let regularization = STABLE_SAMPLE_REGULARIZATION / constraints.len() as f64;
+    let mut covariance = full.map(|row| row.map(|x| regularization * x));
+
+    let mut selected = vec![false; constraints.len()];
+    for _ in 0..n {
+        // the most constraining point left along the least constrained direction
+        let (_, eigenvectors) = eigen_symmetric(&covariance);
+        let weakest = &eigenvectors[0];
+        let projection = |v: &[f64; 6]| {
+            v.iter()
+                .zip(weakest.iter())
+                .map(|(a, b)| a * b)
+                .sum::<f64>()
+        };
+        let Some(i) = (0..constraints.len())
+            .filter(|&i| !selected[i])
+            .max_by(|&a, &b| {
+                projection(&constraints[a])
+                    .abs()
+                    .total_cmp(&projection(&constraints[b]).abs())
+                    .then(b.cmp(&a))
+            })
+        else {
+            break;
+        };
+        selected[i] = true;
+        let v = &constraints[i];
+        for (r, row) in covariance.iter_mut().enumerate() {
+            for (c, x) in row.iter_mut().enumerate() {
+                *x += v[r] * v[c];
+            }
+        }
+    }
+
+    (0..constraints.len()).filter(|&i| selected[i]).collect()
+}
+
+/// Compute the condition number of the point-to-plane constraint matrix of a selection.
+///
+/// The constraint vectors are normalized as in [`stable_sample`], over the whole cloud.
+///
+/// # Arguments
+///
+/// * `cloud` - The point cloud.
+/// * `normals` - The unit normal of each point.
+/// * `indices` - The indices of the selected points.
+///
+/// # Returns
+///
+/// The ratio of the largest to the smallest eigenvalue of the 6x6 constraint matrix, or
+/// infinity if a degree of freedom of the pose is not constrained.
+pub fn constraint_condition_number(
+    cloud: &PointCloud,
+    normals: &[[f64; 3]],
+    indices: &[usize],
+) -> f64 {
+    let constraints = constraint_vectors(cloud, normals);
+    let covariance = constraint_matrix(indices.iter().map(|&i| &constraints[i]));
+    let (eigenvalues, _) = eigen_symmetric(&covariance);
+    if eigenvalues[0] <= 1e-12 * eigenvalues[5] {
+        return f64::INFINITY;
+    }
+    eigenvalues[5] / eigenvalues[0]
+}
+
+/// Compute the normalized constraint vector `[p × n, n]` of each point.
+fn constraint_vectors(cloud: &PointCloud, normals: &[[f64; 3]]) -> Vec<[f64; 6]> {
+    let points = cloud.points();
+    if points.is_empty() {
+        return Vec::new();
+    }
+
+    let num_points = points.len() as f64;
+    let mut centroid = [0.0; 3];
+    for p in points {
+        for k in 0..3 {
+            centroid[k] += p[k] / num_points;
+        }
+    }
+    let scale = points
+        .iter()
+        .map(|p| {
+            ((p[0] - centroid[0]).powi(2)
+                + (p[1] - centroid[1]).powi(2)
+                + (p[2] - centroid[2]).powi(2))
+            .sqrt()
+        })
+        .sum::<f64>()
+        / num_points;
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+
+    points
+        .iter()
+        .zip(normals.iter())
+        .map(|(p, n)| {
+            let q = [0, 1, 2].map(|k| (p[k] - centroid[k]) / scale);
+            let mut torque = [0.0; 3];
+            cross_vec3(&q, n, &mut torque);
+            [torque[0], torque[1], torque[2], n[0], n[1], n[2]]
+        })
+        .collect()
+}
+
+/// Sum the outer products of constraint vectors.
+fn constraint_matrix<'a>(constraints: impl Iterator<Item = &'a [f64; 6]>) -> [[f64; 6]; 6] {
+    let mut covariance = [[0.0; 6]; 6];
+    for v in constraints {
+        for r in 0..6 {
+            for c in 0..6 {
+                covariance[r][c] += v[r] * v[c];
+            }
+        }
+    }
+    covariance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{icp_point_to_plane, IcpError, PointToPlaneConfig};
+    use kornia_3d::{linalg::mat33_mul_vec3, transforms::RigidTransform3};
+    use rand::Rng;
+
+    const IDENTITY: [[f64; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    /// Check if a point is on the small wall across the corridor.
+    fn on_patch(p: &[f64; 3]) -> bool {
+        (p[0] - 3.0).abs() < 1e-9 && p[1] >= 0.8
+    }
+
+    /// A corridor along the x axis with a small wall patch across it, with its normals.
+    ///
+    /// Only the patch constrains the translation along the corridor.
+    fn corridor(num_points: usize, seed: u64) -> (Vec<[f64; 3]>, Vec<[f64; 3]>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        // the areas of the floor, of the two walls and of the patch
+        let areas = [40.0, 40.0, 40.0, 0.4];
+        let total = areas.iter().sum::<f64>();
+        (0..num_points)
+            .map(|_| {
+                let (x, u): (f64, f64) = (rng.random_range(-10.0..10.0), rng.random());
+                let mut pick = rng.random_range(0.0..total);
+                let surface = areas
+                    .iter()
+                    .position(|a| {
+                        pick -= a;
+                        pick < 0.0
+                    })
+                    .unwrap_or(3);
+                match surface {
+                    0 => ([x, 2.0 * u - 1.0, 0.0], [0.0, 0.0, 1.0]),
+                    1 => ([x, -1.0, 2.0 * u], [0.0, 1.0, 0.0]),
+                    2 => ([x, 1.0, 2.0 * u], [0.0, -1.0, 0.0]),
+                    _ => (
+                        [3.0, 0.8 + 0.2 * u, rng.random_range(0.0..2.0)],
+                        [-1.0, 0.0, 0.0],
+                    ),
+                }
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn test_stable_sample_keeps_constraining_points() {
+        let (points, normals) = corridor(20000, 0);
+        let cloud = PointCloud::new(points, None, None);
+        let num_patch = cloud.points().iter().filter(|p| on_patch(p)).count();
+        assert!(num_patch > 30);
+
+        let stable = stable_sample(&cloud, &normals, 100);
+        assert_eq!(stable.len(), 100);
+        assert!(stable.windows(2).all(|w| w[0] < w[1]));
+        let num_stable_patch = stable
+            .iter()
+            .filter(|&&i| on_patch(&cloud.points()[i]))
+            .count();
+        assert!(num_stable_patch >= 3, "{num_stable_patch} patch points");
+        assert!(constraint_condition_number(&cloud, &normals, &stable) < 1e3);
+
+        // random samples of the same budget mostly miss the patch
+        let num_degenerate = (0..10)
+            .filter(|&seed| {
+                let random = SamplingStrategy::Random {
+                    num_samples: 100,
+                    seed,
+                }
+                .sample(&cloud, &normals);
+                constraint_condition_number(&cloud, &normals, &random) == f64::INFINITY
+            })
+            .count();
+        assert!(num_degenerate >= 5, "{num_degenerate} degenerate samples");
+    }
+
+    #[test]
+    fn test_stable_sampling_keeps_pose_observable() -> Result<(), Box<dyn std::error::Error>> {
+        let (dst_points, dst_normals) = corridor(20000, 1);
+        let (points, normals) = corridor(20000, 2);
+
+        let dst_r_src =
+            kornia_3d::transforms::axis_angle_to_rotation_matrix(&[0.1, -0.2, 1.0], 0.03)?;
+        let dst_t_src = [0.1, 0.03, 0.02];
+        let src_from_dst = RigidTransform3::new(dst_r_src, dst_t_src).inverse();
+        let src_points = points.iter().map(|p| src_from_dst.apply(p)).collect();
+        let src_normals = normals
+            .iter()
+            .map(|n| {
+                let mut m = [0.0; 3];
+                mat33_mul_vec3(&src_from_dst.rotation, n, &mut m);
+                m
+            })
+            .collect::<Vec<_>>();
+
+        let source = PointCloud::new(src_points, None, None);
+        let target = PointCloud::new(dst_points, None, None);
+        let register = |sampling: SamplingStrategy| {
+            let config = PointToPlaneConfig {
+                max_iterations: 30,
+                tolerance: 1e-12,
+                entropy_threshold: 0.0,
+                max_correspondence_distance: 0.5,
+                sampling,
+            };
+            icp_point_to_plane(
+                &source,
+                &src_normals,
+                &target,
+                &dst_normals,
+                IDENTITY,
+                [0.0; 3],
+                &config,
+            )
+        };
+
+        let result = register(SamplingStrategy::Stable { num_samples: 100 })?;
+        // the correspondences across the edges of the corridor bias the fit slightly
+        for (t, expected) in result.translation.iter().zip(dst_t_src.iter()) {
+            assert!((t - expected).abs() < 1e-2);
+        }
+
+        // the random samples missing the patch cannot observe the pose along the corridor
+        let num_failures = (0..10)
+            .filter(|&seed| {
+                match register(SamplingStrategy::Random {
+                    num_samples: 100,
+                    seed,
+                }) {
+                    Ok(result) => (result.translation[0] - dst_t_src[0]).abs() > 1e-2,
+                    Err(IcpError::DegenerateConstraints) => true,
+                    Err(_) => false,
+                }
+            })
+            .count();
+        assert!(num_failures >= 5, "{num_failures} failures");
+        Ok(())
+    }
+
+    #[test]
+    fn test_sampling_strategy() {
+        let (points, normals) = corridor(50, 0);
+        let cloud = PointCloud::new(points, None, None);
+        assert_eq!(
+            SamplingStrategy::All.sample(&cloud, &normals),
+            (0..50).collect::<Vec<_>>()
+        );
+        let random = SamplingStrategy::Random {
+            num_samples: 80,
+            seed: 0,
+        };
+        assert_eq!(random.sample(&cloud, &normals).len(), 50);
+        assert_eq!(stable_sample(&cloud, &normals, 60).len(), 50);
+    }
+}
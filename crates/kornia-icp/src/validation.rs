@@ -0,0 +1,172 @@
+use kornia_3d::linalg::{det_mat33, frobenius_norm33, matmul33, transpose_mat33};
+
+/// Tolerance used to check the determinant and the orthogonality of the rotation.
+const ROTATION_TOLERANCE: f64 = 1e-6;
+
+/// Error types for the ICP result validation.
+#[derive(Debug, thiserror::Error)]
+pub enum IcpValidationError {
+    /// The determinant of the rotation matrix is not one.
+    #[error("Invalid rotation determinant: {0}")]
+    InvalidDeterminant(f64),
+
+    /// The rotation matrix is not orthogonal.
+    #[error("Rotation is not orthogonal, |R * R^T - I| = {0}")]
+    NotOrthogonal(f64),
+
+    /// The translation norm exceeds the maximum allowed translation.
+    #[error("Translation norm {norm} exceeds the maximum {max}")]
+    TranslationTooLarge {
+        /// The norm of the translation vector.
+        norm: f64,
+        /// The maximum allowed translation.
+        max: f64,
+    },
+
+    /// The rotation angle exceeds the maximum allowed rotation.
+    #[error("Rotation angle {angle_deg} deg exceeds the maximum {max_deg} deg")]
+    RotationTooLarge {
+        /// The rotation angle in degrees.
+        angle_deg: f64,
+        /// The maximum allowed rotation in degrees.
+        max_deg: f64,
+    },
+}
+
+/// Check whether the output of ICP is physically plausible.
+///
+/// The following checks are performed in order:
+///
+/// 1. `|det(R) - 1| < 1e-6`
+/// 2. `R * R^T ≈ I`
+/// 3. `‖t‖ < max_translation_m`
+/// 4. The rotation angle is below `max_rotation_deg`.
+///
+/// Non-finite values fail the checks, which makes this function a guard against numerical
+/// blowups.
+///
+/// # Arguments
+///
+/// * `r` - The estimated rotation matrix.
+/// * `t` - The estimated translation vector.
+/// * `max_translation_m` - The maximum allowed translation norm.
+/// * `max_rotation_deg` - The maximum allowed rotation angle in degrees.
+///
+/// # Returns
+///
+/// An error indicating which check failed, if any.
+///
+/// Example:
+///
+/// ```
+/// use kornia_icp::validate_icp_result;
+///
+/// let r = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+/// let t = [0.1, 0.2, 0.3];
+/// assert!(validate_icp_result(&r, &t, 1.0, 10.0).is_ok());
+/// ```
+pub fn validate_icp_result(
+    r: &[[f64; 3]; 3],
+    t: &[f64; 3],
+    max_translation_m: f64,
+    max_rotation_deg: f64,
+) -> Result<(), IcpValidationError> {
+    // (1) the determinant of a proper rotation is one
+    let det = det_mat33(r);
+    if !det.is_finite() || (det - 1.0).abs() >= ROTATION_TOLERANCE {
+        return Err(IcpValidationError::InvalidDeterminant(det));
+    }
+
+    // (2) R * R^T = I
+    let mut r_t = [[0.0; 3]; 3];
+    transpose_mat33(r, &mut r_t);
+    let mut r_r_t = [[0.0; 3]; 3];
+    matmul33(r, &r_t, &mut r_r_t);
+    for (i, row) in r_r_t.iter_mut().enumerate() {
+        row[i] -= 1.0;
+    }
+    let ortho_error = frobenius_norm33(&r_r_t);
+    if !ortho_error.is_finite() || ortho_error >= ROTATION_TOLERANCE {
+        return Err(IcpValidationError::NotOrthogonal(ortho_error));
+    }
+
+    // (3) the translation is bounded
+    let norm = (t[0] * t[0] + t[1] * t[1] + t[2] * t[2]).sqrt();
+    if !norm.is_finite() || norm >= max_translation_m {
+        return Err(IcpValidationError::TranslationTooLarge {
+            norm,
+            max: max_translation_m,
+        });
+    }
+
+    // (4) the rotation angle is bounded
+    let trace = r[0][0] + r[1][1] + r[2][2];
+    let angle_deg = ((trace - 1.0) / 2.0).clamp(-1.0, 1.0).acos().to_degrees();
+    if angle_deg.is_nan() || angle_deg >= max_rotation_deg {
+        return Err(IcpValidationError::RotationTooLarge {
+            angle_deg,
+            max_deg: max_rotation_deg,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kornia_3d::transforms::axis_angle_to_rotation_matrix;
+
+    #[test]
+    fn test_validate_icp_result_ok() -> Result<(), Box<dyn std::error::Error>> {
+        let r = axis_angle_to_rotation_matrix(&[0.0, 0.0, 1.0], 0.1)?;
+        let t = [0.1, 0.0, 0.0];
+        validate_icp_result(&r, &t, 1.0, 10.0)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_icp_result_reflection() {
+        let r = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]];
+        let res = validate_icp_result(&r, &[0.0; 3], 1.0, 10.0);
+        assert!(matches!(
+            res,
+            Err(IcpValidationError::InvalidDeterminant(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_icp_result_not_orthogonal() {
+        // a shear matrix with unit determinant
+        let r = [[1.0, 0.5, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let res = validate_icp_result(&r, &[0.0; 3], 1.0, 10.0);
+        assert!(matches!(res, Err(IcpValidationError::NotOrthogonal(_))));
+    }
+
+    #[test]
+    fn test_validate_icp_result_translation() {
+        let r = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let res = validate_icp_result(&r, &[3.0, 4.0, 0.0], 1.0, 10.0);
+        assert!(matches!(
+            res,
+            Err(IcpValidationError::TranslationTooLarge { norm, .. }) if (norm - 5.0).abs() < 1e-12
+        ));
+
+        let res = validate_icp_result(&r, &[f64::NAN, 0.0, 0.0], 1.0, 10.0);
+        assert!(matches!(
+            res,
+            Err(IcpValidationError::TranslationTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_icp_result_rotation() -> Result<(), Box<dyn std::error::Error>> {
+        let r = axis_angle_to_rotation_matrix(&[1.0, 0.0, 0.0], 30f64.to_radians())?;
+        let res = validate_icp_result(&r, &[0.0; 3], 1.0, 10.0);
+        assert!(matches!(
+            res,
+            Err(IcpValidationError::RotationTooLarge { angle_deg, .. }) if (angle_deg - 30.0).abs() < 1e-6
+        ));
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use faer::mat;
-use glam::{Mat3, Vec3};
-use kornia_linalg::linalg;
+use glam::{DMat3, DVec3, Mat3, Vec3};
+use kornia_linalg::{eigen, linalg};
 
 fn bench_svd3(c: &mut Criterion) {
     let mut group = c.benchmark_group("svd3");
@@ -11,6 +11,12 @@ fn bench_svd3(c: &mut Criterion) {
         z_axis: Vec3::new(0.0, 0.0, 3.0),
     };
 
+    let a_f64 = DMat3 {
+        x_axis: DVec3::new(1.0, 0.0, 0.0),
+        y_axis: DVec3::new(0.0, 2.0, 0.0),
+        z_axis: DVec3::new(0.0, 0.0, 3.0),
+    };
+
     let a2 = mat![[1.0, 0.0, 0.0], [0.0, 2.0, 0.0], [0.0, 0.0, 3.0]];
 
     group.bench_function(BenchmarkId::new("svd3", ""), |b| {
@@ -20,13 +26,65 @@ fn bench_svd3(c: &mut Criterion) {
         })
     });
 
+    group.bench_function(BenchmarkId::new("svd3_f64", ""), |b| {
+        b.iter(|| {
+            linalg::svd3_f64(&a_f64);
+            black_box(());
+        })
+    });
+
     group.bench_function(BenchmarkId::new("svd3_faer", ""), |b| {
         b.iter(|| {
             a2.svd();
             black_box(());
         })
     });
+
+    let matrices = (0..10_000)
+        .map(|i| Mat3::from_diagonal(Vec3::new(3.0, 2.0, i as f32 * 1e-4)))
+        .collect::<Vec<_>>();
+    let mut out = vec![linalg::SVD3Set::default(); matrices.len()];
+
+    group.bench_function(BenchmarkId::new("svd3_scalar", "10000"), |b| {
+        b.iter(|| {
+            for (a, svd) in matrices.iter().zip(out.iter_mut()) {
+                *svd = linalg::svd3(a);
+            }
+            black_box(());
+        })
+    });
+
+    #[cfg(feature = "rayon")]
+    group.bench_function(BenchmarkId::new("svd3_batch", "10000"), |b| {
+        b.iter(|| {
+            linalg::svd3_batch(&matrices, &mut out);
+            black_box(());
+        })
+    });
+}
+
+fn bench_eigh3(c: &mut Criterion) {
+    let mut group = c.benchmark_group("eigh3");
+    let a = Mat3 {
+        x_axis: Vec3::new(2.0, 1.0, 0.5),
+        y_axis: Vec3::new(1.0, 3.0, 0.2),
+        z_axis: Vec3::new(0.5, 0.2, 4.0),
+    };
+
+    group.bench_function(BenchmarkId::new("eigh3", ""), |b| {
+        b.iter(|| {
+            eigen::eigh3(&a);
+            black_box(());
+        })
+    });
+
+    group.bench_function(BenchmarkId::new("eigh3_f64", ""), |b| {
+        b.iter(|| {
+            eigen::eigh3_f64(&a.as_dmat3());
+            black_box(());
+        })
+    });
 }
 
-criterion_group!(benches, bench_svd3);
+criterion_group!(benches, bench_svd3, bench_eigh3);
 criterion_main!(benches);
@@ -0,0 +1,166 @@
+use glam::{DMat3, DVec3};
+
+/// The pivots of the factorization, relative to the largest diagonal entry, below which the
+/// matrix is not positive definite.
+const CHOLESKY_EPSILON_F64: f64 = 1e-15;
+
+/// Computes the Cholesky factorization of a symmetric positive definite 3x3 matrix.
+///
+/// Only the lower triangle of `a` is read. The matrix is positive definite when the pivots
+/// of the factorization, the squares of the diagonal of `L`, are larger than a tolerance
+/// relative to the largest diagonal entry of `a`, so that a rounding error does not turn a
+/// semi-definite or indefinite matrix into a factor of NaNs or infinities.
+///
+/// # Arguments
+///
+/// * `a` - The symmetric positive definite matrix to factorize.
+///
+/// # Returns
+///
+/// The lower triangular matrix `L` with a positive diagonal such that `A = L * L^T`, or
+/// `None` if the matrix is not positive definite or not finite.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat3, DVec3};
+/// use kornia_linalg::cholesky::cholesky3;
+///
+/// let a = DMat3::from_cols(
+///     DVec3::new(4.0, 2.0, 0.0),
+///     DVec3::new(2.0, 5.0, 1.0),
+///     DVec3::new(0.0, 1.0, 3.0),
+/// );
+/// let l = cholesky3(&a).unwrap();
+/// assert!(l.mul_mat3(&l.transpose()).abs_diff_eq(a, 1e-12));
+/// assert_eq!(l.x_axis.x, 2.0);
+/// ```
+pub fn cholesky3(a: &DMat3) -> Option<DMat3> {
+    // A and L stored column major, A[j][i] being the entry of row i and column j
+    let a = a.to_cols_array_2d();
+    let scale = a[0][0].max(a[1][1]).max(a[2][2]);
+    if !(scale > 0.0 && scale.is_finite()) {
+        return None;
+    }
+
+    let mut l = [[0.0; 3]; 3];
+    for j in 0..3 {
+        let pivot = a[j][j] - (0..j).map(|k| l[k][j] * l[k][j]).sum::<f64>();
+        if pivot.is_nan() || pivot <= CHOLESKY_EPSILON_F64 * scale {
+            return None;
+        }
+        l[j][j] = pivot.sqrt();
+        for i in j + 1..3 {
+            let sum = (0..j).map(|k| l[k][i] * l[k][j]).sum::<f64>();
+            l[j][i] = (a[j][i] - sum) / l[j][j];
+        }
+    }
+    Some(DMat3::from_cols_array_2d(&l))
+}
+
+/// Solves the linear system `A * x = b` of a symmetric positive definite 3x3 matrix.
+///
+/// The system is solved with the factor `L` of [`cholesky3`], by forward substitution of
+/// `L * y = b` and backward substitution of `L^T * x = y`.
+///
+/// # Arguments
+///
+/// * `a` - The symmetric positive definite matrix of the system.
+/// * `b` - The right hand side of the system.
+///
+/// # Returns
+///
+/// The solution `x`, or `None` if the matrix is not positive definite or not finite.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat3, DVec3};
+/// use kornia_linalg::cholesky::solve_spd3;
+///
+/// let a = DMat3::from_cols(
+///     DVec3::new(4.0, 2.0, 0.0),
+///     DVec3::new(2.0, 5.0, 1.0),
+///     DVec3::new(0.0, 1.0, 3.0),
+/// );
+/// let x = DVec3::new(1.0, -2.0, 0.5);
+/// let solution = solve_spd3(&a, &a.mul_vec3(x)).unwrap();
+/// assert!(solution.abs_diff_eq(x, 1e-12));
+/// ```
+pub fn solve_spd3(a: &DMat3, b: &DVec3) -> Option<DVec3> {
+    let l = cholesky3(a)?.to_cols_array_2d();
+
+    let mut y = [0.0; 3];
+    for i in 0..3 {
+        let sum = (0..i).map(|k| l[k][i] * y[k]).sum::<f64>();
+        y[i] = (b[i] - sum) / l[i][i];
+    }
+    let mut x = [0.0; 3];
+    for i in (0..3).rev() {
+        let sum = (i + 1..3).map(|k| l[i][k] * x[k]).sum::<f64>();
+        x[i] = (y[i] - sum) / l[i][i];
+    }
+    Some(DVec3::from_array(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::generator;
+
+    /// The symmetric matrix of the given eigenvalues in a rotated basis.
+    fn with_eigenvalues(eigenvalues: DVec3) -> DMat3 {
+        let rotation = DMat3::from_euler(glam::EulerRot::XYZ, 0.3, -1.1, 0.7);
+        rotation
+            .mul_mat3(&DMat3::from_diagonal(eigenvalues))
+            .mul_mat3(&rotation.transpose())
+    }
+
+    #[test]
+    fn test_cholesky3_well_conditioned() {
+        let mut next = generator(42);
+        for _ in 0..1000 {
+            let m = DMat3::from_cols_array(&std::array::from_fn(|_| next()));
+            let a = m.transpose().mul_mat3(&m) + DMat3::IDENTITY;
+
+            let l = cholesky3(&a).unwrap();
+            assert!(l.mul_mat3(&l.transpose()).abs_diff_eq(a, 1e-12));
+            assert_eq!([l.y_axis.x, l.z_axis.x, l.z_axis.y], [0.0; 3]);
+            assert!(l.x_axis.x > 0.0 && l.y_axis.y > 0.0 && l.z_axis.z > 0.0);
+
+            let x = DVec3::new(next(), next(), next());
+            let solution = solve_spd3(&a, &a.mul_vec3(x)).unwrap();
+            assert!(solution.abs_diff_eq(x, 1e-12));
+        }
+    }
+
+    #[test]
+    fn test_cholesky3_barely_positive() {
+        let a = with_eigenvalues(DVec3::new(1e-9, 1.0, 2.0));
+        let l = cholesky3(&a).unwrap();
+        assert!(l.mul_mat3(&l.transpose()).abs_diff_eq(a, 1e-12));
+
+        // the residual is small, while the error of x grows with the condition number
+        let x = DVec3::new(0.5, -1.0, 2.0);
+        let b = a.mul_vec3(x);
+        let solution = solve_spd3(&a, &b).unwrap();
+        assert!(a.mul_vec3(solution).abs_diff_eq(b, 1e-12));
+        assert!(solution.abs_diff_eq(x, 1e-5));
+    }
+
+    #[test]
+    fn test_cholesky3_not_positive_definite() {
+        let b = DVec3::new(1.0, 2.0, 3.0);
+        for a in [
+            with_eigenvalues(DVec3::new(-1.0, 1.0, 2.0)),
+            with_eigenvalues(DVec3::new(0.0, 1.0, 2.0)),
+            with_eigenvalues(DVec3::new(-1.0, -2.0, -3.0)),
+            DMat3::from_diagonal(DVec3::new(1.0, 0.0, 1.0)),
+            DMat3::ZERO,
+            DMat3::from_diagonal(DVec3::new(1.0, f64::NAN, 1.0)),
+        ] {
+            assert!(cholesky3(&a).is_none());
+            assert!(solve_spd3(&a, &b).is_none());
+        }
+    }
+}
@@ -0,0 +1,564 @@
+use glam::{DMat2, DMat3, DVec2, DVec3, Mat2, Mat3, Vec2, Vec3};
+
+/// Maximum number of cyclic Jacobi sweeps in single precision.
+const EIGH3_SWEEPS: u8 = 8;
+
+/// Maximum number of cyclic Jacobi sweeps in double precision.
+const EIGH3_SWEEPS_F64: u8 = 16;
+
+/// The pairs of rows and columns annihilated in turn by each sweep.
+const PAIRS: [(usize, usize); 3] = [(0, 1), (0, 2), (1, 2)];
+
+/// Generates the cyclic Jacobi eigenvalue algorithm for a precision.
+macro_rules! eigh3_kernel {
+    ($t:ty, $mat3:ident, $vec3:ident, $sweeps:expr) => {
+        /// Returns the eigenvalues sorted ascending and the matrix of the eigenvectors of the
+        /// symmetric part of A.
+        pub(super) fn eigh3(m: &$mat3) -> ($vec3, $mat3) {
+            // the symmetric part, stored column major
+            let cols = m.to_cols_array_2d();
+            let mut a = [[0.0 as $t; 3]; 3];
+            for (i, row) in a.iter_mut().enumerate() {
+                for (j, x) in row.iter_mut().enumerate() {
+                    *x = 0.5 * (cols[i][j] + cols[j][i]);
+                }
+            }
+            let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+            let norm2 = a.iter().flatten().map(|x| x * x).sum::<$t>();
+            for _ in 0..$sweeps {
+                let off2 = 2.0 * (a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2]);
+                if off2 <= <$t>::EPSILON * <$t>::EPSILON * norm2 {
+                    break;
+                }
+
+                for (p, q) in PAIRS {
+                    if a[p][q] == 0.0 {
+                        continue;
+                    }
+                    // the rotation zeroing A(p, q), taking the smaller angle for stability
+                    let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                    let t = theta.signum() / (theta.abs() + theta.hypot(1.0));
+                    let c = 1.0 / t.hypot(1.0);
+                    let s = t * c;
+
+                    // A = J^T * A * J, the matrix being symmetric
+                    for k in 0..3 {
+                        let (akp, akq) = (a[k][p], a[k][q]);
+                        a[k][p] = c * akp - s * akq;
+                        a[k][q] = s * akp + c * akq;
+                    }
+                    for k in 0..3 {
+                        let (apk, aqk) = (a[p][k], a[q][k]);
+                        a[p][k] = c * apk - s * aqk;
+                        a[q][k] = s * apk + c * aqk;
+                    }
+                    a[p][q] = 0.0;
+                    a[q][p] = 0.0;
+
+                    // V = V * J, the columns of V being the eigenvectors
+                    for k in 0..3 {
+                        let (vpk, vqk) = (v[p][k], v[q][k]);
+                        v[p][k] = c * vpk - s * vqk;
+                        v[q][k] = s * vpk + c * vqk;
+                    }
+                }
+            }
+
+            // sort the eigenvalues ascending, permuting the eigenvectors along
+            let mut order = [0, 1, 2];
+            order.sort_by(|i, j| a[*i][*i].total_cmp(&a[*j][*j]));
+            let values = $vec3::new(
+                a[order[0]][order[0]],
+                a[order[1]][order[1]],
+                a[order[2]][order[2]],
+            );
+            let mut vectors = $mat3::from_cols_array_2d(&order.map(|i| v[i]));
+
+            // a right handed basis, flipping the last eigenvector if needed
+            if vectors.determinant() < 0.0 {
+                vectors.z_axis = -vectors.z_axis;
+            }
+            (values, vectors)
+        }
+    };
+}
+
+mod eigh3_f32 {
+    use super::{EIGH3_SWEEPS, PAIRS};
+    use glam::{Mat3, Vec3};
+
+    eigh3_kernel!(f32, Mat3, Vec3, EIGH3_SWEEPS);
+}
+
+mod eigh3_f64 {
+    use super::{EIGH3_SWEEPS_F64, PAIRS};
+    use glam::{DMat3, DVec3};
+
+    eigh3_kernel!(f64, DMat3, DVec3, EIGH3_SWEEPS_F64);
+}
+
+/// Generates the closed form eigendecomposition of a symmetric 2x2 matrix for a precision.
+macro_rules! eigh2_kernel {
+    ($t:ty, $mat2:ident, $vec2:ident) => {
+        /// Returns the eigenvalues sorted ascending and the rotation of the eigenvectors of
+        /// the symmetric part of A.
+        pub(super) fn eigh2(m: &$mat2) -> ($vec2, $mat2) {
+            let [[a, b0], [b1, c]] = m.to_cols_array_2d();
+            let b = 0.5 * (b0 + b1);
+
+            // the eigenvalues mean +- radius, the one of smaller magnitude from the
+            // determinant to avoid the cancellation
+            let (mean, half_diff) = (0.5 * (a + c), 0.5 * (a - c));
+            let radius = half_diff.hypot(b);
+            let det = a * c - b * b;
+            let (lower, upper) = if mean >= 0.0 {
+                let upper = mean + radius;
+                (if upper != 0.0 { det / upper } else { 0.0 }, upper)
+            } else {
+                let lower = mean - radius;
+                (lower, det / lower)
+            };
+
+            // the eigenvector of the largest eigenvalue at the angle atan2(2b, a - c) / 2,
+            // and the other one a quarter turn before it so that V is a rotation
+            let angle = 0.5 * b.atan2(half_diff);
+            let (sin, cos) = angle.sin_cos();
+            let vectors = $mat2::from_cols($vec2::new(sin, -cos), $vec2::new(cos, sin));
+            ($vec2::new(lower, upper), vectors)
+        }
+    };
+}
+
+mod eigh2_f32 {
+    use glam::{Mat2, Vec2};
+
+    eigh2_kernel!(f32, Mat2, Vec2);
+}
+
+mod eigh2_f64 {
+    use glam::{DMat2, DVec2};
+
+    eigh2_kernel!(f64, DMat2, DVec2);
+}
+
+#[derive(Debug)]
+/// The eigendecomposition of a symmetric 3x3 matrix.
+pub struct Eigh3Result {
+    /// The eigenvalues, sorted ascending.
+    eigenvalues: Vec3,
+
+    /// The matrix of the eigenvectors, one per column.
+    eigenvectors: Mat3,
+}
+
+impl Eigh3Result {
+    /// Get the eigenvalues, sorted ascending.
+    #[inline]
+    pub fn eigenvalues(&self) -> &Vec3 {
+        &self.eigenvalues
+    }
+
+    /// Get the matrix of the eigenvectors, the column `i` matching the eigenvalue `i`.
+    #[inline]
+    pub fn eigenvectors(&self) -> &Mat3 {
+        &self.eigenvectors
+    }
+}
+
+/// Computes the eigendecomposition of a symmetric 3x3 matrix.
+///
+/// The cyclic Jacobi algorithm rotates the matrix until its off-diagonal entries vanish
+/// relative to its norm, with exact rotations, so that repeated eigenvalues and near zero
+/// matrices keep an orthonormal basis of eigenvectors. Only the symmetric part of `a` is
+/// decomposed.
+///
+/// # Arguments
+///
+/// * `a` - The symmetric matrix to decompose.
+///
+/// # Returns
+///
+/// The eigenvalues sorted ascending and the matrix `V` of the eigenvectors, a rotation, such
+/// that `A = V * diag(eigenvalues) * V^T`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{Mat3, Vec3};
+/// use kornia_linalg::eigen::eigh3;
+///
+/// let a = Mat3::from_cols(
+///     Vec3::new(2.0, 1.0, 0.0),
+///     Vec3::new(1.0, 2.0, 0.0),
+///     Vec3::new(0.0, 0.0, 5.0),
+/// );
+/// let eigh = eigh3(&a);
+/// assert!(eigh.eigenvalues().abs_diff_eq(Vec3::new(1.0, 3.0, 5.0), 1e-5));
+/// ```
+pub fn eigh3(a: &Mat3) -> Eigh3Result {
+    let (eigenvalues, eigenvectors) = eigh3_f32::eigh3(a);
+    Eigh3Result {
+        eigenvalues,
+        eigenvectors,
+    }
+}
+
+#[derive(Debug)]
+/// The eigendecomposition of a symmetric 3x3 matrix in double precision, see [`Eigh3Result`].
+pub struct Eigh3Result64 {
+    /// The eigenvalues, sorted ascending.
+    eigenvalues: DVec3,
+
+    /// The matrix of the eigenvectors, one per column.
+    eigenvectors: DMat3,
+}
+
+impl Eigh3Result64 {
+    /// Get the eigenvalues, sorted ascending.
+    #[inline]
+    pub fn eigenvalues(&self) -> &DVec3 {
+        &self.eigenvalues
+    }
+
+    /// Get the matrix of the eigenvectors, the column `i` matching the eigenvalue `i`.
+    #[inline]
+    pub fn eigenvectors(&self) -> &DMat3 {
+        &self.eigenvectors
+    }
+}
+
+/// Computes the eigendecomposition of a symmetric 3x3 matrix in double precision.
+///
+/// The same algorithm and conventions as [`eigh3`], with more sweeps to reach the f64
+/// precision.
+///
+/// # Arguments
+///
+/// * `a` - The symmetric matrix to decompose.
+///
+/// # Returns
+///
+/// The eigenvalues sorted ascending and the matrix `V` of the eigenvectors, a rotation, such
+/// that `A = V * diag(eigenvalues) * V^T`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat3, DVec3};
+/// use kornia_linalg::eigen::eigh3_f64;
+///
+/// let a = DMat3::from_cols(
+///     DVec3::new(4.0, 0.0, 0.0),
+///     DVec3::new(0.0, 1.0, 0.0),
+///     DVec3::new(0.0, 0.0, -2.0),
+/// );
+/// let eigh = eigh3_f64(&a);
+/// assert_eq!(*eigh.eigenvalues(), DVec3::new(-2.0, 1.0, 4.0));
+/// assert_eq!(eigh.eigenvectors().x_axis.z.abs(), 1.0);
+/// ```
+pub fn eigh3_f64(a: &DMat3) -> Eigh3Result64 {
+    let (eigenvalues, eigenvectors) = eigh3_f64::eigh3(a);
+    Eigh3Result64 {
+        eigenvalues,
+        eigenvectors,
+    }
+}
+
+#[derive(Debug)]
+/// The eigendecomposition of a symmetric 2x2 matrix, see [`Eigh3Result`].
+pub struct Eigh2Result {
+    /// The eigenvalues, sorted ascending.
+    eigenvalues: Vec2,
+
+    /// The matrix of the eigenvectors, one per column.
+    eigenvectors: Mat2,
+}
+
+impl Eigh2Result {
+    /// Get the eigenvalues, sorted ascending.
+    #[inline]
+    pub fn eigenvalues(&self) -> &Vec2 {
+        &self.eigenvalues
+    }
+
+    /// Get the matrix of the eigenvectors, the column `i` matching the eigenvalue `i`.
+    #[inline]
+    pub fn eigenvectors(&self) -> &Mat2 {
+        &self.eigenvectors
+    }
+}
+
+/// Computes the eigendecomposition of a symmetric 2x2 matrix in closed form.
+///
+/// The eigenvalues of `[[a, b], [b, c]]` are `(a + c) / 2 +- hypot((a - c) / 2, b)`, the one
+/// of smaller magnitude being computed from the determinant for accuracy, and the
+/// eigenvector of the largest one is at the angle `atan2(2 * b, a - c) / 2`, with no
+/// iteration. This is the analysis of a structure tensor, whose eigenvalues score corners
+/// and whose eigenvectors give the orientation of the edges. Only the symmetric part of `a`
+/// is decomposed.
+///
+/// # Arguments
+///
+/// * `a` - The symmetric matrix to decompose.
+///
+/// # Returns
+///
+/// The eigenvalues sorted ascending and the matrix `V` of the eigenvectors, a rotation, such
+/// that `A = V * diag(eigenvalues) * V^T`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{Mat2, Vec2};
+/// use kornia_linalg::eigen::eigh2;
+///
+/// let a = Mat2::from_cols(Vec2::new(2.0, 1.0), Vec2::new(1.0, 2.0));
+/// let eigh = eigh2(&a);
+/// assert!(eigh.eigenvalues().abs_diff_eq(Vec2::new(1.0, 3.0), 1e-6));
+/// ```
+pub fn eigh2(a: &Mat2) -> Eigh2Result {
+    let (eigenvalues, eigenvectors) = eigh2_f32::eigh2(a);
+    Eigh2Result {
+        eigenvalues,
+        eigenvectors,
+    }
+}
+
+#[derive(Debug)]
+/// The eigendecomposition of a symmetric 2x2 matrix in double precision, see
+/// [`Eigh2Result`].
+pub struct Eigh2Result64 {
+    /// The eigenvalues, sorted ascending.
+    eigenvalues: DVec2,
+
+    /// The matrix of the eigenvectors, one per column.
+    eigenvectors: DMat2,
+}
+
+impl Eigh2Result64 {
+    /// Get the eigenvalues, sorted ascending.
+    #[inline]
+    pub fn eigenvalues(&self) -> &DVec2 {
+        &self.eigenvalues
+    }
+
+    /// Get the matrix of the eigenvectors, the column `i` matching the eigenvalue `i`.
+    #[inline]
+    pub fn eigenvectors(&self) -> &DMat2 {
+        &self.eigenvectors
+    }
+}
+
+/// Computes the eigendecomposition of a symmetric 2x2 matrix in closed form in double
+/// precision.
+///
+/// The same algorithm and conventions as [`eigh2`].
+///
+/// # Arguments
+///
+/// * `a` - The symmetric matrix to decompose.
+///
+/// # Returns
+///
+/// The eigenvalues sorted ascending and the matrix `V` of the eigenvectors, a rotation, such
+/// that `A = V * diag(eigenvalues) * V^T`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat2, DVec2};
+/// use kornia_linalg::eigen::eigh2_f64;
+///
+/// let a = DMat2::from_diagonal(DVec2::new(4.0, -2.0));
+/// let eigh = eigh2_f64(&a);
+/// assert_eq!(*eigh.eigenvalues(), DVec2::new(-2.0, 4.0));
+/// assert_eq!(eigh.eigenvectors().y_axis, DVec2::new(1.0, 0.0));
+/// ```
+pub fn eigh2_f64(a: &DMat2) -> Eigh2Result64 {
+    let (eigenvalues, eigenvectors) = eigh2_f64::eigh2(a);
+    Eigh2Result64 {
+        eigenvalues,
+        eigenvectors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::svd3_f64;
+    use crate::test_utils::generator;
+
+    fn random_symmetric(next: &mut impl FnMut() -> f64) -> DMat3 {
+        let m = DMat3::from_cols_array(&std::array::from_fn(|_| next()));
+        (m + m.transpose()) * 0.5
+    }
+
+    /// Checks that the decomposition reconstructs `a` with a rotation of eigenvectors.
+    fn check_eigh3_f64(a: &DMat3, epsilon: f64) {
+        let eigh = eigh3_f64(a);
+        let (values, vectors) = (*eigh.eigenvalues(), *eigh.eigenvectors());
+        let reconstructed = vectors
+            .mul_mat3(&DMat3::from_diagonal(values))
+            .mul_mat3(&vectors.transpose());
+        assert!(
+            reconstructed.abs_diff_eq(*a, epsilon),
+            "{reconstructed:?} != {a:?}"
+        );
+        assert!(vectors
+            .transpose()
+            .mul_mat3(&vectors)
+            .abs_diff_eq(DMat3::IDENTITY, 1e-12));
+        assert!((vectors.determinant() - 1.0).abs() < 1e-12);
+        assert!(values.x <= values.y && values.y <= values.z);
+    }
+
+    #[test]
+    fn test_eigh3_f64_matches_svd3() {
+        let mut next = generator(7);
+        for _ in 0..1000 {
+            let a = random_symmetric(&mut next);
+            check_eigh3_f64(&a, 1e-12);
+
+            // the singular values of a symmetric matrix are the magnitudes of its eigenvalues
+            let mut magnitudes = eigh3_f64(&a).eigenvalues().abs().to_array();
+            magnitudes.sort_by(|x, y| y.total_cmp(x));
+            let s = svd3_f64(&a).s().to_cols_array_2d();
+            for (i, magnitude) in magnitudes.iter().enumerate() {
+                assert!((s[i][i].abs() - magnitude).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_eigh3_matches_svd3() {
+        let mut next = generator(11);
+        for _ in 0..1000 {
+            // positive semi-definite, so that the eigenvalues are the singular values
+            let m = DMat3::from_cols_array(&std::array::from_fn(|_| next()));
+            let a = m.transpose().mul_mat3(&m).as_mat3();
+
+            let eigh = eigh3(&a);
+            let (values, vectors) = (*eigh.eigenvalues(), *eigh.eigenvectors());
+            let reconstructed = vectors
+                .mul_mat3(&Mat3::from_diagonal(values))
+                .mul_mat3(&vectors.transpose());
+            assert!(reconstructed.abs_diff_eq(a, 1e-5));
+            assert!(vectors
+                .transpose()
+                .mul_mat3(&vectors)
+                .abs_diff_eq(Mat3::IDENTITY, 1e-5));
+
+            // the f32 SVD approximates its rotations, so compare to the f64 one
+            let s = svd3_f64(&a.as_dmat3()).s().to_cols_array_2d();
+            let expected = Vec3::new(s[2][2].abs() as f32, s[1][1] as f32, s[0][0] as f32);
+            assert!(values.abs_diff_eq(expected, 1e-5 * expected.z));
+        }
+    }
+
+    #[test]
+    fn test_eigh3_f64_repeated_eigenvalues() {
+        let rotation = DMat3::from_euler(glam::EulerRot::XYZ, 0.3, -1.1, 0.7);
+        for diagonal in [
+            DVec3::splat(2.0),
+            DVec3::new(1.0, 1.0, 3.0),
+            DVec3::new(-1.0, 4.0, 4.0),
+        ] {
+            let a = rotation
+                .mul_mat3(&DMat3::from_diagonal(diagonal))
+                .mul_mat3(&rotation.transpose());
+            check_eigh3_f64(&a, 1e-12);
+            assert!(eigh3_f64(&a).eigenvalues().abs_diff_eq(diagonal, 1e-12));
+        }
+    }
+
+    #[test]
+    fn test_eigh3_f64_near_zero() {
+        let mut next = generator(3);
+        let tiny = random_symmetric(&mut next) * 1e-30;
+        for a in [DMat3::ZERO, tiny] {
+            check_eigh3_f64(&a, 1e-42);
+        }
+        let eigh = eigh3_f64(&DMat3::ZERO);
+        assert_eq!(*eigh.eigenvalues(), DVec3::ZERO);
+        assert_eq!(*eigh.eigenvectors(), DMat3::IDENTITY);
+    }
+
+    /// Checks that the 2x2 decomposition reconstructs `a` with a rotation of eigenvectors.
+    fn check_eigh2_f64(a: &DMat2, epsilon: f64) -> DVec2 {
+        let eigh = eigh2_f64(a);
+        let (values, vectors) = (*eigh.eigenvalues(), *eigh.eigenvectors());
+        let reconstructed = vectors
+            .mul_mat2(&DMat2::from_diagonal(values))
+            .mul_mat2(&vectors.transpose());
+        assert!(
+            reconstructed.abs_diff_eq(*a, epsilon),
+            "{reconstructed:?} != {a:?}"
+        );
+        assert!(vectors
+            .transpose()
+            .mul_mat2(&vectors)
+            .abs_diff_eq(DMat2::IDENTITY, 1e-12));
+        assert!((vectors.determinant() - 1.0).abs() < 1e-12);
+        assert!(values.x <= values.y);
+        values
+    }
+
+    #[test]
+    fn test_eigh2_f64_random() {
+        let mut next = generator(13);
+        for _ in 0..1000 {
+            let (a, b, c) = (next(), next(), next());
+            let m = DMat2::from_cols(DVec2::new(a, b), DVec2::new(b, c));
+            let values = check_eigh2_f64(&m, 1e-12);
+            assert!((values.x * values.y - m.determinant()).abs() < 1e-12);
+
+            // the single precision decomposition agrees
+            let eigh = eigh2(&m.as_mat2());
+            assert!(eigh.eigenvalues().as_dvec2().abs_diff_eq(values, 1e-6));
+            let (v, v32) = (
+                *eigh2_f64(&m).eigenvectors(),
+                eigh.eigenvectors().as_dmat2(),
+            );
+            let reconstructed = v32
+                .mul_mat2(&DMat2::from_diagonal(eigh.eigenvalues().as_dvec2()))
+                .mul_mat2(&v32.transpose());
+            assert!(reconstructed.abs_diff_eq(m, 1e-5));
+            assert!(values.y - values.x < 1e-3 || v32.abs_diff_eq(v, 1e-3));
+        }
+    }
+
+    #[test]
+    fn test_eigh2_f64_rotation_shear() {
+        // the symmetric part of a rotation is cos(angle) times the identity
+        let rotation = DMat2::from_angle(0.7);
+        let values = check_eigh2_f64(&((rotation + rotation.transpose()) * 0.5), 1e-15);
+        assert!(values.abs_diff_eq(DVec2::splat(0.7f64.cos()), 1e-15));
+        assert!(eigh2_f64(&rotation)
+            .eigenvalues()
+            .abs_diff_eq(DVec2::splat(0.7f64.cos()), 1e-15));
+
+        // the symmetric part of the shear [[1, k], [0, 1]] has the eigenvalues 1 +- k / 2
+        let k = 1.5;
+        let shear = DMat2::from_cols(DVec2::new(1.0, 0.0), DVec2::new(k, 1.0));
+        let values = check_eigh2_f64(&((shear + shear.transpose()) * 0.5), 1e-15);
+        assert!(values.abs_diff_eq(DVec2::new(1.0 - k / 2.0, 1.0 + k / 2.0), 1e-15));
+    }
+
+    #[test]
+    fn test_eigh2_f64_degenerate() {
+        // the structure tensor of a straight edge, of a zero row and column, and zero
+        let edge = DMat2::from_diagonal(DVec2::new(0.0, 3.0));
+        let values = check_eigh2_f64(&edge, 1e-15);
+        assert_eq!(values, DVec2::new(0.0, 3.0));
+        assert_eq!(eigh2_f64(&edge).eigenvectors().y_axis.y.abs(), 1.0);
+        let values = check_eigh2_f64(&DMat2::ZERO, 0.0);
+        assert_eq!(values, DVec2::ZERO);
+
+        // a nearly singular tensor keeps the relative accuracy of its smallest value
+        let a = DMat2::from_cols(DVec2::new(1.0, 1.0), DVec2::new(1.0, 1.0 + 1e-12));
+        let values = check_eigh2_f64(&a, 1e-15);
+        let d = a.y_axis.y - 1.0;
+        assert!((values.x / (0.5 * d) - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,34 @@
+use thiserror::Error;
+
+/// An error type for the linear algebra operations.
+#[derive(Error, Debug, PartialEq)]
+pub enum LinalgError {
+    /// The matrix is singular within the tolerance.
+    #[error("Singular matrix: pivot {pivot} below the tolerance {tolerance}")]
+    SingularMatrix {
+        /// The magnitude of the pivot.
+        pivot: f64,
+        /// The tolerance on the pivots.
+        tolerance: f64,
+    },
+
+    /// The matrix or the right hand side has a NaN or infinite entry.
+    #[error("Non-finite entry in the linear system")]
+    NonFinite,
+
+    /// The source and destination point sets have different lengths.
+    #[error("Length mismatch: src_points has {src} points but dst_points has {dst}")]
+    LengthMismatch {
+        /// The number of source points.
+        src: usize,
+        /// The number of destination points.
+        dst: usize,
+    },
+
+    /// The bottom row of a homogeneous transform is not `[0, 0, 0, 1]`.
+    #[error("Invalid homogeneous transform: bottom row {bottom_row:?} is not [0, 0, 0, 1]")]
+    InvalidBottomRow {
+        /// The bottom row of the matrix.
+        bottom_row: [f64; 4],
+    },
+}
@@ -1,5 +1,31 @@
 #![deny(missing_docs)]
 #![doc = env!("CARGO_PKG_DESCRIPTION")]
 
-/// Module to calculate SVD of a 3x3 matrix
+pub use glam::{DMat2, DMat3, DMat4, Mat2, Mat3, Mat4};
+
+/// Module to calculate the Cholesky factorization of a symmetric positive definite 3x3 matrix
+pub mod cholesky;
+
+/// Module to calculate the eigendecomposition of a symmetric 2x2 or 3x3 matrix
+pub mod eigen;
+
+/// Error types of the linear algebra operations
+pub mod error;
+
+/// Module to calculate SVD of a 2x2, 3x3 or 4x4 matrix
 pub mod linalg;
+
+/// Module to calculate the Moore-Penrose pseudo-inverse of a 3x3 matrix
+pub mod pinv;
+
+/// Module to calculate the QR decomposition of a 3x3 matrix
+pub mod qr;
+
+/// Module to decompose the homogeneous rigid transforms of 4x4 matrices
+pub mod rigid;
+
+/// Module to solve the linear systems of 3x3 and larger square matrices
+pub mod solve;
+
+#[cfg(test)]
+mod test_utils;
@@ -1,12 +1,21 @@
 // Reference: https://github.com/wi-re/tbtSVD/blob/master/source/SVD.h
-use glam::{Mat3, Quat};
-use std::ops::{Index, IndexMut};
+use glam::{DMat2, DMat3, DMat4, Mat2, Mat3, Mat4};
 const GAMMA: f32 = 5.828_427_3;
 const CSTAR: f32 = 0.923_879_5;
 const SSTAR: f32 = 0.382_683_43;
 const SVD3_EPSILON: f32 = 1e-6;
 const JACOBI_STEPS: u8 = 6;
 const RSQRT1_STEPS: u8 = 6;
+const GAMMA_F64: f64 = 5.828_427_124_746_19;
+const CSTAR_F64: f64 = 0.923_879_532_511_286_7;
+const SSTAR_F64: f64 = 0.382_683_432_365_089_8;
+const SVD3_EPSILON_F64: f64 = 1e-15;
+const JACOBI_STEPS_F64: u8 = 12;
+#[cfg(feature = "rayon")]
+const SVD3_BATCH_CHUNK: usize = 256;
+const SVD4_SWEEPS: u8 = 8;
+const SVD4_SWEEPS_F64: u8 = 16;
+const PAIRS4: [(usize, usize); 6] = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)];
 
 /// Standard CPU division.
 fn fdiv(x: f32, y: f32) -> f32 {
@@ -40,81 +49,551 @@ fn accurate_sqrt(x: f32) -> f32 {
     fdiv(1.0, rsqrt1(x))
 }
 
-/// Helper function used to swap X with Y and Y with  X if c == true
-fn cond_swap(c: bool, x: &mut f32, y: &mut f32) {
-    let z = *x;
-    if c {
-        *x = *y;
-        *y = z;
-    }
+/// Exact reciprocal square root, the f64 decomposition has no need for the fast approximation.
+fn rsqrt_f64(x: f64) -> f64 {
+    1.0 / x.sqrt()
 }
 
-// Helper function to swap X and Y and swap Y with -X if c is true
-fn cond_neg_swap(c: bool, x: &mut f32, y: &mut f32) {
-    let z = -(*x);
-    if c {
-        *x = *y;
-        *y = z;
-    }
+/// Exact square root.
+fn accurate_sqrt_f64(x: f64) -> f64 {
+    x.sqrt()
+}
+
+/// Generates the steps of the decomposition for a precision, the constants and the square roots
+/// being taken from the scope of the invocation.
+macro_rules! svd3_kernel {
+    ($t:ty, $mat3:ident, $quat:ident) => {
+        /// Helper function used to swap X with Y and Y with  X if c == true
+        fn cond_swap(c: bool, x: &mut $t, y: &mut $t) {
+            let z = *x;
+            if c {
+                *x = *y;
+                *y = z;
+            }
+        }
+
+        // Helper function to swap X and Y and swap Y with -X if c is true
+        fn cond_neg_swap(c: bool, x: &mut $t, y: &mut $t) {
+            let z = -(*x);
+            if c {
+                *x = *y;
+                *y = z;
+            }
+        }
+
+        #[derive(Debug, Clone)]
+        /// A simple symmetric 3x3 Matrix class (contains no storage for (0, 1) (0, 2) and (1, 2)
+        struct Symmetric3x3 {
+            /// The element at row 0, column 0 of the matrix, typically the first diagonal element.
+            m_00: $t,
+
+            /// The element at row 1, column 0 of the matrix. Since this is a symmetric matrix, it is equivalent to `m_01`.
+            m_10: $t,
+
+            /// The element at row 1, column 1 of the matrix, the second diagonal element.
+            m_11: $t,
+
+            /// The element at row 2, column 0 of the matrix. Since this is a symmetric matrix, it is equivalent to `m_02`.
+            m_20: $t,
+
+            /// The element at row 2, column 1 of the matrix. Since this is a symmetric matrix, it is equivalent to `m_12`.
+            m_21: $t,
+
+            /// The element at row 2, column 2 of the matrix, the third diagonal element.
+            m_22: $t,
+        }
+
+        impl Symmetric3x3 {
+            /// Constructor from a regular Mat3x3 (assuming Mat3x3 exists)
+            fn from_mat3x3(mat: &$mat3) -> Self {
+                Symmetric3x3 {
+                    m_00: mat.x_axis.x,
+                    m_10: mat.y_axis.x,
+                    m_11: mat.y_axis.y,
+                    m_20: mat.x_axis.z,
+                    m_21: mat.y_axis.z,
+                    m_22: mat.z_axis.z,
+                }
+            }
+        }
+
+        #[derive(Debug)]
+        /// Helper struct to store 2 floats to avoid OUT parameters on functions
+        struct Givens {
+            /// The cosine of the angle in the Givens rotation.
+            ch: $t,
+
+            /// The sine of the angle in the Givens rotation.
+            sh: $t,
+        }
+
+        #[derive(Debug)]
+        /// Helper struct to store 2 Matrices to avoid OUT parameters on functions
+        struct QR3 {
+            /// The orthogonal matrix Q from the QR decomposition.
+            q: $mat3,
+
+            /// The upper triangular matrix R from the QR decomposition.
+            r: $mat3,
+        }
+
+        /// Calculates the squared norm of the vector [x y z] using a standard scalar product d = x * x + y * y + z * z
+        fn dist2(x: $t, y: $t, z: $t) -> $t {
+            x * x + y * y + z * z
+        }
+
+        /// For an explanation of the math see http://pages.cs.wisc.edu/~sifakis/papers/SVD_TR1690.pdf
+        /// Computing the Singular Value Decomposition of 3 x 3 matrices with minimal branching and elementary floating point operations
+        /// See Algorithm 2 in reference. Given a matrix A this function returns the givens quaternion (x and w component, y and z are 0)
+        fn approximate_givens_quaternion(a: &Symmetric3x3) -> Givens {
+            let g = Givens {
+                ch: 2.0 * (a.m_00 - a.m_11),
+                sh: a.m_10,
+            };
+            let ch2 = g.ch * g.ch;
+            let sh2 = g.sh * g.sh;
+            let mut b = GAMMA * sh2 < ch2;
+            let w = rsqrt(ch2 + sh2);
+
+            if w.is_nan() {
+                // Checking for NaN
+                b = false;
+            }
+
+            Givens {
+                ch: if b { w * g.ch } else { CSTAR },
+                sh: if b { w * g.sh } else { SSTAR },
+            }
+        }
+
+        #[derive(Debug)]
+        /// A wrapper around the `glam` quaternion type that allows dynamic indexing into its components.
+        ///
+        /// This struct provides custom indexing behavior for quaternion components (`x`, `y`, `z`, and `w`),
+        /// enabling access and mutation using an index (e.g., `q[0]`, `q[1]`, etc.). It implements both
+        /// the `Index` and `IndexMut` traits to allow for immutable and mutable access to the quaternion's components.
+        struct IndexedQuat($quat);
+
+        impl IndexedQuat {
+            fn new(q: $quat) -> Self {
+                IndexedQuat(q)
+            }
+
+            fn to_quat(&self) -> $quat {
+                self.0
+            }
+        }
+
+        impl Index<usize> for IndexedQuat {
+            type Output = $t;
+
+            fn index(&self, index: usize) -> &Self::Output {
+                match index {
+                    0 => &self.0.x,
+                    1 => &self.0.y,
+                    2 => &self.0.z,
+                    3 => &self.0.w,
+                    _ => panic!("Index out of bounds for Quaternion: {}", index),
+                }
+            }
+        }
+
+        impl IndexMut<usize> for IndexedQuat {
+            fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+                match index {
+                    0 => &mut self.0.x,
+                    1 => &mut self.0.y,
+                    2 => &mut self.0.z,
+                    3 => &mut self.0.w,
+                    _ => panic!("Index out of bounds for Quaternion: {}", index),
+                }
+            }
+        }
+
+        /// Function used to apply a givens rotation S. Calculates the weights and updates the quaternion to contain the cumultative rotation
+        fn jacobi_conjugation(
+            x: usize,
+            y: usize,
+            z: usize,
+            s: &mut Symmetric3x3,
+            q: &mut IndexedQuat,
+        ) {
+            // Compute the Givens rotation (approximated)
+            let mut g = approximate_givens_quaternion(s);
+            // Scale and calculate intermediate values
+            let ch2 = g.ch * g.ch;
+            let sh2 = g.sh * g.sh;
+            let scale = 1.0 / (ch2 + sh2);
+            let a = (ch2 - sh2) * scale;
+            let b = 2.0 * g.sh * g.ch * scale;
+
+            // Create a copy of the matrix to avoid modifying the original during calculations
+            let mut _s = s.clone();
+
+            // Perform conjugation: S = Q'*S*Q
+            s.m_00 = a * (a * _s.m_00 + b * _s.m_10) + b * (a * _s.m_10 + b * _s.m_11);
+            s.m_10 = a * (-b * _s.m_00 + a * _s.m_10) + b * (-b * _s.m_10 + a * _s.m_11);
+            s.m_11 = -b * (-b * _s.m_00 + a * _s.m_10) + a * (-b * _s.m_10 + a * _s.m_11);
+            s.m_20 = a * _s.m_20 + b * _s.m_21;
+            s.m_21 = -b * _s.m_20 + a * _s.m_21;
+            s.m_22 = _s.m_22;
+
+            // Update cumulative rotation qV
+            let mut tmp = [0.0, 0.0, 0.0];
+            tmp[0] = q[0] * g.sh;
+            tmp[1] = q[1] * g.sh;
+            tmp[2] = q[2] * g.sh;
+            g.sh *= q[3];
+
+            // (x, y, z) corresponds to (0,1,2), (1,2,0), (2,0,1) for (p, q) = (0,1), (1,2), (0,2)
+            q[z] = q[z] * g.ch + g.sh;
+            q[3] = q[3] * g.ch - tmp[z]; // w
+            q[x] = q[x] * g.ch + tmp[y];
+            q[y] = q[y] * g.ch - tmp[x];
+
+            // Re-arrange matrix for next iteration
+            _s.m_00 = s.m_11;
+            _s.m_10 = s.m_21;
+            _s.m_11 = s.m_22;
+            _s.m_20 = s.m_10;
+            _s.m_21 = s.m_20;
+            _s.m_22 = s.m_00;
+
+            s.m_00 = _s.m_00;
+            s.m_10 = _s.m_10;
+            s.m_11 = _s.m_11;
+            s.m_20 = _s.m_20;
+            s.m_21 = _s.m_21;
+            s.m_22 = _s.m_22;
+        }
+
+        /// Function used to contain the givens permutations and the loop of the jacobi steps controlled by JACOBI_STEPS
+        /// Returns the quaternion q containing the cumultative result used to reconstruct S
+        fn jacobi_eigenanalysis(mut s: Symmetric3x3) -> $mat3 {
+            let mut q = IndexedQuat::new($quat::from_xyzw(0.0, 0.0, 0.0, 1.0));
+            for _i in 0..JACOBI_STEPS {
+                jacobi_conjugation(0, 1, 2, &mut s, &mut q);
+                jacobi_conjugation(1, 2, 0, &mut s, &mut q);
+                jacobi_conjugation(2, 0, 1, &mut s, &mut q);
+            }
+
+            $mat3::from_quat(q.to_quat())
+        }
+
+        /// Implementation of Algorithm 3
+        fn sort_singular_values(b: &mut $mat3, v: &mut $mat3) {
+            let mut rho1 = dist2(b.x_axis.x, b.x_axis.y, b.x_axis.z);
+            let mut rho2 = dist2(b.y_axis.x, b.y_axis.y, b.y_axis.z);
+            let mut rho3 = dist2(b.z_axis.x, b.z_axis.y, b.z_axis.z);
+
+            let mut c = rho1 < rho2;
+            cond_neg_swap(c, &mut b.x_axis.x, &mut b.y_axis.x);
+            cond_neg_swap(c, &mut v.x_axis.x, &mut v.y_axis.x);
+            cond_neg_swap(c, &mut b.x_axis.y, &mut b.y_axis.y);
+            cond_neg_swap(c, &mut v.x_axis.y, &mut v.y_axis.y);
+            cond_neg_swap(c, &mut b.x_axis.z, &mut b.y_axis.z);
+            cond_neg_swap(c, &mut v.x_axis.z, &mut v.y_axis.z);
+            cond_swap(c, &mut rho1, &mut rho2);
+
+            c = rho1 < rho3;
+            cond_neg_swap(c, &mut b.x_axis.x, &mut b.z_axis.x);
+            cond_neg_swap(c, &mut v.x_axis.x, &mut v.z_axis.x);
+            cond_neg_swap(c, &mut b.x_axis.y, &mut b.z_axis.y);
+            cond_neg_swap(c, &mut v.x_axis.y, &mut v.z_axis.y);
+            cond_neg_swap(c, &mut b.x_axis.z, &mut b.z_axis.z);
+            cond_neg_swap(c, &mut v.x_axis.z, &mut v.z_axis.z);
+            cond_swap(c, &mut rho1, &mut rho3);
+
+            c = rho2 < rho3;
+            cond_neg_swap(c, &mut b.y_axis.x, &mut b.z_axis.x);
+            cond_neg_swap(c, &mut v.y_axis.x, &mut v.z_axis.x);
+            cond_neg_swap(c, &mut b.y_axis.y, &mut b.z_axis.y);
+            cond_neg_swap(c, &mut v.y_axis.y, &mut v.z_axis.y);
+            cond_neg_swap(c, &mut b.y_axis.z, &mut b.z_axis.z);
+            cond_neg_swap(c, &mut v.y_axis.z, &mut v.z_axis.z);
+        }
+
+        /// Implementation of Algorithm 4
+        fn qr_givens_quaternion(a1: $t, a2: $t) -> Givens {
+            let epsilon = SVD3_EPSILON;
+            let rho = accurate_sqrt(a1 * a1 + a2 * a2);
+
+            let mut g = Givens {
+                ch: a1.abs() + <$t>::max(rho, epsilon),
+                sh: if rho > epsilon { a2 } else { 0.0 },
+            };
+
+            let b = a1 < 0.0;
+            cond_swap(b, &mut g.sh, &mut g.ch);
+
+            let w = rsqrt(g.ch * g.ch + g.sh * g.sh);
+            g.ch *= w;
+            g.sh *= w;
+            g
+        }
+
+        /// Implements a QR decomposition of a Matrix
+        fn qr_decomposition(b_mat: &mut $mat3) -> QR3 {
+            let mut q = $mat3::ZERO;
+            let mut r = $mat3::ZERO;
+
+            // First Givens rotation (ch, 0, 0, sh)
+            let g1 = qr_givens_quaternion(b_mat.x_axis.x, b_mat.x_axis.y);
+            let mut a = -2.0 * g1.sh * g1.sh + 1.0;
+            let mut b = 2.0 * g1.ch * g1.sh;
+
+            // Apply B = Q' * B
+            r.x_axis.x = a * b_mat.x_axis.x + b * b_mat.x_axis.y;
+            r.y_axis.x = a * b_mat.y_axis.x + b * b_mat.y_axis.y;
+            r.z_axis.x = a * b_mat.z_axis.x + b * b_mat.z_axis.y;
+            r.x_axis.y = -b * b_mat.x_axis.x + a * b_mat.x_axis.y;
+            r.y_axis.y = -b * b_mat.y_axis.x + a * b_mat.y_axis.y;
+            r.z_axis.y = -b * b_mat.z_axis.x + a * b_mat.z_axis.y;
+            r.x_axis.z = b_mat.x_axis.z;
+            r.y_axis.z = b_mat.y_axis.z;
+            r.z_axis.z = b_mat.z_axis.z;
+
+            // Second Givens rotation (ch, 0, -sh, 0)
+            let g2 = qr_givens_quaternion(r.x_axis.x, r.x_axis.z);
+            a = -2.0 * g2.sh * g2.sh + 1.0;
+            b = 2.0 * g2.ch * g2.sh;
+
+            // Apply B = Q' * B
+            b_mat.x_axis.x = a * r.x_axis.x + b * r.x_axis.z;
+            b_mat.y_axis.x = a * r.y_axis.x + b * r.y_axis.z;
+            b_mat.z_axis.x = a * r.z_axis.x + b * r.z_axis.z;
+            b_mat.x_axis.y = r.x_axis.y;
+            b_mat.y_axis.y = r.y_axis.y;
+            b_mat.z_axis.y = r.z_axis.y;
+            b_mat.x_axis.z = -b * r.x_axis.x + a * r.x_axis.z;
+            b_mat.y_axis.z = -b * r.y_axis.x + a * r.y_axis.z;
+            b_mat.z_axis.z = -b * r.z_axis.x + a * r.z_axis.z;
+
+            // Third Givens rotation (ch, sh, 0, 0)
+            let g3 = qr_givens_quaternion(b_mat.y_axis.y, b_mat.y_axis.z);
+            a = -2.0 * g3.sh * g3.sh + 1.0;
+            b = 2.0 * g3.ch * g3.sh;
+
+            // R is now set to desired value
+            r.x_axis.x = b_mat.x_axis.x;
+            r.y_axis.x = b_mat.y_axis.x;
+            r.z_axis.x = b_mat.z_axis.x;
+            r.x_axis.y = a * b_mat.x_axis.y + b * b_mat.x_axis.z;
+            r.y_axis.y = a * b_mat.y_axis.y + b * b_mat.y_axis.z;
+            r.z_axis.y = a * b_mat.z_axis.y + b * b_mat.z_axis.z;
+            r.x_axis.z = -b * b_mat.x_axis.y + a * b_mat.x_axis.z;
+            r.y_axis.z = -b * b_mat.y_axis.y + a * b_mat.y_axis.z;
+            r.z_axis.z = -b * b_mat.z_axis.y + a * b_mat.z_axis.z;
+
+            // Construct the cumulative rotation Q = Q1 * Q2 * Q3, one row at a time
+            let sh12 = 2.0 * (g1.sh * g1.sh - 0.5);
+            let sh22 = 2.0 * (g2.sh * g2.sh - 0.5);
+            let sh32 = 2.0 * (g3.sh * g3.sh - 0.5);
+
+            q.x_axis.x = sh12 * sh22;
+            q.y_axis.x = 4.0 * g2.ch * g3.ch * sh12 * g2.sh * g3.sh + 2.0 * g1.ch * g1.sh * sh32;
+            q.z_axis.x = 4.0 * g1.ch * g3.ch * g1.sh * g3.sh - 2.0 * g2.ch * sh12 * g2.sh * sh32;
+
+            q.x_axis.y = -2.0 * g1.ch * g1.sh * sh22;
+            q.y_axis.y = -8.0 * g1.ch * g2.ch * g3.ch * g1.sh * g2.sh * g3.sh + sh12 * sh32;
+            q.z_axis.y = -2.0 * g3.ch * g3.sh
+                + 4.0 * g1.sh * (g3.ch * g1.sh * g3.sh + g1.ch * g2.ch * g2.sh * sh32);
+
+            q.x_axis.z = 2.0 * g2.ch * g2.sh;
+            q.y_axis.z = -2.0 * g3.ch * sh22 * g3.sh;
+            q.z_axis.z = sh22 * sh32;
+
+            QR3 { q, r }
+        }
+
+        /// Returns the U, S and V matrices of the decomposition of A.
+        pub(super) fn svd3(a: &$mat3) -> ($mat3, $mat3, $mat3) {
+            // Compute the eigenvectors of A^T * A, which is V in SVD (Singular Vectors)
+            let mut v =
+                jacobi_eigenanalysis(Symmetric3x3::from_mat3x3(&(a.transpose().mul_mat3(a))));
+            // Compute B = A * V
+            let mut b = a.mul_mat3(&v);
+
+            // Sort the singular values, permuting the columns of V along with B
+            sort_singular_values(&mut b, &mut v);
+
+            // Perform QR decomposition on B to get Q and R
+            let qr = qr_decomposition(&mut b);
+
+            // Q is U and R is S
+            (qr.q, qr.r, v)
+        }
+    };
+}
+
+mod svd3_f32 {
+    use super::{accurate_sqrt, rsqrt, CSTAR, GAMMA, JACOBI_STEPS, SSTAR, SVD3_EPSILON};
+    use glam::{Mat3, Quat};
+    use std::ops::{Index, IndexMut};
+
+    svd3_kernel!(f32, Mat3, Quat);
 }
 
-#[derive(Debug, Clone)]
-/// A simple symmetric 3x3 Matrix class (contains no storage for (0, 1) (0, 2) and (1, 2)
-struct Symmetric3x3 {
-    /// The element at row 0, column 0 of the matrix, typically the first diagonal element.
-    m_00: f32,
+mod svd3_f64 {
+    use super::{
+        accurate_sqrt_f64 as accurate_sqrt, rsqrt_f64 as rsqrt, CSTAR_F64 as CSTAR,
+        GAMMA_F64 as GAMMA, JACOBI_STEPS_F64 as JACOBI_STEPS, SSTAR_F64 as SSTAR,
+        SVD3_EPSILON_F64 as SVD3_EPSILON,
+    };
+    use glam::{DMat3, DQuat};
+    use std::ops::{Index, IndexMut};
 
-    /// The element at row 1, column 0 of the matrix. Since this is a symmetric matrix, it is equivalent to `m_01`.
-    m_10: f32,
+    svd3_kernel!(f64, DMat3, DQuat);
+}
 
-    /// The element at row 1, column 1 of the matrix, the second diagonal element.
-    m_11: f32,
+/// Generates the closed form SVD of a 2x2 matrix for a precision.
+macro_rules! svd2_kernel {
+    ($t:ty, $mat2:ident, $vec2:ident) => {
+        /// Returns U, S and V such that A = U * S * V^T, U and V being rotations.
+        pub(super) fn svd2(m: &$mat2) -> ($mat2, $mat2, $mat2) {
+            let [[m00, m10], [m01, m11]] = m.to_cols_array_2d();
+
+            // A is the sum of the similarity [[e, -h], [h, e]] of scale q and of the
+            // reflection [[f, g], [g, -f]] of scale r, whose angles give U and V
+            let (e, f) = (0.5 * (m00 + m11), 0.5 * (m00 - m11));
+            let (g, h) = (0.5 * (m10 + m01), 0.5 * (m10 - m01));
+            let (q, r) = (e.hypot(h), f.hypot(g));
+            let (a1, a2) = (g.atan2(f), h.atan2(e));
+            let (theta, phi) = (0.5 * (a2 - a1), 0.5 * (a2 + a1));
+
+            // the smallest singular value from the determinant, avoiding the cancellation of
+            // q - r for a nearly singular matrix
+            let sx = q + r;
+            let det = m00 * m11 - m01 * m10;
+            let sy = if sx > 0.0 { det / sx } else { 0.0 };
+
+            (
+                $mat2::from_angle(phi),
+                $mat2::from_diagonal($vec2::new(sx, sy)),
+                $mat2::from_angle(-theta),
+            )
+        }
+    };
+}
 
-    /// The element at row 2, column 0 of the matrix. Since this is a symmetric matrix, it is equivalent to `m_02`.
-    m_20: f32,
+mod svd2_f32 {
+    use glam::{Mat2, Vec2};
 
-    /// The element at row 2, column 1 of the matrix. Since this is a symmetric matrix, it is equivalent to `m_12`.
-    m_21: f32,
+    svd2_kernel!(f32, Mat2, Vec2);
+}
+
+mod svd2_f64 {
+    use glam::{DMat2, DVec2};
 
-    /// The element at row 2, column 2 of the matrix, the third diagonal element.
-    m_22: f32,
+    svd2_kernel!(f64, DMat2, DVec2);
 }
 
-impl Symmetric3x3 {
-    /// Constructor from a regular Mat3x3 (assuming Mat3x3 exists)
-    fn from_mat3x3(mat: &Mat3) -> Self {
-        Symmetric3x3 {
-            m_00: mat.x_axis.x,
-            m_10: mat.y_axis.x,
-            m_11: mat.y_axis.y,
-            m_20: mat.x_axis.z,
-            m_21: mat.y_axis.z,
-            m_22: mat.z_axis.z,
+/// Generates the two-sided Jacobi SVD of a 4x4 matrix for a precision.
+macro_rules! svd4_kernel {
+    ($t:ty, $mat2:ident, $mat4:ident, $sweeps:expr) => {
+        /// Returns U, S and V such that A = U * S * V^T, U and V being rotations.
+        pub(super) fn svd4(m: &$mat4) -> ($mat4, $mat4, $mat4) {
+            // A stored row major, U and V column major
+            let mut a = m.transpose().to_cols_array_2d();
+            let mut u = $mat4::IDENTITY.to_cols_array_2d();
+            let mut v = u;
+
+            let norm2 = a.iter().flatten().map(|x| x * x).sum::<$t>();
+            for _ in 0..$sweeps {
+                let off2 = PAIRS4
+                    .iter()
+                    .map(|&(p, q)| a[p][q] * a[p][q] + a[q][p] * a[q][p])
+                    .sum::<$t>();
+                if off2 <= <$t>::EPSILON * <$t>::EPSILON * norm2 {
+                    break;
+                }
+
+                for (p, q) in PAIRS4 {
+                    if a[p][q] == 0.0 && a[q][p] == 0.0 {
+                        continue;
+                    }
+                    // the rotations diagonalizing the 2x2 block of the rows and columns p, q
+                    let block =
+                        $mat2::from_cols_array_2d(&[[a[p][p], a[q][p]], [a[p][q], a[q][q]]]);
+                    let (u2, s2, v2) = svd2(&block);
+                    let [[u00, u10], [u01, u11]] = u2.to_cols_array_2d();
+                    let [[v00, v10], [v01, v11]] = v2.to_cols_array_2d();
+
+                    // A = J_u^T * A * J_v, U = U * J_u and V = V * J_v
+                    let (ap, aq) = (a[p], a[q]);
+                    a[p] = [0, 1, 2, 3].map(|j| u00 * ap[j] + u10 * aq[j]);
+                    a[q] = [0, 1, 2, 3].map(|j| u01 * ap[j] + u11 * aq[j]);
+                    for row in a.iter_mut() {
+                        let (ap, aq) = (row[p], row[q]);
+                        row[p] = v00 * ap + v10 * aq;
+                        row[q] = v01 * ap + v11 * aq;
+                    }
+                    let (up, uq) = (u[p], u[q]);
+                    u[p] = [0, 1, 2, 3].map(|k| u00 * up[k] + u10 * uq[k]);
+                    u[q] = [0, 1, 2, 3].map(|k| u01 * up[k] + u11 * uq[k]);
+                    let (vp, vq) = (v[p], v[q]);
+                    v[p] = [0, 1, 2, 3].map(|k| v00 * vp[k] + v10 * vq[k]);
+                    v[q] = [0, 1, 2, 3].map(|k| v01 * vp[k] + v11 * vq[k]);
+
+                    a[p][q] = 0.0;
+                    a[q][p] = 0.0;
+                    a[p][p] = s2.x_axis.x;
+                    a[q][q] = s2.y_axis.y;
+                }
+            }
+
+            // sort the singular values by decreasing magnitude, permuting U and V along
+            let mut order = [0, 1, 2, 3];
+            order.sort_by(|i, j| a[*j][*j].abs().total_cmp(&a[*i][*i].abs()));
+            let mut s = order.map(|i| a[i][i]);
+            let mut u = order.map(|i| u[i]);
+            let mut v = order.map(|i| v[i]);
+
+            // the singular values made non-negative but the last one, flipping the columns
+            // of U in pairs, and U and V made rotations by flipping their last column
+            for i in 0..3 {
+                if s[i] < 0.0 {
+                    s[i] = -s[i];
+                    s[3] = -s[3];
+                    u[i] = u[i].map(|x| -x);
+                    u[3] = u[3].map(|x| -x);
+                }
+            }
+            for w in [&mut u, &mut v] {
+                if $mat4::from_cols_array_2d(w).determinant() < 0.0 {
+                    s[3] = -s[3];
+                    w[3] = w[3].map(|x| -x);
+                }
+            }
+
+            let mut sigma = $mat4::ZERO;
+            for (i, x) in s.iter().enumerate() {
+                sigma.col_mut(i)[i] = *x;
+            }
+            (
+                $mat4::from_cols_array_2d(&u),
+                sigma,
+                $mat4::from_cols_array_2d(&v),
+            )
         }
-    }
+    };
 }
 
-#[derive(Debug)]
-/// Helper struct to store 2 floats to avoid OUT parameters on functions
-struct Givens {
-    /// The cosine of the angle in the Givens rotation.
-    ch: f32,
+mod svd4_f32 {
+    use super::{svd2_f32::svd2, PAIRS4, SVD4_SWEEPS};
+    use glam::{Mat2, Mat4};
 
-    /// The sine of the angle in the Givens rotation.
-    sh: f32,
+    svd4_kernel!(f32, Mat2, Mat4, SVD4_SWEEPS);
 }
 
-#[derive(Debug)]
-/// Helper struct to store 2 Matrices to avoid OUT parameters on functions
-struct QR3 {
-    /// The orthogonal matrix Q from the QR decomposition.
-    q: Mat3,
+mod svd4_f64 {
+    use super::{svd2_f64::svd2, PAIRS4, SVD4_SWEEPS_F64};
+    use glam::{DMat2, DMat4};
 
-    /// The upper triangular matrix R from the QR decomposition.
-    r: Mat3,
+    svd4_kernel!(f64, DMat2, DMat4, SVD4_SWEEPS_F64);
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Default)]
 /// Helper struct to store 3 Matrices to avoid OUT parameters on functions
 pub struct SVD3Set {
     /// The matrix of left singular vectors.
@@ -147,295 +626,400 @@ impl SVD3Set {
     }
 }
 
-/// Calculates the squared norm of the vector [x y z] using a standard scalar product d = x * x + y * y + z * z
-fn dist2(x: f32, y: f32, z: f32) -> f32 {
-    x * x + y * y + z * z
+/// Wrapping function used to contain all of the required sub calls
+pub fn svd3(a: &Mat3) -> SVD3Set {
+    let (u, s, v) = svd3_f32::svd3(a);
+    SVD3Set { u, s, v }
 }
 
-/// For an explanation of the math see http://pages.cs.wisc.edu/~sifakis/papers/SVD_TR1690.pdf
-/// Computing the Singular Value Decomposition of 3 x 3 matrices with minimal branching and elementary floating point operations
-/// See Algorithm 2 in reference. Given a matrix A this function returns the givens quaternion (x and w component, y and z are 0)
-fn approximate_givens_quaternion(a: &Symmetric3x3) -> Givens {
-    let g = Givens {
-        ch: 2.0 * (a.m_00 - a.m_11),
-        sh: a.m_10,
-    };
-    let ch2 = g.ch * g.ch;
-    let sh2 = g.sh * g.sh;
-    let mut b = GAMMA * sh2 < ch2;
-    let w = rsqrt(ch2 + sh2);
+/// Computes the SVD of a batch of 3x3 matrices in parallel.
+///
+/// The matrices are split in chunks of 256 decomposed by the tasks of the rayon thread pool,
+/// each writing its results in place, so that there is no allocation. Each matrix is
+/// decomposed by [`svd3`], with results bit-identical to a scalar loop. It is only available
+/// with the opt-in `rayon` feature.
+///
+/// # Arguments
+///
+/// * `matrices` - The matrices to decompose.
+/// * `out` - The decompositions of the matrices, in the same order.
+///
+/// # Panics
+///
+/// Panics if `matrices` and `out` have different lengths.
+///
+/// Example:
+///
+/// ```
+/// use glam::{Mat3, Vec3};
+/// use kornia_linalg::linalg::{svd3_batch, SVD3Set};
+///
+/// let matrices = (0..1000)
+///     .map(|i| Mat3::from_diagonal(Vec3::new(3.0, 2.0, i as f32 * 1e-3)))
+///     .collect::<Vec<_>>();
+/// let mut out = vec![SVD3Set::default(); matrices.len()];
+/// svd3_batch(&matrices, &mut out);
+/// assert!((out[500].s().z_axis.z - 0.5).abs() < 1e-3);
+/// ```
+#[cfg(feature = "rayon")]
+pub fn svd3_batch(matrices: &[Mat3], out: &mut [SVD3Set]) {
+    use rayon::prelude::*;
+
+    assert_eq!(
+        matrices.len(),
+        out.len(),
+        "the output must have one decomposition per matrix"
+    );
+    matrices
+        .par_chunks(SVD3_BATCH_CHUNK)
+        .zip(out.par_chunks_mut(SVD3_BATCH_CHUNK))
+        .for_each(|(matrices, out)| {
+            for (a, svd) in matrices.iter().zip(out.iter_mut()) {
+                *svd = svd3(a);
+            }
+        });
+}
+
+#[derive(Debug)]
+/// The SVD of a 3x3 matrix in double precision, see [`SVD3Set`].
+pub struct SVD3Result64 {
+    /// The matrix of left singular vectors.
+    u: DMat3,
+
+    /// The diagonal matrix of singular values.
+    s: DMat3,
+
+    /// The matrix of right singular vectors.
+    v: DMat3,
+}
+
+impl SVD3Result64 {
+    /// Get the left singular vectors matrix.
+    #[inline]
+    pub fn u(&self) -> &DMat3 {
+        &self.u
+    }
 
-    if w.is_nan() {
-        // Checking for NaN
-        b = false;
+    /// Get the diagonal matrix of singular values.
+    #[inline]
+    pub fn s(&self) -> &DMat3 {
+        &self.s
     }
 
-    Givens {
-        ch: if b { w * g.ch } else { CSTAR },
-        sh: if b { w * g.sh } else { SSTAR },
+    /// Get the right singular vectors matrix.
+    #[inline]
+    pub fn v(&self) -> &DMat3 {
+        &self.v
     }
 }
 
-#[derive(Debug)]
-/// A wrapper around the `glam::Quat` type that allows dynamic indexing into its components.
+/// Computes the SVD of a 3x3 matrix in double precision.
+///
+/// The decomposition follows the same steps as [`svd3`], with exact square roots and more
+/// Jacobi sweeps to reach the f64 precision. It has the same conventions: `U` and `V` are
+/// rotations, and the singular values on the diagonal of `S` are sorted by decreasing
+/// magnitude, the last one being negative when the determinant of `A` is.
 ///
-/// This struct provides custom indexing behavior for quaternion components (`x`, `y`, `z`, and `w`),
-/// enabling access and mutation using an index (e.g., `q[0]`, `q[1]`, etc.). It implements both
-/// the `Index` and `IndexMut` traits to allow for immutable and mutable access to the quaternion's components.
-struct IndexedQuat(Quat);
+/// # Arguments
+///
+/// * `a` - The matrix to decompose.
+///
+/// # Returns
+///
+/// The matrices `U`, `S` and `V` such that `A = U * S * V^T`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat3, DVec3};
+/// use kornia_linalg::linalg::svd3_f64;
+///
+/// let a = DMat3::from_cols(
+///     DVec3::new(1.0, 2.0, 0.5),
+///     DVec3::new(-0.3, 4.0, 1.0),
+///     DVec3::new(2.0, 0.0, 3.0),
+/// );
+/// let svd = svd3_f64(&a);
+/// let reconstructed = svd.u().mul_mat3(&svd.s().mul_mat3(&svd.v().transpose()));
+/// assert!(reconstructed.abs_diff_eq(a, 1e-12));
+/// ```
+pub fn svd3_f64(a: &DMat3) -> SVD3Result64 {
+    let (u, s, v) = svd3_f64::svd3(a);
+    SVD3Result64 { u, s, v }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// The SVD of a 2x2 matrix, see [`SVD3Set`].
+pub struct SVD2Set {
+    /// The matrix of left singular vectors.
+    u: Mat2,
 
-impl IndexedQuat {
-    fn new(q: Quat) -> Self {
-        IndexedQuat(q)
+    /// The diagonal matrix of singular values.
+    s: Mat2,
+
+    /// The matrix of right singular vectors.
+    v: Mat2,
+}
+
+impl SVD2Set {
+    /// Get the left singular vectors matrix.
+    #[inline]
+    pub fn u(&self) -> &Mat2 {
+        &self.u
+    }
+
+    /// Get the diagonal matrix of singular values.
+    #[inline]
+    pub fn s(&self) -> &Mat2 {
+        &self.s
     }
 
-    fn to_quat(&self) -> Quat {
-        self.0
+    /// Get the right singular vectors matrix.
+    #[inline]
+    pub fn v(&self) -> &Mat2 {
+        &self.v
     }
 }
 
-impl Index<usize> for IndexedQuat {
-    type Output = f32;
+/// Computes the SVD of a 2x2 matrix in closed form.
+///
+/// The matrix is the sum of a similarity, a rotation of angle `a2` scaled by `q`, and of a
+/// reflection about the axis of angle `a1 / 2` scaled by `r`. The singular values are
+/// `q + r` and `q - r`, and `U` and `V^T` are the rotations of angles `(a2 + a1) / 2` and
+/// `(a2 - a1) / 2`, with no iteration. The conventions are the ones of [`svd3`]: `U` and `V`
+/// are rotations, and the singular values are sorted by decreasing magnitude, the last one
+/// being negative when the determinant of `A` is.
+///
+/// # Arguments
+///
+/// * `a` - The matrix to decompose.
+///
+/// # Returns
+///
+/// The matrices `U`, `S` and `V` such that `A = U * S * V^T`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{Mat2, Vec2};
+/// use kornia_linalg::linalg::svd2;
+///
+/// // a structure tensor of an edge along y
+/// let a = Mat2::from_cols(Vec2::new(4.0, 0.0), Vec2::new(0.0, 0.25));
+/// let svd = svd2(&a);
+/// assert!(svd.s().abs_diff_eq(a, 1e-6));
+/// let reconstructed = svd.u().mul_mat2(&svd.s().mul_mat2(&svd.v().transpose()));
+/// assert!(reconstructed.abs_diff_eq(a, 1e-6));
+/// ```
+pub fn svd2(a: &Mat2) -> SVD2Set {
+    let (u, s, v) = svd2_f32::svd2(a);
+    SVD2Set { u, s, v }
+}
 
-    fn index(&self, index: usize) -> &Self::Output {
-        match index {
-            0 => &self.0.x,
-            1 => &self.0.y,
-            2 => &self.0.z,
-            3 => &self.0.w,
-            _ => panic!("Index out of bounds for Quaternion: {}", index),
-        }
-    }
+#[derive(Debug, Clone, Copy, Default)]
+/// The SVD of a 2x2 matrix in double precision, see [`SVD2Set`].
+pub struct SVD2Result64 {
+    /// The matrix of left singular vectors.
+    u: DMat2,
+
+    /// The diagonal matrix of singular values.
+    s: DMat2,
+
+    /// The matrix of right singular vectors.
+    v: DMat2,
 }
 
-impl IndexMut<usize> for IndexedQuat {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        match index {
-            0 => &mut self.0.x,
-            1 => &mut self.0.y,
-            2 => &mut self.0.z,
-            3 => &mut self.0.w,
-            _ => panic!("Index out of bounds for Quaternion: {}", index),
-        }
+impl SVD2Result64 {
+    /// Get the left singular vectors matrix.
+    #[inline]
+    pub fn u(&self) -> &DMat2 {
+        &self.u
+    }
+
+    /// Get the diagonal matrix of singular values.
+    #[inline]
+    pub fn s(&self) -> &DMat2 {
+        &self.s
+    }
+
+    /// Get the right singular vectors matrix.
+    #[inline]
+    pub fn v(&self) -> &DMat2 {
+        &self.v
     }
 }
 
-/// Function used to apply a givens rotation S. Calculates the weights and updates the quaternion to contain the cumultative rotation
-fn jacobi_conjugation(x: usize, y: usize, z: usize, s: &mut Symmetric3x3, q: &mut IndexedQuat) {
-    // Compute the Givens rotation (approximated)
-    let mut g = approximate_givens_quaternion(s);
-    // Scale and calculate intermediate values
-    let ch2 = g.ch * g.ch;
-    let sh2 = g.sh * g.sh;
-    let scale = 1.0 / (ch2 + sh2);
-    let a = (ch2 - sh2) * scale;
-    let b = 2.0 * g.sh * g.ch * scale;
-
-    // Create a copy of the matrix to avoid modifying the original during calculations
-    let mut _s = s.clone();
-
-    // Perform conjugation: S = Q'*S*Q
-    s.m_00 = a * (a * _s.m_00 + b * _s.m_10) + b * (a * _s.m_10 + b * _s.m_11);
-    s.m_10 = a * (-b * _s.m_00 + a * _s.m_10) + b * (-b * _s.m_10 + a * _s.m_11);
-    s.m_11 = -b * (-b * _s.m_00 + a * _s.m_10) + a * (-b * _s.m_10 + a * _s.m_11);
-    s.m_20 = a * _s.m_20 + b * _s.m_21;
-    s.m_21 = -b * _s.m_20 + a * _s.m_21;
-    s.m_22 = _s.m_22;
-
-    // Update cumulative rotation qV
-    let mut tmp = [0.0, 0.0, 0.0];
-    tmp[0] = q[0] * g.sh;
-    tmp[1] = q[1] * g.sh;
-    tmp[2] = q[2] * g.sh;
-    g.sh *= q[3];
-
-    // (x, y, z) corresponds to (0,1,2), (1,2,0), (2,0,1) for (p, q) = (0,1), (1,2), (0,2)
-    q[z] = q[z] * g.ch + g.sh;
-    q[3] = q[3] * g.ch - tmp[z]; // w
-    q[x] = q[x] * g.ch + tmp[y];
-    q[y] = q[y] * g.ch - tmp[x];
-
-    // Re-arrange matrix for next iteration
-    _s.m_00 = s.m_11;
-    _s.m_10 = s.m_21;
-    _s.m_11 = s.m_22;
-    _s.m_20 = s.m_10;
-    _s.m_21 = s.m_20;
-    _s.m_22 = s.m_00;
-
-    s.m_00 = _s.m_00;
-    s.m_10 = _s.m_10;
-    s.m_11 = _s.m_11;
-    s.m_20 = _s.m_20;
-    s.m_21 = _s.m_21;
-    s.m_22 = _s.m_22;
-}
-
-/// Function used to contain the givens permutations and the loop of the jacobi steps controlled by JACOBI_STEPS
-/// Returns the quaternion q containing the cumultative result used to reconstruct S
-fn jacobi_eigenanalysis(mut s: Symmetric3x3) -> Mat3 {
-    let mut q = IndexedQuat::new(Quat::from_xyzw(0.0, 0.0, 0.0, 1.0));
-    for _i in 0..JACOBI_STEPS {
-        jacobi_conjugation(0, 1, 2, &mut s, &mut q);
-        jacobi_conjugation(1, 2, 0, &mut s, &mut q);
-        jacobi_conjugation(2, 0, 1, &mut s, &mut q);
-    }
-
-    Mat3::from_quat(q.to_quat())
-}
-
-/// Implementation of Algorithm 3
-fn sort_singular_values(b: &mut Mat3, v: &mut Mat3) {
-    let mut rho1 = dist2(b.x_axis.x, b.x_axis.y, b.x_axis.z);
-    let mut rho2 = dist2(b.y_axis.x, b.y_axis.y, b.y_axis.z);
-    let mut rho3 = dist2(b.z_axis.x, b.z_axis.y, b.z_axis.z);
-
-    let mut c = rho1 < rho2;
-    cond_neg_swap(c, &mut b.x_axis.x, &mut b.y_axis.x);
-    cond_neg_swap(c, &mut v.x_axis.x, &mut v.y_axis.x);
-    cond_neg_swap(c, &mut b.x_axis.y, &mut b.y_axis.y);
-    cond_neg_swap(c, &mut v.x_axis.y, &mut v.y_axis.y);
-    cond_neg_swap(c, &mut b.x_axis.z, &mut b.y_axis.z);
-    cond_neg_swap(c, &mut v.x_axis.z, &mut v.y_axis.z);
-    cond_swap(c, &mut rho1, &mut rho2);
-
-    c = rho1 < rho3;
-    cond_neg_swap(c, &mut b.x_axis.x, &mut b.z_axis.x);
-    cond_neg_swap(c, &mut v.x_axis.x, &mut v.z_axis.x);
-    cond_neg_swap(c, &mut b.x_axis.y, &mut b.z_axis.y);
-    cond_neg_swap(c, &mut v.x_axis.y, &mut v.z_axis.y);
-    cond_neg_swap(c, &mut b.x_axis.z, &mut b.z_axis.z);
-    cond_neg_swap(c, &mut v.x_axis.z, &mut v.z_axis.z);
-    cond_swap(c, &mut rho1, &mut rho3);
-
-    c = rho2 < rho3;
-    cond_neg_swap(c, &mut b.y_axis.x, &mut b.z_axis.x);
-    cond_neg_swap(c, &mut v.y_axis.x, &mut v.z_axis.x);
-    cond_neg_swap(c, &mut b.y_axis.y, &mut b.z_axis.y);
-    cond_neg_swap(c, &mut v.y_axis.y, &mut v.z_axis.y);
-    cond_neg_swap(c, &mut b.y_axis.z, &mut b.z_axis.z);
-    cond_neg_swap(c, &mut v.y_axis.z, &mut v.z_axis.z);
-}
-
-/// Implementation of Algorithm 4
-fn qr_givens_quaternion(a1: f32, a2: f32) -> Givens {
-    let epsilon = SVD3_EPSILON;
-    let rho = accurate_sqrt(a1 * a1 + a2 * a2);
-
-    let mut g = Givens {
-        ch: a1.abs() + f32::max(rho, epsilon),
-        sh: if rho > epsilon { a2 } else { 0.0 },
-    };
+/// Computes the SVD of a 2x2 matrix in closed form in double precision.
+///
+/// The same algorithm and conventions as [`svd2`].
+///
+/// # Arguments
+///
+/// * `a` - The matrix to decompose.
+///
+/// # Returns
+///
+/// The matrices `U`, `S` and `V` such that `A = U * S * V^T`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat2, DVec2};
+/// use kornia_linalg::linalg::svd2_f64;
+///
+/// // a reflection has the singular values 1 and -1
+/// let a = DMat2::from_cols(DVec2::new(0.0, 1.0), DVec2::new(1.0, 0.0));
+/// let svd = svd2_f64(&a);
+/// assert!(svd.s().abs_diff_eq(DMat2::from_diagonal(DVec2::new(1.0, -1.0)), 1e-15));
+/// ```
+pub fn svd2_f64(a: &DMat2) -> SVD2Result64 {
+    let (u, s, v) = svd2_f64::svd2(a);
+    SVD2Result64 { u, s, v }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// The SVD of a 4x4 matrix, see [`SVD3Set`].
+pub struct SVD4Set {
+    /// The matrix of left singular vectors.
+    u: Mat4,
 
-    let b = a1 < 0.0;
-    cond_swap(b, &mut g.sh, &mut g.ch);
-
-    let w = rsqrt(g.ch * g.ch + g.sh * g.sh);
-    g.ch *= w;
-    g.sh *= w;
-    g
-}
-
-/// Implements a QR decomposition of a Matrix
-fn qr_decomposition(b_mat: &mut Mat3) -> QR3 {
-    let mut q = Mat3::ZERO;
-    let mut r = Mat3::ZERO;
-
-    // First Givens rotation (ch, 0, 0, sh)
-    let g1 = qr_givens_quaternion(b_mat.x_axis.x, b_mat.x_axis.y);
-    let mut a = -2.0 * g1.sh * g1.sh + 1.0;
-    let mut b = 2.0 * g1.ch * g1.sh;
-
-    // Apply B = Q' * B
-    r.x_axis.x = a * b_mat.x_axis.x + b * b_mat.x_axis.y;
-    r.y_axis.x = a * b_mat.y_axis.x + b * b_mat.y_axis.y;
-    r.z_axis.x = a * b_mat.z_axis.x + b * b_mat.z_axis.y;
-    r.x_axis.y = -b * b_mat.x_axis.x + a * b_mat.x_axis.y;
-    r.y_axis.y = -b * b_mat.y_axis.x + a * b_mat.y_axis.y;
-    r.z_axis.y = -b * b_mat.z_axis.x + a * b_mat.z_axis.y;
-    r.x_axis.z = b_mat.x_axis.z;
-    r.y_axis.z = b_mat.y_axis.z;
-    r.z_axis.z = b_mat.z_axis.z;
-
-    // Second Givens rotation (ch, 0, -sh, 0)
-    let g2 = qr_givens_quaternion(r.x_axis.x, r.x_axis.z);
-    a = -2.0 * g2.sh * g2.sh + 1.0;
-    b = 2.0 * g2.ch * g2.sh;
-
-    // Apply B = Q' * B
-    b_mat.x_axis.x = a * r.x_axis.x + b * r.x_axis.z;
-    b_mat.y_axis.x = a * r.y_axis.x + b * r.y_axis.z;
-    b_mat.z_axis.x = a * r.z_axis.x + b * r.z_axis.z;
-    b_mat.x_axis.y = r.x_axis.y;
-    b_mat.y_axis.y = r.y_axis.y;
-    b_mat.z_axis.y = r.z_axis.y;
-    b_mat.x_axis.z = -b * r.x_axis.x + a * r.x_axis.z;
-    b_mat.y_axis.z = -b * r.y_axis.x + a * r.y_axis.z;
-    b_mat.z_axis.z = -b * r.z_axis.x + a * r.z_axis.z;
-
-    // Third Givens rotation (ch, sh, 0, 0)
-    let g3 = qr_givens_quaternion(b_mat.y_axis.y, b_mat.y_axis.z);
-    a = -2.0 * g3.sh * g3.sh + 1.0;
-    b = 2.0 * g3.ch * g3.sh;
-
-    // R is now set to desired value
-    r.x_axis.x = b_mat.x_axis.x;
-    r.y_axis.x = b_mat.y_axis.x;
-    r.z_axis.x = b_mat.z_axis.x;
-    r.x_axis.y = a * b_mat.x_axis.y + b * b_mat.x_axis.z;
-    r.y_axis.y = a * b_mat.y_axis.y + b * b_mat.y_axis.z;
-    r.z_axis.y = a * b_mat.z_axis.y + b * b_mat.z_axis.z;
-    r.x_axis.z = -b * b_mat.x_axis.y + a * b_mat.x_axis.z;
-    r.y_axis.z = -b * b_mat.y_axis.y + a * b_mat.y_axis.z;
-    r.z_axis.z = -b * b_mat.z_axis.y + a * b_mat.z_axis.z;
-
-    // Construct the cumulative rotation Q = Q1 * Q2 * Q3
-    let sh12 = 2.0 * (g1.sh * g1.sh - 0.5);
-    let sh22 = 2.0 * (g2.sh * g2.sh - 0.5);
-    let sh32 = 2.0 * (g3.sh * g3.sh - 0.5);
-
-    q.x_axis.x = sh12 * sh22;
-    q.x_axis.y = 4.0 * g2.ch * g3.ch * sh12 * g2.sh * g3.sh + 2.0 * g1.ch * g1.sh * sh32;
-    q.x_axis.z = 4.0 * g1.ch * g3.ch * g1.sh * g3.sh - 2.0 * g2.ch * sh12 * g2.sh * sh32;
-
-    q.y_axis.x = -2.0 * g1.ch * g1.sh * sh22;
-    q.y_axis.y = -8.0 * g1.ch * g2.ch * g3.ch * g1.sh * g2.sh * g3.sh + sh12 * sh32;
-    q.y_axis.z =
-        -2.0 * g3.ch * g3.sh + 4.0 * g1.sh * (g3.ch * g1.sh * g3.sh + g1.ch * g2.ch * g2.sh * sh32);
-
-    q.z_axis.x = 2.0 * g2.ch * g2.sh;
-    q.z_axis.y = -2.0 * g3.ch * sh22 * g3.sh;
-    q.z_axis.z = sh22 * sh32;
-
-    QR3 { q, r }
+    /// The diagonal matrix of singular values.
+    s: Mat4,
+
+    /// The matrix of right singular vectors.
+    v: Mat4,
 }
 
-/// Wrapping function used to contain all of the required sub calls
-pub fn svd3(a: &Mat3) -> SVD3Set {
-    // Compute the eigenvectors of A^T * A, which is V in SVD (Singular Vectors)
-    let v = jacobi_eigenanalysis(Symmetric3x3::from_mat3x3(&(a.transpose().mul_mat3(a))));
-    // Compute B = A * V
-    let mut b = a.mul_mat3(&v);
+impl SVD4Set {
+    /// Get the left singular vectors matrix.
+    #[inline]
+    pub fn u(&self) -> &Mat4 {
+        &self.u
+    }
+
+    /// Get the diagonal matrix of singular values.
+    #[inline]
+    pub fn s(&self) -> &Mat4 {
+        &self.s
+    }
+
+    /// Get the right singular vectors matrix.
+    #[inline]
+    pub fn v(&self) -> &Mat4 {
+        &self.v
+    }
+}
+
+/// Computes the SVD of a 4x4 matrix.
+///
+/// The two-sided Jacobi algorithm sweeps over the pairs of rows and columns, diagonalizing
+/// each 2x2 block with the closed form [`svd2`] and applying its rotations to the whole
+/// matrix, until the off-diagonal entries vanish relative to its norm. The conventions are
+/// the ones of [`svd3`]: `U` and `V` are rotations, and the singular values are sorted by
+/// decreasing magnitude, the last one being negative when the determinant of `A` is. This
+/// analyzes the homogeneous transforms, e.g. the rank of a projection matrix or the
+/// conditioning of a 3D homography.
+///
+/// # Arguments
+///
+/// * `a` - The matrix to decompose.
+///
+/// # Returns
+///
+/// The matrices `U`, `S` and `V` such that `A = U * S * V^T`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{Mat4, Vec4};
+/// use kornia_linalg::linalg::svd4;
+///
+/// let a = Mat4::from_diagonal(Vec4::new(1.0, -4.0, 2.0, 3.0));
+/// let svd = svd4(&a);
+/// assert!(svd.s().abs_diff_eq(Mat4::from_diagonal(Vec4::new(4.0, 3.0, 2.0, -1.0)), 1e-6));
+/// let reconstructed = svd.u().mul_mat4(&svd.s().mul_mat4(&svd.v().transpose()));
+/// assert!(reconstructed.abs_diff_eq(a, 1e-6));
+/// ```
+pub fn svd4(a: &Mat4) -> SVD4Set {
+    let (u, s, v) = svd4_f32::svd4(a);
+    SVD4Set { u, s, v }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// The SVD of a 4x4 matrix in double precision, see [`SVD4Set`].
+pub struct SVD4Result64 {
+    /// The matrix of left singular vectors.
+    u: DMat4,
+
+    /// The diagonal matrix of singular values.
+    s: DMat4,
 
-    // Sort the singular values
-    sort_singular_values(&mut b, &mut v.clone());
+    /// The matrix of right singular vectors.
+    v: DMat4,
+}
+
+impl SVD4Result64 {
+    /// Get the left singular vectors matrix.
+    #[inline]
+    pub fn u(&self) -> &DMat4 {
+        &self.u
+    }
 
-    // Perform QR decomposition on B to get Q and R
-    let qr = qr_decomposition(&mut b);
+    /// Get the diagonal matrix of singular values.
+    #[inline]
+    pub fn s(&self) -> &DMat4 {
+        &self.s
+    }
 
-    // Return the SVD result, which includes Q (as U), R (as S), and V
-    SVD3Set {
-        u: qr.q,
-        s: qr.r,
-        v,
+    /// Get the right singular vectors matrix.
+    #[inline]
+    pub fn v(&self) -> &DMat4 {
+        &self.v
     }
 }
 
+/// Computes the SVD of a 4x4 matrix in double precision.
+///
+/// The same algorithm and conventions as [`svd4`], with more sweeps to reach the f64
+/// precision.
+///
+/// # Arguments
+///
+/// * `a` - The matrix to decompose.
+///
+/// # Returns
+///
+/// The matrices `U`, `S` and `V` such that `A = U * S * V^T`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat4, DVec4};
+/// use kornia_linalg::linalg::svd4_f64;
+///
+/// // a projection matrix, of rank 3
+/// let p = DMat4::from_cols(
+///     DVec4::new(500.0, 0.0, 0.0, 0.0),
+///     DVec4::new(0.0, 500.0, 0.0, 0.0),
+///     DVec4::new(320.0, 240.0, 1.0, 0.0),
+///     DVec4::new(10.0, -5.0, 0.2, 0.0),
+/// );
+/// let svd = svd4_f64(&p);
+/// assert!(svd.s().w_axis.w.abs() < 1e-9);
+/// let reconstructed = svd.u().mul_mat4(&svd.s().mul_mat4(&svd.v().transpose()));
+/// assert!(reconstructed.abs_diff_eq(p, 1e-9));
+/// ```
+pub fn svd4_f64(a: &DMat4) -> SVD4Result64 {
+    let (u, s, v) = svd4_f64::svd4(a);
+    SVD4Result64 { u, s, v }
+}
+
 #[cfg(test)]
 mod tests {
-    use glam::Vec3;
+    use glam::{DVec2, DVec3, DVec4, Vec3};
 
     use super::*;
+    use crate::test_utils::generator;
 
     #[test]
     fn test_svd3_1() {
@@ -512,4 +1096,289 @@ mod tests {
             SVD3_EPSILON,
         );
     }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_svd3_batch() {
+        let mut next = generator(7);
+        let matrices = (0..10_000)
+            .map(|_| Mat3::from_cols_array(&std::array::from_fn(|_| next() as f32)))
+            .collect::<Vec<_>>();
+
+        let mut out = vec![SVD3Set::default(); matrices.len()];
+        svd3_batch(&matrices, &mut out);
+
+        // bit-identical to the scalar path
+        let bits = |m: &Mat3| m.to_cols_array().map(f32::to_bits);
+        for (a, svd) in matrices.iter().zip(out.iter()) {
+            let expected = svd3(a);
+            assert_eq!(bits(svd.u()), bits(expected.u()));
+            assert_eq!(bits(svd.s()), bits(expected.s()));
+            assert_eq!(bits(svd.v()), bits(expected.v()));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    #[should_panic]
+    fn test_svd3_batch_length_mismatch() {
+        let mut out = vec![SVD3Set::default(); 2];
+        svd3_batch(&[Mat3::IDENTITY; 3], &mut out);
+    }
+
+    fn diagonal(m: &DMat3) -> DVec3 {
+        DVec3::new(m.x_axis.x, m.y_axis.y, m.z_axis.z)
+    }
+
+    /// Checks that the decomposition reconstructs `a` and has the conventions of [`svd3`].
+    fn check_svd3_f64(a: &DMat3, epsilon: f64) {
+        let svd = svd3_f64(a);
+        let (u, s, v) = (*svd.u(), *svd.s(), *svd.v());
+        let reconstructed = u.mul_mat3(&s.mul_mat3(&v.transpose()));
+        assert!(
+            reconstructed.abs_diff_eq(*a, epsilon),
+            "{reconstructed:?} != {a:?}"
+        );
+
+        // U and V are rotations and S is diagonal, sorted by decreasing magnitude
+        for rotation in [u, v] {
+            assert!(rotation
+                .transpose()
+                .mul_mat3(&rotation)
+                .abs_diff_eq(DMat3::IDENTITY, 1e-12));
+            assert!((rotation.determinant() - 1.0).abs() < 1e-12);
+        }
+        assert!(s.abs_diff_eq(DMat3::from_diagonal(diagonal(&s)), epsilon));
+        let magnitudes = diagonal(&s).abs();
+        assert!(magnitudes.x >= magnitudes.y && magnitudes.y >= magnitudes.z);
+    }
+
+    #[test]
+    fn test_svd3_f64_random() {
+        let mut next = generator(42);
+        for _ in 0..1000 {
+            let a = DMat3::from_cols_array(&std::array::from_fn(|_| next()));
+            check_svd3_f64(&a, 1e-12);
+            // the f32 decomposition, less accurate, has the same order and signs
+            let s = diagonal(&svd3(&a.as_mat3()).s().as_dmat3());
+            let s64 = diagonal(svd3_f64(&a).s());
+            assert!(s.x >= s.y && s.y >= s.z.abs());
+            assert!(s64.z.abs() < 0.05 || s.z.signum() == s64.z.signum());
+        }
+    }
+
+    #[test]
+    fn test_svd3_f32_f64_agree() {
+        let mut next = generator(3);
+        let mut compared = 0;
+        while compared < 200 {
+            let a = DMat3::from_cols_array(&std::array::from_fn(|_| next()));
+            let svd64 = svd3_f64(&a);
+            let s64 = diagonal(svd64.s());
+
+            // the singular vectors are only defined up to a rotation for close singular
+            // values, and the sign of the last one flips with a vanishing determinant
+            let magnitudes = s64.abs();
+            if magnitudes.x - magnitudes.y < 0.05
+                || magnitudes.y - magnitudes.z < 0.05
+                || magnitudes.z < 0.05
+            {
+                continue;
+            }
+            compared += 1;
+
+            // the same singular values in the same order and with the same signs, and the
+            // same singular vectors with the same orientations, within the few percents of
+            // the single Newton step of the reciprocal square roots of the f32 path
+            let svd32 = svd3(&a.as_mat3());
+            let s32 = diagonal(&svd32.s().as_dmat3());
+            assert_eq!(s32.signum(), s64.signum());
+            assert!(
+                s32.abs_diff_eq(s64, 5e-2 * magnitudes.x),
+                "{s32:?} != {s64:?}"
+            );
+            assert!(svd32.u().as_dmat3().abs_diff_eq(*svd64.u(), 1e-1));
+            assert!(svd32.v().as_dmat3().abs_diff_eq(*svd64.v(), 1e-1));
+        }
+    }
+
+    #[test]
+    fn test_svd3_f64_rank_deficient() {
+        let rank_one = DMat3::from_cols(
+            DVec3::new(1.0, 2.0, 3.0),
+            DVec3::new(2.0, 4.0, 6.0),
+            DVec3::new(3.0, 6.0, 9.0),
+        );
+        let rank_two = DMat3::from_cols(
+            DVec3::new(1.0, 0.5, -2.0),
+            DVec3::new(0.3, 4.0, 1.0),
+            DVec3::new(1.3, 4.5, -1.0),
+        );
+        for (a, rank) in [(rank_one, 1), (rank_two, 2), (DMat3::ZERO, 0)] {
+            check_svd3_f64(&a, 1e-12);
+            let s = diagonal(svd3_f64(&a).s()).abs();
+            let nonzero = s.to_array().iter().filter(|x| **x > 1e-12).count();
+            assert_eq!(nonzero, rank);
+        }
+    }
+
+    /// Checks that the 2x2 decomposition reconstructs `a` with the conventions of [`svd3`].
+    fn check_svd2_f64(a: &DMat2, epsilon: f64) -> DVec2 {
+        let svd = svd2_f64(a);
+        let (u, s, v) = (*svd.u(), *svd.s(), *svd.v());
+        let reconstructed = u.mul_mat2(&s.mul_mat2(&v.transpose()));
+        assert!(
+            reconstructed.abs_diff_eq(*a, epsilon),
+            "{reconstructed:?} != {a:?}"
+        );
+        for rotation in [u, v] {
+            assert!(rotation
+                .transpose()
+                .mul_mat2(&rotation)
+                .abs_diff_eq(DMat2::IDENTITY, 1e-12));
+            assert!((rotation.determinant() - 1.0).abs() < 1e-12);
+        }
+        let values = DVec2::new(s.x_axis.x, s.y_axis.y);
+        assert_eq!([s.x_axis.y, s.y_axis.x], [0.0; 2]);
+        assert!(values.x >= values.y.abs());
+        values
+    }
+
+    #[test]
+    fn test_svd2_f64_random() {
+        let mut next = generator(3);
+        for _ in 0..1000 {
+            let a = DMat2::from_cols_array(&std::array::from_fn(|_| next()));
+            let values = check_svd2_f64(&a, 1e-12);
+            assert!((values.x * values.y - a.determinant()).abs() < 1e-12);
+
+            // the single precision decomposition agrees
+            let svd = svd2(&a.as_mat2());
+            let reconstructed = svd.u().mul_mat2(&svd.s().mul_mat2(&svd.v().transpose()));
+            assert!(reconstructed.abs_diff_eq(a.as_mat2(), 1e-5));
+            assert!(svd.s().as_dmat2().abs_diff_eq(*svd2_f64(&a).s(), 1e-5));
+        }
+    }
+
+    #[test]
+    fn test_svd2_f64_rotation_shear() {
+        // a rotation has unit singular values
+        let rotation = DMat2::from_angle(0.7);
+        let values = check_svd2_f64(&rotation, 1e-15);
+        assert!(values.abs_diff_eq(DVec2::ONE, 1e-15));
+
+        // the shear [[1, k], [0, 1]] has the singular values sqrt(1 + k^2 / 4) +- k / 2
+        let k = 1.5;
+        let shear = DMat2::from_cols(DVec2::new(1.0, 0.0), DVec2::new(k, 1.0));
+        let values = check_svd2_f64(&shear, 1e-15);
+        let root = (1.0 + k * k / 4.0f64).sqrt();
+        assert!(values.abs_diff_eq(DVec2::new(root + k / 2.0, root - k / 2.0), 1e-15));
+    }
+
+    #[test]
+    fn test_svd2_f64_degenerate() {
+        // a zero row, a zero column and the zero matrix
+        let zero_row = DMat2::from_cols(DVec2::new(3.0, 0.0), DVec2::new(4.0, 0.0));
+        let zero_column = DMat2::from_cols(DVec2::ZERO, DVec2::new(1.0, -2.0));
+        for (a, largest) in [(zero_row, 5.0), (zero_column, 5.0f64.sqrt())] {
+            let values = check_svd2_f64(&a, 1e-15);
+            assert!((values.x - largest).abs() < 1e-15);
+            assert_eq!(values.y, 0.0);
+        }
+        let values = check_svd2_f64(&DMat2::ZERO, 0.0);
+        assert_eq!(values, DVec2::ZERO);
+
+        // a nearly singular matrix keeps the relative accuracy of its smallest value
+        let a = DMat2::from_cols(DVec2::new(1.0, 1.0), DVec2::new(1.0, 1.0 + 1e-12));
+        let values = check_svd2_f64(&a, 1e-15);
+        let d = a.y_axis.y - 1.0;
+        assert!((values.y / (0.5 * d) - 1.0).abs() < 1e-9);
+    }
+
+    /// Checks that the 4x4 decomposition reconstructs `a` with the conventions of [`svd3`].
+    fn check_svd4_f64(a: &DMat4, epsilon: f64) -> DVec4 {
+        let svd = svd4_f64(a);
+        let (u, s, v) = (*svd.u(), *svd.s(), *svd.v());
+        let reconstructed = u.mul_mat4(&s.mul_mat4(&v.transpose()));
+        assert!(
+            reconstructed.abs_diff_eq(*a, epsilon),
+            "{reconstructed:?} != {a:?}"
+        );
+        for rotation in [u, v] {
+            assert!(rotation
+                .transpose()
+                .mul_mat4(&rotation)
+                .abs_diff_eq(DMat4::IDENTITY, 1e-12));
+            assert!((rotation.determinant() - 1.0).abs() < 1e-12);
+        }
+        let values = DVec4::new(s.x_axis.x, s.y_axis.y, s.z_axis.z, s.w_axis.w);
+        assert_eq!(s, DMat4::from_diagonal(values));
+        assert!(values.x >= values.y && values.y >= values.z && values.z >= values.w.abs());
+        values
+    }
+
+    #[test]
+    fn test_svd4_f64_random() {
+        let mut next = generator(21);
+        for _ in 0..1000 {
+            let a = DMat4::from_cols_array(&std::array::from_fn(|_| next()));
+            let values = check_svd4_f64(&a, 1e-12);
+            let product = values.x * values.y * values.z * values.w;
+            assert!((product - a.determinant()).abs() < 1e-12);
+
+            // the single precision decomposition agrees
+            let svd = svd4(&a.as_mat4());
+            let reconstructed = svd.u().mul_mat4(&svd.s().mul_mat4(&svd.v().transpose()));
+            assert!(reconstructed.abs_diff_eq(a.as_mat4(), 1e-5));
+            assert!(svd.s().as_dmat4().abs_diff_eq(*svd4_f64(&a).s(), 1e-5));
+        }
+    }
+
+    #[test]
+    fn test_svd4_f64_rank_deficient() {
+        // a rigid transform has unit singular values
+        let rotation = DMat3::from_euler(glam::EulerRot::XYZ, 0.3, -1.1, 0.7);
+        let rigid = DMat4::from_mat3(rotation);
+        let values = check_svd4_f64(&rigid, 1e-14);
+        assert!(values.abs_diff_eq(DVec4::ONE, 1e-14));
+
+        // the outer product of two vectors has rank one, and a projection rank three
+        let outer = DMat4::from_cols_array_2d(
+            &[1.0, -2.0, 0.5, 3.0].map(|x| [2.0, 1.0, -1.0, 0.5].map(|y| x * y)),
+        );
+        let projection = DMat4::from_cols(
+            DVec4::new(500.0, 0.0, 0.0, 0.0),
+            DVec4::new(0.0, 500.0, 0.0, 0.0),
+            DVec4::new(320.0, 240.0, 1.0, 0.0),
+            DVec4::new(10.0, -5.0, 0.2, 0.0),
+        );
+        for (a, rank, epsilon) in [
+            (outer, 1, 1e-12),
+            (projection, 3, 1e-10),
+            (DMat4::ZERO, 0, 0.0),
+        ] {
+            let values = check_svd4_f64(&a, epsilon);
+            let largest = values.x.max(1.0);
+            let nonzero = values
+                .to_array()
+                .iter()
+                .filter(|x| x.abs() > 1e-12 * largest)
+                .count();
+            assert_eq!(nonzero, rank);
+        }
+    }
+
+    #[test]
+    fn test_svd3_f64_near_identity() {
+        let perturbation = DMat3::from_cols(
+            DVec3::new(1e-9, -2e-9, 0.0),
+            DVec3::new(3e-10, 0.0, 5e-9),
+            DVec3::new(0.0, -1e-9, 2e-9),
+        );
+        for a in [DMat3::IDENTITY, DMat3::IDENTITY + perturbation] {
+            check_svd3_f64(&a, 1e-14);
+            let s = diagonal(svd3_f64(&a).s());
+            assert!(s.abs_diff_eq(DVec3::ONE, 1e-8));
+        }
+    }
 }
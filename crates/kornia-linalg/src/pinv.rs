@@ -0,0 +1,179 @@
+use glam::{DMat3, DVec3, Mat3};
+
+use crate::linalg::svd3_f64;
+
+/// Computes the Moore-Penrose pseudo-inverse of a 3x3 matrix.
+///
+/// The pseudo-inverse is computed in double precision with [`pinv3_f64`], as the single
+/// precision [`svd3`](crate::linalg::svd3) trades accuracy for speed with its approximate
+/// square roots, and the result is rounded to single precision.
+///
+/// # Arguments
+///
+/// * `a` - The matrix to invert.
+/// * `tol` - The tolerance, relative to the largest singular value, below which the
+///   singular values are zeroed instead of inverted.
+///
+/// # Returns
+///
+/// The pseudo-inverse of `a`, its inverse when the matrix is well conditioned.
+///
+/// Example:
+///
+/// ```
+/// use glam::{Mat3, Vec3};
+/// use kornia_linalg::pinv::pinv3;
+///
+/// // a covariance of points on the plane z = 0
+/// let a = Mat3::from_diagonal(Vec3::new(2.0, 0.5, 0.0));
+/// let pinv = pinv3(&a, 1e-6);
+/// assert!(pinv.abs_diff_eq(Mat3::from_diagonal(Vec3::new(0.5, 2.0, 0.0)), 1e-6));
+/// ```
+pub fn pinv3(a: &Mat3, tol: f32) -> Mat3 {
+    pinv3_f64(&a.as_dmat3(), tol as f64).as_mat3()
+}
+
+/// Computes the Moore-Penrose pseudo-inverse of a 3x3 matrix in double precision.
+///
+/// With the SVD `A = U * S * V^T` of [`svd3_f64`], the pseudo-inverse is
+/// `V * S^+ * U^T`, where `S^+` inverts the singular values larger than `tol` times the
+/// largest one and zeroes the others. Zeroing the small singular values, instead of
+/// inverting them, keeps the result finite and bounded by `1 / (tol * s_max)` for a rank
+/// deficient or nearly singular matrix, where the inverse blows up.
+///
+/// # Arguments
+///
+/// * `a` - The matrix to invert.
+/// * `tol` - The tolerance, relative to the largest singular value, below which the
+///   singular values are zeroed instead of inverted.
+///
+/// # Returns
+///
+/// The pseudo-inverse of `a`, its inverse when the matrix is well conditioned.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat3, DVec3};
+/// use kornia_linalg::pinv::pinv3_f64;
+///
+/// // a rank one matrix
+/// let a = DMat3::from_cols(
+///     DVec3::new(1.0, 2.0, 3.0),
+///     DVec3::new(2.0, 4.0, 6.0),
+///     DVec3::new(3.0, 6.0, 9.0),
+/// );
+/// let pinv = pinv3_f64(&a, 1e-12);
+/// assert!(a.mul_mat3(&pinv).mul_mat3(&a).abs_diff_eq(a, 1e-12));
+/// ```
+pub fn pinv3_f64(a: &DMat3, tol: f64) -> DMat3 {
+    let svd = svd3_f64(a);
+    let s = svd.s();
+    // the singular values are sorted by decreasing magnitude, the last one possibly negative
+    let singular_values = DVec3::new(s.x_axis.x, s.y_axis.y, s.z_axis.z);
+    let cutoff = tol * singular_values.x.abs();
+    let inverted = singular_values.map(|x| if x.abs() > cutoff { 1.0 / x } else { 0.0 });
+
+    svd.v()
+        .mul_mat3(&DMat3::from_diagonal(inverted))
+        .mul_mat3(&svd.u().transpose())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::generator;
+    use glam::Vec3;
+
+    /// Checks the four Penrose conditions of the pseudo-inverse `x` of `a`.
+    fn check_penrose(a: &DMat3, x: &DMat3, epsilon: f64) {
+        let ax = a.mul_mat3(x);
+        let xa = x.mul_mat3(a);
+        assert!(ax.mul_mat3(a).abs_diff_eq(*a, epsilon), "A X A != A");
+        assert!(xa.mul_mat3(x).abs_diff_eq(*x, epsilon), "X A X != X");
+        assert!(ax.transpose().abs_diff_eq(ax, epsilon), "A X not symmetric");
+        assert!(xa.transpose().abs_diff_eq(xa, epsilon), "X A not symmetric");
+    }
+
+    fn is_finite(m: &DMat3) -> bool {
+        m.to_cols_array().iter().all(|x| x.is_finite())
+    }
+
+    #[test]
+    fn test_pinv3_f64_full_rank() {
+        let mut next = generator(42);
+        for _ in 0..1000 {
+            let a = DMat3::from_cols_array(&std::array::from_fn(|_| next()));
+            if a.determinant().abs() < 1e-3 {
+                continue;
+            }
+            let pinv = pinv3_f64(&a, 1e-12);
+            check_penrose(&a, &pinv, 1e-9);
+            assert!(pinv.abs_diff_eq(a.inverse(), 1e-6 / a.determinant().abs()));
+        }
+    }
+
+    #[test]
+    fn test_pinv3_f64_rank_deficient() {
+        let rank_one = DMat3::from_cols(
+            DVec3::new(1.0, 2.0, 3.0),
+            DVec3::new(2.0, 4.0, 6.0),
+            DVec3::new(3.0, 6.0, 9.0),
+        );
+        let rank_two = DMat3::from_cols(
+            DVec3::new(1.0, 0.5, -2.0),
+            DVec3::new(0.3, 4.0, 1.0),
+            DVec3::new(1.3, 4.5, -1.0),
+        );
+        for (a, rank) in [(rank_one, 1), (rank_two, 2)] {
+            let pinv = pinv3_f64(&a, 1e-12);
+            assert!(is_finite(&pinv));
+            check_penrose(&a, &pinv, 1e-12);
+
+            // the rank of the pseudo-inverse is the one of the matrix
+            let s = svd3_f64(&pinv).s().to_cols_array();
+            let nonzero = [s[0], s[4], s[8]].iter().filter(|x| x.abs() > 1e-9).count();
+            assert_eq!(nonzero, rank);
+        }
+
+        // the zero matrix is its own pseudo-inverse
+        assert_eq!(pinv3_f64(&DMat3::ZERO, 1e-12), DMat3::ZERO);
+    }
+
+    #[test]
+    fn test_pinv3_f64_nearly_singular() {
+        let rotation = DMat3::from_euler(glam::EulerRot::XYZ, 0.3, -1.1, 0.7);
+        let a = rotation
+            .mul_mat3(&DMat3::from_diagonal(DVec3::new(2.0, 1.0, 1e-14)))
+            .mul_mat3(&rotation.transpose());
+
+        // the tiny singular value is dropped, instead of blowing up to 1e14
+        let pinv = pinv3_f64(&a, 1e-9);
+        let expected = rotation
+            .mul_mat3(&DMat3::from_diagonal(DVec3::new(0.5, 1.0, 0.0)))
+            .mul_mat3(&rotation.transpose());
+        assert!(pinv.abs_diff_eq(expected, 1e-12));
+        check_penrose(&a, &pinv, 1e-12);
+
+        // with no tolerance it is inverted
+        let inverse = pinv3_f64(&a, 0.0);
+        assert!(is_finite(&inverse));
+        assert!(inverse.to_cols_array().iter().any(|x| x.abs() > 1e12));
+    }
+
+    #[test]
+    fn test_pinv3() {
+        let singular = Mat3::from_cols(
+            Vec3::new(1.0, 0.5, -2.0),
+            Vec3::new(0.3, 4.0, 1.0),
+            Vec3::new(1.3, 4.5, -1.0),
+        );
+        let mut next = generator(7);
+        let random = Mat3::from_cols_array(&std::array::from_fn(|_| next() as f32));
+        for a in [singular, random, Mat3::ZERO] {
+            let pinv = pinv3(&a, 1e-6);
+            assert!(is_finite(&pinv.as_dmat3()));
+            check_penrose(&a.as_dmat3(), &pinv.as_dmat3(), 1e-4);
+        }
+    }
+}
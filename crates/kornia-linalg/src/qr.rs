@@ -0,0 +1,232 @@
+use glam::{DMat3, Mat3};
+
+/// Generates the Householder QR decomposition for a precision.
+macro_rules! qr3_kernel {
+    ($t:ty, $mat3:ident) => {
+        /// Returns the Q and R factors of A, with a non-negative diagonal of R.
+        pub(super) fn qr3(m: &$mat3) -> ($mat3, $mat3) {
+            // R and Q stored column major, R being reduced in place from A
+            let mut r = m.to_cols_array_2d();
+            let mut q = $mat3::IDENTITY.to_cols_array_2d();
+
+            for k in 0..2 {
+                // the reflection of the column below the diagonal onto the diagonal, with the
+                // sign avoiding the cancellation
+                let alpha = (k..3).map(|i| r[k][i] * r[k][i]).sum::<$t>().sqrt();
+                let mut v = [0.0 as $t; 3];
+                v[k..3].copy_from_slice(&r[k][k..3]);
+                v[k] += if r[k][k] < 0.0 { -alpha } else { alpha };
+                let vv = v.iter().map(|x| x * x).sum::<$t>();
+                if vv == 0.0 {
+                    continue;
+                }
+
+                // R = H * R, for the columns from k
+                for col in r.iter_mut().skip(k) {
+                    let w = 2.0 * (k..3).map(|i| v[i] * col[i]).sum::<$t>() / vv;
+                    for i in k..3 {
+                        col[i] -= w * v[i];
+                    }
+                }
+                // Q = Q * H, for each row
+                for row in 0..3 {
+                    let w = 2.0 * (k..3).map(|i| q[i][row] * v[i]).sum::<$t>() / vv;
+                    for i in k..3 {
+                        q[i][row] -= w * v[i];
+                    }
+                }
+            }
+
+            // R is upper triangular, and its diagonal is made non-negative by flipping the
+            // rows of R with the columns of Q
+            for (j, col) in r.iter_mut().enumerate() {
+                for x in col.iter_mut().skip(j + 1) {
+                    *x = 0.0;
+                }
+            }
+            for i in 0..3 {
+                if r[i][i] < 0.0 {
+                    for col in r.iter_mut() {
+                        col[i] = -col[i];
+                    }
+                    q[i] = q[i].map(|x| -x);
+                }
+            }
+
+            ($mat3::from_cols_array_2d(&q), $mat3::from_cols_array_2d(&r))
+        }
+    };
+}
+
+mod qr3_f32 {
+    use glam::Mat3;
+
+    qr3_kernel!(f32, Mat3);
+}
+
+mod qr3_f64 {
+    use glam::DMat3;
+
+    qr3_kernel!(f64, DMat3);
+}
+
+/// Computes the QR decomposition of a 3x3 matrix.
+///
+/// The matrix is reduced to upper triangular form by two Householder reflections. The
+/// diagonal of `R` is made non-negative, so that the decomposition is unique for a full rank
+/// matrix and matches the Gram-Schmidt process on its columns: `Q` re-orthonormalizes a
+/// matrix drifting from a rotation. The diagonal entries of `R` are zero, up to rounding,
+/// for the dependent columns of a rank deficient matrix, where `Q` is still orthonormal.
+///
+/// # Arguments
+///
+/// * `a` - The matrix to decompose.
+///
+/// # Returns
+///
+/// The orthonormal matrix `Q` and the upper triangular matrix `R` such that `A = Q * R`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{Mat3, Vec3};
+/// use kornia_linalg::qr::qr3;
+///
+/// let a = Mat3::from_cols(
+///     Vec3::new(0.0, 2.0, 0.0),
+///     Vec3::new(1.0, 1.0, 0.0),
+///     Vec3::new(0.0, 0.0, 3.0),
+/// );
+/// let (q, r) = qr3(&a);
+/// assert!(q.mul_mat3(&r).abs_diff_eq(a, 1e-6));
+/// assert!((r.x_axis.x - 2.0).abs() < 1e-6);
+/// ```
+pub fn qr3(a: &Mat3) -> (Mat3, Mat3) {
+    qr3_f32::qr3(a)
+}
+
+/// Computes the QR decomposition of a 3x3 matrix in double precision.
+///
+/// The same algorithm and conventions as [`qr3`].
+///
+/// # Arguments
+///
+/// * `a` - The matrix to decompose.
+///
+/// # Returns
+///
+/// The orthonormal matrix `Q` and the upper triangular matrix `R` such that `A = Q * R`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat3, DVec3};
+/// use kornia_linalg::qr::qr3_f64;
+///
+/// let a = DMat3::from_cols(
+///     DVec3::new(1.0, 2.0, 0.5),
+///     DVec3::new(-0.3, 4.0, 1.0),
+///     DVec3::new(2.0, 0.0, 3.0),
+/// );
+/// let (q, r) = qr3_f64(&a);
+/// assert!(q.mul_mat3(&r).abs_diff_eq(a, 1e-12));
+/// assert!(q.transpose().mul_mat3(&q).abs_diff_eq(DMat3::IDENTITY, 1e-12));
+/// ```
+pub fn qr3_f64(a: &DMat3) -> (DMat3, DMat3) {
+    qr3_f64::qr3(a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::generator;
+    use glam::{DVec3, EulerRot};
+
+    /// Checks that the decomposition reconstructs `a` with an orthonormal Q and an upper
+    /// triangular R of non-negative diagonal.
+    fn check_qr3_f64(a: &DMat3, epsilon: f64) -> DVec3 {
+        let (q, r) = qr3_f64(a);
+        let reconstructed = q.mul_mat3(&r);
+        assert!(
+            reconstructed.abs_diff_eq(*a, epsilon),
+            "{reconstructed:?} != {a:?}"
+        );
+        assert!(q
+            .transpose()
+            .mul_mat3(&q)
+            .abs_diff_eq(DMat3::IDENTITY, 1e-12));
+        assert_eq!([r.x_axis.y, r.x_axis.z, r.y_axis.z], [0.0; 3]);
+        let diagonal = DVec3::new(r.x_axis.x, r.y_axis.y, r.z_axis.z);
+        assert!(diagonal.cmpge(DVec3::ZERO).all());
+        diagonal
+    }
+
+    #[test]
+    fn test_qr3_f64_random() {
+        let mut next = generator(42);
+        for _ in 0..1000 {
+            let a = DMat3::from_cols_array(&std::array::from_fn(|_| next()));
+            check_qr3_f64(&a, 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_qr3_random() {
+        let mut next = generator(5);
+        for _ in 0..1000 {
+            let a = DMat3::from_cols_array(&std::array::from_fn(|_| next()));
+            let (q, r) = qr3(&a.as_mat3());
+            assert!(q.mul_mat3(&r).abs_diff_eq(a.as_mat3(), 1e-5));
+            assert!(q.transpose().mul_mat3(&q).abs_diff_eq(Mat3::IDENTITY, 1e-5));
+            assert_eq!([r.x_axis.y, r.x_axis.z, r.y_axis.z], [0.0; 3]);
+            assert!(r.x_axis.x >= 0.0 && r.y_axis.y >= 0.0 && r.z_axis.z >= 0.0);
+
+            // the same factors as in double precision
+            let (q64, r64) = qr3_f64(&a);
+            assert!(r.as_dmat3().abs_diff_eq(r64, 1e-4));
+            if r64.z_axis.z > 1e-3 {
+                assert!(q.as_dmat3().abs_diff_eq(q64, 1e-3));
+            }
+        }
+    }
+
+    #[test]
+    fn test_qr3_f64_rank_deficient() {
+        let rank_one = DMat3::from_cols(
+            DVec3::new(1.0, 2.0, 3.0),
+            DVec3::new(2.0, 4.0, 6.0),
+            DVec3::new(3.0, 6.0, 9.0),
+        );
+        let rank_two = DMat3::from_cols(
+            DVec3::new(1.0, 0.5, -2.0),
+            DVec3::new(0.3, 4.0, 1.0),
+            DVec3::new(1.3, 4.5, -1.0),
+        );
+        let zero_first_column = DMat3::from_cols(
+            DVec3::ZERO,
+            DVec3::new(0.3, 4.0, 1.0),
+            DVec3::new(1.0, 0.5, -2.0),
+        );
+
+        let diagonal = check_qr3_f64(&rank_one, 1e-12);
+        assert!(diagonal.x > 1.0 && diagonal.y < 1e-12 && diagonal.z < 1e-12);
+        let diagonal = check_qr3_f64(&rank_two, 1e-12);
+        assert!(diagonal.x > 1.0 && diagonal.y > 1.0 && diagonal.z < 1e-12);
+        let diagonal = check_qr3_f64(&zero_first_column, 1e-12);
+        assert!(diagonal.x == 0.0 && diagonal.y > 1.0 && diagonal.z > 1.0);
+        let diagonal = check_qr3_f64(&DMat3::ZERO, 0.0);
+        assert_eq!(diagonal, DVec3::ZERO);
+    }
+
+    #[test]
+    fn test_qr3_f64_orthonormalize_rotation() {
+        let rotation = DMat3::from_euler(EulerRot::ZYX, 0.4, -0.9, 2.1);
+        let mut next = generator(9);
+        let drifted = rotation + DMat3::from_cols_array(&std::array::from_fn(|_| 1e-6 * next()));
+
+        let (q, r) = qr3_f64(&drifted);
+        assert!((q.determinant() - 1.0).abs() < 1e-12);
+        assert!(q.abs_diff_eq(rotation, 1e-5));
+        assert!(r.abs_diff_eq(DMat3::IDENTITY, 1e-5));
+    }
+}
@@ -0,0 +1,100 @@
+use glam::{DMat3, DMat4, DVec3, DVec4};
+
+use crate::error::LinalgError;
+
+/// The tolerance on the entries of the bottom row of a homogeneous transform.
+pub const DECOMPOSE_RIGID_EPSILON_F64: f64 = 1e-12;
+
+/// Splits a homogeneous 4x4 transform into its rotation and translation.
+///
+/// The matrix maps a point `p` to `R * p + t`, with the rotation `R` in its upper left 3x3
+/// block, the translation `t` in the first three entries of its last column and `[0, 0, 0, 1]`
+/// as its bottom row. The bottom row is checked within [`DECOMPOSE_RIGID_EPSILON_F64`], so
+/// that a projective matrix, such as a homography or a camera projection, is not silently
+/// split into a meaningless rotation. The upper left block is returned as is, without
+/// checking that it is orthonormal.
+///
+/// # Arguments
+///
+/// * `m` - The homogeneous transform.
+///
+/// # Returns
+///
+/// The rotation and the translation of the transform, or an error if the matrix is not
+/// finite or its bottom row is not `[0, 0, 0, 1]`.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat3, DMat4, DQuat, DVec3};
+/// use kornia_linalg::rigid::decompose_rigid;
+///
+/// let rotation = DQuat::from_rotation_z(0.5);
+/// let translation = DVec3::new(1.0, -2.0, 3.0);
+/// let m = DMat4::from_rotation_translation(rotation, translation);
+/// let (r, t) = decompose_rigid(&m).unwrap();
+/// assert!(r.abs_diff_eq(DMat3::from_quat(rotation), 1e-15));
+/// assert_eq!(t, translation);
+/// ```
+pub fn decompose_rigid(m: &DMat4) -> Result<(DMat3, DVec3), LinalgError> {
+    if !m.is_finite() {
+        return Err(LinalgError::NonFinite);
+    }
+    let bottom_row = m.row(3);
+    if !bottom_row.abs_diff_eq(DVec4::W, DECOMPOSE_RIGID_EPSILON_F64) {
+        return Err(LinalgError::InvalidBottomRow {
+            bottom_row: bottom_row.to_array(),
+        });
+    }
+    Ok((DMat3::from_mat4(*m), m.w_axis.truncate()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::DQuat;
+
+    #[test]
+    fn test_decompose_rigid() {
+        let rotation = DQuat::from_euler(glam::EulerRot::XYZ, 0.3, -1.1, 0.7);
+        let translation = DVec3::new(0.5, 2.0, -1.5);
+        let m = DMat4::from_rotation_translation(rotation, translation);
+
+        let (r, t) = decompose_rigid(&m).unwrap();
+        assert!(r.abs_diff_eq(DMat3::from_quat(rotation), 1e-15));
+        assert_eq!(t, translation);
+
+        // the transform applies the rotation then the translation
+        let p = DVec3::new(1.0, -1.0, 0.25);
+        assert!(m.transform_point3(p).abs_diff_eq(r.mul_vec3(p) + t, 1e-15));
+
+        assert_eq!(
+            decompose_rigid(&DMat4::IDENTITY),
+            Ok((DMat3::IDENTITY, DVec3::ZERO))
+        );
+    }
+
+    #[test]
+    fn test_decompose_rigid_invalid() {
+        // a perspective projection
+        let projection = DMat4::perspective_rh(1.0, 1.5, 0.1, 100.0);
+        assert_eq!(
+            decompose_rigid(&projection),
+            Err(LinalgError::InvalidBottomRow {
+                bottom_row: projection.row(3).to_array(),
+            })
+        );
+
+        // a scaled homogeneous coordinate
+        let mut scaled = DMat4::IDENTITY;
+        scaled.w_axis.w = 2.0;
+        assert!(matches!(
+            decompose_rigid(&scaled),
+            Err(LinalgError::InvalidBottomRow { .. })
+        ));
+
+        let mut nan = DMat4::IDENTITY;
+        nan.w_axis.x = f64::NAN;
+        assert_eq!(decompose_rigid(&nan), Err(LinalgError::NonFinite));
+    }
+}
@@ -0,0 +1,351 @@
+use glam::{DMat3, DVec3};
+
+use crate::error::LinalgError;
+
+/// The default tolerance on the pivots of [`solve3`], relative to the largest entry of the
+/// matrix.
+pub const SOLVE3_EPSILON_F64: f64 = 1e-12;
+
+/// The solution of a 3x3 linear system with the condition number of its matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Solve3Result {
+    /// The solution of the system.
+    solution: DVec3,
+
+    /// The condition number of the matrix in the 1-norm.
+    condition_number: f64,
+}
+
+impl Solve3Result {
+    /// Get the solution of the system.
+    #[inline]
+    pub fn solution(&self) -> &DVec3 {
+        &self.solution
+    }
+
+    /// Get the condition number of the matrix in the 1-norm, `||A||_1 * ||A^-1||_1`.
+    ///
+    /// The relative error of the solution is up to the condition number times the relative
+    /// error of the system, so that about `log10(condition_number)` digits are lost.
+    #[inline]
+    pub fn condition_number(&self) -> f64 {
+        self.condition_number
+    }
+}
+
+/// Solves the linear system `A * x = b` of a 3x3 matrix.
+///
+/// The system is solved by [`solve3_with_condition`] with the tolerance
+/// [`SOLVE3_EPSILON_F64`], which is more accurate than multiplying by `A.inverse()` and
+/// reports a singular matrix instead of returning infinities.
+///
+/// # Arguments
+///
+/// * `a` - The matrix of the system.
+/// * `b` - The right hand side of the system.
+///
+/// # Returns
+///
+/// The solution `x`, or an error if the matrix is singular within the tolerance or the
+/// system is not finite.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat3, DVec3};
+/// use kornia_linalg::solve::solve3;
+///
+/// let a = DMat3::from_cols(
+///     DVec3::new(0.0, 2.0, 1.0),
+///     DVec3::new(1.0, 1.0, 0.0),
+///     DVec3::new(3.0, 0.0, 1.0),
+/// );
+/// let x = DVec3::new(1.0, -2.0, 0.5);
+/// let solution = solve3(&a, &a.mul_vec3(x)).unwrap();
+/// assert!(solution.abs_diff_eq(x, 1e-12));
+/// ```
+pub fn solve3(a: &DMat3, b: &DVec3) -> Result<DVec3, LinalgError> {
+    solve3_with_condition(a, b, SOLVE3_EPSILON_F64).map(|result| result.solution)
+}
+
+/// Solves the linear system `A * x = b` of a 3x3 matrix and estimates its conditioning.
+///
+/// The matrix is factorized as `P * A = L * U` by Gaussian elimination with partial
+/// pivoting, each pivot being the largest entry of its column, and the system is solved by
+/// forward and backward substitution. The matrix is singular within the tolerance when a
+/// pivot is not larger than `epsilon` times the largest entry of `A`. The inverse, solved
+/// from the same factors, gives the condition number of the matrix in the 1-norm.
+///
+/// # Arguments
+///
+/// * `a` - The matrix of the system.
+/// * `b` - The right hand side of the system.
+/// * `epsilon` - The tolerance on the pivots, relative to the largest entry of the matrix.
+///
+/// # Returns
+///
+/// The solution `x` with the condition number of `A`, or an error if the matrix is singular
+/// within the tolerance or the system is not finite.
+///
+/// Example:
+///
+/// ```
+/// use glam::{DMat3, DVec3};
+/// use kornia_linalg::solve::solve3_with_condition;
+///
+/// let a = DMat3::from_diagonal(DVec3::new(1.0, 1e-3, 1.0));
+/// let result = solve3_with_condition(&a, &DVec3::ONE, 1e-12).unwrap();
+/// assert_eq!(*result.solution(), DVec3::new(1.0, 1e3, 1.0));
+/// assert_eq!(result.condition_number(), 1e3);
+/// ```
+pub fn solve3_with_condition(
+    a: &DMat3,
+    b: &DVec3,
+    epsilon: f64,
+) -> Result<Solve3Result, LinalgError> {
+    if !(a.is_finite() && b.is_finite()) {
+        return Err(LinalgError::NonFinite);
+    }
+    let (lu, permutation) = lu([0, 1, 2].map(|i| a.row(i).to_array()), epsilon)?;
+
+    let solution = DVec3::from_array(substitute(&lu, &permutation, &b.to_array()));
+    let inverse = DMat3::from_cols_array_2d(&[0, 1, 2].map(|j| {
+        let mut e = [0.0; 3];
+        e[j] = 1.0;
+        substitute(&lu, &permutation, &e)
+    }));
+    let condition_number = norm1(a) * norm1(&inverse);
+
+    Ok(Solve3Result {
+        solution,
+        condition_number,
+    })
+}
+
+/// Solves the linear system `A * x = b` of a square matrix of any size.
+///
+/// The same Gaussian elimination with partial pivoting as [`solve3_with_condition`], on
+/// row-major arrays, for the systems of the least squares problems larger than 3x3, e.g.
+/// the 6x6 normal equations of a rigid pose.
+///
+/// # Arguments
+///
+/// * `a` - The rows of the matrix of the system.
+/// * `b` - The right hand side of the system.
+/// * `epsilon` - The tolerance on the pivots, relative to the largest entry of the matrix.
+///
+/// # Returns
+///
+/// The solution `x`, or an error if the matrix is singular within the tolerance or the
+/// system is not finite.
+///
+/// Example:
+///
+/// ```
+/// use kornia_linalg::solve::solve_linear;
+///
+/// let a = [[2.0, 0.0, 0.0, 0.0], [0.0, 0.0, 4.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 8.0]];
+/// let x = solve_linear(&a, &[1.0, 2.0, 3.0, 4.0], 1e-12).unwrap();
+/// assert_eq!(x, [0.5, 3.0, 0.5, 0.5]);
+/// ```
+pub fn solve_linear<const N: usize>(
+    a: &[[f64; N]; N],
+    b: &[f64; N],
+    epsilon: f64,
+) -> Result<[f64; N], LinalgError> {
+    if !a.iter().flatten().chain(b.iter()).all(|x| x.is_finite()) {
+        return Err(LinalgError::NonFinite);
+    }
+    let (lu, permutation) = lu(*a, epsilon)?;
+    Ok(substitute(&lu, &permutation, b))
+}
+
+/// Factorizes `P * A = L * U` with partial pivoting.
+///
+/// Returns the rows of `U` in the upper triangle and of the multipliers of `L`, of unit
+/// diagonal, below it, with the row of `A` of each row of the factors.
+fn lu<const N: usize>(
+    mut m: [[f64; N]; N],
+    epsilon: f64,
+) -> Result<([[f64; N]; N], [usize; N]), LinalgError> {
+    let mut permutation: [usize; N] = std::array::from_fn(|i| i);
+    let scale = m.iter().flatten().fold(0.0f64, |acc, x| acc.max(x.abs()));
+    let tolerance = epsilon * scale;
+
+    for k in 0..N {
+        let pivot_row = (k..N)
+            .max_by(|i, j| m[*i][k].abs().total_cmp(&m[*j][k].abs()))
+            .unwrap_or(k);
+        let pivot = m[pivot_row][k].abs();
+        if pivot <= tolerance {
+            return Err(LinalgError::SingularMatrix { pivot, tolerance });
+        }
+        m.swap(k, pivot_row);
+        permutation.swap(k, pivot_row);
+
+        let upper = m[k];
+        for row in m.iter_mut().skip(k + 1) {
+            let factor = row[k] / upper[k];
+            row[k] = factor;
+            for j in k + 1..N {
+                row[j] -= factor * upper[j];
+            }
+        }
+    }
+    Ok((m, permutation))
+}
+
+/// Solves `L * U * x = P * b` from the factors of [`lu`].
+fn substitute<const N: usize>(
+    lu: &[[f64; N]; N],
+    permutation: &[usize; N],
+    b: &[f64; N],
+) -> [f64; N] {
+    let mut y = permutation.map(|i| b[i]);
+    for i in 1..N {
+        y[i] -= (0..i).map(|k| lu[i][k] * y[k]).sum::<f64>();
+    }
+    let mut x = [0.0; N];
+    for i in (0..N).rev() {
+        let sum = (i + 1..N).map(|k| lu[i][k] * x[k]).sum::<f64>();
+        x[i] = (y[i] - sum) / lu[i][i];
+    }
+    x
+}
+
+/// The 1-norm of a matrix, the largest absolute column sum.
+fn norm1(m: &DMat3) -> f64 {
+    [m.x_axis, m.y_axis, m.z_axis]
+        .iter()
+        .map(|col| col.abs().element_sum())
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::generator;
+
+    /// The matrix of the given singular values in rotated bases.
+    fn with_singular_values(singular_values: DVec3) -> DMat3 {
+        let u = DMat3::from_euler(glam::EulerRot::XYZ, 0.3, -1.1, 0.7);
+        let v = DMat3::from_euler(glam::EulerRot::ZYX, -0.8, 0.4, 2.0);
+        u.mul_mat3(&DMat3::from_diagonal(singular_values))
+            .mul_mat3(&v.transpose())
+    }
+
+    #[test]
+    fn test_solve3_well_conditioned() {
+        let mut next = generator(42);
+        for _ in 0..1000 {
+            let a = DMat3::from_cols_array(&std::array::from_fn(|_| next())) + DMat3::IDENTITY;
+            let x = DVec3::new(next(), next(), next());
+            let Ok(result) = solve3_with_condition(&a, &a.mul_vec3(x), SOLVE3_EPSILON_F64) else {
+                continue;
+            };
+            let condition_number = result.condition_number();
+            assert!(condition_number >= 1.0);
+            assert!(result
+                .solution()
+                .abs_diff_eq(x, 1e-14 * condition_number.max(1e2)));
+
+            // the condition number is the one of the inverse
+            let expected = norm1(&a) * norm1(&a.inverse());
+            assert!((condition_number - expected).abs() < 1e-9 * expected);
+        }
+
+        // a permutation needs pivoting and is perfectly conditioned
+        let a = DMat3::from_cols(DVec3::Y, DVec3::Z, DVec3::X);
+        let b = DVec3::new(1.0, 2.0, 3.0);
+        let result = solve3_with_condition(&a, &b, SOLVE3_EPSILON_F64).unwrap();
+        assert_eq!(*result.solution(), DVec3::new(2.0, 3.0, 1.0));
+        assert_eq!(result.condition_number(), 1.0);
+    }
+
+    #[test]
+    fn test_solve_linear() {
+        let mut next = generator(7);
+        for _ in 0..100 {
+            // a diagonally dominant 6x6 system
+            let mut a: [[f64; 6]; 6] = std::array::from_fn(|_| std::array::from_fn(|_| next()));
+            for (i, row) in a.iter_mut().enumerate() {
+                row[i] += 6.0;
+            }
+            let x: [f64; 6] = std::array::from_fn(|_| next());
+            let b = a.map(|row| row.iter().zip(x.iter()).map(|(r, x)| r * x).sum::<f64>());
+            let solution = solve_linear(&a, &b, SOLVE3_EPSILON_F64).unwrap();
+            for (s, e) in solution.iter().zip(x.iter()) {
+                assert!((s - e).abs() < 1e-12);
+            }
+        }
+
+        // the 3x3 systems match solve3
+        let a = with_singular_values(DVec3::new(3.0, 1.0, 0.1));
+        let b = DVec3::new(1.0, -2.0, 0.5);
+        let rows = [0, 1, 2].map(|i| a.row(i).to_array());
+        let solution = solve_linear(&rows, &b.to_array(), SOLVE3_EPSILON_F64).unwrap();
+        assert_eq!(DVec3::from_array(solution), solve3(&a, &b).unwrap());
+
+        let mut singular = [[1.0; 4]; 4];
+        singular[0][0] = 2.0;
+        assert!(matches!(
+            solve_linear(&singular, &[1.0; 4], SOLVE3_EPSILON_F64),
+            Err(LinalgError::SingularMatrix { .. })
+        ));
+        assert_eq!(
+            solve_linear(
+                &[[1.0, 0.0], [0.0, f64::NAN]],
+                &[1.0; 2],
+                SOLVE3_EPSILON_F64
+            ),
+            Err(LinalgError::NonFinite)
+        );
+    }
+
+    #[test]
+    fn test_solve3_nearly_singular() {
+        let a = with_singular_values(DVec3::new(2.0, 1.0, 1e-9));
+        let x = DVec3::new(0.5, -1.0, 2.0);
+        let b = a.mul_vec3(x);
+
+        // the residual is small, while the error of x grows with the condition number
+        let result = solve3_with_condition(&a, &b, SOLVE3_EPSILON_F64).unwrap();
+        assert!(result.condition_number() > 1e9 && result.condition_number() < 1e10);
+        assert!(a.mul_vec3(*result.solution()).abs_diff_eq(b, 1e-12));
+        assert!(result.solution().abs_diff_eq(x, 1e-5));
+
+        // a stricter tolerance rejects it
+        assert!(matches!(
+            solve3_with_condition(&a, &b, 1e-6),
+            Err(LinalgError::SingularMatrix { .. })
+        ));
+    }
+
+    #[test]
+    fn test_solve3_singular() {
+        let b = DVec3::new(1.0, 2.0, 3.0);
+        for a in [
+            with_singular_values(DVec3::new(2.0, 1.0, 0.0)),
+            DMat3::from_cols(
+                DVec3::new(1.0, 2.0, 3.0),
+                DVec3::new(2.0, 4.0, 6.0),
+                DVec3::new(3.0, 6.0, 9.0),
+            ),
+            DMat3::from_diagonal(DVec3::new(1.0, 0.0, 1.0)),
+            DMat3::ZERO,
+        ] {
+            assert!(matches!(
+                solve3(&a, &b),
+                Err(LinalgError::SingularMatrix { .. })
+            ));
+        }
+
+        let nan = DMat3::from_diagonal(DVec3::new(1.0, f64::NAN, 1.0));
+        assert_eq!(solve3(&nan, &b), Err(LinalgError::NonFinite));
+        let infinite = DVec3::new(1.0, f64::INFINITY, 0.0);
+        assert_eq!(
+            solve3(&DMat3::IDENTITY, &infinite),
+            Err(LinalgError::NonFinite)
+        );
+    }
+}
@@ -0,0 +1,10 @@
+/// A linear congruential generator of numbers in [-1, 1], shared by the tests so that
+/// they draw reproducible matrices without a dependency on `rand`.
+pub(crate) fn generator(mut state: u64) -> impl FnMut() -> f64 {
+    move || {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (state >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+    }
+}
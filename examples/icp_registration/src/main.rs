@@ -46,6 +46,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         kicp::ICPConvergenceCriteria {
             max_iterations: 2000,
             tolerance: 1e-6,
+            entropy_threshold: 0.0,
         },
     )?;
 
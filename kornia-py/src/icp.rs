@@ -17,6 +17,7 @@ impl PyICPConvergenceCriteria {
         Ok(PyICPConvergenceCriteria(ICPConvergenceCriteria {
             max_iterations,
             tolerance,
+            entropy_threshold: 0.0,
         }))
     }
 
@@ -57,6 +58,8 @@ impl PyICPResult {
             translation: [0.0, 0.0, 0.0],
             num_iterations: 0,
             rmse: 0.0,
+            diagnostics: None,
+            overlap: None,
         }))
     }
 